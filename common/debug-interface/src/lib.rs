@@ -11,6 +11,7 @@ use std::collections::HashMap;
 pub mod json_log;
 pub mod libra_trace;
 pub mod node_debug_service;
+pub mod round_latency;
 
 pub mod prelude {
     pub use crate::{end_trace, event, trace_code_block, trace_edge, trace_event};
@@ -0,0 +1,265 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Breaks the latency of consensus rounds down into phases, based on the trace events emitted by
+//! consensus (see `libra_trace`).
+//!
+//! A phase is the time between a start stage and an end stage traced for the same node (e.g. the
+//! same block). Phases that span several validators (e.g. network delivery of a proposal)
+//! compare timestamps taken on different machines and are only as accurate as their clocks.
+
+use crate::{json_log::JsonLogEntry, libra_trace::TRACE_EVENT};
+use std::{collections::HashMap, fmt};
+
+/// Which events are matched together to measure a phase.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PhaseScope {
+    /// Start and end are traced by the same peer, one sample per peer and node.
+    SamePeer,
+    /// Start is the earliest start traced by any peer, one sample per peer tracing the end.
+    AcrossPeers,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Phase {
+    pub name: &'static str,
+    pub start_stage: &'static str,
+    pub end_stage: &'static str,
+    pub scope: PhaseScope,
+}
+
+/// The phases of a consensus round, in the order they happen.
+pub const CONSENSUS_PHASES: &[Phase] = &[
+    Phase {
+        name: "proposal_generation",
+        start_stage: "round_manager::proposal_generation",
+        end_stage: "round_manager::proposal_generation::done",
+        scope: PhaseScope::SamePeer,
+    },
+    Phase {
+        name: "proposal_delivery",
+        start_stage: "round_manager::generate_proposal",
+        end_stage: "round_manager::pre_process_proposal",
+        scope: PhaseScope::AcrossPeers,
+    },
+    Phase {
+        name: "execution",
+        start_stage: "state_computer::compute",
+        end_stage: "state_computer::compute::done",
+        scope: PhaseScope::SamePeer,
+    },
+    Phase {
+        name: "vote_signing",
+        start_stage: "round_manager::sign_vote",
+        end_stage: "round_manager::sign_vote::done",
+        scope: PhaseScope::SamePeer,
+    },
+    Phase {
+        name: "vote_aggregation",
+        start_stage: "pending_votes::first_vote",
+        end_stage: "pending_votes::qc_aggregated",
+        scope: PhaseScope::SamePeer,
+    },
+    Phase {
+        name: "commit",
+        start_stage: "state_computer::commit",
+        end_stage: "state_computer::commit::done",
+        scope: PhaseScope::SamePeer,
+    },
+    Phase {
+        name: "proposal_to_commit",
+        start_stage: "round_manager::generate_proposal",
+        end_stage: "commit",
+        scope: PhaseScope::AcrossPeers,
+    },
+];
+
+/// Latency samples of a single phase, in milliseconds.
+#[derive(Debug)]
+pub struct PhaseLatency {
+    pub phase: &'static str,
+    /// Sorted in ascending order
+    samples: Vec<u64>,
+}
+
+impl PhaseLatency {
+    pub fn new(phase: &'static str, mut samples: Vec<u64>) -> Self {
+        samples.sort_unstable();
+        Self { phase, samples }
+    }
+
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Nearest-rank percentile, `None` if there are no samples.
+    pub fn percentile(&self, percentile: u64) -> Option<u64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let rank = (percentile as usize * self.samples.len() + 99) / 100;
+        let index = rank.max(1).min(self.samples.len()) - 1;
+        Some(self.samples[index])
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.samples.last().cloned()
+    }
+}
+
+/// Per phase latency percentiles, printable as a table.
+pub struct RoundLatencyReport(pub Vec<PhaseLatency>);
+
+impl fmt::Display for RoundLatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<20} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "phase", "samples", "p50(ms)", "p90(ms)", "p99(ms)", "max(ms)"
+        )?;
+        let display = |v: Option<u64>| v.map_or("-".to_string(), |v| v.to_string());
+        for latency in &self.0 {
+            writeln!(
+                f,
+                "{:<20} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                latency.phase,
+                latency.count(),
+                display(latency.percentile(50)),
+                display(latency.percentile(90)),
+                display(latency.percentile(99)),
+                display(latency.max()),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes the latency of the given phases over trace events collected from one or more peers.
+/// The peer of an event is read from its optional `peer` field.
+pub fn phase_latencies(entries: &[JsonLogEntry], phases: &[Phase]) -> RoundLatencyReport {
+    RoundLatencyReport(
+        phases
+            .iter()
+            .map(|phase| PhaseLatency::new(phase.name, phase_samples(entries, phase)))
+            .collect(),
+    )
+}
+
+/// Shorthand for the latency breakdown of consensus rounds.
+pub fn consensus_round_latencies(entries: &[JsonLogEntry]) -> RoundLatencyReport {
+    phase_latencies(entries, CONSENSUS_PHASES)
+}
+
+fn phase_samples(entries: &[JsonLogEntry], phase: &Phase) -> Vec<u64> {
+    // First timestamp of the start and end stages, keyed by (peer, node).
+    let mut starts: HashMap<(&str, &str), u128> = HashMap::new();
+    let mut ends: HashMap<(&str, &str), u128> = HashMap::new();
+    for entry in entries.iter().filter(|entry| entry.name == TRACE_EVENT) {
+        let (node, stage) = match (str_field(entry, "node"), str_field(entry, "stage")) {
+            (Some(node), Some(stage)) => (node, stage),
+            _ => continue,
+        };
+        let peer = str_field(entry, "peer").unwrap_or("");
+        let timestamps = if stage == phase.start_stage {
+            &mut starts
+        } else if stage == phase.end_stage {
+            &mut ends
+        } else {
+            continue;
+        };
+        let ts = timestamps.entry((peer, node)).or_insert(entry.timestamp);
+        *ts = (*ts).min(entry.timestamp);
+    }
+
+    ends.iter()
+        .filter_map(|(&(peer, node), end)| {
+            let start = match phase.scope {
+                PhaseScope::SamePeer => starts.get(&(peer, node)).cloned(),
+                PhaseScope::AcrossPeers => starts
+                    .iter()
+                    .filter(|((_, start_node), _)| *start_node == node)
+                    .map(|(_, ts)| *ts)
+                    .min(),
+            };
+            start.map(|start| end.saturating_sub(start) as u64)
+        })
+        .collect()
+}
+
+fn str_field<'a>(entry: &'a JsonLogEntry, field: &str) -> Option<&'a str> {
+    entry.json.get(field).and_then(|v| v.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::libra_trace::TRACE_EDGE;
+    use serde_json::json;
+
+    fn event(peer: &str, node: &str, stage: &str, timestamp: u128) -> JsonLogEntry {
+        JsonLogEntry {
+            name: TRACE_EVENT.to_string(),
+            timestamp,
+            json: json!({"path": "consensus", "node": node, "stage": stage, "peer": peer}),
+        }
+    }
+
+    #[test]
+    fn test_same_peer_phase() {
+        let phase = Phase {
+            name: "execution",
+            start_stage: "compute",
+            end_stage: "compute::done",
+            scope: PhaseScope::SamePeer,
+        };
+        let entries = vec![
+            event("a", "block::1", "compute", 100),
+            event("b", "block::1", "compute", 105),
+            event("a", "block::1", "compute::done", 110),
+            event("b", "block::1", "compute::done", 135),
+            // no matching start for this peer
+            event("c", "block::1", "compute::done", 200),
+            JsonLogEntry {
+                name: TRACE_EDGE.to_string(),
+                timestamp: 120,
+                json: json!({"node": "block::1", "node_to": "block::2", "stage": "compute"}),
+            },
+        ];
+        let report = phase_latencies(&entries, &[phase]);
+        let latency = &report.0[0];
+        assert_eq!(latency.count(), 2);
+        assert_eq!(latency.percentile(50), Some(10));
+        assert_eq!(latency.max(), Some(30));
+    }
+
+    #[test]
+    fn test_across_peers_phase() {
+        let phase = Phase {
+            name: "delivery",
+            start_stage: "propose",
+            end_stage: "receive",
+            scope: PhaseScope::AcrossPeers,
+        };
+        let entries = vec![
+            event("a", "block::1", "propose", 100),
+            event("a", "block::1", "receive", 101),
+            event("b", "block::1", "receive", 120),
+            event("c", "block::1", "receive", 150),
+            event("c", "block::2", "receive", 150),
+        ];
+        let latency = &phase_latencies(&entries, &[phase]).0[0];
+        assert_eq!(latency.count(), 3);
+        assert_eq!(latency.percentile(0), Some(1));
+        assert_eq!(latency.percentile(50), Some(20));
+        assert_eq!(latency.percentile(100), Some(50));
+    }
+
+    #[test]
+    fn test_percentile() {
+        let latency = PhaseLatency::new("p", (1..=100).rev().collect());
+        assert_eq!(latency.percentile(50), Some(50));
+        assert_eq!(latency.percentile(90), Some(90));
+        assert_eq!(latency.percentile(99), Some(99));
+        assert_eq!(PhaseLatency::new("empty", vec![]).percentile(50), None);
+    }
+}
@@ -57,6 +57,9 @@ pub enum SecurityEvent {
     /// Network discovery received an invalid DiscoveryMsg
     InvalidDiscoveryMsg,
 
+    /// Network banned a peer for repeated misbehavior
+    BannedNetworkPeer,
+
     /// Error for testing
     #[cfg(test)]
     TestError,
@@ -26,6 +26,10 @@ use pin_project::pin_project;
 use std::{mem::ManuallyDrop, pin::Pin, task, task::Poll, time::Duration};
 use tokio::time::{interval, Interval};
 
+mod token_bucket;
+
+pub use token_bucket::TokenBucket;
+
 /// Config parameters for a rate-limiter.
 /// `capacity`: Max elements allowed in an interval.
 /// `interval`: Granular duration within which control flow is desired.
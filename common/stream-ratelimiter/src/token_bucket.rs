@@ -0,0 +1,81 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! `TokenBucket` is a synchronous token-bucket for callers which decide themselves what to do with
+//! an element over the limit (e.g. drop it), as opposed to [`RateLimiter`](crate::RateLimiter)
+//! which delays the elements of a stream.
+
+use std::time::Instant;
+
+/// A token-bucket refilled continuously at `rate_per_sec` tokens per second, up to `capacity`.
+///
+/// Acquisitions larger than the capacity succeed once the bucket is full and leave the bucket in
+/// debt, so that large elements are delayed rather than refused forever.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate_per_sec: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket.
+    pub fn new(rate_per_sec: u64, capacity: u64, now: Instant) -> Self {
+        Self {
+            rate_per_sec: rate_per_sec as f64,
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            last_refill: now,
+        }
+    }
+
+    /// Takes `amount` tokens if available. Returns false, without taking any token, otherwise.
+    pub fn try_acquire(&mut self, amount: u64, now: Instant) -> bool {
+        self.refill(now);
+        let amount = amount as f64;
+        if self.tokens < amount.min(self.capacity) {
+            return false;
+        }
+        self.tokens -= amount;
+        true
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.last_refill = self.last_refill.max(now);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate_per_sec).min(self.capacity);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_refill() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(2, 2, now);
+        assert!(bucket.try_acquire(1, now));
+        assert!(bucket.try_acquire(1, now));
+        assert!(!bucket.try_acquire(1, now));
+        assert!(!bucket.try_acquire(1, now + Duration::from_millis(400)));
+        assert!(bucket.try_acquire(1, now + Duration::from_millis(600)));
+        // the burst is bounded by the capacity
+        let later = now + Duration::from_secs(60);
+        assert!(bucket.try_acquire(1, later));
+        assert!(bucket.try_acquire(1, later));
+        assert!(!bucket.try_acquire(1, later));
+    }
+
+    #[test]
+    fn test_oversized_acquisition() {
+        let now = Instant::now();
+        // a full bucket lets an oversized acquisition through and goes into debt
+        let mut bucket = TokenBucket::new(100, 100, now);
+        assert!(bucket.try_acquire(150, now));
+        assert!(!bucket.try_acquire(1, now + Duration::from_millis(505)));
+        assert!(bucket.try_acquire(1, now + Duration::from_millis(520)));
+    }
+}
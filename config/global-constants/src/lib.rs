@@ -18,6 +18,7 @@ pub const VALIDATOR_NETWORK_KEY: &str = "validator_network";
 
 /// Definitions of global data items (e.g., as held in secure storage)
 pub const EPOCH: &str = "epoch";
+pub const LAST_COMMIT_VOTE: &str = "last_commit_vote";
 pub const LAST_VOTED_ROUND: &str = "last_voted_round";
 pub const PREFERRED_ROUND: &str = "preferred_round";
 pub const WAYPOINT: &str = "waypoint";
@@ -1,32 +1,41 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::config::SafetyRulesConfig;
+use crate::config::{SafetyRulesConfig, SafetyRulesService};
+use anyhow::{ensure, Result};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct ConsensusConfig {
+    /// Only order blocks in consensus rounds, and execute and commit them in a separate
+    /// pipeline stage that agrees on the execution result with its own commit votes. Both stages
+    /// sign with SafetyRules, which has to run in process (`Local` or `Serializer` service).
+    pub decoupled_execution: bool,
     pub max_block_size: u64,
-    pub contiguous_rounds: u32,
+    /// Deprecated and ignored: the proposer election is configured on-chain. Only kept so that
+    /// existing node configs still load.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contiguous_rounds: Option<u32>,
     pub max_pruned_blocks_in_mem: usize,
     pub round_initial_timeout_ms: u64,
-    pub proposer_type: ConsensusProposerType,
+    /// Deprecated and ignored: the proposer election is configured on-chain. Only kept so that
+    /// existing node configs still load.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposer_type: Option<ConsensusProposerType>,
     pub safety_rules: SafetyRulesConfig,
 }
 
 impl Default for ConsensusConfig {
     fn default() -> ConsensusConfig {
         ConsensusConfig {
+            decoupled_execution: false,
             max_block_size: 1000,
-            proposer_type: ConsensusProposerType::LeaderReputation(LeaderReputationConfig {
-                active_weights: 99,
-                inactive_weights: 1,
-            }),
-            contiguous_rounds: 2,
+            contiguous_rounds: None,
             max_pruned_blocks_in_mem: 10000,
             round_initial_timeout_ms: 1000,
+            proposer_type: None,
             safety_rules: SafetyRulesConfig::default(),
         }
     }
@@ -36,8 +45,25 @@ impl ConsensusConfig {
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
         self.safety_rules.set_data_dir(data_dir);
     }
+
+    /// Checks that the options are compatible with each other
+    pub fn verify(&self) -> Result<()> {
+        // The execution pipeline needs its own SafetyRules client next to the RoundManager's,
+        // a remote SafetyRules only serves a single client.
+        ensure!(
+            !self.decoupled_execution
+                || matches!(
+                    self.safety_rules.service,
+                    SafetyRulesService::Local | SafetyRulesService::Serializer
+                ),
+            "Decoupled execution requires a Local or Serializer SafetyRules service, found {:?}",
+            self.safety_rules.service
+        );
+        Ok(())
+    }
 }
 
+/// The proposer election of node configs that predate the on-chain consensus config.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum ConsensusProposerType {
@@ -54,3 +80,40 @@ pub struct LeaderReputationConfig {
     pub active_weights: u64,
     pub inactive_weights: u64,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_deprecated_proposer_fields() {
+        let text = "
+max_block_size = 1000
+max_pruned_blocks_in_mem = 10000
+round_initial_timeout_ms = 1000
+contiguous_rounds = 2
+
+[proposer_type]
+type = \"leader_reputation\"
+active_weights = 99
+inactive_weights = 1
+        ";
+
+        let config: ConsensusConfig = toml::from_str(text).unwrap();
+        assert_eq!(config.contiguous_rounds, Some(2));
+        assert_eq!(
+            config.proposer_type,
+            Some(ConsensusProposerType::LeaderReputation(
+                LeaderReputationConfig {
+                    active_weights: 99,
+                    inactive_weights: 1,
+                }
+            ))
+        );
+
+        // The deprecated fields are dropped once the config is saved again
+        let serialized = toml::to_string(&ConsensusConfig::default()).unwrap();
+        assert!(!serialized.contains("contiguous_rounds"));
+        assert!(!serialized.contains("proposer_type"));
+    }
+}
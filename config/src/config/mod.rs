@@ -207,6 +207,8 @@ impl NodeConfig {
             );
         }
 
+        config.consensus.verify()?;
+
         let input_dir = RootPath::new(input_path);
         config.execution.load(&input_dir)?;
         if let Some(network) = &mut config.validator_network {
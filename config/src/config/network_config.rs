@@ -12,7 +12,12 @@ use libra_network_address::NetworkAddress;
 use libra_types::{transaction::authenticator::AuthenticationKey, PeerId};
 use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, convert::TryFrom, path::PathBuf, string::ToString};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    path::PathBuf,
+    string::ToString,
+};
 
 const NETWORK_PEERS_DEFAULT: &str = "network_peers.config.toml";
 const SEED_PEERS_DEFAULT: &str = "seed_peers.toml";
@@ -53,8 +58,35 @@ pub struct NetworkConfig {
     #[serde(skip)]
     pub seed_peers: SeedPeersConfig,
     pub seed_peers_file: PathBuf,
+    // Hostnames whose TXT records advertise seed peers as `<peer_id> <network_address>`, resolved
+    // every `dns_discovery_interval_ms`, along with the hostnames within the advertised addresses.
+    pub dns_seeds: Vec<String>,
+    pub dns_discovery_interval_ms: u64,
+    // Limits on inbound connections. Network peers, seed peers and trusted peers are exempt from
+    // the per-IP and per-subnet limits, and take precedence over unknown peers once
+    // `max_inbound_connections` is reached. `max_inbound_handshakes_per_sec` applies to each source
+    // IP separately, and 0 leaves the inbound handshakes unlimited.
+    pub max_inbound_connections: usize,
+    pub max_inbound_connections_per_ip: usize,
+    pub max_inbound_connections_per_subnet: usize,
+    pub max_inbound_handshakes_per_sec: u64,
+    // Interval at which `network_peers_file` and `seed_peers_file` are re-read, so that changes
+    // to the peers apply without a restart. 0 disables reloading.
+    pub peers_reload_interval_ms: u64,
+    // File to which the messages exchanged with all peers are recorded, for debugging with the
+    // network-capture tool. Recording is disabled when unset.
+    pub traffic_capture_file: Option<PathBuf>,
     #[serde(rename = "identity_private_key")]
     pub identity_keypair: Option<KeyPair<x25519::PrivateKey>>,
+    // Misbehavior reported by the upper layers adds a penalty to the score of the remote peer, a
+    // peer whose score reaches the threshold gets banned. Trusted peers are never banned.
+    pub peer_reputation: PeerReputationConfig,
+    // Protocols whose messages are compressed when the remote peer supports it, keyed by protocol
+    // name, with the minimum size in bytes of the messages to compress.
+    pub compression_thresholds: BTreeMap<String, usize>,
+    // Per-connection bandwidth limits, keyed by protocol name (e.g. "MempoolDirectSend").
+    // Protocols without an entry are unlimited. These tables must remain the last fields.
+    pub protocol_rate_limits: BTreeMap<String, ProtocolRateLimitConfig>,
 }
 
 impl Default for NetworkConfig {
@@ -73,6 +105,23 @@ impl Default for NetworkConfig {
             network_peers: NetworkPeersConfig::default(),
             seed_peers_file: PathBuf::new(),
             seed_peers: SeedPeersConfig::default(),
+            dns_seeds: vec![],
+            dns_discovery_interval_ms: 60_000,
+            max_inbound_connections: 100,
+            max_inbound_connections_per_ip: 8,
+            max_inbound_connections_per_subnet: 32,
+            max_inbound_handshakes_per_sec: 20,
+            peers_reload_interval_ms: 0,
+            traffic_capture_file: None,
+            peer_reputation: PeerReputationConfig::default(),
+            compression_thresholds: [
+                ("MempoolDirectSend".to_string(), 4096),
+                ("StateSynchronizerDirectSend".to_string(), 4096),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+            protocol_rate_limits: BTreeMap::new(),
         }
     }
 }
@@ -95,6 +144,17 @@ impl NetworkConfig {
             network_peers: self.network_peers.clone(),
             seed_peers_file: self.seed_peers_file.clone(),
             seed_peers: self.seed_peers.clone(),
+            dns_seeds: self.dns_seeds.clone(),
+            dns_discovery_interval_ms: self.dns_discovery_interval_ms,
+            max_inbound_connections: self.max_inbound_connections,
+            max_inbound_connections_per_ip: self.max_inbound_connections_per_ip,
+            max_inbound_connections_per_subnet: self.max_inbound_connections_per_subnet,
+            max_inbound_handshakes_per_sec: self.max_inbound_handshakes_per_sec,
+            peers_reload_interval_ms: self.peers_reload_interval_ms,
+            traffic_capture_file: self.traffic_capture_file.clone(),
+            peer_reputation: self.peer_reputation,
+            compression_thresholds: self.compression_thresholds.clone(),
+            protocol_rate_limits: self.protocol_rate_limits.clone(),
         }
     }
 
@@ -132,6 +192,11 @@ impl NetworkConfig {
                 self.network_peers.peers.is_empty(),
                 "Validators should not define network_peers"
             );
+            ensure!(
+                self.peers_reload_interval_ms == 0,
+                "Validators should not define peers_reload_interval_ms, their peers are the \
+                 on-chain validator set"
+            );
         }
 
         // TODO(joshlind): investigate the implications of removing these checks.
@@ -157,6 +222,17 @@ impl NetworkConfig {
         Ok(())
     }
 
+    /// Makes `network_peers_file` and `seed_peers_file` absolute, so that they can be reloaded
+    /// while the node runs.
+    pub fn resolve_peers_files(&mut self, root_dir: &RootPath) {
+        if !self.network_peers_file.as_os_str().is_empty() {
+            self.network_peers_file = root_dir.full_path(&self.network_peers_file);
+        }
+        if !self.seed_peers_file.as_os_str().is_empty() {
+            self.seed_peers_file = root_dir.full_path(&self.seed_peers_file);
+        }
+    }
+
     fn default_path(&self, config_path: &str) -> String {
         format!("{}.{}", self.peer_id.to_string(), config_path)
     }
@@ -249,6 +325,42 @@ pub enum DiscoveryMethod {
     None,
 }
 
+/// The misbehavior score at which a peer gets banned and the penalty of each misbehavior. The
+/// scores decay by half every `score_half_life_ms`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeerReputationConfig {
+    pub ban_score_threshold: f64,
+    pub ban_duration_ms: u64,
+    pub score_half_life_ms: u64,
+    pub undecodable_message_penalty: f64,
+    pub invalid_message_penalty: f64,
+    pub invalid_chunk_penalty: f64,
+    pub protocol_violation_penalty: f64,
+}
+
+impl Default for PeerReputationConfig {
+    fn default() -> Self {
+        Self {
+            ban_score_threshold: 100.0,
+            ban_duration_ms: 10 * 60 * 1000, /* 10 minutes */
+            score_half_life_ms: 60 * 1000,   /* 1 minute */
+            undecodable_message_penalty: 20.0,
+            invalid_message_penalty: 10.0,
+            invalid_chunk_penalty: 25.0,
+            protocol_violation_penalty: 5.0,
+        }
+    }
+}
+
+/// Per-connection bandwidth limits of a protocol, in bytes per second. `None` means unlimited.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProtocolRateLimitConfig {
+    pub inbound_bytes_per_sec: Option<u64>,
+    pub outbound_bytes_per_sec: Option<u64>,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
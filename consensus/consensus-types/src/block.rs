@@ -79,6 +79,10 @@ impl<T> Block<T> {
         block.parent_id() == self.id
     }
 
+    pub fn failed_authors(&self) -> &[(Round, Author)] {
+        self.block_data.failed_authors()
+    }
+
     pub fn parent_id(&self) -> HashValue {
         self.block_data.quorum_cert().certified_block().id()
     }
@@ -169,6 +173,7 @@ where
         timestamp_usecs: u64,
         quorum_cert: QuorumCert,
         validator_signer: &ValidatorSigner,
+        failed_authors: Vec<(Round, Author)>,
     ) -> Self {
         let block_data = BlockData::new_proposal(
             payload,
@@ -176,6 +181,7 @@ where
             round,
             timestamp_usecs,
             quorum_cert,
+            failed_authors,
         );
 
         Self::new_proposal_from_block_data(block_data, validator_signer)
@@ -201,7 +207,8 @@ where
         match self.block_data.block_type() {
             BlockType::Genesis => bail!("We should not accept genesis from others"),
             BlockType::NilBlock => self.quorum_cert().verify(validator),
-            BlockType::Proposal { author, .. } => {
+            BlockType::Proposal { author, .. }
+            | BlockType::ProposalWithFailedAuthors { author, .. } => {
                 let signature = self
                     .signature
                     .as_ref()
@@ -245,6 +252,14 @@ where
                 "Blocks must have strictly increasing timestamps"
             );
         }
+        let mut failed_round = parent.round();
+        for (round, _) in self.failed_authors() {
+            ensure!(
+                failed_round < *round && *round < self.round(),
+                "Failed authors must be in increasing rounds between the parent and the block"
+            );
+            failed_round = *round;
+        }
         ensure!(
             !self.quorum_cert().ends_epoch(),
             "Block cannot be proposed in an epoch that has ended"
@@ -300,6 +315,11 @@ impl<T> From<&Block<T>> for BlockMetadata {
                 .collect(),
             // For nil block, we use 0x0 which is convention for nil address in move.
             block.author().unwrap_or_default(),
+            block
+                .failed_authors()
+                .iter()
+                .map(|(_, author)| *author)
+                .collect(),
         )
     }
 }
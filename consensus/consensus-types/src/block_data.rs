@@ -32,6 +32,19 @@ pub enum BlockType<T> {
     /// from the previous epoch.  The genesis block is used as the the first root block of the
     /// BlockTree for all epochs.
     Genesis,
+    /// A proposal that also records the rounds skipped between its parent and itself. It is
+    /// appended to keep the layout of the other block types, and only proposed when the
+    /// on-chain proposer election reads the failed authors back, so that validators predating
+    /// it can decode the blocks of every other election.
+    ProposalWithFailedAuthors {
+        /// T of the block (e.g. one or more transaction(s)
+        payload: T,
+        /// Author of the block that can be validated by the author's public key and the signature
+        author: Author,
+        /// The rounds skipped between the parent and this block, with the author expected to
+        /// propose in each of them.
+        failed_authors: Vec<(Round, Author)>,
+    },
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, CryptoHasher, LCSCryptoHash)]
@@ -70,10 +83,10 @@ pub struct BlockData<T> {
 
 impl<T> BlockData<T> {
     pub fn author(&self) -> Option<Author> {
-        if let BlockType::Proposal { author, .. } = self.block_type {
-            Some(author)
-        } else {
-            None
+        match self.block_type {
+            BlockType::Proposal { author, .. }
+            | BlockType::ProposalWithFailedAuthors { author, .. } => Some(author),
+            _ => None,
         }
     }
 
@@ -89,11 +102,21 @@ impl<T> BlockData<T> {
         self.quorum_cert.certified_block().id()
     }
 
-    pub fn payload(&self) -> Option<&T> {
-        if let BlockType::Proposal { payload, .. } = &self.block_type {
-            Some(payload)
+    /// The rounds skipped between the parent and this block with their expected authors, empty
+    /// for any block that doesn't record them.
+    pub fn failed_authors(&self) -> &[(Round, Author)] {
+        if let BlockType::ProposalWithFailedAuthors { failed_authors, .. } = &self.block_type {
+            failed_authors
         } else {
-            None
+            &[]
+        }
+    }
+
+    pub fn payload(&self) -> Option<&T> {
+        match &self.block_type {
+            BlockType::Proposal { payload, .. }
+            | BlockType::ProposalWithFailedAuthors { payload, .. } => Some(payload),
+            _ => None,
         }
     }
 
@@ -180,19 +203,31 @@ where
         }
     }
 
+    /// A proposal recording the given failed authors. Without any, the proposal keeps the layout
+    /// of `BlockType::Proposal`.
     pub fn new_proposal(
         payload: T,
         author: Author,
         round: Round,
         timestamp_usecs: u64,
         quorum_cert: QuorumCert,
+        failed_authors: Vec<(Round, Author)>,
     ) -> Self {
+        let block_type = if failed_authors.is_empty() {
+            BlockType::Proposal { payload, author }
+        } else {
+            BlockType::ProposalWithFailedAuthors {
+                payload,
+                author,
+                failed_authors,
+            }
+        };
         Self {
             epoch: quorum_cert.certified_block().epoch(),
             round,
             timestamp_usecs,
             quorum_cert,
-            block_type: BlockType::Proposal { payload, author },
+            block_type,
         }
     }
 }
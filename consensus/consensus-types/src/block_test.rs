@@ -6,10 +6,15 @@ use crate::{
         block_test_utils::{certificate_for_genesis, *},
         Block,
     },
+    common::{Author, Round},
     quorum_cert::QuorumCert,
 };
-use libra_crypto::hash::{CryptoHash, HashValue};
+use libra_crypto::{
+    ed25519::Ed25519Signature,
+    hash::{CryptoHash, HashValue},
+};
 use libra_types::{validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier};
+use serde::Serialize;
 use std::{collections::BTreeMap, panic, sync::Arc};
 
 #[test]
@@ -66,6 +71,7 @@ fn test_nil_block() {
         get_current_timestamp().as_micros() as u64,
         nil_block_qc,
         &signer,
+        vec![],
     );
     assert_eq!(nil_block_child.is_nil_block(), false);
     assert_eq!(nil_block_child.round(), 2);
@@ -85,6 +91,7 @@ fn test_block_relation() {
         get_current_timestamp().as_micros() as u64,
         quorum_cert,
         &signer,
+        vec![],
     );
     assert_eq!(next_block.round(), 1);
     assert_eq!(genesis_block.is_parent_of(&next_block), true);
@@ -113,6 +120,7 @@ fn test_same_qc_different_authors() {
         current_timestamp,
         genesis_qc.clone(),
         &signer,
+        vec![],
     );
 
     let signature = signer.sign_message(genesis_qc.ledger_info().ledger_info().hash());
@@ -126,11 +134,120 @@ fn test_same_qc_different_authors() {
         current_timestamp,
         genesis_qc_altered,
         &signer,
+        vec![],
     );
 
-    let block_round_1_same =
-        Block::new_proposal(payload, round, current_timestamp, genesis_qc, &signer);
+    let block_round_1_same = Block::new_proposal(
+        payload,
+        round,
+        current_timestamp,
+        genesis_qc,
+        &signer,
+        vec![],
+    );
 
     assert!(block_round_1.id() != block_round_1_altered.id());
     assert_eq!(block_round_1.id(), block_round_1_same.id());
 }
+
+#[test]
+fn test_failed_authors_rounds() {
+    let signer = ValidatorSigner::random(None);
+    let genesis_qc = certificate_for_genesis();
+    let author = signer.author();
+    let make_block = |failed_authors| {
+        Block::new_proposal(
+            42,
+            4,
+            get_current_timestamp().as_micros() as u64,
+            genesis_qc.clone(),
+            &signer,
+            failed_authors,
+        )
+    };
+
+    assert!(make_block(vec![(1, author), (3, author)])
+        .verify_well_formed()
+        .is_ok());
+    // The rounds must be skipped between the parent and the block, in increasing order
+    assert!(make_block(vec![(0, author)]).verify_well_formed().is_err());
+    assert!(make_block(vec![(4, author)]).verify_well_formed().is_err());
+    assert!(make_block(vec![(2, author), (1, author)])
+        .verify_well_formed()
+        .is_err());
+}
+
+/// The layout of the blocks proposed before they could record failed authors.
+#[derive(Serialize)]
+#[serde(rename = "Block")]
+struct OldBlock {
+    block_data: OldBlockData,
+    signature: Option<Ed25519Signature>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "BlockData")]
+struct OldBlockData {
+    epoch: u64,
+    round: Round,
+    timestamp_usecs: u64,
+    quorum_cert: QuorumCert,
+    block_type: OldBlockType,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "BlockType")]
+#[allow(dead_code)]
+enum OldBlockType {
+    Proposal { payload: i16, author: Author },
+    NilBlock,
+    Genesis,
+}
+
+#[test]
+fn test_old_block_format() {
+    let signer = ValidatorSigner::random(None);
+    let genesis_qc = certificate_for_genesis();
+    let block = Block::new_proposal(
+        42,
+        1,
+        get_current_timestamp().as_micros() as u64,
+        genesis_qc.clone(),
+        &signer,
+        vec![],
+    );
+    let old_block = OldBlock {
+        block_data: OldBlockData {
+            epoch: block.epoch(),
+            round: block.round(),
+            timestamp_usecs: block.timestamp_usecs(),
+            quorum_cert: genesis_qc,
+            block_type: OldBlockType::Proposal {
+                payload: 42,
+                author: signer.author(),
+            },
+        },
+        signature: block.signature().cloned(),
+    };
+    let old_bytes = lcs::to_bytes(&old_block).unwrap();
+
+    // Proposals without failed authors keep the old layout, and thus the same id
+    assert_eq!(lcs::to_bytes(&block).unwrap(), old_bytes);
+    let decoded: Block<i16> = lcs::from_bytes(&old_bytes).unwrap();
+    assert_eq!(decoded, block);
+    assert_eq!(decoded.id(), block.id());
+    assert!(decoded.failed_authors().is_empty());
+
+    // Blocks recording failed authors round trip through the appended block type
+    let block = Block::new_proposal(
+        42,
+        3,
+        get_current_timestamp().as_micros() as u64,
+        certificate_for_genesis(),
+        &signer,
+        vec![(1, signer.author()), (2, signer.author())],
+    );
+    let decoded: Block<i16> = lcs::from_bytes(&lcs::to_bytes(&block).unwrap()).unwrap();
+    assert_eq!(decoded.id(), block.id());
+    assert_eq!(decoded.failed_authors(), block.failed_authors());
+}
@@ -44,6 +44,7 @@ prop_compose! {
             get_current_timestamp().as_micros() as u64,
             parent_qc,
             &signer,
+            vec![],
         )
     }
 }
@@ -88,6 +89,7 @@ prop_compose! {
                     block.round(),
                     get_current_timestamp().as_micros() as u64,
                     block.quorum_cert().clone(),
+                    vec![],
                 ),
                 signature: Some(block.signature().unwrap().clone()),
             }
@@ -0,0 +1,46 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::Round;
+use anyhow::Context;
+use libra_types::{ledger_info::LedgerInfoWithSignatures, validator_verifier::ValidatorVerifier};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// CommitDecision carries the LedgerInfo aggregated from the CommitVotes of a quorum, it lets
+/// validators whose execution lags behind catch up through state synchronization.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct CommitDecision {
+    ledger_info: LedgerInfoWithSignatures,
+}
+
+impl Display for CommitDecision {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "CommitDecision: [{}]", self.ledger_info)
+    }
+}
+
+impl CommitDecision {
+    pub fn new(ledger_info: LedgerInfoWithSignatures) -> Self {
+        Self { ledger_info }
+    }
+
+    pub fn ledger_info(&self) -> &LedgerInfoWithSignatures {
+        &self.ledger_info
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.ledger_info.ledger_info().epoch()
+    }
+
+    pub fn round(&self) -> Round {
+        self.ledger_info.ledger_info().round()
+    }
+
+    /// Verifies the quorum signatures of the LedgerInfo.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        self.ledger_info
+            .verify_signatures(validator)
+            .context("Failed to verify CommitDecision")
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{Author, Round};
+use anyhow::Context;
+use libra_crypto::{ed25519::Ed25519Signature, hash::CryptoHash};
+use libra_types::{ledger_info::LedgerInfo, validator_verifier::ValidatorVerifier};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// CommitVote is the signature of a validator on the LedgerInfo it computed by executing ordered
+/// blocks in the decoupled execution pipeline. 2f+1 CommitVotes on the same LedgerInfo allow to
+/// commit it to storage.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct CommitVote {
+    /// The identity of the voter.
+    author: Author,
+    /// LedgerInfo of the last executed block of an ordered batch.
+    ledger_info: LedgerInfo,
+    /// Signature of the LedgerInfo
+    signature: Ed25519Signature,
+}
+
+impl Display for CommitVote {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "CommitVote: [author: {}, {}]",
+            self.author.short_str(),
+            self.ledger_info
+        )
+    }
+}
+
+impl CommitVote {
+    pub fn new_with_signature(
+        author: Author,
+        ledger_info: LedgerInfo,
+        signature: Ed25519Signature,
+    ) -> Self {
+        Self {
+            author,
+            ledger_info,
+            signature,
+        }
+    }
+
+    /// Return the author of the vote
+    pub fn author(&self) -> Author {
+        self.author
+    }
+
+    /// Return the LedgerInfo associated with this vote
+    pub fn ledger_info(&self) -> &LedgerInfo {
+        &self.ledger_info
+    }
+
+    /// Return the signature of the vote
+    pub fn signature(&self) -> &Ed25519Signature {
+        &self.signature
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.ledger_info.epoch()
+    }
+
+    pub fn round(&self) -> Round {
+        self.ledger_info.round()
+    }
+
+    /// Verifies that the signature is from the author, this does not verify the execution result.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify_signature(self.author(), self.ledger_info.hash(), &self.signature)
+            .context("Failed to verify CommitVote")
+    }
+}
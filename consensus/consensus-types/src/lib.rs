@@ -6,6 +6,8 @@
 pub mod block;
 pub mod block_data;
 pub mod block_retrieval;
+pub mod commit_decision;
+pub mod commit_vote;
 pub mod common;
 pub mod epoch_retrieval;
 pub mod executed_block;
@@ -6,7 +6,7 @@ use crate::{
     common::{Author, Payload, Round},
     sync_info::SyncInfo,
 };
-use anyhow::{ensure, format_err, Context, Result};
+use anyhow::{ensure, Context, Result};
 use libra_types::validator_verifier::ValidatorVerifier;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -81,12 +81,10 @@ impl<T: Payload> ProposalMsg<T> {
     }
 
     pub fn verify(&self, validator: &ValidatorVerifier) -> Result<()> {
-        self.proposal
-            .validate_signatures(validator)
-            .map_err(|e| format_err!("{:?}", e))?;
+        self.proposal.validate_signatures(validator)?;
         // if there is a timeout certificate, verify its signatures
         if let Some(tc) = self.sync_info.highest_timeout_certificate() {
-            tc.verify(validator).map_err(|e| format_err!("{:?}", e))?;
+            tc.verify(validator)?;
         }
         // Note that we postpone the verification of SyncInfo until it's being used.
         self.verify_well_formed()
@@ -19,6 +19,9 @@ pub struct VoteProposal<T> {
     block: Block<T>,
     /// An optional field containing the next epoch info.
     next_epoch_state: Option<EpochState>,
+    /// The block is only ordered, it is executed later by the decoupled execution pipeline and
+    /// the vote does not carry an execution result.
+    decoupled_execution: bool,
 }
 
 impl<T> VoteProposal<T> {
@@ -31,6 +34,17 @@ impl<T> VoteProposal<T> {
             accumulator_extension_proof,
             block,
             next_epoch_state,
+            decoupled_execution: false,
+        }
+    }
+
+    /// A proposal to vote on the ordering of the block only, used with decoupled execution.
+    pub fn new_ordering_only(block: Block<T>) -> Self {
+        Self {
+            accumulator_extension_proof: AccumulatorExtensionProof::new(vec![], 0, vec![]),
+            block,
+            next_epoch_state: None,
+            decoupled_execution: true,
         }
     }
 
@@ -47,6 +61,10 @@ impl<T> VoteProposal<T> {
     pub fn next_epoch_state(&self) -> Option<&EpochState> {
         self.next_epoch_state.as_ref()
     }
+
+    pub fn decoupled_execution(&self) -> bool {
+        self.decoupled_execution
+    }
 }
 
 impl<T: PartialEq> Display for VoteProposal<T> {
@@ -0,0 +1,254 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use consensus_types::{block::Block, quorum_cert::QuorumCert};
+use libra_crypto::HashValue;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Write},
+};
+
+/// A block as persisted in the ConsensusDB, annotated with its certification status.
+pub struct BlockNode<T> {
+    pub block: Block<T>,
+    /// There is a QuorumCert for this block in the ConsensusDB
+    pub certified: bool,
+    /// This block is the highest committed block or one of its ancestors
+    pub committed: bool,
+}
+
+/// Offline view of the block tree reconstructed from the blocks and quorum certificates stored in
+/// the ConsensusDB.
+pub struct BlockTreeView<T> {
+    /// Sorted by (epoch, round)
+    nodes: Vec<BlockNode<T>>,
+}
+
+impl<T> BlockTreeView<T> {
+    pub fn new(blocks: Vec<Block<T>>, quorum_certs: &[QuorumCert]) -> Self {
+        let certified_ids: HashSet<HashValue> = quorum_certs
+            .iter()
+            .map(|qc| qc.certified_block().id())
+            .collect();
+        let parents: HashMap<HashValue, HashValue> = blocks
+            .iter()
+            .map(|block| (block.id(), block.parent_id()))
+            .collect();
+
+        // Every ancestor of the highest committed block is committed too.
+        let mut committed_ids = HashSet::new();
+        let highest_commit = quorum_certs
+            .iter()
+            .map(|qc| qc.commit_info())
+            .filter(|commit_info| commit_info.id() != HashValue::zero())
+            .max_by_key(|commit_info| (commit_info.epoch(), commit_info.round()));
+        let mut cursor = highest_commit.map(|commit_info| commit_info.id());
+        while let Some(id) = cursor {
+            if !committed_ids.insert(id) {
+                break;
+            }
+            cursor = parents.get(&id).cloned();
+        }
+
+        let mut nodes: Vec<_> = blocks
+            .into_iter()
+            .map(|block| BlockNode {
+                certified: certified_ids.contains(&block.id()),
+                committed: committed_ids.contains(&block.id()),
+                block,
+            })
+            .collect();
+        nodes.sort_by_key(|node| (node.block.epoch(), node.block.round()));
+        Self { nodes }
+    }
+
+    pub fn nodes(&self) -> &[BlockNode<T>] {
+        &self.nodes
+    }
+
+    /// Render the tree in the Graphviz dot language, with an edge from each block to its parent.
+    pub fn to_graphviz(&self) -> Result<String, fmt::Error> {
+        let mut out = String::new();
+        writeln!(out, "digraph block_tree {{")?;
+        writeln!(out, "  rankdir=RL;")?;
+        writeln!(out, "  node [shape=box, style=filled];")?;
+        let known_ids: HashSet<HashValue> = self.nodes.iter().map(|n| n.block.id()).collect();
+        for node in &self.nodes {
+            let block = &node.block;
+            let color = if node.committed {
+                "palegreen"
+            } else if node.certified {
+                "lightblue"
+            } else {
+                "white"
+            };
+            let author = author_str(block);
+            writeln!(
+                out,
+                "  \"{:x}\" [label=\"epoch {} round {}\\n{}\\nauthor: {}\", fillcolor={}];",
+                block.id(),
+                block.epoch(),
+                block.round(),
+                block.id().short_str(),
+                author,
+                color,
+            )?;
+            if known_ids.contains(&block.parent_id()) {
+                writeln!(
+                    out,
+                    "  \"{:x}\" -> \"{:x}\";",
+                    block.id(),
+                    block.parent_id()
+                )?;
+            }
+        }
+        writeln!(out, "}}")?;
+        Ok(out)
+    }
+}
+
+impl<T> fmt::Display for BlockTreeView<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for node in &self.nodes {
+            let block = &node.block;
+            let author = author_str(block);
+            writeln!(
+                f,
+                "epoch {} round {:>6} id {} parent {} author {} certified: {} committed: {}",
+                block.epoch(),
+                block.round(),
+                block.id().short_str(),
+                block.parent_id().short_str(),
+                author,
+                node.certified,
+                node.committed,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn author_str<T>(block: &Block<T>) -> String {
+    match block.author() {
+        Some(author) => author.short_str(),
+        None if block.is_genesis_block() => "genesis".to_string(),
+        None => "NIL".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use consensus_types::vote_data::VoteData;
+    use libra_crypto::hash::CryptoHash;
+    use libra_types::{
+        block_info::BlockInfo,
+        ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+        validator_signer::ValidatorSigner,
+    };
+    use std::collections::BTreeMap;
+
+    fn certificate_for(
+        block: &Block<Vec<usize>>,
+        parent: &Block<Vec<usize>>,
+        committed: Option<&Block<Vec<usize>>>,
+    ) -> QuorumCert {
+        let block_info =
+            |block: &Block<Vec<usize>>| block.gen_block_info(HashValue::zero(), 0, None);
+        let vote_data = VoteData::new(block_info(block), block_info(parent));
+        let commit_info = committed.map_or_else(BlockInfo::empty, block_info);
+        let ledger_info = LedgerInfo::new(commit_info, vote_data.hash());
+        QuorumCert::new(
+            vote_data,
+            LedgerInfoWithSignatures::new(ledger_info, BTreeMap::new()),
+        )
+    }
+
+    // genesis <- b1 <- b2 and a fork genesis <- b3, where b2 is certified and commits b1
+    fn block_tree() -> (Vec<Block<Vec<usize>>>, Vec<QuorumCert>) {
+        let signer = ValidatorSigner::random(None);
+        let genesis_ledger_info = LedgerInfo::new(BlockInfo::empty(), HashValue::zero());
+        let genesis = Block::make_genesis_block_from_ledger_info(&genesis_ledger_info);
+        let genesis_qc = QuorumCert::certificate_for_genesis_from_ledger_info(
+            &genesis_ledger_info,
+            genesis.id(),
+        );
+        let b1 = Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &signer, vec![]);
+        let b1_qc = certificate_for(&b1, &genesis, None);
+        let b2 = Block::new_proposal(vec![2], 2, 2, b1_qc.clone(), &signer, vec![]);
+        let b2_qc = certificate_for(&b2, &b1, Some(&b1));
+        let b3 = Block::new_proposal(vec![3], 3, 3, genesis_qc.clone(), &signer, vec![]);
+        (vec![b3, b2, genesis, b1], vec![b1_qc, genesis_qc, b2_qc])
+    }
+
+    #[test]
+    fn test_certified_and_committed() {
+        let (blocks, quorum_certs) = block_tree();
+        let tree = BlockTreeView::new(blocks, &quorum_certs);
+        let status: Vec<_> = tree
+            .nodes()
+            .iter()
+            .map(|node| (node.block.round(), node.certified, node.committed))
+            .collect();
+        assert_eq!(
+            status,
+            vec![
+                (0, true, true),
+                (1, true, true),
+                (2, true, false),
+                (3, false, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_commit() {
+        let (blocks, quorum_certs) = block_tree();
+        // without the certificate of b2 nothing but genesis is committed
+        let tree = BlockTreeView::new(blocks, &quorum_certs[..2]);
+        let committed: Vec<_> = tree
+            .nodes()
+            .iter()
+            .filter(|node| node.committed)
+            .map(|node| node.block.round())
+            .collect();
+        assert_eq!(committed, vec![0]);
+        assert!(!tree.nodes()[2].certified);
+    }
+
+    #[test]
+    fn test_display() {
+        let (blocks, quorum_certs) = block_tree();
+        let tree = BlockTreeView::new(blocks, &quorum_certs);
+        let output = tree.to_string();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("author genesis"));
+        assert!(lines[3].ends_with("certified: false committed: false"));
+    }
+
+    #[test]
+    fn test_graphviz() {
+        let (blocks, quorum_certs) = block_tree();
+        let tree = BlockTreeView::new(blocks, &quorum_certs);
+        let dot = tree.to_graphviz().unwrap();
+        assert!(dot.starts_with("digraph block_tree {"));
+        assert!(dot.ends_with("}\n"));
+        let nodes = tree.nodes();
+        let edge = |child: usize, parent: usize| {
+            format!(
+                "  \"{:x}\" -> \"{:x}\";",
+                nodes[child].block.id(),
+                nodes[parent].block.id()
+            )
+        };
+        assert!(dot.contains(&edge(1, 0)));
+        assert!(dot.contains(&edge(2, 1)));
+        assert!(dot.contains(&edge(3, 0)));
+        // the parent of genesis is not in the tree
+        assert_eq!(dot.matches(" -> ").count(), 3);
+        assert_eq!(dot.matches("fillcolor=palegreen").count(), 2);
+        assert_eq!(dot.matches("fillcolor=lightblue").count(), 1);
+        assert_eq!(dot.matches("fillcolor=white").count(), 1);
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Offline inspection of the consensus data of a node: the block tree, last vote and highest
+//! timeout certificate persisted in the ConsensusDB, and the safety data persisted by
+//! SafetyRules.
+
+mod block_tree;
+
+use crate::block_tree::BlockTreeView;
+use anyhow::{Context, Result};
+use consensus::ConsensusDBReader;
+use consensus_types::{timeout_certificate::TimeoutCertificate, vote::Vote};
+use libra_config::config::NodeConfig;
+use libra_secure_storage::Storage;
+use libra_types::transaction::SignedTransaction;
+use safety_rules::PersistentSafetyStorage;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+/// The payload of the blocks proposed by Libra validators.
+type Payload = Vec<SignedTransaction>;
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Offline inspection of the consensus data of a node")]
+enum Command {
+    /// Print the block tree, the last vote and the highest timeout certificate
+    #[structopt(name = "print-tree")]
+    PrintTree {
+        /// The storage directory of the node, which contains the consensusdb directory
+        #[structopt(long, parse(from_os_str))]
+        db: PathBuf,
+    },
+    /// Export the block tree in the Graphviz dot language
+    #[structopt(name = "export-graphviz")]
+    ExportGraphviz {
+        /// The storage directory of the node, which contains the consensusdb directory
+        #[structopt(long, parse(from_os_str))]
+        db: PathBuf,
+        /// Write to this file instead of stdout
+        #[structopt(long, short, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Print the epoch, last voted round, preferred round and waypoint stored by SafetyRules
+    #[structopt(name = "print-safety-data")]
+    PrintSafetyData {
+        /// The node config, its safety rules backend is the storage to read from
+        #[structopt(long, parse(from_os_str))]
+        config: PathBuf,
+    },
+}
+
+fn open_db(db: &Path) -> Result<ConsensusDBReader> {
+    ConsensusDBReader::open(db)
+        .with_context(|| format!("Unable to open ConsensusDB under {:?}", db))
+}
+
+fn print_tree(db: &ConsensusDBReader) -> Result<()> {
+    let (last_vote, highest_timeout_certificate, blocks, quorum_certs) =
+        db.get_data::<Payload>()?;
+
+    println!("Blocks ({}):", blocks.len());
+    print!("{}", BlockTreeView::new(blocks, &quorum_certs));
+
+    match last_vote {
+        Some(bytes) => {
+            let vote: Vote = lcs::from_bytes(&bytes).context("Unable to deserialize last vote")?;
+            println!("Last vote: {}", vote);
+        }
+        None => println!("Last vote: None"),
+    }
+    match highest_timeout_certificate {
+        Some(bytes) => {
+            let tc: TimeoutCertificate = lcs::from_bytes(&bytes)
+                .context("Unable to deserialize highest timeout certificate")?;
+            println!("Highest timeout certificate: {}", tc);
+        }
+        None => println!("Highest timeout certificate: None"),
+    }
+    Ok(())
+}
+
+fn export_graphviz(db: &ConsensusDBReader, output: Option<PathBuf>) -> Result<()> {
+    let (_, _, blocks, quorum_certs) = db.get_data::<Payload>()?;
+    let dot = BlockTreeView::new(blocks, &quorum_certs).to_graphviz()?;
+    match output {
+        Some(path) => fs::write(&path, dot).with_context(|| format!("Unable to write {:?}", path)),
+        None => {
+            print!("{}", dot);
+            Ok(())
+        }
+    }
+}
+
+fn print_safety_data(config: &Path) -> Result<()> {
+    let config = NodeConfig::load(config)
+        .with_context(|| format!("Unable to load node config {:?}", config))?;
+    let internal_storage: Box<dyn Storage> = (&config.consensus.safety_rules.backend).into();
+    let storage = PersistentSafetyStorage::new(internal_storage);
+
+    println!("Epoch: {}", storage.epoch()?);
+    println!("Last voted round: {}", storage.last_voted_round()?);
+    println!("Preferred round: {}", storage.preferred_round()?);
+    println!("Waypoint: {}", storage.waypoint()?);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    match Command::from_args() {
+        Command::PrintTree { db } => print_tree(&open_db(&db)?),
+        Command::ExportGraphviz { db, output } => export_graphviz(&open_db(&db)?, output),
+        Command::PrintSafetyData { config } => print_safety_data(&config),
+    }
+}
@@ -18,6 +18,10 @@ define_counters![
         "sign_proposal counter counts sign_proposals"
     ),
     (sign_timeout: Counter, "counts successful sign_timeouts"),
+    (
+        sign_commit_vote: Counter,
+        "counts successful sign_commit_votes"
+    ),
     (some_gauge_counter: Gauge, "example help for a gauge metric"),
 ];
 
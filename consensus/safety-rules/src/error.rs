@@ -26,6 +26,17 @@ pub enum Error {
     #[error("No next_epoch_state specified in the provided Ledger Info")]
     InvalidLedgerInfo,
 
+    /// The execution result to sign does not extend the last one signed
+    #[error(
+        "Commit vote at round {:?} does not extend the last commit vote at round {:?}",
+        commit_round,
+        last_commit_round
+    )]
+    NonExtendingCommitVote {
+        commit_round: Round,
+        last_commit_round: Round,
+    },
+
     #[error("Invalid QC: {}", {0})]
     InvalidQuorumCertificate(String),
 
@@ -51,6 +62,18 @@ pub enum Error {
         proposal_round: Round,
     },
 
+    /// The block to sign the execution result of is past the last voted round, so it was not
+    /// ordered in rounds this validator voted in
+    #[error(
+        "Commit vote at round {:?} is newer than the last vote round {:?}",
+        commit_round,
+        last_voted_round
+    )]
+    UnorderedCommitVote {
+        commit_round: Round,
+        last_voted_round: Round,
+    },
+
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
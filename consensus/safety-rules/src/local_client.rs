@@ -7,7 +7,7 @@ use consensus_types::{
     timeout::Timeout, vote::Vote, vote_proposal::VoteProposal,
 };
 use libra_crypto::ed25519::Ed25519Signature;
-use libra_types::epoch_change::EpochChangeProof;
+use libra_types::{epoch_change::EpochChangeProof, ledger_info::LedgerInfo};
 use std::sync::{Arc, RwLock};
 
 /// A local interface into SafetyRules. Constructed in such a way that the container / caller
@@ -50,4 +50,8 @@ impl<T: Payload> TSafetyRules<T> for LocalClient<T> {
     fn sign_timeout(&mut self, timeout: &Timeout) -> Result<Ed25519Signature, Error> {
         self.internal.write().unwrap().sign_timeout(timeout)
     }
+
+    fn sign_commit_vote(&mut self, ledger_info: &LedgerInfo) -> Result<Ed25519Signature, Error> {
+        self.internal.write().unwrap().sign_commit_vote(ledger_info)
+    }
 }
@@ -4,9 +4,11 @@
 use anyhow::Result;
 use consensus_types::common::Round;
 use libra_crypto::ed25519::Ed25519PrivateKey;
-use libra_global_constants::{CONSENSUS_KEY, EPOCH, LAST_VOTED_ROUND, PREFERRED_ROUND, WAYPOINT};
-use libra_secure_storage::{InMemoryStorage, Storage, Value};
-use libra_types::waypoint::Waypoint;
+use libra_global_constants::{
+    CONSENSUS_KEY, EPOCH, LAST_COMMIT_VOTE, LAST_VOTED_ROUND, PREFERRED_ROUND, WAYPOINT,
+};
+use libra_secure_storage::{Error, InMemoryStorage, Storage, Value};
+use libra_types::{block_info::BlockInfo, waypoint::Waypoint};
 use std::str::FromStr;
 
 /// SafetyRules needs an abstract storage interface to act as a common utility for storing
@@ -83,6 +85,21 @@ impl PersistentSafetyStorage {
         Ok(())
     }
 
+    /// The execution result of the last commit vote signed, not set before the first one.
+    pub fn last_commit_vote(&self) -> Result<Option<BlockInfo>> {
+        match self.internal_store.get(LAST_COMMIT_VOTE) {
+            Ok(response) => Ok(Some(response.value.block_info()?)),
+            Err(Error::KeyNotSet(_)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn set_last_commit_vote(&mut self, last_commit_vote: &BlockInfo) -> Result<()> {
+        self.internal_store
+            .set(LAST_COMMIT_VOTE, Value::BlockInfo(last_commit_vote.clone()))?;
+        Ok(())
+    }
+
     pub fn preferred_round(&self) -> Result<Round> {
         Ok(self
             .internal_store
@@ -114,6 +131,7 @@ impl PersistentSafetyStorage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use libra_crypto::HashValue;
     use libra_types::validator_signer::ValidatorSigner;
 
     #[test]
@@ -123,11 +141,16 @@ mod tests {
         assert_eq!(storage.epoch().unwrap(), 1);
         assert_eq!(storage.last_voted_round().unwrap(), 0);
         assert_eq!(storage.preferred_round().unwrap(), 0);
+        assert_eq!(storage.last_commit_vote().unwrap(), None);
         storage.set_epoch(9).unwrap();
         storage.set_last_voted_round(8).unwrap();
         storage.set_preferred_round(1).unwrap();
         assert_eq!(storage.epoch().unwrap(), 9);
         assert_eq!(storage.last_voted_round().unwrap(), 8);
         assert_eq!(storage.preferred_round().unwrap(), 1);
+        let commit_info =
+            BlockInfo::new(9, 3, HashValue::random(), HashValue::random(), 2, 0, None);
+        storage.set_last_commit_vote(&commit_info).unwrap();
+        assert_eq!(storage.last_commit_vote().unwrap(), Some(commit_info));
     }
 }
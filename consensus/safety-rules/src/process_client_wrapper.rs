@@ -16,7 +16,9 @@ use libra_config::{
     utils,
 };
 use libra_crypto::ed25519::Ed25519Signature;
-use libra_types::{epoch_change::EpochChangeProof, validator_signer::ValidatorSigner};
+use libra_types::{
+    epoch_change::EpochChangeProof, ledger_info::LedgerInfo, validator_signer::ValidatorSigner,
+};
 use std::{
     any::TypeId,
     net::{IpAddr, Ipv4Addr, SocketAddr},
@@ -104,4 +106,8 @@ impl<T: Payload> TSafetyRules<T> for ProcessClientWrapper<T> {
     fn sign_timeout(&mut self, timeout: &Timeout) -> Result<Ed25519Signature, Error> {
         self.safety_rules.sign_timeout(timeout)
     }
+
+    fn sign_commit_vote(&mut self, ledger_info: &LedgerInfo) -> Result<Ed25519Signature, Error> {
+        self.safety_rules.sign_commit_vote(ledger_info)
+    }
 }
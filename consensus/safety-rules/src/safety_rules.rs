@@ -15,11 +15,18 @@ use consensus_types::{
     vote_data::VoteData,
     vote_proposal::VoteProposal,
 };
-use libra_crypto::{ed25519::Ed25519Signature, hash::HashValue};
+use libra_crypto::{
+    ed25519::Ed25519Signature,
+    hash::{CryptoHash, HashValue},
+};
 use libra_logger::debug;
 use libra_types::{
-    block_info::BlockInfo, epoch_change::EpochChangeProof, ledger_info::LedgerInfo,
-    validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier, waypoint::Waypoint,
+    block_info::{BlockInfo, ORDERED_ONLY_VERSION},
+    epoch_change::EpochChangeProof,
+    ledger_info::LedgerInfo,
+    validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
+    waypoint::Waypoint,
 };
 use std::marker::PhantomData;
 
@@ -200,28 +207,39 @@ impl<T: Payload> TSafetyRules<T> for SafetyRules<T> {
             return Err(Error::ProposalRoundLowerThenPreferredBlock { preferred_round });
         }
 
-        let new_tree = vote_proposal
-            .accumulator_extension_proof()
-            .verify(
-                proposed_block
-                    .quorum_cert()
-                    .certified_block()
-                    .executed_state_id(),
+        // With decoupled execution the vote only orders the block, the execution result is
+        // agreed upon later through commit votes.
+        let proposed_block_info = if vote_proposal.decoupled_execution() {
+            proposed_block.gen_block_info(
+                BlockInfo::ordered_only_state_id(),
+                ORDERED_ONLY_VERSION,
+                None,
+            )
+        } else {
+            let new_tree = vote_proposal
+                .accumulator_extension_proof()
+                .verify(
+                    proposed_block
+                        .quorum_cert()
+                        .certified_block()
+                        .executed_state_id(),
+                )
+                .map_err(|e| Error::InvalidAccumulatorExtension {
+                    error: format!("{}", e),
+                })?;
+            proposed_block.gen_block_info(
+                new_tree.root_hash(),
+                new_tree.version(),
+                vote_proposal.next_epoch_state().cloned(),
             )
-            .map_err(|e| Error::InvalidAccumulatorExtension {
-                error: format!("{}", e),
-            })?;
+        };
 
         self.persistent_storage
             .set_last_voted_round(proposed_block.round())?;
 
         Ok(Vote::new(
             VoteData::new(
-                proposed_block.gen_block_info(
-                    new_tree.root_hash(),
-                    new_tree.version(),
-                    vote_proposal.next_epoch_state().cloned(),
-                ),
+                proposed_block_info,
                 proposed_block.quorum_cert().certified_block().clone(),
             ),
             self.validator_signer.author(),
@@ -278,4 +296,44 @@ impl<T: Payload> TSafetyRules<T> for SafetyRules<T> {
         debug!("Successfully signed timeout message.");
         Ok(signature)
     }
+
+    /// Only sign the execution result of blocks of the current epoch which were ordered in rounds
+    /// this validator voted in, and which extend the last execution result signed. Execution is
+    /// deterministic, so signing the same result twice cannot lead to conflicting commits.
+    fn sign_commit_vote(&mut self, ledger_info: &LedgerInfo) -> Result<Ed25519Signature, Error> {
+        debug!("Incoming commit vote for round {}", ledger_info.round());
+        self.verify_epoch(ledger_info.epoch())?;
+        let commit_info = ledger_info.commit_info();
+        if commit_info.is_ordered_only() {
+            return Err(Error::InternalError {
+                error: format!("Commit vote on an unexecuted block: {}", ledger_info),
+            });
+        }
+
+        let last_voted_round = self.persistent_storage.last_voted_round()?;
+        if commit_info.round() > last_voted_round {
+            return Err(Error::UnorderedCommitVote {
+                commit_round: commit_info.round(),
+                last_voted_round,
+            });
+        }
+        if let Some(last_commit_info) = self.persistent_storage.last_commit_vote()? {
+            let extends = last_commit_info.epoch() != commit_info.epoch()
+                || &last_commit_info == commit_info
+                || (commit_info.round() > last_commit_info.round()
+                    && commit_info.version() >= last_commit_info.version());
+            if !extends {
+                return Err(Error::NonExtendingCommitVote {
+                    commit_round: commit_info.round(),
+                    last_commit_round: last_commit_info.round(),
+                });
+            }
+        }
+
+        // Persisted before signing, a restarted validator keeps refusing conflicting results.
+        self.persistent_storage.set_last_commit_vote(commit_info)?;
+        let signature = self.validator_signer.sign_message(ledger_info.hash());
+        COUNTERS.sign_commit_vote.inc();
+        Ok(signature)
+    }
 }
@@ -7,7 +7,7 @@ use consensus_types::{
     timeout::Timeout, vote::Vote, vote_proposal::VoteProposal,
 };
 use libra_crypto::ed25519::Ed25519Signature;
-use libra_types::epoch_change::EpochChangeProof;
+use libra_types::{epoch_change::EpochChangeProof, ledger_info::LedgerInfo};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
 
@@ -21,6 +21,7 @@ pub enum SafetyRulesInput<T> {
     #[serde(bound = "T: Payload")]
     SignProposal(Box<BlockData<T>>),
     SignTimeout(Box<Timeout>),
+    SignCommitVote(Box<LedgerInfo>),
 }
 
 pub struct SerializerService<T> {
@@ -48,6 +49,9 @@ impl<T: Payload> SerializerService<T> {
             SafetyRulesInput::SignTimeout(timeout) => {
                 lcs::to_bytes(&self.internal.sign_timeout(&timeout))
             }
+            SafetyRulesInput::SignCommitVote(ledger_info) => {
+                lcs::to_bytes(&self.internal.sign_commit_vote(&ledger_info))
+            }
         };
 
         Ok(output?)
@@ -105,6 +109,13 @@ impl<T: Payload> TSafetyRules<T> for SerializerClient<T> {
         let response = self.request(SafetyRulesInput::SignTimeout(Box::new(timeout.clone())))?;
         lcs::from_bytes(&response)?
     }
+
+    fn sign_commit_vote(&mut self, ledger_info: &LedgerInfo) -> Result<Ed25519Signature, Error> {
+        let response = self.request(SafetyRulesInput::SignCommitVote(Box::new(
+            ledger_info.clone(),
+        )))?;
+        lcs::from_bytes(&response)?
+    }
 }
 
 pub trait TSerializerClient<T>: Send + Sync {
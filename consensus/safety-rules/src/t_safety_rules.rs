@@ -7,7 +7,7 @@ use consensus_types::{
     vote_proposal::VoteProposal,
 };
 use libra_crypto::ed25519::Ed25519Signature;
-use libra_types::epoch_change::EpochChangeProof;
+use libra_types::{epoch_change::EpochChangeProof, ledger_info::LedgerInfo};
 
 /// Interface for SafetyRules
 pub trait TSafetyRules<T> {
@@ -35,4 +35,8 @@ pub trait TSafetyRules<T> {
     /// As the holder of the private key, SafetyRules also signs what is effectively a
     /// timeout message. This returns the signature for that timeout message.
     fn sign_timeout(&mut self, timeout: &Timeout) -> Result<Ed25519Signature, Error>;
+
+    /// With decoupled execution, signs the LedgerInfo resulting from the execution of ordered
+    /// blocks. This returns the signature for the commit vote.
+    fn sign_commit_vote(&mut self, ledger_info: &LedgerInfo) -> Result<Ed25519Signature, Error>;
 }
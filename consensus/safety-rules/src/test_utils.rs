@@ -51,6 +51,7 @@ pub fn make_proposal_with_qc_and_proof<P: Payload>(
                 .as_secs(),
             qc,
             validator_signer,
+            vec![],
         ),
         None,
     )
@@ -1,9 +1,15 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{test_utils, tests::suite, SafetyRules, TSafetyRules};
-use consensus_types::common::{Payload, Round};
-use libra_types::validator_signer::ValidatorSigner;
+use crate::{test_utils, tests::suite, Error, PersistentSafetyStorage, SafetyRules, TSafetyRules};
+use consensus_types::{
+    common::{Payload, Round},
+    vote_proposal::VoteProposal,
+};
+use libra_crypto::HashValue;
+use libra_secure_storage::OnDiskStorage;
+use libra_temppath::TempPath;
+use libra_types::{ledger_info::LedgerInfo, validator_signer::ValidatorSigner};
 
 #[test]
 fn test() {
@@ -16,3 +22,49 @@ fn safety_rules<T: Payload>() -> (Box<dyn TSafetyRules<T>>, ValidatorSigner) {
     let safety_rules = Box::new(SafetyRules::<T>::new(signer.author(), storage));
     (safety_rules, signer)
 }
+
+#[test]
+fn test_commit_vote_after_restart() {
+    let signer = ValidatorSigner::from_int(0);
+    let storage_path = TempPath::new();
+    let waypoint = test_utils::validator_signers_to_waypoints(&[&signer]);
+    let storage = PersistentSafetyStorage::initialize(
+        Box::new(OnDiskStorage::new(storage_path.path().to_path_buf())),
+        signer.private_key().clone(),
+        waypoint,
+    );
+    let mut safety_rules = SafetyRules::<Round>::new(signer.author(), storage);
+
+    let (proof, genesis_qc) = suite::make_genesis::<Round>(&signer);
+    let round = genesis_qc.certified_block().round() + 1;
+    let p0 = test_utils::make_proposal_with_qc(round, genesis_qc, &signer);
+    safety_rules.initialize(&proof).unwrap();
+    safety_rules
+        .construct_and_sign_vote(&VoteProposal::new_ordering_only(p0.block().clone()))
+        .unwrap();
+    let executed_li = LedgerInfo::new(
+        p0.block().gen_block_info(HashValue::random(), 1, None),
+        HashValue::zero(),
+    );
+    safety_rules.sign_commit_vote(&executed_li).unwrap();
+
+    // The restarted SafetyRules reads the last commit vote back from storage
+    let storage = PersistentSafetyStorage::new(Box::new(OnDiskStorage::new(
+        storage_path.path().to_path_buf(),
+    )));
+    let mut safety_rules = SafetyRules::<Round>::new(signer.author(), storage);
+    safety_rules.initialize(&proof).unwrap();
+
+    let conflicting_li = LedgerInfo::new(
+        p0.block().gen_block_info(HashValue::random(), 1, None),
+        HashValue::zero(),
+    );
+    let actual_err = safety_rules.sign_commit_vote(&conflicting_li).unwrap_err();
+    let expected_err = Error::NonExtendingCommitVote {
+        commit_round: round,
+        last_commit_round: round,
+    };
+    assert_eq!(actual_err, expected_err);
+    // The same execution result can still be signed again
+    assert!(safety_rules.sign_commit_vote(&executed_li).is_ok());
+}
@@ -11,18 +11,20 @@ use consensus_types::{
 };
 use libra_crypto::hash::{CryptoHash, HashValue};
 use libra_types::{
+    block_info::BlockInfo,
     epoch_change::EpochChangeProof,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     on_chain_config::ValidatorSet,
     validator_info::ValidatorInfo,
     validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
 };
 use rand::Rng;
 use std::collections::BTreeMap;
 
 type Proof = test_utils::Proof;
 
-fn make_genesis<T: Payload>(signer: &ValidatorSigner) -> (EpochChangeProof, QuorumCert) {
+pub fn make_genesis<T: Payload>(signer: &ValidatorSigner) -> (EpochChangeProof, QuorumCert) {
     let validator_info =
         ValidatorInfo::new_with_test_network_keys(signer.author(), signer.public_key(), 1);
     let validator_set = ValidatorSet::new(vec![validator_info]);
@@ -58,6 +60,7 @@ type ByteArrayCallback = fn() -> (Box<dyn TSafetyRules<Vec<u8>>>, ValidatorSigne
 pub fn run_test_suite(round_func: RoundCallback, byte_func: ByteArrayCallback) {
     test_bad_execution_output(round_func);
     test_commit_rule_consecutive_rounds(round_func);
+    test_decoupled_execution(round_func);
     test_end_to_end(byte_func);
     test_initialize(round_func);
     test_preferred_block_rule(round_func);
@@ -145,6 +148,91 @@ fn test_commit_rule_consecutive_rounds(func: RoundCallback) {
     safety_rules.construct_and_sign_vote(&a4).unwrap();
 }
 
+fn test_decoupled_execution(func: RoundCallback) {
+    let (mut safety_rules, signer) = func();
+
+    let (proof, genesis_qc) = make_genesis::<Round>(&signer);
+    let round = genesis_qc.certified_block().round();
+    let epoch = genesis_qc.certified_block().epoch();
+
+    let p0 = test_utils::make_proposal_with_qc(round + 1, genesis_qc, &signer);
+    let ordering_proposal = VoteProposal::new_ordering_only(p0.block().clone());
+
+    safety_rules.initialize(&proof).unwrap();
+
+    // The ordering vote does not carry an execution result
+    let vote = safety_rules
+        .construct_and_sign_vote(&ordering_proposal)
+        .unwrap();
+    assert!(vote.vote_data().proposed().is_ordered_only());
+    assert_eq!(vote.vote_data().proposed().id(), p0.block().id());
+
+    // The execution result of the block can be signed, possibly more than once
+    let executed_info = p0.block().gen_block_info(HashValue::random(), 1, None);
+    let ledger_info = LedgerInfo::new(executed_info, HashValue::zero());
+    let sign1 = safety_rules.sign_commit_vote(&ledger_info).unwrap();
+    let sign2 = safety_rules.sign_commit_vote(&ledger_info).unwrap();
+    assert_eq!(sign1, sign2);
+    ValidatorVerifier::new_single(signer.author(), signer.public_key())
+        .verify_signature(signer.author(), ledger_info.hash(), &sign1)
+        .unwrap();
+
+    // Nor execution results which don't extend the last one signed
+    let older_info = BlockInfo::new(
+        epoch,
+        round,
+        HashValue::random(),
+        HashValue::random(),
+        1,
+        0,
+        None,
+    );
+    let older_li = LedgerInfo::new(older_info, HashValue::zero());
+    let actual_err = safety_rules.sign_commit_vote(&older_li).unwrap_err();
+    let expected_err = Error::NonExtendingCommitVote {
+        commit_round: round,
+        last_commit_round: round + 1,
+    };
+    assert_eq!(actual_err, expected_err);
+
+    // Nor blocks past the rounds this validator voted in
+    let unordered_info = BlockInfo::new(
+        epoch,
+        round + 2,
+        HashValue::random(),
+        HashValue::random(),
+        2,
+        0,
+        None,
+    );
+    let unordered_li = LedgerInfo::new(unordered_info, HashValue::zero());
+    let actual_err = safety_rules.sign_commit_vote(&unordered_li).unwrap_err();
+    let expected_err = Error::UnorderedCommitVote {
+        commit_round: round + 2,
+        last_voted_round: round + 1,
+    };
+    assert_eq!(actual_err, expected_err);
+
+    // Unexecuted blocks cannot be signed
+    let ordered_li = LedgerInfo::new(vote.vote_data().proposed().clone(), HashValue::zero());
+    assert!(safety_rules.sign_commit_vote(&ordered_li).is_err());
+
+    // Nor blocks from another epoch
+    let other_epoch_info = BlockInfo::new(
+        epoch + 1,
+        round + 1,
+        HashValue::random(),
+        HashValue::random(),
+        1,
+        0,
+        None,
+    );
+    let other_epoch_li = LedgerInfo::new(other_epoch_info, HashValue::zero());
+    let actual_err = safety_rules.sign_commit_vote(&other_epoch_li).unwrap_err();
+    let expected_err = Error::IncorrectEpoch(epoch + 1, epoch);
+    assert_eq!(actual_err, expected_err);
+}
+
 fn test_end_to_end(func: ByteArrayCallback) {
     let (mut safety_rules, signer) = func();
 
@@ -4,6 +4,7 @@
 use crate::{
     block_storage::{block_tree::BlockTree, BlockReader},
     counters,
+    decoupled_execution::ordering_state_computer::ordered_only_compute_result,
     persistent_liveness_storage::{
         PersistentLivenessStorage, RecoveryData, RootInfo, RootMetadata,
     },
@@ -23,7 +24,7 @@ use libra_logger::prelude::*;
 use libra_types::epoch_state::EpochState;
 use libra_types::{ledger_info::LedgerInfoWithSignatures, transaction::TransactionStatus};
 use std::{
-    collections::vec_deque::VecDeque,
+    collections::{vec_deque::VecDeque, HashSet},
     sync::{Arc, RwLock},
     time::Duration,
 };
@@ -123,34 +124,40 @@ impl<T: Payload> BlockStore<T> {
         max_pruned_blocks_in_mem: usize,
     ) -> Self {
         let RootInfo(root_block, root_qc, root_li) = root;
-        //verify root is correct
-        assert_eq!(
-            root_qc.certified_block().version(),
-            root_metadata.version(),
-            "root qc version {} doesn't match committed trees {}",
-            root_qc.certified_block().version(),
-            root_metadata.version(),
-        );
-        assert_eq!(
-            root_qc.certified_block().executed_state_id(),
-            root_metadata.accu_hash,
-            "root qc state id {} doesn't match committed trees {}",
-            root_qc.certified_block().executed_state_id(),
-            root_metadata.accu_hash,
-        );
-
-        let executed_root_block = ExecutedBlock::new(
-            root_block,
-            // Create a dummy state_compute_result with necessary fields filled in.
-            StateComputeResult::new(
+        let executed_root_block = if root_qc.certified_block().is_ordered_only() {
+            // With decoupled execution the root QC only certifies the ordering of the root, the
+            // root is then kept with the ordered result for its children to extend it.
+            ExecutedBlock::new(root_block, ordered_only_compute_result())
+        } else {
+            //verify root is correct
+            assert_eq!(
+                root_qc.certified_block().version(),
+                root_metadata.version(),
+                "root qc version {} doesn't match committed trees {}",
+                root_qc.certified_block().version(),
+                root_metadata.version(),
+            );
+            assert_eq!(
+                root_qc.certified_block().executed_state_id(),
                 root_metadata.accu_hash,
-                root_metadata.frozen_root_hashes,
-                root_metadata.num_leaves, /* num_leaves */
-                None,                     /* epoch_state */
-                vec![],                   /* compute_status */
-                vec![],                   /* transaction_info_hashes */
-            ),
-        );
+                "root qc state id {} doesn't match committed trees {}",
+                root_qc.certified_block().executed_state_id(),
+                root_metadata.accu_hash,
+            );
+
+            ExecutedBlock::new(
+                root_block,
+                // Create a dummy state_compute_result with necessary fields filled in.
+                StateComputeResult::new(
+                    root_metadata.accu_hash,
+                    root_metadata.frozen_root_hashes,
+                    root_metadata.num_leaves, /* num_leaves */
+                    None,                     /* epoch_state */
+                    vec![],                   /* compute_status */
+                    vec![],                   /* transaction_info_hashes */
+                ),
+            )
+        };
         let tree = BlockTree::new(
             executed_root_block,
             root_qc,
@@ -248,11 +255,17 @@ impl<T: Payload> BlockStore<T> {
             .unwrap_or_else(|_| panic!("New block tree is not shared"))
             .into_inner()
             .unwrap();
-        // If we fail to commit B_i via state computer and crash, after restart our highest commit cert
-        // will not match the latest commit B_j(j<i) of state computer.
-        // This introduces an inconsistent state if we send out SyncInfo and others try to sync to
-        // B_i and figure out we only have B_j.
-        // Here we commit up to the highest_commit_cert to maintain highest_commit_cert == state_computer.committed_trees.
+        self.commit_to_highest_commit_cert().await;
+    }
+
+    /// If we fail to commit B_i via state computer and crash, after restart our highest commit cert
+    /// will not match the latest commit B_j(j<i) of state computer.
+    /// This introduces an inconsistent state if we send out SyncInfo and others try to sync to
+    /// B_i and figure out we only have B_j.
+    /// Here we commit up to the highest_commit_cert to maintain highest_commit_cert == state_computer.committed_trees.
+    /// With decoupled execution, this hands the ordered blocks that were not executed before the
+    /// restart to the execution pipeline again.
+    pub async fn commit_to_highest_commit_cert(&self) {
         if self.highest_commit_cert().commit_info().round() > self.root().round() {
             let finality_proof = self.highest_commit_cert().ledger_info().clone();
             if let Err(e) = self.commit(finality_proof).await {
@@ -365,6 +378,10 @@ impl<T: Payload> BlockStore<T> {
     /// prune_tree(B3) should be left with
     /// B3--> B4, root = B3
     ///
+    /// With decoupled execution, the ordered blocks are only removed from storage by the
+    /// execution pipeline once their execution is committed, as a restart recovers from the block
+    /// committed in storage.
+    ///
     /// Returns the block ids of the blocks removed.
     fn prune_tree(&self, next_root_id: HashValue) -> VecDeque<HashValue> {
         let id_to_remove = self
@@ -372,10 +389,26 @@ impl<T: Payload> BlockStore<T> {
             .read()
             .unwrap()
             .find_blocks_to_prune(next_root_id);
-        if let Err(e) = self
-            .storage
-            .prune_tree(id_to_remove.clone().into_iter().collect())
-        {
+        let ordered_only = self
+            .get_block(next_root_id)
+            .map_or(false, |block| block.block_info().is_ordered_only());
+        let storage_to_remove = if ordered_only {
+            let mut ordered: HashSet<HashValue> = self
+                .path_from_root(next_root_id)
+                .unwrap_or_else(Vec::new)
+                .iter()
+                .map(|block| block.id())
+                .collect();
+            ordered.insert(self.root().id());
+            id_to_remove
+                .iter()
+                .filter(|id| !ordered.contains(id))
+                .cloned()
+                .collect()
+        } else {
+            id_to_remove.clone().into_iter().collect()
+        };
+        if let Err(e) = self.storage.prune_tree(storage_to_remove) {
             // it's fine to fail here, as long as the commit succeeds, the next restart will clean
             // up dangling blocks, and we need to prune the tree to keep the root consistent with
             // executor.
@@ -330,6 +330,7 @@ fn test_illegal_timestamp() {
         genesis.timestamp_usecs(),
         certificate_for_genesis(),
         &signer,
+        vec![],
     );
     let result = block_store.execute_and_insert_block(block_with_illegal_timestamp);
     assert!(result.is_err());
@@ -95,9 +95,12 @@ where
         max_pruned_blocks_in_mem: usize,
         highest_timeout_cert: Option<Arc<TimeoutCertificate>>,
     ) -> Self {
-        assert_eq!(
-            root.id(),
-            root_ledger_info.commit_info().id(),
+        // With decoupled execution the ledger info may be the ordering commit of a descendant of
+        // the root, storage only having executed part of the ordered blocks.
+        assert!(
+            root.id() == root_ledger_info.commit_info().id()
+                || (root_ledger_info.commit_info().is_ordered_only()
+                    && root_ledger_info.commit_info().round() > root.round()),
             "inconsistent root and ledger info"
         );
         let root_id = root.id();
@@ -8,6 +8,7 @@ use consensus_types::{
     timeout_certificate::TimeoutCertificate,
     vote::Vote,
 };
+use debug_interface::prelude::*;
 use libra_crypto::{hash::CryptoHash, HashValue};
 use libra_logger::prelude::*;
 use libra_types::{
@@ -91,16 +92,21 @@ impl PendingVotes {
         // Note that the digest covers the ledger info information, which is also indirectly
         // covering vote data hash (in its `consensus_data_hash` field).
         let li_digest = vote.ledger_info().hash();
+        let block_id = vote.vote_data().proposed().id();
         let li_with_sig = self.li_digest_to_votes.entry(li_digest).or_insert_with(|| {
+            trace_event!("pending_votes::first_vote", {"block", block_id});
             LedgerInfoWithSignatures::new(vote.ledger_info().clone(), BTreeMap::new())
         });
         li_with_sig.add_signature(vote.author(), vote.signature().clone());
 
         match validator_verifier.check_voting_power(li_with_sig.signatures().keys()) {
-            Ok(_) => VoteReceptionResult::NewQuorumCertificate(Arc::new(QuorumCert::new(
-                vote.vote_data().clone(),
-                li_with_sig.clone(),
-            ))),
+            Ok(_) => {
+                trace_event!("pending_votes::qc_aggregated", {"block", block_id});
+                VoteReceptionResult::NewQuorumCertificate(Arc::new(QuorumCert::new(
+                    vote.vote_data().clone(),
+                    li_with_sig.clone(),
+                )))
+            }
             Err(VerifyError::TooLittleVotingPower { voting_power, .. }) => {
                 VoteReceptionResult::VoteAdded(voting_power)
             }
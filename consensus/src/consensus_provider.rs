@@ -51,6 +51,8 @@ pub fn start_consensus(
     let (timeout_sender, timeout_receiver) = channel::new(1_024, &counters::PENDING_ROUND_TIMEOUTS);
     let (self_sender, self_receiver) = channel::new(1_024, &counters::PENDING_SELF_MESSAGES);
 
+    let (network_task, network_receiver) =
+        NetworkTask::new(network_events, network_sender.clone(), self_receiver);
     let epoch_mgr = EpochManager::new(
         node_config,
         time_service,
@@ -62,8 +64,6 @@ pub fn start_consensus(
         storage,
     );
 
-    let (network_task, network_receiver) = NetworkTask::new(network_events, self_receiver);
-
     runtime.spawn(network_task.start());
     runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver, reconfig_events));
 
@@ -15,7 +15,7 @@ use consensus_types::{block::Block, common::Payload, quorum_cert::QuorumCert};
 use libra_crypto::HashValue;
 use libra_logger::prelude::*;
 use schema::{BLOCK_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME};
-use schemadb::{ReadOptions, SchemaBatch, DB, DEFAULT_CF_NAME};
+use schemadb::{ColumnFamilyName, ReadOptions, SchemaBatch, DB, DEFAULT_CF_NAME};
 use std::{collections::HashMap, iter::Iterator, path::Path, time::Instant};
 
 pub struct ConsensusDB {
@@ -24,16 +24,9 @@ pub struct ConsensusDB {
 
 impl ConsensusDB {
     pub fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
-        let column_families = vec![
-            /* UNUSED CF = */ DEFAULT_CF_NAME,
-            BLOCK_CF_NAME,
-            QC_CF_NAME,
-            SINGLE_ENTRY_CF_NAME,
-        ];
-
         let path = db_root_path.as_ref().join("consensusdb");
         let instant = Instant::now();
-        let db = DB::open(path.clone(), "consensus", column_families)
+        let db = DB::open(path.clone(), "consensus", Self::column_families())
             .expect("ConsensusDB open failed; unable to continue");
 
         info!(
@@ -45,6 +38,22 @@ impl ConsensusDB {
         Self { db }
     }
 
+    /// Open an existing ConsensusDB under the given directory in readonly mode.
+    fn new_readonly<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let path = db_root_path.as_ref().join("consensusdb");
+        let db = DB::open_readonly(path, "consensus_ro", Self::column_families())?;
+        Ok(Self { db })
+    }
+
+    fn column_families() -> Vec<ColumnFamilyName> {
+        vec![
+            /* UNUSED CF = */ DEFAULT_CF_NAME,
+            BLOCK_CF_NAME,
+            QC_CF_NAME,
+            SINGLE_ENTRY_CF_NAME,
+        ]
+    }
+
     pub fn get_data<T: Payload>(
         &self,
     ) -> Result<(
@@ -179,3 +188,30 @@ impl ConsensusDB {
         iter.collect::<Result<HashMap<HashValue, QuorumCert>>>()
     }
 }
+
+/// Readonly access to the ConsensusDB of a node, for inspecting its data offline.
+pub struct ConsensusDBReader {
+    db: ConsensusDB,
+}
+
+impl ConsensusDBReader {
+    /// Open the existing ConsensusDB under the given directory.
+    pub fn open<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        Ok(Self {
+            db: ConsensusDB::new_readonly(db_root_path)?,
+        })
+    }
+
+    /// Return the serialized last vote, the serialized highest timeout certificate, all blocks
+    /// and all quorum certificates.
+    pub fn get_data<T: Payload>(
+        &self,
+    ) -> Result<(
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+        Vec<Block<T>>,
+        Vec<QuorumCert>,
+    )> {
+        self.db.get_data()
+    }
+}
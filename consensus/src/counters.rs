@@ -127,6 +127,17 @@ pub static BLOCK_RETRIEVAL_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counters(queued,dequeued,dropped) related to the commit messages channel of the execution
+/// pipeline
+pub static COMMIT_MSG_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_consensus_commit_msg_channel_msgs_count",
+        "Counters(queued,dequeued,dropped) related to the commit messages channel",
+        &["state"]
+    )
+    .unwrap()
+});
+
 //////////////////////
 // PROPOSAL ELECTION
 //////////////////////
@@ -0,0 +1,595 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters,
+    network::NetworkSender,
+    persistent_liveness_storage::PersistentLivenessStorage,
+    state_replication::{StateComputer, TxnManager},
+};
+use anyhow::{ensure, format_err, Result};
+use channel::{libra_channel, message_queues::QueueStyle};
+use consensus_types::{
+    block::Block,
+    commit_decision::CommitDecision,
+    commit_vote::CommitVote,
+    common::{Author, Payload, Round},
+};
+use futures::{
+    channel::{mpsc, oneshot},
+    select, StreamExt,
+};
+use libra_crypto::{hash::CryptoHash, HashValue};
+use libra_logger::prelude::*;
+use libra_types::{
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_verifier::ValidatorVerifier,
+};
+use safety_rules::TSafetyRules;
+use std::{
+    cmp::max,
+    collections::{BTreeMap, HashMap, VecDeque},
+    num::NonZeroUsize,
+    sync::Arc,
+};
+
+#[cfg(test)]
+#[path = "execution_pipeline_test.rs"]
+mod execution_pipeline_test;
+
+/// The number of commit messages queued per author, the oldest ones are dropped first.
+const COMMIT_MSG_QUEUE_SIZE_PER_AUTHOR: usize = 32;
+/// Commit votes further ahead of the latest ordered or committed round are dropped: honest
+/// validators can't execute blocks that are not ordered yet.
+const MAX_COMMIT_VOTE_ROUNDS_AHEAD: Round = 100;
+/// The number of commit votes of an author waiting for a quorum.
+const MAX_PENDING_VOTES_PER_AUTHOR: usize = 100;
+
+/// The inputs of the execution pipeline from the ordering of this validator, processed in order.
+pub enum PipelineInput<T> {
+    /// Blocks committed by the ordering, in order.
+    Ordered(OrderedBlocks<T>),
+    /// The ordering is syncing up to the given ordered commit, the callback is answered once
+    /// storage is at the execution result of the same block.
+    SyncTo(LedgerInfoWithSignatures, oneshot::Sender<Result<()>>),
+}
+
+/// The inputs of the execution pipeline received from the network.
+pub enum CommitMsg {
+    /// A commit vote from a validator (including self).
+    Vote(CommitVote),
+    /// A commit decision sent by a validator for a lagging pipeline.
+    Decision(CommitDecision),
+}
+
+pub type PipelineSender<T> = mpsc::UnboundedSender<PipelineInput<T>>;
+pub type PipelineReceiver<T> = mpsc::UnboundedReceiver<PipelineInput<T>>;
+pub type CommitMsgSender = libra_channel::Sender<Author, CommitMsg>;
+pub type CommitMsgReceiver = libra_channel::Receiver<Author, CommitMsg>;
+
+pub fn pipeline_channel<T>() -> (PipelineSender<T>, PipelineReceiver<T>) {
+    mpsc::unbounded()
+}
+
+/// The commit messages are queued per sending peer, so that a peer flooding the pipeline can
+/// neither exhaust the memory nor delay the messages of the others.
+pub fn commit_msg_channel() -> (CommitMsgSender, CommitMsgReceiver) {
+    libra_channel::new(
+        QueueStyle::KLAST,
+        NonZeroUsize::new(COMMIT_MSG_QUEUE_SIZE_PER_AUTHOR).unwrap(),
+        Some(&counters::COMMIT_MSG_CHANNEL_MSGS),
+    )
+}
+
+/// A batch of blocks committed by the ordering.
+pub struct OrderedBlocks<T> {
+    pub blocks: Vec<Block<T>>,
+    /// The ordering commit proof of the last block
+    pub ordered_proof: LedgerInfoWithSignatures,
+}
+
+/// Collects the commit votes on the same LedgerInfo until they gather a quorum.
+#[derive(Default)]
+pub struct CommitVoteAggregator {
+    /// LedgerInfo hash -> the LedgerInfo with the signatures collected so far
+    votes: HashMap<HashValue, LedgerInfoWithSignatures>,
+}
+
+impl CommitVoteAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a verified commit vote, returns the LedgerInfo with its signatures the first time
+    /// they reach the quorum voting power.
+    pub fn insert_vote(
+        &mut self,
+        vote: &CommitVote,
+        verifier: &ValidatorVerifier,
+    ) -> Option<LedgerInfoWithSignatures> {
+        let li_hash = vote.ledger_info().hash();
+        let duplicate = self
+            .votes
+            .get(&li_hash)
+            .map_or(false, |li| li.signatures().contains_key(&vote.author()));
+        if !duplicate {
+            self.make_room_for(vote.author());
+        }
+        let li_with_sig = self.votes.entry(li_hash).or_insert_with(|| {
+            LedgerInfoWithSignatures::new(vote.ledger_info().clone(), BTreeMap::new())
+        });
+        li_with_sig.add_signature(vote.author(), vote.signature().clone());
+        if verifier
+            .check_voting_power(li_with_sig.signatures().keys())
+            .is_ok()
+        {
+            self.votes.remove(&li_hash)
+        } else {
+            None
+        }
+    }
+
+    /// Keeps at most `MAX_PENDING_VOTES_PER_AUTHOR` votes of the author by dropping its oldest
+    /// one: a quorum on a later round commits the earlier blocks too.
+    fn make_room_for(&mut self, author: Author) {
+        let pending: Vec<_> = self
+            .votes
+            .iter()
+            .filter(|(_, li)| li.signatures().contains_key(&author))
+            .map(|(li_hash, li)| (li.ledger_info().round(), *li_hash))
+            .collect();
+        if pending.len() < MAX_PENDING_VOTES_PER_AUTHOR {
+            return;
+        }
+        if let Some((_, oldest)) = pending.into_iter().min() {
+            if let Some(li) = self.votes.get_mut(&oldest) {
+                li.remove_signature(author);
+                if li.signatures().is_empty() {
+                    self.votes.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    /// Drops the votes up to the committed round.
+    pub fn prune(&mut self, committed_round: Round) {
+        self.votes
+            .retain(|_, li| li.ledger_info().round() > committed_round);
+    }
+}
+
+/// An executed block waiting for its commit decision.
+struct ExecutedBlock {
+    block_id: HashValue,
+    /// The LedgerInfo committing the execution result up to this block
+    ledger_info: LedgerInfo,
+}
+
+/// Executes the blocks ordered by consensus, agrees with the other validators on the execution
+/// result through commit votes and commits it once a quorum is reached.
+///
+/// Validators vote on the execution result of every ordered block: the LedgerInfos they vote on
+/// only depend on the blocks and their execution results, not on how the ordering of each
+/// validator happened to batch its commits. A validator lagging behind gets the latest commit
+/// decision from each of the others instead of voting on the blocks decided already.
+/// A pipeline that can't execute the ordered blocks (e.g. after the ordering synced up from
+/// peers) stalls and catches up through state sync once a commit decision is known.
+///
+/// The ordered blocks stay in ConsensusDB until storage commits a later block, for a restart to
+/// execute them again.
+pub struct ExecutionPipeline<T> {
+    author: Author,
+    epoch_state: EpochState,
+    execution_proxy: Arc<dyn StateComputer<Payload = T>>,
+    storage: Arc<dyn PersistentLivenessStorage<T>>,
+    safety_rules: Box<dyn TSafetyRules<T> + Send + Sync>,
+    network: NetworkSender<T>,
+    txn_manager: Box<dyn TxnManager<Payload = T>>,
+    aggregator: CommitVoteAggregator,
+    ordered: VecDeque<Block<T>>,
+    executed: VecDeque<ExecutedBlock>,
+    /// The (round, id) of the ordered blocks that are still in ConsensusDB
+    persisted: VecDeque<(Round, HashValue)>,
+    /// Commit decisions on blocks that are not executed yet, keyed by block id
+    decisions: HashMap<HashValue, LedgerInfoWithSignatures>,
+    last_decision: Option<LedgerInfoWithSignatures>,
+    /// The round of the last decision sent to each lagging author
+    decisions_sent: HashMap<Author, Round>,
+    last_ordered_round: Round,
+    last_executed_round: Round,
+    last_committed_round: Round,
+    /// Set once the block ending the epoch is executed, the following blocks are dropped
+    reconfiguration: bool,
+    stalled: bool,
+    pending_sync: Option<(LedgerInfoWithSignatures, oneshot::Sender<Result<()>>)>,
+}
+
+impl<T: Payload> ExecutionPipeline<T> {
+    pub fn new(
+        author: Author,
+        epoch_state: EpochState,
+        execution_proxy: Arc<dyn StateComputer<Payload = T>>,
+        storage: Arc<dyn PersistentLivenessStorage<T>>,
+        safety_rules: Box<dyn TSafetyRules<T> + Send + Sync>,
+        network: NetworkSender<T>,
+        txn_manager: Box<dyn TxnManager<Payload = T>>,
+    ) -> Self {
+        Self {
+            author,
+            epoch_state,
+            execution_proxy,
+            storage,
+            safety_rules,
+            network,
+            txn_manager,
+            aggregator: CommitVoteAggregator::new(),
+            ordered: VecDeque::new(),
+            executed: VecDeque::new(),
+            persisted: VecDeque::new(),
+            decisions: HashMap::new(),
+            last_decision: None,
+            decisions_sent: HashMap::new(),
+            last_ordered_round: 0,
+            last_executed_round: 0,
+            last_committed_round: 0,
+            reconfiguration: false,
+            stalled: false,
+            pending_sync: None,
+        }
+    }
+
+    /// Processes the inputs until the ordering drops its senders, i.e. the epoch ends.
+    pub async fn start(
+        mut self,
+        mut input: PipelineReceiver<T>,
+        mut commit_msgs: CommitMsgReceiver,
+    ) {
+        info!(
+            "[ExecutionPipeline] Started for epoch {}",
+            self.epoch_state.epoch
+        );
+        loop {
+            let result = select! {
+                msg = input.next() => match msg {
+                    Some(PipelineInput::Ordered(ordered)) => {
+                        self.process_ordered_blocks(ordered).await
+                    }
+                    Some(PipelineInput::SyncTo(target, callback)) => {
+                        self.process_sync_request(target, callback).await
+                    }
+                    None => break,
+                },
+                msg = commit_msgs.select_next_some() => match msg {
+                    CommitMsg::Vote(vote) => self.process_commit_vote(vote).await,
+                    CommitMsg::Decision(decision) => self.process_commit_decision(decision).await,
+                },
+            };
+            if let Err(e) = result {
+                error!("[ExecutionPipeline] {:?}", e);
+            }
+        }
+        info!(
+            "[ExecutionPipeline] Stopped for epoch {}",
+            self.epoch_state.epoch
+        );
+    }
+
+    async fn process_ordered_blocks(&mut self, ordered: OrderedBlocks<T>) -> Result<()> {
+        debug!(
+            "[ExecutionPipeline] Received ordered blocks up to {}",
+            ordered.ordered_proof
+        );
+        self.last_ordered_round = max(
+            self.last_ordered_round,
+            ordered.ordered_proof.ledger_info().round(),
+        );
+        for block in &ordered.blocks {
+            // The parent of the first block is the previous root of the ordering.
+            let parent = block.quorum_cert().certified_block();
+            if self.persisted.back().map(|(_, id)| *id) != Some(parent.id()) {
+                self.persisted.push_back((parent.round(), parent.id()));
+            }
+            self.persisted.push_back((block.round(), block.id()));
+        }
+        if self.reconfiguration {
+            return Ok(());
+        }
+        self.ordered.extend(ordered.blocks);
+        self.execute_ordered().await
+    }
+
+    /// Executes the ordered blocks unless the pipeline is stalled, votes on their execution
+    /// results and commits the blocks whose decision is already known.
+    async fn execute_ordered(&mut self) -> Result<()> {
+        while !self.stalled && !self.reconfiguration {
+            let block = match self.ordered.pop_front() {
+                Some(block) => block,
+                None => break,
+            };
+            if block.round() <= self.last_executed_round {
+                continue;
+            }
+            let compute_result = match self.execution_proxy.compute(&block, block.parent_id()) {
+                Ok(compute_result) => compute_result,
+                Err(e) => {
+                    self.stall();
+                    return Err(e.context(format!("Failed to execute block {}", block)));
+                }
+            };
+            // notify mempool about failed txn
+            if let Some(payload) = block.payload() {
+                if let Err(e) = self.txn_manager.commit_txns(payload, &compute_result).await {
+                    error!(
+                        "[ExecutionPipeline] Failed to notify mempool of rejected txns: {:?}",
+                        e
+                    );
+                }
+            }
+            let ledger_info = LedgerInfo::new(
+                block.gen_block_info(
+                    compute_result.root_hash(),
+                    compute_result.version(),
+                    compute_result.epoch_state().clone(),
+                ),
+                HashValue::zero(),
+            );
+            self.last_executed_round = block.round();
+            self.executed.push_back(ExecutedBlock {
+                block_id: block.id(),
+                ledger_info: ledger_info.clone(),
+            });
+            // Blocks following a reconfiguration in the same epoch are not executed.
+            if compute_result.has_reconfiguration() {
+                self.reconfiguration = true;
+                self.ordered.clear();
+            }
+            // A lagging pipeline catching up on known decisions doesn't need to vote, the other
+            // validators reached a quorum on this block already.
+            let decided = self
+                .decisions
+                .values()
+                .any(|d| d.ledger_info().round() >= block.round());
+            // The other validators may still reach a quorum without our vote.
+            if !decided {
+                if let Err(e) = self.vote(ledger_info).await {
+                    warn!(
+                        "[ExecutionPipeline] Failed to vote on block {}: {:?}",
+                        block, e
+                    );
+                }
+            }
+            if let Some(decision) = self.decisions.remove(&block.id()) {
+                self.commit_executed(decision).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn vote(&mut self, ledger_info: LedgerInfo) -> Result<()> {
+        let signature = self.safety_rules.sign_commit_vote(&ledger_info)?;
+        let vote = CommitVote::new_with_signature(self.author, ledger_info, signature);
+        debug!("[ExecutionPipeline] Broadcast {}", vote);
+        self.network.broadcast_commit_vote(vote).await;
+        Ok(())
+    }
+
+    async fn process_commit_vote(&mut self, vote: CommitVote) -> Result<()> {
+        ensure!(
+            vote.epoch() == self.epoch_state.epoch,
+            "[ExecutionPipeline] {} is not in epoch {}",
+            vote,
+            self.epoch_state.epoch
+        );
+        let max_round = max(self.last_ordered_round, self.last_committed_round)
+            .saturating_add(MAX_COMMIT_VOTE_ROUNDS_AHEAD);
+        ensure!(
+            vote.round() <= max_round,
+            "[ExecutionPipeline] {} is too far ahead of round {}",
+            vote,
+            max_round
+        );
+        vote.verify(&self.epoch_state.verifier)?;
+        if vote.round() <= self.last_committed_round {
+            self.send_decision_to_lagging(vote.author(), vote.round());
+            return Ok(());
+        }
+        match self
+            .aggregator
+            .insert_vote(&vote, &self.epoch_state.verifier)
+        {
+            Some(decision) => self.process_decision(decision).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Sends our latest decision to an author still voting on a block committed before it, at
+    /// most once per decision. An author voting on the latest committed block gets its quorum
+    /// from the votes of the others.
+    fn send_decision_to_lagging(&mut self, author: Author, vote_round: Round) {
+        let decision = match &self.last_decision {
+            Some(decision) => decision,
+            None => return,
+        };
+        let decision_round = decision.ledger_info().round();
+        if author == self.author || vote_round >= decision_round {
+            return;
+        }
+        let sent_round = self.decisions_sent.entry(author).or_insert(0);
+        if *sent_round >= decision_round {
+            return;
+        }
+        *sent_round = decision_round;
+        self.network
+            .send_commit_decision(CommitDecision::new(decision.clone()), author);
+    }
+
+    async fn process_commit_decision(&mut self, decision: CommitDecision) -> Result<()> {
+        ensure!(
+            decision.epoch() == self.epoch_state.epoch,
+            "[ExecutionPipeline] {} is not in epoch {}",
+            decision,
+            self.epoch_state.epoch
+        );
+        decision.verify(&self.epoch_state.verifier)?;
+        self.process_decision(decision.ledger_info().clone()).await
+    }
+
+    async fn process_decision(&mut self, decision: LedgerInfoWithSignatures) -> Result<()> {
+        if decision.ledger_info().round() <= self.last_committed_round {
+            return Ok(());
+        }
+        let block_id = decision.ledger_info().consensus_block_id();
+        if self.executed.iter().any(|b| b.block_id == block_id) {
+            return self.commit_executed(decision).await;
+        }
+        let sync_requested = self.pending_sync.as_ref().map_or(false, |(target, _)| {
+            target.ledger_info().consensus_block_id() == block_id
+        });
+        // A pipeline stalled by an execution failure syncs to any decision, as the ordering
+        // went past it already.
+        if sync_requested || (self.stalled && self.pending_sync.is_none()) {
+            self.sync_to_decision(decision).await?;
+            return self.execute_ordered().await;
+        }
+        self.decisions.insert(block_id, decision);
+        Ok(())
+    }
+
+    /// Commits the executed blocks up to the block of the decision.
+    async fn commit_executed(&mut self, decision: LedgerInfoWithSignatures) -> Result<()> {
+        let block_id = decision.ledger_info().consensus_block_id();
+        let position = self
+            .executed
+            .iter()
+            .position(|b| b.block_id == block_id)
+            .ok_or_else(|| format_err!("Block {} is not executed", block_id))?;
+        if self.executed[position].ledger_info != *decision.ledger_info() {
+            error!(
+                "[ExecutionPipeline] Execution result {} differs from the commit decision {}, syncing to the decision",
+                self.executed[position].ledger_info,
+                decision
+            );
+            self.stall();
+            return self.sync_to_decision(decision).await;
+        }
+        let block_ids = self
+            .executed
+            .drain(..=position)
+            .map(|b| b.block_id)
+            .collect();
+        self.execution_proxy
+            .commit(block_ids, decision.clone())
+            .await?;
+        self.on_committed(decision).await;
+        Ok(())
+    }
+
+    /// Syncs storage to the decision instead of executing the blocks.
+    async fn sync_to_decision(&mut self, decision: LedgerInfoWithSignatures) -> Result<()> {
+        let round = decision.ledger_info().round();
+        self.execution_proxy.sync_to(decision.clone()).await?;
+        self.executed.clear();
+        self.ordered.retain(|block| block.round() > round);
+        self.last_executed_round = round;
+        self.stalled = false;
+        self.reconfiguration = decision.ledger_info().next_epoch_state().is_some();
+        self.on_committed(decision).await;
+        Ok(())
+    }
+
+    async fn on_committed(&mut self, decision: LedgerInfoWithSignatures) {
+        let round = decision.ledger_info().round();
+        debug!("[ExecutionPipeline] Committed {}", decision);
+        self.last_committed_round = round;
+        self.aggregator.prune(round);
+        self.decisions
+            .retain(|_, d| d.ledger_info().round() > round);
+        self.prune_persisted(round);
+        if let Some((target, callback)) = self.pending_sync.take() {
+            if target.ledger_info().round() > round {
+                self.pending_sync = Some((target, callback));
+            } else {
+                let result = if target.ledger_info().consensus_block_id()
+                    == decision.ledger_info().consensus_block_id()
+                {
+                    Ok(())
+                } else {
+                    Err(format_err!("Committed {} instead of {}", decision, target))
+                };
+                let _ = callback.send(result);
+            }
+        }
+        self.last_decision = Some(decision.clone());
+        if decision.ledger_info().next_epoch_state().is_some() {
+            self.network
+                .broadcast_epoch_change(EpochChangeProof::new(
+                    vec![decision],
+                    /* more = */ false,
+                ))
+                .await;
+        }
+    }
+
+    async fn process_sync_request(
+        &mut self,
+        target: LedgerInfoWithSignatures,
+        callback: oneshot::Sender<Result<()>>,
+    ) -> Result<()> {
+        let block_id = target.ledger_info().consensus_block_id();
+        if target.ledger_info().round() <= self.last_committed_round {
+            let result = match &self.last_decision {
+                Some(decision) if decision.ledger_info().consensus_block_id() == block_id => Ok(()),
+                _ => Err(format_err!("Storage already committed past {}", target)),
+            };
+            let _ = callback.send(result);
+            return Ok(());
+        }
+        debug!("[ExecutionPipeline] Sync requested to {}", target);
+        // The ordering moves to a new root, the blocks it ordered so far are not needed anymore.
+        self.stall();
+        if let Some((previous, previous_callback)) = self.pending_sync.take() {
+            let _ = previous_callback.send(Err(format_err!(
+                "Sync to {} superseded by {}",
+                previous,
+                target
+            )));
+        }
+        let round = target.ledger_info().round();
+        self.last_ordered_round = max(self.last_ordered_round, round);
+        self.decisions
+            .retain(|_, d| d.ledger_info().round() >= round);
+        self.pending_sync = Some((target, callback));
+        if let Some(decision) = self.decisions.remove(&block_id) {
+            self.sync_to_decision(decision).await?;
+        }
+        Ok(())
+    }
+
+    /// Removes the ordered blocks older than the committed round from ConsensusDB, a restart
+    /// recovers from the committed block.
+    fn prune_persisted(&mut self, committed_round: Round) {
+        let mut to_remove = vec![];
+        while let Some(&(round, id)) = self.persisted.front() {
+            if round >= committed_round {
+                break;
+            }
+            to_remove.push(id);
+            self.persisted.pop_front();
+        }
+        if to_remove.is_empty() {
+            return;
+        }
+        if let Err(e) = self.storage.prune_tree(to_remove) {
+            // it's fine to fail here, the next restart will clean up dangling blocks.
+            error!("[ExecutionPipeline] Failed to delete blocks: {:?}", e);
+        }
+    }
+
+    fn stall(&mut self) {
+        self.stalled = true;
+        self.ordered.clear();
+        self.executed.clear();
+    }
+}
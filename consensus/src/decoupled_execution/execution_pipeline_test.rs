@@ -0,0 +1,566 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    decoupled_execution::execution_pipeline::{
+        commit_msg_channel, CommitMsg, CommitVoteAggregator, ExecutionPipeline, OrderedBlocks,
+        COMMIT_MSG_QUEUE_SIZE_PER_AUTHOR, MAX_COMMIT_VOTE_ROUNDS_AHEAD,
+        MAX_PENDING_VOTES_PER_AUTHOR,
+    },
+    network::NetworkSender,
+    network_interface::{ConsensusMsg, ConsensusNetworkSender},
+    state_replication::StateComputer,
+    test_utils::{EmptyStorage, MockTransactionManager, TestPayload},
+};
+use anyhow::{bail, Result};
+use channel::{self, libra_channel, message_queues::QueueStyle};
+use consensus_types::{
+    block::{
+        block_test_utils::{certificate_for_genesis, gen_test_certificate},
+        Block,
+    },
+    commit_decision::CommitDecision,
+    commit_vote::CommitVote,
+    common::{Author, Round},
+};
+use executor_types::StateComputeResult;
+use futures::{channel::oneshot, executor::block_on, FutureExt, StreamExt};
+use libra_crypto::{hash::CryptoHash, HashValue};
+use libra_types::{
+    block_info::{BlockInfo, ORDERED_ONLY_VERSION},
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+    validator_verifier::random_validator_verifier,
+    waypoint::Waypoint,
+    PeerId,
+};
+use network::{
+    peer_manager::{ConnectionRequestSender, PeerManagerRequest, PeerManagerRequestSender},
+    ProtocolId,
+};
+use safety_rules::{PersistentSafetyStorage, SafetyRulesManager};
+use std::{
+    collections::{BTreeMap, HashSet},
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+fn executed_ledger_info(round: Round) -> LedgerInfo {
+    LedgerInfo::new(
+        BlockInfo::new(
+            1,
+            round,
+            HashValue::random(),
+            HashValue::random(),
+            round,
+            0,
+            None,
+        ),
+        HashValue::zero(),
+    )
+}
+
+fn commit_vote(signer: &ValidatorSigner, ledger_info: &LedgerInfo) -> CommitVote {
+    CommitVote::new_with_signature(
+        signer.author(),
+        ledger_info.clone(),
+        signer.sign_message(ledger_info.hash()),
+    )
+}
+
+#[test]
+fn test_commit_vote_aggregation() {
+    let (signers, validator) = random_validator_verifier(4, None, false);
+    let mut aggregator = CommitVoteAggregator::new();
+    let li = executed_ledger_info(1);
+    let other_li = executed_ledger_info(1);
+
+    assert!(aggregator
+        .insert_vote(&commit_vote(&signers[0], &li), &validator)
+        .is_none());
+    // duplicate votes don't count twice
+    assert!(aggregator
+        .insert_vote(&commit_vote(&signers[0], &li), &validator)
+        .is_none());
+    // votes on a different execution result are aggregated separately
+    assert!(aggregator
+        .insert_vote(&commit_vote(&signers[1], &other_li), &validator)
+        .is_none());
+    assert!(aggregator
+        .insert_vote(&commit_vote(&signers[2], &li), &validator)
+        .is_none());
+
+    let decision = aggregator
+        .insert_vote(&commit_vote(&signers[3], &li), &validator)
+        .expect("quorum of commit votes");
+    assert_eq!(decision.ledger_info(), &li);
+    assert_eq!(decision.signatures().len(), 3);
+    assert!(decision.verify_signatures(&validator).is_ok());
+}
+
+#[test]
+fn test_commit_vote_pruning() {
+    let (signers, validator) = random_validator_verifier(4, None, false);
+    let mut aggregator = CommitVoteAggregator::new();
+    let lis: Vec<_> = (1..=3).map(executed_ledger_info).collect();
+    for li in &lis {
+        for signer in &signers[..2] {
+            assert!(aggregator
+                .insert_vote(&commit_vote(signer, li), &validator)
+                .is_none());
+        }
+    }
+    aggregator.prune(2);
+    // the votes up to the committed round are dropped
+    for li in &lis[..2] {
+        assert!(aggregator
+            .insert_vote(&commit_vote(&signers[2], li), &validator)
+            .is_none());
+    }
+    assert!(aggregator
+        .insert_vote(&commit_vote(&signers[2], &lis[2]), &validator)
+        .is_some());
+}
+
+#[test]
+fn test_commit_vote_limit_per_author() {
+    let (signers, validator) = random_validator_verifier(4, None, false);
+    let mut aggregator = CommitVoteAggregator::new();
+    let lis: Vec<_> = (1..=MAX_PENDING_VOTES_PER_AUTHOR as Round + 1)
+        .map(executed_ledger_info)
+        .collect();
+    for li in &lis {
+        assert!(aggregator
+            .insert_vote(&commit_vote(&signers[0], li), &validator)
+            .is_none());
+    }
+    // the oldest vote made room for the latest one
+    for signer in &signers[1..3] {
+        assert!(aggregator
+            .insert_vote(&commit_vote(signer, &lis[0]), &validator)
+            .is_none());
+    }
+    assert!(aggregator
+        .insert_vote(&commit_vote(&signers[1], lis.last().unwrap()), &validator)
+        .is_none());
+    assert!(aggregator
+        .insert_vote(&commit_vote(&signers[2], lis.last().unwrap()), &validator)
+        .is_some());
+}
+
+#[test]
+fn test_commit_msg_channel_per_author() {
+    let (signers, _) = random_validator_verifier(2, None, false);
+    let (mut tx, mut rx) = commit_msg_channel();
+    let flooding = signers[0].author();
+    for round in 1..=1000 {
+        let vote = commit_vote(&signers[0], &executed_ledger_info(round));
+        tx.push(flooding, CommitMsg::Vote(vote)).unwrap();
+    }
+    let vote = commit_vote(&signers[1], &executed_ledger_info(1));
+    tx.push(signers[1].author(), CommitMsg::Vote(vote)).unwrap();
+
+    let mut received = vec![];
+    while let Some(Some(CommitMsg::Vote(vote))) = rx.next().now_or_never() {
+        received.push(vote);
+    }
+    // only the latest messages of the flooding author are kept, and they don't delay the others
+    assert_eq!(received.len(), COMMIT_MSG_QUEUE_SIZE_PER_AUTHOR + 1);
+    assert!(received[..2]
+        .iter()
+        .any(|vote| vote.author() == signers[1].author()));
+    assert!(received
+        .iter()
+        .filter(|vote| vote.author() == flooding)
+        .all(|vote| vote.round() > 1000 - COMMIT_MSG_QUEUE_SIZE_PER_AUTHOR as Round));
+}
+
+/// Executes the blocks deterministically by round, except for the rounds set to fail, and
+/// records the commits and syncs of the pipeline.
+#[derive(Default)]
+struct TestExecutionProxy {
+    failures: Mutex<HashSet<Round>>,
+    committed: Mutex<Vec<(Vec<HashValue>, LedgerInfoWithSignatures)>>,
+    synced: Mutex<Vec<LedgerInfoWithSignatures>>,
+}
+
+fn compute_result(round: Round) -> StateComputeResult {
+    StateComputeResult::new(
+        HashValue::sha3_256_of(&round.to_le_bytes()),
+        vec![], /* frozen_subtree_roots */
+        round + 1,
+        None,   /* epoch_state */
+        vec![], /* compute_status */
+        vec![], /* transaction_info_hashes */
+    )
+}
+
+#[async_trait::async_trait]
+impl StateComputer for TestExecutionProxy {
+    type Payload = TestPayload;
+
+    fn compute(
+        &self,
+        block: &Block<Self::Payload>,
+        _parent_block_id: HashValue,
+    ) -> Result<StateComputeResult> {
+        if self.failures.lock().unwrap().contains(&block.round()) {
+            bail!("Failed to execute round {}", block.round());
+        }
+        Ok(compute_result(block.round()))
+    }
+
+    async fn commit(
+        &self,
+        block_ids: Vec<HashValue>,
+        finality_proof: LedgerInfoWithSignatures,
+    ) -> Result<()> {
+        self.committed
+            .lock()
+            .unwrap()
+            .push((block_ids, finality_proof));
+        Ok(())
+    }
+
+    async fn sync_to(&self, target: LedgerInfoWithSignatures) -> Result<()> {
+        self.synced.lock().unwrap().push(target);
+        Ok(())
+    }
+}
+
+struct PipelineSetup {
+    pipeline: ExecutionPipeline<TestPayload>,
+    execution_proxy: Arc<TestExecutionProxy>,
+    signers: Vec<ValidatorSigner>,
+    network_reqs_rx: libra_channel::Receiver<(PeerId, ProtocolId), PeerManagerRequest>,
+}
+
+impl PipelineSetup {
+    /// The pipeline of the first of 4 validators. Its SafetyRules didn't vote on the ordered
+    /// blocks, so the pipeline can't sign commit votes and commits through the decisions of the
+    /// others.
+    fn new() -> Self {
+        let (signers, verifier) = random_validator_verifier(4, None, false);
+        let author = signers[0].author();
+        let epoch_state = EpochState {
+            epoch: 1,
+            verifier: verifier.clone(),
+        };
+        let execution_proxy = Arc::new(TestExecutionProxy::default());
+
+        let waypoint =
+            Waypoint::new_epoch_boundary(&LedgerInfo::mock_genesis(Some((&verifier).into())))
+                .unwrap();
+        let safety_storage = PersistentSafetyStorage::initialize(
+            Box::new(libra_secure_storage::InMemoryStorage::new()),
+            signers[0].private_key().clone(),
+            waypoint,
+        );
+        let safety_rules_manager = SafetyRulesManager::new_local(author, safety_storage);
+
+        let (network_reqs_tx, network_reqs_rx) =
+            libra_channel::new(QueueStyle::FIFO, NonZeroUsize::new(8).unwrap(), None);
+        let (connection_reqs_tx, _) =
+            libra_channel::new(QueueStyle::FIFO, NonZeroUsize::new(8).unwrap(), None);
+        let (conn_mgr_reqs_tx, _) = channel::new_test(8);
+        let network_sender = ConsensusNetworkSender::new(
+            PeerManagerRequestSender::new(network_reqs_tx),
+            ConnectionRequestSender::new(connection_reqs_tx),
+            conn_mgr_reqs_tx,
+        );
+        let (self_sender, _) = channel::new_test(8);
+        let network = NetworkSender::new(author, network_sender, self_sender, verifier);
+
+        let pipeline = ExecutionPipeline::new(
+            author,
+            epoch_state,
+            execution_proxy.clone(),
+            Arc::new(EmptyStorage::<TestPayload>::new()),
+            safety_rules_manager.client(),
+            network,
+            Box::new(MockTransactionManager::new(None)),
+        );
+        Self {
+            pipeline,
+            execution_proxy,
+            signers,
+            network_reqs_rx,
+        }
+    }
+
+    /// The commit decision of the other validators on the execution result of the block.
+    fn commit_decision(&self, block: &Block<TestPayload>) -> CommitDecision {
+        let ledger_info = executed_ledger_info_of(block);
+        let signatures = self.signers[1..]
+            .iter()
+            .map(|signer| (signer.author(), signer.sign_message(ledger_info.hash())))
+            .collect();
+        CommitDecision::new(LedgerInfoWithSignatures::new(ledger_info, signatures))
+    }
+
+    fn committed(&self) -> Vec<(Vec<HashValue>, LedgerInfoWithSignatures)> {
+        self.execution_proxy.committed.lock().unwrap().clone()
+    }
+
+    fn synced(&self) -> Vec<LedgerInfoWithSignatures> {
+        self.execution_proxy.synced.lock().unwrap().clone()
+    }
+
+    /// Takes the commit decisions sent so far, with their recipient.
+    fn sent_decisions(&mut self) -> Vec<(Author, CommitDecision)> {
+        let mut decisions = vec![];
+        while let Some(Some(request)) = self.network_reqs_rx.next().now_or_never() {
+            if let PeerManagerRequest::SendMessage(peer, msg) = request {
+                let msg: ConsensusMsg<TestPayload> = lcs::from_bytes(&msg.mdata).unwrap();
+                if let ConsensusMsg::CommitDecisionMsg(decision) = msg {
+                    decisions.push((peer, *decision));
+                }
+            }
+        }
+        decisions
+    }
+}
+
+/// A chain of blocks for rounds 1 to num_blocks, each certified by the ordering.
+fn ordered_chain(signer: &ValidatorSigner, num_blocks: Round) -> Vec<Block<TestPayload>> {
+    let mut blocks: Vec<Block<TestPayload>> = vec![];
+    for round in 1..=num_blocks {
+        let quorum_cert = match blocks.last() {
+            Some(parent) => gen_test_certificate(
+                vec![signer],
+                ordered_info_of(parent),
+                parent.quorum_cert().certified_block().clone(),
+                None,
+            ),
+            None => certificate_for_genesis(),
+        };
+        blocks.push(Block::new_proposal(
+            vec![round as usize],
+            round,
+            round,
+            quorum_cert,
+            signer,
+            vec![],
+        ));
+    }
+    blocks
+}
+
+fn ordered_info_of(block: &Block<TestPayload>) -> BlockInfo {
+    block.gen_block_info(
+        BlockInfo::ordered_only_state_id(),
+        ORDERED_ONLY_VERSION,
+        None,
+    )
+}
+
+/// The ordering commit of the block, as handed over to the pipeline.
+fn ordered_proof_of(block: &Block<TestPayload>) -> LedgerInfoWithSignatures {
+    LedgerInfoWithSignatures::new(
+        LedgerInfo::new(ordered_info_of(block), HashValue::zero()),
+        BTreeMap::new(),
+    )
+}
+
+fn ordered_blocks(blocks: &[Block<TestPayload>]) -> OrderedBlocks<TestPayload> {
+    OrderedBlocks {
+        blocks: blocks.to_vec(),
+        ordered_proof: ordered_proof_of(blocks.last().unwrap()),
+    }
+}
+
+/// The LedgerInfo the pipeline votes on after executing the block.
+fn executed_ledger_info_of(block: &Block<TestPayload>) -> LedgerInfo {
+    let compute_result = compute_result(block.round());
+    LedgerInfo::new(
+        block.gen_block_info(compute_result.root_hash(), compute_result.version(), None),
+        HashValue::zero(),
+    )
+}
+
+#[test]
+fn test_commit_decision_before_execution() {
+    let mut setup = PipelineSetup::new();
+    let blocks = ordered_chain(&setup.signers[0], 3);
+
+    // the decision on block 2 arrives before the ordering hands the blocks over
+    let decision = setup.commit_decision(&blocks[1]);
+    block_on(setup.pipeline.process_commit_decision(decision)).unwrap();
+    assert!(setup.committed().is_empty());
+
+    block_on(
+        setup
+            .pipeline
+            .process_ordered_blocks(ordered_blocks(&blocks)),
+    )
+    .unwrap();
+    // blocks 1 and 2 are committed once block 2 is executed, block 3 waits for its decision
+    let committed = setup.committed();
+    assert_eq!(committed.len(), 1);
+    assert_eq!(committed[0].0, vec![blocks[0].id(), blocks[1].id()]);
+    assert_eq!(
+        committed[0].1.ledger_info(),
+        &executed_ledger_info_of(&blocks[1])
+    );
+    assert_eq!(setup.pipeline.last_committed_round, 2);
+    assert_eq!(setup.pipeline.last_executed_round, 3);
+    assert!(setup.pipeline.decisions.is_empty());
+
+    // a decision older than the committed round is ignored
+    let decision = setup.commit_decision(&blocks[0]);
+    block_on(setup.pipeline.process_commit_decision(decision)).unwrap();
+    assert_eq!(setup.committed().len(), 1);
+
+    let decision = setup.commit_decision(&blocks[2]);
+    block_on(setup.pipeline.process_commit_decision(decision)).unwrap();
+    let committed = setup.committed();
+    assert_eq!(committed.len(), 2);
+    assert_eq!(committed[1].0, vec![blocks[2].id()]);
+    assert!(setup.synced().is_empty());
+}
+
+#[test]
+fn test_stalled_execution_syncs_to_decision() {
+    let mut setup = PipelineSetup::new();
+    let blocks = ordered_chain(&setup.signers[0], 5);
+    setup.execution_proxy.failures.lock().unwrap().insert(2);
+
+    assert!(block_on(
+        setup
+            .pipeline
+            .process_ordered_blocks(ordered_blocks(&blocks[..3]))
+    )
+    .is_err());
+    assert!(setup.pipeline.stalled);
+    assert_eq!(setup.pipeline.last_executed_round, 1);
+
+    // the stalled pipeline keeps the following blocks without executing them
+    block_on(
+        setup
+            .pipeline
+            .process_ordered_blocks(ordered_blocks(&blocks[3..4])),
+    )
+    .unwrap();
+    assert_eq!(setup.pipeline.last_executed_round, 1);
+
+    // the decision of the others on block 3 brings storage past the failed block, and the
+    // execution resumes after it
+    let decision = setup.commit_decision(&blocks[2]);
+    block_on(setup.pipeline.process_commit_decision(decision.clone())).unwrap();
+    assert_eq!(setup.synced(), vec![decision.ledger_info().clone()]);
+    assert!(setup.committed().is_empty());
+    assert!(!setup.pipeline.stalled);
+    assert_eq!(setup.pipeline.last_committed_round, 3);
+    assert_eq!(setup.pipeline.last_executed_round, 4);
+
+    block_on(
+        setup
+            .pipeline
+            .process_ordered_blocks(ordered_blocks(&blocks[4..])),
+    )
+    .unwrap();
+    assert_eq!(setup.pipeline.last_executed_round, 5);
+    let decision = setup.commit_decision(&blocks[4]);
+    block_on(setup.pipeline.process_commit_decision(decision)).unwrap();
+    let committed = setup.committed();
+    assert_eq!(committed.len(), 1);
+    assert_eq!(committed[0].0, vec![blocks[3].id(), blocks[4].id()]);
+}
+
+#[test]
+fn test_sync_to_commit_decision() {
+    let mut setup = PipelineSetup::new();
+    let blocks = ordered_chain(&setup.signers[0], 4);
+
+    // the ordering syncs up to block 2, the pipeline waits for the decision on it
+    let (callback, mut callback_rcv) = oneshot::channel();
+    block_on(
+        setup
+            .pipeline
+            .process_sync_request(ordered_proof_of(&blocks[1]), callback),
+    )
+    .unwrap();
+    assert!(setup.pipeline.stalled);
+    assert!(callback_rcv.try_recv().unwrap().is_none());
+
+    let decision = setup.commit_decision(&blocks[1]);
+    block_on(setup.pipeline.process_commit_decision(decision.clone())).unwrap();
+    assert_eq!(setup.synced(), vec![decision.ledger_info().clone()]);
+    assert!(callback_rcv.try_recv().unwrap().unwrap().is_ok());
+
+    // a decision known before the sync request is synced to right away
+    let decision = setup.commit_decision(&blocks[3]);
+    block_on(setup.pipeline.process_commit_decision(decision.clone())).unwrap();
+    assert_eq!(setup.synced().len(), 1);
+    let (callback, mut callback_rcv) = oneshot::channel();
+    block_on(
+        setup
+            .pipeline
+            .process_sync_request(ordered_proof_of(&blocks[3]), callback),
+    )
+    .unwrap();
+    assert_eq!(setup.synced().len(), 2);
+    assert_eq!(setup.synced()[1], *decision.ledger_info());
+    assert!(callback_rcv.try_recv().unwrap().unwrap().is_ok());
+    assert!(setup.committed().is_empty());
+}
+
+#[test]
+fn test_commit_decision_sent_once_to_lagging_author() {
+    let mut setup = PipelineSetup::new();
+    let blocks = ordered_chain(&setup.signers[0], 3);
+    block_on(
+        setup
+            .pipeline
+            .process_ordered_blocks(ordered_blocks(&blocks)),
+    )
+    .unwrap();
+    let decision = setup.commit_decision(&blocks[2]);
+    block_on(setup.pipeline.process_commit_decision(decision)).unwrap();
+    assert_eq!(setup.pipeline.last_committed_round, 3);
+
+    // a late vote on the latest committed block gets its quorum from the other votes
+    let lagging = setup.signers[3].clone();
+    let vote = commit_vote(&lagging, &executed_ledger_info_of(&blocks[2]));
+    block_on(setup.pipeline.process_commit_vote(vote)).unwrap();
+    assert!(setup.sent_decisions().is_empty());
+
+    // votes on older blocks are answered with the latest decision, once
+    for block in &blocks[..2] {
+        let vote = commit_vote(&lagging, &executed_ledger_info_of(block));
+        block_on(setup.pipeline.process_commit_vote(vote)).unwrap();
+    }
+    let sent = setup.sent_decisions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0, lagging.author());
+    assert_eq!(sent[0].1.round(), 3);
+}
+
+#[test]
+fn test_commit_vote_too_far_ahead() {
+    let mut setup = PipelineSetup::new();
+    let blocks = ordered_chain(&setup.signers[0], 2);
+    block_on(
+        setup
+            .pipeline
+            .process_ordered_blocks(ordered_blocks(&blocks)),
+    )
+    .unwrap();
+
+    // votes are accepted up to the window ahead of the ordered round
+    let voter = setup.signers[1].clone();
+    let vote = commit_vote(
+        &voter,
+        &executed_ledger_info(2 + MAX_COMMIT_VOTE_ROUNDS_AHEAD),
+    );
+    block_on(setup.pipeline.process_commit_vote(vote)).unwrap();
+    assert_eq!(setup.pipeline.aggregator.votes.len(), 1);
+
+    let vote = commit_vote(
+        &voter,
+        &executed_ledger_info(3 + MAX_COMMIT_VOTE_ROUNDS_AHEAD),
+    );
+    assert!(block_on(setup.pipeline.process_commit_vote(vote)).is_err());
+    assert_eq!(setup.pipeline.aggregator.votes.len(), 1);
+}
@@ -0,0 +1,12 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoupled execution: consensus rounds only order blocks, the ordered blocks are then executed
+//! and committed by a separate pipeline stage, which agrees on the execution result through its
+//! own signed commit votes. Execution is thus taken off the critical path of the rounds.
+//!
+//! The ordering stage plugs into the `BlockStore` as the `OrderingStateComputer`, which hands
+//! the blocks committed by the ordering over to the `ExecutionPipeline`.
+
+pub mod execution_pipeline;
+pub mod ordering_state_computer;
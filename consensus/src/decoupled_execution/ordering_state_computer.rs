@@ -0,0 +1,123 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    decoupled_execution::execution_pipeline::{OrderedBlocks, PipelineInput, PipelineSender},
+    state_replication::StateComputer,
+};
+use anyhow::{format_err, Result};
+use consensus_types::block::Block;
+use executor_types::StateComputeResult;
+use futures::channel::oneshot;
+use libra_crypto::HashValue;
+use libra_types::{block_info::BlockInfo, ledger_info::LedgerInfoWithSignatures};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// How long the ordering waits for storage to catch up with the block it synced to.
+const SYNC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The result of a block that is only ordered, which carries no execution state.
+pub fn ordered_only_compute_result() -> StateComputeResult {
+    StateComputeResult::new(
+        BlockInfo::ordered_only_state_id(),
+        vec![], /* frozen_subtree_roots */
+        0,      /* num_leaves */
+        None,   /* epoch_state */
+        vec![], /* compute_status */
+        vec![], /* transaction_info_hashes */
+    )
+}
+
+/// The StateComputer of the ordering stage: blocks are not executed, and committing them hands
+/// them over to the execution pipeline.
+pub struct OrderingStateComputer<T> {
+    pipeline: PipelineSender<T>,
+    /// Used for the targets that carry an execution result (e.g. epoch change ledger infos)
+    execution_proxy: Arc<dyn StateComputer<Payload = T>>,
+    /// The blocks inserted in the block tree that are not ordered yet
+    block_cache: Mutex<HashMap<HashValue, Block<T>>>,
+}
+
+impl<T> OrderingStateComputer<T> {
+    pub fn new(
+        pipeline: PipelineSender<T>,
+        execution_proxy: Arc<dyn StateComputer<Payload = T>>,
+    ) -> Self {
+        Self {
+            pipeline,
+            execution_proxy,
+            block_cache: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: Clone + Send + Sync + 'static> StateComputer for OrderingStateComputer<T> {
+    type Payload = T;
+
+    fn compute(
+        &self,
+        block: &Block<Self::Payload>,
+        _parent_block_id: HashValue,
+    ) -> Result<StateComputeResult> {
+        self.block_cache
+            .lock()
+            .unwrap()
+            .insert(block.id(), block.clone());
+        Ok(ordered_only_compute_result())
+    }
+
+    /// The blocks are ordered, send them to the execution pipeline without waiting for their
+    /// execution.
+    async fn commit(
+        &self,
+        block_ids: Vec<HashValue>,
+        finality_proof: LedgerInfoWithSignatures,
+    ) -> Result<()> {
+        let blocks = {
+            let mut block_cache = self.block_cache.lock().unwrap();
+            let blocks = block_ids
+                .iter()
+                .map(|id| {
+                    block_cache
+                        .remove(id)
+                        .ok_or_else(|| format_err!("Ordered block {} not found", id))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            // The remaining blocks up to the ordered round are on forks that are pruned
+            let ordered_round = finality_proof.ledger_info().round();
+            block_cache.retain(|_, block| block.round() > ordered_round);
+            blocks
+        };
+        self.pipeline
+            .unbounded_send(PipelineInput::Ordered(OrderedBlocks {
+                blocks,
+                ordered_proof: finality_proof,
+            }))
+            .map_err(|e| format_err!("Execution pipeline is stopped: {:?}", e))
+    }
+
+    /// Ordered commits do not carry the execution result to sync storage to, the execution
+    /// pipeline syncs storage to the commit decision of the same block instead.
+    async fn sync_to(&self, target: LedgerInfoWithSignatures) -> Result<()> {
+        if !target.ledger_info().commit_info().is_ordered_only() {
+            return self.execution_proxy.sync_to(target).await;
+        }
+        let (callback, callback_rcv) = oneshot::channel();
+        self.pipeline
+            .unbounded_send(PipelineInput::SyncTo(target.clone(), callback))
+            .map_err(|e| format_err!("Execution pipeline is stopped: {:?}", e))?;
+        match tokio::time::timeout(SYNC_TIMEOUT, callback_rcv).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(format_err!("Execution pipeline dropped the sync request")),
+            Err(_) => Err(format_err!(
+                "Timed out waiting for the commit decision of {}",
+                target
+            )),
+        }
+    }
+}
@@ -4,7 +4,14 @@
 use crate::{
     block_storage::BlockStore,
     counters,
+    decoupled_execution::{
+        execution_pipeline::{
+            commit_msg_channel, pipeline_channel, CommitMsg, CommitMsgSender, ExecutionPipeline,
+        },
+        ordering_state_computer::OrderingStateComputer,
+    },
     liveness::{
+        failure_aware_leader_reputation::FailureAwareLeaderReputation,
         leader_reputation::{ActiveInactiveHeuristic, LeaderReputation, LibraDBBackend},
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
@@ -25,15 +32,18 @@ use consensus_types::{
     epoch_retrieval::EpochRetrievalRequest,
 };
 use futures::{select, StreamExt};
-use libra_config::config::{ConsensusConfig, ConsensusProposerType, NodeConfig};
+use libra_config::config::{ConsensusConfig, NodeConfig};
 use libra_logger::prelude::*;
 use libra_types::{
     account_address::AccountAddress,
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
-    on_chain_config::{OnChainConfigPayload, ValidatorSet},
+    on_chain_config::{
+        LeaderReputationConfig, OnChainConfig, OnChainConfigPayload, OnChainConsensusConfig,
+        ProposerElectionType, ValidatorSet,
+    },
 };
-use network::protocols::network::Event;
+use network::{peer_manager::reputation::Misbehavior, protocols::network::Event};
 use safety_rules::SafetyRulesManager;
 use std::{
     cmp::Ordering,
@@ -79,6 +89,11 @@ pub struct EpochManager<T> {
     storage: Arc<dyn PersistentLivenessStorage<T>>,
     safety_rules_manager: SafetyRulesManager<T>,
     processor: Option<RoundProcessor<T>>,
+    // The on-chain consensus config of the current epoch, shared by the whole validator set
+    onchain_config: OnChainConsensusConfig,
+    // With decoupled execution, the execution pipeline of the current epoch and the state
+    // computer ordering the blocks for it
+    execution_pipeline: Option<(CommitMsgSender, Arc<dyn StateComputer<Payload = T>>)>,
 }
 
 impl<T: Payload> EpochManager<T> {
@@ -107,6 +122,8 @@ impl<T: Payload> EpochManager<T> {
             storage,
             safety_rules_manager,
             processor: None,
+            onchain_config: OnChainConsensusConfig::default(),
+            execution_pipeline: None,
         }
     }
 
@@ -140,7 +157,7 @@ impl<T: Payload> EpochManager<T> {
         RoundState::new(time_interval, time_service, timeout_sender)
     }
 
-    /// Create a proposer election handler based on proposers
+    /// Create a proposer election handler based on proposers and the on-chain consensus config
     fn create_proposer_election(
         &self,
         epoch_state: &EpochState,
@@ -149,33 +166,80 @@ impl<T: Payload> EpochManager<T> {
             .verifier
             .get_ordered_account_addresses_iter()
             .collect::<Vec<_>>();
-        match self.config.proposer_type {
-            ConsensusProposerType::RotatingProposer => Box::new(RotatingProposer::new(
-                proposers,
-                self.config.contiguous_rounds,
-            )),
+        match self.onchain_config.proposer_election_type() {
+            ProposerElectionType::RotatingProposer(contiguous_rounds) => {
+                Box::new(RotatingProposer::new(proposers, *contiguous_rounds))
+            }
             // We don't really have a fixed proposer!
-            ConsensusProposerType::FixedProposer => {
+            ProposerElectionType::FixedProposer(contiguous_rounds) => {
                 let proposer = choose_leader(proposers);
-                Box::new(RotatingProposer::new(
-                    vec![proposer],
-                    self.config.contiguous_rounds,
-                ))
+                Box::new(RotatingProposer::new(vec![proposer], *contiguous_rounds))
             }
-            ConsensusProposerType::LeaderReputation(heuristic_config) => {
-                let backend = Box::new(LibraDBBackend::new(
-                    proposers.len(),
-                    self.storage.libra_db(),
-                ));
-                let heuristic = Box::new(ActiveInactiveHeuristic::new(
-                    heuristic_config.active_weights,
-                    heuristic_config.inactive_weights,
-                ));
-                Box::new(LeaderReputation::new(proposers, backend, heuristic))
+            ProposerElectionType::LeaderReputation(heuristic_config) => {
+                Box::new(self.create_leader_reputation(proposers, heuristic_config))
             }
+            ProposerElectionType::FailureAwareLeaderReputation(config) => {
+                let reputation =
+                    self.create_leader_reputation(proposers, &config.leader_reputation);
+                Box::new(FailureAwareLeaderReputation::new(
+                    reputation,
+                    config.failed_weights,
+                ))
+            }
+        }
+    }
+
+    fn create_leader_reputation(
+        &self,
+        proposers: Vec<Author>,
+        heuristic_config: &LeaderReputationConfig,
+    ) -> LeaderReputation<T> {
+        let backend = Box::new(LibraDBBackend::new(
+            heuristic_config.window_size(proposers.len()),
+            self.storage.libra_db(),
+        ));
+        let heuristic = Box::new(ActiveInactiveHeuristic::new(
+            heuristic_config.active_weights,
+            heuristic_config.inactive_weights,
+        ));
+        LeaderReputation::new(proposers, backend, heuristic)
+    }
+
+    /// The state computer used by the consensus rounds: the blocks are only ordered with
+    /// decoupled execution, otherwise they are executed right away.
+    fn round_state_computer(&self) -> Arc<dyn StateComputer<Payload = T>> {
+        match &self.execution_pipeline {
+            Some((_, ordering_state_computer)) => Arc::clone(ordering_state_computer),
+            None => Arc::clone(&self.state_computer),
         }
     }
 
+    /// Starts the execution pipeline of the epoch, which executes the ordered blocks.
+    fn start_execution_pipeline(&mut self, epoch_state: &EpochState) {
+        let (pipeline_tx, pipeline_rx) = pipeline_channel();
+        let (commit_msg_tx, commit_msg_rx) = commit_msg_channel();
+        let network_sender = NetworkSender::new(
+            self.author,
+            self.network_sender.clone(),
+            self.self_sender.clone(),
+            epoch_state.verifier.clone(),
+        );
+        let pipeline = ExecutionPipeline::new(
+            self.author,
+            epoch_state.clone(),
+            Arc::clone(&self.state_computer),
+            Arc::clone(&self.storage),
+            self.safety_rules_manager.client(),
+            network_sender,
+            self.txn_manager.clone(),
+        );
+        tokio::spawn(pipeline.start(pipeline_rx, commit_msg_rx));
+        let ordering_state_computer: Arc<dyn StateComputer<Payload = T>> = Arc::new(
+            OrderingStateComputer::new(pipeline_tx, Arc::clone(&self.state_computer)),
+        );
+        self.execution_pipeline = Some((commit_msg_tx, ordering_state_computer));
+    }
+
     async fn process_epoch_retrieval(
         &mut self,
         request: EpochRetrievalRequest,
@@ -269,9 +333,12 @@ impl<T: Payload> EpochManager<T> {
         let block_store = Arc::new(BlockStore::new(
             Arc::clone(&self.storage),
             recovery_data,
-            Arc::clone(&self.state_computer),
+            self.round_state_computer(),
             self.config.max_pruned_blocks_in_mem,
         ));
+        // Storage may not have committed all the blocks committed before the restart, e.g. the
+        // ordered blocks not executed yet with decoupled execution.
+        block_store.commit_to_highest_commit_cert().await;
 
         info!("Update SafetyRules");
 
@@ -324,6 +391,7 @@ impl<T: Payload> EpochManager<T> {
             self.txn_manager.clone(),
             self.storage.clone(),
             self.time_service.clone(),
+            self.config.decoupled_execution,
         );
         processor.start(last_vote).await;
         self.processor = Some(RoundProcessor::Normal(processor));
@@ -349,7 +417,7 @@ impl<T: Payload> EpochManager<T> {
             epoch_state,
             network_sender,
             self.storage.clone(),
-            self.state_computer.clone(),
+            self.round_state_computer(),
             ledger_recovery_data.commit_round(),
         )));
         info!("SyncProcessor started");
@@ -363,6 +431,32 @@ impl<T: Payload> EpochManager<T> {
             epoch: payload.epoch(),
             verifier: (&validator_set).into(),
         };
+        // A missing config falls back to the default: networks started from a genesis without
+        // LibraConsensusConfig don't have it, and all their validators agree on the default. A
+        // published config this node can't decode is fatal, validators have to be upgraded
+        // before a new config version is published.
+        self.onchain_config = if payload
+            .configs()
+            .contains_key(&OnChainConsensusConfig::CONFIG_ID)
+        {
+            payload
+                .get()
+                .expect("failed to decode the on-chain consensus config from payload")
+        } else {
+            warn!("[EpochManager] On-chain consensus config not in payload, using default");
+            OnChainConsensusConfig::default()
+        };
+        info!(
+            "Epoch {} consensus config: {:?}",
+            epoch_state.epoch, self.onchain_config
+        );
+
+        // Release the previous epoch's pipeline, it stops once its ordered blocks are processed
+        self.processor = None;
+        self.execution_pipeline = None;
+        if self.config.decoupled_execution {
+            self.start_execution_pipeline(&epoch_state);
+        }
 
         match self.storage.start() {
             LivenessStorageData::RecoveryData(initial_data) => {
@@ -381,9 +475,19 @@ impl<T: Payload> EpochManager<T> {
         consensus_msg: ConsensusMsg<T>,
     ) -> anyhow::Result<()> {
         if let Some(event) = self.process_epoch(peer_id, consensus_msg).await? {
-            let verified_event = event
-                .verify(&self.epoch_state().verifier)
-                .context("[EpochManager] Verify event")?;
+            let verified_event = match event.verify(&self.epoch_state().verifier) {
+                Ok(verified_event) => verified_event,
+                Err(e) => {
+                    if let Some(misbehavior) = Misbehavior::from_verification_error(&e) {
+                        if let Err(err) =
+                            self.network_sender.report_misbehavior(peer_id, misbehavior)
+                        {
+                            warn!("Failed to report misbehavior of {}: {:?}", peer_id, err);
+                        }
+                    }
+                    return Err(e).context("[EpochManager] Verify event");
+                }
+            };
             self.process_event(peer_id, verified_event).await?;
         }
         Ok(())
@@ -411,6 +515,21 @@ impl<T: Payload> EpochManager<T> {
                     self.process_different_epoch(msg_epoch, peer_id).await?;
                 }
             }
+            ConsensusMsg::CommitVoteMsg(vote) => {
+                if vote.epoch() == self.epoch() {
+                    self.send_to_execution_pipeline(peer_id, CommitMsg::Vote(*vote))?;
+                } else {
+                    self.process_different_epoch(vote.epoch(), peer_id).await?;
+                }
+            }
+            ConsensusMsg::CommitDecisionMsg(decision) => {
+                if decision.epoch() == self.epoch() {
+                    self.send_to_execution_pipeline(peer_id, CommitMsg::Decision(*decision))?;
+                } else {
+                    self.process_different_epoch(decision.epoch(), peer_id)
+                        .await?;
+                }
+            }
             ConsensusMsg::EpochRetrievalRequest(request) => {
                 ensure!(
                     request.end_epoch <= self.epoch(),
@@ -425,6 +544,19 @@ impl<T: Payload> EpochManager<T> {
         Ok(None)
     }
 
+    fn send_to_execution_pipeline(
+        &mut self,
+        peer_id: AccountAddress,
+        msg: CommitMsg,
+    ) -> anyhow::Result<()> {
+        match &mut self.execution_pipeline {
+            Some((commit_msg_tx, _)) => commit_msg_tx
+                .push(peer_id, msg)
+                .map_err(|e| anyhow!("[EpochManager] Execution pipeline is stopped: {:?}", e)),
+            None => bail!("[EpochManager] Decoupled execution is disabled"),
+        }
+    }
+
     async fn process_event(
         &mut self,
         peer_id: AccountAddress,
@@ -15,6 +15,7 @@
 mod block_storage;
 mod consensusdb;
 mod counters;
+mod decoupled_execution;
 mod epoch_manager;
 mod liveness;
 mod network;
@@ -36,5 +37,6 @@ pub mod consensus_provider;
 /// LibraNet interface.
 pub mod network_interface;
 
+pub use consensusdb::ConsensusDBReader;
 #[cfg(feature = "fuzzing")]
 pub use round_manager::round_manager_fuzzing;
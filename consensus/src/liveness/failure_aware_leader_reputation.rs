@@ -0,0 +1,62 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::liveness::{
+    leader_reputation::{choose_index, LeaderReputation},
+    proposer_election::ProposerElection,
+};
+use consensus_types::common::{Author, Round};
+use libra_types::block_metadata::NewBlockEvent;
+use std::collections::HashSet;
+
+/// LeaderReputation that additionally down-weights candidates that were the expected proposer
+/// of a recently failed round: such a leader is likely to be down or slow, and electing it again
+/// costs a full round timeout.
+/// Every proposal records the expected proposers of the rounds skipped before it, the failures
+/// are read from the committed history so that all honest replicas agree on them.
+pub struct FailureAwareLeaderReputation<T> {
+    reputation: LeaderReputation<T>,
+    failed_weight: u64,
+}
+
+impl<T> FailureAwareLeaderReputation<T> {
+    pub fn new(reputation: LeaderReputation<T>, failed_weight: u64) -> Self {
+        Self {
+            reputation,
+            failed_weight,
+        }
+    }
+
+    fn get_failed_proposers(history: &[NewBlockEvent]) -> HashSet<Author> {
+        history
+            .iter()
+            .flat_map(|event| event.failed_proposers().iter().cloned())
+            .collect()
+    }
+}
+
+impl<T> ProposerElection<T> for FailureAwareLeaderReputation<T> {
+    fn records_failed_authors(&self) -> bool {
+        true
+    }
+
+    fn is_proposer_candidate(&self, author: Author, round: Round) -> bool {
+        self.reputation.is_proposer_candidate(author, round)
+    }
+
+    fn get_valid_proposer(&self, round: Round) -> Author {
+        let history = self.reputation.get_history(round);
+        let failed_proposers = Self::get_failed_proposers(&history);
+        let mut weights = self.reputation.get_weights(&history);
+        for (author, weight) in self.reputation.proposers().iter().zip(weights.iter_mut()) {
+            if failed_proposers.contains(author) {
+                *weight = std::cmp::min(*weight, self.failed_weight);
+            }
+        }
+        // Every candidate failed recently, fall back to the plain reputation.
+        if weights.iter().all(|w| *w == 0) {
+            weights = self.reputation.get_weights(&history);
+        }
+        self.reputation.proposers()[choose_index(weights, round)]
+    }
+}
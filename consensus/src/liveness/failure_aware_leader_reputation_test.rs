@@ -0,0 +1,139 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    liveness::{
+        failure_aware_leader_reputation::FailureAwareLeaderReputation,
+        leader_reputation::{ActiveInactiveHeuristic, LeaderReputation, MetadataBackend},
+        proposer_election::ProposerElection,
+    },
+    test_utils::TestPayload,
+};
+use consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::{Author, Round},
+};
+use libra_types::{
+    block_metadata::{BlockMetadata, NewBlockEvent},
+    validator_signer::ValidatorSigner,
+};
+use std::sync::{Arc, Mutex};
+
+/// Committed blocks, in increasing rounds
+#[derive(Clone, Default)]
+struct MockHistory {
+    data: Arc<Mutex<Vec<NewBlockEvent>>>,
+}
+
+impl MockHistory {
+    fn commit(&self, event: NewBlockEvent) {
+        self.data.lock().unwrap().push(event);
+    }
+}
+
+impl MetadataBackend for MockHistory {
+    fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEvent> {
+        self.data
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|block| block.round() <= target_round)
+            .cloned()
+            .collect()
+    }
+}
+
+fn create_signers(num: u8) -> Vec<ValidatorSigner> {
+    (0..num).map(|i| ValidatorSigner::random([i; 32])).collect()
+}
+
+fn create_election(
+    proposers: Vec<Author>,
+    history: MockHistory,
+    failed_weight: u64,
+) -> FailureAwareLeaderReputation<TestPayload> {
+    let reputation = LeaderReputation::new(
+        proposers,
+        Box::new(history),
+        Box::new(ActiveInactiveHeuristic::new(9, 1)),
+    );
+    FailureAwareLeaderReputation::new(reputation, failed_weight)
+}
+
+/// Commits the proposal of the round the way the committed block metadata records it, voted by
+/// every proposer.
+fn commit_proposal(
+    history: &MockHistory,
+    election: &FailureAwareLeaderReputation<TestPayload>,
+    signer: &ValidatorSigner,
+    round: Round,
+    parent_round: Round,
+    voters: &[Author],
+) {
+    let block = Block::<TestPayload>::new_proposal(
+        vec![],
+        round,
+        round,
+        certificate_for_genesis(),
+        signer,
+        election.get_failed_authors(round, parent_round),
+    );
+    let metadata = BlockMetadata::from(&block);
+    let (round, timestamp, _, proposer, failed_proposers) = metadata.into_inner().unwrap();
+    history.commit(NewBlockEvent::new(
+        round,
+        proposer,
+        voters.to_vec(),
+        timestamp,
+        failed_proposers,
+    ));
+}
+
+#[test]
+fn test_failed_proposer_is_excluded() {
+    let signers = create_signers(4);
+    let proposers: Vec<_> = signers.iter().map(|signer| signer.author()).collect();
+    let history = MockHistory::default();
+    let election = create_election(proposers.clone(), history.clone(), 0);
+    let down = proposers[1];
+
+    // The rounds of the down proposer time out, the next proposal records them as failed.
+    let mut parent_round = 0;
+    let mut timeouts = vec![];
+    for round in 1..100 {
+        let proposer = election.get_valid_proposer(round);
+        if proposer == down {
+            timeouts.push(round);
+            continue;
+        }
+        let signer = signers
+            .iter()
+            .find(|signer| signer.author() == proposer)
+            .unwrap();
+        commit_proposal(&history, &election, signer, round, parent_round, &proposers);
+        parent_round = round;
+    }
+    assert!(!timeouts.is_empty());
+
+    // Once the block recording the first failure is part of the election history, the down
+    // proposer isn't elected anymore.
+    let recorded_round = history
+        .get_block_metadata(Round::max_value())
+        .into_iter()
+        .rev()
+        .find(|event| event.failed_proposers().contains(&down))
+        .unwrap()
+        .round();
+    assert!(timeouts.iter().all(|round| *round <= recorded_round + 4));
+}
+
+#[test]
+fn test_all_proposers_failed() {
+    let signers = create_signers(1);
+    let proposers = vec![signers[0].author()];
+    let history = MockHistory::default();
+    let election = create_election(proposers.clone(), history.clone(), 0);
+    commit_proposal(&history, &election, &signers[0], 10, 1, &proposers);
+    assert_eq!(election.get_valid_proposer(20), proposers[0]);
+}
@@ -23,7 +23,18 @@ pub trait MetadataBackend: Send + Sync {
 pub struct LibraDBBackend {
     window_size: usize,
     libra_db: Arc<dyn DbReader>,
-    window: Mutex<Vec<(u64, NewBlockEvent)>>,
+    window: Mutex<Window>,
+}
+
+/// The committed BlockMetadata last read, the same for every target round as long as no new
+/// block gets committed.
+#[derive(Default)]
+struct Window {
+    /// Version and round of the latest committed BlockMetadata when the window was read.
+    latest: Option<(u64, Round)>,
+    /// The round the window ends at: the target round, or the latest committed one if earlier.
+    end_round: Round,
+    events: Vec<NewBlockEvent>,
 }
 
 impl LibraDBBackend {
@@ -31,11 +42,22 @@ impl LibraDBBackend {
         Self {
             window_size,
             libra_db,
-            window: Mutex::new(vec![]),
+            window: Mutex::new(Window::default()),
         }
     }
 
-    fn refresh_window(&self, target_round: Round) -> anyhow::Result<()> {
+    /// Return the version and round of the latest committed BlockMetadata.
+    fn get_latest(&self) -> anyhow::Result<Option<(u64, Round)>> {
+        let events =
+            self.libra_db
+                .get_events(&new_block_event_key(), u64::max_value(), false, 1)?;
+        events
+            .first()
+            .map(|(v, e)| Ok((*v, NewBlockEvent::try_from_bytes(e.event_data())?.round())))
+            .transpose()
+    }
+
+    fn refresh_window(&self, target_round: Round) -> anyhow::Result<Window> {
         // assumes target round is not too far from latest commit
         let buffer = 10;
         let events = self.libra_db.get_events(
@@ -44,43 +66,56 @@ impl LibraDBBackend {
             false,
             self.window_size as u64 + buffer,
         )?;
+        let mut latest = None;
         let mut result = vec![];
         for (v, e) in events {
-            let e = lcs::from_bytes::<NewBlockEvent>(e.event_data())?;
+            let e = NewBlockEvent::try_from_bytes(e.event_data())?;
+            if latest.is_none() {
+                latest = Some((v, e.round()));
+            }
             if e.round() <= target_round && result.len() < self.window_size {
-                result.push((v, e));
+                result.push(e);
             }
         }
-        *self.window.lock().unwrap() = result;
-        Ok(())
+        Ok(Window {
+            latest,
+            end_round: end_round(target_round, latest),
+            events: result,
+        })
     }
 }
 
+/// The last round of the window of a target round given the latest committed BlockMetadata.
+fn end_round(target_round: Round, latest: Option<(u64, Round)>) -> Round {
+    latest.map_or(0, |(_, round)| std::cmp::min(target_round, round))
+}
+
 impl MetadataBackend for LibraDBBackend {
-    // assume the target_round only increases
     fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEvent> {
-        let (known_version, known_round) = self
-            .window
-            .lock()
-            .unwrap()
-            .first()
-            .map(|(v, e)| (*v, e.round()))
-            .unwrap_or((0, 0));
-        if !(known_round == target_round
-            || known_version == self.libra_db.get_latest_version().unwrap_or(0))
-        {
-            if let Err(e) = self.refresh_window(target_round) {
-                error!("[leader reputation] Fail to refresh window: {:?}", e);
+        let mut window = self.window.lock().unwrap();
+        let latest = match self.get_latest() {
+            Ok(latest) => latest,
+            Err(e) => {
+                error!(
+                    "[leader reputation] Fail to read latest block metadata: {:?}",
+                    e
+                );
                 return vec![];
             }
+        };
+        // The ledger version moves with every committed transaction, but the window only
+        // changes when new BlockMetadata gets committed, or for a target round ending it
+        // elsewhere, e.g. the one of a failed round recorded in a proposal.
+        if window.latest != latest || window.end_round != end_round(target_round, latest) {
+            match self.refresh_window(target_round) {
+                Ok(refreshed) => *window = refreshed,
+                Err(e) => {
+                    error!("[leader reputation] Fail to refresh window: {:?}", e);
+                    return vec![];
+                }
+            }
         }
-        self.window
-            .lock()
-            .unwrap()
-            .clone()
-            .into_iter()
-            .map(|(_, e)| e)
-            .collect()
+        window.events.clone()
     }
 }
 
@@ -147,31 +182,69 @@ impl<T> LeaderReputation<T> {
             phantom: PhantomData,
         }
     }
+
+    /// The candidates this election chooses from, in the order all honest replicas agree on.
+    pub fn proposers(&self) -> &[Author] {
+        &self.proposers
+    }
+
+    /// Return the committed history preceding the round, the most recent block first.
+    pub fn get_history(&self, round: Round) -> Vec<NewBlockEvent> {
+        self.backend.get_block_metadata(target_round(round))
+    }
+
+    /// Return the weights of all candidates based on the given committed history.
+    pub fn get_weights(&self, history: &[NewBlockEvent]) -> Vec<u64> {
+        let weights = self.heuristic.get_weights(&self.proposers, history);
+        assert_eq!(weights.len(), self.proposers.len());
+        weights
+    }
 }
 
 impl<T> ProposerElection<T> for LeaderReputation<T> {
     fn get_valid_proposer(&self, round: Round) -> Author {
-        // TODO: configure the round gap
-        let target_round = if round >= 4 { round - 4 } else { 0 };
-        let sliding_window = self.backend.get_block_metadata(target_round);
-        let mut weights = self.heuristic.get_weights(&self.proposers, &sliding_window);
-        assert_eq!(weights.len(), self.proposers.len());
-        let mut total_weight = 0;
-        for w in &mut weights {
-            total_weight += *w;
-            *w = total_weight;
-        }
-        let mut state = round.to_le_bytes().to_vec();
-        let chosen_weight = next(&mut state) % total_weight;
-        let chosen_index = weights
-            .binary_search_by(|w| {
-                if *w <= chosen_weight {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
-            })
-            .unwrap_err();
-        self.proposers[chosen_index]
+        let weights = self.get_weights(&self.get_history(round));
+        self.proposers[choose_index(weights, round)]
+    }
+
+    fn is_proposer_candidate(&self, author: Author, _round: Round) -> bool {
+        self.proposers.contains(&author)
     }
 }
+
+/// The history used to elect the proposer of a round ends at the returned round, the most recent
+/// rounds might not be committed yet.
+fn target_round(round: Round) -> Round {
+    // TODO: configure the round gap
+    if round >= 4 {
+        round - 4
+    } else {
+        0
+    }
+}
+
+/// Pick an index with a probability proportional to its weight, seeded by the round so that all
+/// honest replicas pick the same one.
+pub(crate) fn choose_index(mut weights: Vec<u64>, round: Round) -> usize {
+    // Nobody can be chosen if all weights are zero, fall back to a uniform choice
+    if weights.iter().all(|w| *w == 0) {
+        weights.iter_mut().for_each(|w| *w = 1);
+    }
+    let mut total_weight: u64 = 0;
+    for w in &mut weights {
+        // Candidates past the saturation point can't be chosen, but nothing overflows
+        total_weight = total_weight.saturating_add(*w);
+        *w = total_weight;
+    }
+    let mut state = round.to_le_bytes().to_vec();
+    let chosen_weight = next(&mut state) % total_weight;
+    weights
+        .binary_search_by(|w| {
+            if *w <= chosen_weight {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        })
+        .unwrap_err()
+}
@@ -4,17 +4,37 @@
 use crate::{
     liveness::{
         leader_reputation::{
-            ActiveInactiveHeuristic, LeaderReputation, MetadataBackend, ReputationHeuristic,
+            choose_index, ActiveInactiveHeuristic, LeaderReputation, LibraDBBackend,
+            MetadataBackend, ReputationHeuristic,
         },
         proposer_election::{next, ProposerElection},
     },
     test_utils::TestPayload,
 };
+use anyhow::Result;
 use consensus_types::{
     block::{block_test_utils::certificate_for_genesis, Block},
     common::{Author, Round},
 };
-use libra_types::{block_metadata::NewBlockEvent, validator_signer::ValidatorSigner};
+use libra_crypto::HashValue;
+use libra_types::{
+    account_address::AccountAddress,
+    account_state_blob::{AccountStateBlob, AccountStateWithProof},
+    block_metadata::{new_block_event_key, NewBlockEvent},
+    contract_event::ContractEvent,
+    epoch_change::EpochChangeProof,
+    event::EventKey,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{AccumulatorConsistencyProof, SparseMerkleProof},
+    transaction::{TransactionListWithProof, TransactionWithProof, Version},
+    validator_signer::ValidatorSigner,
+};
+use move_core_types::language_storage::TypeTag;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+use storage_interface::{DbReader, StartupInfo, TreeState};
 
 struct MockHistory {
     window_size: usize,
@@ -39,7 +59,13 @@ impl MetadataBackend for MockHistory {
 }
 
 fn create_block(proposer: Author, voters: Vec<&ValidatorSigner>) -> NewBlockEvent {
-    NewBlockEvent::new(0, proposer, voters.iter().map(|v| v.author()).collect(), 0)
+    NewBlockEvent::new(
+        0,
+        proposer,
+        voters.iter().map(|v| v.author()).collect(),
+        0,
+        vec![],
+    )
 }
 
 #[test]
@@ -79,6 +105,17 @@ fn test_simple_heuristic() {
     }
 }
 
+#[test]
+fn test_choose_index_degenerate_weights() {
+    for round in 0..20 {
+        // All-zero weights fall back to a uniform choice
+        assert!(choose_index(vec![0, 0, 0], round) < 3);
+        // Weights summing past u64::MAX don't overflow, the saturated candidate is never chosen
+        assert!(choose_index(vec![u64::MAX, u64::MAX, 1], round) < 2);
+    }
+    assert_eq!(choose_index(vec![0, 5, 0], 7), 1);
+}
+
 #[test]
 fn test_api() {
     let active_weight = 9;
@@ -134,6 +171,7 @@ fn test_api() {
         1,
         certificate_for_genesis(),
         &signers[expected_index],
+        vec![],
     );
     assert!(proposer_election.is_valid_proposal(&good_proposal));
     let bad_proposal = Block::new_proposal(
@@ -142,6 +180,179 @@ fn test_api() {
         1,
         certificate_for_genesis(),
         &signers[unexpected_index],
+        vec![],
     );
     assert!(!proposer_election.is_valid_proposal(&bad_proposal));
 }
+
+/// Committed NewBlockEvents, the latest one first. Only the events can be read: the window
+/// must not depend on the ledger version, which every user transaction moves.
+#[derive(Default)]
+struct MockBlockEvents {
+    events: Mutex<Vec<(u64, ContractEvent)>>,
+    window_reads: AtomicUsize,
+}
+
+impl MockBlockEvents {
+    fn commit(&self, version: u64, round: Round) {
+        let mut events = self.events.lock().unwrap();
+        let event = NewBlockEvent::new(round, AccountAddress::random(), vec![], 0, vec![]);
+        let event = ContractEvent::new(
+            new_block_event_key(),
+            events.len() as u64,
+            TypeTag::Bool,
+            lcs::to_bytes(&event).unwrap(),
+        );
+        events.insert(0, (version, event));
+    }
+}
+
+impl DbReader for MockBlockEvents {
+    fn get_epoch_change_ledger_infos(
+        &self,
+        _start_epoch: u64,
+        _end_epoch: u64,
+    ) -> Result<EpochChangeProof> {
+        unimplemented!()
+    }
+
+    fn get_transactions(
+        &self,
+        _start_version: Version,
+        _batch_size: u64,
+        _ledger_version: Version,
+        _fetch_events: bool,
+    ) -> Result<TransactionListWithProof> {
+        unimplemented!()
+    }
+
+    fn get_events(
+        &self,
+        event_key: &EventKey,
+        start: u64,
+        ascending: bool,
+        limit: u64,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        assert_eq!(*event_key, new_block_event_key());
+        assert_eq!(start, u64::max_value());
+        assert!(!ascending);
+        if limit > 1 {
+            self.window_reads.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    fn get_latest_account_state(
+        &self,
+        _address: AccountAddress,
+    ) -> Result<Option<AccountStateBlob>> {
+        unimplemented!()
+    }
+
+    fn get_latest_ledger_info(&self) -> Result<LedgerInfoWithSignatures> {
+        unimplemented!()
+    }
+
+    fn get_startup_info(&self) -> Result<Option<StartupInfo>> {
+        unimplemented!()
+    }
+
+    fn get_txn_by_account(
+        &self,
+        _address: AccountAddress,
+        _seq_num: u64,
+        _ledger_version: Version,
+        _fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>> {
+        unimplemented!()
+    }
+
+    fn get_state_proof_with_ledger_info(
+        &self,
+        _known_version: u64,
+        _ledger_info: LedgerInfoWithSignatures,
+    ) -> Result<(EpochChangeProof, AccumulatorConsistencyProof)> {
+        unimplemented!()
+    }
+
+    fn get_state_proof(
+        &self,
+        _known_version: u64,
+    ) -> Result<(
+        LedgerInfoWithSignatures,
+        EpochChangeProof,
+        AccumulatorConsistencyProof,
+    )> {
+        unimplemented!()
+    }
+
+    fn get_account_state_with_proof(
+        &self,
+        _address: AccountAddress,
+        _version: Version,
+        _ledger_version: Version,
+    ) -> Result<AccountStateWithProof> {
+        unimplemented!()
+    }
+
+    fn get_account_state_with_proof_by_version(
+        &self,
+        _address: AccountAddress,
+        _version: Version,
+    ) -> Result<(Option<AccountStateBlob>, SparseMerkleProof)> {
+        unimplemented!()
+    }
+
+    fn get_latest_state_root(&self) -> Result<(Version, HashValue)> {
+        unimplemented!()
+    }
+
+    fn get_latest_tree_state(&self) -> Result<TreeState> {
+        unimplemented!()
+    }
+
+    fn get_ledger_info(&self, _known_version: u64) -> Result<LedgerInfoWithSignatures> {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn test_libra_db_backend_window() {
+    let db = Arc::new(MockBlockEvents::default());
+    // Blocks carry user transactions: block metadata is only every 10 versions
+    for round in 1..=5 {
+        db.commit(round * 10, round);
+    }
+    let backend = LibraDBBackend::new(3, db.clone());
+    let rounds = |target_round| -> Vec<Round> {
+        backend
+            .get_block_metadata(target_round)
+            .iter()
+            .map(NewBlockEvent::round)
+            .collect()
+    };
+
+    assert_eq!(rounds(10), vec![5, 4, 3]);
+    assert_eq!(db.window_reads.load(Ordering::SeqCst), 1);
+    // Later target rounds share the window until new block metadata gets committed
+    assert_eq!(rounds(11), vec![5, 4, 3]);
+    assert_eq!(db.window_reads.load(Ordering::SeqCst), 1);
+    // An earlier target round needs the window again
+    assert_eq!(rounds(3), vec![3, 2, 1]);
+    assert_eq!(rounds(3), vec![3, 2, 1]);
+    assert_eq!(db.window_reads.load(Ordering::SeqCst), 2);
+    assert_eq!(rounds(12), vec![5, 4, 3]);
+    assert_eq!(db.window_reads.load(Ordering::SeqCst), 3);
+
+    db.commit(60, 6);
+    assert_eq!(rounds(12), vec![6, 5, 4]);
+    assert_eq!(rounds(13), vec![6, 5, 4]);
+    assert_eq!(db.window_reads.load(Ordering::SeqCst), 4);
+}
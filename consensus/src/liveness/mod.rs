@@ -1,12 +1,15 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub(crate) mod failure_aware_leader_reputation;
 pub(crate) mod leader_reputation;
 pub(crate) mod proposal_generator;
 pub(crate) mod proposer_election;
 pub(crate) mod rotating_proposer_election;
 pub(crate) mod round_state;
 
+#[cfg(test)]
+mod failure_aware_leader_reputation_test;
 #[cfg(test)]
 mod leader_reputation_test;
 #[cfg(test)]
@@ -4,6 +4,7 @@
 use crate::{
     block_storage::BlockReader,
     counters,
+    liveness::proposer_election::ProposerElection,
     state_replication::TxnManager,
     util::time_service::{wait_if_possible, TimeService, WaitingError, WaitingSuccess},
 };
@@ -79,7 +80,11 @@ impl<T: Payload> ProposalGenerator<T> {
 
     /// Reconfiguration rule - we propose empty blocks with parents' timestamp
     /// after reconfiguration until it's committed
-    pub fn generate_reconfig_empty_suffix(&self, round: Round) -> anyhow::Result<BlockData<T>> {
+    pub fn generate_reconfig_empty_suffix(
+        &self,
+        round: Round,
+        failed_authors: Vec<(Round, Author)>,
+    ) -> anyhow::Result<BlockData<T>> {
         let hqc = self.ensure_highest_quorum_cert(round)?;
         Ok(BlockData::new_proposal(
             T::default(),
@@ -87,6 +92,7 @@ impl<T: Payload> ProposalGenerator<T> {
             round,
             hqc.certified_block().timestamp_usecs(),
             hqc.as_ref().clone(),
+            failed_authors,
         ))
     }
 
@@ -100,10 +106,13 @@ impl<T: Payload> ProposalGenerator<T> {
     /// 2. The round is provided by the caller.
     /// 3. In case a given round is not greater than the calculated parent, return an OldRound
     /// error.
+    /// The proposal records the expected proposers of the rounds skipped since its parent if the
+    /// proposer election reads them back.
     pub async fn generate_proposal(
         &mut self,
         round: Round,
         round_deadline: Instant,
+        proposer_election: &(dyn ProposerElection<T> + Send + Sync),
     ) -> anyhow::Result<BlockData<T>> {
         {
             let mut last_round_generated = self.last_round_generated.lock().unwrap();
@@ -115,9 +124,14 @@ impl<T: Payload> ProposalGenerator<T> {
         }
 
         let hqc = self.ensure_highest_quorum_cert(round)?;
+        let failed_authors = if proposer_election.records_failed_authors() {
+            proposer_election.get_failed_authors(round, hqc.certified_block().round())
+        } else {
+            vec![]
+        };
 
         if hqc.certified_block().has_reconfiguration() {
-            return self.generate_reconfig_empty_suffix(round);
+            return self.generate_reconfig_empty_suffix(round, failed_authors);
         }
 
         // One needs to hold the blocks with the references to the payloads while get_block is
@@ -215,6 +229,7 @@ impl<T: Payload> ProposalGenerator<T> {
             round,
             block_timestamp.as_micros() as u64,
             hqc.as_ref().clone(),
+            failed_authors,
         ))
     }
 
@@ -3,15 +3,24 @@
 
 use crate::{
     block_storage::BlockReader,
-    liveness::proposal_generator::ProposalGenerator,
-    test_utils::{build_empty_tree, MockTransactionManager, TreeInserter},
+    liveness::{
+        failure_aware_leader_reputation::FailureAwareLeaderReputation,
+        leader_reputation::{ActiveInactiveHeuristic, LeaderReputation, MetadataBackend},
+        proposal_generator::ProposalGenerator,
+        proposer_election::{ProposerElection, MAX_FAILED_AUTHORS},
+        rotating_proposer_election::RotatingProposer,
+    },
+    test_utils::{build_empty_tree, MockTransactionManager, TestPayload, TreeInserter},
     util::mock_time_service::SimulatedTimeService,
 };
-use consensus_types::block::{
-    block_test_utils::{certificate_for_genesis, gen_test_certificate},
-    Block,
+use consensus_types::{
+    block::{
+        block_test_utils::{certificate_for_genesis, gen_test_certificate},
+        Block,
+    },
+    common::Round,
 };
-use libra_types::validator_signer::ValidatorSigner;
+use libra_types::{block_metadata::NewBlockEvent, validator_signer::ValidatorSigner};
 use std::{
     sync::Arc,
     time::{Duration, Instant},
@@ -32,11 +41,12 @@ async fn test_proposal_generation_empty_tree() {
         Arc::new(SimulatedTimeService::new()),
         1,
     );
+    let proposer_election = RotatingProposer::new(vec![proposal_generator.author()], 1);
     let genesis = block_store.root();
 
     // Generate proposals for an empty tree.
     let proposal_data = proposal_generator
-        .generate_proposal(1, minute_from_now())
+        .generate_proposal(1, minute_from_now(), &proposer_election)
         .await
         .unwrap();
     let proposal = Block::new_proposal_from_block_data(proposal_data, &signer);
@@ -46,7 +56,7 @@ async fn test_proposal_generation_empty_tree() {
 
     // Duplicate proposals on the same round are not allowed
     let proposal_err = proposal_generator
-        .generate_proposal(1, minute_from_now())
+        .generate_proposal(1, minute_from_now(), &proposer_election)
         .await
         .err();
     assert!(proposal_err.is_some());
@@ -63,6 +73,7 @@ async fn test_proposal_generation_parent() {
         Arc::new(SimulatedTimeService::new()),
         1,
     );
+    let proposer_election = RotatingProposer::new(vec![proposal_generator.author()], 1);
     let genesis = block_store.root();
     let a1 = inserter.insert_block_with_qc(certificate_for_genesis(), &genesis, 1);
     let b1 = inserter.insert_block_with_qc(certificate_for_genesis(), &genesis, 2);
@@ -71,7 +82,7 @@ async fn test_proposal_generation_parent() {
     // generate proposals for an empty tree.
     assert_eq!(
         proposal_generator
-            .generate_proposal(10, minute_from_now())
+            .generate_proposal(10, minute_from_now(), &proposer_election)
             .await
             .unwrap()
             .parent_id(),
@@ -81,7 +92,7 @@ async fn test_proposal_generation_parent() {
     // Once a1 is certified, it should be the one to choose from
     inserter.insert_qc_for_block(a1.as_ref(), None);
     let a1_child_res = proposal_generator
-        .generate_proposal(11, minute_from_now())
+        .generate_proposal(11, minute_from_now(), &proposer_election)
         .await
         .unwrap();
     assert_eq!(a1_child_res.parent_id(), a1.id());
@@ -91,7 +102,7 @@ async fn test_proposal_generation_parent() {
     // Once b1 is certified, it should be the one to choose from
     inserter.insert_qc_for_block(b1.as_ref(), None);
     let b1_child_res = proposal_generator
-        .generate_proposal(12, minute_from_now())
+        .generate_proposal(12, minute_from_now(), &proposer_election)
         .await
         .unwrap();
     assert_eq!(b1_child_res.parent_id(), b1.id());
@@ -110,12 +121,13 @@ async fn test_old_proposal_generation() {
         Arc::new(SimulatedTimeService::new()),
         1,
     );
+    let proposer_election = RotatingProposer::new(vec![proposal_generator.author()], 1);
     let genesis = block_store.root();
     let a1 = inserter.insert_block_with_qc(certificate_for_genesis(), &genesis, 1);
     inserter.insert_qc_for_block(a1.as_ref(), None);
 
     let proposal_err = proposal_generator
-        .generate_proposal(1, minute_from_now())
+        .generate_proposal(1, minute_from_now(), &proposer_election)
         .await
         .err();
     assert!(proposal_err.is_some());
@@ -132,11 +144,12 @@ async fn test_empty_proposal_after_reconfiguration() {
         Arc::new(SimulatedTimeService::new()),
         1,
     );
+    let proposer_election = RotatingProposer::new(vec![proposal_generator.author()], 1);
     let genesis = block_store.root();
     let a1 = inserter.insert_block_with_qc(certificate_for_genesis(), &genesis, 1);
     // Normal proposal is not empty
     let normal_proposal_1 = proposal_generator
-        .generate_proposal(42, minute_from_now())
+        .generate_proposal(42, minute_from_now(), &proposer_election)
         .await
         .unwrap();
     assert!(!normal_proposal_1.payload().unwrap().is_empty());
@@ -144,7 +157,7 @@ async fn test_empty_proposal_after_reconfiguration() {
     inserter.insert_qc_for_block(a2.as_ref(), None);
     // The direct child is empty
     let empty_proposal_1 = proposal_generator
-        .generate_proposal(43, minute_from_now())
+        .generate_proposal(43, minute_from_now(), &proposer_election)
         .await
         .unwrap();
     assert!(empty_proposal_1.payload().unwrap().is_empty());
@@ -159,7 +172,7 @@ async fn test_empty_proposal_after_reconfiguration() {
     inserter.insert_qc_for_block(a3.as_ref(), None);
     // Indirect child is empty too
     let empty_proposal_2 = proposal_generator
-        .generate_proposal(44, minute_from_now())
+        .generate_proposal(44, minute_from_now(), &proposer_election)
         .await
         .unwrap();
     assert!(empty_proposal_2.payload().unwrap().is_empty());
@@ -179,7 +192,64 @@ async fn test_empty_proposal_after_reconfiguration() {
     );
     block_store.insert_single_quorum_cert(li).unwrap();
     let err_proposal = proposal_generator
-        .generate_proposal(45, minute_from_now())
+        .generate_proposal(45, minute_from_now(), &proposer_election)
         .await;
     assert!(err_proposal.is_err());
 }
+
+/// No committed block metadata, every candidate gets the same weight.
+struct EmptyHistory;
+
+impl MetadataBackend for EmptyHistory {
+    fn get_block_metadata(&self, _target_round: Round) -> Vec<NewBlockEvent> {
+        vec![]
+    }
+}
+
+#[tokio::test]
+async fn test_proposal_records_failed_authors() {
+    let signer = ValidatorSigner::random(None);
+    let other = ValidatorSigner::random([1; 32]).author();
+    let block_store = build_empty_tree();
+    let mut proposal_generator = ProposalGenerator::new(
+        signer.author(),
+        block_store,
+        Box::new(MockTransactionManager::new(None)),
+        Arc::new(SimulatedTimeService::new()),
+        1,
+    );
+    let proposers = vec![signer.author(), other];
+    let round = MAX_FAILED_AUTHORS + 4;
+
+    // Elections that don't read the failed authors back keep the original proposal format
+    let rotating_proposer = RotatingProposer::new(proposers.clone(), 1);
+    let proposal_data = proposal_generator
+        .generate_proposal(round, minute_from_now(), &rotating_proposer)
+        .await
+        .unwrap();
+    assert!(proposal_data.failed_authors().is_empty());
+
+    // Only the most recent rounds skipped since genesis are recorded
+    let proposer_election = FailureAwareLeaderReputation::<TestPayload>::new(
+        LeaderReputation::new(
+            proposers,
+            Box::new(EmptyHistory),
+            Box::new(ActiveInactiveHeuristic::new(9, 1)),
+        ),
+        0,
+    );
+    let proposal_data = proposal_generator
+        .generate_proposal(round + 1, minute_from_now(), &proposer_election)
+        .await
+        .unwrap();
+    let expected: Vec<_> = (5..round + 1)
+        .map(|failed_round| {
+            (
+                failed_round,
+                proposer_election.get_valid_proposer(failed_round),
+            )
+        })
+        .collect();
+    assert_eq!(proposal_data.failed_authors(), expected.as_slice());
+    assert!(expected.iter().any(|(_, author)| *author == other));
+}
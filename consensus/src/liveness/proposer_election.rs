@@ -6,6 +6,10 @@ use consensus_types::{
     common::{Author, Round},
 };
 
+/// The maximum number of skipped rounds a proposal records the expected proposers of, the most
+/// recent ones are kept.
+pub const MAX_FAILED_AUTHORS: u64 = 10;
+
 /// ProposerElection incorporates the logic of choosing a leader among multiple candidates.
 /// We are open to a possibility for having multiple proposers per round, the ultimate choice
 /// of a proposal is exposed by the election protocol via the stream of proposals.
@@ -21,12 +25,34 @@ pub trait ProposerElection<T> {
     /// used by e.g., voters for choosing the destinations for sending their votes to).
     fn get_valid_proposer(&self, round: Round) -> Author;
 
+    /// Whether the author may be the proposer of the round at all. Unlike is_valid_proposer, it
+    /// doesn't depend on the committed history, so that a proposal can be filtered before
+    /// syncing up to the certificates it carries.
+    fn is_proposer_candidate(&self, author: Author, round: Round) -> bool {
+        self.is_valid_proposer(author, round)
+    }
+
     /// Return if a given proposed block is valid.
     fn is_valid_proposal(&self, block: &Block<T>) -> bool {
         block.author().map_or(false, |author| {
             self.is_valid_proposer(author, block.round())
         })
     }
+
+    /// Whether proposals record the expected proposers of the rounds skipped before them, only
+    /// elections reading them back from the committed history need them.
+    fn records_failed_authors(&self) -> bool {
+        false
+    }
+
+    /// Return the rounds skipped between a parent at parent_round and a proposal at round, with
+    /// the proposer that was expected in each of them.
+    fn get_failed_authors(&self, round: Round, parent_round: Round) -> Vec<(Round, Author)> {
+        let start = std::cmp::max(parent_round + 1, round.saturating_sub(MAX_FAILED_AUTHORS));
+        (start..round)
+            .map(|failed_round| (failed_round, self.get_valid_proposer(failed_round)))
+            .collect()
+    }
 }
 
 // next continuously mutates a state and returns a u64-index
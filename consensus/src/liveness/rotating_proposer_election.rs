@@ -27,7 +27,8 @@ impl RotatingProposer {
     pub fn new(proposers: Vec<Author>, contiguous_rounds: u32) -> Self {
         Self {
             proposers,
-            contiguous_rounds,
+            // Guard against dividing by zero, a valid on-chain config has at least one round
+            contiguous_rounds: std::cmp::max(contiguous_rounds, 1),
         }
     }
 }
@@ -22,10 +22,24 @@ fn test_rotating_proposer() {
     // Test genesis and the next block
     let quorum_cert = certificate_for_genesis();
 
-    let good_proposal =
-        Block::new_proposal(1, 1, 1, quorum_cert.clone(), &another_validator_signer);
-    let bad_proposal = Block::new_proposal(2, 1, 2, quorum_cert.clone(), &chosen_validator_signer);
-    let next_good_proposal = Block::new_proposal(3, 2, 3, quorum_cert, &chosen_validator_signer);
+    let good_proposal = Block::new_proposal(
+        1,
+        1,
+        1,
+        quorum_cert.clone(),
+        &another_validator_signer,
+        vec![],
+    );
+    let bad_proposal = Block::new_proposal(
+        2,
+        1,
+        2,
+        quorum_cert.clone(),
+        &chosen_validator_signer,
+        vec![],
+    );
+    let next_good_proposal =
+        Block::new_proposal(3, 2, 3, quorum_cert, &chosen_validator_signer, vec![]);
     assert!(pe.is_valid_proposal(&good_proposal));
     assert!(!pe.is_valid_proposal(&bad_proposal));
     assert!(pe.is_valid_proposal(&next_good_proposal),);
@@ -52,9 +66,24 @@ fn test_rotating_proposer_with_three_contiguous_rounds() {
     // Test genesis and the next block
     let quorum_cert = certificate_for_genesis();
 
-    let good_proposal = Block::new_proposal(1, 1, 1, quorum_cert.clone(), &chosen_validator_signer);
-    let bad_proposal = Block::new_proposal(2, 1, 2, quorum_cert.clone(), &another_validator_signer);
-    let next_good_proposal = Block::new_proposal(3, 2, 3, quorum_cert, &chosen_validator_signer);
+    let good_proposal = Block::new_proposal(
+        1,
+        1,
+        1,
+        quorum_cert.clone(),
+        &chosen_validator_signer,
+        vec![],
+    );
+    let bad_proposal = Block::new_proposal(
+        2,
+        1,
+        2,
+        quorum_cert.clone(),
+        &another_validator_signer,
+        vec![],
+    );
+    let next_good_proposal =
+        Block::new_proposal(3, 2, 3, quorum_cert, &chosen_validator_signer, vec![]);
     assert!(pe.is_valid_proposal(&good_proposal),);
     assert!(!pe.is_valid_proposal(&bad_proposal));
     assert!(pe.is_valid_proposal(&next_good_proposal),);
@@ -81,9 +110,24 @@ fn test_fixed_proposer() {
     // Test genesis and the next block
     let quorum_cert = certificate_for_genesis();
 
-    let good_proposal = Block::new_proposal(1, 1, 1, quorum_cert.clone(), &chosen_validator_signer);
-    let bad_proposal = Block::new_proposal(2, 1, 2, quorum_cert.clone(), &another_validator_signer);
-    let next_good_proposal = Block::new_proposal(2, 2, 3, quorum_cert, &chosen_validator_signer);
+    let good_proposal = Block::new_proposal(
+        1,
+        1,
+        1,
+        quorum_cert.clone(),
+        &chosen_validator_signer,
+        vec![],
+    );
+    let bad_proposal = Block::new_proposal(
+        2,
+        1,
+        2,
+        quorum_cert.clone(),
+        &another_validator_signer,
+        vec![],
+    );
+    let next_good_proposal =
+        Block::new_proposal(2, 2, 3, quorum_cert, &chosen_validator_signer, vec![]);
     assert!(pe.is_valid_proposal(&good_proposal));
     assert!(!pe.is_valid_proposal(&bad_proposal));
     assert!(pe.is_valid_proposal(&next_good_proposal));
@@ -91,3 +135,15 @@ fn test_fixed_proposer() {
     assert!(!pe.is_valid_proposer(another_author, 1));
     assert_eq!(pe.get_valid_proposer(1), chosen_author);
 }
+
+#[test]
+fn test_rotating_proposer_zero_contiguous_rounds() {
+    let proposers = vec![
+        ValidatorSigner::random([0u8; 32]).author(),
+        ValidatorSigner::random([1u8; 32]).author(),
+    ];
+    // Zero contiguous rounds behaves like one instead of dividing by zero
+    let pe: Box<dyn ProposerElection<u32>> = Box::new(RotatingProposer::new(proposers.clone(), 0));
+    assert_eq!(pe.get_valid_proposer(1), proposers[1]);
+    assert_eq!(pe.get_valid_proposer(2), proposers[0]);
+}
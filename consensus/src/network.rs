@@ -10,6 +10,8 @@ use bytes::Bytes;
 use channel::{self, libra_channel, message_queues::QueueStyle};
 use consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse},
+    commit_decision::CommitDecision,
+    commit_vote::CommitVote,
     common::{Author, Payload},
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
@@ -22,7 +24,10 @@ use libra_types::{
     account_address::AccountAddress, epoch_change::EpochChangeProof,
     validator_verifier::ValidatorVerifier,
 };
-use network::protocols::{network::Event, rpc::error::RpcError};
+use network::{
+    error::NetworkError,
+    protocols::{network::Event, rpc::error::RpcError},
+};
 use std::{
     marker::PhantomData,
     mem::{discriminant, Discriminant},
@@ -201,6 +206,25 @@ impl<T: Payload> NetworkSender<T> {
         self.broadcast(msg).await
     }
 
+    /// Broadcasts the commit vote on the execution result of ordered blocks to all validators
+    /// (including self), used with decoupled execution.
+    pub async fn broadcast_commit_vote(&mut self, commit_vote: CommitVote) {
+        let msg = ConsensusMsg::CommitVoteMsg::<T>(Box::new(commit_vote));
+        self.broadcast(msg).await
+    }
+
+    /// Sends a commit decision to a validator whose execution is lagging behind.
+    pub fn send_commit_decision(&self, commit_decision: CommitDecision, recipient: Author) {
+        let msg = ConsensusMsg::CommitDecisionMsg::<T>(Box::new(commit_decision));
+        let mut network_sender = self.network_sender.clone();
+        if let Err(e) = network_sender.send_to(recipient, msg) {
+            warn!(
+                "Failed to send a commit decision to peer {:?}: {:?}",
+                recipient, e
+            );
+        }
+    }
+
     pub async fn notify_epoch_change(&mut self, proof: EpochChangeProof) {
         let msg = ConsensusMsg::EpochChangeProof::<T>(Box::new(proof));
         let self_msg = Event::Message((self.author, msg));
@@ -217,12 +241,15 @@ pub struct NetworkTask<T> {
     >,
     block_retrieval_tx: libra_channel::Sender<AccountAddress, IncomingBlockRetrievalRequest>,
     all_events: Box<dyn Stream<Item = anyhow::Result<Event<ConsensusMsg<T>>>> + Send + Unpin>,
+    /// Reports the senders of undecodable messages.
+    network_sender: ConsensusNetworkSender<T>,
 }
 
 impl<T: Payload> NetworkTask<T> {
     /// Establishes the initial connections with the peers and returns the receivers.
     pub fn new(
         network_events: ConsensusNetworkEvents<T>,
+        network_sender: ConsensusNetworkSender<T>,
         self_receiver: channel::Receiver<anyhow::Result<Event<ConsensusMsg<T>>>>,
     ) -> (NetworkTask<T>, NetworkReceivers<T>) {
         let (consensus_messages_tx, consensus_messages) = libra_channel::new(
@@ -242,6 +269,7 @@ impl<T: Payload> NetworkTask<T> {
                 consensus_messages_tx,
                 block_retrieval_tx,
                 all_events,
+                network_sender,
             },
             NetworkReceivers {
                 consensus_messages,
@@ -251,7 +279,28 @@ impl<T: Payload> NetworkTask<T> {
     }
 
     pub async fn start(mut self) {
-        while let Some(Ok(message)) = self.all_events.next().await {
+        while let Some(event) = self.all_events.next().await {
+            let message = match event {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("Failed to receive consensus msg: {:?}", e);
+                    if let Some(misbehavior) = e
+                        .downcast_ref::<NetworkError>()
+                        .and_then(NetworkError::misbehavior)
+                    {
+                        if let Err(err) = self
+                            .network_sender
+                            .report_misbehavior(misbehavior.peer_id, misbehavior.misbehavior)
+                        {
+                            warn!(
+                                "Failed to report misbehavior of {}: {:?}",
+                                misbehavior.peer_id, err
+                            );
+                        }
+                    }
+                    continue;
+                }
+            };
             match message {
                 Event::Message((peer_id, msg)) => {
                     if let Err(e) = self
@@ -7,6 +7,8 @@ use crate::counters;
 use channel::message_queues::QueueStyle;
 use consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse},
+    commit_decision::CommitDecision,
+    commit_vote::CommitVote,
     common::Payload,
     epoch_retrieval::EpochRetrievalRequest,
     proposal_msg::ProposalMsg,
@@ -19,7 +21,7 @@ use network::{
     common::NetworkPublicKeys,
     connectivity_manager::ConnectivityRequest,
     error::NetworkError,
-    peer_manager::{ConnectionRequestSender, PeerManagerRequestSender},
+    peer_manager::{reputation::Misbehavior, ConnectionRequestSender, PeerManagerRequestSender},
     protocols::{
         network::{NetworkEvents, NetworkSender},
         rpc::error::RpcError,
@@ -52,6 +54,12 @@ pub enum ConsensusMsg<T> {
     /// VoteMsg is the struct that is ultimately sent by the voter in response for receiving a
     /// proposal.
     VoteMsg(Box<VoteMsg>),
+    /// With decoupled execution, the signature of a validator on the execution result of
+    /// ordered blocks.
+    CommitVoteMsg(Box<CommitVote>),
+    /// With decoupled execution, the execution result of ordered blocks certified by a quorum of
+    /// commit votes.
+    CommitDecisionMsg(Box<CommitDecision>),
 }
 
 /// The interface from Network to Consensus layer.
@@ -152,6 +160,15 @@ impl<T: Payload> ConsensusNetworkSender<T> {
             .await
     }
 
+    /// Report the misbehavior of a peer, the network layer bans peers that misbehave repeatedly.
+    pub fn report_misbehavior(
+        &mut self,
+        peer: PeerId,
+        misbehavior: Misbehavior,
+    ) -> Result<(), NetworkError> {
+        self.network_sender.report_misbehavior(peer, misbehavior)
+    }
+
     /// Update set of nodes eligible to join the network. In the future, this should be handled by
     /// the unified reconfiguration event.
     pub async fn update_eligible_nodes(
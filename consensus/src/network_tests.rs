@@ -353,12 +353,22 @@ impl DropConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{network::NetworkTask, test_utils::TestPayload};
+    use crate::{network::NetworkTask, network_interface::add_to_network, test_utils::TestPayload};
     use consensus_types::block_retrieval::{
         BlockRetrievalRequest, BlockRetrievalResponse, BlockRetrievalStatus,
     };
-    use libra_crypto::HashValue;
+    use libra_config::config::RoleType;
+    use libra_crypto::{test_utils::TEST_SEED, x25519, HashValue, Uniform};
     use libra_types::validator_verifier::random_validator_verifier;
+    use netcore::transport::simulated::{
+        DelayDistribution, LinkConfig, NetworkSimulator, SimulationEvent,
+    };
+    use network::{
+        validator_network::network_builder::{AuthenticationMode, NetworkBuilder},
+        NetworkPublicKeys,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+    use tokio::time::timeout;
 
     #[test]
     fn test_network_api() {
@@ -387,13 +397,14 @@ mod tests {
 
             playground.add_node(*peer, consensus_tx, network_reqs_rx, conn_mgr_reqs_rx);
             let (self_sender, self_receiver) = channel::new_test(8);
+            let (task, receiver) =
+                NetworkTask::new(network_events, network_sender.clone(), self_receiver);
             let node = NetworkSender::new(
                 *peer,
                 network_sender,
                 self_sender,
                 validator_verifier.clone(),
             );
-            let (task, receiver) = NetworkTask::new(network_events, self_receiver);
             receivers.push(receiver);
             runtime.handle().spawn(task.start());
             nodes.push(node);
@@ -409,7 +420,7 @@ mod tests {
         );
         let previous_qc = certificate_for_genesis();
         let proposal = ProposalMsg::new(
-            Block::new_proposal(vec![0], 1, 1, previous_qc.clone(), &signers[0]),
+            Block::new_proposal(vec![0], 1, 1, previous_qc.clone(), &signers[0], vec![]),
             SyncInfo::new(previous_qc.clone(), previous_qc, None),
         );
         timed_block_on(&mut runtime, async {
@@ -474,7 +485,8 @@ mod tests {
                 self_sender,
                 validator_verifier.clone(),
             );
-            let (task, receiver) = NetworkTask::new(network_events, self_receiver);
+            let (task, receiver) =
+                NetworkTask::new(network_events, network_sender.clone(), self_receiver);
             senders.push(network_sender);
             receivers.push(receiver);
             runtime.handle().spawn(task.start());
@@ -528,4 +540,160 @@ mod tests {
             assert_eq!(response.status(), BlockRetrievalStatus::IdNotFound);
         });
     }
+
+    /// The next proposal `receiver` gets within `wait` of real time, ignoring the other messages.
+    async fn next_proposal(
+        receiver: &mut NetworkReceivers<TestPayload>,
+        wait: Duration,
+    ) -> Option<ProposalMsg<TestPayload>> {
+        loop {
+            match timeout(wait, receiver.consensus_messages.next()).await {
+                Ok(Some((_, ConsensusMsg::ProposalMsg(proposal)))) => return Some(*proposal),
+                Ok(Some(_)) => continue,
+                _ => return None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_network_over_simulated_noise() {
+        let mut runtime = consensus_runtime();
+        let link = LinkConfig {
+            delay: DelayDistribution::Fixed(Duration::from_millis(10)),
+            ..LinkConfig::default()
+        };
+        let simulator = NetworkSimulator::new(0);
+        simulator.apply(SimulationEvent::SetDefaultLink(link));
+        let num_nodes = 2;
+        let (signers, validator_verifier) = random_validator_verifier(num_nodes, None, false);
+        let peers: Vec<_> = signers.iter().map(|signer| signer.author()).collect();
+        let mut rng = StdRng::from_seed(TEST_SEED);
+        let keys: Vec<_> = peers
+            .iter()
+            .map(|_| x25519::PrivateKey::generate(&mut rng))
+            .collect();
+        let trusted_peers: HashMap<_, _> = peers
+            .iter()
+            .zip(&keys)
+            .map(|(peer, key)| {
+                (
+                    *peer,
+                    NetworkPublicKeys {
+                        identity_public_key: key.public_key(),
+                    },
+                )
+            })
+            .collect();
+
+        let mut addrs = vec![];
+        let mut nodes = vec![];
+        let mut receivers = vec![];
+        for (peer, key) in peers.iter().zip(keys) {
+            // each node dials the nodes before it
+            let seed_peers = peers
+                .iter()
+                .zip(&addrs)
+                .map(|(other, addr)| (*other, vec![addr.clone()]))
+                .collect();
+            let mut network_builder = NetworkBuilder::new(
+                runtime.handle().clone(),
+                *peer,
+                RoleType::Validator,
+                "/memory/0".parse().unwrap(),
+            );
+            network_builder
+                .authentication_mode(AuthenticationMode::Mutual(key))
+                .trusted_peers(trusted_peers.clone())
+                .seed_peers(seed_peers)
+                .simulated_transport(simulator.add_node())
+                .add_connectivity_manager();
+            let (network_sender, network_events) =
+                add_to_network::<TestPayload>(&mut network_builder);
+            addrs.push(network_builder.build().unwrap());
+
+            let (self_sender, self_receiver) = channel::new_test(8);
+            let (task, receiver) =
+                NetworkTask::new(network_events, network_sender.clone(), self_receiver);
+            runtime.handle().spawn(task.start());
+            nodes.push(NetworkSender::new(
+                *peer,
+                network_sender,
+                self_sender,
+                validator_verifier.clone(),
+            ));
+            receivers.push(receiver);
+        }
+
+        let vote_msg = VoteMsg::new(
+            Vote::new(
+                VoteData::new(BlockInfo::random(1), BlockInfo::random(0)),
+                peers[1],
+                placeholder_ledger_info(),
+                &signers[1],
+            ),
+            test_utils::placeholder_sync_info(),
+        );
+        let previous_qc = certificate_for_genesis();
+        let proposal = |round| {
+            ProposalMsg::new(
+                Block::new_proposal(vec![0], round, 1, previous_qc.clone(), &signers[1], vec![]),
+                SyncInfo::new(previous_qc.clone(), previous_qc.clone(), None),
+            )
+        };
+        timed_block_on(&mut runtime, async {
+            // The clock is stepped until the nodes are connected and the vote gets through
+            loop {
+                nodes[1].send_vote(vote_msg.clone(), vec![peers[0]]).await;
+                simulator.advance(Duration::from_millis(100));
+                let msg = timeout(
+                    Duration::from_millis(100),
+                    receivers[0].consensus_messages.next(),
+                )
+                .await;
+                if let Ok(Some((_, msg))) = msg {
+                    match msg {
+                        ConsensusMsg::VoteMsg(v) => assert_eq!(*v, vote_msg),
+                        _ => panic!("unexpected messages"),
+                    }
+                    break;
+                }
+            }
+
+            // The messages are delivered after the delay of the link
+            nodes[1].broadcast_proposal(proposal(1)).await;
+            let wait = Duration::from_millis(500);
+            assert_eq!(next_proposal(&mut receivers[0], wait).await, None);
+            simulator.advance(Duration::from_millis(10));
+            assert_eq!(
+                next_proposal(&mut receivers[0], wait).await,
+                Some(proposal(1))
+            );
+
+            // Dropped messages don't break the encrypted connection
+            simulator.apply(SimulationEvent::SetLink {
+                from: 1,
+                to: 0,
+                config: LinkConfig {
+                    drop_probability: 1.0,
+                    ..link
+                },
+            });
+            nodes[1].broadcast_proposal(proposal(2)).await;
+            simulator.advance(Duration::from_millis(10));
+            assert_eq!(next_proposal(&mut receivers[0], wait).await, None);
+            simulator.apply(SimulationEvent::SetLink {
+                from: 1,
+                to: 0,
+                config: link,
+            });
+            nodes[1].broadcast_proposal(proposal(3)).await;
+            // let the proposal reach the connection before the clock moves
+            assert_eq!(next_proposal(&mut receivers[0], wait).await, None);
+            simulator.advance(Duration::from_millis(10));
+            assert_eq!(
+                next_proposal(&mut receivers[0], wait).await,
+                Some(proposal(3))
+            );
+        });
+    }
 }
@@ -23,7 +23,8 @@ use storage_interface::DbReader;
 /// upon a restart, a correct node will recover.  Even if all nodes crash, liveness is
 /// guaranteed.
 /// Blocks persisted are proposed but not yet committed.  The committed state is persisted
-/// via StateComputer.
+/// via StateComputer.  With decoupled execution, the blocks committed by the ordering stay until
+/// their execution is committed.
 pub trait PersistentLivenessStorage<T>: Send + Sync {
     /// Persist the blocks and quorum certs into storage atomically.
     fn save_tree(&self, blocks: Vec<Block<T>>, quorum_certs: Vec<QuorumCert>) -> Result<()>;
@@ -113,9 +114,25 @@ impl LedgerRecoveryData {
             .find(|qc| qc.certified_block().id() == root_block.id())
             .ok_or_else(|| format_err!("No QC found for root: {}", root_id))?
             .clone();
+        // With decoupled execution storage commits the root once it is executed, which may be in
+        // the middle of a batch committed by the ordering: the LI is then the ordering commit of
+        // a descendant, the blocks up to it are committed again to execute them.
         let root_ledger_info = quorum_certs
             .iter()
             .find(|qc| qc.commit_info().id() == root_block.id())
+            .or_else(|| {
+                if !root_quorum_cert.certified_block().is_ordered_only() {
+                    return None;
+                }
+                quorum_certs
+                    .iter()
+                    .filter(|qc| {
+                        qc.commit_info().is_ordered_only()
+                            && qc.commit_info().epoch() == root_block.epoch()
+                            && qc.commit_info().round() > root_block.round()
+                    })
+                    .min_by_key(|qc| qc.commit_info().round())
+            })
             .ok_or_else(|| format_err!("No LI found for root: {}", root_id))?
             .clone();
 
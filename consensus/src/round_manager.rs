@@ -190,6 +190,8 @@ pub struct RoundManager<T> {
     txn_manager: Box<dyn TxnManager<Payload = T>>,
     storage: Arc<dyn PersistentLivenessStorage<T>>,
     time_service: Arc<dyn TimeService>,
+    // Blocks are only ordered, their execution result is voted on by the execution pipeline
+    decoupled_execution: bool,
 }
 
 impl<T: Payload> RoundManager<T> {
@@ -204,6 +206,7 @@ impl<T: Payload> RoundManager<T> {
         txn_manager: Box<dyn TxnManager<Payload = T>>,
         storage: Arc<dyn PersistentLivenessStorage<T>>,
         time_service: Arc<dyn TimeService>,
+        decoupled_execution: bool,
     ) -> Self {
         counters::BLOCK_RETRIEVAL_COUNT.get();
         counters::STATE_SYNC_COUNT.get();
@@ -219,6 +222,7 @@ impl<T: Payload> RoundManager<T> {
             network,
             storage,
             time_service,
+            decoupled_execution,
         }
     }
 
@@ -272,15 +276,22 @@ impl<T: Payload> RoundManager<T> {
         &mut self,
         new_round_event: NewRoundEvent,
     ) -> anyhow::Result<ProposalMsg<T>> {
+        // The block id is only known once the proposal is generated, so the generation itself
+        // is traced by round and linked to the resulting block below.
+        let epoch = self.epoch_state.epoch;
+        let round = new_round_event.round;
+        trace_code_block!("round_manager::proposal_generation", {"round", epoch, round});
         // Proposal generator will ensure that at most one proposal is generated per round
         let proposal = self
             .proposal_generator
             .generate_proposal(
-                new_round_event.round,
+                round,
                 self.round_state.current_round_deadline(),
+                self.proposer_election.as_ref(),
             )
             .await?;
         let signed_proposal = self.safety_rules.sign_proposal(proposal)?;
+        trace_edge!("round_proposal", {"round", epoch, round}, {"block", signed_proposal.id()});
         if let Some(ref payload) = signed_proposal.payload() {
             self.txn_manager
                 .trace_transactions(payload, signed_proposal.id());
@@ -323,10 +334,12 @@ impl<T: Payload> RoundManager<T> {
             proposal_msg.round(),
             current_round,
         );
+        // Only a possible proposer of the round gets to trigger the block retrieval and the
+        // state sync, the full check follows once synced up.
         ensure!(
             self.proposer_election
-                .is_valid_proposal(proposal_msg.proposal()),
-            "[RoundManager] Proposer {} for block {} is not a valid proposer for this round",
+                .is_proposer_candidate(proposal_msg.proposer(), proposal_msg.round()),
+            "[RoundManager] Proposer {} for block {} is not a proposer candidate for this round",
             proposal_msg.proposer(),
             proposal_msg.proposal()
         );
@@ -340,6 +353,32 @@ impl<T: Payload> RoundManager<T> {
             proposal_msg.round() == current_round,
             "[RoundManager] Proposal round doesn't match current round after sync"
         );
+        // Elections based on the committed history only agree with the proposer once the
+        // history the proposal certifies is committed locally, hence the checks after sync up.
+        ensure!(
+            self.proposer_election
+                .is_valid_proposal(proposal_msg.proposal()),
+            "[RoundManager] Proposer {} for block {} is not a valid proposer for this round",
+            proposal_msg.proposer(),
+            proposal_msg.proposal()
+        );
+        let failed_authors = if self.proposer_election.records_failed_authors() {
+            let parent_round = proposal_msg
+                .proposal()
+                .quorum_cert()
+                .certified_block()
+                .round();
+            self.proposer_election
+                .get_failed_authors(proposal_msg.round(), parent_round)
+        } else {
+            vec![]
+        };
+        ensure!(
+            proposal_msg.proposal().failed_authors() == failed_authors.as_slice(),
+            "[RoundManager] Proposal {} doesn't record the failed authors {:?}",
+            proposal_msg.proposal(),
+            failed_authors
+        );
         Ok(proposal_msg.take_proposal())
     }
 
@@ -615,28 +654,34 @@ impl<T: Payload> RoundManager<T> {
         self.wait_before_vote_if_needed(block.timestamp_usecs())
             .await?;
 
-        let vote_proposal = VoteProposal::new(
-            AccumulatorExtensionProof::<TransactionAccumulatorHasher>::new(
-                parent_block.compute_result().frozen_subtree_roots().clone(),
-                parent_block.compute_result().num_leaves(),
-                executed_block
-                    .compute_result()
-                    .transaction_info_hashes()
-                    .clone(),
-            ),
-            block.clone(),
-            executed_block.compute_result().epoch_state().clone(),
-        );
+        let vote_proposal = if self.decoupled_execution {
+            VoteProposal::new_ordering_only(block.clone())
+        } else {
+            VoteProposal::new(
+                AccumulatorExtensionProof::<TransactionAccumulatorHasher>::new(
+                    parent_block.compute_result().frozen_subtree_roots().clone(),
+                    parent_block.compute_result().num_leaves(),
+                    executed_block
+                        .compute_result()
+                        .transaction_info_hashes()
+                        .clone(),
+                ),
+                block.clone(),
+                executed_block.compute_result().epoch_state().clone(),
+            )
+        };
 
-        let vote = self
-            .safety_rules
-            .construct_and_sign_vote(&vote_proposal)
-            .context(format!(
-                "[RoundManager] SafetyRules {}Rejected{} {}",
-                Fg(Red),
-                Fg(Reset),
-                block
-            ))?;
+        let vote = {
+            trace_code_block!("round_manager::sign_vote", {"block", block.id()});
+            self.safety_rules
+                .construct_and_sign_vote(&vote_proposal)
+                .context(format!(
+                    "[RoundManager] SafetyRules {}Rejected{} {}",
+                    Fg(Red),
+                    Fg(Reset),
+                    block
+                ))?
+        };
 
         let consensus_state = self.safety_rules.consensus_state()?;
         counters::LAST_VOTE_ROUND.set(consensus_state.last_voted_round() as i64);
@@ -144,6 +144,7 @@ fn create_node_for_fuzzing() -> RoundManager<TestPayload> {
         Box::new(MockTransactionManager::new(None)),
         storage,
         time_service,
+        false,
     )
 }
 
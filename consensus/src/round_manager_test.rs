@@ -4,6 +4,8 @@
 use crate::{
     block_storage::{BlockReader, BlockStore},
     liveness::{
+        failure_aware_leader_reputation::FailureAwareLeaderReputation,
+        leader_reputation::{ActiveInactiveHeuristic, LeaderReputation, MetadataBackend},
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
         rotating_proposer_election::RotatingProposer,
@@ -27,7 +29,7 @@ use consensus_types::{
         Block,
     },
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalStatus},
-    common::Author,
+    common::{Author, Round},
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
     timeout::Timeout,
@@ -42,6 +44,7 @@ use futures::{
 };
 use libra_crypto::{hash::CryptoHash, HashValue};
 use libra_types::{
+    block_metadata::NewBlockEvent,
     epoch_state::EpochState,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     validator_signer::ValidatorSigner,
@@ -202,6 +205,7 @@ impl NodeSetup {
             Box::new(MockTransactionManager::new(None)),
             storage.clone(),
             time_service,
+            false,
         );
         block_on(round_manager.start(last_vote_sent));
         Self {
@@ -314,7 +318,7 @@ fn vote_on_successful_proposal() {
         // Start round 1 and clear the message queue
         node.next_proposal().await;
 
-        let proposal = Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer);
+        let proposal = Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer, vec![]);
         let proposal_id = proposal.id();
         node.round_manager
             .process_proposed_block(proposal)
@@ -340,9 +344,9 @@ fn no_vote_on_old_proposal() {
     let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
     let node = &mut nodes[0];
     let genesis_qc = certificate_for_genesis();
-    let new_block = Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer);
+    let new_block = Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer, vec![]);
     let new_block_id = new_block.id();
-    let old_block = Block::new_proposal(vec![1], 1, 2, genesis_qc, &node.signer);
+    let old_block = Block::new_proposal(vec![1], 1, 2, genesis_qc, &node.signer, vec![]);
     let old_block_id = old_block.id();
     timed_block_on(&mut runtime, async {
         // clear the message queue
@@ -376,8 +380,10 @@ fn no_vote_on_mismatch_round() {
         .pop()
         .unwrap();
     let genesis_qc = certificate_for_genesis();
-    let correct_block = Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer);
-    let block_skip_round = Block::new_proposal(vec![1], 2, 2, genesis_qc.clone(), &node.signer);
+    let correct_block =
+        Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer, vec![]);
+    let block_skip_round =
+        Block::new_proposal(vec![1], 2, 2, genesis_qc.clone(), &node.signer, vec![]);
     timed_block_on(&mut runtime, async {
         let bad_proposal = ProposalMsg::<TestPayload>::new(
             block_skip_round,
@@ -457,13 +463,15 @@ fn no_vote_on_invalid_proposer() {
     let incorrect_proposer = nodes.pop().unwrap();
     let mut node = nodes.pop().unwrap();
     let genesis_qc = certificate_for_genesis();
-    let correct_block = Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer);
+    let correct_block =
+        Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer, vec![]);
     let block_incorrect_proposer = Block::new_proposal(
         vec![1],
         1,
         1,
         genesis_qc.clone(),
         &incorrect_proposer.signer,
+        vec![],
     );
     timed_block_on(&mut runtime, async {
         let bad_proposal = ProposalMsg::<TestPayload>::new(
@@ -501,8 +509,19 @@ fn new_round_on_timeout_certificate() {
         .pop()
         .unwrap();
     let genesis_qc = certificate_for_genesis();
-    let correct_block = Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer);
-    let block_skip_round = Block::new_proposal(vec![1], 2, 2, genesis_qc.clone(), &node.signer);
+    let correct_block =
+        Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer, vec![]);
+    let block_skip_round =
+        Block::new_proposal(vec![1], 2, 2, genesis_qc.clone(), &node.signer, vec![]);
+    // The election doesn't read failed authors back, proposals must not record any
+    let block_skip_round_failed_authors = Block::new_proposal(
+        vec![1],
+        2,
+        3,
+        genesis_qc.clone(),
+        &node.signer,
+        vec![(1, node.signer.author())],
+    );
     let timeout = Timeout::new(1, 1);
     let timeout_signature = timeout.sign(&node.signer);
 
@@ -510,6 +529,15 @@ fn new_round_on_timeout_certificate() {
     tc.add_signature(node.signer.author(), timeout_signature);
 
     timed_block_on(&mut runtime, async {
+        let failed_authors_proposal = ProposalMsg::<TestPayload>::new(
+            block_skip_round_failed_authors,
+            SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), Some(tc.clone())),
+        );
+        assert!(node
+            .round_manager
+            .pre_process_proposal(failed_authors_proposal)
+            .await
+            .is_err());
         let skip_round_proposal = ProposalMsg::<TestPayload>::new(
             block_skip_round,
             SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), Some(tc)),
@@ -533,6 +561,125 @@ fn new_round_on_timeout_certificate() {
     });
 }
 
+/// Committed history of the proposer election, limited to what the node's storage committed.
+struct CommittedHistory {
+    events: Vec<NewBlockEvent>,
+    storage: Option<Arc<MockStorage<TestPayload>>>,
+}
+
+impl MetadataBackend for CommittedHistory {
+    fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEvent> {
+        let committed_round = self.storage.as_ref().map_or(Round::max_value(), |storage| {
+            storage.get_ledger_info().round()
+        });
+        self.events
+            .iter()
+            .rev()
+            .filter(|event| event.round() <= std::cmp::min(target_round, committed_round))
+            .cloned()
+            .collect()
+    }
+}
+
+fn create_failure_aware_election(
+    proposers: Vec<Author>,
+    history: CommittedHistory,
+) -> FailureAwareLeaderReputation<TestPayload> {
+    FailureAwareLeaderReputation::new(
+        LeaderReputation::new(
+            proposers,
+            Box::new(history),
+            Box::new(ActiveInactiveHeuristic::new(9, 1)),
+        ),
+        0,
+    )
+}
+
+#[test]
+/// A node that is behind only agrees with the proposer and its failed authors once it synced up
+/// to the history the proposal certifies
+fn failed_authors_checked_after_sync_up() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2);
+    let proposer = nodes.pop().unwrap();
+    let mut node = nodes.pop().unwrap();
+    let authors = vec![node.signer.author(), proposer.signer.author()];
+    // The committed block of round 1 records the first node failing to propose
+    let events = vec![NewBlockEvent::new(
+        1,
+        proposer.signer.author(),
+        authors.clone(),
+        1,
+        vec![node.signer.author()],
+    )];
+    node.round_manager.proposer_election = Box::new(create_failure_aware_election(
+        authors.clone(),
+        CommittedHistory {
+            events: events.clone(),
+            storage: Some(node.storage.clone()),
+        },
+    ));
+    let synced_election = create_failure_aware_election(
+        authors.clone(),
+        CommittedHistory {
+            events,
+            storage: None,
+        },
+    );
+
+    // The node has the blocks but not the certificate committing the first one
+    let genesis = node.block_store.root();
+    let mut inserter =
+        TreeInserter::new_with_store(proposer.signer.clone(), node.block_store.clone());
+    let b1 = inserter.insert_block_with_qc(certificate_for_genesis(), &genesis, 1);
+    let b2 = inserter.insert_block(&b1, 2, None);
+    let b3 = inserter.insert_block(&b2, 3, None);
+    let qc_b3 = gen_test_certificate(
+        vec![&node.signer, &proposer.signer],
+        b3.block_info(),
+        b2.block_info(),
+        Some(b1.block_info()),
+    );
+    let timeout = Timeout::new(1, 11);
+    let mut tc = TimeoutCertificate::new(timeout.clone());
+    tc.add_signature(node.signer.author(), timeout.sign(&node.signer));
+    tc.add_signature(proposer.signer.author(), timeout.sign(&proposer.signer));
+
+    let round = 12;
+    let failed_authors = synced_election.get_failed_authors(round, b3.round());
+    assert_eq!(
+        synced_election.get_valid_proposer(round),
+        proposer.signer.author()
+    );
+    assert_ne!(
+        node.round_manager
+            .proposer_election
+            .get_failed_authors(round, b3.round()),
+        failed_authors
+    );
+    let proposal = Block::new_proposal(
+        vec![1],
+        round,
+        b3.timestamp_usecs() + 1,
+        qc_b3.clone(),
+        &proposer.signer,
+        failed_authors,
+    );
+    let proposal_msg =
+        ProposalMsg::<TestPayload>::new(proposal, SyncInfo::new(qc_b3.clone(), qc_b3, Some(tc)));
+    timed_block_on(&mut runtime, async {
+        assert_eq!(
+            node.round_manager
+                .pre_process_proposal(proposal_msg.clone())
+                .await
+                .unwrap(),
+            proposal_msg.take_proposal()
+        );
+    });
+    assert_eq!(node.storage.get_ledger_info().round(), b1.round());
+}
+
 #[test]
 fn response_on_block_retrieval() {
     let mut runtime = consensus_runtime();
@@ -542,7 +689,7 @@ fn response_on_block_retrieval() {
         .unwrap();
 
     let genesis_qc = certificate_for_genesis();
-    let block = Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer);
+    let block = Block::new_proposal(vec![1], 1, 1, genesis_qc.clone(), &node.signer, vec![]);
     let block_id = block.id();
     let proposal =
         ProposalMsg::<TestPayload>::new(block, SyncInfo::new(genesis_qc.clone(), genesis_qc, None));
@@ -745,7 +892,7 @@ fn sync_info_sent_on_stale_sync_info() {
     let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2);
     runtime.spawn(playground.start::<TestPayload>());
     let genesis_qc = certificate_for_genesis();
-    let block_0 = Block::new_proposal(vec![1], 1, 1, genesis_qc, &nodes[0].signer);
+    let block_0 = Block::new_proposal(vec![1], 1, 1, genesis_qc, &nodes[0].signer, vec![]);
     let parent_block_info = block_0.quorum_cert().certified_block();
     let block_0_quorum_cert = gen_test_certificate(
         vec![&nodes[0].signer, &nodes[1].signer],
@@ -843,3 +990,54 @@ fn sync_on_partial_newer_sync_info() {
         let _ = node.next_sync_info().await;
     });
 }
+
+#[test]
+/// A proposal from a validator that can't propose in the round doesn't make the node sync up
+fn proposal_from_non_candidate_not_synced() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2);
+    let other = nodes.pop().unwrap();
+    let mut node = nodes.pop().unwrap();
+    let authors = vec![node.signer.author(), other.signer.author()];
+    node.round_manager.proposer_election = Box::new(create_failure_aware_election(
+        authors,
+        CommittedHistory {
+            events: vec![],
+            storage: Some(node.storage.clone()),
+        },
+    ));
+
+    let genesis = node.block_store.root();
+    let mut inserter = TreeInserter::new_with_store(other.signer.clone(), node.block_store.clone());
+    let b1 = inserter.insert_block_with_qc(certificate_for_genesis(), &genesis, 1);
+    let b2 = inserter.insert_block(&b1, 2, None);
+    let b3 = inserter.insert_block(&b2, 3, None);
+    let qc_b3 = gen_test_certificate(
+        vec![&node.signer, &other.signer],
+        b3.block_info(),
+        b2.block_info(),
+        Some(b1.block_info()),
+    );
+
+    let outsider = ValidatorSigner::random([7u8; 32]);
+    let proposal = Block::new_proposal(
+        vec![1],
+        4,
+        b3.timestamp_usecs() + 1,
+        qc_b3.clone(),
+        &outsider,
+        vec![],
+    );
+    let proposal_msg =
+        ProposalMsg::<TestPayload>::new(proposal, SyncInfo::new(qc_b3.clone(), qc_b3, None));
+    timed_block_on(&mut runtime, async {
+        assert!(node
+            .round_manager
+            .pre_process_proposal(proposal_msg)
+            .await
+            .is_err());
+    });
+    // the certificate committing b1 was not processed
+    assert_eq!(node.storage.get_ledger_info().round(), 0);
+}
@@ -4,6 +4,7 @@
 use crate::{counters, state_replication::StateComputer};
 use anyhow::{ensure, Error, Result};
 use consensus_types::block::Block;
+use debug_interface::prelude::*;
 use executor_types::{BlockExecutor, StateComputeResult};
 use libra_crypto::HashValue;
 use libra_logger::prelude::*;
@@ -38,7 +39,7 @@ impl ExecutionProxy {
     }
 
     fn transactions_from_block(block: &Block<Vec<SignedTransaction>>) -> Vec<Transaction> {
-        let mut transactions = vec![Transaction::BlockMetadata(block.into())];
+        let mut transactions = vec![Transaction::block_metadata(block.into())];
         transactions.extend(
             block
                 .payload()
@@ -61,6 +62,7 @@ impl StateComputer for ExecutionProxy {
         // The parent block id.
         parent_block_id: HashValue,
     ) -> Result<StateComputeResult> {
+        trace_code_block!("state_computer::compute", {"block", block.id()});
         let pre_execution_instant = Instant::now();
         debug!(
             "Executing block {:x}. Parent: {:x}.",
@@ -102,6 +104,7 @@ impl StateComputer for ExecutionProxy {
     ) -> Result<()> {
         let version = finality_proof.ledger_info().version();
         counters::LAST_COMMITTED_VERSION.set(version as i64);
+        trace_code_block!("state_computer::commit", {"block", finality_proof.ledger_info().consensus_block_id()});
 
         let pre_commit_instant = Instant::now();
 
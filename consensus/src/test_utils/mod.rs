@@ -185,7 +185,14 @@ impl TreeInserter {
         round: Round,
         payload: TestPayload,
     ) -> Block<TestPayload> {
-        Block::new_proposal(payload, round, timestamp_usecs, parent_qc, &self.signer)
+        Block::new_proposal(
+            payload,
+            round,
+            timestamp_usecs,
+            parent_qc,
+            &self.signer,
+            vec![],
+        )
     }
 
     pub fn insert_reconfiguration_block(
@@ -16,16 +16,17 @@ use channel::{self, libra_channel, message_queues::QueueStyle};
 use consensus_types::block::Block;
 use futures::channel::mpsc;
 use libra_config::{
-    config::{
-        ConsensusProposerType::{self, RotatingProposer},
-        NodeConfig,
-    },
+    config::NodeConfig,
     generator::{self, ValidatorSwarm},
 };
 use libra_mempool::mocks::MockSharedMempool;
 use libra_types::{
     ledger_info::LedgerInfoWithSignatures,
-    on_chain_config::{OnChainConfig, OnChainConfigPayload, ValidatorSet},
+    on_chain_config::{
+        ConsensusConfigV1, OnChainConfig, OnChainConfigPayload, OnChainConsensusConfig,
+        ProposerElectionType::{self, RotatingProposer},
+        ValidatorSet,
+    },
     validator_info::ValidatorInfo,
     waypoint::Waypoint,
 };
@@ -52,6 +53,7 @@ impl SMRNode {
         mut config: NodeConfig,
         smr_id: usize,
         storage: Arc<MockStorage<TestPayload>>,
+        onchain_consensus_config: OnChainConsensusConfig,
     ) -> Self {
         let author = config.validator_network.as_ref().unwrap().peer_id;
 
@@ -89,6 +91,11 @@ impl SMRNode {
             ValidatorSet::CONFIG_ID,
             lcs::to_bytes(storage.get_validator_set()).unwrap(),
         );
+        configs.insert(
+            OnChainConsensusConfig::CONFIG_ID,
+            // Vec<u8> of the serialized config, as stored on chain
+            lcs::to_bytes(&lcs::to_bytes(&onchain_consensus_config).unwrap()).unwrap(),
+        );
         let payload = OnChainConfigPayload::new(1, Arc::new(configs));
         reconfig_sender.push((), payload).unwrap();
         let (self_sender, self_receiver) = channel::new_test(1_024);
@@ -102,6 +109,8 @@ impl SMRNode {
             .unwrap();
         let time_service = Arc::new(SimulatedTimeService::new());
 
+        let (network_task, network_receiver) =
+            NetworkTask::new(network_events, network_sender.clone(), self_receiver);
         let epoch_mgr = EpochManager::new(
             &mut config,
             time_service,
@@ -112,7 +121,6 @@ impl SMRNode {
             state_computer,
             storage.clone(),
         );
-        let (network_task, network_receiver) = NetworkTask::new(network_events, self_receiver);
 
         runtime.spawn(network_task.start());
         runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver, reconfig_events));
@@ -130,7 +138,7 @@ impl SMRNode {
     fn start_num_nodes(
         num_nodes: usize,
         playground: &mut NetworkPlayground,
-        proposer_type: ConsensusProposerType,
+        proposer_type: ProposerElectionType,
     ) -> Vec<Self> {
         let ValidatorSwarm { nodes } = generator::validator_swarm_for_testing(num_nodes);
         let validator_set = ValidatorSet::new(
@@ -160,11 +168,19 @@ impl SMRNode {
             let waypoint = Waypoint::new_epoch_boundary(&storage.get_ledger_info())
                 .expect("Unable to produce waypoint with the provided LedgerInfo");
             config.base.waypoint = Some(waypoint);
-            config.consensus.proposer_type = proposer_type;
+            let onchain_consensus_config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
+                proposer_election_type: proposer_type.clone(),
+            });
             // Use in memory storage for testing
             // node_config.consensus.safety_rules = SafetyRulesConfig::default();
 
-            smr_nodes.push(Self::start(playground, config, smr_id, storage));
+            smr_nodes.push(Self::start(
+                playground,
+                config,
+                smr_id,
+                storage,
+                onchain_consensus_config,
+            ));
         }
         smr_nodes
     }
@@ -174,7 +190,7 @@ impl SMRNode {
 fn basic_start_test() {
     let mut runtime = consensus_runtime();
     let mut playground = NetworkPlayground::new(runtime.handle().clone());
-    let nodes = SMRNode::start_num_nodes(4, &mut playground, RotatingProposer);
+    let nodes = SMRNode::start_num_nodes(4, &mut playground, RotatingProposer(2));
     let genesis = Block::<TestPayload>::make_genesis_block_from_ledger_info(
         &nodes[0].storage.get_ledger_info(),
     );
@@ -348,7 +348,8 @@ where
                     // maybe other writeset transactions).
                     match transaction {
                         Transaction::WaypointWriteSet(_) => (),
-                        Transaction::BlockMetadata(_) => {
+                        Transaction::BlockMetadata(_)
+                        | Transaction::BlockMetadataWithFailedProposers(_) => {
                             bail!("Write set should be a subset of read set.")
                         }
                         Transaction::UserTransaction(txn) => match txn.payload() {
@@ -397,7 +397,8 @@ fn test_get_transactions() {
             }
 
             match tx {
-                Transaction::BlockMetadata(t) => match view.transaction {
+                Transaction::BlockMetadata(t)
+                | Transaction::BlockMetadataWithFailedProposers(t) => match view.transaction {
                     TransactionDataView::BlockMetadata { timestamp_usecs } => {
                         assert_eq!(t.clone().into_inner().unwrap().1, timestamp_usecs);
                     }
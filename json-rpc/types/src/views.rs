@@ -380,7 +380,7 @@ impl ScriptView {
 impl From<Transaction> for TransactionDataView {
     fn from(tx: Transaction) -> Self {
         let x = match tx {
-            Transaction::BlockMetadata(t) => {
+            Transaction::BlockMetadata(t) | Transaction::BlockMetadataWithFailedProposers(t) => {
                 t.into_inner().map(|x| TransactionDataView::BlockMetadata {
                     timestamp_usecs: x.1,
                 })
@@ -1735,6 +1735,13 @@ fn compile_bytecode(
             let constant = compile_constant(context, MoveTypeLayout::Address, address_value)?;
             Bytecode::LdConst(context.constant_index(constant)?)
         }
+        IRBytecode_::LdConst(ty, v) => {
+            let type_ = compile_type(context, function_frame.type_parameters(), &ty)?;
+            let data = v
+                .simple_serialize()
+                .ok_or_else(|| format_err!("Could not serialize constant"))?;
+            Bytecode::LdConst(context.constant_index(Constant { type_, data })?)
+        }
         IRBytecode_::LdTrue => Bytecode::LdTrue,
         IRBytecode_::LdFalse => Bytecode::LdFalse,
         IRBytecode_::CopyLoc(sp!(_, v_)) => Bytecode::CopyLoc(function_frame.get_local(&v_)?),
@@ -244,16 +244,17 @@ impl FakeExecutor {
             self.block_time,
             vec![],
             *validator_set.payload()[0].account_address(),
+            vec![],
         );
         let output = self
-            .execute_transaction_block(vec![Transaction::BlockMetadata(new_block)])
+            .execute_transaction_block(vec![Transaction::block_metadata(new_block)])
             .expect("Executing block prologue should succeed")
             .pop()
             .expect("Failed to get the execution result for Block Prologue");
         // check if we emit the expected event, there might be more events for transaction fees
         let event = output.events()[0].clone();
         assert!(event.key() == &new_block_event_key());
-        assert!(lcs::from_bytes::<NewBlockEvent>(event.event_data()).is_ok());
+        assert!(NewBlockEvent::try_from_bytes(event.event_data()).is_ok());
         self.apply_write_set(output.write_set());
     }
 }
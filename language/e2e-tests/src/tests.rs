@@ -23,4 +23,5 @@ mod scripts;
 mod transaction_builder;
 mod validator_set_management;
 mod verify_txn;
+mod vm_tracing;
 mod write_set;
@@ -1,9 +1,17 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{account::Account, executor::FakeExecutor, gas_costs::TXN_RESERVED};
+use crate::{
+    account::Account, executor::FakeExecutor, gas_costs::TXN_RESERVED, transaction_status_eq,
+};
 use libra_types::{
-    account_config::LBR_NAME, on_chain_config::LibraVersion, transaction::TransactionArgument,
+    account_config::LBR_NAME,
+    on_chain_config::{
+        config_address, new_epoch_event_key, ConsensusConfigV1, LeaderReputationConfig,
+        LibraVersion, OnChainConfig, OnChainConsensusConfig, ProposerElectionType,
+    },
+    transaction::{TransactionArgument, TransactionStatus},
+    vm_error::{StatusCode, VMStatus},
 };
 use libra_vm::LibraVM;
 use stdlib::transaction_scripts::StdlibScript;
@@ -38,3 +46,96 @@ fn initial_libra_version() {
         LibraVersion { major: 2 }
     );
 }
+
+#[test]
+fn update_consensus_config() {
+    // The staged stdlib predates LibraConsensusConfig
+    let mut executor = FakeExecutor::from_fresh_genesis();
+    assert_eq!(
+        OnChainConsensusConfig::fetch_config(executor.get_state_view()),
+        Some(OnChainConsensusConfig::default())
+    );
+
+    let config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
+        proposer_election_type: ProposerElectionType::RotatingProposer(3),
+    });
+    let account = Account::new_genesis_account(config_address());
+    let txn = account.create_signed_txn_with_args(
+        StdlibScript::UpdateConsensusConfig
+            .compiled_bytes()
+            .into_vec(),
+        vec![],
+        vec![TransactionArgument::U8Vector(
+            lcs::to_bytes(&config).unwrap(),
+        )],
+        0,
+        TXN_RESERVED,
+        0,
+        LBR_NAME.to_owned(),
+    );
+    executor.new_block();
+    let output = executor.execute_and_apply(txn);
+
+    // The validators switch to the new proposer election at the epoch started by the update
+    assert!(output
+        .events()
+        .iter()
+        .any(|event| event.key() == &new_epoch_event_key()));
+    assert_eq!(
+        OnChainConsensusConfig::fetch_config(executor.get_state_view()),
+        Some(config)
+    );
+}
+
+#[test]
+fn update_consensus_config_rejects_invalid_config() {
+    let mut executor = FakeExecutor::from_fresh_genesis();
+    let account = Account::new_genesis_account(config_address());
+    let zero_weights = ProposerElectionType::LeaderReputation(LeaderReputationConfig {
+        active_weights: 0,
+        inactive_weights: 0,
+        window_num_validators_multiplier: 1,
+    });
+    let configs = vec![
+        // Malformed: not an OnChainConsensusConfig
+        (vec![1, 2, 3], 2),
+        (
+            lcs::to_bytes(&OnChainConsensusConfig::V1(ConsensusConfigV1 {
+                proposer_election_type: ProposerElectionType::RotatingProposer(0),
+            }))
+            .unwrap(),
+            3,
+        ),
+        (
+            lcs::to_bytes(&OnChainConsensusConfig::V1(ConsensusConfigV1 {
+                proposer_election_type: zero_weights,
+            }))
+            .unwrap(),
+            3,
+        ),
+    ];
+    for (config, abort_code) in configs {
+        let txn = account.create_signed_txn_with_args(
+            StdlibScript::UpdateConsensusConfig
+                .compiled_bytes()
+                .into_vec(),
+            vec![],
+            vec![TransactionArgument::U8Vector(config)],
+            0,
+            TXN_RESERVED,
+            0,
+            LBR_NAME.to_owned(),
+        );
+        let output = executor.execute_transaction(txn);
+        assert!(transaction_status_eq(
+            &output.status(),
+            &TransactionStatus::Keep(
+                VMStatus::new(StatusCode::ABORTED).with_sub_status(abort_code)
+            )
+        ));
+    }
+    assert_eq!(
+        OnChainConsensusConfig::fetch_config(executor.get_state_view()),
+        Some(OnChainConsensusConfig::default())
+    );
+}
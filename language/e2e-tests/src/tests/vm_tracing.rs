@@ -0,0 +1,168 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_types::transaction::Transaction;
+use libra_vm::LibraVM;
+use move_vm_runtime::{
+    gas_profiler::GasProfiler,
+    tracer::{GlobalAccessKind, TraceEvent, VMTracer},
+};
+use std::sync::{Arc, Mutex};
+
+/// The events of a trace, without their values
+#[derive(Debug, PartialEq)]
+enum Event {
+    Call(String),
+    Return(String),
+    Instruction(String, u16),
+    GasCharged(u64),
+    GlobalAccess(GlobalAccessKind),
+    Error,
+}
+
+#[derive(Default)]
+struct Recorder(Mutex<Vec<Event>>);
+
+impl VMTracer for Recorder {
+    fn trace(&self, event: &TraceEvent) {
+        let event = match event {
+            TraceEvent::Call { function, .. } => Event::Call(function.to_string()),
+            TraceEvent::Return { function, .. } => Event::Return(function.to_string()),
+            TraceEvent::Instruction { function, pc, .. } => {
+                Event::Instruction(function.to_string(), *pc)
+            }
+            TraceEvent::GasCharged { amount, .. } => Event::GasCharged(*amount),
+            TraceEvent::GlobalAccess { kind, .. } => Event::GlobalAccess(*kind),
+            TraceEvent::Error { .. } => Event::Error,
+        };
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+#[test]
+fn traced_peer_to_peer() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+
+    let output = executor.execute_transaction(txn.clone());
+
+    let recorder = Arc::new(Recorder::default());
+    let mut vm = LibraVM::new_with_tracer(recorder.clone());
+    let traced_output = vm
+        .execute_transactions(
+            vec![Transaction::UserTransaction(txn)],
+            executor.get_state_view(),
+        )
+        .unwrap()
+        .pop()
+        .unwrap();
+
+    // Tracing doesn't change the execution
+    assert_eq!(traced_output.status(), output.status());
+    assert_eq!(traced_output.gas_used(), output.gas_used());
+    assert_eq!(traced_output.write_set(), output.write_set());
+
+    let events = recorder.0.lock().unwrap();
+    let script = "Script::main".to_string();
+    assert!(events.contains(&Event::Call(script.clone())));
+    assert!(events.contains(&Event::Instruction(script.clone(), 0)));
+    assert!(events.contains(&Event::Return(script)));
+    assert!(events.iter().any(|event| match event {
+        Event::Call(function) =>
+            function.ends_with("::LibraAccount::pay_from_sender_with_metadata"),
+        _ => false,
+    }));
+    assert!(events
+        .iter()
+        .any(|event| event == &Event::GlobalAccess(GlobalAccessKind::BorrowGlobal)));
+
+    // Calls and returns are balanced
+    let calls = events
+        .iter()
+        .filter(|event| matches!(event, Event::Call(_)))
+        .count();
+    let returns = events
+        .iter()
+        .filter(|event| matches!(event, Event::Return(_)))
+        .count();
+    assert_eq!(calls, returns);
+    assert!(!events.contains(&Event::Error));
+
+    // The gas charged by instructions is part of the gas used, along with the intrinsic gas
+    let gas_charged: u64 = events
+        .iter()
+        .map(|event| match event {
+            Event::GasCharged(amount) => *amount,
+            _ => 0,
+        })
+        .sum();
+    assert!(gas_charged > 0);
+    assert!(gas_charged <= output.gas_used());
+}
+
+#[test]
+fn gas_profile_peer_to_peer() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+
+    let profiler = GasProfiler::new();
+    let mut vm = LibraVM::new_with_tracer(profiler.clone());
+    let output = vm
+        .execute_transactions(
+            vec![Transaction::UserTransaction(txn)],
+            executor.get_state_view(),
+        )
+        .unwrap()
+        .pop()
+        .unwrap();
+    let profile = profiler.profile();
+
+    assert!(profile.total_gas > 0);
+    assert!(profile.total_gas <= output.gas_used());
+    assert_eq!(profile.stacks.values().sum::<u64>(), profile.total_gas);
+    assert_eq!(
+        profile
+            .functions
+            .values()
+            .map(|function| function.self_gas)
+            .sum::<u64>(),
+        profile.total_gas
+    );
+    assert_eq!(
+        profile
+            .instructions
+            .values()
+            .map(|instruction| instruction.gas)
+            .sum::<u64>(),
+        profile.total_gas
+    );
+
+    // The prologue and epilogue are not charged, so all the gas is charged under the script
+    let main = &profile.functions["Script::main"];
+    assert_eq!(main.calls, 1);
+    assert_eq!(main.total_gas, profile.total_gas);
+    let (name, pay) = profile
+        .functions
+        .iter()
+        .find(|(name, _)| name.ends_with("::LibraAccount::pay_from_sender_with_metadata"))
+        .unwrap();
+    assert!(pay.total_gas > pay.self_gas);
+    assert!(profile
+        .stacks
+        .keys()
+        .any(|stack| stack.len() > 2 && &stack[0] == "Script::main" && &stack[1] == name));
+
+    let mut folded = vec![];
+    profile.output_folded(&mut folded).unwrap();
+    let folded = String::from_utf8(folded).unwrap();
+    assert!(folded.lines().all(|line| line.starts_with("Script::main")));
+}
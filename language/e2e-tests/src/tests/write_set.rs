@@ -4,6 +4,7 @@
 use crate::{
     account::{self, Account, AccountData},
     common_transactions::peer_to_peer_txn,
+    compile::compile_module_with_address,
     executor::FakeExecutor,
 };
 use libra_types::{
@@ -20,6 +21,7 @@ use move_core_types::{
     language_storage::{ResourceKey, StructTag},
 };
 use transaction_builder::encode_mint_lbr_to_address_script;
+use vm::CompiledModule;
 
 #[test]
 fn invalid_write_set_sender() {
@@ -311,3 +313,113 @@ fn transfer_and_execute_writeset() {
 
     executor.apply_write_set(output.write_set());
 }
+
+fn module_blob(account: &AccountData, code: &str) -> Vec<u8> {
+    match compile_module_with_address(account.address(), "file_name", code) {
+        TransactionPayload::Module(module) => module.into_inner(),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn module_upgrade_writesets() {
+    // create a FakeExecutor with a genesis from file
+    let mut executor = FakeExecutor::from_genesis_file();
+    let genesis_account = Account::new_association();
+    executor.new_block();
+
+    let account = AccountData::new(1000, 10);
+    executor.add_account_data(&account);
+    let module = CompiledModule::deserialize(&module_blob(
+        &account,
+        "
+        module M {
+            struct T { x: u64 }
+            public f(): u64 {
+                return 0;
+            }
+        }
+        ",
+    ))
+    .unwrap();
+    let path = AccessPath::code_access_path(&module.self_id());
+    executor.add_module(&module.self_id(), &module);
+
+    // (1) The WriteSet adds a field to a published struct, will be dropped.
+    let upgrade = module_blob(
+        &account,
+        "
+        module M {
+            struct T { x: u64, y: u64 }
+            public f(): u64 {
+                return 0;
+            }
+        }
+        ",
+    );
+    let write_set = WriteSetMut::new(vec![(path.clone(), WriteOp::Value(upgrade))])
+        .freeze()
+        .unwrap();
+    let writeset_txn = genesis_account.create_signed_txn_impl(
+        *genesis_account.address(),
+        TransactionPayload::WriteSet(ChangeSet::new(write_set, vec![])),
+        1,
+        100_000,
+        0,
+        LBR_NAME.to_owned(),
+    );
+
+    let output = executor.execute_transaction(writeset_txn.clone());
+    let status = output.status();
+    assert!(status.is_discarded());
+    assert_eq!(
+        status.vm_status().major_status,
+        StatusCode::INVALID_WRITE_SET
+    );
+    assert_eq!(
+        executor
+            .verify_transaction(writeset_txn)
+            .status()
+            .unwrap()
+            .major_status,
+        StatusCode::INVALID_WRITE_SET
+    );
+
+    // (2) The WriteSet adds a public function and changes the code of another one, it's
+    // compatible with the published module.
+    let upgrade = module_blob(
+        &account,
+        "
+        module M {
+            struct T { x: u64 }
+            public f(): u64 {
+                return 1;
+            }
+            public g() {
+                return;
+            }
+        }
+        ",
+    );
+    let write_set = WriteSetMut::new(vec![(path.clone(), WriteOp::Value(upgrade.clone()))])
+        .freeze()
+        .unwrap();
+    let writeset_txn = genesis_account.create_signed_txn_impl(
+        *genesis_account.address(),
+        TransactionPayload::WriteSet(ChangeSet::new(write_set, vec![])),
+        1,
+        100_000,
+        0,
+        LBR_NAME.to_owned(),
+    );
+
+    let output = executor.execute_transaction(writeset_txn.clone());
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::new(StatusCode::EXECUTED))
+    );
+    assert!(executor.verify_transaction(writeset_txn).status().is_none());
+
+    executor.apply_write_set(output.write_set());
+    assert_eq!(executor.read_from_access_path(&path), Some(upgrade));
+}
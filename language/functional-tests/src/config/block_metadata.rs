@@ -89,7 +89,14 @@ pub fn build_block_metadata(config: &GlobalConfig, entries: &[Entry]) -> Result<
     }
     if let (Some(t), Some(addr)) = (timestamp, proposer) {
         // TODO: Add parser for hash value and vote maps.
-        Ok(BlockMetadata::new(HashValue::zero(), 0, *t, vec![], addr))
+        Ok(BlockMetadata::new(
+            HashValue::zero(),
+            0,
+            *t,
+            vec![],
+            addr,
+            vec![],
+        ))
     } else {
         Err(ErrorKind::Other("Cannot generate block metadata".to_string()).into())
     }
@@ -544,7 +544,7 @@ pub fn eval_block_metadata(
     log: &mut EvaluationLog,
 ) -> Result<Status> {
     let outputs =
-        executor.execute_transaction_block(vec![LibraTransaction::BlockMetadata(block_metadata)]);
+        executor.execute_transaction_block(vec![LibraTransaction::block_metadata(block_metadata)]);
 
     match outputs {
         Ok(mut outputs) => {
@@ -24,7 +24,7 @@ use libra_types::{
         VMValidatorResult,
     },
     vm_error::{sub_status, StatusCode, VMStatus},
-    write_set::{WriteSet, WriteSetMut},
+    write_set::{WriteOp, WriteSet, WriteSetMut},
 };
 use move_core_types::{
     gas_schedule::{AbstractMemorySize, CostTable, GasAlgebra, GasCarrier, GasUnits},
@@ -34,6 +34,7 @@ use move_core_types::{
 use move_vm_runtime::{
     data_cache::{RemoteCache, TransactionDataCache},
     move_vm::MoveVM,
+    tracer::VMTracer,
 };
 use move_vm_types::{
     gas_schedule::{calculate_intrinsic_gas, zero_cost_schedule, CostStrategy},
@@ -41,7 +42,12 @@ use move_vm_types::{
 };
 use rayon::prelude::*;
 use std::{collections::HashSet, convert::TryFrom, sync::Arc};
-use vm::errors::{convert_prologue_runtime_error, VMResult};
+use vm::{
+    access::ModuleAccess,
+    compatibility::check_compatibility,
+    errors::{convert_prologue_runtime_error, VMResult},
+    CompiledModule,
+};
 
 #[derive(Clone)]
 /// A wrapper to make VMRuntime standalone and thread safe.
@@ -62,6 +68,17 @@ impl LibraVM {
         }
     }
 
+    /// A Libra VM reporting the execution of Move code to `tracer`, e.g. to investigate how a
+    /// committed transaction was executed.
+    pub fn new_with_tracer(tracer: Arc<dyn VMTracer>) -> Self {
+        let inner = MoveVM::new_with_tracer(tracer);
+        Self {
+            move_vm: Arc::new(inner),
+            on_chain_config: None,
+            version: None,
+        }
+    }
+
     pub fn init_with_config(version: LibraVersion, on_chain_config: VMConfig) -> Self {
         let inner = MoveVM::new();
         Self {
@@ -268,11 +285,12 @@ impl LibraVM {
     fn verify_writeset(
         &self,
         remote_cache: &dyn RemoteCache,
-        _change_set: &ChangeSet,
+        change_set: &ChangeSet,
         txn_data: &TransactionMetadata,
     ) -> VMResult<()> {
         let mut data_store = TransactionDataCache::new(remote_cache);
         self.run_writeset_prologue(&mut data_store, &txn_data)?;
+        check_module_upgrades(remote_cache, change_set.write_set())?;
         Ok(())
     }
 
@@ -507,20 +525,27 @@ impl LibraVM {
         let registered_currencies =
             RegisteredCurrencies::fetch_config(remote_cache as &dyn RemoteCache)
                 .ok_or_else(|| VMStatus::new(StatusCode::MALFORMED))?;
+        let records_failed_proposers =
+            block_prologue_records_failed_proposers(remote_cache as &dyn RemoteCache)?;
 
         let gas_schedule = zero_cost_schedule();
         let mut cost_strategy = CostStrategy::transaction(&gas_schedule, txn_data.max_gas_amount());
         cost_strategy.charge_intrinsic_gas(txn_data.transaction_size())?;
         let mut data_store = TransactionDataCache::new(remote_cache);
 
-        if let Ok((round, timestamp, previous_vote, proposer)) = block_metadata.into_inner() {
-            let args = vec![
+        if let Ok((round, timestamp, previous_vote, proposer, failed_proposers)) =
+            block_metadata.into_inner()
+        {
+            let mut args = vec![
                 Value::transaction_argument_signer_reference(txn_data.sender),
                 Value::u64(round),
                 Value::u64(timestamp),
                 Value::vector_address(previous_vote),
                 Value::address(proposer),
             ];
+            if records_failed_proposers {
+                args.push(Value::vector_address(failed_proposers));
+            }
             self.move_vm.execute_function(
                 &LIBRA_BLOCK_MODULE,
                 &BLOCK_PROLOGUE,
@@ -795,6 +820,16 @@ impl LibraVM {
         )
     }
 
+    /// Executes a block of transactions with this VM. `VMExecutor::execute_block` does the same
+    /// with a new VM.
+    pub fn execute_transactions(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+    ) -> VMResult<Vec<TransactionOutput>> {
+        self.execute_block_impl(transactions, state_view)
+    }
+
     fn execute_block_impl(
         &mut self,
         transactions: Vec<Transaction>,
@@ -1063,6 +1098,63 @@ fn normalize_gas_price(
     }
 }
 
+/// Whether the published `LibraBlock::block_prologue` takes the failed proposers of the block.
+/// The prologue of a stdlib published before they were recorded takes one argument less, the
+/// signature of the published function is what decides which arguments to pass.
+fn block_prologue_records_failed_proposers(remote_cache: &dyn RemoteCache) -> VMResult<bool> {
+    let module_path = AccessPath::from(&*LIBRA_BLOCK_MODULE);
+    let blob = remote_cache.get(&module_path)?.ok_or_else(|| {
+        VMStatus::new(StatusCode::LINKER_ERROR)
+            .with_message(format!("Cannot find {:?}", *LIBRA_BLOCK_MODULE))
+    })?;
+    let module = CompiledModule::deserialize(&blob)?;
+    let prologue = module
+        .function_handles()
+        .iter()
+        .find(|handle| {
+            module.self_handle_idx() == handle.module
+                && module.identifier_at(handle.name) == BLOCK_PROLOGUE.as_ident_str()
+        })
+        .ok_or_else(|| {
+            VMStatus::new(StatusCode::LINKER_ERROR)
+                .with_message(format!("Cannot find {} in LibraBlock", *BLOCK_PROLOGUE))
+        })?;
+    // vm: &signer, round, timestamp, previous_block_votes, proposer[, failed_proposers]
+    Ok(module.signature_at(prologue.parameters).0.len() > 5)
+}
+
+/// Rejects a write set replacing a published module with a version incompatible with the data
+/// and the callers of the published one.
+fn check_module_upgrades(remote_cache: &dyn RemoteCache, write_set: &WriteSet) -> VMResult<()> {
+    for (ap, op) in write_set.iter() {
+        let new_blob = match op {
+            WriteOp::Value(blob) if ap.path.first() == Some(&AccessPath::CODE_TAG) => blob,
+            _ => continue,
+        };
+        let old_blob = match remote_cache.get(ap)? {
+            Some(blob) => blob,
+            None => continue,
+        };
+        let old = CompiledModule::deserialize(&old_blob)?;
+        let new = CompiledModule::deserialize(new_blob)?;
+        let incompatibilities = check_compatibility(&old, &new);
+        if !incompatibilities.is_empty() {
+            let incompatibilities: Vec<_> = incompatibilities
+                .iter()
+                .map(|incompatibility| incompatibility.to_string())
+                .collect();
+            return Err(
+                VMStatus::new(StatusCode::INVALID_WRITE_SET).with_message(format!(
+                    "Incompatible upgrade of {:?}: {}",
+                    old.self_id(),
+                    incompatibilities.join(", ")
+                )),
+            );
+        }
+    }
+    Ok(())
+}
+
 /// Transactions divided by transaction flow.
 /// Transaction flows are different across different types of transactions.
 pub enum TransactionBlock {
@@ -1077,7 +1169,8 @@ pub fn chunk_block_transactions(txns: Vec<Transaction>) -> Vec<TransactionBlock>
     let mut buf = vec![];
     for txn in txns {
         match txn {
-            Transaction::BlockMetadata(data) => {
+            Transaction::BlockMetadata(data)
+            | Transaction::BlockMetadataWithFailedProposers(data) => {
                 if !buf.is_empty() {
                     blocks.push(TransactionBlock::UserTransaction(buf));
                     buf = vec![];
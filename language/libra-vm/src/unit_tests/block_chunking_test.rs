@@ -11,7 +11,7 @@ fn reconstruct_transaction_vec(blocks: Vec<TransactionBlock>) -> Vec<Transaction
         match block {
             TransactionBlock::WriteSet(txn) => txns.push(Transaction::UserTransaction(*txn)),
             TransactionBlock::WaypointWriteSet(ws) => txns.push(Transaction::WaypointWriteSet(ws)),
-            TransactionBlock::BlockPrologue(ws) => txns.push(Transaction::BlockMetadata(ws)),
+            TransactionBlock::BlockPrologue(ws) => txns.push(Transaction::block_metadata(ws)),
             TransactionBlock::UserTransaction(user_txns) => {
                 assert!(!user_txns.is_empty());
                 txns.append(
@@ -7,7 +7,7 @@ use crate::{
 };
 use anyhow::Result;
 use libra_types::account_address::AccountAddress;
-use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId, value::MoveValue};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -625,6 +625,7 @@ pub enum Bytecode_ {
     CastU128,
     LdByteArray(Vec<u8>),
     LdAddr(AccountAddress),
+    LdConst(Type, MoveValue),
     LdTrue,
     LdFalse,
     CopyLoc(Var),
@@ -1777,6 +1778,7 @@ impl fmt::Display for Bytecode_ {
             Bytecode_::CastU128 => write!(f, "CastU128"),
             Bytecode_::LdByteArray(b) => write!(f, "LdByteArray 0b{}", hex::encode(b)),
             Bytecode_::LdAddr(a) => write!(f, "LdAddr {}", a),
+            Bytecode_::LdConst(ty, v) => write!(f, "LdConst<{}> {:?}", ty, v),
             Bytecode_::LdTrue => write!(f, "LdTrue"),
             Bytecode_::LdFalse => write!(f, "LdFalse"),
             Bytecode_::CopyLoc(v) => write!(f, "CopyLoc {}", v),
@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    hlir::ast::{Command, Command_, FunctionSignature, Label, SingleType, StructDefinition},
-    parser::ast::{FunctionName, FunctionVisibility, ModuleIdent, StructName, Var},
+    hlir::ast::{
+        Command, Command_, Constant, FunctionSignature, Label, SingleType, StructDefinition,
+    },
+    parser::ast::{ConstantName, FunctionName, FunctionVisibility, ModuleIdent, StructName, Var},
     shared::{ast_debug::*, unique_map::UniqueMap},
 };
 use move_ir_types::location::*;
@@ -28,6 +30,7 @@ pub struct Program {
 #[derive(Debug)]
 pub struct Script {
     pub loc: Loc,
+    pub constants: UniqueMap<ConstantName, Constant>,
     pub function_name: FunctionName,
     pub function: Function,
 }
@@ -42,6 +45,7 @@ pub struct ModuleDefinition {
     /// `dependency_order` is the topological order/rank in the dependency graph.
     pub dependency_order: usize,
     pub structs: UniqueMap<StructName, StructDefinition>,
+    pub constants: UniqueMap<ConstantName, Constant>,
     pub functions: UniqueMap<FunctionName, Function>,
 }
 
@@ -141,9 +145,14 @@ impl AstDebug for Script {
     fn ast_debug(&self, w: &mut AstWriter) {
         let Script {
             loc: _loc,
+            constants,
             function_name,
             function,
         } = self;
+        for cdef in constants {
+            cdef.ast_debug(w);
+            w.new_line();
+        }
         (function_name.clone(), function).ast_debug(w);
     }
 }
@@ -154,6 +163,7 @@ impl AstDebug for ModuleDefinition {
             is_source_module,
             dependency_order,
             structs,
+            constants,
             functions,
         } = self;
         if *is_source_module {
@@ -166,6 +176,10 @@ impl AstDebug for ModuleDefinition {
             sdef.ast_debug(w);
             w.new_line();
         }
+        for cdef in constants {
+            cdef.ast_debug(w);
+            w.new_line();
+        }
         for fdef in functions {
             fdef.ast_debug(w);
             w.new_line();
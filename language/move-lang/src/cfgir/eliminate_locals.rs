@@ -133,7 +133,7 @@ mod count {
     fn exp(context: &mut Context, parent_e: &Exp) {
         use UnannotatedExp_ as E;
         match &parent_e.exp.value {
-            E::Unit | E::Value(_) | E::UnresolvedError => (),
+            E::Unit | E::Value(_) | E::Constant(_) | E::UnresolvedError => (),
             E::Spec(_, used_locals) => {
                 used_locals.keys().for_each(|var| context.used(var, false));
             }
@@ -205,7 +205,7 @@ mod count {
             | E::Move { .. }
             | E::Borrow(_, _, _) => false,
 
-            E::Unit | E::Value(_) => true,
+            E::Unit | E::Value(_) | E::Constant(_) => true,
 
             E::Cast(e, _) => can_subst_exp_single(e),
             E::UnaryExp(op, e) => can_subst_exp_unary(op) && can_subst_exp_single(e),
@@ -370,7 +370,12 @@ mod eliminate {
                 }
             }
 
-            E::Unit | E::Value(_) | E::Spec(_, _) | E::UnresolvedError | E::BorrowLocal(_, _) => (),
+            E::Unit
+            | E::Value(_)
+            | E::Constant(_)
+            | E::Spec(_, _)
+            | E::UnresolvedError
+            | E::BorrowLocal(_, _) => (),
 
             E::ModuleCall(mcall) => exp(context, &mut mcall.arguments),
             E::Builtin(_, e)
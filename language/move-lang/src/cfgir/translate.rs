@@ -124,6 +124,7 @@ fn module(
     let is_source_module = mdef.is_source_module;
     let dependency_order = mdef.dependency_order;
     let structs = mdef.structs;
+    let constants = mdef.constants;
     let functions = mdef.functions.map(|name, f| function(context, name, f));
     (
         module_ident,
@@ -131,6 +132,7 @@ fn module(
             is_source_module,
             dependency_order,
             structs,
+            constants,
             functions,
         },
     )
@@ -149,12 +151,14 @@ fn scripts(
 fn script(context: &mut Context, hscript: H::Script) -> G::Script {
     let H::Script {
         loc,
+        constants,
         function_name,
         function: hfunction,
     } = hscript;
     let function = function(context, function_name.clone(), hfunction);
     G::Script {
         loc,
+        constants,
         function_name,
         function,
     }
@@ -3,9 +3,9 @@
 
 use crate::{
     parser::ast::{
-        BinOp, Field, FunctionName, FunctionVisibility, Kind, ModuleIdent, PragmaProperty,
-        ResourceLoc, SpecApplyPattern, SpecBlockTarget, SpecConditionKind, StructName, UnaryOp,
-        Value, Var,
+        BinOp, ConstantName, Field, FunctionName, FunctionVisibility, Kind, ModuleIdent,
+        PragmaProperty, ResourceLoc, SpecApplyPattern, SpecBlockTarget, SpecConditionKind,
+        StructName, UnaryOp, Value, Var,
     },
     shared::{ast_debug::*, unique_map::UniqueMap, *},
 };
@@ -32,6 +32,7 @@ pub struct Program {
 #[derive(Debug)]
 pub struct Script {
     pub loc: Loc,
+    pub constants: UniqueMap<ConstantName, Constant>,
     pub function_name: FunctionName,
     pub function: Function,
     pub specs: Vec<SpecBlock>,
@@ -46,6 +47,7 @@ pub struct ModuleDefinition {
     pub loc: Loc,
     pub is_source_module: bool,
     pub structs: UniqueMap<StructName, StructDefinition>,
+    pub constants: UniqueMap<ConstantName, Constant>,
     pub functions: UniqueMap<FunctionName, Function>,
     pub specs: Vec<SpecBlock>,
 }
@@ -102,6 +104,18 @@ pub struct Function {
     pub specs: BTreeMap<SpecId, SpecBlock>,
 }
 
+//**************************************************************************************************
+// Constants
+//**************************************************************************************************
+
+#[derive(PartialEq, Debug)]
+pub struct Constant {
+    pub loc: Loc,
+    pub signature: Type,
+    // Always a literal, `Value` or `InferredNum`
+    pub value: Exp,
+}
+
 //**************************************************************************************************
 // Specification Blocks
 //**************************************************************************************************
@@ -333,10 +347,15 @@ impl AstDebug for Script {
     fn ast_debug(&self, w: &mut AstWriter) {
         let Script {
             loc: _loc,
+            constants,
             function_name,
             function,
             specs,
         } = self;
+        for cdef in constants {
+            cdef.ast_debug(w);
+            w.new_line();
+        }
         (function_name.clone(), function).ast_debug(w);
         for spec in specs {
             spec.ast_debug(w);
@@ -351,6 +370,7 @@ impl AstDebug for ModuleDefinition {
             loc: _loc,
             is_source_module,
             structs,
+            constants,
             functions,
             specs,
         } = self;
@@ -363,6 +383,10 @@ impl AstDebug for ModuleDefinition {
             sdef.ast_debug(w);
             w.new_line();
         }
+        for cdef in constants {
+            cdef.ast_debug(w);
+            w.new_line();
+        }
         for fdef in functions {
             fdef.ast_debug(w);
             w.new_line();
@@ -374,6 +398,24 @@ impl AstDebug for ModuleDefinition {
     }
 }
 
+impl AstDebug for (ConstantName, &Constant) {
+    fn ast_debug(&self, w: &mut AstWriter) {
+        let (
+            name,
+            Constant {
+                loc: _loc,
+                signature,
+                value,
+            },
+        ) = self;
+        w.write(&format!("const {}:", name));
+        signature.ast_debug(w);
+        w.write(" = ");
+        value.ast_debug(w);
+        w.write(";");
+    }
+}
+
 impl AstDebug for (StructName, &StructDefinition) {
     fn ast_debug(&self, w: &mut AstWriter) {
         let (
@@ -8,8 +8,8 @@ use crate::{
         ast::{self as E, Fields, SpecId},
     },
     parser::ast::{
-        self as P, Field, FunctionName, FunctionVisibility, Kind, ModuleIdent, ModuleIdent_,
-        ModuleName, StructName, Var,
+        self as P, ConstantName, Field, FunctionName, FunctionVisibility, Kind, ModuleIdent,
+        ModuleIdent_, ModuleName, StructName, Var,
     },
     shared::{unique_map::UniqueMap, *},
 };
@@ -26,6 +26,7 @@ use std::{
 struct ModuleMembers {
     loc: Loc,
     structs: BTreeSet<Name>,
+    constants: BTreeSet<Name>,
     functions: BTreeSet<Name>,
     schemas: BTreeSet<Name>,
 }
@@ -35,19 +36,27 @@ impl ModuleMembers {
         ModuleMembers {
             loc,
             structs: BTreeSet::new(),
+            constants: BTreeSet::new(),
             functions: BTreeSet::new(),
             schemas: BTreeSet::new(),
         }
     }
 
     fn is_member(&self, name: &Name) -> bool {
-        self.is_struct(name) || self.is_function(name) || self.is_schema(name)
+        self.is_struct(name)
+            || self.is_constant(name)
+            || self.is_function(name)
+            || self.is_schema(name)
     }
 
     fn is_struct(&self, name: &Name) -> bool {
         self.structs.contains(name)
     }
 
+    fn is_constant(&self, name: &Name) -> bool {
+        self.constants.contains(name)
+    }
+
     fn is_function(&self, name: &Name) -> bool {
         self.functions.contains(name)
     }
@@ -292,6 +301,7 @@ fn module_(context: &mut Context, mdef: P::ModuleDefinition) -> (ModuleIdent, E:
 
     let mut functions = UniqueMap::new();
     let mut structs = UniqueMap::new();
+    let mut constants = UniqueMap::new();
     let mut specs = vec![];
     for member in members {
         match member {
@@ -308,6 +318,7 @@ fn module_(context: &mut Context, mdef: P::ModuleDefinition) -> (ModuleIdent, E:
                 }
                 struct_def(context, &mut structs, s)
             }
+            P::ModuleMember::Constant(c) => constant(context, &mut constants, c),
             P::ModuleMember::Spec(s) => specs.push(spec(context, s)),
         }
     }
@@ -317,6 +328,7 @@ fn module_(context: &mut Context, mdef: P::ModuleDefinition) -> (ModuleIdent, E:
         loc,
         is_source_module: context.is_source_module,
         structs,
+        constants,
         functions,
         specs,
     };
@@ -334,6 +346,7 @@ fn script_(context: &mut Context, pscript: P::Script) -> E::Script {
     let P::Script {
         loc,
         uses,
+        constants: pconstants,
         function: pfunction,
         specs: pspecs,
     } = pscript;
@@ -348,6 +361,21 @@ fn script_(context: &mut Context, pscript: P::Script) -> E::Script {
         "ICE there should be no aliases entering a script"
     );
 
+    let mut constants = UniqueMap::new();
+    for c in pconstants {
+        // Script constants are not module members, so duplicates are detected here
+        let (cname, cdef) = constant_(context, c);
+        if let Err(old_loc) = constants.add(cname.clone(), cdef) {
+            context.error(vec![
+                (
+                    cname.loc(),
+                    format!("Duplicate definition for constant '{}'", cname),
+                ),
+                (old_loc, "Previously defined here".into()),
+            ]);
+        }
+    }
+
     let (function_name, function) = function_(context, pfunction);
     if let FunctionVisibility::Public(loc) = &function.visibility {
         let msg = "Extraneous 'public' modifier. Script functions are always public";
@@ -365,6 +393,7 @@ fn script_(context: &mut Context, pscript: P::Script) -> E::Script {
 
     E::Script {
         loc,
+        constants,
         function_name,
         function,
         specs,
@@ -415,6 +444,9 @@ fn module_members(
             P::ModuleMember::Struct(s) => {
                 cur_members.structs.insert(s.name.0.clone());
             }
+            P::ModuleMember::Constant(c) => {
+                cur_members.constants.insert(c.name.0.clone());
+            }
             P::ModuleMember::Spec(sp!(_, SB { target, members, .. })) => match &target.value {
                 SBT::Schema(n, _) => {
                     cur_members.schemas.insert(n.clone());
@@ -469,6 +501,15 @@ fn aliases_from_member(
             }
             Some(P::ModuleMember::Struct(s))
         }
+        P::ModuleMember::Constant(c) => {
+            let n = c.name.0.clone();
+            if let Err(loc) =
+                acc.add_implicit_member_alias(n.clone(), current_module.clone(), n.clone())
+            {
+                duplicate_module_member(context, loc, n)
+            }
+            Some(P::ModuleMember::Constant(c))
+        }
         P::ModuleMember::Spec(s) => {
             let sp!(_, SB { target, members, .. }) = &s;
             match &target.value {
@@ -545,6 +586,7 @@ fn use_(context: &mut Context, acc: &mut AliasMap, u: P::Use) {
                 let mloc = members.loc;
                 let is_member = members.is_member(&member);
                 let is_struct = members.is_struct(&member);
+                let is_constant = members.is_constant(&member);
                 let is_schema = members.is_schema(&member);
                 let is_function = members.is_function(&member);
                 if !is_member {
@@ -569,6 +611,7 @@ fn use_(context: &mut Context, acc: &mut AliasMap, u: P::Use) {
                     Some(alias) => {
                         let (lcase, mcase) = match alias {
                             _ if is_struct => ("struct alias", "Struct alias"),
+                            _ if is_constant => ("constant alias", "Constant alias"),
                             _ if is_schema => ("schema alias", "Schema alias"),
                             _ => unreachable!("handled above"),
                         };
@@ -692,6 +735,47 @@ fn struct_fields(
     E::StructFields::Defined(field_map)
 }
 
+//**************************************************************************************************
+// Constants
+//**************************************************************************************************
+
+fn constant(
+    context: &mut Context,
+    constants: &mut UniqueMap<ConstantName, E::Constant>,
+    pconstant: P::Constant,
+) {
+    let (cname, cdef) = constant_(context, pconstant);
+    if let Err(_old_loc) = constants.add(cname, cdef) {
+        assert!(context.has_errors())
+    }
+}
+
+fn constant_(context: &mut Context, pconstant: P::Constant) -> (ConstantName, E::Constant) {
+    let P::Constant {
+        loc,
+        name,
+        signature: psignature,
+        value: pvalue,
+    } = pconstant;
+    let signature = type_(context, psignature);
+    let value = match &pvalue.value {
+        P::Exp_::Value(_) | P::Exp_::InferredNum(_) => exp_(context, pvalue),
+        _ => {
+            let msg = "Invalid constant value. Constants must be initialized with a literal: a \
+                       number, a boolean, an address, or a byte string";
+            context.error(vec![(pvalue.loc, msg)]);
+            sp(pvalue.loc, E::Exp_::UnresolvedError)
+        }
+    };
+    let cdef = E::Constant {
+        loc,
+        signature,
+        value,
+    };
+    check_valid_constant_name(context, &name);
+    (name, cdef)
+}
+
 //**************************************************************************************************
 // Functions
 //**************************************************************************************************
@@ -1569,6 +1653,12 @@ fn check_valid_struct_name(context: &mut Context, n: &StructName) {
     }
 }
 
+fn check_valid_constant_name(context: &mut Context, n: &ConstantName) {
+    if let Err(e) = check_valid_struct_constant_or_schema_name(&n.0, "constant", "Constant") {
+        context.error(e)
+    }
+}
+
 fn check_valid_schema_name(context: &mut Context, n: &Name) {
     if let Err(e) = check_valid_struct_constant_or_schema_name(n, "schema", "Schema") {
         context.error(e)
@@ -5,8 +5,8 @@ use crate::{
     expansion::ast::SpecId,
     naming::ast::{BuiltinTypeName, BuiltinTypeName_, TParam},
     parser::ast::{
-        BinOp, Field, FunctionName, FunctionVisibility, Kind, Kind_, ModuleIdent, ResourceLoc,
-        StructName, UnaryOp, Value, Var,
+        BinOp, ConstantName, Field, FunctionName, FunctionVisibility, Kind, Kind_, ModuleIdent,
+        ResourceLoc, StructName, UnaryOp, Value, Var,
     },
     shared::{ast_debug::*, unique_map::UniqueMap},
 };
@@ -32,6 +32,7 @@ pub struct Program {
 #[derive(Debug)]
 pub struct Script {
     pub loc: Loc,
+    pub constants: UniqueMap<ConstantName, Constant>,
     pub function_name: FunctionName,
     pub function: Function,
 }
@@ -46,6 +47,7 @@ pub struct ModuleDefinition {
     /// `dependency_order` is the topological order/rank in the dependency graph.
     pub dependency_order: usize,
     pub structs: UniqueMap<StructName, StructDefinition>,
+    pub constants: UniqueMap<ConstantName, Constant>,
     pub functions: UniqueMap<FunctionName, Function>,
 }
 
@@ -66,6 +68,17 @@ pub enum StructFields {
     Native(Loc),
 }
 
+//**************************************************************************************************
+// Constants
+//**************************************************************************************************
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Constant {
+    pub loc: Loc,
+    pub signature: BaseType,
+    pub value: Value,
+}
+
 //**************************************************************************************************
 // Functions
 //**************************************************************************************************
@@ -227,6 +240,7 @@ pub type BuiltinFunction = Spanned<BuiltinFunction_>;
 pub enum UnannotatedExp_ {
     Unit,
     Value(Value),
+    Constant(ConstantName),
     Move { from_user: bool, var: Var },
     Copy { from_user: bool, var: Var },
 
@@ -507,9 +521,14 @@ impl AstDebug for Script {
     fn ast_debug(&self, w: &mut AstWriter) {
         let Script {
             loc: _loc,
+            constants,
             function_name,
             function,
         } = self;
+        for cdef in constants {
+            cdef.ast_debug(w);
+            w.new_line();
+        }
         (function_name.clone(), function).ast_debug(w);
     }
 }
@@ -520,6 +539,7 @@ impl AstDebug for ModuleDefinition {
             is_source_module,
             dependency_order,
             structs,
+            constants,
             functions,
         } = self;
         if *is_source_module {
@@ -532,6 +552,10 @@ impl AstDebug for ModuleDefinition {
             sdef.ast_debug(w);
             w.new_line();
         }
+        for cdef in constants {
+            cdef.ast_debug(w);
+            w.new_line();
+        }
         for fdef in functions {
             fdef.ast_debug(w);
             w.new_line();
@@ -569,6 +593,24 @@ impl AstDebug for (StructName, &StructDefinition) {
     }
 }
 
+impl AstDebug for (ConstantName, &Constant) {
+    fn ast_debug(&self, w: &mut AstWriter) {
+        let (
+            name,
+            Constant {
+                loc: _loc,
+                signature,
+                value,
+            },
+        ) = self;
+        w.write(&format!("const {}:", name));
+        signature.ast_debug(w);
+        w.write(" = ");
+        value.ast_debug(w);
+        w.write(";");
+    }
+}
+
 impl AstDebug for (FunctionName, &Function) {
     fn ast_debug(&self, w: &mut AstWriter) {
         let (
@@ -817,6 +859,7 @@ impl AstDebug for UnannotatedExp_ {
         match self {
             E::Unit => w.write("()"),
             E::Value(v) => v.ast_debug(w),
+            E::Constant(c) => w.write(&format!("{}", c)),
             E::Move {
                 from_user: false,
                 var: v,
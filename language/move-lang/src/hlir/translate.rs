@@ -6,7 +6,9 @@ use crate::{
     expansion::ast::Fields,
     hlir::ast::{self as H, Block},
     naming::ast as N,
-    parser::ast::{BinOp_, Field, FunctionName, Kind_, ModuleIdent, StructName, Value_, Var},
+    parser::ast::{
+        BinOp_, ConstantName, Field, FunctionName, Kind_, ModuleIdent, StructName, Value_, Var,
+    },
     shared::{unique_map::UniqueMap, *},
     typing::ast as T,
 };
@@ -179,6 +181,8 @@ fn module(
 
     let structs = mdef.structs.map(|name, s| struct_def(context, name, s));
 
+    let constants = mdef.constants.map(|name, c| constant(context, name, c));
+
     context.add_struct_fields(&structs);
     let functions = mdef.functions.map(|name, f| function(context, name, f));
     context.structs = UniqueMap::new();
@@ -189,6 +193,7 @@ fn module(
             is_source_module,
             dependency_order,
             structs,
+            constants,
             functions,
         },
     )
@@ -207,17 +212,42 @@ fn scripts(
 fn script(context: &mut Context, tscript: T::Script) -> H::Script {
     let T::Script {
         loc,
+        constants: tconstants,
         function_name,
         function: tfunction,
     } = tscript;
+    let constants = tconstants.map(|name, c| constant(context, name, c));
     let function = function(context, function_name.clone(), tfunction);
     H::Script {
         loc,
+        constants,
         function_name,
         function,
     }
 }
 
+//**************************************************************************************************
+// Constants
+//**************************************************************************************************
+
+fn constant(context: &mut Context, _name: ConstantName, c: T::Constant) -> H::Constant {
+    let T::Constant {
+        loc,
+        signature: tsignature,
+        value: tvalue,
+    } = c;
+    let signature = base_type(context, tsignature);
+    let value = match tvalue.exp.value {
+        T::UnannotatedExp_::Value(v) => v,
+        _ => panic!("ICE constant values should be literals after typing"),
+    };
+    H::Constant {
+        loc,
+        signature,
+        value,
+    }
+}
+
 //**************************************************************************************************
 // Functions
 //**************************************************************************************************
@@ -809,6 +839,7 @@ fn exp_impl(context: &mut Context, result: &mut Block, e: T::Exp) -> H::Exp {
         // All other expressiosn
         TE::Unit => HE::Unit,
         TE::Value(v) => HE::Value(v),
+        TE::Constant(c) => HE::Constant(c),
         TE::InferredNum(_) => panic!("ICE unexpanded inferred num"),
         TE::Move { from_user, var } => HE::Move {
             from_user,
@@ -1371,7 +1402,11 @@ fn bind_for_short_circuit(e: &T::Exp) -> bool {
     use T::UnannotatedExp_ as TE;
     match &e.exp.value {
         TE::Use(_) | TE::InferredNum(_) => panic!("ICE should have been expanded"),
-        TE::Value(_) | TE::Move { .. } | TE::Copy { .. } | TE::UnresolvedError => false,
+        TE::Value(_)
+        | TE::Constant(_)
+        | TE::Move { .. }
+        | TE::Copy { .. }
+        | TE::UnresolvedError => false,
 
         // TODO might want to case ModuleCall for fake natives
         TE::ModuleCall(_) => true,
@@ -19,22 +19,26 @@ pub mod shared;
 pub mod test_utils;
 mod to_bytecode;
 pub mod typing;
+pub mod unit_test;
 
 use anyhow::anyhow;
 use codespan::{ByteIndex, Span};
 use compiled_unit::CompiledUnit;
 use errors::*;
 use move_ir_types::location::*;
+use once_cell::sync::Lazy;
 use parser::syntax::parse_file_string;
 use shared::Address;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     fs::File,
     io::{Read, Write},
     iter::Peekable,
     path::{Path, PathBuf},
     str::Chars,
+    sync::Mutex,
 };
+use unit_test::TestPlan;
 
 pub const MOVE_EXTENSION: &str = "move";
 pub const MOVE_COMPILED_EXTENSION: &str = "mv";
@@ -67,7 +71,7 @@ pub fn move_check_no_report(
     deps: &[String],
     sender_opt: Option<Address>,
 ) -> anyhow::Result<(FilesSourceText, Errors)> {
-    let (files, pprog_and_comments_res) = parse_program(targets, deps)?;
+    let (files, pprog_and_comments_res) = parse_program(targets, deps, sender_opt)?;
     let pprog_res = pprog_and_comments_res.map(|(pprog, _)| pprog);
     match check_program(pprog_res, sender_opt) {
         Err(errors) => Ok((files, errors)),
@@ -85,7 +89,7 @@ pub fn move_compile(
     deps: &[String],
     sender_opt: Option<Address>,
 ) -> anyhow::Result<(FilesSourceText, Vec<CompiledUnit>)> {
-    let (files, pprog_and_comments_res) = parse_program(targets, deps)?;
+    let (files, pprog_and_comments_res) = parse_program(targets, deps, sender_opt)?;
     let pprog_res = pprog_and_comments_res.map(|(pprog, _)| pprog);
     match compile_program(pprog_res, sender_opt) {
         Err(errors) => errors::report_errors(files, errors),
@@ -99,7 +103,7 @@ pub fn move_compile_no_report(
     deps: &[String],
     sender_opt: Option<Address>,
 ) -> anyhow::Result<(FilesSourceText, Result<Vec<CompiledUnit>, Errors>)> {
-    let (files, pprog_and_comments_res) = parse_program(targets, deps)?;
+    let (files, pprog_and_comments_res) = parse_program(targets, deps, sender_opt)?;
     let pprog_res = pprog_and_comments_res.map(|(pprog, _)| pprog);
     Ok(match compile_program(pprog_res, sender_opt) {
         Err(errors) => (files, Err(errors)),
@@ -118,7 +122,7 @@ pub fn move_compile_to_expansion_no_report(
     FilesSourceText,
     Result<(expansion::ast::Program, CommentMap), Errors>,
 )> {
-    let (files, pprog_and_comments_res) = parse_program(targets, deps)?;
+    let (files, pprog_and_comments_res) = parse_program(targets, deps, sender_opt)?;
     let res = pprog_and_comments_res.and_then(|(pprog, comment_map)| {
         let (eprog, errors) = expansion::translate::program(pprog, sender_opt);
         check_errors(errors)?;
@@ -127,6 +131,65 @@ pub fn move_compile_to_expansion_no_report(
     Ok((files, res))
 }
 
+/// Given a set of targets and a set of dependencies
+/// - Checks the targets with the dependencies, keeping the test functions of the targets
+/// - Compiles the targets and the dependencies to Move bytecode, so that they can be published
+/// Returns the compiled units with the tests of the targets
+pub fn move_compile_for_testing(
+    targets: &[String],
+    deps: &[String],
+    sender_opt: Option<Address>,
+) -> anyhow::Result<(
+    FilesSourceText,
+    Result<(Vec<CompiledUnit>, TestPlan), Errors>,
+)> {
+    // The dependencies are compiled along with the targets, so they must not repeat them.
+    let targets = find_move_filenames(targets)?;
+    let deps: Vec<String> = find_move_filenames(deps)?
+        .into_iter()
+        .filter(|dep| !targets.contains(dep))
+        .collect();
+    let (files, pprog_res) = parse_program_impl(&targets, &deps)?;
+    let res = pprog_res.and_then(|(pprog, _)| {
+        let (mut pprog, test_plan) =
+            unit_test::filter_test_functions(pprog, sender_opt, /* keep_source_tests */ true)?;
+        pprog.source_definitions.append(&mut pprog.lib_definitions);
+        let units = compile_program(Ok(pprog), sender_opt)?;
+        Ok((units, test_plan))
+    });
+    Ok((files, res))
+}
+
+/// Given a set of in-memory targets and a set of parsed dependencies
+/// - Checks the targets with the dependencies up to typing, keeping the test functions of the
+///   targets
+/// Returns the typed program even when there are errors, for tools working on unsaved sources,
+/// e.g. editors. The typed program is missing when the sources don't parse.
+/// Dependencies which are also targets are skipped, the targets taking their place.
+pub fn move_check_sources_to_typing(
+    targets: Vec<(&'static str, String)>,
+    deps: &ParsedDependencies,
+    sender_opt: Option<Address>,
+) -> (FilesSourceText, Option<typing::ast::Program>, Errors) {
+    let (files, pprog_res) = parse_sources(targets, deps, sender_opt);
+    let pprog = match pprog_res {
+        Ok(pprog) => pprog,
+        Err(errors) => return (files, None, errors),
+    };
+    let (eprog, errors) = expansion::translate::program(pprog, sender_opt);
+    let (nprog, errors) = naming::translate::program(eprog, errors);
+    let (tprog, errors) = typing::translate::program(nprog, errors);
+    (files, Some(tprog), errors)
+}
+
+/// Runs the checks after typing, e.g. borrow checking, on a program typed without errors by
+/// `move_check_sources_to_typing`
+pub fn move_check_typed_program_no_report(tprog: typing::ast::Program) -> Errors {
+    let (hprog, errors) = hlir::translate::program(tprog);
+    let (_, errors) = cfgir::translate::program(errors, hprog);
+    errors
+}
+
 //**************************************************************************************************
 // Utils
 //**************************************************************************************************
@@ -245,20 +308,39 @@ fn compile_program(
 // Parsing
 //**************************************************************************************************
 
+/// Parses the targets and the dependencies, dropping their test functions.
 fn parse_program(
     targets: &[String],
     deps: &[String],
+    sender_opt: Option<Address>,
+) -> anyhow::Result<(
+    FilesSourceText,
+    Result<(parser::ast::Program, CommentMap), Errors>,
+)> {
+    let (files, res) = parse_program_impl(targets, deps)?;
+    let res = res.and_then(|(pprog, comment_map)| {
+        let (pprog, _) = unit_test::filter_test_functions(
+            pprog, sender_opt, /* keep_source_tests */ false,
+        )?;
+        Ok((pprog, comment_map))
+    });
+    Ok((files, res))
+}
+
+fn parse_program_impl(
+    targets: &[String],
+    deps: &[String],
 ) -> anyhow::Result<(
     FilesSourceText,
     Result<(parser::ast::Program, CommentMap), Errors>,
 )> {
     let targets = find_move_filenames(targets)?
         .iter()
-        .map(|s| leak_str(s))
+        .map(|s| intern_file_name(s))
         .collect::<Vec<&'static str>>();
     let deps = find_move_filenames(deps)?
         .iter()
-        .map(|s| leak_str(s))
+        .map(|s| intern_file_name(s))
         .collect::<Vec<&'static str>>();
     let mut files: FilesSourceText = HashMap::new();
     let mut source_definitions = Vec::new();
@@ -293,6 +375,65 @@ fn parse_program(
     Ok((files, res))
 }
 
+/// Dependencies parsed once, to check in-memory targets against them repeatedly
+pub struct ParsedDependencies {
+    /// The definitions and the parsing errors of each dependency file, with its text
+    files: Vec<(&'static str, String, Vec<parser::ast::Definition>, Errors)>,
+}
+
+/// Parses the dependencies, for `move_check_sources_to_typing`
+pub fn parse_dependencies(deps: &[String]) -> anyhow::Result<ParsedDependencies> {
+    let mut files = vec![];
+    for fname in find_move_filenames(deps)? {
+        let fname = intern_file_name(&fname);
+        let mut texts: FilesSourceText = HashMap::new();
+        let (defs, _, errors) = parse_file(&mut texts, fname)?;
+        let text = texts.remove(fname).unwrap_or_default();
+        files.push((fname, text, defs, errors));
+    }
+    Ok(ParsedDependencies { files })
+}
+
+/// Parses the in-memory targets and takes the definitions of the dependencies, keeping the test
+/// functions of the targets.
+fn parse_sources(
+    targets: Vec<(&'static str, String)>,
+    deps: &ParsedDependencies,
+    sender_opt: Option<Address>,
+) -> (FilesSourceText, Result<parser::ast::Program, Errors>) {
+    let mut files: FilesSourceText = HashMap::new();
+    let mut source_definitions = Vec::new();
+    let mut lib_definitions = Vec::new();
+    let mut errors: Errors = Vec::new();
+
+    for (fname, text, defs, es) in &deps.files {
+        if targets.iter().any(|(target, _)| target == fname) {
+            continue;
+        }
+        files.insert(*fname, text.clone());
+        lib_definitions.extend(defs.iter().cloned());
+        errors.extend(es.iter().cloned());
+    }
+
+    for (fname, source) in targets {
+        let (defs, _, mut es) = parse_source(&mut files, fname, source);
+        source_definitions.extend(defs);
+        errors.append(&mut es);
+    }
+
+    if !errors.is_empty() {
+        return (files, Err(errors));
+    }
+    let pprog = parser::ast::Program {
+        source_definitions,
+        lib_definitions,
+    };
+    let res =
+        unit_test::filter_test_functions(pprog, sender_opt, /* keep_source_tests */ true)
+            .map(|(pprog, _)| pprog);
+    (files, res)
+}
+
 pub fn find_move_filenames(files: &[String]) -> anyhow::Result<Vec<String>> {
     let mut result = vec![];
     let has_move_extension = |path: &Path| match path.extension().and_then(|s| s.to_str()) {
@@ -329,25 +470,45 @@ pub fn find_move_filenames(files: &[String]) -> anyhow::Result<Vec<String>> {
     Ok(result)
 }
 
-// TODO replace with some sort of intern table
-fn leak_str(s: &str) -> &'static str {
-    Box::leak(Box::new(s.to_owned()))
+/// The file names given to the parser, which wants them to live forever. Each name is leaked
+/// once, however many times the file is parsed.
+static FILE_NAMES: Lazy<Mutex<BTreeSet<&'static str>>> = Lazy::new(|| Mutex::new(BTreeSet::new()));
+
+/// Returns the file name living forever for `s`
+pub fn intern_file_name(s: &str) -> &'static str {
+    let mut file_names = FILE_NAMES.lock().unwrap();
+    match file_names.get(s) {
+        Some(file_name) => file_name,
+        None => {
+            let file_name: &'static str = Box::leak(s.to_owned().into_boxed_str());
+            file_names.insert(file_name);
+            file_name
+        }
+    }
 }
 
 fn parse_file(
     files: &mut FilesSourceText,
     fname: &'static str,
 ) -> anyhow::Result<(Vec<parser::ast::Definition>, MatchedFileCommentMap, Errors)> {
-    let mut errors: Errors = Vec::new();
     let mut f = File::open(fname)
         .map_err(|err| std::io::Error::new(err.kind(), format!("{}: {}", err, fname)))?;
     let mut source_buffer = String::new();
     f.read_to_string(&mut source_buffer)?;
+    Ok(parse_source(files, fname, source_buffer))
+}
+
+fn parse_source(
+    files: &mut FilesSourceText,
+    fname: &'static str,
+    source_buffer: String,
+) -> (Vec<parser::ast::Definition>, MatchedFileCommentMap, Errors) {
+    let mut errors: Errors = Vec::new();
     let (no_comments_buffer, comment_map) = match strip_comments_and_verify(fname, &source_buffer) {
         Err(errs) => {
             errors.extend(errs.into_iter());
             files.insert(fname, source_buffer);
-            return Ok((vec![], MatchedFileCommentMap::new(), errors));
+            return (vec![], MatchedFileCommentMap::new(), errors);
         }
         Ok(result) => result,
     };
@@ -359,7 +520,7 @@ fn parse_file(
         }
     };
     files.insert(fname, source_buffer);
-    Ok((defs, comments, errors))
+    (defs, comments, errors)
 }
 
 //**************************************************************************************************
@@ -430,7 +591,12 @@ pub type FileCommentMap = BTreeMap<Span, String>;
 /// (`/// .. <newline>` and `/** .. */`) will be not included in extracted comment string. The
 /// span in the returned map, however, covers the whole region of the comment, including the
 /// delimiters.
-fn strip_comments(fname: &'static str, input: &str) -> Result<(String, FileCommentMap), Errors> {
+///
+/// The spans of all comments, documentation or not, are returned as well, in source order.
+fn strip_comments(
+    fname: &'static str,
+    input: &str,
+) -> Result<(String, FileCommentMap, Vec<Span>), Errors> {
     const SLASH: char = '/';
     const SPACE: char = ' ';
     const STAR: char = '*';
@@ -443,6 +609,7 @@ fn strip_comments(fname: &'static str, input: &str) -> Result<(String, FileComme
 
     let mut source = String::with_capacity(input.len());
     let mut comment_map = FileCommentMap::new();
+    let mut comment_spans = vec![];
 
     let mut state = State::Source;
     let mut pos = 0;
@@ -453,11 +620,14 @@ fn strip_comments(fname: &'static str, input: &str) -> Result<(String, FileComme
     let next_is =
         |peekable: &mut Peekable<Chars>, chr| peekable.peek().map(|c| *c == chr).unwrap_or(false);
 
-    let mut commit_comment = |state, start_pos, end_pos, content: String| match state {
-        State::BlockComment if !content.starts_with('*') || content.starts_with("**") => {}
-        State::LineComment if !content.starts_with('/') || content.starts_with("//") => {}
-        _ => {
-            comment_map.insert(Span::new(start_pos, end_pos), content[1..].to_string());
+    let mut commit_comment = |state, start_pos, end_pos, content: String| {
+        comment_spans.push(Span::new(start_pos, end_pos));
+        match state {
+            State::BlockComment if !content.starts_with('*') || content.starts_with("**") => {}
+            State::LineComment if !content.starts_with('/') || content.starts_with("//") => {}
+            _ => {
+                comment_map.insert(Span::new(start_pos, end_pos), content[1..].to_string());
+            }
         }
     };
 
@@ -502,8 +672,10 @@ fn strip_comments(fname: &'static str, input: &str) -> Result<(String, FileComme
                 // Starting nested block comment.
                 char_iter.next();
                 source.push(SPACE);
-                comment.push(chr);
-                pos += 1;
+                source.push(SPACE);
+                comment.push(SLASH);
+                comment.push(STAR);
+                pos += 2;
                 block_nest += 1;
             }
             State::BlockComment
@@ -512,8 +684,10 @@ fn strip_comments(fname: &'static str, input: &str) -> Result<(String, FileComme
                 // Ending nested block comment.
                 char_iter.next();
                 source.push(SPACE);
-                comment.push(chr);
-                pos -= 1;
+                source.push(SPACE);
+                comment.push(STAR);
+                comment.push(SLASH);
+                pos += 2;
                 block_nest -= 1;
             }
             State::BlockComment
@@ -565,7 +739,7 @@ fn strip_comments(fname: &'static str, input: &str) -> Result<(String, FileComme
         State::Source => {}
     }
 
-    Ok((source, comment_map))
+    Ok((source, comment_map, comment_spans))
 }
 
 // We restrict strings to only ascii visual characters (0x20 <= c <= 0x7E) or a permitted newline
@@ -575,5 +749,17 @@ fn strip_comments_and_verify(
     string: &str,
 ) -> Result<(String, FileCommentMap), Errors> {
     verify_string(fname, string)?;
-    strip_comments(fname, string)
+    let (source, comment_map, _) = strip_comments(fname, string)?;
+    Ok((source, comment_map))
+}
+
+/// Like `strip_comments_and_verify`, but also returns the spans of all the comments of `input`,
+/// documentation or not, in source order. For tools that have to reproduce the comments of a
+/// file, e.g. formatters.
+pub fn strip_comments_and_collect(
+    fname: &'static str,
+    input: &str,
+) -> Result<(String, FileCommentMap, Vec<Span>), Errors> {
+    verify_string(fname, input)?;
+    strip_comments(fname, input)
 }
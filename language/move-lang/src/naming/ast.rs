@@ -4,8 +4,8 @@
 use crate::{
     expansion::ast::{Fields, SpecId},
     parser::ast::{
-        BinOp, Field, FunctionName, FunctionVisibility, Kind, Kind_, ModuleIdent, ResourceLoc,
-        StructName, UnaryOp, Value, Value_, Var,
+        BinOp, ConstantName, Field, FunctionName, FunctionVisibility, Kind, Kind_, ModuleIdent,
+        ResourceLoc, StructName, UnaryOp, Value, Value_, Var,
     },
     shared::{ast_debug::*, unique_map::UniqueMap, *},
 };
@@ -32,6 +32,7 @@ pub struct Program {
 #[derive(Debug)]
 pub struct Script {
     pub loc: Loc,
+    pub constants: UniqueMap<ConstantName, Constant>,
     pub function_name: FunctionName,
     pub function: Function,
 }
@@ -47,6 +48,7 @@ pub struct ModuleDefinition {
     /// `dependency_order` is initialized at `0` and set in the uses pass
     pub dependency_order: usize,
     pub structs: UniqueMap<StructName, StructDefinition>,
+    pub constants: UniqueMap<ConstantName, Constant>,
     pub functions: UniqueMap<FunctionName, Function>,
 }
 
@@ -93,6 +95,17 @@ pub struct Function {
     pub body: FunctionBody,
 }
 
+//**************************************************************************************************
+// Constants
+//**************************************************************************************************
+
+#[derive(PartialEq, Debug)]
+pub struct Constant {
+    pub loc: Loc,
+    pub signature: Type,
+    pub value: Exp,
+}
+
 //**************************************************************************************************
 // Types
 //**************************************************************************************************
@@ -200,6 +213,7 @@ pub enum Exp_ {
     Move(Var),
     Copy(Var),
     Use(Var),
+    Constant(ConstantName),
 
     ModuleCall(
         ModuleIdent,
@@ -509,9 +523,14 @@ impl AstDebug for Script {
     fn ast_debug(&self, w: &mut AstWriter) {
         let Script {
             loc: _loc,
+            constants,
             function_name,
             function,
         } = self;
+        for cdef in constants {
+            cdef.ast_debug(w);
+            w.new_line();
+        }
         (function_name.clone(), function).ast_debug(w);
     }
 }
@@ -522,6 +541,7 @@ impl AstDebug for ModuleDefinition {
             is_source_module,
             dependency_order,
             structs,
+            constants,
             functions,
         } = self;
         if *is_source_module {
@@ -534,6 +554,10 @@ impl AstDebug for ModuleDefinition {
             sdef.ast_debug(w);
             w.new_line();
         }
+        for cdef in constants {
+            cdef.ast_debug(w);
+            w.new_line();
+        }
         for fdef in functions {
             fdef.ast_debug(w);
             w.new_line();
@@ -541,6 +565,24 @@ impl AstDebug for ModuleDefinition {
     }
 }
 
+impl AstDebug for (ConstantName, &Constant) {
+    fn ast_debug(&self, w: &mut AstWriter) {
+        let (
+            name,
+            Constant {
+                loc: _loc,
+                signature,
+                value,
+            },
+        ) = self;
+        w.write(&format!("const {}:", name));
+        signature.ast_debug(w);
+        w.write(" = ");
+        value.ast_debug(w);
+        w.write(";");
+    }
+}
+
 impl AstDebug for (StructName, &StructDefinition) {
     fn ast_debug(&self, w: &mut AstWriter) {
         let (
@@ -750,6 +792,7 @@ impl AstDebug for Exp_ {
             E::Move(v) => w.write(&format!("move {}", v)),
             E::Copy(v) => w.write(&format!("copy {}", v)),
             E::Use(v) => w.write(&format!("{}", v)),
+            E::Constant(c) => w.write(&format!("{}", c)),
             E::ModuleCall(m, f, tys_opt, sp!(_, rhs)) => {
                 w.write(&format!("{}::{}", m, f));
                 if let Some(ss) = tys_opt {
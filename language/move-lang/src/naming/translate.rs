@@ -5,7 +5,7 @@ use crate::{
     errors::*,
     expansion::ast as E,
     naming::ast as N,
-    parser::ast::{Field, FunctionName, Kind, Kind_, ModuleIdent, StructName, Var},
+    parser::ast::{ConstantName, Field, FunctionName, Kind, Kind_, ModuleIdent, StructName, Var},
     shared::{unique_map::UniqueMap, *},
     typing::core::{self, Subst},
 };
@@ -40,6 +40,8 @@ struct Context {
     scoped_types: BTreeMap<ModuleIdent, BTreeMap<String, (Loc, ModuleIdent, Option<Kind>)>>,
     unscoped_types: BTreeMap<String, ResolvedType>,
     scoped_functions: BTreeMap<ModuleIdent, BTreeMap<String, Loc>>,
+    scoped_constants: BTreeMap<ModuleIdent, BTreeMap<String, Loc>>,
+    unscoped_constants: BTreeMap<String, Loc>,
 }
 
 impl Context {
@@ -72,6 +74,18 @@ impl Context {
                 (mident, mems)
             })
             .collect();
+        let scoped_constants = prog
+            .modules
+            .iter()
+            .map(|(mident, mdef)| {
+                let mems = mdef
+                    .constants
+                    .iter()
+                    .map(|(n, _)| (n.value().to_string(), n.loc()))
+                    .collect();
+                (mident, mems)
+            })
+            .collect();
         let unscoped_types = N::BuiltinTypeName_::all_names()
             .into_iter()
             .map(|s| (s.to_string(), RT::BuiltinType))
@@ -81,7 +95,9 @@ impl Context {
             current_module: None,
             scoped_types,
             scoped_functions,
+            scoped_constants,
             unscoped_types,
+            unscoped_constants: BTreeMap::new(),
         }
     }
 
@@ -154,6 +170,43 @@ impl Context {
         }
     }
 
+    fn resolve_module_constant(
+        &mut self,
+        loc: Loc,
+        m: &ModuleIdent,
+        n: &Name,
+    ) -> Option<ConstantName> {
+        let constants = match self.scoped_constants.get(m) {
+            None => {
+                self.error(vec![(loc, format!("Unbound module '{}'", m,))]);
+                return None;
+            }
+            Some(members) => members,
+        };
+        if !constants.contains_key(&n.value) {
+            self.error(vec![(
+                loc,
+                format!(
+                    "Invalid module access. Unbound constant '{}' in module '{}'",
+                    n, m
+                ),
+            )]);
+            return None;
+        }
+        if self.current_module.as_ref() != Some(m) {
+            self.error(vec![(
+                loc,
+                format!(
+                    "Invalid access of '{}::{}'. Constants are internal to their module, and \
+                     cannot be accessed outside of their module",
+                    m, n
+                ),
+            )]);
+            return None;
+        }
+        Some(ConstantName(n.clone()))
+    }
+
     fn resolve_unscoped_type(&mut self, n: &Name) -> Option<ResolvedType> {
         match self.unscoped_types.get(&n.value) {
             None => {
@@ -241,6 +294,10 @@ fn module(
         context.restore_unscoped(unscoped.clone());
         struct_def(context, name, s)
     });
+    let constants = mdef.constants.map(|name, c| {
+        context.restore_unscoped(unscoped.clone());
+        constant(context, name, c)
+    });
     let functions = mdef.functions.map(|name, f| {
         context.restore_unscoped(unscoped.clone());
         function(context, name, f)
@@ -250,6 +307,7 @@ fn module(
         is_source_module,
         dependency_order: 0,
         structs,
+        constants,
         functions,
     }
 }
@@ -265,20 +323,48 @@ fn scripts(
 }
 
 fn script(context: &mut Context, escript: E::Script) -> N::Script {
+    context.current_module = None;
     let E::Script {
         loc,
+        constants: econstants,
         function_name,
         function: efunction,
         specs: _specs,
     } = escript;
+    context.unscoped_constants = econstants
+        .iter()
+        .map(|(n, _)| (n.value().to_string(), n.loc()))
+        .collect();
+    let constants = econstants.map(|name, c| constant(context, name, c));
     let function = function(context, function_name.clone(), efunction);
+    context.unscoped_constants = BTreeMap::new();
     N::Script {
         loc,
+        constants,
         function_name,
         function,
     }
 }
 
+//**************************************************************************************************
+// Constants
+//**************************************************************************************************
+
+fn constant(context: &mut Context, _name: ConstantName, econstant: E::Constant) -> N::Constant {
+    let E::Constant {
+        loc,
+        signature: esignature,
+        value: evalue,
+    } = econstant;
+    let signature = type_(context, esignature);
+    let value = exp_(context, evalue);
+    N::Constant {
+        loc,
+        signature,
+        value,
+    }
+}
+
 //**************************************************************************************************
 // Functions
 //**************************************************************************************************
@@ -618,7 +704,21 @@ fn exp_(context: &mut Context, e: E::Exp) -> N::Exp {
         EE::Value(val) => NE::Value(val),
         EE::Move(v) => NE::Move(v),
         EE::Copy(v) => NE::Copy(v),
+        EE::Name(sp!(_, E::ModuleAccess_::Name(n)), None)
+            if context.unscoped_constants.contains_key(&n.value) =>
+        {
+            NE::Constant(ConstantName(n))
+        }
         EE::Name(sp!(_, E::ModuleAccess_::Name(v)), None) => NE::Use(Var(v)),
+        EE::Name(sp!(mloc, E::ModuleAccess_::ModuleAccess(m, n)), None) => {
+            match context.resolve_module_constant(mloc, &m, &n) {
+                None => {
+                    assert!(context.has_errors());
+                    NE::UnresolvedError
+                }
+                Some(c) => NE::Constant(c),
+            }
+        }
 
         EE::IfElse(eb, et, ef) => {
             NE::IfElse(exp(context, *eb), exp(context, *et), exp(context, *ef))
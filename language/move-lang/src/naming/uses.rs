@@ -231,7 +231,8 @@ fn exp(context: &mut Context, sp!(loc, e_): &N::Exp) {
         | E::Value(_)
         | E::Move(_)
         | E::Copy(_)
-        | E::Use(_) => (),
+        | E::Use(_)
+        | E::Constant(_) => (),
 
         E::ModuleCall(m, _, bs_opt, sp!(_, es_)) => {
             context.add_usage(m, *loc);
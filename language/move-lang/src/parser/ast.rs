@@ -48,13 +48,13 @@ macro_rules! new_name {
 // Program
 //**************************************************************************************************
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Program {
     pub source_definitions: Vec<Definition>,
     pub lib_definitions: Vec<Definition>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
 pub enum Definition {
     Module(ModuleDefinition),
@@ -62,15 +62,16 @@ pub enum Definition {
     Script(Script),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Script {
     pub loc: Loc,
     pub uses: Vec<Use>,
+    pub constants: Vec<Constant>,
     pub function: Function,
     pub specs: Vec<SpecBlock>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Use {
     Module(ModuleIdent, Option<ModuleName>),
     Members(ModuleIdent, Vec<(Name, Option<Name>)>),
@@ -90,17 +91,18 @@ pub struct ModuleIdent_ {
 #[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
 pub struct ModuleIdent(pub Spanned<ModuleIdent_>);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ModuleDefinition {
     pub loc: Loc,
     pub name: ModuleName,
     pub members: Vec<ModuleMember>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ModuleMember {
     Function(Function),
     Struct(StructDefinition),
+    Constant(Constant),
     Spec(SpecBlock),
     Use(Use),
 }
@@ -114,7 +116,7 @@ new_name!(StructName);
 
 pub type ResourceLoc = Option<Loc>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct StructDefinition {
     pub loc: Loc,
     pub resource_opt: ResourceLoc,
@@ -123,7 +125,7 @@ pub struct StructDefinition {
     pub fields: StructFields,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum StructFields {
     Defined(Vec<(Field, Type)>),
     Native(Loc),
@@ -135,7 +137,7 @@ pub enum StructFields {
 
 new_name!(FunctionName);
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct FunctionSignature {
     pub type_parameters: Vec<(Name, Kind)>,
     pub parameters: Vec<(Var, Type)>,
@@ -148,20 +150,31 @@ pub enum FunctionVisibility {
     Internal,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum FunctionBody_ {
     Defined(Sequence),
     Native,
 }
 pub type FunctionBody = Spanned<FunctionBody_>;
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
+pub enum FunctionAttribute_ {
+    // #[test]
+    Test,
+    // #[expected_failure] or #[expected_failure(abort_code = <u64>)]
+    ExpectedFailure(Option<u64>),
+}
+pub type FunctionAttribute = Spanned<FunctionAttribute_>;
+
+#[derive(PartialEq, Debug, Clone)]
+// #[attribute, ...]*
 // (public?) foo<T1(: copyable?), ..., TN(: copyable?)>(x1: t1, ..., xn: tn): t1 * ... * tn {
 //    body
 //  }
 // (public?) native foo<T1(: copyable?), ..., TN(: copyable?)>(x1: t1, ..., xn: tn): t1 * ... * tn;
 pub struct Function {
     pub loc: Loc,
+    pub attributes: Vec<FunctionAttribute>,
     pub visibility: FunctionVisibility,
     pub signature: FunctionSignature,
     pub acquires: Vec<ModuleAccess>,
@@ -169,13 +182,28 @@ pub struct Function {
     pub body: FunctionBody,
 }
 
+//**************************************************************************************************
+// Constants
+//**************************************************************************************************
+
+new_name!(ConstantName);
+
+#[derive(PartialEq, Debug, Clone)]
+// const NAME: ty = value;
+pub struct Constant {
+    pub loc: Loc,
+    pub signature: Type,
+    pub name: ConstantName,
+    pub value: Exp,
+}
+
 //**************************************************************************************************
 // Specification Blocks
 //**************************************************************************************************
 
 // Specification block:
 //    SpecBlock = "spec" <SpecBlockTarget> "{" SpecBlockMember* "}"
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct SpecBlock_ {
     pub target: SpecBlockTarget,
     pub uses: Vec<Use>,
@@ -184,7 +212,7 @@ pub struct SpecBlock_ {
 
 pub type SpecBlock = Spanned<SpecBlock_>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum SpecBlockTarget_ {
     Code,
     Module,
@@ -195,7 +223,7 @@ pub enum SpecBlockTarget_ {
 
 pub type SpecBlockTarget = Spanned<SpecBlockTarget_>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct PragmaProperty_ {
     pub name: Name,
     pub value: Option<Value>,
@@ -203,7 +231,7 @@ pub struct PragmaProperty_ {
 
 pub type PragmaProperty = Spanned<PragmaProperty_>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct SpecApplyPattern_ {
     pub visibility: Option<FunctionVisibility>,
     pub name_pattern: Vec<SpecApplyFragment>,
@@ -212,7 +240,7 @@ pub struct SpecApplyPattern_ {
 
 pub type SpecApplyPattern = Spanned<SpecApplyPattern_>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum SpecApplyFragment_ {
     Wildcard,
     NamePart(Name),
@@ -220,7 +248,7 @@ pub enum SpecApplyFragment_ {
 
 pub type SpecApplyFragment = Spanned<SpecApplyFragment_>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 #[allow(clippy::large_enum_variant)]
 pub enum SpecBlockMember_ {
     Condition {
@@ -254,7 +282,7 @@ pub enum SpecBlockMember_ {
 pub type SpecBlockMember = Spanned<SpecBlockMember_>;
 
 // Specification condition kind.
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum SpecConditionKind {
     Assert,
     Assume,
@@ -271,7 +299,7 @@ pub enum SpecConditionKind {
 }
 
 // Specification invaiant kind.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum InvariantKind {
     Data,
     Update,
@@ -286,7 +314,7 @@ pub enum InvariantKind {
 
 // A ModuleAccess references a local or global name or something from a module,
 // either a struct type or a function.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum ModuleAccess_ {
     // N
     Name(Name),
@@ -312,7 +340,7 @@ pub enum Kind_ {
 }
 pub type Kind = Spanned<Kind_>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Type_ {
     // N
     // N<t1, ... , tn>
@@ -336,7 +364,7 @@ pub type Type = Spanned<Type_>;
 
 new_name!(Var);
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Bind_ {
     // x
     Var(Var),
@@ -348,7 +376,7 @@ pub type Bind = Spanned<Bind_>;
 // b1, ..., bn
 pub type BindList = Spanned<Vec<Bind>>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Value_ {
     // 0x<hex representation up to 64 digits with padding 0s>
     Address(Address),
@@ -365,14 +393,14 @@ pub enum Value_ {
 }
 pub type Value = Spanned<Value_>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum UnaryOp_ {
     // !
     Not,
 }
 pub type UnaryOp = Spanned<UnaryOp_>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum BinOp_ {
     // Int ops
     // +
@@ -422,7 +450,7 @@ pub enum BinOp_ {
 }
 pub type BinOp = Spanned<BinOp_>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 #[allow(clippy::large_enum_variant)]
 pub enum Exp_ {
     Value(Value),
@@ -503,7 +531,7 @@ pub type Exp = Spanned<Exp_>;
 // { e1; ... ; en; }
 // The Loc field holds the source location of the final semicolon, if there is one.
 pub type Sequence = (Vec<SequenceItem>, Option<Loc>, Box<Option<Exp>>);
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 #[allow(clippy::large_enum_variant)]
 pub enum SequenceItem_ {
     // e;
@@ -733,6 +761,7 @@ impl AstDebug for Script {
         let Script {
             loc: _loc,
             uses,
+            constants,
             function,
             specs,
         } = self;
@@ -741,6 +770,11 @@ impl AstDebug for Script {
             w.new_line();
         }
         w.new_line();
+        for cdef in constants {
+            cdef.ast_debug(w);
+            w.new_line();
+        }
+        w.new_line();
         function.ast_debug(w);
         for spec in specs {
             spec.ast_debug(w);
@@ -770,6 +804,7 @@ impl AstDebug for ModuleMember {
         match self {
             ModuleMember::Function(f) => f.ast_debug(w),
             ModuleMember::Struct(s) => s.ast_debug(w),
+            ModuleMember::Constant(c) => c.ast_debug(w),
             ModuleMember::Spec(s) => s.ast_debug(w),
             ModuleMember::Use(u) => u.ast_debug(w),
         }
@@ -829,6 +864,22 @@ impl AstDebug for StructDefinition {
     }
 }
 
+impl AstDebug for Constant {
+    fn ast_debug(&self, w: &mut AstWriter) {
+        let Constant {
+            loc: _loc,
+            name,
+            signature,
+            value,
+        } = self;
+        w.write(&format!("const {}:", name));
+        signature.ast_debug(w);
+        w.write(" = ");
+        value.ast_debug(w);
+        w.write(";");
+    }
+}
+
 impl AstDebug for SpecBlock_ {
     fn ast_debug(&self, w: &mut AstWriter) {
         w.write("spec ");
@@ -992,12 +1043,17 @@ impl AstDebug for Function {
     fn ast_debug(&self, w: &mut AstWriter) {
         let Function {
             loc: _loc,
+            attributes,
             visibility,
             signature,
             acquires,
             name,
             body,
         } = self;
+        for attribute in attributes {
+            attribute.ast_debug(w);
+            w.new_line();
+        }
         visibility.ast_debug(w);
         if let FunctionBody_::Native = &body.value {
             w.write("native ");
@@ -1016,6 +1072,18 @@ impl AstDebug for Function {
     }
 }
 
+impl AstDebug for FunctionAttribute_ {
+    fn ast_debug(&self, w: &mut AstWriter) {
+        match self {
+            FunctionAttribute_::Test => w.write("#[test]"),
+            FunctionAttribute_::ExpectedFailure(None) => w.write("#[expected_failure]"),
+            FunctionAttribute_::ExpectedFailure(Some(abort_code)) => {
+                w.write(&format!("#[expected_failure(abort_code = {})]", abort_code))
+            }
+        }
+    }
+}
+
 impl AstDebug for FunctionVisibility {
     fn ast_debug(&self, w: &mut AstWriter) {
         match self {
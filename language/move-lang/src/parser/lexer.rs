@@ -77,6 +77,8 @@ pub enum Tok {
     RBrace,
     Fun,
     Script,
+    NumSign,
+    Const,
 }
 
 impl fmt::Display for Tok {
@@ -152,6 +154,8 @@ impl fmt::Display for Tok {
             RBrace => "}",
             Fun => "fun",
             Script => "script",
+            NumSign => "#",
+            Const => "const",
         };
         fmt::Display::fmt(s, formatter)
     }
@@ -389,6 +393,7 @@ fn find_token(file: &'static str, text: &str, start_offset: usize) -> Result<(To
             }
         }
         '%' => (Tok::Percent, 1),
+        '#' => (Tok::NumSign, 1),
         '(' => (Tok::LParen, 1),
         ')' => (Tok::RParen, 1),
         '[' => (Tok::LBracket, 1),
@@ -466,6 +471,7 @@ fn get_name_token(name: &str) -> Tok {
         "acquires" => Tok::Acquires,
         "as" => Tok::As,
         "break" => Tok::Break,
+        "const" => Tok::Const,
         "continue" => Tok::Continue,
         "copy" => Tok::Copy,
         "copyable" => Tok::Copyable,
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod lexer;
-pub(crate) mod syntax;
+pub mod syntax;
 
 pub mod ast;
 mod byte_string;
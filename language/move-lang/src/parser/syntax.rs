@@ -1174,6 +1174,82 @@ fn parse_optional_type_parameters<'input>(
     }
 }
 
+//**************************************************************************************************
+// Attributes
+//**************************************************************************************************
+
+// Parse the attributes of a function:
+//      Attributes = ("#" "[" Comma<Attribute> "]")*
+fn parse_function_attributes<'input>(
+    tokens: &mut Lexer<'input>,
+) -> Result<Vec<FunctionAttribute>, Error> {
+    let mut attributes = vec![];
+    while match_token(tokens, Tok::NumSign)? {
+        attributes.extend(parse_comma_list(
+            tokens,
+            Tok::LBracket,
+            Tok::RBracket,
+            parse_function_attribute,
+            "an attribute",
+        )?);
+    }
+    Ok(attributes)
+}
+
+// Parse a function attribute:
+//      Attribute =
+//          "test"
+//          | "expected_failure" ("(" "abort_code" "=" <AbortCode> ")")?
+//      AbortCode = <NumValue> | <U64Value>
+fn parse_function_attribute<'input>(
+    tokens: &mut Lexer<'input>,
+) -> Result<FunctionAttribute, Error> {
+    let start_loc = tokens.start_loc();
+    let name = parse_identifier(tokens)?;
+    let attribute = match name.value.as_str() {
+        "test" => FunctionAttribute_::Test,
+        "expected_failure" => {
+            let abort_code = if match_token(tokens, Tok::LParen)? {
+                consume_identifier(tokens, "abort_code")?;
+                consume_token(tokens, Tok::Equal)?;
+                let abort_code = parse_abort_code(tokens)?;
+                consume_token(tokens, Tok::RParen)?;
+                Some(abort_code)
+            } else {
+                None
+            };
+            FunctionAttribute_::ExpectedFailure(abort_code)
+        }
+        _ => {
+            return Err(vec![(
+                name.loc,
+                format!(
+                    "Unknown attribute '{}'. Expected 'test' or 'expected_failure'",
+                    name.value
+                ),
+            )])
+        }
+    };
+    let end_loc = tokens.previous_end_loc();
+    Ok(spanned(tokens.file_name(), start_loc, end_loc, attribute))
+}
+
+fn parse_abort_code<'input>(tokens: &mut Lexer<'input>) -> Result<u64, Error> {
+    let s = match tokens.peek() {
+        Tok::NumValue => tokens.content(),
+        Tok::U64Value => tokens.content().trim_end_matches("u64"),
+        _ => return Err(unexpected_token_error(tokens, "an abort code")),
+    };
+    let abort_code = u64::from_str(s).map_err(|_| {
+        vec![(
+            current_token_loc(tokens),
+            "Invalid abort code. The given literal is too large to fit into a 'u64'".to_owned(),
+        )]
+    })?;
+    tokens.advance()?;
+    Ok(abort_code)
+}
+
 //**************************************************************************************************
 // Functions
 //**************************************************************************************************
@@ -1183,13 +1259,13 @@ fn parse_optional_type_parameters<'input>(
 //          <NativeFunctionDecl>
 //          | <MoveFunctionDecl>
 //      NativeFunctionDecl =
-//          <DocComments> "native" ( "public" )? "fun"
+//          <DocComments> <Attributes> "native" ( "public" )? "fun"
 //          <FunctionDefName> "(" Comma<Parameter> ")"
 //          (":" <Type>)?
 //          ("acquires" <ModuleAccess> ("," <ModuleAccess>)*)?
 //          ";"
 //      MoveFunctionDecl =
-//          <DocComments> ( "public" )? "fun"
+//          <DocComments> <Attributes> ( "public" )? "fun"
 //          <FunctionDefName> "(" Comma<Parameter> ")"
 //          (":" <Type>)?
 //          ("acquires" <ModuleAccess> ("," <ModuleAccess>)*)?
@@ -1205,6 +1281,7 @@ fn parse_function_decl<'input>(
 ) -> Result<Function, Error> {
     tokens.match_doc_comments();
     let start_loc = tokens.start_loc();
+    let attributes = parse_function_attributes(tokens)?;
     // Record the source location of the "native" keyword (if there is one).
     let native_opt = if allow_native {
         consume_optional_token_with_loc(tokens, Tok::Native)?
@@ -1292,6 +1369,7 @@ fn parse_function_decl<'input>(
     let loc = make_loc(tokens.file_name(), start_loc, tokens.previous_end_loc());
     Ok(Function {
         loc,
+        attributes,
         visibility,
         signature,
         acquires,
@@ -1309,6 +1387,31 @@ fn parse_parameter<'input>(tokens: &mut Lexer<'input>) -> Result<(Var, Type), Er
     Ok((v, t))
 }
 
+//**************************************************************************************************
+// Constants
+//**************************************************************************************************
+
+// Parse a constant:
+//      ConstantDecl = <DocComments> "const" <Identifier> ":" <Type> "=" <Exp> ";"
+fn parse_constant_decl<'input>(tokens: &mut Lexer<'input>) -> Result<Constant, Error> {
+    tokens.match_doc_comments();
+    let start_loc = tokens.start_loc();
+    consume_token(tokens, Tok::Const)?;
+    let name = ConstantName(parse_identifier(tokens)?);
+    consume_token(tokens, Tok::Colon)?;
+    let signature = parse_type(tokens)?;
+    consume_token(tokens, Tok::Equal)?;
+    let value = parse_exp(tokens)?;
+    consume_token(tokens, Tok::Semicolon)?;
+    let loc = make_loc(tokens.file_name(), start_loc, tokens.previous_end_loc());
+    Ok(Constant {
+        loc,
+        signature,
+        name,
+        value,
+    })
+}
+
 //**************************************************************************************************
 // Structs
 //**************************************************************************************************
@@ -1484,7 +1587,7 @@ fn is_struct_definition<'input>(tokens: &mut Lexer<'input>) -> Result<bool, Erro
 //      Module =
 //          <DocComments> "module" <ModuleName> "{"
 //              <UseDecl>*
-//              ( <StructDefinition> | <FunctionDecl> | <Spec> )*
+//              ( <ConstantDecl> | <StructDefinition> | <FunctionDecl> | <Spec> )*
 //          "}"
 fn parse_module<'input>(tokens: &mut Lexer<'input>) -> Result<ModuleDefinition, Error> {
     tokens.match_doc_comments();
@@ -1499,6 +1602,7 @@ fn parse_module<'input>(tokens: &mut Lexer<'input>) -> Result<ModuleDefinition,
         members.push(match tokens.peek() {
             Tok::Spec => ModuleMember::Spec(parse_spec_block(tokens)?),
             Tok::Use => ModuleMember::Use(parse_use_decl(tokens)?),
+            Tok::Const => ModuleMember::Constant(parse_constant_decl(tokens)?),
             // TODO rework parsing modifiers
             _ if is_struct_definition(tokens)? => {
                 ModuleMember::Struct(parse_struct_definition(tokens)?)
@@ -1520,6 +1624,7 @@ fn parse_module<'input>(tokens: &mut Lexer<'input>) -> Result<ModuleDefinition,
 //      Script =
 //          "script" "{"
 //              <UseDecl>*
+//              <ConstantDecl>*
 //              <MoveFunctionDecl>
 //          "}"
 fn parse_script<'input>(tokens: &mut Lexer<'input>) -> Result<Script, Error> {
@@ -1532,6 +1637,10 @@ fn parse_script<'input>(tokens: &mut Lexer<'input>) -> Result<Script, Error> {
     while tokens.peek() == Tok::Use {
         uses.push(parse_use_decl(tokens)?);
     }
+    let mut constants = vec![];
+    while tokens.peek() == Tok::Const {
+        constants.push(parse_constant_decl(tokens)?);
+    }
     let function = parse_function_decl(tokens, /* allow_native */ false)?;
     let mut specs = vec![];
     while tokens.peek() == Tok::Spec {
@@ -1551,6 +1660,7 @@ fn parse_script<'input>(tokens: &mut Lexer<'input>) -> Result<Script, Error> {
     Ok(Script {
         loc,
         uses,
+        constants,
         function,
         specs,
     })
@@ -4,7 +4,10 @@
 use crate::{
     expansion::ast::SpecId,
     hlir::ast as H,
-    parser::ast::{FunctionName, ModuleIdent, ModuleIdent_, ModuleName, StructName, Var},
+    parser::ast::{
+        ConstantName, FunctionName, ModuleIdent, ModuleIdent_, ModuleName, StructName, Var,
+    },
+    shared::unique_map::UniqueMap,
 };
 use libra_types::account_address::AccountAddress as LibraAddress;
 use move_ir_types::ast as IR;
@@ -17,6 +20,7 @@ use std::{
 /// Contains all of the dependencies actually used in the module
 pub struct Context<'a> {
     current_module: Option<&'a ModuleIdent>,
+    constants: UniqueMap<ConstantName, H::Constant>,
     seen_structs: BTreeSet<(ModuleIdent, StructName)>,
     seen_functions: BTreeSet<(ModuleIdent, FunctionName)>,
     spec_info: BTreeMap<SpecId, (IR::NopLabel, BTreeMap<Var, H::SingleType>)>,
@@ -26,9 +30,14 @@ impl<'a> Context<'a> {
     /// Given the dependencies and the current module, creates an empty context.
     /// The current module is a dummy `Self` for CompiledScript.
     /// It initializes an "import" of `Self` as the alias for the current_module.
-    pub fn new(current_module: Option<&'a ModuleIdent>) -> Self {
+    /// `constants` are the constants declared in the unit.
+    pub fn new(
+        current_module: Option<&'a ModuleIdent>,
+        constants: UniqueMap<ConstantName, H::Constant>,
+    ) -> Self {
         Self {
             current_module,
+            constants,
             seen_structs: BTreeSet::new(),
             seen_functions: BTreeSet::new(),
             spec_info: BTreeMap::new(),
@@ -39,6 +48,12 @@ impl<'a> Context<'a> {
         self.current_module
     }
 
+    pub fn constant(&self, c: &ConstantName) -> &H::Constant {
+        self.constants
+            .get(c)
+            .expect("ICE should have failed in naming")
+    }
+
     fn is_current_module(&self, m: &ModuleIdent) -> bool {
         self.current_module.map(|cur| cur == m).unwrap_or(false)
     }
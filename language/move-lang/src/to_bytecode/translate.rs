@@ -13,13 +13,14 @@ use crate::{
     },
     naming::ast::{BuiltinTypeName_, TParam},
     parser::ast::{
-        BinOp, BinOp_, Field, FunctionName, FunctionVisibility, Kind, Kind_, ModuleIdent,
-        StructName, UnaryOp, UnaryOp_, Value_, Var,
+        BinOp, BinOp_, ConstantName, Field, FunctionName, FunctionVisibility, Kind, Kind_,
+        ModuleIdent, StructName, UnaryOp, UnaryOp_, Value, Value_, Var,
     },
     shared::{unique_map::UniqueMap, *},
 };
 use bytecode_source_map::source_map::SourceMap;
 use libra_types::account_address::AccountAddress as LibraAddress;
+use move_core_types::value::MoveValue;
 use move_ir_types::{ast as IR, location::*};
 use move_vm::file_format as F;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
@@ -61,7 +62,10 @@ pub fn program(prog: G::Program) -> Result<Vec<CompiledUnit>, Errors> {
             mdef.functions.iter().map(move |(f, fdef)| {
                 let key = (m.clone(), f);
                 let seen = seen_structs(&fdef.signature);
-                let sig = function_signature(&mut Context::new(None), fdef.signature.clone());
+                let sig = function_signature(
+                    &mut Context::new(None, UniqueMap::new()),
+                    fdef.signature.clone(),
+                );
                 (key, (seen, sig))
             })
         })
@@ -82,10 +86,19 @@ pub fn program(prog: G::Program) -> Result<Vec<CompiledUnit>, Errors> {
     for (key, s) in prog.scripts {
         let G::Script {
             loc: _,
+            constants,
             function_name,
             function,
         } = s;
-        match script(key, function_name, function, &orderings, &sdecls, &fdecls) {
+        match script(
+            key,
+            constants,
+            function_name,
+            function,
+            &orderings,
+            &sdecls,
+            &fdecls,
+        ) {
             Ok(unit) => units.push(unit),
             Err(err) => errors.push(err),
         }
@@ -104,7 +117,7 @@ fn module(
         (BTreeSet<(ModuleIdent, StructName)>, IR::FunctionSignature),
     >,
 ) -> Result<CompiledUnit, Error> {
-    let mut context = Context::new(Some(&ident));
+    let mut context = Context::new(Some(&ident), mdef.constants);
     let structs = mdef
         .structs
         .into_iter()
@@ -151,6 +164,7 @@ fn module(
 
 fn script(
     key: String,
+    constants: UniqueMap<ConstantName, H::Constant>,
     name: FunctionName,
     fdef: G::Function,
     dependency_orderings: &HashMap<ModuleIdent, usize>,
@@ -161,7 +175,7 @@ fn script(
     >,
 ) -> Result<CompiledUnit, Error> {
     let loc = name.loc();
-    let mut context = Context::new(None);
+    let mut context = Context::new(None, constants);
 
     let ((_, main), info) = function(&mut context, None, name, fdef);
 
@@ -632,6 +646,18 @@ fn types(context: &mut Context, sp!(_, t_): H::Type) -> Vec<IR::Type> {
     }
 }
 
+fn move_value(sp!(_, v_): Value) -> MoveValue {
+    use Value_ as V;
+    match v_ {
+        V::Address(a) => MoveValue::Address(LibraAddress::new(a.to_u8())),
+        V::Bytearray(bytes) => MoveValue::vector_u8(bytes),
+        V::U8(u) => MoveValue::U8(u),
+        V::U64(u) => MoveValue::U64(u),
+        V::U128(u) => MoveValue::U128(u),
+        V::Bool(b) => MoveValue::Bool(b),
+    }
+}
+
 //**************************************************************************************************
 // Commands
 //**************************************************************************************************
@@ -759,6 +785,13 @@ fn exp_(context: &mut Context, code: &mut IR::BytecodeBlock, e: H::Exp) {
                 },
             ));
         }
+        E::Constant(c) => {
+            let H::Constant {
+                signature, value, ..
+            } = context.constant(&c).clone();
+            let ty = base_type(context, signature);
+            code.push(sp(loc, B::LdConst(ty, move_value(value))));
+        }
         E::Move { var: v, .. } => {
             code.push(sp(loc, B::MoveLoc(var(v))));
         }
@@ -8,8 +8,8 @@ use crate::{
         Type, TypeName, TypeName_, Type_,
     },
     parser::ast::{
-        Field, FunctionName, FunctionVisibility, Kind, Kind_, ModuleIdent, ResourceLoc, StructName,
-        Var,
+        ConstantName, Field, FunctionName, FunctionVisibility, Kind, Kind_, ModuleIdent,
+        ResourceLoc, StructName, Var,
     },
     shared::{unique_map::UniqueMap, *},
 };
@@ -45,9 +45,15 @@ pub struct FunctionInfo {
     pub acquires: BTreeMap<StructName, Loc>,
 }
 
+pub struct ConstantInfo {
+    pub defined_loc: Loc,
+    pub signature: Type,
+}
+
 pub struct ModuleInfo {
     pub structs: UniqueMap<StructName, StructDefinition>,
     pub functions: UniqueMap<FunctionName, FunctionInfo>,
+    pub constants: UniqueMap<ConstantName, ConstantInfo>,
 }
 
 pub struct Context {
@@ -55,6 +61,7 @@ pub struct Context {
 
     pub current_module: Option<ModuleIdent>,
     pub current_function: Option<FunctionName>,
+    pub current_script_constants: Option<UniqueMap<ConstantName, ConstantInfo>>,
     pub return_type: Option<Type>,
     locals: UniqueMap<Var, Type>,
 
@@ -77,12 +84,18 @@ impl Context {
                 signature: fdef.signature.clone(),
                 acquires: fdef.acquires.clone(),
             });
-            ModuleInfo { structs, functions }
+            let constants = constant_infos(&mdef.constants);
+            ModuleInfo {
+                structs,
+                functions,
+                constants,
+            }
         });
         Context {
             subst: Subst::empty(),
             current_module: None,
             current_function: None,
+            current_script_constants: None,
             return_type: None,
             constraints: vec![],
             errors,
@@ -236,6 +249,17 @@ impl Context {
             .expect("ICE should have failed in naming")
     }
 
+    pub fn constant_info(&self, n: &ConstantName) -> &ConstantInfo {
+        let constants = match &self.current_module {
+            Some(m) => &self.module_info(m).constants,
+            None => self
+                .current_script_constants
+                .as_ref()
+                .expect("ICE constants are only accessible in a module or a script"),
+        };
+        constants.get(n).expect("ICE should have failed in naming")
+    }
+
     fn struct_definition(&self, m: &ModuleIdent, n: &StructName) -> &StructDefinition {
         let minfo = self.module_info(m);
         minfo
@@ -268,6 +292,15 @@ impl Context {
     }
 }
 
+pub fn constant_infos(
+    constants: &UniqueMap<ConstantName, N::Constant>,
+) -> UniqueMap<ConstantName, ConstantInfo> {
+    constants.ref_map(|cname, cdef| ConstantInfo {
+        defined_loc: cname.loc(),
+        signature: cdef.signature.clone(),
+    })
+}
+
 //**************************************************************************************************
 // Subst
 //**************************************************************************************************
@@ -28,6 +28,11 @@ pub fn function_signature(context: &mut Context, sig: &mut FunctionSignature) {
     type_(context, &mut sig.return_type);
 }
 
+pub fn constant(context: &mut Context, c: &mut T::Constant) {
+    type_(context, &mut c.signature);
+    exp(context, &mut c.value);
+}
+
 //**************************************************************************************************
 // Types
 //**************************************************************************************************
@@ -211,6 +216,7 @@ fn exp(context: &mut Context, e: &mut T::Exp) {
 
         E::Unit
         | E::Value(_)
+        | E::Constant(_)
         | E::Move { .. }
         | E::Copy { .. }
         | E::BorrowLocal(_, _)
@@ -84,6 +84,7 @@ fn exp(
 
         E::Unit
         | E::Value(_)
+        | E::Constant(_)
         | E::Move { .. }
         | E::Copy { .. }
         | E::BorrowLocal(_, _)
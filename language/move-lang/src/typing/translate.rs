@@ -9,7 +9,9 @@ use crate::{
     errors::Errors,
     expansion::ast::Fields,
     naming::ast::{self as N, Type, TypeName_, Type_},
-    parser::ast::{BinOp_, Field, FunctionName, ModuleIdent, StructName, UnaryOp_, Var},
+    parser::ast::{
+        BinOp_, ConstantName, Field, FunctionName, ModuleIdent, StructName, UnaryOp_, Var,
+    },
     shared::{unique_map::UniqueMap, *},
     typing::ast as T,
 };
@@ -49,18 +51,21 @@ fn module(
         is_source_module,
         dependency_order,
         mut structs,
+        constants: n_constants,
         functions: n_functions,
         ..
     } = mdef;
     structs
         .iter_mut()
         .for_each(|(name, s)| struct_def(context, name, s));
+    let constants = n_constants.map(|name, c| constant(context, name, c));
     let functions = n_functions.map(|name, f| function(context, name, f, false));
     assert!(context.constraints.is_empty());
     T::ModuleDefinition {
         is_source_module,
         dependency_order,
         structs,
+        constants,
         functions,
     }
 }
@@ -79,12 +84,17 @@ fn script(context: &mut Context, nscript: N::Script) -> T::Script {
     context.current_module = None;
     let N::Script {
         loc,
+        constants: n_constants,
         function_name,
         function: nfunction,
     } = nscript;
+    context.current_script_constants = Some(core::constant_infos(&n_constants));
+    let constants = n_constants.map(|name, c| constant(context, name, c));
     let function = function(context, function_name.clone(), nfunction, true);
+    context.current_script_constants = None;
     T::Script {
         loc,
+        constants,
         function_name,
         function,
     }
@@ -137,6 +147,70 @@ fn check_primitive_script_arg(context: &mut Context, mloc: Loc, idx: usize, ty:
     }
 }
 
+//**************************************************************************************************
+// Constants
+//**************************************************************************************************
+
+fn constant(context: &mut Context, _name: ConstantName, nconstant: N::Constant) -> T::Constant {
+    assert!(context.constraints.is_empty());
+    context.reset_for_module_item();
+
+    let N::Constant {
+        loc,
+        signature,
+        value: nvalue,
+    } = nconstant;
+    check_constant_type(context, loc, &signature);
+
+    let value = exp_(context, nvalue);
+    let vloc = value.exp.loc;
+    subtype(
+        context,
+        vloc,
+        || "Invalid constant value",
+        value.ty.clone(),
+        signature.clone(),
+    );
+    core::solve_constraints(context);
+
+    let mut tconstant = T::Constant {
+        loc,
+        signature,
+        value,
+    };
+    expand::constant(context, &mut tconstant);
+    tconstant
+}
+
+fn check_constant_type(context: &mut Context, cloc: Loc, ty: &Type) {
+    let loc = ty.loc;
+    let acceptable_types = vec![
+        Type_::u8(loc),
+        Type_::u64(loc),
+        Type_::u128(loc),
+        Type_::bool(loc),
+        Type_::address(loc),
+        Type_::vector(loc, Type_::u8(loc)),
+    ];
+    let ty_is_an_acceptable_type = acceptable_types.iter().any(|acceptable_type| {
+        subtype_no_report(context, ty.clone(), acceptable_type.clone()).is_ok()
+    });
+    if !ty_is_an_acceptable_type {
+        let tys = acceptable_types
+            .iter()
+            .map(|t| core::error_format(t, &Subst::empty()));
+        let tmsg = format!(
+            "Found: {}. But expected one of: {}",
+            core::error_format(ty, &Subst::empty()),
+            format_comma(tys),
+        );
+        context.error(vec![
+            (cloc, "Unpermitted constant type".to_string()),
+            (loc, tmsg),
+        ]);
+    }
+}
+
 //**************************************************************************************************
 // Functions
 //**************************************************************************************************
@@ -532,6 +606,10 @@ fn exp_(context: &mut Context, sp!(eloc, ne_): N::Exp) -> T::Exp {
             let ty = context.get_local(eloc, "local usage", &var);
             (ty, TE::Use(var))
         }
+        NE::Constant(c) => {
+            let ty = context.constant_info(&c).signature.clone();
+            (ty, TE::Constant(c))
+        }
 
         NE::ModuleCall(m, f, ty_args_opt, sp!(argloc, nargs_)) => {
             let args = exp_vec(context, nargs_);
@@ -0,0 +1,237 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for unit tests written in Move.
+//!
+//! A module function annotated with `#[test]` is a unit test. It takes no arguments other than
+//! `&signer`s and returns nothing. `#[expected_failure]` marks a test which must fail, and
+//! `#[expected_failure(abort_code = <u64>)]` one which must abort with the given code.
+//!
+//! Test functions are only compiled when building for tests (see `move_compile_for_testing`):
+//! the other entry points of the compiler drop them right after parsing, so that they never end
+//! up in published bytecode.
+
+use crate::{
+    errors::*,
+    parser::ast::{
+        Definition, Function, FunctionAttribute_, FunctionBody_, ModuleAccess_, ModuleDefinition,
+        ModuleMember, Program, Type_,
+    },
+    shared::{Address, Identifier},
+};
+use move_ir_types::location::*;
+
+/// The outcome expected from a test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpectedFailure {
+    /// The test must fail, in any way.
+    Any,
+    /// The test must abort with this code.
+    AbortCode(u64),
+}
+
+#[derive(Clone, Debug)]
+pub struct TestCase {
+    pub function_name: String,
+    pub loc: Loc,
+    /// `None` if the test must succeed.
+    pub expected_failure: Option<ExpectedFailure>,
+    /// Number of `&signer` parameters.
+    pub signers: usize,
+}
+
+/// The tests of a module.
+#[derive(Clone, Debug)]
+pub struct ModuleTestPlan {
+    pub address: Address,
+    pub module_name: String,
+    pub tests: Vec<TestCase>,
+}
+
+/// The tests of the source definitions of a program, in declaration order.
+pub type TestPlan = Vec<ModuleTestPlan>;
+
+/// Checks the attributes of the functions of `prog`, and removes the test functions from it.
+/// With `keep_source_tests`, the test functions of the source definitions are kept and returned
+/// in a `TestPlan`.
+pub fn filter_test_functions(
+    prog: Program,
+    sender_opt: Option<Address>,
+    keep_source_tests: bool,
+) -> Result<(Program, TestPlan), Errors> {
+    let Program {
+        source_definitions,
+        lib_definitions,
+    } = prog;
+    let mut context = Context {
+        sender_opt,
+        errors: vec![],
+        test_plan: vec![],
+    };
+    let source_definitions = source_definitions
+        .into_iter()
+        .map(|def| context.definition(def, keep_source_tests))
+        .collect();
+    let lib_definitions = lib_definitions
+        .into_iter()
+        .map(|def| context.definition(def, false))
+        .collect();
+    check_errors(context.errors)?;
+    Ok((
+        Program {
+            source_definitions,
+            lib_definitions,
+        },
+        context.test_plan,
+    ))
+}
+
+struct Context {
+    sender_opt: Option<Address>,
+    errors: Errors,
+    test_plan: TestPlan,
+}
+
+impl Context {
+    fn definition(&mut self, def: Definition, keep_tests: bool) -> Definition {
+        match def {
+            Definition::Module(m) => {
+                let address_opt = self.sender_opt;
+                Definition::Module(self.module(address_opt, m, keep_tests))
+            }
+            Definition::Address(loc, address, modules) => {
+                let modules = modules
+                    .into_iter()
+                    .map(|m| self.module(Some(address), m, keep_tests))
+                    .collect();
+                Definition::Address(loc, address, modules)
+            }
+            Definition::Script(script) => {
+                for attribute in &script.function.attributes {
+                    self.errors.push(vec![(
+                        attribute.loc,
+                        "Invalid attribute. Attributes are only allowed on module functions"
+                            .to_owned(),
+                    )]);
+                }
+                Definition::Script(script)
+            }
+        }
+    }
+
+    fn module(
+        &mut self,
+        address_opt: Option<Address>,
+        mut m: ModuleDefinition,
+        keep_tests: bool,
+    ) -> ModuleDefinition {
+        let mut tests = vec![];
+        let members = std::mem::replace(&mut m.members, vec![]);
+        for member in members {
+            match member {
+                ModuleMember::Function(f) => match self.test_case(&f) {
+                    Some(test) => {
+                        if keep_tests {
+                            tests.push(test);
+                            m.members.push(ModuleMember::Function(f));
+                        }
+                    }
+                    None => m.members.push(ModuleMember::Function(f)),
+                },
+                member => m.members.push(member),
+            }
+        }
+        // Without an address, the module is reported as invalid during expansion.
+        if let (Some(address), false) = (address_opt, tests.is_empty()) {
+            self.test_plan.push(ModuleTestPlan {
+                address,
+                module_name: m.name.0.value.clone(),
+                tests,
+            });
+        }
+        m
+    }
+
+    /// The test case of `f` if it is a test, checking its attributes.
+    fn test_case(&mut self, f: &Function) -> Option<TestCase> {
+        let mut test_loc = None;
+        let mut expected_failure = None;
+        for attribute in &f.attributes {
+            let previous_loc = match &attribute.value {
+                FunctionAttribute_::Test => test_loc.replace(attribute.loc),
+                FunctionAttribute_::ExpectedFailure(abort_code) => {
+                    let expected = match abort_code {
+                        Some(abort_code) => ExpectedFailure::AbortCode(*abort_code),
+                        None => ExpectedFailure::Any,
+                    };
+                    expected_failure
+                        .replace((attribute.loc, expected))
+                        .map(|(loc, _)| loc)
+                }
+            };
+            if let Some(previous_loc) = previous_loc {
+                self.errors.push(vec![
+                    (attribute.loc, "Duplicate attribute".to_owned()),
+                    (previous_loc, "Previously declared here".to_owned()),
+                ]);
+            }
+        }
+
+        let test_loc = match test_loc {
+            Some(test_loc) => test_loc,
+            None => {
+                if let Some((loc, _)) = expected_failure {
+                    self.errors.push(vec![(
+                        loc,
+                        "Invalid attribute. 'expected_failure' is only allowed on functions \
+                         annotated with 'test'"
+                            .to_owned(),
+                    )]);
+                }
+                return None;
+            }
+        };
+        let mut invalid = |msg: &str| {
+            self.errors.push(vec![
+                (f.name.loc(), format!("Invalid test function. {}", msg)),
+                (test_loc, "Declared as a test here".to_owned()),
+            ])
+        };
+        if let FunctionBody_::Native = f.body.value {
+            invalid("Test functions cannot be native");
+        }
+        if !f.signature.type_parameters.is_empty() {
+            invalid("Test functions cannot have type parameters");
+        }
+        if !f
+            .signature
+            .parameters
+            .iter()
+            .all(|(_, ty)| is_signer_ref(&ty.value))
+        {
+            invalid("Test functions can only take '&signer' parameters");
+        }
+        if f.signature.return_type.value != Type_::Unit {
+            invalid("Test functions cannot return values");
+        }
+        Some(TestCase {
+            function_name: f.name.0.value.clone(),
+            loc: f.loc,
+            expected_failure: expected_failure.map(|(_, expected)| expected),
+            signers: f.signature.parameters.len(),
+        })
+    }
+}
+
+fn is_signer_ref(ty: &Type_) -> bool {
+    match ty {
+        Type_::Ref(false, inner) => match &inner.value {
+            Type_::Apply(access, ty_args) => {
+                ty_args.is_empty()
+                    && matches!(&access.value, ModuleAccess_::Name(n) if n.value == "signer")
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,47 @@
+module M {
+    const ZERO: u8 = 0;
+    const MAX_U64: u64 = 18446744073709551615;
+    const BIG: u128 = 340282366920938463463374607431768211455;
+    const FLAG: bool = true;
+    const ADDR: address = 0x42;
+    const BYTES: vector<u8> = x"0123";
+    const NAME: vector<u8> = b"Libra";
+
+    public fun zero(): u8 { ZERO }
+    public fun max_u64(): u64 { MAX_U64 }
+    public fun big(): u128 { BIG }
+    public fun flag(): bool { FLAG }
+    public fun addr(): address { ADDR }
+    public fun bytes(): vector<u8> { BYTES }
+    public fun name(): vector<u8> { NAME }
+}
+
+//! new-transaction
+script {
+use 0x0::Transaction;
+use {{default}}::M;
+
+const ERROR_CODE: u64 = 42;
+
+fun main() {
+    Transaction::assert(M::zero() == 0, ERROR_CODE);
+    Transaction::assert(M::max_u64() == 18446744073709551615, ERROR_CODE + 1);
+    Transaction::assert(M::big() == 340282366920938463463374607431768211455, ERROR_CODE + 2);
+    Transaction::assert(M::flag(), ERROR_CODE + 3);
+    Transaction::assert(M::addr() == 0x42, ERROR_CODE + 4);
+    Transaction::assert(M::bytes() == x"0123", ERROR_CODE + 5);
+    Transaction::assert(M::name() == x"4c69627261", ERROR_CODE + 6);
+}
+}
+// check: EXECUTED
+
+//! new-transaction
+script {
+const ERROR_CODE: u64 = 42;
+
+fun main() {
+    abort ERROR_CODE
+}
+}
+// check: ABORTED
+// check: 42
@@ -34,7 +34,7 @@ use 0x0::LibraBlock;
 use 0x0::Vector;
 
 fun main(account: &signer) {
-    LibraBlock::block_prologue(account, 1, 10, Vector::empty<address>(), {{vivian}});
+    LibraBlock::block_prologue(account, 1, 10, Vector::empty<address>(), {{vivian}}, Vector::empty<address>());
 }
 }
 // check: ABORTED
@@ -0,0 +1,3 @@
+module M {
+    const c: u64 = 0;
+}
@@ -0,0 +1,12 @@
+address 0x2 {
+module X {
+    const C: u64 = 0;
+}
+
+module M {
+    use 0x2::X::C;
+    fun t(): u64 {
+        C
+    }
+}
+}
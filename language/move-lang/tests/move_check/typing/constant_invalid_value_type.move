@@ -0,0 +1,3 @@
+module M {
+    const C: u64 = false;
+}
@@ -0,0 +1,4 @@
+module M {
+    struct S {}
+    const C: S = 0;
+}
@@ -0,0 +1,28 @@
+module M {
+    const C1: u8 = 0;
+    const C2: u64 = 0;
+    const C3: u128 = 0;
+    const C4: bool = false;
+    const C5: address = 0x0;
+    const C6: vector<u8> = x"0123";
+    const C7: vector<u8> = b"abcd";
+    const C8: u64 = 18446744073709551615;
+
+    fun t1(): u8 { C1 }
+    fun t2(): u64 { C2 + C8 }
+    fun t3(): u128 { C3 }
+    fun t4(): bool { C4 && C1 == 0 }
+    fun t5(): address { C5 }
+    fun t6(): vector<u8> { C6 }
+    fun t7(): &vector<u8> { &C7 }
+}
+
+script {
+    const C1: u8 = 0;
+    const C2: bool = true;
+
+    fun main() {
+        let _x: u8 = C1;
+        if (C2) () else ();
+    }
+}
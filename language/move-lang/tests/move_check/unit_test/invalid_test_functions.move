@@ -0,0 +1,7 @@
+module M {
+    #[test]
+    fun takes_u64(_x: u64) {}
+
+    #[expected_failure]
+    fun not_a_test() {}
+}
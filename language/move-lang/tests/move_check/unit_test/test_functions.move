@@ -0,0 +1,21 @@
+module M {
+    fun add(a: u64, b: u64): u64 {
+        a + b
+    }
+
+    #[test]
+    fun add_works() {
+        assert(add(1, 2) == 3, 0);
+    }
+
+    #[test]
+    #[expected_failure]
+    fun add_overflows() {
+        add(18446744073709551615, 1);
+    }
+
+    #[test, expected_failure(abort_code = 7u64)]
+    fun aborts_with_signers(_a: &signer, _b: &signer) {
+        abort 7
+    }
+}
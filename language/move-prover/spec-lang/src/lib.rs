@@ -142,6 +142,7 @@ fn run_spec_checker(
                 } => {
                     let move_lang::expansion::ast::Script {
                         loc,
+                        constants,
                         function_name,
                         function,
                         specs,
@@ -182,6 +183,7 @@ fn run_spec_checker(
                         loc,
                         is_source_module: true,
                         structs: UniqueMap::new(),
+                        constants,
                         functions,
                         specs,
                     };
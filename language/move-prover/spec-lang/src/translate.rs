@@ -76,6 +76,8 @@ pub struct Translator<'env> {
     reverse_struct_table: BTreeMap<(ModuleId, StructId), QualifiedSymbol>,
     /// A symbol table for functions.
     fun_table: BTreeMap<QualifiedSymbol, FunEntry>,
+    /// A symbol table for constants.
+    const_table: BTreeMap<QualifiedSymbol, ConstEntry>,
 }
 
 /// A declaration of a specification function or operator in the translator state.
@@ -138,6 +140,13 @@ struct FunEntry {
     result_type: Type,
 }
 
+/// A declaration of a constant.
+#[derive(Debug, Clone)]
+struct ConstEntry {
+    ty: Type,
+    value: Value,
+}
+
 /// ## General
 
 impl<'env> Translator<'env> {
@@ -152,6 +161,7 @@ impl<'env> Translator<'env> {
             struct_table: BTreeMap::new(),
             reverse_struct_table: BTreeMap::new(),
             fun_table: BTreeMap::new(),
+            const_table: BTreeMap::new(),
         };
         translator.declare_builtins();
         translator
@@ -299,6 +309,13 @@ impl<'env> Translator<'env> {
         assert!(self.fun_table.insert(name, entry).is_none());
     }
 
+    /// Defines a constant.
+    fn define_const(&mut self, name: QualifiedSymbol, ty: Type, value: Value) {
+        let entry = ConstEntry { ty, value };
+        // Duplicate declarations have been checked by the move compiler.
+        assert!(self.const_table.insert(name, entry).is_none());
+    }
+
     /// Looks up a type (struct), reporting an error if it is not found.
     fn lookup_type(&self, loc: &Loc, name: &QualifiedSymbol) -> Type {
         self.struct_table
@@ -904,6 +921,9 @@ impl<'env, 'translator> ModuleTranslator<'env, 'translator> {
         for (name, struct_def) in &module_def.structs {
             self.decl_ana_struct(&name, struct_def);
         }
+        for (name, const_def) in &module_def.constants {
+            self.decl_ana_const(&name, const_def);
+        }
         for (name, fun_def) in &module_def.functions {
             self.decl_ana_fun(&name, fun_def);
         }
@@ -928,6 +948,21 @@ impl<'env, 'translator> ModuleTranslator<'env, 'translator> {
         );
     }
 
+    fn decl_ana_const(&mut self, name: &PA::ConstantName, def: &EA::Constant) {
+        let qsym = self.qualified_by_module_from_name(&name.0);
+        let mut et = ExpTranslator::new(self);
+        let ty = et.translate_type(&def.signature);
+        let value = match &def.value.value {
+            EA::Exp_::Value(v) => et.translate_value(v).map(|(value, _)| value),
+            EA::Exp_::InferredNum(x) => Some(Value::Number(BigUint::from_u128(*x).unwrap())),
+            // Invalid constant values have been reported by the move compiler.
+            _ => None,
+        };
+        if let Some(value) = value {
+            et.parent.parent.define_const(qsym, ty, value);
+        }
+    }
+
     fn decl_ana_fun(&mut self, name: &PA::FunctionName, def: &EA::Function) {
         let qsym = self.qualified_by_module_from_name(&name.0);
         let fun_id = FunId::new(qsym.symbol);
@@ -3524,6 +3559,12 @@ impl<'env, 'translator, 'module_translator> ExpTranslator<'env, 'translator, 'mo
                 self.parent.qualified_by_module(sym)
             }
         };
+        if let Some(entry) = self.parent.parent.const_table.get(&spec_var_sym) {
+            let ConstEntry { ty, value, .. } = entry.clone();
+            let ty = self.check_type(loc, &ty, expected_type, "in const expression");
+            let id = self.new_node_id_with_type_loc(&ty, loc);
+            return Exp::Value(id, value);
+        }
         if let Some(entry) = self.parent.parent.spec_var_table.get(&spec_var_sym) {
             let type_args = type_args.unwrap_or(&[]);
             if entry.type_params.len() != type_args.len() {
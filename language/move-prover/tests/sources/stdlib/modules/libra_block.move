@@ -27,6 +27,9 @@ module LibraBlock {
 
       // On-chain time during  he block at the given height
       time_microseconds: u64,
+
+      // The expected proposers of the rounds skipped before this block
+      failed_proposers: vector<address>,
     }
 
     // This can only be invoked by the Association address, and only a single time.
@@ -50,12 +53,13 @@ module LibraBlock {
         round: u64,
         timestamp: u64,
         previous_block_votes: vector<address>,
-        proposer: address
+        proposer: address,
+        failed_proposers: vector<address>
     ) acquires BlockMetadata {
       // Can only be invoked by LibraVM privilege.
       Transaction::assert(Transaction::sender() == 0x0, 33);
 
-      process_block_prologue(round, timestamp, previous_block_votes, proposer);
+      process_block_prologue(round, timestamp, previous_block_votes, proposer, failed_proposers);
 
       // Currently distribute once per-block.
       // TODO: Once we have a better on-chain representation of epochs we will make this per-epoch.
@@ -67,7 +71,8 @@ module LibraBlock {
         round: u64,
         timestamp: u64,
         previous_block_votes: vector<address>,
-        proposer: address
+        proposer: address,
+        failed_proposers: vector<address>
     ) acquires BlockMetadata {
         let block_metadata_ref = borrow_global_mut<BlockMetadata>(0xA550C18);
 
@@ -82,6 +87,7 @@ module LibraBlock {
             proposer: proposer,
             previous_block_votes: previous_block_votes,
             time_microseconds: timestamp,
+            failed_proposers: failed_proposers,
           }
         );
     }
@@ -0,0 +1,224 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Attribution of the gas charged by the interpreter to call frames and instruction types.
+//!
+//! The `GasProfiler` is a `VMTracer` following the call stack of the executions it traces. The
+//! gas of an instruction is charged to the stack of frames executing it, and the gas of a native
+//! function to the stack calling it, whose top is the native function. Gas charged outside of the
+//! interpreter, e.g. the intrinsic gas of a transaction, is not seen by the profiler.
+
+use crate::tracer::{TraceEvent, VMTracer};
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+use vm::file_format::Bytecode;
+
+/// The instruction type of the gas charged by native functions
+pub const NATIVE_FUNCTION: &str = "<native>";
+
+/// The gas of a function over all its calls
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FunctionGas {
+    pub calls: u64,
+    /// The gas charged by the function's own instructions
+    pub self_gas: u64,
+    /// The gas charged while the function is on the call stack, callees included
+    pub total_gas: u64,
+}
+
+/// The gas of all the instructions of a type
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InstructionGas {
+    pub count: u64,
+    pub gas: u64,
+}
+
+/// The gas charged by the executions traced by a `GasProfiler`
+#[derive(Clone, Debug, Default)]
+pub struct GasProfile {
+    /// The gas charged by the frame on top of each call stack, outermost function first
+    pub stacks: BTreeMap<Vec<String>, u64>,
+    /// Functions by `0x<address>::Module::function` name, or `Script::main`
+    pub functions: BTreeMap<String, FunctionGas>,
+    /// Instructions by type, e.g. `BorrowGlobal`, natives being `NATIVE_FUNCTION`
+    pub instructions: BTreeMap<String, InstructionGas>,
+    pub total_gas: u64,
+}
+
+/// A tracer computing the `GasProfile` of the executions it traces. Executions are expected to
+/// be traced one at a time.
+#[derive(Default)]
+pub struct GasProfiler {
+    state: Mutex<ProfilerState>,
+}
+
+#[derive(Default)]
+struct ProfilerState {
+    profile: GasProfile,
+    /// The call stack of the running execution
+    stack: Vec<String>,
+    /// The type of the instruction being executed, or `None` if a native function is
+    instruction: Option<String>,
+}
+
+/// The type of an instruction, i.e. its name without arguments
+fn instruction_type(instruction: &Bytecode) -> String {
+    let instruction = format!("{:?}", instruction);
+    match instruction.find('(') {
+        Some(arguments_start) => instruction[..arguments_start].to_string(),
+        None => instruction,
+    }
+}
+
+impl ProfilerState {
+    fn trace(&mut self, event: &TraceEvent) {
+        match event {
+            TraceEvent::Call { function, .. } => {
+                let name = function.to_string();
+                self.profile
+                    .functions
+                    .entry(name.clone())
+                    .or_default()
+                    .calls += 1;
+                self.stack.push(name);
+                self.instruction = None;
+            }
+            TraceEvent::Return { .. } => {
+                self.stack.pop();
+            }
+            TraceEvent::Instruction { instruction, .. } => {
+                let instruction = instruction_type(instruction);
+                self.profile
+                    .instructions
+                    .entry(instruction.clone())
+                    .or_default()
+                    .count += 1;
+                self.instruction = Some(instruction);
+            }
+            TraceEvent::GasCharged { amount, .. } => self.charge(*amount),
+            TraceEvent::GlobalAccess { .. } => (),
+            TraceEvent::Error { .. } => {
+                self.stack.clear();
+                self.instruction = None;
+            }
+        }
+    }
+
+    fn charge(&mut self, amount: u64) {
+        let profile = &mut self.profile;
+        profile.total_gas += amount;
+        *profile.stacks.entry(self.stack.clone()).or_insert(0) += amount;
+
+        let instruction = self
+            .instruction
+            .clone()
+            .unwrap_or_else(|| NATIVE_FUNCTION.to_string());
+        profile.instructions.entry(instruction).or_default().gas += amount;
+
+        if let Some(top) = self.stack.last() {
+            profile.functions.entry(top.clone()).or_default().self_gas += amount;
+        }
+        // Recursive functions are only charged once per stack
+        let mut on_stack = self.stack.clone();
+        on_stack.sort();
+        on_stack.dedup();
+        for function in on_stack {
+            profile.functions.entry(function).or_default().total_gas += amount;
+        }
+    }
+}
+
+impl GasProfiler {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// The profile of the executions traced so far
+    pub fn profile(&self) -> GasProfile {
+        self.state
+            .lock()
+            .map(|state| state.profile.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl VMTracer for GasProfiler {
+    fn trace(&self, event: &TraceEvent) {
+        if let Ok(mut state) = self.state.lock() {
+            state.trace(event)
+        }
+    }
+}
+
+fn percent(gas: u64, total_gas: u64) -> f64 {
+    if total_gas == 0 {
+        0.0
+    } else {
+        (gas as f64 / total_gas as f64) * 100.0
+    }
+}
+
+impl GasProfile {
+    /// Outputs the gas of each call stack as folded stacks, the input format of flame graph
+    /// tools: one line per stack, with the frames separated by `;` followed by the gas.
+    pub fn output_folded<W: Write>(&self, folded_writer: &mut W) -> io::Result<()> {
+        for (stack, gas) in self.stacks.iter() {
+            if *gas > 0 {
+                writeln!(folded_writer, "{} {}", stack.join(";"), gas)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Outputs the gas of each function and each instruction type, most expensive first.
+    pub fn output_table<W: Write>(&self, table_writer: &mut W) -> io::Result<()> {
+        writeln!(table_writer, "Total gas: {}", self.total_gas)?;
+        writeln!(table_writer)?;
+
+        let mut functions: Vec<_> = self.functions.iter().collect();
+        functions.sort_by(|(name1, gas1), (name2, gas2)| {
+            (gas2.total_gas, gas2.self_gas, name1).cmp(&(gas1.total_gas, gas1.self_gas, name2))
+        });
+        writeln!(
+            table_writer,
+            "{:>10} {:>10} {:>8} {:>10} {:>8}  function",
+            "calls", "total", "total %", "self", "self %"
+        )?;
+        for (name, gas) in functions {
+            writeln!(
+                table_writer,
+                "{:>10} {:>10} {:>8.2} {:>10} {:>8.2}  {}",
+                gas.calls,
+                gas.total_gas,
+                percent(gas.total_gas, self.total_gas),
+                gas.self_gas,
+                percent(gas.self_gas, self.total_gas),
+                name
+            )?;
+        }
+        writeln!(table_writer)?;
+
+        let mut instructions: Vec<_> = self.instructions.iter().collect();
+        instructions
+            .sort_by(|(name1, gas1), (name2, gas2)| (gas2.gas, name1).cmp(&(gas1.gas, name2)));
+        writeln!(
+            table_writer,
+            "{:>10} {:>10} {:>8}  instruction",
+            "count", "gas", "gas %"
+        )?;
+        for (name, gas) in instructions {
+            writeln!(
+                table_writer,
+                "{:>10} {:>10} {:>8.2}  {}",
+                gas.count,
+                gas.gas,
+                percent(gas.gas, self.total_gas),
+                name
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
     loader::{Function, Loader, Resolver},
     native_functions::FunctionContext,
     trace,
+    tracer::{GlobalAccessKind, TraceEvent, TracedFunction, VMTracer},
 };
 use libra_logger::prelude::*;
 use libra_types::{
@@ -59,6 +60,10 @@ pub(crate) struct Interpreter {
     call_stack: CallStack,
     /// The sender of the transaction. This can materialize as an argument to a script.
     sender: AccountAddress,
+    /// The tracer of the execution, if any.
+    tracer: Option<Arc<dyn VMTracer>>,
+    /// The gas left at the last trace event, to report the gas charged since.
+    traced_gas_left: u64,
 }
 
 impl Interpreter {
@@ -72,20 +77,27 @@ impl Interpreter {
         data_store: &mut dyn DataStore,
         cost_strategy: &mut CostStrategy,
         loader: &Loader,
+        tracer: Option<Arc<dyn VMTracer>>,
     ) -> VMResult<()> {
         // We count the intrinsic cost of the transaction here, since that needs to also cover the
         // setup of the function.
-        let mut interp = Self::new(sender);
+        let mut interp = Self::new(sender, tracer, cost_strategy);
         interp.execute(loader, data_store, cost_strategy, function, ty_args, args)
     }
 
     /// Create a new instance of an `Interpreter` in the context of a transaction with a
     /// given module cache and gas schedule.
-    fn new(sender: AccountAddress) -> Self {
+    fn new(
+        sender: AccountAddress,
+        tracer: Option<Arc<dyn VMTracer>>,
+        cost_strategy: &CostStrategy,
+    ) -> Self {
         Interpreter {
             operand_stack: Stack::new(),
             call_stack: CallStack::new(),
             sender,
+            tracer,
+            traced_gas_left: cost_strategy.remaining_gas().get(),
         }
     }
 
@@ -101,7 +113,12 @@ impl Interpreter {
     ) -> VMResult<()> {
         // No unwinding of the call stack and value stack need to be done here -- the context will
         // take care of that.
-        self.execute_main(loader, data_store, cost_strategy, function, ty_args, args)
+        let result = self.execute_main(loader, data_store, cost_strategy, function, ty_args, args);
+        if let Err(err) = &result {
+            self.trace_gas(cost_strategy);
+            self.trace_error(err);
+        }
+        result
     }
 
     /// Main loop for the execution of a function.
@@ -122,6 +139,13 @@ impl Interpreter {
         args: Vec<Value>,
     ) -> VMResult<()> {
         let mut locals = Locals::new(function.local_count());
+        if let Some(tracer) = &self.tracer {
+            tracer.trace(&TraceEvent::Call {
+                function: TracedFunction::new(&function),
+                ty_args: &ty_args,
+                args: &args,
+            });
+        }
         // TODO: assert consistency of args and function formals
         for (i, value) in args.into_iter().enumerate() {
             locals.store_loc(i, value)?;
@@ -135,6 +159,8 @@ impl Interpreter {
                     .or_else(|err| Err(self.maybe_core_dump(err, &current_frame)))?;
             match exit_code {
                 ExitCode::Return => {
+                    self.trace_gas(cost_strategy);
+                    self.trace_return(&current_frame.function);
                     current_frame.locals.check_resources_for_return()?;
                     if let Some(frame) = self.call_stack.pop() {
                         current_frame = frame;
@@ -148,6 +174,7 @@ impl Interpreter {
                         AbstractMemorySize::new(1 as GasCarrier),
                     )?;
                     let func = resolver.function_at(fh_idx);
+                    self.trace_gas(cost_strategy);
                     if func.is_native() {
                         self.call_native(&resolver, data_store, cost_strategy, func, vec![])?;
                         continue;
@@ -171,6 +198,7 @@ impl Interpreter {
                     )?;
                     let func = loader.function_at(func_inst.handle());
                     let ty_args = func_inst.materialize(current_frame.ty_args())?;
+                    self.trace_gas(cost_strategy);
                     if func.is_native() {
                         self.call_native(&resolver, data_store, cost_strategy, func, ty_args)?;
                         continue;
@@ -198,6 +226,7 @@ impl Interpreter {
     fn make_call_frame(&mut self, func: Arc<Function>, ty_args: Vec<Type>) -> VMResult<Frame> {
         let mut locals = Locals::new(func.local_count());
         let arg_count = func.arg_count();
+        self.trace_call(&func, &ty_args);
         for i in 0..arg_count {
             locals.store_loc(arg_count - i - 1, self.operand_stack.pop()?)?;
         }
@@ -215,17 +244,21 @@ impl Interpreter {
     ) -> VMResult<()> {
         let mut arguments = VecDeque::new();
         let expected_args = function.arg_count();
+        self.trace_call(&function, &ty_args);
         for _ in 0..expected_args {
             arguments.push_front(self.operand_stack.pop()?);
         }
         let mut native_context = FunctionContext::new(self, data_store, cost_strategy, resolver);
         let native_function = function.get_native()?;
         let result = native_function.dispatch(&mut native_context, ty_args, arguments)?;
-        cost_strategy.deduct_gas(result.cost)?;
+        let charged = cost_strategy.deduct_gas(result.cost);
+        self.trace_gas(cost_strategy);
+        charged?;
         result.result.and_then(|values| {
             for value in values {
                 self.operand_stack.push(value)?;
             }
+            self.trace_return(&function);
             Ok(())
         })
     }
@@ -338,7 +371,9 @@ impl Interpreter {
     ) -> VMResult<AbstractMemorySize<GasCarrier>> {
         let g = borrow_global(data_store, &ap, struct_ty)?;
         let size = g.size();
-        self.operand_stack.push(g.borrow_global()?)?;
+        let reference = g.borrow_global()?;
+        self.trace_global_access(GlobalAccessKind::BorrowGlobal, &ap, struct_ty, &reference);
+        self.operand_stack.push(reference)?;
         Ok(size)
     }
 
@@ -350,7 +385,9 @@ impl Interpreter {
         struct_ty: &FatStructType,
     ) -> VMResult<AbstractMemorySize<GasCarrier>> {
         let (exists, mem_size) = resource_exists(data_store, &ap, struct_ty)?;
-        self.operand_stack.push(Value::bool(exists))?;
+        let exists = Value::bool(exists);
+        self.trace_global_access(GlobalAccessKind::Exists, &ap, struct_ty, &exists);
+        self.operand_stack.push(exists)?;
         Ok(mem_size)
     }
 
@@ -363,6 +400,7 @@ impl Interpreter {
     ) -> VMResult<AbstractMemorySize<GasCarrier>> {
         let resource = move_resource_from(data_store, &ap, struct_ty)?;
         let size = resource.size();
+        self.trace_global_access(GlobalAccessKind::MoveFrom, &ap, struct_ty, &resource);
         self.operand_stack.push(resource)?;
         Ok(size)
     }
@@ -376,8 +414,9 @@ impl Interpreter {
         AccessPath,
         &FatStructType,
     ) -> VMResult<AbstractMemorySize<GasCarrier>> {
-        |_interpreter, data_store, ap, struct_ty| {
+        |interpreter, data_store, ap, struct_ty| {
             let size = resource.size();
+            let resource = interpreter.trace_move_to(&ap, struct_ty, resource)?;
             move_resource_to(data_store, &ap, struct_ty, resource)?;
             Ok(size)
         }
@@ -392,10 +431,106 @@ impl Interpreter {
     ) -> VMResult<AbstractMemorySize<GasCarrier>> {
         let resource = self.operand_stack.pop_as::<Struct>()?;
         let size = resource.size();
+        let resource = self.trace_move_to(&ap, struct_ty, resource)?;
         move_resource_to(data_store, &ap, struct_ty, resource)?;
         Ok(size)
     }
 
+    //
+    // Tracing helpers.
+    //
+
+    /// Reports the call of `function`, whose arguments are on top of the operand stack.
+    fn trace_call(&self, function: &Function, ty_args: &[Type]) {
+        if let Some(tracer) = &self.tracer {
+            let stack = &self.operand_stack.0;
+            let args_start = stack.len().saturating_sub(function.arg_count());
+            tracer.trace(&TraceEvent::Call {
+                function: TracedFunction::new(function),
+                ty_args,
+                args: &stack[args_start..],
+            });
+        }
+    }
+
+    /// Reports the return of `function`, whose return values are on top of the operand stack.
+    fn trace_return(&self, function: &Function) {
+        if let Some(tracer) = &self.tracer {
+            let stack = &self.operand_stack.0;
+            let return_start = stack.len().saturating_sub(function.return_count());
+            tracer.trace(&TraceEvent::Return {
+                function: TracedFunction::new(function),
+                return_values: &stack[return_start..],
+            });
+        }
+    }
+
+    /// Reports the instruction at `pc` in `frame`, about to be executed.
+    fn trace_instruction(&self, frame: &Frame, pc: u16, instruction: &Bytecode) {
+        if let Some(tracer) = &self.tracer {
+            tracer.trace(&TraceEvent::Instruction {
+                function: TracedFunction::new(&frame.function),
+                pc,
+                instruction,
+                stack: &self.operand_stack.0,
+                locals: &frame.locals,
+            });
+        }
+    }
+
+    /// Reports the gas charged since the last report, if any.
+    fn trace_gas(&mut self, cost_strategy: &CostStrategy) {
+        if let Some(tracer) = &self.tracer {
+            let gas_left = cost_strategy.remaining_gas().get();
+            if gas_left < self.traced_gas_left {
+                tracer.trace(&TraceEvent::GasCharged {
+                    amount: self.traced_gas_left - gas_left,
+                    gas_left,
+                });
+            }
+            self.traced_gas_left = gas_left;
+        }
+    }
+
+    /// Reports the failure of the execution, which discards all its frames.
+    fn trace_error(&self, error: &VMStatus) {
+        if let Some(tracer) = &self.tracer {
+            tracer.trace(&TraceEvent::Error { error });
+        }
+    }
+
+    fn trace_global_access(
+        &self,
+        kind: GlobalAccessKind,
+        ap: &AccessPath,
+        struct_ty: &FatStructType,
+        value: &Value,
+    ) {
+        if let Some(tracer) = &self.tracer {
+            tracer.trace(&TraceEvent::GlobalAccess {
+                kind,
+                address: ap.address,
+                struct_type: struct_ty,
+                value,
+            });
+        }
+    }
+
+    /// Reports `resource` moved to global storage, and gives it back.
+    fn trace_move_to(
+        &self,
+        ap: &AccessPath,
+        struct_ty: &FatStructType,
+        resource: Struct,
+    ) -> VMResult<Struct> {
+        if self.tracer.is_none() {
+            return Ok(resource);
+        }
+        let value = Value::struct_(resource);
+        self.trace_global_access(GlobalAccessKind::MoveTo, ap, struct_ty, &value);
+        value.value_as::<Struct>()
+    }
+
     //
     // Debugging and logging helpers.
     //
@@ -675,6 +810,10 @@ impl Frame {
         loop {
             for instruction in &code[self.pc as usize..] {
                 trace!(self.function.pretty_string(), self.pc, instruction);
+                if interpreter.tracer.is_some() {
+                    interpreter.trace_gas(cost_strategy);
+                    interpreter.trace_instruction(self, self.pc, instruction);
+                }
                 self.pc += 1;
 
                 match instruction {
@@ -14,10 +14,12 @@ extern crate mirai_annotations;
 
 pub mod data_cache;
 mod data_operations;
+pub mod gas_profiler;
 mod interpreter;
 mod loader;
 pub mod move_vm;
 mod native_functions;
 mod runtime;
+pub mod tracer;
 #[macro_use]
 mod tracing;
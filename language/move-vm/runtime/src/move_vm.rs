@@ -1,7 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::runtime::VMRuntime;
+use crate::{runtime::VMRuntime, tracer::VMTracer};
 use bytecode_verifier::VerifiedModule;
 use move_core_types::{
     account_address::AccountAddress,
@@ -9,6 +9,7 @@ use move_core_types::{
     language_storage::{ModuleId, TypeTag},
 };
 use move_vm_types::{data_store::DataStore, gas_schedule::CostStrategy, values::Value};
+use std::sync::Arc;
 use vm::errors::VMResult;
 
 pub struct MoveVM {
@@ -18,7 +19,14 @@ pub struct MoveVM {
 impl MoveVM {
     pub fn new() -> Self {
         Self {
-            runtime: VMRuntime::new(),
+            runtime: VMRuntime::new(None),
+        }
+    }
+
+    /// A VM reporting the execution of the functions it runs to `tracer`.
+    pub fn new_with_tracer(tracer: Arc<dyn VMTracer>) -> Self {
+        Self {
+            runtime: VMRuntime::new(Some(tracer)),
         }
     }
 
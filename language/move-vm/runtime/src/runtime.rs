@@ -1,7 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{interpreter::Interpreter, loader::Loader};
+use crate::{interpreter::Interpreter, loader::Loader, tracer::VMTracer};
 use bytecode_verifier::VerifiedModule;
 use libra_logger::prelude::*;
 use libra_types::vm_error::{StatusCode, VMStatus};
@@ -11,6 +11,7 @@ use move_core_types::{
     language_storage::{ModuleId, TypeTag},
 };
 use move_vm_types::{data_store::DataStore, gas_schedule::CostStrategy, values::Value};
+use std::sync::Arc;
 use vm::{
     access::ModuleAccess,
     errors::{verification_error, vm_error, Location, VMResult},
@@ -21,12 +22,14 @@ use vm::{
 /// An instantiation of the MoveVM.
 pub(crate) struct VMRuntime {
     loader: Loader,
+    tracer: Option<Arc<dyn VMTracer>>,
 }
 
 impl VMRuntime {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(tracer: Option<Arc<dyn VMTracer>>) -> Self {
         VMRuntime {
             loader: Loader::new(),
+            tracer,
         }
     }
 
@@ -109,6 +112,7 @@ impl VMRuntime {
             data_store,
             cost_strategy,
             &self.loader,
+            self.tracer.clone(),
         )
     }
 
@@ -143,6 +147,7 @@ impl VMRuntime {
             data_store,
             cost_strategy,
             &self.loader,
+            self.tracer.clone(),
         )
     }
 
@@ -0,0 +1,212 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracing hooks of the Move VM.
+//!
+//! A `MoveVM` created with a `VMTracer` reports the execution of every function it runs as a
+//! sequence of `TraceEvent`s. Unlike the `MOVE_VM_TRACE` file trace, which only exists in debug
+//! builds, the hooks are available in all builds: they cost a branch per instruction when no
+//! tracer is set.
+
+use crate::loader::Function;
+use libra_types::{account_address::AccountAddress, vm_error::VMStatus};
+use move_core_types::language_storage::ModuleId;
+use move_vm_types::{
+    loaded_data::{runtime_types::Type, types::FatStructType},
+    values::{Locals, Value},
+};
+use std::{
+    fmt,
+    io::Write,
+    sync::{Arc, Mutex},
+};
+use vm::file_format::Bytecode;
+
+/// A function of a trace event
+#[derive(Clone, Copy, Debug)]
+pub struct TracedFunction<'a> {
+    /// The module of the function, or `None` for the main function of a script
+    pub module: Option<&'a ModuleId>,
+    pub name: &'a str,
+}
+
+/// How a global resource is accessed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GlobalAccessKind {
+    BorrowGlobal,
+    Exists,
+    MoveFrom,
+    MoveTo,
+}
+
+/// An event of the execution of the VM, whose data is only borrowed for the time of the event.
+pub enum TraceEvent<'a> {
+    /// A function is called, natives included, with its type and value arguments
+    Call {
+        function: TracedFunction<'a>,
+        ty_args: &'a [Type],
+        args: &'a [Value],
+    },
+    /// A function returns its return values
+    Return {
+        function: TracedFunction<'a>,
+        return_values: &'a [Value],
+    },
+    /// An instruction is about to be executed, with the operand stack and the locals of its frame
+    Instruction {
+        function: TracedFunction<'a>,
+        pc: u16,
+        instruction: &'a Bytecode,
+        stack: &'a [Value],
+        locals: &'a Locals,
+    },
+    /// Gas was charged since the previous event. It is charged by the instruction of the last
+    /// `Instruction` event, or by the native function being called.
+    GasCharged { amount: u64, gas_left: u64 },
+    /// A global resource is read or written. Resources mutated through references are only
+    /// written when the transaction's effects are computed, so they are seen as borrows.
+    GlobalAccess {
+        kind: GlobalAccessKind,
+        address: AccountAddress,
+        struct_type: &'a FatStructType,
+        /// The reference, resource or flag pushed by the instruction, or the resource moved to
+        /// global storage
+        value: &'a Value,
+    },
+    /// The execution fails. Its frames are discarded without `Return` events.
+    Error { error: &'a VMStatus },
+}
+
+/// A sink of trace events. Sinks are shared by the threads running the VM.
+pub trait VMTracer: Send + Sync {
+    fn trace(&self, event: &TraceEvent);
+}
+
+/// A tracer writing one line per event to `W`
+pub struct TraceWriter<W: Write + Send> {
+    writer: Mutex<W>,
+}
+
+impl<'a> TracedFunction<'a> {
+    pub(crate) fn new(function: &'a Function) -> Self {
+        Self {
+            module: function.module_id(),
+            name: function.name(),
+        }
+    }
+}
+
+impl<'a> fmt::Display for TracedFunction<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.module {
+            Some(module) => write!(
+                f,
+                "0x{}::{}::{}",
+                module.address(),
+                module.name(),
+                self.name
+            ),
+            None => write!(f, "Script::{}", self.name),
+        }
+    }
+}
+
+impl GlobalAccessKind {
+    /// Whether the access changes global storage
+    pub fn is_write(self) -> bool {
+        match self {
+            GlobalAccessKind::BorrowGlobal | GlobalAccessKind::Exists => false,
+            GlobalAccessKind::MoveFrom | GlobalAccessKind::MoveTo => true,
+        }
+    }
+}
+
+fn display_values(values: &[Value]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl<W: Write + Send> TraceWriter<W> {
+    pub fn new(writer: W) -> Arc<Self> {
+        Arc::new(Self {
+            writer: Mutex::new(writer),
+        })
+    }
+
+    fn write_event(writer: &mut W, event: &TraceEvent) -> std::io::Result<()> {
+        match event {
+            TraceEvent::Call {
+                function,
+                ty_args,
+                args,
+            } => {
+                if ty_args.is_empty() {
+                    writeln!(writer, "call {}({})", function, display_values(args))
+                } else {
+                    writeln!(
+                        writer,
+                        "call {}<{:?}>({})",
+                        function,
+                        ty_args,
+                        display_values(args)
+                    )
+                }
+            }
+            TraceEvent::Return {
+                function,
+                return_values,
+            } => writeln!(
+                writer,
+                "return {}({})",
+                function,
+                display_values(return_values)
+            ),
+            TraceEvent::Instruction {
+                function,
+                pc,
+                instruction,
+                stack,
+                ..
+            } => writeln!(
+                writer,
+                "instr {},{},{:?} [{}]",
+                function,
+                pc,
+                instruction,
+                display_values(stack)
+            ),
+            TraceEvent::GasCharged { amount, gas_left } => {
+                writeln!(writer, "gas {} (left {})", amount, gas_left)
+            }
+            TraceEvent::GlobalAccess {
+                kind,
+                address,
+                struct_type,
+                value,
+            } => {
+                let struct_tag = match struct_type.struct_tag() {
+                    Ok(struct_tag) => format!("{}", struct_tag),
+                    Err(_) => format!("{:?}", struct_type),
+                };
+                writeln!(
+                    writer,
+                    "global {:?} {} {} {}",
+                    kind, address, struct_tag, value
+                )
+            }
+            TraceEvent::Error { error } => writeln!(writer, "error {:?}", error.major_status),
+        }
+    }
+}
+
+impl<W: Write + Send> VMTracer for TraceWriter<W> {
+    fn trace(&self, event: &TraceEvent) {
+        // A tracer cannot fail the execution, so write errors are dropped
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = Self::write_event(&mut writer, event);
+        }
+    }
+}
@@ -913,6 +913,21 @@ impl Locals {
         }
     }
 
+    /// Displays each local in order, for tools inspecting an execution. Locals which are not
+    /// assigned or were moved are displayed as `Invalid`.
+    pub fn display_locals(&self) -> Vec<String> {
+        match &*self.0.borrow() {
+            Container::General(v) => v.iter().map(|val| val.to_string()).collect(),
+
+            Container::Resource(_)
+            | Container::U8(_)
+            | Container::U64(_)
+            | Container::U128(_)
+            | Container::Address(_)
+            | Container::Bool(_) => unreachable!(),
+        }
+    }
+
     fn swap_loc(&mut self, idx: usize, x: Value) -> VMResult<Value> {
         let mut r = self.0.borrow_mut();
         let v = match &mut *r {
@@ -22,6 +22,9 @@ module LibraBlock {
 
       // On-chain time during  he block at the given height
       time_microseconds: u64,
+
+      // The expected proposers of the rounds skipped before this block
+      failed_proposers: vector<address>,
     }
 
     // This can only be invoked by the Association address, and only a single time.
@@ -49,12 +52,13 @@ module LibraBlock {
         round: u64,
         timestamp: u64,
         previous_block_votes: vector<address>,
-        proposer: address
+        proposer: address,
+        failed_proposers: vector<address>
     ) acquires BlockMetadata {
         // Can only be invoked by LibraVM privilege.
         Transaction::assert(Signer::address_of(vm) == 0x0, 33);
 
-        process_block_prologue(vm,  round, timestamp, previous_block_votes, proposer);
+        process_block_prologue(vm,  round, timestamp, previous_block_votes, proposer, failed_proposers);
 
         // TODO(valerini): call regular reconfiguration here LibraSystem2::update_all_validator_info()
     }
@@ -65,7 +69,8 @@ module LibraBlock {
         round: u64,
         timestamp: u64,
         previous_block_votes: vector<address>,
-        proposer: address
+        proposer: address,
+        failed_proposers: vector<address>
     ) acquires BlockMetadata {
         let block_metadata_ref = borrow_global_mut<BlockMetadata>(0xA550C18);
 
@@ -80,6 +85,7 @@ module LibraBlock {
             proposer: proposer,
             previous_block_votes: previous_block_votes,
             time_microseconds: timestamp,
+            failed_proposers: failed_proposers,
           }
         );
     }
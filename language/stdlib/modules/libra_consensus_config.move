@@ -0,0 +1,90 @@
+address 0x0 {
+
+module LibraConsensusConfig {
+    use 0x0::LibraConfig;
+    use 0x0::Signer;
+    use 0x0::Transaction;
+    use 0x0::Vector;
+
+    // The struct to hold the consensus config of the validator set.
+    // * config: LCS encoded `OnChainConsensusConfig`, e.g. which proposer election scheme is used
+    //   and its parameters. It is only interpreted by consensus, this module just checks that it
+    //   is valid.
+    struct T {
+        config: vector<u8>,
+    }
+
+    // Publish the config under the config address. Invoked in the genesis transaction.
+    public fun initialize(config_account: &signer, config: vector<u8>) {
+        Transaction::assert(Signer::address_of(config_account) == LibraConfig::default_config_address(), 1);
+        assert_valid_config(&config);
+
+        LibraConfig::publish_new_config<Self::T>(
+            config_account,
+            T { config },
+        );
+    }
+
+    // Update the config and trigger a reconfiguration, so that all validators switch to it at
+    // the start of the next epoch.
+    public fun set(account: &signer, config: vector<u8>) {
+        assert_valid_config(&config);
+        LibraConfig::set<Self::T>(
+            account,
+            T { config }
+        );
+    }
+
+    // Abort unless the config decodes into an `OnChainConsensusConfig::V1` that consensus can run
+    // with, mirroring `OnChainConsensusConfig::validate`. A config that consensus can't use would
+    // halt the network at the next epoch.
+    // Aborts with 2 if the config is malformed and with 3 if its parameters are out of range.
+    fun assert_valid_config(config: &vector<u8>) {
+        let len = Vector::length(config);
+        Transaction::assert(len >= 2, 2);
+        // Only the V1 layout is known
+        Transaction::assert(*Vector::borrow(config, 0) == 0, 2);
+        let election_type = *Vector::borrow(config, 1);
+        if (election_type == 0 || election_type == 1) {
+            // FixedProposer and RotatingProposer: the u32 number of contiguous rounds
+            Transaction::assert(len == 6, 2);
+            Transaction::assert(read_u64(config, 2, 4) > 0, 3);
+        } else if (election_type == 2) {
+            // LeaderReputation: the u64 active weights, inactive weights and window multiplier
+            Transaction::assert(len == 26, 2);
+            assert_valid_leader_reputation(config, 2);
+        } else if (election_type == 3) {
+            // FailureAwareLeaderReputation: a LeaderReputation config and the u64 failed weights
+            Transaction::assert(len == 34, 2);
+            assert_valid_leader_reputation(config, 2);
+            Transaction::assert(read_u64(config, 26, 8) <= max_proposer_weight(), 3);
+        } else {
+            abort 2
+        };
+    }
+
+    fun assert_valid_leader_reputation(config: &vector<u8>, offset: u64) {
+        let active_weights = read_u64(config, offset, 8);
+        let inactive_weights = read_u64(config, offset + 8, 8);
+        Transaction::assert(active_weights > 0 && active_weights <= max_proposer_weight(), 3);
+        Transaction::assert(inactive_weights > 0 && inactive_weights <= max_proposer_weight(), 3);
+    }
+
+    // The sum of the weights of all the candidates of an epoch must fit in a u64
+    fun max_proposer_weight(): u64 {
+        4294967295
+    }
+
+    // Read a little endian unsigned integer of `size` bytes starting at `offset`
+    fun read_u64(bytes: &vector<u8>, offset: u64, size: u64): u64 {
+        let value = 0;
+        let i = size;
+        while (i > 0) {
+            i = i - 1;
+            value = value * 256 + (*Vector::borrow(bytes, offset + i) as u64);
+        };
+        value
+    }
+}
+
+}
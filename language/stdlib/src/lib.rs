@@ -7,7 +7,8 @@ pub mod transaction_scripts;
 
 use bytecode_verifier::{batch_verify_modules, VerifiedModule};
 use log::LevelFilter;
-use move_lang::{compiled_unit::CompiledUnit, move_compile, shared::Address};
+use move_lang::{compiled_unit::CompiledUnit, move_compile};
+use move_package::Package;
 use once_cell::sync::Lazy;
 use std::path::PathBuf;
 use vm::file_format::CompiledModule;
@@ -109,18 +110,19 @@ pub fn filter_move_files(dir_iter: impl Iterator<Item = PathBuf>) -> impl Iterat
     })
 }
 
+/// The stdlib as described by its `Move.toml` manifest.
+pub fn stdlib_package() -> Package {
+    Package::load(env!("CARGO_MANIFEST_DIR")).unwrap()
+}
+
 pub fn stdlib_files() -> Vec<String> {
-    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    path.push(STD_LIB_DIR);
-    let dirfiles = datatest_stable::utils::iterate_directory(&path);
-    filter_move_files(dirfiles)
-        .flat_map(|path| path.into_os_string().into_string())
-        .collect()
+    stdlib_package().source_files().unwrap()
 }
 
 pub fn build_stdlib() -> Vec<VerifiedModule> {
+    let package = stdlib_package();
     let (_, compiled_units) =
-        move_compile(&stdlib_files(), &[], Some(Address::LIBRA_CORE)).unwrap();
+        move_compile(&package.source_files().unwrap(), &[], package.address()).unwrap();
     batch_verify_modules(
         compiled_units
             .into_iter()
@@ -133,10 +135,11 @@ pub fn build_stdlib() -> Vec<VerifiedModule> {
 }
 
 pub fn compile_script(source_file_str: String) -> Vec<u8> {
+    let package = stdlib_package();
     let (_, mut compiled_program) = move_compile(
         &[source_file_str],
-        &stdlib_files(),
-        Some(Address::LIBRA_CORE),
+        &package.source_files().unwrap(),
+        package.address(),
     )
     .unwrap();
     let mut script_bytes = vec![];
@@ -54,6 +54,7 @@ pub enum StdlibScript {
     TieredMint,
     UnfreezeAccount,
     UnmintLbr,
+    UpdateConsensusConfig,
     UpdateLibraVersion,
     UpdateExchangeRate,
     UpdateMintingAbility,
@@ -98,6 +99,7 @@ impl StdlibScript {
             TieredMint,
             UnfreezeAccount,
             UnmintLbr,
+            UpdateConsensusConfig,
             UpdateLibraVersion,
             UpdateExchangeRate,
             UpdateMintingAbility,
@@ -220,6 +222,7 @@ impl fmt::Display for StdlibScript {
                 TieredMint => "tiered_mint",
                 UnfreezeAccount => "unfreeze_account",
                 UnmintLbr => "unmint_lbr",
+                UpdateConsensusConfig => "update_consensus_config",
                 UpdateLibraVersion => "update_libra_version",
                 UpdateExchangeRate => "update_exchange_rate",
                 UpdateMintingAbility => "update_minting_ability",
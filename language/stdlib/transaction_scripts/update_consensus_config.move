@@ -0,0 +1,7 @@
+script {
+use 0x0::LibraConsensusConfig;
+
+fun main(account: &signer, config: vector<u8>) {
+    LibraConsensusConfig::set(account, config)
+}
+}
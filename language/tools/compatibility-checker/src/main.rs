@@ -0,0 +1,82 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::{bail, format_err, Result};
+use libra_types::{access_path::AccessPath, account_state::AccountState};
+use libradb::LibraDB;
+use move_core_types::language_storage::ModuleId;
+use std::{convert::TryFrom, fs, path::PathBuf};
+use storage_interface::DbReader;
+use structopt::StructOpt;
+use vm::{access::ModuleAccess, compatibility::check_compatibility, CompiledModule};
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "compatibility-checker",
+    about = "Check that compiled modules can replace the versions published on chain"
+)]
+struct Args {
+    /// The directory of the LibraDB holding the published modules
+    #[structopt(long = "db", parse(from_os_str))]
+    pub db: PathBuf,
+    /// The compiled modules to check
+    #[structopt(name = "PATH_TO_COMPILED_MODULE", parse(from_os_str))]
+    pub modules: Vec<PathBuf>,
+}
+
+fn display(id: &ModuleId) -> String {
+    format!("{}::{}", id.address(), id.name())
+}
+
+/// The latest version of the module with the id of `module` published in `db`, if any
+fn published_module(db: &LibraDB, module: &CompiledModule) -> Result<Option<CompiledModule>> {
+    let id = module.self_id();
+    let blob = match db.get_latest_account_state(*id.address())? {
+        Some(blob) => blob,
+        None => return Ok(None),
+    };
+    let account_state = AccountState::try_from(&blob)?;
+    match account_state.get(&AccessPath::code_access_path(&id).path) {
+        Some(bytes) => Ok(Some(CompiledModule::deserialize(bytes).map_err(|e| {
+            format_err!(
+                "Unable to deserialize the published module {}: {:?}",
+                display(&id),
+                e
+            )
+        })?)),
+        None => Ok(None),
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Args::from_args();
+    let db = LibraDB::open(&args.db, true /* readonly */, None /* pruner */)?;
+
+    let mut incompatible = 0;
+    for path in &args.modules {
+        let module = CompiledModule::deserialize(&fs::read(path)?)
+            .map_err(|e| format_err!("Unable to deserialize '{}': {:?}", path.display(), e))?;
+        let id = display(&module.self_id());
+        match published_module(&db, &module)? {
+            None => println!("{}: not published", id),
+            Some(published) => {
+                let incompatibilities = check_compatibility(&published, &module);
+                if incompatibilities.is_empty() {
+                    println!("{}: compatible", id);
+                } else {
+                    incompatible += 1;
+                    println!("{}: incompatible", id);
+                    for incompatibility in incompatibilities {
+                        println!("    {}", incompatibility);
+                    }
+                }
+            }
+        }
+    }
+    if incompatible > 0 {
+        bail!("{} incompatible module(s)", incompatible)
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::{bail, format_err, Result};
+use libra_types::transaction::{SignedTransaction, Transaction, TransactionOutput, Version};
+use libradb::LibraDB;
+use move_vm_runtime::gas_profiler::GasProfiler;
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use storage_interface::DbReader;
+use structopt::StructOpt;
+use transaction_replay::{
+    execute_transaction, replay_transaction, ChangeSetStateView, DbStateView,
+};
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "gas-profiler",
+    about = "Profile the gas of a transaction by Move function and instruction type"
+)]
+struct Args {
+    /// The directory of a LibraDB. Transactions from files run on the testing genesis without it.
+    #[structopt(long = "db", parse(from_os_str))]
+    pub db: Option<PathBuf>,
+    /// The version of a committed transaction to profile, replayed on the state it was
+    /// committed on
+    #[structopt(long = "version", short = "v")]
+    pub version: Option<Version>,
+    /// The path to an LCS-serialized signed transaction to profile
+    #[structopt(long = "transaction-path", short = "t", parse(from_os_str))]
+    pub transaction_path: Option<PathBuf>,
+    /// The version of the state of the LibraDB the transaction file runs on, the latest if not
+    /// present
+    #[structopt(long = "state-version", short = "s")]
+    pub state_version: Option<Version>,
+    /// Optional path for the folded stacks of the profile, the input of flame graph tools
+    #[structopt(long = "folded-path", short = "f", parse(from_os_str))]
+    pub folded_path: Option<PathBuf>,
+    /// Optional path for the table of the profile. Printed to stdout if not present.
+    #[structopt(long = "table-path", short = "o", parse(from_os_str))]
+    pub table_path: Option<PathBuf>,
+}
+
+fn read_transaction(path: &Path) -> Result<Transaction> {
+    let bytes = fs::read(path)
+        .map_err(|err| format_err!("Unable to read '{}': {}", path.display(), err))?;
+    let transaction: SignedTransaction = lcs::from_bytes(&bytes)
+        .map_err(|err| format_err!("Unable to deserialize '{}': {}", path.display(), err))?;
+    Ok(Transaction::UserTransaction(transaction))
+}
+
+/// Runs the transaction of `args`, returning its output
+fn run(args: &Args, profiler: &Arc<GasProfiler>) -> Result<TransactionOutput> {
+    let db = match &args.db {
+        Some(path) => {
+            let db = LibraDB::open(path, true /* readonly */, None /* pruner */)?;
+            Some(db)
+        }
+        None => None,
+    };
+    match (&args.version, &args.transaction_path) {
+        (Some(version), None) => {
+            let db = match &db {
+                Some(db) => db,
+                None => bail!("Replaying a committed transaction needs a LibraDB"),
+            };
+            let (_transaction, output, _info) = replay_transaction(db, *version, profiler.clone())?;
+            Ok(output)
+        }
+        (None, Some(path)) => {
+            let transaction = read_transaction(path)?;
+            match &db {
+                Some(db) => {
+                    let state_version = match args.state_version {
+                        Some(state_version) => state_version,
+                        None => db.get_latest_version()?,
+                    };
+                    let state_view = DbStateView::new(db, Some(state_version));
+                    execute_transaction(&state_view, transaction, profiler.clone())
+                }
+                None => execute_transaction(
+                    &ChangeSetStateView::genesis(),
+                    transaction,
+                    profiler.clone(),
+                ),
+            }
+        }
+        _ => bail!("Exactly one of --version and --transaction-path must be passed"),
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Args::from_args();
+    let profiler = GasProfiler::new();
+    let output = run(&args, &profiler)?;
+    let profile = profiler.profile();
+
+    if let Some(path) = &args.folded_path {
+        profile.output_folded(&mut File::create(path)?)?;
+    }
+    let mut table_writer: Box<dyn Write> = match &args.table_path {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    writeln!(
+        table_writer,
+        "Status: {:?}\nGas used: {} ({} outside of the interpreter, e.g. intrinsic gas)",
+        output.status(),
+        output.gas_used(),
+        output.gas_used().saturating_sub(profile.total_gas)
+    )?;
+    profile.output_table(&mut table_writer)?;
+    Ok(())
+}
@@ -39,6 +39,10 @@ echo "> cargo run --bin source-coverage -- -t trace.mvcov -b ../../move-lang/mov
 echo "---------------------------------------------------------------------------"
 echo "You can can also getter a finer-grained coverage summary for each function by running:"
 echo "> cargo run --bin coverage-summaries -- -t trace.mvcov -s ../../stdlib/staged/stdlib.mv"
+echo "---------------------------------------------------------------------------"
+echo "You can get the line and branch coverage of each source file, or an LCOV tracefile with --lcov, by running:"
+echo "> cargo run --bin file-coverage -- -t trace.mvcov -d ../../move-lang/move_build_output/modules -s ../../move-lang"
+echo "Coverage maps and traces of several runs can be merged by passing -t more than once."
 echo "==========================================================================="
 
 unset MOVE_VM_TRACE
@@ -21,10 +21,10 @@ use vm::file_format::CompiledModule;
     about = "Creates a coverage summary from the trace data collected from the Move VM"
 )]
 struct Args {
-    /// The path to the coverage map or trace file
+    /// The paths to the coverage maps or trace files, whose coverage is merged
     #[structopt(long = "input-trace-path", short = "t")]
-    pub input_trace_path: String,
-    /// Whether the passed-in file is a raw trace file or a serialized coverage map
+    pub input_trace_path: Vec<String>,
+    /// Whether the passed-in files are raw trace files or serialized coverage maps
     #[structopt(long = "is-raw-trace", short = "r")]
     pub is_raw_trace_file: bool,
     /// The path to the module binary
@@ -100,7 +100,11 @@ fn format_human_summary<W: Write>(args: &Args, coverage_map: &CoverageMap, summa
 }
 
 fn format_csv_summary<W: Write>(args: &Args, coverage_map: &CoverageMap, summary_writer: &mut W) {
-    writeln!(summary_writer, "ModuleName,FunctionName,Covered,Uncovered").unwrap();
+    writeln!(
+        summary_writer,
+        "ModuleName,FunctionName,Covered,Uncovered,CoveredBranches,TotalBranches"
+    )
+    .unwrap();
 
     for module in get_modules(&args).iter() {
         let mut summary_options = ModuleSummaryOptions::default();
@@ -113,13 +117,7 @@ fn format_csv_summary<W: Write>(args: &Args, coverage_map: &CoverageMap, summary
 
 fn main() {
     let args = Args::from_args();
-    let input_trace_path = Path::new(&args.input_trace_path);
-
-    let coverage_map = if args.is_raw_trace_file {
-        CoverageMap::from_trace_file(&input_trace_path)
-    } else {
-        CoverageMap::from_binary_file(&input_trace_path)
-    };
+    let coverage_map = CoverageMap::from_files(&args.input_trace_path, args.is_raw_trace_file);
 
     let mut summary_writer: Box<dyn Write> = match &args.summary_path {
         Some(x) => {
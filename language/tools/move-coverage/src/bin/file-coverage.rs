@@ -0,0 +1,103 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use bytecode_source_map::utils::{remap_owned_loc_to_loc, source_map_from_file, OwnedLoc};
+use move_coverage::{coverage_map::CoverageMap, file_coverage::FilesCoverage};
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+use vm::file_format::CompiledModule;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "Move File Coverage",
+    about = "Computes the line and branch coverage of Move source files, optionally as LCOV"
+)]
+struct Args {
+    /// The paths to the coverage maps or trace files, whose coverage is merged
+    #[structopt(long = "input-trace-path", short = "t")]
+    pub input_trace_path: Vec<String>,
+    /// Whether the passed-in files are raw trace files or serialized coverage maps
+    #[structopt(long = "is-raw-trace", short = "r")]
+    pub is_raw_trace_file: bool,
+    /// The paths to module binaries, each with its source map next to it
+    #[structopt(long = "module-path", short = "b")]
+    pub module_binary_paths: Vec<String>,
+    /// A directory of module binaries, e.g. the output of `move-build -m`
+    #[structopt(long = "modules-dir", short = "d")]
+    pub modules_dir: Option<String>,
+    /// The directory relative source paths of the source maps are resolved against
+    #[structopt(long = "source-root", short = "s", default_value = ".")]
+    pub source_root: String,
+    /// Optional path for the coverage. Printed to stdout if not present.
+    #[structopt(long = "coverage-path", short = "o")]
+    pub coverage_path: Option<String>,
+    /// Output the coverage as an LCOV tracefile
+    #[structopt(long = "lcov", short = "l")]
+    pub lcov_output: bool,
+}
+
+fn get_module_paths(args: &Args) -> Vec<PathBuf> {
+    let mut module_paths: Vec<_> = args.module_binary_paths.iter().map(PathBuf::from).collect();
+    if let Some(modules_dir) = &args.modules_dir {
+        let mut dir_paths: Vec<_> = fs::read_dir(modules_dir)
+            .expect("Unable to read modules directory")
+            .map(|entry| entry.expect("Unable to read modules directory").path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "mv"))
+            .collect();
+        dir_paths.sort();
+        module_paths.extend(dir_paths);
+    }
+
+    if module_paths.is_empty() {
+        panic!("No modules provided for coverage checking")
+    }
+
+    module_paths
+}
+
+fn main() {
+    let args = Args::from_args();
+    let source_map_extension = "mvsm";
+    let coverage_map = CoverageMap::from_files(&args.input_trace_path, args.is_raw_trace_file);
+
+    let mut files_coverage = FilesCoverage::default();
+    for module_path in get_module_paths(&args) {
+        let bytecode_bytes = fs::read(&module_path).expect("Unable to read bytecode file");
+        let compiled_module = CompiledModule::deserialize(&bytecode_bytes)
+            .expect("Module blob can't be deserialized");
+        let source_map =
+            source_map_from_file::<OwnedLoc>(&module_path.with_extension(source_map_extension))
+                .map(remap_owned_loc_to_loc)
+                .unwrap();
+        files_coverage
+            .add_module(
+                &compiled_module,
+                &source_map,
+                &coverage_map,
+                Path::new(&args.source_root),
+            )
+            .unwrap();
+    }
+
+    let mut coverage_writer: Box<dyn Write> = match &args.coverage_path {
+        Some(x) => {
+            let path = Path::new(x);
+            Box::new(File::create(&path).unwrap())
+        }
+        None => Box::new(io::stdout()),
+    };
+
+    if args.lcov_output {
+        files_coverage.output_lcov(&mut coverage_writer).unwrap()
+    } else {
+        files_coverage
+            .summarize_human(&mut coverage_writer)
+            .unwrap()
+    }
+}
@@ -3,7 +3,7 @@
 
 #![forbid(unsafe_code)]
 
-use anyhow::{format_err, Result};
+use anyhow::{bail, format_err, Result};
 use libra_types::account_address::AccountAddress;
 use move_core_types::identifier::{IdentStr, Identifier};
 use serde::{Deserialize, Serialize};
@@ -14,8 +14,24 @@ use std::{
     path::Path,
 };
 
+/// The start of serialized coverage maps, followed by the version of their format. Coverage maps
+/// serialized before branch coverage have no header, and are read without branch coverage.
+const COVERAGE_MAP_MAGIC: &[u8] = b"MVCOV";
+/// Version 1 adds `ModuleCoverageMap::branch_maps`
+const COVERAGE_MAP_VERSION: u8 = 1;
+
 pub type FunctionCoverage = BTreeMap<u64, u64>;
 
+/// The branch coverage of a function, by the code offset of its conditional branches
+pub type FunctionBranchCoverage = BTreeMap<u64, BranchCoverage>;
+
+/// How many times a conditional branch jumped to its target, and how many times it fell through
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct BranchCoverage {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CoverageMap {
     pub module_maps: BTreeMap<(AccountAddress, Identifier), ModuleCoverageMap>,
@@ -26,17 +42,44 @@ pub struct ModuleCoverageMap {
     pub module_addr: AccountAddress,
     pub module_name: Identifier,
     pub function_maps: BTreeMap<Identifier, FunctionCoverage>,
+    pub branch_maps: BTreeMap<Identifier, FunctionBranchCoverage>,
+}
+
+/// The layout of coverage maps serialized without a header
+#[derive(Deserialize)]
+struct LegacyCoverageMap {
+    module_maps: BTreeMap<(AccountAddress, Identifier), LegacyModuleCoverageMap>,
+}
+
+#[derive(Deserialize)]
+struct LegacyModuleCoverageMap {
+    module_addr: AccountAddress,
+    module_name: Identifier,
+    function_maps: BTreeMap<Identifier, FunctionCoverage>,
+}
+
+/// A conditional branch seen in a trace, whose outcome is given by the next instruction traced
+struct PendingBranch {
+    context: String,
+    pc: u64,
+    target: u64,
 }
 
 impl CoverageMap {
     /// Takes in a file containing a raw VM trace, and returns an updated coverage map.
+    ///
+    /// A conditional branch is attributed to the instruction traced right after it in the same
+    /// function, which is either its target or the next instruction. Scripts are not counted, so
+    /// neither are their branches.
     pub fn update_coverage_from_trace_file<P: AsRef<Path>>(mut self, filename: P) -> Self {
         let file = File::open(filename).unwrap();
+        let mut pending_branch: Option<PendingBranch> = None;
         for line in BufReader::new(file).lines() {
             let line = line.unwrap();
-            let mut splits = line.split(',');
+            let mut splits = line.splitn(3, ',');
             let context = splits.next().unwrap();
             let pc = splits.next().unwrap().parse::<u64>().unwrap();
+            let instr = splits.next().unwrap_or("");
 
             let mut context_segs: Vec<_> = context.split("::").collect();
             let is_script = context_segs.len() == 2;
@@ -49,12 +92,39 @@ impl CoverageMap {
                     .module_maps
                     .entry((addr, module_name.clone()))
                     .or_insert_with(|| ModuleCoverageMap::new(addr, module_name));
+                if let Some(branch) = pending_branch.take() {
+                    if branch.context == context {
+                        if pc == branch.target {
+                            entry.insert_branch(func_name.clone(), branch.pc, true);
+                        } else if pc == branch.pc + 1 {
+                            entry.insert_branch(func_name.clone(), branch.pc, false);
+                        }
+                    }
+                }
                 entry.insert(func_name, pc);
+                pending_branch = branch_target(instr).map(|target| PendingBranch {
+                    context: context.to_string(),
+                    pc,
+                    target,
+                });
+            } else {
+                pending_branch = None;
             }
         }
         self
     }
 
+    /// Takes in files containing raw VM traces, e.g. of the different test suites, and returns
+    /// their merged coverage map.
+    pub fn from_trace_files<P: AsRef<Path>>(filenames: &[P]) -> Self {
+        filenames.iter().fold(
+            CoverageMap {
+                module_maps: BTreeMap::new(),
+            },
+            |coverage_map, filename| coverage_map.update_coverage_from_trace_file(filename),
+        )
+    }
+
     /// Takes in a file containing a raw VM trace, and returns a coverage map.
     pub fn from_trace_file<P: AsRef<Path>>(filename: P) -> Self {
         let empty_module_map = CoverageMap {
@@ -71,10 +141,79 @@ impl CoverageMap {
             .and_then(|mut file| file.read_to_end(&mut bytes).ok())
             .ok_or_else(|| format_err!("Error while reading in coverage map binary"))
             .unwrap();
-        lcs::from_bytes(&bytes)
-            .map_err(|_| format_err!("Error deserializing into coverage map"))
+        Self::from_bytes(&bytes)
+            .map_err(|err| format_err!("Error deserializing into coverage map: {}", err))
             .unwrap()
     }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if !bytes.starts_with(COVERAGE_MAP_MAGIC) {
+            let legacy_map: LegacyCoverageMap = lcs::from_bytes(bytes)?;
+            return Ok(legacy_map.into());
+        }
+        match bytes.get(COVERAGE_MAP_MAGIC.len()) {
+            Some(&COVERAGE_MAP_VERSION) => {
+                Ok(lcs::from_bytes(&bytes[COVERAGE_MAP_MAGIC.len() + 1..])?)
+            }
+            Some(version) => bail!("Unsupported coverage map version {}", version),
+            None => bail!("Missing coverage map version"),
+        }
+    }
+
+    /// Serializes the coverage map to `file_name`, with the header of the current format.
+    pub fn output_to_file<P: AsRef<Path>>(&self, file_name: P) -> Result<()> {
+        let mut file = File::create(file_name)?;
+        file.write_all(COVERAGE_MAP_MAGIC)?;
+        file.write_all(&[COVERAGE_MAP_VERSION])?;
+        file.write_all(&lcs::to_bytes(self)?)?;
+        Ok(())
+    }
+
+    /// Takes in files containing either raw VM traces or serialized coverage maps, and returns
+    /// their merged coverage map.
+    pub fn from_files<P: AsRef<Path>>(filenames: &[P], is_raw_trace: bool) -> Self {
+        if is_raw_trace {
+            return Self::from_trace_files(filenames);
+        }
+        filenames.iter().fold(
+            CoverageMap {
+                module_maps: BTreeMap::new(),
+            },
+            |coverage_map, filename| coverage_map.merge(Self::from_binary_file(filename)),
+        )
+    }
+
+    /// Adds the counts of `other` to the counts of this coverage map.
+    pub fn merge(mut self, other: CoverageMap) -> Self {
+        for (key, other_module_map) in other.module_maps {
+            match self.module_maps.get_mut(&key) {
+                None => {
+                    self.module_maps.insert(key, other_module_map);
+                }
+                Some(module_map) => module_map.merge(other_module_map),
+            }
+        }
+        self
+    }
+}
+
+impl From<LegacyCoverageMap> for CoverageMap {
+    fn from(legacy_map: LegacyCoverageMap) -> Self {
+        let module_maps = legacy_map
+            .module_maps
+            .into_iter()
+            .map(|(key, module_map)| {
+                let module_map = ModuleCoverageMap {
+                    module_addr: module_map.module_addr,
+                    module_name: module_map.module_name,
+                    function_maps: module_map.function_maps,
+                    branch_maps: BTreeMap::new(),
+                };
+                (key, module_map)
+            })
+            .collect();
+        CoverageMap { module_maps }
+    }
 }
 
 impl ModuleCoverageMap {
@@ -83,6 +222,7 @@ impl ModuleCoverageMap {
             module_addr,
             module_name,
             function_maps: BTreeMap::new(),
+            branch_maps: BTreeMap::new(),
         }
     }
 
@@ -95,14 +235,225 @@ impl ModuleCoverageMap {
         *pc_entry += 1;
     }
 
+    pub fn insert_branch(&mut self, func_name: Identifier, pc: u64, taken: bool) {
+        let branch_entry = self
+            .branch_maps
+            .entry(func_name)
+            .or_insert_with(FunctionBranchCoverage::new)
+            .entry(pc)
+            .or_insert_with(BranchCoverage::default);
+        if taken {
+            branch_entry.taken += 1;
+        } else {
+            branch_entry.not_taken += 1;
+        }
+    }
+
+    pub fn merge(&mut self, other: ModuleCoverageMap) {
+        for (func_name, other_func_map) in other.function_maps {
+            let func_entry = self
+                .function_maps
+                .entry(func_name)
+                .or_insert_with(FunctionCoverage::new);
+            for (pc, count) in other_func_map {
+                *func_entry.entry(pc).or_insert(0) += count;
+            }
+        }
+        for (func_name, other_branch_map) in other.branch_maps {
+            let branch_entry = self
+                .branch_maps
+                .entry(func_name)
+                .or_insert_with(FunctionBranchCoverage::new);
+            for (pc, other_branch) in other_branch_map {
+                let branch = branch_entry
+                    .entry(pc)
+                    .or_insert_with(BranchCoverage::default);
+                branch.taken += other_branch.taken;
+                branch.not_taken += other_branch.not_taken;
+            }
+        }
+    }
+
     pub fn get_function_coverage(&self, func_name: &IdentStr) -> Option<&FunctionCoverage> {
         self.function_maps.get(func_name)
     }
+
+    pub fn get_function_branch_coverage(
+        &self,
+        func_name: &IdentStr,
+    ) -> Option<&FunctionBranchCoverage> {
+        self.branch_maps.get(func_name)
+    }
+}
+
+/// The target of a traced `BrTrue` or `BrFalse` instruction
+fn branch_target(instr: &str) -> Option<u64> {
+    let mut splits = instr.splitn(2, '(');
+    match (splits.next(), splits.next()) {
+        (Some("BrTrue"), Some(offset)) | (Some("BrFalse"), Some(offset)) => {
+            offset.trim_end_matches(')').parse().ok()
+        }
+        _ => None,
+    }
 }
 
-pub fn output_map_to_file<M: Serialize, P: AsRef<Path>>(file_name: P, data: &M) -> Result<()> {
-    let bytes = lcs::to_bytes(data)?;
-    let mut file = File::create(file_name)?;
-    file.write_all(&bytes)?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libra_temppath::TempPath;
+    use std::fs;
+
+    fn ident(name: &str) -> Identifier {
+        Identifier::new(name).unwrap()
+    }
+
+    fn get_module_map<'a>(coverage_map: &'a CoverageMap, name: &str) -> &'a ModuleCoverageMap {
+        &coverage_map.module_maps[&(
+            AccountAddress::from_hex_literal("0x1").unwrap(),
+            ident(name),
+        )]
+    }
+
+    fn branch(module_map: &ModuleCoverageMap, func_name: &str, pc: u64) -> (u64, u64) {
+        let branch = module_map.branch_maps[&ident(func_name)][&pc];
+        (branch.taken, branch.not_taken)
+    }
+
+    #[test]
+    fn test_branch_target() {
+        assert_eq!(branch_target("BrTrue(12)"), Some(12));
+        assert_eq!(branch_target("BrFalse(3)"), Some(3));
+        assert_eq!(branch_target("Branch(3)"), None);
+        assert_eq!(branch_target("Ret"), None);
+        assert_eq!(branch_target(""), None);
+    }
+
+    #[test]
+    fn test_branch_coverage_from_trace() {
+        let trace = TempPath::new();
+        fs::write(
+            trace.path(),
+            "0x1::M::f,0,CopyLoc(0)\n\
+             0x1::M::f,1,BrFalse(4)\n\
+             0x1::M::f,2,LdU64(1)\n\
+             0x1::M::f,3,Ret\n\
+             0x1::M::f,0,CopyLoc(0)\n\
+             0x1::M::f,1,BrFalse(4)\n\
+             0x1::M::f,4,LdU64(0)\n\
+             0x1::M::f,5,Ret\n\
+             0x1::M::f,0,CopyLoc(0)\n\
+             0x1::M::f,1,BrFalse(4)\n\
+             0x1::M::f,4,LdU64(0)\n\
+             0x1::M::f,5,Ret\n\
+             0x1::M::g,0,BrTrue(2)\n\
+             0x1::M::g,2,Ret\n\
+             0x1::M::h,0,BrTrue(3)\n\
+             0x1::M::f,0,CopyLoc(0)\n\
+             0x1::M::g,0,BrTrue(2)\n\
+             0x0::main,0,Ret\n",
+        )
+        .unwrap();
+        let coverage_map = CoverageMap::from_trace_file(trace.path());
+        let module_map = get_module_map(&coverage_map, "M");
+
+        let f = module_map.get_function_coverage(&ident("f")).unwrap();
+        assert_eq!(f[&0], 4);
+        assert_eq!(f[&1], 3);
+        assert_eq!(f[&2], 1);
+        assert_eq!(f[&4], 2);
+        // BrFalse jumps to its target when the condition is false
+        assert_eq!(branch(module_map, "f", 1), (2, 1));
+        assert_eq!(branch(module_map, "g", 0), (1, 0));
+        // Branches followed by another function or a script have no known outcome
+        assert!(module_map
+            .get_function_branch_coverage(&ident("h"))
+            .is_none());
+        assert_eq!(
+            module_map.get_function_coverage(&ident("g")).unwrap()[&0],
+            2
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let addr = AccountAddress::from_hex_literal("0x1").unwrap();
+        let mut m1 = ModuleCoverageMap::new(addr, ident("M"));
+        m1.insert(ident("f"), 0);
+        m1.insert(ident("f"), 1);
+        m1.insert_branch(ident("f"), 1, true);
+        let mut m2 = ModuleCoverageMap::new(addr, ident("M"));
+        m2.insert(ident("f"), 1);
+        m2.insert(ident("g"), 0);
+        m2.insert_branch(ident("f"), 1, false);
+        m2.insert_branch(ident("f"), 1, true);
+        let n = ModuleCoverageMap::new(addr, ident("N"));
+
+        let mut module_maps = BTreeMap::new();
+        module_maps.insert((addr, ident("M")), m1);
+        let first = CoverageMap { module_maps };
+        let mut module_maps = BTreeMap::new();
+        module_maps.insert((addr, ident("M")), m2);
+        module_maps.insert((addr, ident("N")), n);
+        let second = CoverageMap { module_maps };
+
+        let merged = first.merge(second);
+        assert_eq!(merged.module_maps.len(), 2);
+        let module_map = get_module_map(&merged, "M");
+        let f = module_map.get_function_coverage(&ident("f")).unwrap();
+        assert_eq!((f[&0], f[&1]), (1, 2));
+        assert_eq!(
+            module_map.get_function_coverage(&ident("g")).unwrap()[&0],
+            1
+        );
+        assert_eq!(branch(module_map, "f", 1), (2, 1));
+    }
+
+    /// The layout of coverage maps serialized before branch coverage
+    #[derive(Serialize)]
+    struct LegacyOutput {
+        module_maps: BTreeMap<(AccountAddress, Identifier), LegacyModuleOutput>,
+    }
+
+    #[derive(Serialize)]
+    struct LegacyModuleOutput {
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        function_maps: BTreeMap<Identifier, FunctionCoverage>,
+    }
+
+    #[test]
+    fn test_legacy_coverage_map() {
+        let addr = AccountAddress::from_hex_literal("0x1").unwrap();
+        let mut function_maps = BTreeMap::new();
+        function_maps.insert(ident("f"), vec![(0, 3), (2, 1)].into_iter().collect());
+        let mut module_maps = BTreeMap::new();
+        module_maps.insert(
+            (addr, ident("M")),
+            LegacyModuleOutput {
+                module_addr: addr,
+                module_name: ident("M"),
+                function_maps,
+            },
+        );
+        let bytes = lcs::to_bytes(&LegacyOutput { module_maps }).unwrap();
+
+        let coverage_map = CoverageMap::from_bytes(&bytes).unwrap();
+        let module_map = get_module_map(&coverage_map, "M");
+        let f = module_map.get_function_coverage(&ident("f")).unwrap();
+        assert_eq!((f[&0], f[&2]), (3, 1));
+        assert!(module_map.branch_maps.is_empty());
+
+        // Maps with a header keep their branch coverage
+        let path = TempPath::new();
+        coverage_map.output_to_file(path.path()).unwrap();
+        let coverage_map = CoverageMap::from_binary_file(path.path());
+        assert_eq!(
+            get_module_map(&coverage_map, "M").function_maps[&ident("f")][&0],
+            3
+        );
+
+        let mut bytes = COVERAGE_MAP_MAGIC.to_vec();
+        bytes.push(COVERAGE_MAP_VERSION + 1);
+        assert!(CoverageMap::from_bytes(&bytes).is_err());
+    }
 }
@@ -0,0 +1,398 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use crate::{coverage_map::CoverageMap, summary::percent_coverage_for_counts};
+use anyhow::{format_err, Result};
+use bytecode_source_map::source_map::SourceMap;
+use codespan::{FileId, Files};
+use move_ir_types::location::Loc;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+use vm::{
+    access::ModuleAccess,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+
+/// The coverage of a function, at the line of its declaration
+#[derive(Debug)]
+pub struct FunctionLineCoverage {
+    pub line: u32,
+    pub calls: u64,
+}
+
+/// The coverage of a conditional branch at some line. The counts are `None` if the branch was
+/// never reached.
+#[derive(Debug)]
+pub struct BranchLineCoverage {
+    pub line: u32,
+    pub taken: Option<u64>,
+    pub not_taken: Option<u64>,
+}
+
+/// The line and branch coverage of a source file, aggregated over the modules it defines.
+/// Lines are numbered from 1.
+pub struct FileCoverage {
+    /// The path of the file in the source maps
+    pub source_path: String,
+    /// The path the file was read from
+    pub file_path: String,
+    /// Functions by `Module::function` name
+    pub functions: BTreeMap<String, FunctionLineCoverage>,
+    /// Execution counts of the lines with code, i.e. the count of their most executed instruction
+    pub lines: BTreeMap<u32, u64>,
+    pub branches: Vec<BranchLineCoverage>,
+    files: Files<String>,
+    file_id: FileId,
+}
+
+/// The coverage of all the source files of a set of modules, by source path
+#[derive(Default)]
+pub struct FilesCoverage {
+    pub file_coverages: BTreeMap<String, FileCoverage>,
+}
+
+impl FileCoverage {
+    pub fn new(source_path: String, file_path: &Path) -> Result<Self> {
+        let file_contents = fs::read_to_string(file_path).map_err(|err| {
+            format_err!(
+                "Unable to read source file {}: {}",
+                file_path.display(),
+                err
+            )
+        })?;
+        let mut files = Files::new();
+        let file_id = files.add(source_path.clone(), file_contents);
+        Ok(Self {
+            source_path,
+            file_path: file_path.display().to_string(),
+            functions: BTreeMap::new(),
+            lines: BTreeMap::new(),
+            branches: Vec::new(),
+            files,
+            file_id,
+        })
+    }
+
+    /// Adds the coverage of the functions of `module` declared in this file.
+    pub fn add_module(
+        &mut self,
+        module: &CompiledModule,
+        source_map: &SourceMap<Loc>,
+        coverage_map: &CoverageMap,
+    ) {
+        let module_name = module.self_id();
+        let module_map = coverage_map
+            .module_maps
+            .get(&(*module_name.address(), module_name.name().to_owned()));
+
+        for (function_def_idx, function_def) in module.function_defs().iter().enumerate() {
+            let code_unit = match &function_def.code {
+                None => continue,
+                Some(code_unit) => code_unit,
+            };
+            let function_def_idx = FunctionDefinitionIndex(function_def_idx as u16);
+            let function_map = match source_map.get_function_source_map(function_def_idx) {
+                Ok(function_map) if function_map.decl_location.file() == self.source_path => {
+                    function_map
+                }
+                _ => continue,
+            };
+            let fn_handle = module.function_handle_at(function_def.function);
+            let fn_name = module.identifier_at(fn_handle.name);
+            let function_coverage =
+                module_map.and_then(|module_map| module_map.get_function_coverage(fn_name));
+            let branch_coverage =
+                module_map.and_then(|module_map| module_map.get_function_branch_coverage(fn_name));
+            let count_at = |code_offset: u64| {
+                function_coverage
+                    .and_then(|coverage| coverage.get(&code_offset))
+                    .cloned()
+                    .unwrap_or(0)
+            };
+
+            self.functions.insert(
+                format!("{}::{}", module_name.name(), fn_name),
+                FunctionLineCoverage {
+                    line: self.line(function_map.decl_location),
+                    calls: count_at(0),
+                },
+            );
+
+            for (code_offset, instr) in code_unit.code.iter().enumerate() {
+                let loc = match source_map
+                    .get_code_location(function_def_idx, code_offset as CodeOffset)
+                {
+                    Ok(loc) => loc,
+                    Err(_) => continue,
+                };
+                let line = self.line(loc);
+                let count = count_at(code_offset as u64);
+                let line_count = self.lines.entry(line).or_insert(0);
+                *line_count = (*line_count).max(count);
+
+                if instr.is_conditional_branch() {
+                    let branch = if count > 0 {
+                        Some(
+                            branch_coverage
+                                .and_then(|coverage| coverage.get(&(code_offset as u64)))
+                                .cloned()
+                                .unwrap_or_default(),
+                        )
+                    } else {
+                        None
+                    };
+                    self.branches.push(BranchLineCoverage {
+                        line,
+                        taken: branch.map(|branch| branch.taken),
+                        not_taken: branch.map(|branch| branch.not_taken),
+                    });
+                }
+            }
+        }
+    }
+
+    /// The lines with code, and the lines executed
+    pub fn line_counts(&self) -> (u64, u64) {
+        let hit = self.lines.values().filter(|count| **count > 0).count();
+        (self.lines.len() as u64, hit as u64)
+    }
+
+    /// The edges of the conditional branches, and the edges taken
+    pub fn branch_counts(&self) -> (u64, u64) {
+        let hit: usize = self
+            .branches
+            .iter()
+            .map(|branch| {
+                [branch.taken, branch.not_taken]
+                    .iter()
+                    .filter(|count| count.unwrap_or(0) > 0)
+                    .count()
+            })
+            .sum();
+        (2 * self.branches.len() as u64, hit as u64)
+    }
+
+    /// Outputs the coverage of the file as an LCOV tracefile record.
+    pub fn output_lcov<W: Write>(&self, lcov_writer: &mut W) -> io::Result<()> {
+        writeln!(lcov_writer, "SF:{}", self.file_path)?;
+
+        for (fn_name, function) in self.functions.iter() {
+            writeln!(lcov_writer, "FN:{},{}", function.line, fn_name)?;
+        }
+        for (fn_name, function) in self.functions.iter() {
+            writeln!(lcov_writer, "FNDA:{},{}", function.calls, fn_name)?;
+        }
+        let functions_hit = self
+            .functions
+            .values()
+            .filter(|function| function.calls > 0)
+            .count();
+        writeln!(lcov_writer, "FNF:{}", self.functions.len())?;
+        writeln!(lcov_writer, "FNH:{}", functions_hit)?;
+
+        let format_count = |count: Option<u64>| match count {
+            Some(count) => count.to_string(),
+            None => "-".to_string(),
+        };
+        for (block, branch) in self.branches.iter().enumerate() {
+            writeln!(
+                lcov_writer,
+                "BRDA:{},{},0,{}",
+                branch.line,
+                block,
+                format_count(branch.taken)
+            )?;
+            writeln!(
+                lcov_writer,
+                "BRDA:{},{},1,{}",
+                branch.line,
+                block,
+                format_count(branch.not_taken)
+            )?;
+        }
+        let (branches_found, branches_hit) = self.branch_counts();
+        writeln!(lcov_writer, "BRF:{}", branches_found)?;
+        writeln!(lcov_writer, "BRH:{}", branches_hit)?;
+
+        for (line, count) in self.lines.iter() {
+            writeln!(lcov_writer, "DA:{},{}", line, count)?;
+        }
+        let (lines_found, lines_hit) = self.line_counts();
+        writeln!(lcov_writer, "LF:{}", lines_found)?;
+        writeln!(lcov_writer, "LH:{}", lines_hit)?;
+
+        writeln!(lcov_writer, "end_of_record")
+    }
+
+    fn line(&self, loc: Loc) -> u32 {
+        self.files
+            .location(self.file_id, loc.span().start())
+            .map(|location| location.line.0 + 1)
+            .unwrap_or(0)
+    }
+}
+
+impl FilesCoverage {
+    /// Adds the coverage of `module` to the files it is declared in. Relative source paths in
+    /// the source map are read from `source_root`.
+    pub fn add_module(
+        &mut self,
+        module: &CompiledModule,
+        source_map: &SourceMap<Loc>,
+        coverage_map: &CoverageMap,
+        source_root: &Path,
+    ) -> Result<()> {
+        let mut source_paths: Vec<_> = (0..module.function_defs().len())
+            .filter_map(|function_def_idx| {
+                source_map
+                    .get_function_source_map(FunctionDefinitionIndex(function_def_idx as u16))
+                    .ok()
+            })
+            .map(|function_map| function_map.decl_location.file())
+            .collect();
+        source_paths.sort();
+        source_paths.dedup();
+
+        for source_path in source_paths {
+            let file_coverage = match self.file_coverages.entry(source_path.to_string()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(FileCoverage::new(
+                    source_path.to_string(),
+                    &source_root.join(source_path),
+                )?),
+            };
+            file_coverage.add_module(module, source_map, coverage_map);
+        }
+        Ok(())
+    }
+
+    /// Outputs the coverage of all the files as an LCOV tracefile.
+    pub fn output_lcov<W: Write>(&self, lcov_writer: &mut W) -> io::Result<()> {
+        writeln!(lcov_writer, "TN:")?;
+        for file_coverage in self.file_coverages.values() {
+            file_coverage.output_lcov(lcov_writer)?;
+        }
+        Ok(())
+    }
+
+    /// Summarizes the line and branch coverage of each file in a human-readable format.
+    pub fn summarize_human<W: Write>(&self, summary_writer: &mut W) -> io::Result<()> {
+        let mut total_lines = (0, 0);
+        let mut total_branches = (0, 0);
+        for (source_path, file_coverage) in self.file_coverages.iter() {
+            let (lines_found, lines_hit) = file_coverage.line_counts();
+            let (branches_found, branches_hit) = file_coverage.branch_counts();
+            writeln!(summary_writer, "File {}", source_path)?;
+            writeln!(
+                summary_writer,
+                "\tlines: {}/{} ({:.2}%)",
+                lines_hit,
+                lines_found,
+                percent_coverage_for_counts(lines_found, lines_hit)
+            )?;
+            if branches_found > 0 {
+                writeln!(
+                    summary_writer,
+                    "\tbranches: {}/{} ({:.2}%)",
+                    branches_hit,
+                    branches_found,
+                    percent_coverage_for_counts(branches_found, branches_hit)
+                )?;
+            }
+            total_lines = (total_lines.0 + lines_found, total_lines.1 + lines_hit);
+            total_branches = (
+                total_branches.0 + branches_found,
+                total_branches.1 + branches_hit,
+            );
+        }
+        writeln!(
+            summary_writer,
+            ">>> % Line coverage: {:.2}",
+            percent_coverage_for_counts(total_lines.0, total_lines.1)
+        )?;
+        writeln!(
+            summary_writer,
+            ">>> % Branch coverage: {:.2}",
+            percent_coverage_for_counts(total_branches.0, total_branches.1)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage_map::ModuleCoverageMap;
+    use ir_to_bytecode::{compiler::compile_module, parser::parse_module};
+    use libra_temppath::TempPath;
+    use libra_types::account_address::AccountAddress;
+    use move_core_types::identifier::Identifier;
+
+    const SOURCE: &str = "\
+module M {
+    public f(x: u64): u64 {
+        if (copy(x) > 0) {
+            return 1;
+        }
+        return 0;
+    }
+}
+";
+
+    #[test]
+    fn test_output_lcov() {
+        let source_file = TempPath::new();
+        fs::write(source_file.path(), SOURCE).unwrap();
+        let address = AccountAddress::default();
+        let empty_deps: Vec<CompiledModule> = Vec::new();
+        let (module, source_map) = compile_module(
+            address,
+            parse_module("M.mvir", SOURCE).unwrap(),
+            &empty_deps,
+        )
+        .unwrap();
+
+        // f called once with a positive x: `BrFalse` at offset 3 falls through to `return 1`
+        let mut module_map = ModuleCoverageMap::new(address, Identifier::new("M").unwrap());
+        for pc in 0..6 {
+            module_map.insert(Identifier::new("f").unwrap(), pc);
+        }
+        module_map.insert_branch(Identifier::new("f").unwrap(), 3, false);
+        let mut module_maps = BTreeMap::new();
+        module_maps.insert((address, Identifier::new("M").unwrap()), module_map);
+        let coverage_map = CoverageMap { module_maps };
+
+        let mut file_coverage =
+            FileCoverage::new("M.mvir".to_string(), source_file.path()).unwrap();
+        file_coverage.add_module(&module, &source_map, &coverage_map);
+        let mut lcov = Vec::new();
+        file_coverage.output_lcov(&mut lcov).unwrap();
+
+        let expected = format!(
+            "SF:{}
+FN:2,M::f
+FNDA:1,M::f
+FNF:1
+FNH:1
+BRDA:3,0,0,0
+BRDA:3,0,1,1
+BRF:2
+BRH:1
+DA:3,1
+DA:4,1
+DA:6,0
+LF:3
+LH:2
+end_of_record
+",
+            source_file.path().display()
+        );
+        assert_eq!(String::from_utf8(lcov).unwrap(), expected);
+    }
+}
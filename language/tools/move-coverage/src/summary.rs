@@ -29,6 +29,9 @@ pub struct FunctionSummary {
     pub fn_is_native: bool,
     pub total_number_of_instructions: u64,
     pub covered_instructions: u64,
+    /// Both edges of every conditional branch
+    pub total_number_of_branches: u64,
+    pub covered_branches: u64,
 }
 
 impl Default for ModuleSummaryOptions {
@@ -62,6 +65,8 @@ impl ModuleSummary {
                         fn_is_native: true,
                         total_number_of_instructions: 0,
                         covered_instructions: 0,
+                        total_number_of_branches: 0,
+                        covered_branches: 0,
                     },
                     Some(code_unit) => {
                         let total_number_of_instructions = code_unit.code.len() as u64;
@@ -73,10 +78,29 @@ impl ModuleSummary {
                                     .map(|function_map| function_map.len())
                             })
                             .unwrap_or(0) as u64;
+                        let total_number_of_branches = 2 * code_unit
+                            .code
+                            .iter()
+                            .filter(|instr| instr.is_conditional_branch())
+                            .count()
+                            as u64;
+                        let covered_branches = module_map
+                            .and_then(|fn_map| fn_map.get_function_branch_coverage(&fn_name))
+                            .map(|branch_map| {
+                                branch_map
+                                    .values()
+                                    .map(|branch| {
+                                        (branch.taken > 0) as u64 + (branch.not_taken > 0) as u64
+                                    })
+                                    .sum()
+                            })
+                            .unwrap_or(0);
                         FunctionSummary {
                             fn_is_native: false,
                             total_number_of_instructions,
                             covered_instructions,
+                            total_number_of_branches,
+                            covered_branches,
                         }
                     }
                 };
@@ -100,11 +124,11 @@ impl ModuleSummary {
             self.module_name.name()
         );
 
-        let mut format_line = |fn_name, covered, uncovered| {
+        let mut format_line = |fn_name, covered, uncovered, covered_branches, total_branches| {
             writeln!(
                 summary_writer,
-                "{},{},{},{}",
-                module, fn_name, covered, uncovered
+                "{},{},{},{},{},{}",
+                module, fn_name, covered, uncovered, covered_branches, total_branches
             )
         };
 
@@ -117,6 +141,8 @@ impl ModuleSummary {
                 fn_name,
                 fn_summary.covered_instructions,
                 fn_summary.total_number_of_instructions,
+                fn_summary.covered_branches,
+                fn_summary.total_number_of_branches,
             )?;
         }
 
@@ -162,6 +188,13 @@ impl ModuleSummary {
                     "\t\t% coverage: {:.2}",
                     fn_summary.percent_coverage()
                 )?;
+                if fn_summary.total_number_of_branches > 0 {
+                    writeln!(
+                        summary_writer,
+                        "\t\tcovered branches: {}/{}",
+                        fn_summary.covered_branches, fn_summary.total_number_of_branches
+                    )?;
+                }
             }
         }
 
@@ -0,0 +1,598 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::sources::{SourceLine, Sources};
+use anyhow::{bail, format_err, Result};
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath, account_address::AccountAddress, transaction::Version,
+    write_set::WriteSet,
+};
+use libra_vm::data_cache::RemoteStorage;
+use libradb::LibraDB;
+use move_core_types::{
+    gas_schedule::{GasAlgebra, GasUnits},
+    language_storage::{ModuleId, ResourceKey, TypeTag},
+    parser::parse_type_tags,
+};
+use move_vm_runtime::{
+    data_cache::TransactionDataCache,
+    move_vm::MoveVM,
+    tracer::{GlobalAccessKind, TraceEvent, TracedFunction, VMTracer},
+};
+use move_vm_types::{
+    gas_schedule::{zero_cost_schedule, CostStrategy},
+    values::{Locals, Value},
+};
+use resource_viewer::MoveValueAnnotator;
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, Write},
+    sync::{Arc, Mutex},
+};
+use storage_interface::DbReader;
+use transaction_replay::{ChangeSetStateView, DbStateView};
+use vm::file_format::Bytecode;
+
+const HELP: &str = "\
+Execution:
+  continue, c               run until the next breakpoint
+  step, s                   run until another source line, stepping into calls
+  next, n                   run until another source line of this function or its callers
+  stepi, si                 run one instruction
+  finish, f                 run until the current function returns
+  quit, q                   abort the execution, discarding its results
+Breakpoints:
+  break, b <function>       break on entry of a function, e.g. LibraAccount::pay_from_sender
+  break, b <file>:<line>    break on a source line, the file being matched by suffix
+  breakpoints               list the breakpoints
+  delete, d <index>         delete a breakpoint
+Inspection:
+  backtrace, bt             the call stack
+  list, l                   the source around the current line
+  locals                    the parameters and locals of the current function
+  stack                     the operand stack, top last
+  global, g <addr> <type>   a global resource, as last accessed and in the initial state.
+                            Writes through a reference after the access are not shown.
+  globals                   the global resources accessed so far";
+
+/// The state a script is debugged on
+pub enum State {
+    /// The state of a LibraDB after the transaction at `version`, or the latest state
+    Db {
+        db: Arc<LibraDB>,
+        version: Option<Version>,
+    },
+    /// The state after the testing genesis
+    Genesis(ChangeSetStateView),
+}
+
+impl State {
+    /// Calls `f` with a view of the state
+    pub fn with_view<T>(&self, f: impl FnOnce(&dyn StateView) -> Result<T>) -> Result<T> {
+        match self {
+            State::Db { db, version } => {
+                let version = match version {
+                    Some(version) => *version,
+                    None => db.get_latest_version()?,
+                };
+                f(&DbStateView::new(&**db, Some(version)))
+            }
+            State::Genesis(state_view) => f(state_view),
+        }
+    }
+}
+
+enum Breakpoint {
+    /// The entry of the functions whose names end with the name
+    Function(String),
+    /// The first instruction of a line, the file being matched by suffix
+    Line { file: String, line: u32 },
+}
+
+/// When to stop at the next instruction
+enum Mode {
+    Continue,
+    StepInstruction,
+    /// Stop at another line or in another frame, skipping the frames of calls if `over_calls`
+    StepLine {
+        depth: usize,
+        line: Option<SourceLine>,
+        over_calls: bool,
+    },
+    /// Stop once the call stack is shallower than `depth`
+    Finish {
+        depth: usize,
+    },
+}
+
+struct Frame {
+    /// The display name of the function
+    name: String,
+    module: Option<ModuleId>,
+    function: String,
+    pc: u16,
+    line: Option<SourceLine>,
+}
+
+/// The last access to a global resource. The value is the one at the time of the access: a
+/// borrowed resource mutated through its reference afterwards is not tracked, as the mutations
+/// only reach the data cache once the effects of the execution are computed.
+struct GlobalAccess {
+    kind: GlobalAccessKind,
+    function: String,
+    pc: u16,
+    value: String,
+}
+
+struct DebuggerState {
+    state: Arc<State>,
+    sources: Sources,
+    input: Box<dyn BufRead + Send>,
+    output: Box<dyn Write + Send>,
+    breakpoints: Vec<Breakpoint>,
+    mode: Mode,
+    /// Whether the debugger stopped reading commands, the input being closed or the execution
+    /// aborted
+    detached: bool,
+    /// Whether the execution was aborted by `quit`
+    aborted: bool,
+    frames: Vec<Frame>,
+    globals: BTreeMap<(AccountAddress, String), GlobalAccess>,
+}
+
+/// An interactive debugger of the executions it traces. The execution stops before its first
+/// instruction, and then at breakpoints and steps, to read commands from the input.
+pub struct Debugger {
+    state: Mutex<DebuggerState>,
+}
+
+impl Debugger {
+    pub fn new(
+        state: Arc<State>,
+        sources: Sources,
+        input: Box<dyn BufRead + Send>,
+        output: Box<dyn Write + Send>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(DebuggerState {
+                state,
+                sources,
+                input,
+                output,
+                breakpoints: vec![],
+                mode: Mode::StepInstruction,
+                detached: false,
+                aborted: false,
+                frames: vec![],
+                globals: BTreeMap::new(),
+            }),
+        })
+    }
+
+    /// Whether the execution was aborted by `quit`. The VM cannot be stopped by a tracer, so
+    /// the execution runs to its end without stopping and its results are to be discarded.
+    pub fn aborted(&self) -> bool {
+        self.state.lock().map_or(true, |state| state.aborted)
+    }
+}
+
+/// Executes a script on `state` under `debugger`. Gas is not charged, so that scripts can be
+/// debugged without funds. Returns the write set of the script and the number of events it
+/// emitted, or an error if it failed or was aborted.
+pub fn execute_script(
+    state: &State,
+    debugger: Arc<Debugger>,
+    script: Vec<u8>,
+    type_args: Vec<TypeTag>,
+    args: Vec<Value>,
+    sender: AccountAddress,
+) -> Result<(WriteSet, usize)> {
+    let vm = MoveVM::new_with_tracer(debugger.clone());
+    let gas_schedule = zero_cost_schedule();
+    let result = state.with_view(|state_view| {
+        let remote_storage = RemoteStorage::new(state_view);
+        let mut data_store = TransactionDataCache::new(&remote_storage);
+        let mut cost_strategy = CostStrategy::system(&gas_schedule, GasUnits::new(0));
+        let executed = vm.execute_script(
+            script,
+            type_args,
+            args,
+            sender,
+            &mut data_store,
+            &mut cost_strategy,
+        );
+        Ok(executed
+            .and_then(|()| data_store.make_write_set())
+            .map(|write_set| (write_set, data_store.event_data().len())))
+    })?;
+    if debugger.aborted() {
+        bail!("Execution aborted");
+    }
+    result.map_err(|status| format_err!("Script failed: {:?}", status))
+}
+
+impl VMTracer for Debugger {
+    fn trace(&self, event: &TraceEvent) {
+        if let Ok(mut state) = self.state.lock() {
+            // The execution cannot be failed by the debugger, so output errors are dropped
+            let _ = state.trace(event);
+        }
+    }
+}
+
+fn function_matches(name: &str, pattern: &str) -> bool {
+    name == pattern || name.ends_with(&format!("::{}", pattern))
+}
+
+fn display_line(line: &Option<SourceLine>) -> String {
+    match line {
+        Some(line) => format!("{}:{}", line.file, line.line),
+        None => "no source".to_string(),
+    }
+}
+
+impl DebuggerState {
+    fn trace(&mut self, event: &TraceEvent) -> Result<()> {
+        match event {
+            TraceEvent::Call { function, .. } => self.frames.push(Frame {
+                name: function.to_string(),
+                module: function.module.cloned(),
+                function: function.name.to_string(),
+                pc: 0,
+                line: None,
+            }),
+            TraceEvent::Return { .. } => {
+                self.frames.pop();
+            }
+            TraceEvent::Instruction {
+                function,
+                pc,
+                instruction,
+                stack,
+                locals,
+            } => self.instruction(function, *pc, instruction, stack, locals)?,
+            TraceEvent::GasCharged { .. } => (),
+            TraceEvent::GlobalAccess {
+                kind,
+                address,
+                struct_type,
+                value,
+            } => {
+                let struct_tag = match struct_type.struct_tag() {
+                    Ok(struct_tag) => struct_tag.to_string(),
+                    Err(_) => format!("{:?}", struct_type),
+                };
+                let (function, pc) = match self.frames.last() {
+                    Some(frame) => (frame.name.clone(), frame.pc),
+                    None => (String::new(), 0),
+                };
+                self.globals.insert(
+                    (*address, struct_tag),
+                    GlobalAccess {
+                        kind: *kind,
+                        function,
+                        pc,
+                        value: value.to_string(),
+                    },
+                );
+            }
+            TraceEvent::Error { error } => {
+                writeln!(self.output, "Execution failed: {:?}", error)?;
+                self.print_backtrace()?;
+                self.frames.clear();
+            }
+        }
+        Ok(())
+    }
+
+    fn instruction(
+        &mut self,
+        function: &TracedFunction,
+        pc: u16,
+        instruction: &Bytecode,
+        stack: &[Value],
+        locals: &Locals,
+    ) -> Result<()> {
+        let line = self.sources.line(function.module, function.name, pc);
+        let depth = self.frames.len();
+        let previous_line = match self.frames.last_mut() {
+            Some(frame) => {
+                frame.pc = pc;
+                std::mem::replace(&mut frame.line, line.clone())
+            }
+            None => return Ok(()),
+        };
+        let new_line = pc == 0 || line != previous_line;
+
+        let mut stop = match &self.mode {
+            Mode::Continue => false,
+            Mode::StepInstruction => true,
+            Mode::StepLine {
+                depth: start_depth,
+                line: start_line,
+                over_calls,
+            } => {
+                if *over_calls && depth > *start_depth {
+                    false
+                } else {
+                    depth != *start_depth || line.is_none() || &line != start_line
+                }
+            }
+            Mode::Finish { depth: start_depth } => depth < *start_depth,
+        };
+        let name = &self.frames[depth - 1].name;
+        for (index, breakpoint) in self.breakpoints.iter().enumerate() {
+            let hit = match breakpoint {
+                Breakpoint::Function(pattern) => pc == 0 && function_matches(name, pattern),
+                Breakpoint::Line {
+                    file,
+                    line: breakpoint_line,
+                } => {
+                    new_line
+                        && line.as_ref().map_or(false, |line| {
+                            line.file.ends_with(file.as_str()) && line.line == *breakpoint_line
+                        })
+                }
+            };
+            if hit {
+                writeln!(self.output, "Breakpoint {}", index)?;
+                stop = true;
+                break;
+            }
+        }
+        if !stop || self.detached {
+            return Ok(());
+        }
+
+        self.mode = Mode::Continue;
+        writeln!(self.output, "{} pc {}: {:?}", name, pc, instruction)?;
+        self.print_line(&line)?;
+        self.read_commands(stack, locals)
+    }
+
+    /// Reads commands until one resumes the execution
+    fn read_commands(&mut self, stack: &[Value], locals: &Locals) -> Result<()> {
+        loop {
+            write!(self.output, "(move-debugger) ")?;
+            self.output.flush()?;
+            let mut command = String::new();
+            if self.input.read_line(&mut command)? == 0 {
+                self.detached = true;
+                return Ok(());
+            }
+            let words: Vec<_> = command.split_whitespace().collect();
+            let (command, args) = match words.split_first() {
+                Some((command, args)) => (*command, args),
+                None => continue,
+            };
+            let resumed = match self.command(command, args, stack, locals) {
+                Ok(resumed) => resumed,
+                Err(err) => {
+                    writeln!(self.output, "{}", err)?;
+                    false
+                }
+            };
+            if resumed {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Runs a command, returning whether it resumes the execution
+    fn command(
+        &mut self,
+        command: &str,
+        args: &[&str],
+        stack: &[Value],
+        locals: &Locals,
+    ) -> Result<bool> {
+        let depth = self.frames.len();
+        let line = self.frames.last().and_then(|frame| frame.line.clone());
+        match command {
+            "continue" | "c" => self.mode = Mode::Continue,
+            "step" | "s" => {
+                self.mode = Mode::StepLine {
+                    depth,
+                    line,
+                    over_calls: false,
+                }
+            }
+            "next" | "n" => {
+                self.mode = Mode::StepLine {
+                    depth,
+                    line,
+                    over_calls: true,
+                }
+            }
+            "stepi" | "si" => self.mode = Mode::StepInstruction,
+            "finish" | "f" => self.mode = Mode::Finish { depth },
+            "quit" | "q" => {
+                self.aborted = true;
+                self.detached = true;
+            }
+            "break" | "b" => {
+                let breakpoint = parse_breakpoint(args)?;
+                writeln!(self.output, "Breakpoint {}", self.breakpoints.len())?;
+                self.breakpoints.push(breakpoint);
+                return Ok(false);
+            }
+            "breakpoints" => {
+                for (index, breakpoint) in self.breakpoints.iter().enumerate() {
+                    match breakpoint {
+                        Breakpoint::Function(function) => {
+                            writeln!(self.output, "{}: {}", index, function)?
+                        }
+                        Breakpoint::Line { file, line } => {
+                            writeln!(self.output, "{}: {}:{}", index, file, line)?
+                        }
+                    }
+                }
+                return Ok(false);
+            }
+            "delete" | "d" => {
+                let index = match args {
+                    [index] => index.parse::<usize>()?,
+                    _ => bail!("Usage: delete <index>"),
+                };
+                if index >= self.breakpoints.len() {
+                    bail!("No breakpoint {}", index)
+                }
+                self.breakpoints.remove(index);
+                return Ok(false);
+            }
+            "backtrace" | "bt" => {
+                self.print_backtrace()?;
+                return Ok(false);
+            }
+            "list" | "l" => {
+                self.print_source(&line)?;
+                return Ok(false);
+            }
+            "locals" => {
+                self.print_locals(locals)?;
+                return Ok(false);
+            }
+            "stack" => {
+                for value in stack {
+                    writeln!(self.output, "{}", value)?;
+                }
+                return Ok(false);
+            }
+            "global" | "g" => {
+                self.print_global(args)?;
+                return Ok(false);
+            }
+            "globals" => {
+                for ((address, struct_tag), access) in self.globals.iter() {
+                    writeln!(
+                        self.output,
+                        "{} {}: {:?} in {} pc {}: {}",
+                        address, struct_tag, access.kind, access.function, access.pc, access.value
+                    )?;
+                }
+                return Ok(false);
+            }
+            "help" | "h" => {
+                writeln!(self.output, "{}", HELP)?;
+                return Ok(false);
+            }
+            _ => bail!("Unknown command {}, see help", command),
+        }
+        Ok(true)
+    }
+
+    fn print_line(&mut self, line: &Option<SourceLine>) -> Result<()> {
+        match line {
+            Some(line) => {
+                let text = self.sources.text(line).unwrap_or_default();
+                writeln!(self.output, "{}:{}\t{}", line.file, line.line, text)?
+            }
+            None => writeln!(self.output, "(no source)")?,
+        }
+        Ok(())
+    }
+
+    fn print_source(&mut self, line: &Option<SourceLine>) -> Result<()> {
+        let line = match line {
+            Some(line) => line,
+            None => bail!("No source for the current instruction"),
+        };
+        for number in line.line.saturating_sub(5).max(1)..=line.line + 5 {
+            let source_line = SourceLine {
+                file: line.file.clone(),
+                line: number,
+            };
+            if let Some(text) = self.sources.text(&source_line) {
+                let marker = if number == line.line { ">" } else { " " };
+                writeln!(self.output, "{} {:>5}  {}", marker, number, text)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn print_backtrace(&mut self) -> Result<()> {
+        for (index, frame) in self.frames.iter().rev().enumerate() {
+            writeln!(
+                self.output,
+                "#{} {} pc {} ({})",
+                index,
+                frame.name,
+                frame.pc,
+                display_line(&frame.line)
+            )?;
+        }
+        Ok(())
+    }
+
+    fn print_locals(&mut self, locals: &Locals) -> Result<()> {
+        let frame = match self.frames.last() {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+        for (index, value) in locals.display_locals().iter().enumerate() {
+            match self
+                .sources
+                .local_name(frame.module.as_ref(), &frame.function, index)
+            {
+                Some(name) => writeln!(self.output, "[{}] {}: {}", index, name, value)?,
+                None => writeln!(self.output, "[{}] {}", index, value)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints a global resource as last accessed by the execution, see `GlobalAccess`, and in the
+    /// state the execution started from
+    fn print_global(&mut self, args: &[&str]) -> Result<()> {
+        let (address, struct_tag) = match args {
+            [address, type_tag] => {
+                let address = AccountAddress::from_hex_literal(address)?;
+                match parse_type_tags(type_tag)?.as_slice() {
+                    [TypeTag::Struct(struct_tag)] => (address, struct_tag.clone()),
+                    _ => bail!("{} is not a struct type", type_tag),
+                }
+            }
+            _ => bail!("Usage: global <address> <struct type>"),
+        };
+        match self.globals.get(&(address, struct_tag.to_string())) {
+            Some(access) => writeln!(
+                self.output,
+                "Last {:?} in {} pc {}: {}",
+                access.kind, access.function, access.pc, access.value
+            )?,
+            None => writeln!(self.output, "Not accessed by the execution")?,
+        }
+        let access_path = AccessPath::resource_access_path(&ResourceKey::new(address, struct_tag));
+        let initial = self
+            .state
+            .with_view(|state_view| match state_view.get(&access_path)? {
+                Some(blob) => Ok(MoveValueAnnotator::new(state_view)
+                    .view_access_path(access_path.clone(), &blob)?
+                    .to_string()),
+                None => Ok("none".to_string()),
+            })?;
+        writeln!(self.output, "Initial state: {}", initial)?;
+        Ok(())
+    }
+}
+
+fn parse_breakpoint(args: &[&str]) -> Result<Breakpoint> {
+    let location = match args {
+        [location] => *location,
+        _ => bail!("Usage: break <function> or break <file>:<line>"),
+    };
+    if let Some(separator) = location.rfind(':') {
+        let (file, line) = (&location[..separator], &location[separator + 1..]);
+        if !file.ends_with(':') {
+            let line = line
+                .parse::<u32>()
+                .map_err(|_| format_err!("Invalid line in {}", location))?;
+            return Ok(Breakpoint::Line {
+                file: file.to_string(),
+                line,
+            });
+        }
+    }
+    Ok(Breakpoint::Function(location.to_string()))
+}
@@ -0,0 +1,10 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! An interactive debugger of Move scripts, stepping through their bytecode and sources (see
+//! `debugger`), along with the source maps it reads (see `sources`).
+
+pub mod debugger;
+pub mod sources;
@@ -0,0 +1,135 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::{format_err, Result};
+use libra_types::{
+    account_address::AccountAddress, account_config, transaction::Version, write_set::WriteOp,
+};
+use libradb::LibraDB;
+use move_core_types::{
+    parser::{parse_transaction_arguments, parse_type_tags},
+    transaction_argument::TransactionArgument,
+};
+use move_debugger::{
+    debugger::{execute_script, Debugger, State},
+    sources::Sources,
+};
+use move_vm_types::values::Value;
+use std::{
+    fs,
+    io::{self, BufReader},
+    path::PathBuf,
+    sync::Arc,
+};
+use structopt::StructOpt;
+use transaction_replay::ChangeSetStateView;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "move-debugger",
+    about = "Debug the execution of a Move script, stepping through its bytecode and sources"
+)]
+struct Args {
+    /// The path to the compiled script, with its source map next to it
+    #[structopt(name = "PATH_TO_COMPILED_SCRIPT", parse(from_os_str))]
+    pub script: PathBuf,
+    /// The arguments of the script, e.g. "0x1, 10u64"
+    #[structopt(long = "args", short = "a", default_value = "")]
+    pub args: String,
+    /// The type arguments of the script, e.g. "0x1::LBR::T"
+    #[structopt(long = "type-args", default_value = "")]
+    pub type_args: String,
+    /// The sender of the script, the association if not present
+    #[structopt(long = "sender", short = "s")]
+    pub sender: Option<String>,
+    /// The directory of the LibraDB the script runs on. It runs on the testing genesis without it.
+    #[structopt(long = "db", parse(from_os_str))]
+    pub db: Option<PathBuf>,
+    /// The version of the state of the LibraDB, the latest if not present
+    #[structopt(long = "version", short = "v")]
+    pub version: Option<Version>,
+    /// The paths to module binaries, each with its source map next to it, to debug their sources
+    #[structopt(long = "module-path", short = "b", parse(from_os_str))]
+    pub module_paths: Vec<PathBuf>,
+    /// A directory of module binaries with their source maps, e.g. the output of `move-build -m`
+    #[structopt(long = "modules-dir", short = "d", parse(from_os_str))]
+    pub modules_dir: Option<PathBuf>,
+    /// The directory relative source paths of the source maps are resolved against
+    #[structopt(long = "source-root", default_value = ".", parse(from_os_str))]
+    pub source_root: PathBuf,
+}
+
+fn get_module_paths(args: &Args) -> Result<Vec<PathBuf>> {
+    let mut module_paths = args.module_paths.clone();
+    if let Some(modules_dir) = &args.modules_dir {
+        let mut dir_paths = vec![];
+        for entry in fs::read_dir(modules_dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == "mv") {
+                dir_paths.push(path);
+            }
+        }
+        dir_paths.sort();
+        module_paths.extend(dir_paths);
+    }
+    Ok(module_paths)
+}
+
+fn convert_txn_args(args: &[TransactionArgument]) -> Vec<Value> {
+    args.iter()
+        .map(|arg| match arg {
+            TransactionArgument::U8(i) => Value::u8(*i),
+            TransactionArgument::U64(i) => Value::u64(*i),
+            TransactionArgument::U128(i) => Value::u128(*i),
+            TransactionArgument::Address(a) => Value::address(*a),
+            TransactionArgument::Bool(b) => Value::bool(*b),
+            TransactionArgument::U8Vector(v) => Value::vector_u8(v.clone()),
+        })
+        .collect()
+}
+
+fn main() -> Result<()> {
+    let args = Args::from_args();
+    let script = fs::read(&args.script)
+        .map_err(|err| format_err!("Unable to read '{}': {}", args.script.display(), err))?;
+    let script_args = convert_txn_args(&parse_transaction_arguments(&args.args)?);
+    let type_args = parse_type_tags(&args.type_args)?;
+    let sender = match &args.sender {
+        Some(sender) => AccountAddress::from_hex_literal(sender)?,
+        None => account_config::association_address(),
+    };
+
+    let state = Arc::new(match &args.db {
+        Some(path) => {
+            let db = LibraDB::open(path, true /* readonly */, None /* pruner */)?;
+            State::Db {
+                db: Arc::new(db),
+                version: args.version,
+            }
+        }
+        None => State::Genesis(ChangeSetStateView::genesis()),
+    });
+    let sources = Sources::new(
+        args.source_root.clone(),
+        &args.script,
+        &get_module_paths(&args)?,
+    )?;
+    let debugger = Debugger::new(
+        state.clone(),
+        sources,
+        Box::new(BufReader::new(io::stdin())),
+        Box::new(io::stdout()),
+    );
+    let (write_set, event_count) =
+        execute_script(&state, debugger, script, type_args, script_args, sender)?;
+    println!("Script executed, emitting {} event(s)", event_count);
+    for (access_path, write_op) in &write_set {
+        match write_op {
+            WriteOp::Value(_) => println!("write {}", access_path),
+            WriteOp::Deletion => println!("delete {}", access_path),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,192 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{format_err, Result};
+use bytecode_source_map::{
+    source_map::SourceMap,
+    utils::{remap_owned_loc_to_loc, source_map_from_file, OwnedLoc},
+};
+use move_core_types::language_storage::ModuleId;
+use move_ir_types::location::Loc;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use vm::{
+    access::ModuleAccess,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+
+const SOURCE_MAP_EXTENSION: &str = "mvsm";
+
+/// A line of a source file, numbered from 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLine {
+    pub file: String,
+    pub line: u32,
+}
+
+struct SourceFile {
+    text: String,
+    /// The offset of the start of each line
+    line_starts: Vec<usize>,
+}
+
+/// The source maps of the script and modules being debugged, and their source files
+pub struct Sources {
+    source_root: PathBuf,
+    script: Option<SourceMap<Loc>>,
+    modules: BTreeMap<ModuleId, (CompiledModule, SourceMap<Loc>)>,
+    files: BTreeMap<String, Option<SourceFile>>,
+}
+
+/// Reads the source map next to the binary at `path`, if any
+fn source_map_next_to(path: &Path) -> Result<Option<SourceMap<Loc>>> {
+    let source_map_path = path.with_extension(SOURCE_MAP_EXTENSION);
+    if !source_map_path.exists() {
+        return Ok(None);
+    }
+    let source_map = source_map_from_file::<OwnedLoc>(&source_map_path).map_err(|err| {
+        format_err!(
+            "Unable to read source map '{}': {}",
+            source_map_path.display(),
+            err
+        )
+    })?;
+    Ok(Some(remap_owned_loc_to_loc(source_map)))
+}
+
+impl SourceFile {
+    fn new(text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    fn line_of(&self, offset: usize) -> u32 {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line as u32 + 1,
+            Err(next_line) => next_line as u32,
+        }
+    }
+
+    fn text_of(&self, line: u32) -> Option<&str> {
+        let start = *self.line_starts.get((line as usize).checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line as usize)
+            .cloned()
+            .unwrap_or_else(|| self.text.len());
+        Some(self.text[start..end].trim_end())
+    }
+}
+
+impl Sources {
+    /// Loads the source map of the script at `script_path` and of the modules at
+    /// `module_paths`, each next to its binary. Relative source paths in the source maps are
+    /// read from `source_root`.
+    pub fn new(source_root: PathBuf, script_path: &Path, module_paths: &[PathBuf]) -> Result<Self> {
+        let mut modules = BTreeMap::new();
+        for module_path in module_paths {
+            let bytes = fs::read(module_path).map_err(|err| {
+                format_err!("Unable to read '{}': {}", module_path.display(), err)
+            })?;
+            let module = CompiledModule::deserialize(&bytes).map_err(|err| {
+                format_err!(
+                    "Unable to deserialize '{}': {:?}",
+                    module_path.display(),
+                    err
+                )
+            })?;
+            if let Some(source_map) = source_map_next_to(module_path)? {
+                modules.insert(module.self_id(), (module, source_map));
+            }
+        }
+        Ok(Self {
+            source_root,
+            script: source_map_next_to(script_path)?,
+            modules,
+            files: BTreeMap::new(),
+        })
+    }
+
+    /// The source map of a function, and its index in it. The main function of a script has
+    /// no module.
+    fn function_map(
+        &self,
+        module: Option<&ModuleId>,
+        function: &str,
+    ) -> Option<(&SourceMap<Loc>, FunctionDefinitionIndex)> {
+        match module {
+            None => self
+                .script
+                .as_ref()
+                .map(|source_map| (source_map, FunctionDefinitionIndex(0))),
+            Some(module_id) => {
+                let (module, source_map) = self.modules.get(module_id)?;
+                let function_def_idx = module.function_defs().iter().position(|function_def| {
+                    let handle = module.function_handle_at(function_def.function);
+                    module.identifier_at(handle.name).as_str() == function
+                })?;
+                Some((source_map, FunctionDefinitionIndex(function_def_idx as u16)))
+            }
+        }
+    }
+
+    /// The source line of the instruction at `pc` of a function
+    pub fn line(
+        &mut self,
+        module: Option<&ModuleId>,
+        function: &str,
+        pc: CodeOffset,
+    ) -> Option<SourceLine> {
+        let loc = {
+            let (source_map, function_def_idx) = self.function_map(module, function)?;
+            source_map.get_code_location(function_def_idx, pc).ok()?
+        };
+        let line = self
+            .file(loc.file())?
+            .line_of(loc.span().start().to_usize());
+        Some(SourceLine {
+            file: loc.file().to_string(),
+            line,
+        })
+    }
+
+    /// The text of a source line
+    pub fn text(&mut self, source_line: &SourceLine) -> Option<String> {
+        self.file(&source_line.file)?
+            .text_of(source_line.line)
+            .map(|text| text.to_string())
+    }
+
+    /// The name of the parameter or local at `index` of a function
+    pub fn local_name(
+        &self,
+        module: Option<&ModuleId>,
+        function: &str,
+        index: usize,
+    ) -> Option<String> {
+        let (source_map, function_def_idx) = self.function_map(module, function)?;
+        let function_map = source_map.get_function_source_map(function_def_idx).ok()?;
+        function_map
+            .get_parameter_or_local_name(index as u64)
+            .map(|(name, _)| name)
+    }
+
+    /// The source file at `path`, read on first use. Files which cannot be read are skipped.
+    fn file(&mut self, path: &str) -> Option<&SourceFile> {
+        let source_root = &self.source_root;
+        self.files
+            .entry(path.to_string())
+            .or_insert_with(|| {
+                fs::read_to_string(source_root.join(path))
+                    .ok()
+                    .map(SourceFile::new)
+            })
+            .as_ref()
+    }
+}
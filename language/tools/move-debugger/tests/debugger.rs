@@ -0,0 +1,233 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use libra_temppath::TempPath;
+use libra_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    transaction::ChangeSet,
+    write_set::{WriteOp, WriteSetMut},
+};
+use move_debugger::{
+    debugger::{execute_script, Debugger, State},
+    sources::Sources,
+};
+use move_lang::{compiled_unit::CompiledUnit, shared::Address};
+use std::{
+    fs,
+    io::{self, Cursor, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use transaction_replay::ChangeSetStateView;
+use vm::CompiledModule;
+
+// Locals are used twice, as the compiler eliminates the locals assigned and used once
+const MODULE: &str = "\
+address 0x2 {
+module M {
+    public fun double(x: u64): u64 {
+        let y = x + x;
+        if (y == 0) abort 1;
+        y
+    }
+}
+}
+";
+
+const SCRIPT: &str = "\
+script {
+use 0x2::M;
+fun main() {
+    let a = 1;
+    let b = M::double(a);
+    let c = M::double(b);
+    if (a + b + c != 7) abort 42;
+    if (c != 4) abort 43;
+}
+}
+";
+
+/// The output of the debugger, shared with the test
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Compiles the module and the script with their source maps, and debugs the script with
+/// `commands` as input. Returns the result of the execution and the output of the debugger.
+fn debug(commands: &str) -> (Result<()>, String) {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let module_path = dir.path().join("module.move");
+    let script_path = dir.path().join("script.move");
+    fs::write(&module_path, MODULE).unwrap();
+    fs::write(&script_path, SCRIPT).unwrap();
+
+    let targets = [&module_path, &script_path]
+        .iter()
+        .map(|path| path.to_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    let sender = Address::parse_str("0x2").unwrap();
+    let (_, units) = move_lang::move_compile_no_report(&targets, &[], Some(sender)).unwrap();
+    let mut module_paths = vec![];
+    let mut compiled_script_path = PathBuf::new();
+    let mut write_set = WriteSetMut::new(vec![]);
+    for unit in units.unwrap() {
+        let path = match &unit {
+            CompiledUnit::Module { .. } => dir.path().join(format!("{}.mv", unit.name())),
+            CompiledUnit::Script { .. } => dir.path().join("script.mv"),
+        };
+        fs::write(path.with_extension("mvsm"), unit.serialize_source_map()).unwrap();
+        let is_module = matches!(unit, CompiledUnit::Module { .. });
+        let bytes = unit.serialize();
+        fs::write(&path, &bytes).unwrap();
+        if is_module {
+            let module_id = CompiledModule::deserialize(&bytes).unwrap().self_id();
+            write_set.push((
+                AccessPath::code_access_path(&module_id),
+                WriteOp::Value(bytes),
+            ));
+            module_paths.push(path);
+        } else {
+            compiled_script_path = path;
+        }
+    }
+
+    // The module is the only state the script needs
+    let change_set = ChangeSet::new(write_set.freeze().unwrap(), vec![]);
+    let state = Arc::new(State::Genesis(ChangeSetStateView::new(&change_set)));
+    let sources = Sources::new(PathBuf::from("."), &compiled_script_path, &module_paths).unwrap();
+    let output = Output::default();
+    let debugger = Debugger::new(
+        state.clone(),
+        sources,
+        Box::new(Cursor::new(commands.to_string())),
+        Box::new(output.clone()),
+    );
+    let script = fs::read(&compiled_script_path).unwrap();
+    let sender = AccountAddress::new(sender.to_u8());
+    let result = execute_script(&state, debugger, script, vec![], vec![], sender).map(|_| ());
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    (result, output)
+}
+
+/// Asserts that `output` contains each of `expected`, in order
+fn assert_in_order(output: &str, expected: &[&str]) {
+    let mut rest = output;
+    for text in expected {
+        match rest.find(text) {
+            Some(index) => rest = &rest[index + text.len()..],
+            None => panic!("{:?} not found in order in:\n{}", text, output),
+        }
+    }
+}
+
+#[test]
+fn stops_before_first_instruction() {
+    let (result, output) = debug("");
+    result.unwrap();
+    assert_in_order(
+        &output,
+        &["Script::main pc 0", "script.move:4\t    let a = 1;"],
+    );
+}
+
+#[test]
+fn function_breakpoint() {
+    let (result, output) = debug("break double\ncontinue\nlocals\nbt\ncontinue\nlocals\n");
+    result.unwrap();
+    assert_in_order(
+        &output,
+        &[
+            "Breakpoint 0",
+            // First call
+            "Breakpoint 0",
+            "::M::double pc 0",
+            "[0] x: U64(1)",
+            "#0 0x",
+            "::M::double pc 0 (",
+            "module.move:4)",
+            "#1 Script::main pc ",
+            "script.move:5)",
+            // Second call
+            "Breakpoint 0",
+            "::M::double pc 0",
+            "[0] x: U64(2)",
+        ],
+    );
+}
+
+#[test]
+fn line_breakpoint() {
+    let (result, output) = debug("break script.move:6\nbreakpoints\ncontinue\nlocals\n");
+    result.unwrap();
+    assert_in_order(
+        &output,
+        &[
+            "0: script.move:6",
+            "Breakpoint 0",
+            "script.move:6\t    let c = M::double(b);",
+            "b: U64(2)",
+        ],
+    );
+    // The breakpoint is only hit on the first instruction of the line
+    assert_eq!(output.matches("Breakpoint 0").count(), 2);
+}
+
+#[test]
+fn next_steps_over_calls() {
+    let (result, output) = debug("next\nnext\nnext\n");
+    result.unwrap();
+    assert_in_order(
+        &output,
+        &[
+            "script.move:4\t",
+            "script.move:5\t",
+            "script.move:6\t",
+            "script.move:7\t",
+        ],
+    );
+    assert!(!output.contains("::M::double pc"));
+}
+
+#[test]
+fn step_into_and_finish() {
+    let (result, output) = debug("next\nstep\nstepi\nstack\nfinish\nbt\nstack\n");
+    result.unwrap();
+    assert_in_order(
+        &output,
+        &[
+            "script.move:5\t",
+            // Into the call
+            "::M::double pc 0",
+            "module.move:4\t        let y = x + x;",
+            // `x` copied on the stack by the first instruction
+            "::M::double pc 1",
+            "U64(1)\n",
+            // Back in the caller, with the result of the call on the stack
+            "Script::main pc ",
+            "script.move:5\t",
+            "#0 Script::main pc ",
+            "U64(2)\n",
+        ],
+    );
+    assert!(!output.contains("#1 "));
+}
+
+#[test]
+fn quit_aborts_the_execution() {
+    let (result, output) = debug("quit\nlocals\n");
+    assert!(result.unwrap_err().to_string().contains("aborted"));
+    // No commands are read once the execution is aborted
+    assert_eq!(output.matches("(move-debugger) ").count(), 1);
+}
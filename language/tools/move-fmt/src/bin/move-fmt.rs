@@ -0,0 +1,55 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::{bail, Result};
+use move_lang::{errors::report_errors_to_buffer, find_move_filenames};
+use std::{collections::HashMap, fs};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "move-fmt", about = "Format Move source files")]
+struct Args {
+    /// The Move files to format, or directories to search for Move files
+    #[structopt(name = "PATH_TO_SOURCE_FILE")]
+    pub files: Vec<String>,
+    /// List the files which are not formatted instead of formatting them
+    #[structopt(long = "check")]
+    pub check: bool,
+}
+
+fn main() -> Result<()> {
+    let args = Args::from_args();
+    let mut unformatted = 0;
+    let mut failed = 0;
+    for fname in find_move_filenames(&args.files)? {
+        let source = fs::read_to_string(&fname)?;
+        let fname: &'static str = Box::leak(fname.into_boxed_str());
+        match move_fmt::format_source(fname, &source) {
+            Ok(formatted) if formatted == source => (),
+            Ok(formatted) => {
+                unformatted += 1;
+                if args.check {
+                    println!("{}", fname);
+                } else {
+                    fs::write(fname, formatted)?;
+                }
+            }
+            Err(errors) => {
+                failed += 1;
+                let mut files = HashMap::new();
+                files.insert(fname, source);
+                let buffer = report_errors_to_buffer(files, errors);
+                eprint!("{}", String::from_utf8_lossy(&buffer));
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{} file(s) could not be parsed", failed)
+    }
+    if args.check && unformatted > 0 {
+        bail!("{} file(s) are not formatted", unformatted)
+    }
+    Ok(())
+}
@@ -0,0 +1,326 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Documents to be laid out, in the style of Wadler's "A prettier printer".
+//!
+//! A `Group` is laid out on a single line when it fits in the remaining width, and otherwise has
+//! all of its own `Line`s broken. Hard lines and comments never fit on a single line, so they
+//! break every group around them.
+//!
+//! Newlines are not written right away but kept pending until the next text, so that comments
+//! trailing a line can still be appended to it, and so that several requests for line breaks at
+//! the same place collapse into one (or into one blank line).
+
+use std::cmp::max;
+
+pub const INDENT: usize = 4;
+
+#[derive(Clone, Debug)]
+pub enum Doc {
+    Nil,
+    /// Text without newlines
+    Text(String),
+    /// A space, or a newline when the enclosing group is broken
+    Line,
+    /// Nothing, or a newline when the enclosing group is broken
+    SoftLine,
+    HardLine,
+    /// A newline followed by an empty line
+    BlankLine,
+    Comment(Comment),
+    /// Indents the lines broken inside by `INDENT`
+    Nest(Box<Doc>),
+    Group(Box<Doc>),
+    /// The first document when the enclosing group is broken, the second otherwise
+    IfBreak(Box<Doc>, Box<Doc>),
+    Concat(Vec<Doc>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Comment {
+    /// The comment as found in the source, delimiters included
+    pub text: String,
+    /// Follows code on the same line, and stays at the end of that line
+    pub trailing: bool,
+    /// Preceded by an empty line (leading comments only)
+    pub blank_before: bool,
+    /// Followed by a newline: always the case for line comments
+    pub newline_after: bool,
+}
+
+pub fn nil() -> Doc {
+    Doc::Nil
+}
+
+pub fn text(s: impl Into<String>) -> Doc {
+    Doc::Text(s.into())
+}
+
+pub fn line() -> Doc {
+    Doc::Line
+}
+
+pub fn softline() -> Doc {
+    Doc::SoftLine
+}
+
+pub fn hardline() -> Doc {
+    Doc::HardLine
+}
+
+pub fn blankline() -> Doc {
+    Doc::BlankLine
+}
+
+pub fn nest(doc: Doc) -> Doc {
+    Doc::Nest(Box::new(doc))
+}
+
+pub fn group(doc: Doc) -> Doc {
+    Doc::Group(Box::new(doc))
+}
+
+pub fn if_break(broken: Doc, flat: Doc) -> Doc {
+    Doc::IfBreak(Box::new(broken), Box::new(flat))
+}
+
+pub fn concat(docs: Vec<Doc>) -> Doc {
+    Doc::Concat(docs)
+}
+
+/// `docs` separated by `separator`
+pub fn join(docs: Vec<Doc>, separator: Doc) -> Doc {
+    let mut joined = vec![];
+    for (i, doc) in docs.into_iter().enumerate() {
+        if i > 0 {
+            joined.push(separator.clone());
+        }
+        joined.push(doc);
+    }
+    concat(joined)
+}
+
+/// `open`, `docs` separated by commas, the comments `before_close` and `close`: all on one line if
+/// it fits, one item per line with a trailing comma otherwise
+pub fn comma_list(open: &str, docs: Vec<Doc>, before_close: Doc, close: &str) -> Doc {
+    if docs.is_empty() && before_close.is_nil() {
+        return text(format!("{}{}", open, close));
+    }
+    group(concat(vec![
+        text(open),
+        nest(concat(vec![
+            softline(),
+            join(docs, concat(vec![text(","), line()])),
+            if_break(text(","), nil()),
+            before_close,
+        ])),
+        softline(),
+        text(close),
+    ]))
+}
+
+/// Like `comma_list` between braces, with spaces inside them when on one line
+pub fn brace_list(docs: Vec<Doc>, before_close: Doc) -> Doc {
+    if docs.is_empty() && before_close.is_nil() {
+        return text("{}");
+    }
+    group(concat(vec![
+        text("{"),
+        nest(concat(vec![
+            line(),
+            join(docs, concat(vec![text(","), line()])),
+            if_break(text(","), nil()),
+            before_close,
+        ])),
+        line(),
+        text("}"),
+    ]))
+}
+
+impl Doc {
+    pub fn is_nil(&self) -> bool {
+        match self {
+            Doc::Nil => true,
+            Doc::Concat(docs) => docs.iter().all(Doc::is_nil),
+            _ => false,
+        }
+    }
+}
+
+//**************************************************************************************************
+// Rendering
+//**************************************************************************************************
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+struct Renderer {
+    output: String,
+    column: usize,
+    /// Newlines to write before the next text, and the indentation of the line after them
+    pending_newlines: usize,
+    pending_indent: usize,
+}
+
+/// Lays `doc` out in `width` columns
+pub fn render(doc: &Doc, width: usize) -> String {
+    let mut renderer = Renderer {
+        output: String::new(),
+        column: 0,
+        pending_newlines: 0,
+        pending_indent: 0,
+    };
+    let mut stack: Vec<(usize, Mode, &Doc)> = vec![(0, Mode::Break, doc)];
+    while let Some((indent, mode, doc)) = stack.pop() {
+        match doc {
+            Doc::Nil => (),
+            Doc::Text(s) => renderer.text(s),
+            Doc::Line if mode == Mode::Flat => {
+                if renderer.pending_newlines == 0 {
+                    renderer.text(" ")
+                }
+            }
+            Doc::SoftLine if mode == Mode::Flat => (),
+            Doc::Line | Doc::SoftLine | Doc::HardLine => renderer.newlines(1, indent),
+            Doc::BlankLine => renderer.newlines(2, indent),
+            Doc::Comment(comment) => renderer.comment(comment, indent),
+            Doc::Nest(doc) => stack.push((indent + INDENT, mode, doc)),
+            Doc::Group(doc) => {
+                let column = if renderer.pending_newlines > 0 {
+                    renderer.pending_indent
+                } else {
+                    renderer.column
+                };
+                let remaining = width as isize - column as isize;
+                let mode = if fits(remaining, doc, &stack) {
+                    Mode::Flat
+                } else {
+                    Mode::Break
+                };
+                stack.push((indent, mode, doc))
+            }
+            Doc::IfBreak(broken, flat) => {
+                let doc = if mode == Mode::Break { broken } else { flat };
+                stack.push((indent, mode, doc))
+            }
+            Doc::Concat(docs) => {
+                for doc in docs.iter().rev() {
+                    stack.push((indent, mode, doc))
+                }
+            }
+        }
+    }
+    let mut output = renderer.output;
+    output.truncate(output.trim_end().len());
+    output.push('\n');
+    output
+}
+
+/// Whether `doc` fits on a single line in `remaining` columns, along with what follows it up to
+/// the next line break in a broken group
+fn fits(mut remaining: isize, doc: &Doc, rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut rest = rest.iter().rev();
+    // The documents to measure, and whether they come after the group
+    let mut stack: Vec<(Mode, &Doc, bool)> = vec![(Mode::Flat, doc, false)];
+    loop {
+        let (mode, doc, after) = match stack.pop() {
+            Some(next) => next,
+            None => match rest.next() {
+                Some((_, mode, doc)) => (*mode, *doc, true),
+                None => return true,
+            },
+        };
+        match doc {
+            Doc::Nil => (),
+            Doc::Text(s) => {
+                remaining -= s.len() as isize;
+                if remaining < 0 {
+                    return false;
+                }
+            }
+            Doc::Line if mode == Mode::Flat => remaining -= 1,
+            Doc::SoftLine if mode == Mode::Flat => (),
+            Doc::Line | Doc::SoftLine => return true,
+            // The group itself can't be flat, but the next line may start after it
+            Doc::HardLine | Doc::BlankLine | Doc::Comment(_) => return after,
+            Doc::Nest(doc) => stack.push((mode, doc, after)),
+            // What follows the group may still break at its own lines
+            Doc::Group(doc) if after => stack.push((mode, doc, after)),
+            Doc::Group(doc) => stack.push((Mode::Flat, doc, after)),
+            Doc::IfBreak(broken, flat) => {
+                let doc = if mode == Mode::Break { broken } else { flat };
+                stack.push((mode, doc, after))
+            }
+            Doc::Concat(docs) => {
+                for doc in docs.iter().rev() {
+                    stack.push((mode, doc, after))
+                }
+            }
+        }
+    }
+}
+
+impl Renderer {
+    fn newlines(&mut self, count: usize, indent: usize) {
+        self.pending_newlines = max(self.pending_newlines, count);
+        self.pending_indent = indent;
+    }
+
+    fn flush_newlines(&mut self) {
+        if self.pending_newlines == 0 {
+            return;
+        }
+        if !self.output.is_empty() {
+            self.output
+                .truncate(self.output.trim_end_matches(' ').len());
+            for _ in 0..self.pending_newlines {
+                self.output.push('\n');
+            }
+            self.output.push_str(&" ".repeat(self.pending_indent));
+            self.column = self.pending_indent;
+        }
+        self.pending_newlines = 0;
+    }
+
+    fn text(&mut self, s: &str) {
+        self.flush_newlines();
+        self.output.push_str(s);
+        self.column += s.len();
+    }
+
+    fn comment(&mut self, comment: &Comment, indent: usize) {
+        let on_own_line = self.output.is_empty() || self.output.ends_with('\n');
+        if comment.trailing && !on_own_line {
+            // Goes at the end of the current line, before any pending newline
+            self.output
+                .truncate(self.output.trim_end_matches(' ').len());
+            self.output.push(' ');
+        } else {
+            if self.pending_newlines == 0 && !self.current_line_is_blank() {
+                self.newlines(1, indent);
+            }
+            if comment.blank_before {
+                self.newlines(2, indent);
+            }
+            self.flush_newlines();
+        }
+        self.output.push_str(&comment.text);
+        self.column += comment.text.len();
+        if comment.newline_after {
+            if self.pending_newlines == 0 {
+                self.newlines(1, indent);
+            }
+        } else {
+            self.output.push(' ');
+            self.column += 1;
+        }
+    }
+
+    fn current_line_is_blank(&self) -> bool {
+        let line_start = self.output.rfind('\n').map_or(0, |i| i + 1);
+        self.output[line_start..].trim().is_empty()
+    }
+}
@@ -0,0 +1,34 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! A formatter for Move source files.
+//!
+//! A file is parsed with `move-lang`'s parser and printed back in a canonical layout: four spaces
+//! of indentation, lines of at most `MAX_WIDTH` columns, one member per line in modules and
+//! blocks, and lists broken one item per line when they don't fit. Comments, including
+//! documentation comments and comments in specification blocks, are kept where they were, as are
+//! parentheses and single empty lines between members.
+//!
+//! Formatting is idempotent: formatting a formatted file leaves it unchanged.
+
+mod doc;
+mod printer;
+
+use move_lang::{errors::Errors, parser::syntax::parse_file_string, strip_comments_and_collect};
+
+/// The width past which lists are broken over several lines
+pub const MAX_WIDTH: usize = 100;
+
+/// Formats the source of the Move file `fname`, failing when it doesn't parse
+pub fn format_source(fname: &'static str, source: &str) -> Result<String, Errors> {
+    let (stripped, comment_map, comments) = strip_comments_and_collect(fname, source)?;
+    let (defs, _) = parse_file_string(fname, &stripped, comment_map)?;
+    let comments = comments
+        .iter()
+        .map(|span| (span.start().to_usize(), span.end().to_usize()))
+        .collect();
+    let doc = printer::Printer::new(source, &stripped, comments).file(&defs);
+    Ok(doc::render(&doc, MAX_WIDTH))
+}
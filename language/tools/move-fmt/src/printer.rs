@@ -0,0 +1,958 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Translation of a parsed file to a `Doc`.
+//!
+//! The parser AST doesn't keep everything there is to print, so the source is consulted as well:
+//! - literals and addresses are printed as written, from their location in the source
+//! - parentheses are not in the AST, but an expression was in parentheses when its location
+//!   starts before the expressions it is made of, or with a `(` otherwise. They are all kept.
+//! - comments are not in the AST either. A comment is printed before the first node which starts
+//!   after it, on its own line, or at the end of the previous line if it was there in the source.
+//! - empty lines before the members of modules and blocks are kept, up to one
+
+use crate::doc::*;
+use move_ir_types::{location::*, sp};
+use move_lang::{
+    parser::ast::*,
+    shared::{Identifier, Name},
+};
+
+pub struct Printer<'a> {
+    source: &'a str,
+    /// The source with comments replaced by spaces
+    stripped: &'a str,
+    /// The spans of the comments, in order
+    comments: Vec<(usize, usize)>,
+    next_comment: usize,
+    /// Nothing was printed yet in the innermost block
+    at_block_start: bool,
+}
+
+/// A member of a module, script or block, printed on its own lines
+enum Item<'a> {
+    Use(&'a Use),
+    Constant(&'a Constant),
+    Struct(&'a StructDefinition),
+    Field(&'a Field, &'a Type),
+    Function(&'a Function),
+    Spec(&'a SpecBlock),
+    SpecMember(&'a SpecBlockMember),
+    Statement(&'a SequenceItem),
+    /// The expression ending a sequence, without `;`
+    Result(&'a Exp),
+}
+
+fn start(loc: Loc) -> usize {
+    loc.span().start().to_usize()
+}
+
+fn end(loc: Loc) -> usize {
+    loc.span().end().to_usize()
+}
+
+fn is_block(e: &Exp) -> bool {
+    matches!(e.value, Exp_::Block(_))
+}
+
+/// Binary operators of the same precedence are printed as a single chain
+fn precedence(op: &BinOp_) -> u32 {
+    use BinOp_ as B;
+    match op {
+        B::Implies => 2,
+        B::Or => 3,
+        B::And => 4,
+        B::Eq | B::Neq | B::Lt | B::Gt | B::Le | B::Ge => 5,
+        B::Range => 6,
+        B::BitOr => 7,
+        B::Xor => 8,
+        B::BitAnd => 9,
+        B::Shl | B::Shr => 10,
+        B::Add | B::Sub => 11,
+        B::Mul | B::Div | B::Mod => 12,
+    }
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(source: &'a str, stripped: &'a str, comments: Vec<(usize, usize)>) -> Self {
+        Self {
+            source,
+            stripped,
+            comments,
+            next_comment: 0,
+            at_block_start: true,
+        }
+    }
+
+    //**********************************************************************************************
+    // Source
+    //**********************************************************************************************
+
+    /// The code at `loc`, without whitespace
+    fn code(&self, loc: Loc) -> String {
+        self.stripped[start(loc)..end(loc)]
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect()
+    }
+
+    /// The literal at `loc`, without the parentheses around it
+    fn literal(&self, loc: Loc) -> String {
+        let mut literal = self.code(loc);
+        while literal.starts_with('(') && literal.ends_with(')') {
+            literal = literal[1..literal.len() - 1].to_string();
+        }
+        literal
+    }
+
+    /// The position of the keyword ending right before `pos`, spaces aside
+    fn keyword_before(&self, pos: usize, keyword: &str) -> usize {
+        let code = self.stripped[..pos].trim_end();
+        debug_assert!(code.ends_with(keyword));
+        code.len() - keyword.len()
+    }
+
+    /// The position of the first `c` from `pos` on
+    fn find(&self, pos: usize, c: char) -> usize {
+        pos + self.stripped[pos..].find(c).unwrap_or(0)
+    }
+
+    fn newlines_before(&self, pos: usize) -> usize {
+        self.source[..pos]
+            .chars()
+            .rev()
+            .take_while(|c| c.is_whitespace())
+            .filter(|c| *c == '\n')
+            .count()
+    }
+
+    fn parenthesized(&self, e: &Exp) -> bool {
+        use Exp_ as E;
+        match &e.value {
+            E::Unit | E::ExpList(_) | E::Cast(..) | E::Annotate(..) => false,
+            E::BinopExp(lhs, _, _) | E::Dot(lhs, _) | E::Index(lhs, _) | E::Assign(lhs, _) => {
+                start(e.loc) < start(lhs.loc)
+            }
+            _ => self.stripped[start(e.loc)..].starts_with('('),
+        }
+    }
+
+    //**********************************************************************************************
+    // Comments and empty lines
+    //**********************************************************************************************
+
+    /// The comments not printed yet which start before `pos`
+    fn comments_before(&mut self, pos: usize) -> Doc {
+        let mut docs = vec![];
+        while let Some((comment_start, comment_end)) = self.comments.get(self.next_comment) {
+            let (comment_start, comment_end) = (*comment_start, *comment_end);
+            if comment_start >= pos {
+                break;
+            }
+            self.next_comment += 1;
+
+            let text = self.source[comment_start..comment_end].trim_end();
+            let line_start = self.source[..comment_start]
+                .rfind('\n')
+                .map_or(0, |i| i + 1);
+            let trailing = !self.source[line_start..comment_start].trim().is_empty();
+            let line_end = self.source[comment_end..]
+                .find('\n')
+                .map_or(self.source.len(), |i| comment_end + i);
+            let newline_after =
+                text.starts_with("//") || self.source[comment_end..line_end].trim().is_empty();
+            let blank_before =
+                !trailing && !self.at_block_start && self.newlines_before(comment_start) > 1;
+            self.at_block_start = false;
+            docs.push(Doc::Comment(Comment {
+                text: text.to_string(),
+                trailing,
+                blank_before,
+                newline_after,
+            }))
+        }
+        concat(docs)
+    }
+
+    /// An empty line if there is one in the source before the item at `pos`
+    fn blank_before(&mut self, pos: usize) -> Doc {
+        let blank = !self.at_block_start && self.newlines_before(pos) > 1;
+        self.at_block_start = false;
+        if blank {
+            blankline()
+        } else {
+            nil()
+        }
+    }
+
+    //**********************************************************************************************
+    // Definitions
+    //**********************************************************************************************
+
+    pub fn file(&mut self, defs: &[Definition]) -> Doc {
+        let mut docs = vec![];
+        for (i, def) in defs.iter().enumerate() {
+            let def_start = match def {
+                Definition::Module(m) => start(m.loc),
+                Definition::Address(loc, _, _) => self.keyword_before(start(*loc), "address"),
+                Definition::Script(s) => start(s.loc),
+            };
+            if i > 0 {
+                docs.push(blankline());
+            }
+            docs.push(self.comments_before(def_start));
+            docs.push(self.blank_before(def_start));
+            docs.push(self.definition(def));
+        }
+        docs.push(self.comments_before(self.source.len()));
+        concat(docs)
+    }
+
+    fn definition(&mut self, def: &Definition) -> Doc {
+        match def {
+            Definition::Module(m) => self.module(m),
+            Definition::Address(loc, _, modules) => {
+                // Modules are not indented in address blocks, and set apart by empty lines
+                let mut docs = vec![text(format!("address {} {{", self.code(*loc))), blankline()];
+                self.at_block_start = true;
+                for (i, m) in modules.iter().enumerate() {
+                    if i > 0 {
+                        docs.push(blankline());
+                    }
+                    docs.push(self.comments_before(start(m.loc)));
+                    docs.push(self.blank_before(start(m.loc)));
+                    docs.push(self.module(m));
+                }
+                let body_start = match modules.last() {
+                    Some(m) => end(m.loc),
+                    None => self.find(end(*loc), '{') + 1,
+                };
+                let close = self.find(body_start, '}');
+                docs.push(self.comments_before(close));
+                docs.push(blankline());
+                docs.push(text("}"));
+                self.at_block_start = false;
+                concat(docs)
+            }
+            Definition::Script(s) => {
+                let mut items = vec![];
+                items.extend(s.uses.iter().map(Item::Use));
+                items.extend(s.constants.iter().map(Item::Constant));
+                items.push(Item::Function(&s.function));
+                items.extend(s.specs.iter().map(Item::Spec));
+                let block = self.block(items, end(s.loc) - 1, true);
+                concat(vec![text("script "), block])
+            }
+        }
+    }
+
+    fn module(&mut self, m: &ModuleDefinition) -> Doc {
+        let items = m
+            .members
+            .iter()
+            .map(|member| match member {
+                ModuleMember::Use(u) => Item::Use(u),
+                ModuleMember::Constant(c) => Item::Constant(c),
+                ModuleMember::Struct(s) => Item::Struct(s),
+                ModuleMember::Function(f) => Item::Function(f),
+                ModuleMember::Spec(s) => Item::Spec(s),
+            })
+            .collect();
+        let block = self.block(items, end(m.loc) - 1, true);
+        concat(vec![text(format!("module {} ", m.name)), block])
+    }
+
+    /// `items` between braces, one per line if `force_break` or if they don't fit on one. `close`
+    /// is the position of the closing brace.
+    fn block(&mut self, items: Vec<Item>, close: usize, force_break: bool) -> Doc {
+        let separator = if force_break { hardline() } else { line() };
+        self.at_block_start = true;
+        let mut docs = vec![];
+        for item in &items {
+            let item_start = self.item_start(item);
+            docs.push(separator.clone());
+            docs.push(self.comments_before(item_start));
+            docs.push(self.blank_before(item_start));
+            docs.push(self.item(item));
+        }
+        docs.push(self.comments_before(close));
+        self.at_block_start = false;
+        if docs.iter().all(Doc::is_nil) {
+            return text("{}");
+        }
+        group(concat(vec![
+            text("{"),
+            nest(concat(docs)),
+            separator,
+            text("}"),
+        ]))
+    }
+
+    fn item_start(&self, item: &Item) -> usize {
+        match item {
+            Item::Use(u) => {
+                let ident = match u {
+                    Use::Module(ident, _) | Use::Members(ident, _) => ident,
+                };
+                self.keyword_before(start(ident.loc()), "use")
+            }
+            Item::Constant(c) => start(c.loc),
+            Item::Struct(s) => start(s.loc),
+            Item::Field(f, _) => start(f.loc()),
+            Item::Function(f) => start(f.loc),
+            Item::Spec(s) => start(s.loc),
+            Item::SpecMember(m) => start(m.loc),
+            Item::Statement(s) => start(s.loc),
+            Item::Result(e) => start(e.loc),
+        }
+    }
+
+    fn item(&mut self, item: &Item) -> Doc {
+        match item {
+            Item::Use(u) => concat(vec![self.use_(u), text(";")]),
+            Item::Constant(c) => self.constant(c),
+            Item::Struct(s) => self.struct_(s),
+            Item::Field(f, ty) => concat(vec![text(format!("{}: ", f)), self.type_(ty), text(",")]),
+            Item::Function(f) => self.function(f),
+            Item::Spec(s) => self.spec_block(s),
+            Item::SpecMember(m) => self.spec_member(m),
+            Item::Statement(s) => concat(vec![self.sequence_item(s), text(";")]),
+            Item::Result(e) => self.exp(e),
+        }
+    }
+
+    fn use_(&mut self, u: &Use) -> Doc {
+        match u {
+            Use::Module(ident, alias) => {
+                let alias = match alias {
+                    Some(alias) => format!(" as {}", alias),
+                    None => "".to_string(),
+                };
+                text(format!("use {}{}", self.code(ident.loc()), alias))
+            }
+            Use::Members(ident, members) => {
+                let mut members: Vec<Doc> = members
+                    .iter()
+                    .map(|(member, alias)| match alias {
+                        Some(alias) => text(format!("{} as {}", member, alias)),
+                        None => text(&member.value),
+                    })
+                    .collect();
+                let members = if members.len() == 1 {
+                    members.pop().unwrap()
+                } else {
+                    comma_list("{", members, nil(), "}")
+                };
+                concat(vec![
+                    text(format!("use {}::", self.code(ident.loc()))),
+                    members,
+                ])
+            }
+        }
+    }
+
+    fn constant(&mut self, c: &Constant) -> Doc {
+        concat(vec![
+            text(format!("const {}: ", c.name)),
+            self.type_(&c.signature),
+            text(" = "),
+            self.exp(&c.value),
+            text(";"),
+        ])
+    }
+
+    fn struct_(&mut self, s: &StructDefinition) -> Doc {
+        let mut docs = vec![];
+        if let StructFields::Native(_) = s.fields {
+            docs.push(text("native "));
+        }
+        if s.resource_opt.is_some() {
+            docs.push(text("resource "));
+        }
+        docs.push(text(format!("struct {}", s.name)));
+        docs.push(self.type_parameters(&s.type_parameters));
+        match &s.fields {
+            StructFields::Native(_) => docs.push(text(";")),
+            StructFields::Defined(fields) => {
+                let items = fields.iter().map(|(f, ty)| Item::Field(f, ty)).collect();
+                docs.push(text(" "));
+                docs.push(self.block(items, end(s.loc) - 1, true));
+            }
+        }
+        concat(docs)
+    }
+
+    fn function(&mut self, f: &Function) -> Doc {
+        let mut docs = vec![];
+        for attribute in &f.attributes {
+            docs.push(self.comments_before(start(attribute.loc)));
+            docs.push(text(match &attribute.value {
+                FunctionAttribute_::Test => "#[test]".to_string(),
+                FunctionAttribute_::ExpectedFailure(None) => "#[expected_failure]".to_string(),
+                FunctionAttribute_::ExpectedFailure(Some(code)) => {
+                    format!("#[expected_failure(abort_code = {})]", code)
+                }
+            }));
+            docs.push(hardline());
+        }
+        if let FunctionBody_::Native = f.body.value {
+            docs.push(text("native "));
+        }
+        if let FunctionVisibility::Public(_) = f.visibility {
+            docs.push(text("public "));
+        }
+        docs.push(text(format!("fun {}", f.name)));
+        docs.push(self.signature(&f.signature, /* spec */ false));
+        if !f.acquires.is_empty() {
+            let acquires = f
+                .acquires
+                .iter()
+                .map(|access| self.module_access(access))
+                .collect();
+            docs.push(group(nest(concat(vec![
+                line(),
+                text("acquires "),
+                join(acquires, text(", ")),
+            ]))));
+        }
+        match &f.body.value {
+            FunctionBody_::Native => docs.push(text(";")),
+            FunctionBody_::Defined(seq) => {
+                docs.push(text(" "));
+                docs.push(self.sequence(seq, end(f.body.loc) - 1, true));
+            }
+        }
+        concat(docs)
+    }
+
+    /// The return type is omitted when it is `()`, except in specification functions
+    fn signature(&mut self, signature: &FunctionSignature, spec: bool) -> Doc {
+        let type_parameters = self.type_parameters(&signature.type_parameters);
+        let parameters = signature
+            .parameters
+            .iter()
+            .map(|(var, ty)| concat(vec![text(format!("{}: ", var)), self.type_(ty)]))
+            .collect();
+        let return_type = match &signature.return_type.value {
+            Type_::Unit if !spec => nil(),
+            _ => concat(vec![text(": "), self.type_(&signature.return_type)]),
+        };
+        concat(vec![
+            type_parameters,
+            comma_list("(", parameters, nil(), ")"),
+            return_type,
+        ])
+    }
+
+    fn type_parameters(&mut self, type_parameters: &[(Name, Kind)]) -> Doc {
+        if type_parameters.is_empty() {
+            return nil();
+        }
+        let type_parameters = type_parameters
+            .iter()
+            .map(|(name, kind)| {
+                text(match kind.value {
+                    Kind_::Unknown => name.value.clone(),
+                    Kind_::Resource => format!("{}: resource", name),
+                    Kind_::Affine | Kind_::Copyable => format!("{}: copyable", name),
+                })
+            })
+            .collect();
+        concat(vec![
+            text("<"),
+            join(type_parameters, text(", ")),
+            text(">"),
+        ])
+    }
+
+    //**********************************************************************************************
+    // Specifications
+    //**********************************************************************************************
+
+    fn spec_block(&mut self, sb: &SpecBlock) -> Doc {
+        let target = match &sb.value.target.value {
+            SpecBlockTarget_::Code => "".to_string(),
+            SpecBlockTarget_::Module => " module".to_string(),
+            SpecBlockTarget_::Function(name) => format!(" fun {}", name),
+            SpecBlockTarget_::Structure(name) => format!(" struct {}", name),
+            SpecBlockTarget_::Schema(name, _) => format!(" schema {}", name),
+        };
+        let type_parameters = match &sb.value.target.value {
+            SpecBlockTarget_::Schema(_, type_parameters) => self.type_parameters(type_parameters),
+            _ => nil(),
+        };
+        let mut items = vec![];
+        items.extend(sb.value.uses.iter().map(Item::Use));
+        items.extend(sb.value.members.iter().map(Item::SpecMember));
+        concat(vec![
+            text(format!("spec{}", target)),
+            type_parameters,
+            text(" "),
+            self.block(items, end(sb.loc) - 1, true),
+        ])
+    }
+
+    fn spec_member(&mut self, member: &SpecBlockMember) -> Doc {
+        use SpecBlockMember_ as M;
+        match &member.value {
+            M::Condition { kind, exp } => {
+                let kind = match kind {
+                    SpecConditionKind::Assert => "assert",
+                    SpecConditionKind::Assume => "assume",
+                    SpecConditionKind::Decreases => "decreases",
+                    SpecConditionKind::AbortsIf => "aborts_if",
+                    SpecConditionKind::Ensures => "ensures",
+                    SpecConditionKind::Requires => "requires",
+                    SpecConditionKind::RequiresModule => "requires module",
+                    SpecConditionKind::Invariant => "invariant",
+                    SpecConditionKind::InvariantUpdate => "invariant update",
+                    SpecConditionKind::InvariantPack => "invariant pack",
+                    SpecConditionKind::InvariantUnpack => "invariant unpack",
+                    SpecConditionKind::InvariantModule => "invariant module",
+                };
+                concat(vec![text(format!("{} ", kind)), self.exp(exp), text(";")])
+            }
+            M::Function {
+                name,
+                signature,
+                body,
+            } => {
+                let mut docs = vec![];
+                if let FunctionBody_::Native = body.value {
+                    docs.push(text("native "));
+                }
+                docs.push(text(format!("define {}", name)));
+                docs.push(self.signature(signature, /* spec */ true));
+                match &body.value {
+                    FunctionBody_::Native => docs.push(text(";")),
+                    FunctionBody_::Defined(seq) => {
+                        docs.push(text(" "));
+                        docs.push(self.sequence(seq, end(body.loc) - 1, false));
+                    }
+                }
+                concat(docs)
+            }
+            M::Variable {
+                is_global,
+                name,
+                type_parameters,
+                type_,
+            } => {
+                // `local` is optional, but needed for names which are also keywords of members
+                let scope = if *is_global {
+                    "global "
+                } else if self.stripped[start(member.loc)..start(name.loc)].contains("local") {
+                    "local "
+                } else {
+                    ""
+                };
+                concat(vec![
+                    text(format!("{}{}", scope, name)),
+                    self.type_parameters(type_parameters),
+                    text(": "),
+                    self.type_(type_),
+                    text(";"),
+                ])
+            }
+            M::Include { exp } => concat(vec![text("include "), self.exp(exp), text(";")]),
+            M::Apply {
+                exp,
+                patterns,
+                exclusion_patterns,
+            } => {
+                let mut docs = vec![line(), text("to ")];
+                let patterns = patterns.iter().map(|p| self.apply_pattern(p)).collect();
+                docs.push(join(patterns, concat(vec![text(","), line()])));
+                if !exclusion_patterns.is_empty() {
+                    let exclusion_patterns = exclusion_patterns
+                        .iter()
+                        .map(|p| self.apply_pattern(p))
+                        .collect();
+                    docs.push(line());
+                    docs.push(text("except "));
+                    docs.push(join(exclusion_patterns, concat(vec![text(","), line()])));
+                }
+                docs.push(text(";"));
+                group(concat(vec![
+                    text("apply "),
+                    self.exp(exp),
+                    nest(concat(docs)),
+                ]))
+            }
+            M::Pragma { properties } => {
+                let properties = properties
+                    .iter()
+                    .map(|property| {
+                        let value = match &property.value.value {
+                            // A number without suffix is located along with the property name
+                            Some(value) => {
+                                let literal = self.literal(value.loc);
+                                let literal = match literal.find('=') {
+                                    Some(i) if start(value.loc) == start(property.loc) => {
+                                        literal[i + 1..].to_string()
+                                    }
+                                    _ => literal,
+                                };
+                                format!(" = {}", literal)
+                            }
+                            None => "".to_string(),
+                        };
+                        text(format!("{}{}", property.value.name, value))
+                    })
+                    .collect();
+                concat(vec![
+                    text("pragma "),
+                    join(properties, text(", ")),
+                    text(";"),
+                ])
+            }
+        }
+    }
+
+    fn apply_pattern(&mut self, pattern: &SpecApplyPattern) -> Doc {
+        let visibility = match pattern.value.visibility {
+            Some(FunctionVisibility::Public(_)) => "public ",
+            Some(FunctionVisibility::Internal) => "internal ",
+            None => "",
+        };
+        let name: String = pattern
+            .value
+            .name_pattern
+            .iter()
+            .map(|fragment| match &fragment.value {
+                SpecApplyFragment_::Wildcard => "*",
+                SpecApplyFragment_::NamePart(name) => name.value.as_str(),
+            })
+            .collect();
+        concat(vec![
+            text(format!("{}{}", visibility, name)),
+            self.type_parameters(&pattern.value.type_parameters),
+        ])
+    }
+
+    //**********************************************************************************************
+    // Types and names
+    //**********************************************************************************************
+
+    fn module_access(&mut self, access: &ModuleAccess) -> Doc {
+        let comments = self.comments_before(start(access.loc));
+        let access = match &access.value {
+            ModuleAccess_::Name(n) => n.value.clone(),
+            ModuleAccess_::Global(n) => format!("::{}", n),
+            ModuleAccess_::ModuleAccess(m, n) => format!("{}::{}", m, n),
+            ModuleAccess_::QualifiedModuleAccess(ident, n) => {
+                format!("{}::{}", self.code(ident.loc()), n)
+            }
+        };
+        concat(vec![comments, text(access)])
+    }
+
+    fn type_(&mut self, ty: &Type) -> Doc {
+        let comments = self.comments_before(start(ty.loc));
+        let doc = match &ty.value {
+            Type_::Apply(access, type_arguments) => concat(vec![
+                self.module_access(access),
+                self.type_arguments(type_arguments),
+            ]),
+            Type_::Ref(mutable, inner) => {
+                let prefix = match (mutable, &inner.value) {
+                    (true, _) => "&mut ",
+                    // `&&` is the boolean operator
+                    (false, Type_::Ref(..)) => "& ",
+                    (false, _) => "&",
+                };
+                concat(vec![text(prefix), self.type_(inner)])
+            }
+            Type_::Fun(parameters, result) => {
+                let parameters = parameters.iter().map(|t| self.type_(t)).collect();
+                concat(vec![
+                    comma_list("|", parameters, nil(), "|"),
+                    text(" "),
+                    self.type_(result),
+                ])
+            }
+            Type_::Unit => text("()"),
+            Type_::Multiple(types) => {
+                let types = types.iter().map(|t| self.type_(t)).collect();
+                comma_list("(", types, nil(), ")")
+            }
+        };
+        concat(vec![comments, doc])
+    }
+
+    fn type_arguments(&mut self, type_arguments: &[Type]) -> Doc {
+        if type_arguments.is_empty() {
+            return nil();
+        }
+        let type_arguments = type_arguments.iter().map(|t| self.type_(t)).collect();
+        concat(vec![text("<"), join(type_arguments, text(", ")), text(">")])
+    }
+
+    fn optional_type_arguments(&mut self, type_arguments: &Option<Vec<Type>>) -> Doc {
+        match type_arguments {
+            // Explicitly empty type arguments are kept
+            Some(type_arguments) if type_arguments.is_empty() => text("<>"),
+            Some(type_arguments) => self.type_arguments(type_arguments),
+            None => nil(),
+        }
+    }
+
+    fn bind(&mut self, bind: &Bind) -> Doc {
+        let comments = self.comments_before(start(bind.loc));
+        let doc = match &bind.value {
+            Bind_::Var(v) => text(&v.0.value),
+            Bind_::Unpack(access, type_arguments, fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(field, bind)| match &bind.value {
+                        // Shorthand for a variable named after the field
+                        Bind_::Var(v) if bind.loc == field.loc() => text(&v.0.value),
+                        _ => concat(vec![text(format!("{}: ", field)), self.bind(bind)]),
+                    })
+                    .collect();
+                concat(vec![
+                    self.module_access(access),
+                    self.optional_type_arguments(type_arguments),
+                    text(" "),
+                    brace_list(fields, self.comments_before(end(bind.loc) - 1)),
+                ])
+            }
+        };
+        concat(vec![comments, doc])
+    }
+
+    fn bind_list(&mut self, binds: &BindList) -> Doc {
+        if binds.value.len() == 1 {
+            return self.bind(&binds.value[0]);
+        }
+        let binds = binds.value.iter().map(|b| self.bind(b)).collect();
+        comma_list("(", binds, nil(), ")")
+    }
+
+    //**********************************************************************************************
+    // Expressions
+    //**********************************************************************************************
+
+    /// The block of `seq`. `close` is the position of its closing brace.
+    fn sequence(&mut self, seq: &Sequence, close: usize, force_break: bool) -> Doc {
+        let (items, _, result) = seq;
+        let mut items: Vec<Item> = items.iter().map(Item::Statement).collect();
+        if let Some(result) = result.as_ref() {
+            items.push(Item::Result(result));
+        }
+        self.block(items, close, force_break)
+    }
+
+    fn sequence_item(&mut self, item: &SequenceItem) -> Doc {
+        match &item.value {
+            SequenceItem_::Seq(e) => self.exp(e),
+            SequenceItem_::Declare(binds, ty) => concat(vec![
+                text("let "),
+                self.bind_list(binds),
+                self.annotation(ty),
+            ]),
+            SequenceItem_::Bind(binds, ty, e) => concat(vec![
+                text("let "),
+                self.bind_list(binds),
+                self.annotation(ty),
+                text(" = "),
+                self.exp(e),
+            ]),
+        }
+    }
+
+    fn annotation(&mut self, ty: &Option<Type>) -> Doc {
+        match ty {
+            Some(ty) => concat(vec![text(": "), self.type_(ty)]),
+            None => nil(),
+        }
+    }
+
+    fn exp(&mut self, e: &Exp) -> Doc {
+        let comments = self.comments_before(start(e.loc));
+        let doc = self.exp_(e);
+        if self.parenthesized(e) {
+            concat(vec![comments, text("("), doc, text(")")])
+        } else {
+            concat(vec![comments, doc])
+        }
+    }
+
+    fn exp_(&mut self, e: &Exp) -> Doc {
+        use Exp_ as E;
+        match &e.value {
+            E::Value(v) => text(self.literal(v.loc)),
+            E::InferredNum(_) => text(self.literal(e.loc)),
+            E::Move(v) => text(format!("move {}", v)),
+            E::Copy(v) => text(format!("copy {}", v)),
+            E::Name(access, type_arguments) => concat(vec![
+                self.module_access(access),
+                self.optional_type_arguments(type_arguments),
+            ]),
+            E::Call(access, type_arguments, arguments) => {
+                let access = self.module_access(access);
+                let type_arguments = self.optional_type_arguments(type_arguments);
+                let args = arguments.value.iter().map(|a| self.exp(a)).collect();
+                let close_comments = self.comments_before(end(arguments.loc) - 1);
+                concat(vec![
+                    access,
+                    type_arguments,
+                    comma_list("(", args, close_comments, ")"),
+                ])
+            }
+            E::Pack(access, type_arguments, fields) => {
+                let access = self.module_access(access);
+                let type_arguments = self.optional_type_arguments(type_arguments);
+                let fields = fields
+                    .iter()
+                    .map(|(field, e)| match &e.value {
+                        // Shorthand for a variable named after the field
+                        E::Name(sp!(_, ModuleAccess_::Name(_)), None) if e.loc == field.loc() => {
+                            text(&field.0.value)
+                        }
+                        _ => concat(vec![text(format!("{}: ", field)), self.exp(e)]),
+                    })
+                    .collect();
+                let close_comments = self.comments_before(end(e.loc) - 1);
+                concat(vec![
+                    access,
+                    type_arguments,
+                    text(" "),
+                    brace_list(fields, close_comments),
+                ])
+            }
+            E::IfElse(condition, if_true, if_false) => {
+                let condition = concat(vec![text("if ("), self.exp(condition), text(")")]);
+                let if_true_is_block = is_block(if_true) && !self.parenthesized(if_true);
+                let if_true = self.branch(if_true);
+                let if_false = if_false.as_ref().map(|if_false| self.else_branch(if_false));
+                if if_true_is_block {
+                    let mut docs = vec![condition, if_true];
+                    if let Some(if_false) = if_false {
+                        docs.push(text(" else"));
+                        docs.push(group(if_false));
+                    }
+                    concat(docs)
+                } else {
+                    let mut docs = vec![condition, if_true];
+                    if let Some(if_false) = if_false {
+                        docs.push(line());
+                        docs.push(text("else"));
+                        docs.push(if_false);
+                    }
+                    group(concat(docs))
+                }
+            }
+            E::While(condition, body) => {
+                let condition = concat(vec![text("while ("), self.exp(condition), text(")")]);
+                let body = self.branch(body);
+                group(concat(vec![condition, body]))
+            }
+            E::Loop(body) => {
+                let body = self.branch(body);
+                group(concat(vec![text("loop"), body]))
+            }
+            E::Block(seq) => self.sequence(seq, end(e.loc) - 1, false),
+            E::Lambda(binds, body) => {
+                let binds = binds.value.iter().map(|b| self.bind(b)).collect();
+                concat(vec![
+                    comma_list("|", binds, nil(), "|"),
+                    text(" "),
+                    self.exp(body),
+                ])
+            }
+            E::ExpList(es) => {
+                let es = es.iter().map(|e| self.exp(e)).collect();
+                comma_list("(", es, nil(), ")")
+            }
+            E::Unit => text("()"),
+            E::Assign(lhs, rhs) => concat(vec![self.exp(lhs), text(" = "), self.exp(rhs)]),
+            E::Return(None) => text("return"),
+            E::Return(Some(e)) => concat(vec![text("return "), self.exp(e)]),
+            E::Abort(e) => concat(vec![text("abort "), self.exp(e)]),
+            E::Break => text("break"),
+            E::Continue => text("continue"),
+            E::Dereference(e) => concat(vec![text("*"), self.exp(e)]),
+            E::UnaryExp(op, e) => concat(vec![text(op.value.symbol()), self.exp(e)]),
+            E::BinopExp(..) => self.binop_chain(e),
+            E::Borrow(mutable, inner) => {
+                let prefix = match (mutable, &inner.value) {
+                    (true, _) => "&mut ",
+                    // `&&` is the boolean operator
+                    (false, E::Borrow(..)) if !self.parenthesized(inner) => "& ",
+                    (false, _) => "&",
+                };
+                concat(vec![text(prefix), self.exp(inner)])
+            }
+            E::Dot(e, name) => concat(vec![self.exp(e), text(format!(".{}", name))]),
+            E::Index(e, index) => concat(vec![self.exp(e), text("["), self.exp(index), text("]")]),
+            E::Cast(e, ty) => concat(vec![
+                text("("),
+                self.exp(e),
+                text(" as "),
+                self.type_(ty),
+                text(")"),
+            ]),
+            E::Annotate(e, ty) => concat(vec![
+                text("("),
+                self.exp(e),
+                text(": "),
+                self.type_(ty),
+                text(")"),
+            ]),
+            E::Spec(sb) => self.spec_block(sb),
+            E::UnresolvedError => unreachable!("parsing succeeded"),
+        }
+    }
+
+    /// The body of an `if`, `while` or `loop`, following the keyword or condition
+    fn branch(&mut self, e: &Exp) -> Doc {
+        if is_block(e) && !self.parenthesized(e) {
+            let comments = self.comments_before(start(e.loc));
+            match &e.value {
+                Exp_::Block(seq) => {
+                    let block = self.sequence(seq, end(e.loc) - 1, true);
+                    concat(vec![comments, text(" "), block])
+                }
+                _ => unreachable!(),
+            }
+        } else {
+            nest(concat(vec![line(), self.exp(e)]))
+        }
+    }
+
+    /// The body of an `else`, following the keyword. `else if` chains stay on the same line.
+    fn else_branch(&mut self, e: &Exp) -> Doc {
+        match &e.value {
+            Exp_::IfElse(..) if !self.parenthesized(e) => concat(vec![text(" "), self.exp(e)]),
+            _ => self.branch(e),
+        }
+    }
+
+    /// A chain of binary operators of the same precedence, broken before the operators
+    fn binop_chain(&mut self, e: &Exp) -> Doc {
+        let prec = match &e.value {
+            Exp_::BinopExp(_, op, _) => precedence(&op.value),
+            _ => unreachable!(),
+        };
+        let mut operations = vec![];
+        let mut first = e;
+        while let Exp_::BinopExp(lhs, op, rhs) = &first.value {
+            if precedence(&op.value) != prec
+                || (first as *const Exp != e && self.parenthesized(first))
+            {
+                break;
+            }
+            operations.push((op, rhs));
+            first = lhs;
+        }
+        let mut docs = vec![self.exp(first)];
+        for (op, rhs) in operations.into_iter().rev() {
+            docs.push(line());
+            docs.push(self.comments_before(start(op.loc)));
+            docs.push(text(format!("{} ", op.value.symbol())));
+            docs.push(self.exp(rhs));
+        }
+        group(concat(vec![docs.remove(0), nest(concat(docs))]))
+    }
+}
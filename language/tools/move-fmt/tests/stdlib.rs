@@ -0,0 +1,109 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_fmt::format_source;
+use move_lang::{
+    find_move_filenames, parser::syntax::parse_file_string, shared::ast_debug,
+    strip_comments_and_collect,
+};
+use std::fs;
+
+const STDLIB_DIRS: &[&str] = &["../../stdlib/modules", "../../stdlib/transaction_scripts"];
+
+fn stdlib_files() -> Vec<(&'static str, String)> {
+    let dirs: Vec<String> = STDLIB_DIRS.iter().map(|dir| dir.to_string()).collect();
+    let files = find_move_filenames(&dirs).unwrap();
+    assert!(!files.is_empty());
+    files
+        .into_iter()
+        .map(|fname| {
+            let source = fs::read_to_string(&fname).unwrap();
+            let fname: &'static str = Box::leak(fname.into_boxed_str());
+            (fname, source)
+        })
+        .collect()
+}
+
+fn format(fname: &'static str, source: &str) -> String {
+    format_source(fname, source)
+        .unwrap_or_else(|errors| panic!("{}: {} error(s)", fname, errors.len()))
+}
+
+/// The definitions of a file, printed without locations
+fn definitions(fname: &'static str, source: &str) -> Vec<String> {
+    let (stripped, comment_map, _) = strip_comments_and_collect(fname, source).unwrap();
+    let (defs, _) = parse_file_string(fname, &stripped, comment_map)
+        .unwrap_or_else(|_| panic!("{}: formatted file does not parse", fname));
+    defs.iter().map(ast_debug::display).collect()
+}
+
+/// The text of the comments of a file
+fn comments(fname: &'static str, source: &str) -> Vec<String> {
+    let (_, _, spans) = strip_comments_and_collect(fname, source).unwrap();
+    spans
+        .iter()
+        .map(|span| {
+            source[span.start().to_usize()..span.end().to_usize()]
+                .trim()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn idempotent() {
+    for (fname, source) in stdlib_files() {
+        let formatted = format(fname, &source);
+        assert_eq!(
+            format(fname, &formatted),
+            formatted,
+            "{}: formatting is not idempotent",
+            fname
+        );
+    }
+}
+
+#[test]
+fn preserves_definitions() {
+    for (fname, source) in stdlib_files() {
+        let formatted = format(fname, &source);
+        assert_eq!(
+            definitions(fname, &formatted),
+            definitions(fname, &source),
+            "{}",
+            fname
+        );
+    }
+}
+
+#[test]
+fn preserves_comments() {
+    for (fname, source) in stdlib_files() {
+        let formatted = format(fname, &source);
+        assert_eq!(
+            comments(fname, &formatted),
+            comments(fname, &source),
+            "{}",
+            fname
+        );
+    }
+}
+
+#[test]
+fn fits_in_width() {
+    for (fname, source) in stdlib_files() {
+        let formatted = format(fname, &source);
+        for line in formatted.lines() {
+            // Comments and long literals are left as they are
+            if line.contains("//") || line.contains("/*") || line.contains("x\"") {
+                continue;
+            }
+            assert!(
+                line.len() <= move_fmt::MAX_WIDTH,
+                "{}: line too long: {}",
+                fname,
+                line
+            );
+        }
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::Result;
+use move_lang::{command_line as cli, shared::Address};
+use move_lsp::Server;
+use std::{
+    io::{self, BufReader},
+    time::Duration,
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "move-lsp",
+    about = "Language server for Move sources, on stdin and stdout"
+)]
+struct Args {
+    /// The library files needed as dependencies, e.g. the standard library
+    #[structopt(
+        name = "PATH_TO_DEPENDENCY_FILE",
+        short = cli::DEPENDENCY_SHORT,
+        long = cli::DEPENDENCY,
+    )]
+    pub dependencies: Vec<String>,
+    /// The sender address for modules and scripts
+    #[structopt(
+        name = "ADDRESS",
+        short = cli::SENDER_SHORT,
+        long = cli::SENDER,
+        parse(try_from_str = cli::parse_address)
+    )]
+    pub sender: Option<Address>,
+    /// The time without messages after a change before checking the documents, in milliseconds
+    #[structopt(long = "check-delay-ms", default_value = "200")]
+    pub check_delay_ms: u64,
+}
+
+fn main() -> Result<()> {
+    let args = Args::from_args();
+    let stdout = io::stdout();
+    Server::new(&args.dependencies, args.sender)?
+        .with_check_delay(Duration::from_millis(args.check_delay_ms))
+        .run(BufReader::new(io::stdin()), stdout.lock())
+}
@@ -0,0 +1,135 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Completion of module members after `M::`.
+//!
+//! A document being edited rarely parses, so the module names in scope are read from its `use`
+//! declarations in the text, and the members from the last program which could be typed.
+
+use crate::symbols::{MemberKind, ModuleKey, Symbols};
+use move_lang::shared::{Address, ADDRESS_LENGTH};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Completion item kinds of the protocol
+const FUNCTION: u64 = 3;
+const MODULE: u64 = 9;
+const CONSTANT: u64 = 21;
+const STRUCT: u64 = 22;
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// The offset of the identifier which ends `s`
+fn identifier_start(s: &str) -> usize {
+    s.char_indices()
+        .rev()
+        .find(|(_, c)| !is_identifier_char(*c))
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+/// `Address::parse_str` expects a well-formed address
+fn parse_address(s: &str) -> Option<Address> {
+    if !s.starts_with("0x") {
+        return None;
+    }
+    let digits = &s[2..];
+    if digits.is_empty()
+        || digits.len() > 2 * ADDRESS_LENGTH
+        || !digits.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    Address::parse_str(s).ok()
+}
+
+/// The modules named by the `use` declarations of `source`, by alias
+pub fn module_aliases(source: &str) -> BTreeMap<String, ModuleKey> {
+    let mut aliases = BTreeMap::new();
+    let code = source
+        .lines()
+        .map(|line| line.splitn(2, "//").next().unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n");
+    for declaration in code.split(';') {
+        let words = declaration.split_whitespace().collect::<Vec<_>>();
+        let use_idx = match words.iter().rposition(|word| *word == "use") {
+            Some(idx) => idx,
+            None => continue,
+        };
+        let (path, alias) = match &words[use_idx + 1..] {
+            [path] => (*path, None),
+            [path, "as", alias] => (*path, Some(*alias)),
+            _ => continue,
+        };
+        let mut parts = path.split("::");
+        let (address, module) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(address), Some(module), None) => (address, module),
+            _ => continue,
+        };
+        if let Some(address) = parse_address(address) {
+            let alias = alias.unwrap_or(module).to_string();
+            aliases.insert(alias, (address, module.to_string()));
+        }
+    }
+    aliases
+}
+
+/// The completion items at the byte `offset` in `source`
+pub fn completions(symbols: &Symbols, source: &str, offset: usize) -> Vec<Value> {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let prefix_start = identifier_start(before);
+    let aliases = module_aliases(source);
+    if !before[..prefix_start].ends_with("::") {
+        // Module names
+        return aliases
+            .iter()
+            .map(|(alias, (address, module))| {
+                json!({
+                    "label": alias,
+                    "kind": MODULE,
+                    "detail": format!("module {}::{}", address, module),
+                })
+            })
+            .collect();
+    }
+    let qualifier = &before[..prefix_start - 2];
+    let module_start = identifier_start(qualifier);
+    let module = &qualifier[module_start..];
+    // Either an alias, or a module qualified by its address
+    let key = if qualifier[..module_start].ends_with("::") {
+        let address_part = &qualifier[..module_start - 2];
+        let address_start = identifier_start(address_part);
+        match parse_address(&address_part[address_start..]) {
+            Some(address) => (address, module.to_string()),
+            None => return vec![],
+        }
+    } else {
+        match aliases.get(module) {
+            Some(key) => key.clone(),
+            None => return vec![],
+        }
+    };
+    symbols
+        .members(&key)
+        .iter()
+        .filter(|member| member.is_public)
+        .map(|member| {
+            let kind = match member.kind {
+                MemberKind::Function => FUNCTION,
+                MemberKind::Struct => STRUCT,
+                MemberKind::Constant => CONSTANT,
+            };
+            json!({
+                "label": member.name,
+                "kind": kind,
+                "detail": member.description,
+            })
+        })
+        .collect()
+}
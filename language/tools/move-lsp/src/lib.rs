@@ -0,0 +1,21 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! A language server for Move, speaking the Language Server Protocol on stdin and stdout.
+//!
+//! The open documents are checked with `move-lang` once they stop changing, along with the
+//! dependencies the server was started with, e.g. the standard library, which are parsed once.
+//! The server provides
+//! - diagnostics for the errors of the check
+//! - go-to-definition and hovers for functions, structs, fields, constants and locals (see
+//!   `symbols`)
+//! - completion of the members of modules after `M::` (see `completion`)
+
+pub mod completion;
+pub mod protocol;
+pub mod server;
+pub mod symbols;
+
+pub use server::Server;
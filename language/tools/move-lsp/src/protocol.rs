@@ -0,0 +1,146 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The base protocol of the Language Server Protocol: JSON-RPC messages preceded by a
+//! `Content-Length` header, and the positions of the protocol.
+//!
+//! Positions count lines and UTF-16 code units, while the compiler locations are byte offsets.
+//! Move sources only contain ASCII characters, but a document being edited may contain anything.
+
+use anyhow::{bail, format_err, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, Write};
+
+/// JSON-RPC error codes
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Reads the next message, or `None` at the end of the input
+pub fn read_message(input: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let mut parts = header.splitn(2, ':');
+        let name = parts.next().unwrap_or("");
+        let value = parts.next().unwrap_or("").trim();
+        if name.eq_ignore_ascii_case("Content-Length") {
+            content_length = Some(value.parse::<usize>()?);
+        }
+    }
+    let content_length =
+        content_length.ok_or_else(|| format_err!("Message without a Content-Length header"))?;
+    let mut content = vec![0; content_length];
+    std::io::Read::read_exact(input, &mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> Result<()> {
+    let content = serde_json::to_string(message)?;
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    output.flush()?;
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u64,
+    pub character: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub uri: String,
+    pub range: Range,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentPositionParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TextDocumentIdentifier {
+    pub uri: String,
+}
+
+/// The position of the byte at `offset` in `source`, or of the character containing it
+pub fn position(source: &str, offset: usize) -> Position {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line: before.matches('\n').count() as u64,
+        character: before[line_start..].encode_utf16().count() as u64,
+    }
+}
+
+pub fn range(source: &str, start: usize, end: usize) -> Range {
+    Range {
+        start: position(source, start),
+        end: position(source, end),
+    }
+}
+
+/// The byte offset of `position` in `source`, the end of its line when past it. A position in the
+/// middle of a character is the start of that character.
+pub fn offset(source: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match source[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return source.len(),
+        }
+    }
+    let line = source[line_start..].split('\n').next().unwrap_or("");
+    let mut character = 0;
+    for (i, c) in line.char_indices() {
+        character += c.len_utf16() as u64;
+        if character > position.character {
+            return line_start + i;
+        }
+    }
+    line_start + line.len()
+}
+
+pub fn path_to_uri(path: &str) -> Result<String> {
+    match url::Url::from_file_path(path) {
+        Ok(uri) => Ok(uri.to_string()),
+        Err(()) => bail!("Not an absolute path: {}", path),
+    }
+}
+
+pub fn uri_to_path(uri: &str) -> Result<String> {
+    let path = url::Url::parse(uri)?
+        .to_file_path()
+        .map_err(|()| format_err!("Not a file URI: {}", uri))?;
+    // Paths are canonical so that open documents and dependencies can be matched
+    let path = path.canonicalize().unwrap_or(path);
+    path.to_str()
+        .map(|path| path.to_string())
+        .ok_or_else(|| format_err!("Non-Unicode path: {}", uri))
+}
@@ -0,0 +1,403 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    completion,
+    protocol::{self, Location, TextDocumentPositionParams},
+    symbols::Symbols,
+};
+use anyhow::{Context, Result};
+use move_ir_types::location::Loc;
+use move_lang::{
+    errors::{Errors, FilesSourceText},
+    shared::Address,
+    ParsedDependencies,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{BufRead, Write},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+/// Diagnostic severity and message type of the protocol
+const ERROR: u64 = 1;
+
+/// The default time without messages after a change before checking the documents
+const DEFAULT_CHECK_DELAY: Duration = Duration::from_millis(200);
+
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+impl From<anyhow::Error> for ResponseError {
+    fn from(error: anyhow::Error) -> Self {
+        Self {
+            code: protocol::INTERNAL_ERROR,
+            message: format!("{:#}", error),
+        }
+    }
+}
+
+fn params<T: DeserializeOwned>(params: Value) -> Result<T, ResponseError> {
+    serde_json::from_value(params).map_err(|error| ResponseError {
+        code: protocol::INVALID_PARAMS,
+        message: error.to_string(),
+    })
+}
+
+/// A language server for the open documents, checked along with the dependencies.
+///
+/// The documents are checked again once they stop changing for the check delay, or before
+/// answering a request. Definitions, hovers and completions are answered from the last check
+/// which got as far as typing.
+pub struct Server {
+    /// The dependencies, parsed once
+    deps: ParsedDependencies,
+    sender_opt: Option<Address>,
+    check_delay: Duration,
+    /// The text of the open documents, by canonical path
+    documents: BTreeMap<&'static str, String>,
+    /// Whether the documents changed since the last check
+    pending_check: bool,
+    symbols: Symbols,
+    /// The files with diagnostics, to clear them once fixed
+    diagnosed: BTreeSet<&'static str>,
+    /// The messages to send once the current message is handled
+    outbox: Vec<Value>,
+}
+
+impl Server {
+    pub fn new(deps: &[String], sender_opt: Option<Address>) -> Result<Self> {
+        // Paths are canonical so that open documents and dependencies can be matched
+        let deps = deps
+            .iter()
+            .map(|dep| -> Result<String> {
+                let path = fs::canonicalize(dep).with_context(|| format!("Dependency {}", dep))?;
+                Ok(path.to_string_lossy().into_owned())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            deps: move_lang::parse_dependencies(&deps)?,
+            sender_opt,
+            check_delay: DEFAULT_CHECK_DELAY,
+            documents: BTreeMap::new(),
+            pending_check: false,
+            symbols: Symbols::default(),
+            diagnosed: BTreeSet::new(),
+            outbox: vec![],
+        })
+    }
+
+    /// Sets the time without messages after a change before checking the documents
+    pub fn with_check_delay(mut self, check_delay: Duration) -> Self {
+        self.check_delay = check_delay;
+        self
+    }
+
+    /// Serves the client on `input` and `output` until it exits or closes `input`
+    pub fn run(
+        &mut self,
+        mut input: impl BufRead + Send + 'static,
+        mut output: impl Write,
+    ) -> Result<()> {
+        // Messages are read on their own thread, to check the documents once the client is idle
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            while let Some(message) = protocol::read_message(&mut input).transpose() {
+                let failed = message.is_err();
+                if sender.send(message).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        loop {
+            let message = if self.pending_check {
+                match receiver.recv_timeout(self.check_delay) {
+                    Ok(message) => message?,
+                    Err(RecvTimeoutError::Timeout) => {
+                        self.check_pending();
+                        self.flush(&mut output)?;
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            } else {
+                match receiver.recv() {
+                    Ok(message) => message?,
+                    Err(_) => break,
+                }
+            };
+            let exit = self.handle(message);
+            self.flush(&mut output)?;
+            if exit {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, output: &mut impl Write) -> Result<()> {
+        for message in self.outbox.drain(..) {
+            protocol::write_message(output, &message)?;
+        }
+        Ok(())
+    }
+
+    /// Handles a message, returning whether the client asked to exit
+    fn handle(&mut self, mut message: Value) -> bool {
+        let params = message.get_mut("params").map_or(Value::Null, Value::take);
+        let method = match message["method"].as_str() {
+            Some(method) => method.to_string(),
+            // A response, but no requests are sent to the client
+            None => return false,
+        };
+        match message.get("id") {
+            Some(id) => {
+                // Requests are answered from the current documents
+                self.check_pending();
+                let response = match self.request(&method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err(error) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": error.code, "message": error.message },
+                    }),
+                };
+                self.outbox.push(response)
+            }
+            None if method == "exit" => return true,
+            None => {
+                if let Err(error) = self.notification(&method, params) {
+                    self.log(format!("{}: {:#}", method, error))
+                }
+            }
+        }
+        false
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Result<Value, ResponseError> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    // Full documents are sent on changes
+                    "textDocumentSync": 1,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                    "completionProvider": { "triggerCharacters": [":"] },
+                },
+                "serverInfo": { "name": "move-lsp" },
+            })),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/definition" => {
+                let params: TextDocumentPositionParams = self::params(params)?;
+                let (path, offset) = self.position(&params)?;
+                let location = self
+                    .symbols
+                    .occurrence(path, offset)
+                    .and_then(|occurrence| {
+                        location(self.symbols.files(), occurrence.symbol.definition)
+                    });
+                Ok(json!(location))
+            }
+            "textDocument/hover" => {
+                let params: TextDocumentPositionParams = self::params(params)?;
+                let (path, offset) = self.position(&params)?;
+                let occurrence = match self.symbols.occurrence(path, offset) {
+                    Some(occurrence) => occurrence,
+                    None => return Ok(Value::Null),
+                };
+                let range = self
+                    .documents
+                    .get(path)
+                    .map(|source| protocol::range(source, occurrence.start, occurrence.end));
+                Ok(json!({
+                    "contents": {
+                        "kind": "markdown",
+                        "value": format!("```move\n{}\n```", occurrence.symbol.description),
+                    },
+                    "range": range,
+                }))
+            }
+            "textDocument/completion" => {
+                let params: TextDocumentPositionParams = self::params(params)?;
+                let (path, offset) = self.position(&params)?;
+                let source = self
+                    .documents
+                    .get(path)
+                    .map_or("", |source| source.as_str());
+                Ok(json!(completion::completions(
+                    &self.symbols,
+                    source,
+                    offset
+                )))
+            }
+            _ => Err(ResponseError {
+                code: protocol::METHOD_NOT_FOUND,
+                message: format!("Unsupported method {}", method),
+            }),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: Value) -> Result<()> {
+        match method {
+            "textDocument/didOpen" => {
+                let path = self.path(&params["textDocument"]["uri"])?;
+                let text = params["textDocument"]["text"].as_str().unwrap_or("");
+                self.documents.insert(path, text.to_string());
+                self.pending_check = true;
+                Ok(())
+            }
+            "textDocument/didChange" => {
+                let path = self.path(&params["textDocument"]["uri"])?;
+                // Changes carry the full document
+                let change = params["contentChanges"].as_array().and_then(|c| c.last());
+                if let Some(text) = change.and_then(|change| change["text"].as_str()) {
+                    self.documents.insert(path, text.to_string());
+                    self.pending_check = true;
+                }
+                Ok(())
+            }
+            "textDocument/didClose" => {
+                let path = self.path(&params["textDocument"]["uri"])?;
+                self.documents.remove(path);
+                self.pending_check = true;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    //**********************************************************************************************
+    // Checks
+    //**********************************************************************************************
+
+    /// Checks the open documents if they changed since the last check
+    fn check_pending(&mut self) {
+        if !self.pending_check {
+            return;
+        }
+        self.pending_check = false;
+        if let Err(error) = self.check() {
+            self.log(format!("check: {:#}", error))
+        }
+    }
+
+    /// Checks the open documents, publishing their errors
+    fn check(&mut self) -> Result<()> {
+        let targets = self
+            .documents
+            .iter()
+            .map(|(path, text)| (*path, text.clone()))
+            .collect::<Vec<_>>();
+        let (files, program, mut errors) =
+            move_lang::move_check_sources_to_typing(targets, &self.deps, self.sender_opt);
+        if let Some(program) = program {
+            self.symbols = Symbols::new(files.clone(), &program);
+            if errors.is_empty() {
+                // Errors of the passes after typing, e.g. borrow checking
+                errors = move_lang::move_check_typed_program_no_report(program);
+            }
+        }
+        self.publish_diagnostics(&files, errors)
+    }
+
+    fn publish_diagnostics(&mut self, files: &FilesSourceText, errors: Errors) -> Result<()> {
+        let mut diagnostics: BTreeMap<&'static str, Vec<Value>> = BTreeMap::new();
+        for error in errors {
+            let ((loc, message), notes) = match error.split_first() {
+                Some(split) => split,
+                None => continue,
+            };
+            let source = match files.get(loc.file()) {
+                Some(source) => source,
+                None => continue,
+            };
+            let related = notes
+                .iter()
+                .filter_map(|(loc, message)| {
+                    let location = location(files, *loc)?;
+                    Some(json!({ "location": location, "message": message }))
+                })
+                .collect::<Vec<_>>();
+            let span = loc.span();
+            diagnostics
+                .entry(loc.file())
+                .or_insert_with(Vec::new)
+                .push(json!({
+                    "range": protocol::range(source, span.start().to_usize(), span.end().to_usize()),
+                    "severity": ERROR,
+                    "source": "move",
+                    "message": message,
+                    "relatedInformation": related,
+                }));
+        }
+        // Documents without errors, or whose errors were fixed, get an empty list
+        let paths = self
+            .documents
+            .keys()
+            .chain(self.diagnosed.iter())
+            .chain(diagnostics.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        for path in paths {
+            let file_diagnostics = diagnostics.get(path).cloned().unwrap_or_default();
+            self.outbox.push(json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/publishDiagnostics",
+                "params": {
+                    "uri": protocol::path_to_uri(path)?,
+                    "diagnostics": file_diagnostics,
+                },
+            }));
+        }
+        self.diagnosed = diagnostics.keys().cloned().collect();
+        Ok(())
+    }
+
+    //**********************************************************************************************
+    // Utils
+    //**********************************************************************************************
+
+    fn log(&mut self, message: String) {
+        self.outbox.push(json!({
+            "jsonrpc": "2.0",
+            "method": "window/logMessage",
+            "params": { "type": ERROR, "message": message },
+        }))
+    }
+
+    fn path(&self, uri: &Value) -> Result<&'static str> {
+        let path = protocol::uri_to_path(uri.as_str().unwrap_or(""))?;
+        Ok(move_lang::intern_file_name(&path))
+    }
+
+    /// The path and offset of a position in an open document
+    fn position(
+        &self,
+        params: &TextDocumentPositionParams,
+    ) -> Result<(&'static str, usize), ResponseError> {
+        let path = self.path(&json!(params.text_document.uri))?;
+        let source = self
+            .documents
+            .get(path)
+            .map(|source| source.as_str())
+            .or_else(|| self.symbols.files().get(path).map(|source| source.as_str()))
+            .unwrap_or("");
+        Ok((path, protocol::offset(source, params.position)))
+    }
+}
+
+fn location(files: &FilesSourceText, loc: Loc) -> Option<Location> {
+    let source = files.get(loc.file())?;
+    let span = loc.span();
+    Some(Location {
+        uri: protocol::path_to_uri(loc.file()).ok()?,
+        range: protocol::range(source, span.start().to_usize(), span.end().to_usize()),
+    })
+}
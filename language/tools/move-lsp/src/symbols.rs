@@ -0,0 +1,645 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An index of the symbols of a typed program.
+//!
+//! Every occurrence of a function, struct, field, constant or local in the sources, definitions
+//! included, is recorded with the location of its definition and a description of it for hovers.
+//! The members of every module are recorded for completion.
+//!
+//! Names in the typed program carry the location where they were written, but types which were
+//! inferred carry the location of the expression they were inferred from. So an occurrence is
+//! only recorded where the source has the name.
+
+use move_ir_types::{location::*, sp};
+use move_lang::{
+    errors::FilesSourceText,
+    expansion::ast::Fields,
+    naming::ast::{
+        FunctionSignature, StructDefinition, StructFields, TParam, Type, TypeName_, Type_,
+    },
+    parser::ast::{ConstantName, FunctionVisibility, Kind_, ModuleIdent, StructName, Var},
+    shared::{unique_map::UniqueMap, Address, Identifier},
+    typing::ast as T,
+};
+use std::collections::BTreeMap;
+
+pub type ModuleKey = (Address, String);
+
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    /// The location of the name in the definition
+    pub definition: Loc,
+    /// The definition, in Move syntax
+    pub description: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Occurrence {
+    pub start: usize,
+    pub end: usize,
+    pub symbol: Symbol,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemberKind {
+    Function,
+    Struct,
+    Constant,
+}
+
+#[derive(Clone, Debug)]
+pub struct Member {
+    pub name: String,
+    pub kind: MemberKind,
+    pub is_public: bool,
+    pub description: String,
+}
+
+#[derive(Default)]
+pub struct Symbols {
+    /// The occurrences in each file, sorted by position
+    occurrences: BTreeMap<&'static str, Vec<Occurrence>>,
+    modules: BTreeMap<ModuleKey, Vec<Member>>,
+    files: FilesSourceText,
+}
+
+impl Symbols {
+    pub fn new(files: FilesSourceText, program: &T::Program) -> Self {
+        let mut builder = Builder::new(&files);
+        builder.definitions(program);
+        builder.program(program);
+        let Builder {
+            mut occurrences,
+            modules,
+            ..
+        } = builder;
+        for file_occurrences in occurrences.values_mut() {
+            file_occurrences.sort_by_key(|occurrence| (occurrence.start, occurrence.end));
+        }
+        Self {
+            occurrences,
+            modules,
+            files,
+        }
+    }
+
+    /// The innermost occurrence at `offset` in `file`
+    pub fn occurrence(&self, file: &str, offset: usize) -> Option<&Occurrence> {
+        self.occurrences
+            .get(file)?
+            .iter()
+            .filter(|occurrence| occurrence.start <= offset && offset <= occurrence.end)
+            .min_by_key(|occurrence| occurrence.end - occurrence.start)
+    }
+
+    pub fn members(&self, module: &ModuleKey) -> &[Member] {
+        self.modules
+            .get(module)
+            .map(|members| members.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The sources the program was built from
+    pub fn files(&self) -> &FilesSourceText {
+        &self.files
+    }
+}
+
+pub fn module_key(ident: &ModuleIdent) -> ModuleKey {
+    (
+        ident.0.value.address,
+        ident.0.value.name.value().to_string(),
+    )
+}
+
+//**************************************************************************************************
+// Descriptions
+//**************************************************************************************************
+
+pub fn type_description(sp!(_, ty_): &Type) -> String {
+    match ty_ {
+        Type_::Unit => "()".to_string(),
+        Type_::Ref(mut_, ty) => format!(
+            "&{}{}",
+            if *mut_ { "mut " } else { "" },
+            type_description(ty)
+        ),
+        Type_::Param(tp) => tp.user_specified_name.value.clone(),
+        Type_::Apply(_, sp!(_, TypeName_::Multiple(_)), tys) => format!("({})", types(tys)),
+        Type_::Apply(_, name, tys) if tys.is_empty() => format!("{}", name.value),
+        Type_::Apply(_, name, tys) => format!("{}<{}>", name.value, types(tys)),
+        Type_::Var(_) | Type_::Anything | Type_::UnresolvedError => "_".to_string(),
+    }
+}
+
+fn types(tys: &[Type]) -> String {
+    tys.iter()
+        .map(type_description)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn type_parameters(tparams: &[TParam]) -> String {
+    if tparams.is_empty() {
+        return "".to_string();
+    }
+    let tparams = tparams
+        .iter()
+        .map(|tp| match tp.kind.value {
+            Kind_::Unknown => tp.user_specified_name.value.clone(),
+            Kind_::Resource => format!("{}: resource", tp.user_specified_name),
+            Kind_::Affine | Kind_::Copyable => format!("{}: copyable", tp.user_specified_name),
+        })
+        .collect::<Vec<_>>();
+    format!("<{}>", tparams.join(", "))
+}
+
+fn function_description(
+    prefix: &str,
+    name: &str,
+    function: &T::Function,
+    signature: &FunctionSignature,
+) -> String {
+    let visibility = match function.visibility {
+        FunctionVisibility::Public(_) => "public ",
+        FunctionVisibility::Internal => "",
+    };
+    let native = match function.body.value {
+        T::FunctionBody_::Native => "native ",
+        T::FunctionBody_::Defined(_) => "",
+    };
+    let parameters = signature
+        .parameters
+        .iter()
+        .map(|(var, ty)| format!("{}: {}", var, type_description(ty)))
+        .collect::<Vec<_>>();
+    let return_type = match &signature.return_type.value {
+        Type_::Unit => "".to_string(),
+        _ => format!(": {}", type_description(&signature.return_type)),
+    };
+    format!(
+        "{}{}fun {}{}{}({}){}",
+        native,
+        visibility,
+        prefix,
+        name,
+        type_parameters(&signature.type_parameters),
+        parameters.join(", "),
+        return_type
+    )
+}
+
+fn struct_description(prefix: &str, name: &str, struct_def: &StructDefinition) -> String {
+    let resource = if struct_def.resource_opt.is_some() {
+        "resource "
+    } else {
+        ""
+    };
+    let header = format!(
+        "{}struct {}{}{}",
+        resource,
+        prefix,
+        name,
+        type_parameters(&struct_def.type_parameters)
+    );
+    match &struct_def.fields {
+        StructFields::Native(_) => format!("native {}", header),
+        StructFields::Defined(fields) => {
+            let mut fields = fields.iter().collect::<Vec<_>>();
+            fields.sort_by_key(|(_, (idx, _))| *idx);
+            let fields = fields
+                .iter()
+                .map(|(field, (_, ty))| format!("    {}: {},\n", field, type_description(ty)))
+                .collect::<String>();
+            format!("{} {{\n{}}}", header, fields)
+        }
+    }
+}
+
+//**************************************************************************************************
+// Builder
+//**************************************************************************************************
+
+struct Builder<'a> {
+    files: &'a FilesSourceText,
+    occurrences: BTreeMap<&'static str, Vec<Occurrence>>,
+    modules: BTreeMap<ModuleKey, Vec<Member>>,
+    functions: BTreeMap<(ModuleKey, String), Symbol>,
+    structs: BTreeMap<(ModuleKey, String), Symbol>,
+    fields: BTreeMap<(ModuleKey, String, String), Symbol>,
+    /// The constants of the module or script being visited
+    constants: BTreeMap<String, Symbol>,
+    /// The locals in scope, innermost scope last
+    scopes: Vec<BTreeMap<String, Symbol>>,
+}
+
+impl<'a> Builder<'a> {
+    fn new(files: &'a FilesSourceText) -> Self {
+        Self {
+            files,
+            occurrences: BTreeMap::new(),
+            modules: BTreeMap::new(),
+            functions: BTreeMap::new(),
+            structs: BTreeMap::new(),
+            fields: BTreeMap::new(),
+            constants: BTreeMap::new(),
+            scopes: vec![],
+        }
+    }
+
+    /// Records `symbol` at `loc` if the source has `name` there
+    fn add(&mut self, loc: Loc, name: &str, symbol: Symbol) {
+        let (start, end) = (loc.span().start().to_usize(), loc.span().end().to_usize());
+        let written = self
+            .files
+            .get(loc.file())
+            .and_then(|source| source.get(start..end));
+        if written != Some(name) {
+            return;
+        }
+        self.occurrences
+            .entry(loc.file())
+            .or_insert_with(Vec::new)
+            .push(Occurrence { start, end, symbol })
+    }
+
+    /// Records the definitions of all module members, before any of their uses is visited
+    fn definitions(&mut self, program: &T::Program) {
+        for (ident, module) in program.modules.iter() {
+            let key = module_key(&ident);
+            let prefix = format!("{}::", ident.0.value.name);
+            let mut members = vec![];
+            for (name, struct_def) in module.structs.iter() {
+                let description = struct_description(&prefix, name.value(), struct_def);
+                let symbol = Symbol {
+                    definition: name.loc(),
+                    description: description.clone(),
+                };
+                self.structs
+                    .insert((key.clone(), name.value().to_string()), symbol);
+                members.push(Member {
+                    name: name.value().to_string(),
+                    kind: MemberKind::Struct,
+                    is_public: true,
+                    description,
+                });
+                if let StructFields::Defined(fields) = &struct_def.fields {
+                    for (field, (_, ty)) in fields.iter() {
+                        let symbol = Symbol {
+                            definition: field.loc(),
+                            description: format!(
+                                "{}{}.{}: {}",
+                                prefix,
+                                name,
+                                field,
+                                type_description(ty)
+                            ),
+                        };
+                        let field_key = (key.clone(), name.value().to_string(), field.to_string());
+                        self.fields.insert(field_key, symbol);
+                    }
+                }
+            }
+            for (name, constant) in module.constants.iter() {
+                members.push(Member {
+                    name: name.value().to_string(),
+                    kind: MemberKind::Constant,
+                    is_public: false,
+                    description: format!(
+                        "const {}{}: {}",
+                        prefix,
+                        name,
+                        type_description(&constant.signature)
+                    ),
+                });
+            }
+            for (name, function) in module.functions.iter() {
+                let description =
+                    function_description(&prefix, name.value(), function, &function.signature);
+                let symbol = Symbol {
+                    definition: name.loc(),
+                    description: description.clone(),
+                };
+                self.functions
+                    .insert((key.clone(), name.value().to_string()), symbol);
+                members.push(Member {
+                    name: name.value().to_string(),
+                    kind: MemberKind::Function,
+                    is_public: matches!(function.visibility, FunctionVisibility::Public(_)),
+                    description,
+                });
+            }
+            self.modules.insert(key, members);
+        }
+    }
+
+    fn program(&mut self, program: &T::Program) {
+        for (ident, module) in program.modules.iter() {
+            let key = module_key(&ident);
+            let prefix = format!("{}::", ident.0.value.name);
+            self.constants(&prefix, &module.constants);
+            for (name, struct_def) in module.structs.iter() {
+                let symbol = self.structs[&(key.clone(), name.value().to_string())].clone();
+                self.add(name.loc(), name.value(), symbol);
+                self.struct_fields(&key, &name, struct_def);
+            }
+            for (name, function) in module.functions.iter() {
+                let symbol = self.functions[&(key.clone(), name.value().to_string())].clone();
+                self.add(name.loc(), name.value(), symbol);
+                self.function(function);
+            }
+        }
+        for script in program.scripts.values() {
+            self.constants("", &script.constants);
+            let name = &script.function_name;
+            let description = function_description(
+                "",
+                name.value(),
+                &script.function,
+                &script.function.signature,
+            );
+            let symbol = Symbol {
+                definition: name.loc(),
+                description,
+            };
+            self.add(name.loc(), name.value(), symbol);
+            self.function(&script.function);
+        }
+    }
+
+    fn constants(&mut self, prefix: &str, constants: &UniqueMap<ConstantName, T::Constant>) {
+        self.constants.clear();
+        for (name, constant) in constants.iter() {
+            let symbol = Symbol {
+                definition: name.loc(),
+                description: format!(
+                    "const {}{}: {}",
+                    prefix,
+                    name,
+                    type_description(&constant.signature)
+                ),
+            };
+            self.add(name.loc(), name.value(), symbol.clone());
+            self.constants.insert(name.value().to_string(), symbol);
+        }
+        for (_, constant) in constants.iter() {
+            self.type_(&constant.signature);
+            self.exp(&constant.value);
+        }
+    }
+
+    fn struct_fields(&mut self, key: &ModuleKey, name: &StructName, struct_def: &StructDefinition) {
+        if let StructFields::Defined(fields) = &struct_def.fields {
+            for (field, (_, ty)) in fields.iter() {
+                let field_key = (key.clone(), name.value().to_string(), field.to_string());
+                let symbol = self.fields[&field_key].clone();
+                self.add(field.loc(), field.value(), symbol);
+                self.type_(ty);
+            }
+        }
+    }
+
+    fn function(&mut self, function: &T::Function) {
+        self.scopes.push(BTreeMap::new());
+        for (var, ty) in &function.signature.parameters {
+            self.declare(var, ty);
+            self.type_(ty);
+        }
+        self.type_(&function.signature.return_type);
+        for (name, loc) in &function.acquires {
+            self.struct_reference(*loc, name, None);
+        }
+        if let T::FunctionBody_::Defined(seq) = &function.body.value {
+            self.sequence(seq);
+        }
+        self.scopes.pop();
+    }
+
+    //**********************************************************************************************
+    // References
+    //**********************************************************************************************
+
+    fn declare(&mut self, var: &Var, ty: &Type) {
+        let symbol = Symbol {
+            definition: var.loc(),
+            description: format!("{}: {}", var, type_description(ty)),
+        };
+        self.add(var.loc(), var.value(), symbol.clone());
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(var.value().to_string(), symbol);
+        }
+    }
+
+    fn local(&mut self, var: &Var) {
+        let symbol = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(var.value()))
+            .cloned();
+        if let Some(symbol) = symbol {
+            self.add(var.loc(), var.value(), symbol)
+        }
+    }
+
+    /// A reference to the struct `name`, of `module` or of the module it is written in
+    fn struct_reference(&mut self, loc: Loc, name: &StructName, module: Option<&ModuleIdent>) {
+        let symbol = match module {
+            Some(module) => self
+                .structs
+                .get(&(module_key(module), name.value().to_string())),
+            // Acquires lists name structs of the module of the function
+            None => self
+                .structs
+                .iter()
+                .find(|((_, struct_name), symbol)| {
+                    struct_name == name.value() && symbol.definition.file() == loc.file()
+                })
+                .map(|(_, symbol)| symbol),
+        };
+        if let Some(symbol) = symbol.cloned() {
+            self.add(loc, name.value(), symbol)
+        }
+    }
+
+    fn field_reference<V>(&mut self, module: &ModuleIdent, name: &StructName, fields: &Fields<V>) {
+        for (field, _) in fields.iter() {
+            let field_key = (
+                module_key(module),
+                name.value().to_string(),
+                field.to_string(),
+            );
+            if let Some(symbol) = self.fields.get(&field_key).cloned() {
+                self.add(field.loc(), field.value(), symbol);
+            }
+        }
+    }
+
+    fn type_(&mut self, sp!(_, ty_): &Type) {
+        match ty_ {
+            Type_::Ref(_, ty) => self.type_(ty),
+            Type_::Apply(_, sp!(_, TypeName_::ModuleType(module, name)), tys) => {
+                self.struct_reference(name.loc(), name, Some(module));
+                tys.iter().for_each(|ty| self.type_(ty))
+            }
+            Type_::Apply(_, _, tys) => tys.iter().for_each(|ty| self.type_(ty)),
+            Type_::Unit
+            | Type_::Param(_)
+            | Type_::Var(_)
+            | Type_::Anything
+            | Type_::UnresolvedError => (),
+        }
+    }
+
+    fn sequence(&mut self, seq: &T::Sequence) {
+        self.scopes.push(BTreeMap::new());
+        for item in seq {
+            match &item.value {
+                T::SequenceItem_::Seq(e) => self.exp(e),
+                T::SequenceItem_::Declare(lvalues) => self.lvalues(lvalues, true),
+                T::SequenceItem_::Bind(lvalues, tys, e) => {
+                    tys.iter().flatten().for_each(|ty| self.type_(ty));
+                    self.exp(e);
+                    self.lvalues(lvalues, true)
+                }
+            }
+        }
+        self.scopes.pop();
+    }
+
+    fn lvalues(&mut self, lvalues: &T::LValueList, declare: bool) {
+        for lvalue in &lvalues.value {
+            self.lvalue(lvalue, declare)
+        }
+    }
+
+    fn lvalue(&mut self, lvalue: &T::LValue, declare: bool) {
+        match &lvalue.value {
+            T::LValue_::Ignore => (),
+            T::LValue_::Var(var, ty) if declare => self.declare(var, ty),
+            T::LValue_::Var(var, _) => self.local(var),
+            T::LValue_::Unpack(module, name, tys, fields)
+            | T::LValue_::BorrowUnpack(_, module, name, tys, fields) => {
+                self.struct_reference(name.loc(), name, Some(module));
+                tys.iter().for_each(|ty| self.type_(ty));
+                self.field_reference(module, name, fields);
+                for (_, (_, (_, lvalue))) in fields.iter() {
+                    self.lvalue(lvalue, declare)
+                }
+            }
+        }
+    }
+
+    fn exp(&mut self, e: &T::Exp) {
+        use T::UnannotatedExp_ as E;
+        match &e.exp.value {
+            E::Unit
+            | E::Value(_)
+            | E::InferredNum(_)
+            | E::Break
+            | E::Continue
+            | E::Spec(..)
+            | E::UnresolvedError => (),
+            E::Move { var, .. } | E::Copy { var, .. } | E::Use(var) | E::BorrowLocal(_, var) => {
+                self.local(var)
+            }
+            E::Constant(name) => {
+                if let Some(symbol) = self.constants.get(name.value()).cloned() {
+                    self.add(name.loc(), name.value(), symbol)
+                }
+            }
+            E::ModuleCall(call) => {
+                let key = (module_key(&call.module), call.name.value().to_string());
+                if let Some(symbol) = self.functions.get(&key).cloned() {
+                    self.add(call.name.loc(), call.name.value(), symbol)
+                }
+                call.type_arguments.iter().for_each(|ty| self.type_(ty));
+                self.exp(&call.arguments)
+            }
+            E::Builtin(builtin, e) => {
+                use T::BuiltinFunction_ as B;
+                match &builtin.value {
+                    B::MoveToSender(ty)
+                    | B::MoveTo(ty)
+                    | B::MoveFrom(ty)
+                    | B::BorrowGlobal(_, ty)
+                    | B::Exists(ty)
+                    | B::Freeze(ty) => self.type_(ty),
+                }
+                self.exp(e)
+            }
+            E::IfElse(cond, if_true, if_false) => {
+                self.exp(cond);
+                self.exp(if_true);
+                self.exp(if_false)
+            }
+            E::While(cond, body) => {
+                self.exp(cond);
+                self.exp(body)
+            }
+            E::Loop { body, .. } => self.exp(body),
+            E::Block(seq) => self.sequence(seq),
+            E::Assign(lvalues, tys, e) => {
+                tys.iter().flatten().for_each(|ty| self.type_(ty));
+                self.exp(e);
+                self.lvalues(lvalues, false)
+            }
+            E::Mutate(lhs, rhs) => {
+                self.exp(lhs);
+                self.exp(rhs)
+            }
+            E::Return(e)
+            | E::Abort(e)
+            | E::Dereference(e)
+            | E::UnaryExp(_, e)
+            | E::TempBorrow(_, e) => self.exp(e),
+            E::BinopExp(lhs, _, _, rhs) => {
+                self.exp(lhs);
+                self.exp(rhs)
+            }
+            E::Pack(module, name, tys, fields) => {
+                self.struct_reference(name.loc(), name, Some(module));
+                tys.iter().for_each(|ty| self.type_(ty));
+                self.field_reference(module, name, fields);
+                for (_, (_, (_, e))) in fields.iter() {
+                    self.exp(e)
+                }
+            }
+            E::ExpList(items) => {
+                for item in items {
+                    match item {
+                        T::ExpListItem::Single(e, _) | T::ExpListItem::Splat(_, e, _) => {
+                            self.exp(e)
+                        }
+                    }
+                }
+            }
+            E::Borrow(_, e, field) => {
+                self.exp(e);
+                let mut ty = &e.ty;
+                while let sp!(_, Type_::Ref(_, inner)) = ty {
+                    ty = &**inner;
+                }
+                if let sp!(
+                    _,
+                    Type_::Apply(_, sp!(_, TypeName_::ModuleType(module, name)), _)
+                ) = ty
+                {
+                    let field_key = (
+                        module_key(module),
+                        name.value().to_string(),
+                        field.to_string(),
+                    );
+                    if let Some(symbol) = self.fields.get(&field_key).cloned() {
+                        self.add(field.loc(), field.value(), symbol);
+                    }
+                }
+            }
+            E::Cast(e, ty) | E::Annotate(e, ty) => {
+                self.exp(e);
+                self.type_(ty)
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_lsp::{protocol, Server};
+use serde_json::{json, Value};
+use std::{io::Cursor, time::Duration};
+
+const STDLIB_MODULES: &str = "../../stdlib/modules";
+
+const SCRIPT: &str = "\
+script {
+use 0x0::Vector;
+fun main() {
+    let v = Vector::empty<u64>();
+    Vector::push_back(&mut v, 1);
+    let n = Vector::length(&v);
+    if (n != 1) abort 42;
+}
+}
+";
+
+fn uri() -> String {
+    let path = std::env::temp_dir().join("move_lsp_test_script.move");
+    protocol::path_to_uri(path.to_str().unwrap()).unwrap()
+}
+
+fn did_open(text: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": { "uri": uri(), "languageId": "move", "version": 1, "text": text },
+        },
+    })
+}
+
+fn request(id: u64, method: &str, line: u64, character: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": {
+            "textDocument": { "uri": uri() },
+            "position": { "line": line, "character": character },
+        },
+    })
+}
+
+fn did_change(text: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didChange",
+        "params": {
+            "textDocument": { "uri": uri(), "version": 2 },
+            "contentChanges": [{ "text": text }],
+        },
+    })
+}
+
+/// Runs a server on `messages`, initialization and shutdown aside, and returns its messages.
+/// The check delay never elapses, so the documents are checked before each request only.
+fn run(messages: Vec<Value>) -> Vec<Value> {
+    let mut input = vec![];
+    let initialize = json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {} });
+    protocol::write_message(&mut input, &initialize).unwrap();
+    for message in messages {
+        protocol::write_message(&mut input, &message).unwrap();
+    }
+    let shutdown = json!({ "jsonrpc": "2.0", "id": 1000, "method": "shutdown" });
+    protocol::write_message(&mut input, &shutdown).unwrap();
+    let exit = json!({ "jsonrpc": "2.0", "method": "exit" });
+    protocol::write_message(&mut input, &exit).unwrap();
+
+    let mut output = vec![];
+    Server::new(&[STDLIB_MODULES.to_string()], None)
+        .unwrap()
+        .with_check_delay(Duration::from_secs(3600))
+        .run(Cursor::new(input), &mut output)
+        .unwrap();
+    let mut output = &output[..];
+    let mut messages = vec![];
+    while let Some(message) = protocol::read_message(&mut output).unwrap() {
+        messages.push(message)
+    }
+    messages
+}
+
+fn response(messages: &[Value], id: u64) -> &Value {
+    let response = messages.iter().find(|message| message["id"] == id).unwrap();
+    &response["result"]
+}
+
+fn diagnostics(messages: &[Value]) -> Vec<&Value> {
+    messages
+        .iter()
+        .filter(|message| message["method"] == "textDocument/publishDiagnostics")
+        .map(|message| &message["params"])
+        .filter(|params| params["uri"] == uri())
+        .collect()
+}
+
+#[test]
+fn initialize() {
+    let messages = run(vec![]);
+    let capabilities = &response(&messages, 0)["capabilities"];
+    assert_eq!(capabilities["definitionProvider"], true);
+    assert_eq!(capabilities["hoverProvider"], true);
+}
+
+#[test]
+fn no_diagnostics() {
+    let messages = run(vec![did_open(SCRIPT)]);
+    let diagnostics = diagnostics(&messages);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["diagnostics"], json!([]));
+}
+
+#[test]
+fn type_error_diagnostics() {
+    let script = SCRIPT.replace("abort 42", "abort true");
+    let messages = run(vec![did_open(&script)]);
+    let diagnostics = &diagnostics(&messages)[0]["diagnostics"];
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 6);
+}
+
+#[test]
+fn fixed_diagnostics_are_cleared() {
+    let script = SCRIPT.replace("abort 42", "abort true");
+    let messages = run(vec![
+        did_open(&script),
+        request(1, "textDocument/hover", 3, 8),
+        did_change(SCRIPT),
+    ]);
+    let diagnostics = diagnostics(&messages);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0]["diagnostics"].as_array().unwrap().len(), 1);
+    assert_eq!(diagnostics[1]["diagnostics"], json!([]));
+}
+
+#[test]
+fn changes_are_checked_once() {
+    let script = SCRIPT.replace("abort 42", "abort true");
+    let messages = run(vec![
+        did_open(SCRIPT),
+        did_change(&script),
+        did_change(SCRIPT),
+        did_change(&script),
+    ]);
+    let diagnostics = diagnostics(&messages);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["diagnostics"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn definition_in_dependency() {
+    // `push_back`
+    let messages = run(vec![
+        did_open(SCRIPT),
+        request(1, "textDocument/definition", 4, 14),
+    ]);
+    let location = response(&messages, 1);
+    assert!(location["uri"].as_str().unwrap().ends_with("/vector.move"));
+    let line = location["range"]["start"]["line"].as_u64().unwrap() as usize;
+    let vector = std::fs::read_to_string(format!("{}/vector.move", STDLIB_MODULES)).unwrap();
+    assert!(vector.lines().nth(line).unwrap().contains("fun push_back"));
+}
+
+#[test]
+fn definition_of_local() {
+    // `v` in `&v`
+    let messages = run(vec![
+        did_open(SCRIPT),
+        request(1, "textDocument/definition", 5, 28),
+    ]);
+    let location = response(&messages, 1);
+    assert_eq!(location["uri"], uri());
+    assert_eq!(
+        location["range"]["start"],
+        json!({ "line": 3, "character": 8 })
+    );
+}
+
+#[test]
+fn hovers() {
+    let messages = run(vec![
+        did_open(SCRIPT),
+        // `v` in `let v`
+        request(1, "textDocument/hover", 3, 8),
+        // `n` in `if (n != 1)`
+        request(2, "textDocument/hover", 6, 8),
+        // `length`
+        request(3, "textDocument/hover", 5, 20),
+    ]);
+    let hover = |id| {
+        response(&messages, id)["contents"]["value"]
+            .as_str()
+            .unwrap()
+    };
+    assert!(hover(1).contains("v: vector<u64>"));
+    assert!(hover(2).contains("n: u64"));
+    assert!(hover(3).contains("fun Vector::length<Element>(v: &vector<Element>): u64"));
+}
+
+#[test]
+fn member_completion() {
+    let script = SCRIPT.replace("    if (n != 1) abort 42;", "    Vector::");
+    let messages = run(vec![
+        did_open(SCRIPT),
+        request(1, "textDocument/hover", 3, 8),
+        did_open(&script),
+        request(2, "textDocument/completion", 6, 12),
+    ]);
+    let labels: Vec<_> = response(&messages, 2)
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect();
+    assert!(labels.contains(&"push_back"));
+    assert!(labels.contains(&"empty"));
+    // The incomplete script doesn't parse
+    let diagnostics = diagnostics(&messages);
+    assert!(!diagnostics[1]["diagnostics"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn utf16_positions() {
+    // 'é' is 2 bytes and 1 UTF-16 code unit, '😀' is 4 bytes and 2 UTF-16 code units
+    let source = "ab\n é😀x\n";
+    let x = source.find('x').unwrap();
+    let position = protocol::Position {
+        line: 1,
+        character: 4,
+    };
+    assert_eq!(protocol::position(source, x), position);
+    assert_eq!(protocol::offset(source, position), x);
+    // Within '😀', and past the end of the line
+    let within = protocol::Position {
+        line: 1,
+        character: 3,
+    };
+    assert_eq!(protocol::offset(source, within), source.find('😀').unwrap());
+    let past = protocol::Position {
+        line: 1,
+        character: 10,
+    };
+    assert_eq!(protocol::offset(source, past), x + 1);
+}
+
+#[test]
+fn member_completion_after_multibyte_characters() {
+    let script = SCRIPT.replace("    if (n != 1) abort 42;", "    /* é */ 😀Vector::");
+    let messages = run(vec![
+        did_open(SCRIPT),
+        request(1, "textDocument/hover", 3, 8),
+        did_open(&script),
+        // After `Vector::`, 4 + 8 + 2 + 8 UTF-16 code units
+        request(2, "textDocument/completion", 6, 22),
+    ]);
+    let labels: Vec<_> = response(&messages, 2)
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect();
+    assert!(labels.contains(&"push_back"));
+    assert!(labels.contains(&"empty"));
+}
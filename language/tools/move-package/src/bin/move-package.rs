@@ -0,0 +1,60 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::Result;
+use move_package::{BuildConfig, BuildStatus, PackageGraph};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "move-package", about = "Check and build Move packages")]
+struct Args {
+    /// The directory of the package's Move.toml
+    #[structopt(long = "path", short = "p", default_value = ".")]
+    pub path: PathBuf,
+    #[structopt(subcommand)]
+    pub cmd: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Compile the package and its dependencies, writing bytecode and source maps
+    #[structopt(name = "build")]
+    Build {
+        /// The output directory, 'build' in the package by default
+        #[structopt(long = "build-dir", short = "o")]
+        build_dir: Option<PathBuf>,
+        /// Compile all packages, even the ones whose outputs are up to date
+        #[structopt(long = "force", short = "f")]
+        force: bool,
+    },
+    /// Check the package and its dependencies without writing any output
+    #[structopt(name = "check")]
+    Check,
+}
+
+fn main() -> Result<()> {
+    let args = Args::from_args();
+    let graph = PackageGraph::resolve(&args.path)?;
+    match args.cmd {
+        Command::Build { build_dir, force } => {
+            let config = BuildConfig { build_dir, force };
+            for build in move_package::build(&graph, &config)? {
+                let status = match build.status {
+                    BuildStatus::Compiled => "COMPILED",
+                    BuildStatus::Cached => "CACHED",
+                };
+                println!(
+                    "{:>8} {} ({})",
+                    status,
+                    build.name,
+                    build.output_dir.display()
+                );
+            }
+        }
+        Command::Check => move_package::check(&graph)?,
+    }
+    Ok(())
+}
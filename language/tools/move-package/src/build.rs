@@ -0,0 +1,176 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Builds of the packages of a `PackageGraph`.
+//!
+//! Every package is compiled against the sources of its transitive dependencies, and its
+//! compiled units and their source maps are written to `<build dir>/<package name>`, the way
+//! `move-build` lays them out. Along with them goes a fingerprint of everything the outputs
+//! depend on (sources, address and the fingerprints of the dependencies), and a package whose
+//! fingerprint is unchanged is not compiled again.
+
+use crate::{manifest::Package, resolution::PackageGraph};
+use anyhow::{bail, Result};
+use libra_crypto::HashValue;
+use move_lang::{
+    errors::{report_errors_to_buffer, Errors, FilesSourceText},
+    move_check_no_report, move_compile_no_report, output_compiled_units,
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The default build directory, relative to the root package
+pub const DEFAULT_BUILD_DIR: &str = "build";
+pub const FINGERPRINT_FILE_NAME: &str = "fingerprint";
+
+#[derive(Debug, Default)]
+pub struct BuildConfig {
+    /// Where the outputs are written. Defaults to `DEFAULT_BUILD_DIR` in the root package.
+    pub build_dir: Option<PathBuf>,
+    /// Compile every package, even the ones whose outputs are up to date
+    pub force: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BuildStatus {
+    Compiled,
+    Cached,
+}
+
+#[derive(Debug)]
+pub struct PackageBuild {
+    pub name: String,
+    pub status: BuildStatus,
+    /// The directory of the compiled units and source maps of the package
+    pub output_dir: PathBuf,
+}
+
+/// Builds the packages of `graph` in build order
+pub fn build(graph: &PackageGraph, config: &BuildConfig) -> Result<Vec<PackageBuild>> {
+    let build_dir = match &config.build_dir {
+        Some(dir) => dir.clone(),
+        None => graph.root().root().join(DEFAULT_BUILD_DIR),
+    };
+    let mut fingerprints: BTreeMap<&str, HashValue> = BTreeMap::new();
+    let mut builds = vec![];
+    for package in graph.build_order() {
+        let deps = graph.transitive_dependencies(package.name());
+        let targets = package.source_files()?;
+        let dep_files = source_files(&deps)?;
+
+        let dep_fingerprints = deps.iter().map(|dep| fingerprints[dep.name()]);
+        let fingerprint = fingerprint(package, &targets, dep_fingerprints)?;
+        let output_dir = build_dir.join(package.name());
+        let status = if !config.force && is_up_to_date(&output_dir, &fingerprint) {
+            BuildStatus::Cached
+        } else {
+            compile(package, &targets, &dep_files, &output_dir, &fingerprint)?;
+            BuildStatus::Compiled
+        };
+
+        fingerprints.insert(package.name(), fingerprint);
+        builds.push(PackageBuild {
+            name: package.name().to_string(),
+            status,
+            output_dir,
+        });
+    }
+    Ok(builds)
+}
+
+/// Checks the packages of `graph` in build order, without writing any output
+pub fn check(graph: &PackageGraph) -> Result<()> {
+    for package in graph.build_order() {
+        let targets = package.source_files()?;
+        let dep_files = source_files(&graph.transitive_dependencies(package.name()))?;
+        let (files, errors) = move_check_no_report(&targets, &dep_files, package.address())?;
+        if !errors.is_empty() {
+            return compilation_failed(package, files, errors);
+        }
+    }
+    Ok(())
+}
+
+fn source_files(packages: &[&Package]) -> Result<Vec<String>> {
+    let mut files = vec![];
+    for package in packages {
+        files.extend(package.source_files()?);
+    }
+    Ok(files)
+}
+
+fn compile(
+    package: &Package,
+    targets: &[String],
+    deps: &[String],
+    output_dir: &Path,
+    fingerprint: &HashValue,
+) -> Result<()> {
+    let (files, units_or_errors) = move_compile_no_report(targets, deps, package.address())?;
+    let units = match units_or_errors {
+        Ok(units) => units,
+        Err(errors) => return compilation_failed(package, files, errors),
+    };
+    // Start from an empty directory, so that the outputs of removed sources don't linger
+    if output_dir.exists() {
+        fs::remove_dir_all(output_dir)?;
+    }
+    fs::create_dir_all(output_dir)?;
+    let output_dir_str = match output_dir.to_str() {
+        Some(dir) => dir,
+        None => bail!("non-Unicode build directory '{}'", output_dir.display()),
+    };
+    output_compiled_units(
+        /* emit_source_maps */ true,
+        files,
+        units,
+        output_dir_str,
+    )?;
+    // Written last: an interrupted build is never mistaken for a complete one
+    fs::write(output_dir.join(FINGERPRINT_FILE_NAME), fingerprint.to_hex())?;
+    Ok(())
+}
+
+fn compilation_failed<T>(package: &Package, files: FilesSourceText, errors: Errors) -> Result<T> {
+    bail!(
+        "Compilation of package '{}' failed:\n{}",
+        package.name(),
+        String::from_utf8_lossy(&report_errors_to_buffer(files, errors))
+    )
+}
+
+fn is_up_to_date(output_dir: &Path, fingerprint: &HashValue) -> bool {
+    match fs::read_to_string(output_dir.join(FINGERPRINT_FILE_NAME)) {
+        Ok(previous) => previous == fingerprint.to_hex(),
+        Err(_) => false,
+    }
+}
+
+// The paths of the sources are taken relative to the package, so that moving a package around
+// doesn't invalidate its outputs.
+fn fingerprint(
+    package: &Package,
+    targets: &[String],
+    dep_fingerprints: impl Iterator<Item = HashValue>,
+) -> Result<HashValue> {
+    let mut bytes = vec![];
+    if let Some(address) = package.address() {
+        bytes.extend_from_slice(&address.to_u8());
+    }
+    for target in targets {
+        let path = Path::new(target);
+        let relative = path.strip_prefix(package.root()).unwrap_or(path);
+        bytes.extend_from_slice(relative.to_string_lossy().as_bytes());
+        bytes.push(0);
+        let contents = fs::read(path)?;
+        bytes.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&contents);
+    }
+    for dep_fingerprint in dep_fingerprints {
+        bytes.extend_from_slice(&dep_fingerprint.to_vec());
+    }
+    Ok(HashValue::sha3_256_of(&bytes))
+}
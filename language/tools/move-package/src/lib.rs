@@ -0,0 +1,19 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Move packages: a directory of Move sources with a `Move.toml` manifest naming the package,
+//! its address and the packages it depends on (see `manifest`).
+//!
+//! The manifest of a package and of all its transitive dependencies are resolved into a
+//! `PackageGraph`, whose packages are then built dependencies first into a shared build
+//! directory (see `build`).
+
+pub mod build;
+pub mod manifest;
+pub mod resolution;
+
+pub use build::{build, check, BuildConfig, BuildStatus, PackageBuild};
+pub use manifest::{Manifest, Package, MANIFEST_FILE_NAME};
+pub use resolution::PackageGraph;
@@ -0,0 +1,159 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `Move.toml` manifest of a package, e.g.
+//!
+//! ```toml
+//! [package]
+//! name = "Wallet"
+//! address = "0x42"
+//! sources = ["sources"]
+//!
+//! [dependencies]
+//! Stdlib = { local = "../libra/language/stdlib" }
+//! ```
+//!
+//! `address` is the sender of the modules and scripts declared outside of an address block, and
+//! `sources` lists the files or directories holding the package's Move sources, relative to the
+//! manifest. Dependencies are keyed by the name of the package they point to.
+
+use anyhow::{anyhow, bail, Context, Result};
+use move_lang::{
+    find_move_filenames,
+    shared::{Address, ADDRESS_LENGTH},
+};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const MANIFEST_FILE_NAME: &str = "Move.toml";
+pub const DEFAULT_SOURCE_DIR: &str = "sources";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub package: PackageInfo,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackageInfo {
+    pub name: String,
+    pub address: Option<String>,
+    #[serde(default = "default_sources")]
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dependency {
+    /// The directory of the dependency's manifest, relative to the depending manifest
+    pub local: String,
+}
+
+fn default_sources() -> Vec<String> {
+    vec![DEFAULT_SOURCE_DIR.to_string()]
+}
+
+impl Manifest {
+    pub fn parse(toml_source: &str) -> Result<Self> {
+        let manifest: Manifest = toml::from_str(toml_source)?;
+        if manifest.package.name.is_empty() {
+            bail!("The package name cannot be empty")
+        }
+        manifest.address()?;
+        Ok(manifest)
+    }
+
+    /// The sender address of the package, if any
+    pub fn address(&self) -> Result<Option<Address>> {
+        match &self.package.address {
+            None => Ok(None),
+            Some(s) => parse_address(s).map(Some),
+        }
+    }
+}
+
+fn parse_address(s: &str) -> Result<Address> {
+    let invalid = || anyhow!("Invalid package address '{}'", s);
+    if !s.starts_with("0x") {
+        return Err(invalid());
+    }
+    let digits = &s[2..];
+    if digits.is_empty()
+        || digits.len() > 2 * ADDRESS_LENGTH
+        || !digits.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Err(invalid());
+    }
+    Address::parse_str(s).map_err(|_| invalid())
+}
+
+/// A package on disk: its manifest and the directory that holds it.
+#[derive(Debug)]
+pub struct Package {
+    root: PathBuf,
+    manifest: Manifest,
+}
+
+impl Package {
+    /// Loads the package whose manifest is in the directory `root`
+    pub fn load(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref();
+        let manifest_path = root.join(MANIFEST_FILE_NAME);
+        let toml_source = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Unable to read '{}'", manifest_path.display()))?;
+        let manifest = Manifest::parse(&toml_source)
+            .with_context(|| format!("Invalid manifest '{}'", manifest_path.display()))?;
+        let root = root
+            .canonicalize()
+            .with_context(|| format!("Unable to resolve '{}'", root.display()))?;
+        Ok(Self { root, manifest })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.manifest.package.name
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    pub fn address(&self) -> Option<Address> {
+        // Checked when the manifest was parsed
+        self.manifest.address().unwrap()
+    }
+
+    /// The Move source files of the package, sorted by path
+    pub fn source_files(&self) -> Result<Vec<String>> {
+        let mut source_paths = vec![];
+        for source in &self.manifest.package.sources {
+            match self.root.join(source).into_os_string().into_string() {
+                Ok(path) => source_paths.push(path),
+                Err(_) => bail!("non-Unicode source path '{}'", source),
+            }
+        }
+        let mut files = find_move_filenames(&source_paths)
+            .with_context(|| format!("Invalid sources of package '{}'", self.name()))?;
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// The directories of the package's dependencies, keyed by package name
+    pub fn dependency_roots(&self) -> impl Iterator<Item = (&str, PathBuf)> {
+        self.manifest
+            .dependencies
+            .iter()
+            .map(move |(name, dep)| (name.as_str(), self.root.join(&dep.local)))
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resolution of the dependency graph of a package.
+
+use crate::manifest::Package;
+use anyhow::{bail, Context, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+/// A package and all of its transitive dependencies, each loaded once.
+#[derive(Debug)]
+pub struct PackageGraph {
+    packages: BTreeMap<String, Package>,
+    /// The direct dependencies of each package
+    edges: BTreeMap<String, BTreeSet<String>>,
+    /// Package names, every package after its dependencies. The root package is last.
+    build_order: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+impl PackageGraph {
+    /// Loads the package in the directory `root` and its dependencies
+    pub fn resolve(root: impl AsRef<Path>) -> Result<Self> {
+        let mut graph = PackageGraph {
+            packages: BTreeMap::new(),
+            edges: BTreeMap::new(),
+            build_order: vec![],
+        };
+        let root = Package::load(root)?;
+        let mut visits = BTreeMap::new();
+        let mut path = vec![];
+        graph.visit(root, &mut visits, &mut path)?;
+        Ok(graph)
+    }
+
+    // Depth first: a package is added to the build order once all of its dependencies are.
+    // `path` is the chain of packages from the root to `package`, used to report cycles.
+    fn visit(
+        &mut self,
+        package: Package,
+        visits: &mut BTreeMap<String, Visit>,
+        path: &mut Vec<String>,
+    ) -> Result<()> {
+        let name = package.name().to_string();
+        visits.insert(name.clone(), Visit::InProgress);
+        path.push(name.clone());
+
+        let mut deps = BTreeSet::new();
+        for (dep_name, dep_root) in package.dependency_roots() {
+            deps.insert(dep_name.to_string());
+            match visits.get(dep_name) {
+                Some(Visit::InProgress) => bail!(
+                    "Cyclic dependency between packages: {} -> {}",
+                    path.join(" -> "),
+                    dep_name
+                ),
+                Some(Visit::Done) => {
+                    let loaded = &self.packages[dep_name];
+                    let same_root = dep_root
+                        .canonicalize()
+                        .map(|r| r == loaded.root())
+                        .unwrap_or(false);
+                    if !same_root {
+                        bail!(
+                            "Conflicting locations for package '{}': '{}' and '{}'",
+                            dep_name,
+                            loaded.root().display(),
+                            dep_root.display()
+                        )
+                    }
+                }
+                None => {
+                    let dep = Package::load(&dep_root).with_context(|| {
+                        format!("Unable to load dependency '{}' of '{}'", dep_name, name)
+                    })?;
+                    if dep.name() != dep_name {
+                        bail!(
+                            "Dependency '{}' of '{}' is the package '{}' in '{}'",
+                            dep_name,
+                            name,
+                            dep.name(),
+                            dep.root().display()
+                        )
+                    }
+                    self.visit(dep, visits, path)?;
+                }
+            }
+        }
+
+        path.pop();
+        visits.insert(name.clone(), Visit::Done);
+        self.edges.insert(name.clone(), deps);
+        self.build_order.push(name.clone());
+        self.packages.insert(name, package);
+        Ok(())
+    }
+
+    pub fn root(&self) -> &Package {
+        self.package(self.build_order.last().unwrap())
+    }
+
+    pub fn package(&self, name: &str) -> &Package {
+        &self.packages[name]
+    }
+
+    /// All packages, every package after its dependencies
+    pub fn build_order(&self) -> impl Iterator<Item = &Package> {
+        self.build_order.iter().map(move |name| self.package(name))
+    }
+
+    /// The packages `name` depends on, directly or not, in build order
+    pub fn transitive_dependencies(&self, name: &str) -> Vec<&Package> {
+        let mut reachable = BTreeSet::new();
+        let mut stack = vec![name];
+        while let Some(cur) = stack.pop() {
+            for dep in &self.edges[cur] {
+                if reachable.insert(dep.as_str()) {
+                    stack.push(dep)
+                }
+            }
+        }
+        self.build_order()
+            .filter(|package| reachable.contains(package.name()))
+            .collect()
+    }
+}
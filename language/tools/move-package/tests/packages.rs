@@ -0,0 +1,76 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use libra_temppath::TempPath;
+use move_package::{build, check, BuildConfig, BuildStatus, PackageGraph};
+use std::fs;
+
+const PACKAGES_DIR: &str = "tests/packages";
+
+fn resolve(name: &str) -> anyhow::Result<PackageGraph> {
+    PackageGraph::resolve(format!("{}/{}", PACKAGES_DIR, name))
+}
+
+#[test]
+fn build_order() {
+    let graph = resolve("App").unwrap();
+    let order: Vec<_> = graph.build_order().map(|p| p.name()).collect();
+    assert_eq!(order, vec!["Base", "Util", "App"]);
+    assert_eq!(graph.root().name(), "App");
+
+    let deps: Vec<_> = graph
+        .transitive_dependencies("Util")
+        .into_iter()
+        .map(|p| p.name())
+        .collect();
+    assert_eq!(deps, vec!["Base"]);
+}
+
+#[test]
+fn cyclic_dependencies() {
+    let err = resolve("CycleA").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cyclic dependency between packages: CycleA -> CycleB -> CycleA"
+    );
+}
+
+#[test]
+fn misnamed_dependency() {
+    let err = resolve("Misnamed").unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("Dependency 'Core' of 'Misnamed' is the package 'Base'"));
+}
+
+#[test]
+fn build_and_cache() {
+    let graph = resolve("App").unwrap();
+    check(&graph).unwrap();
+
+    let build_dir = TempPath::new();
+    build_dir.create_as_dir().unwrap();
+    let mut config = BuildConfig {
+        build_dir: Some(build_dir.path().to_path_buf()),
+        force: false,
+    };
+    let statuses = |config: &BuildConfig| -> Vec<BuildStatus> {
+        build(&graph, config)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.status)
+            .collect()
+    };
+
+    assert_eq!(statuses(&config), vec![BuildStatus::Compiled; 3]);
+    let modules = build_dir.path().join("App").join("modules");
+    assert!(modules.join("0_App.mv").exists());
+    assert!(modules.join("0_App.mvsm").exists());
+    // Only the modules of the package itself are in its outputs
+    assert_eq!(fs::read_dir(&modules).unwrap().count(), 2);
+
+    assert_eq!(statuses(&config), vec![BuildStatus::Cached; 3]);
+
+    config.force = true;
+    assert_eq!(statuses(&config), vec![BuildStatus::Compiled; 3]);
+}
@@ -0,0 +1,8 @@
+module App {
+    use 0x1::Base;
+    use 0x2::Util;
+
+    public fun three(): u64 {
+        Base::one() + Util::two()
+    }
+}
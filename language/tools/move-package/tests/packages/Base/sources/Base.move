@@ -0,0 +1,5 @@
+module Base {
+    public fun one(): u64 {
+        1
+    }
+}
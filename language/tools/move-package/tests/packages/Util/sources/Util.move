@@ -0,0 +1,7 @@
+module Util {
+    use 0x1::Base;
+
+    public fun two(): u64 {
+        Base::one() + Base::one()
+    }
+}
@@ -0,0 +1,56 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::{anyhow, Result};
+use move_lang::shared::Address;
+use move_unit_test::{
+    compile_tests, report_results,
+    storage::{Genesis, TestStorage},
+    UnitTestRunner,
+};
+use std::io;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "move-test", about = "Run the unit tests written in Move")]
+struct Args {
+    /// The source files or directories of the modules under test
+    #[structopt(name = "PATH_TO_SOURCE_FILE")]
+    pub source_files: Vec<String>,
+    /// The source files or directories of the dependencies
+    #[structopt(name = "PATH_TO_DEPENDENCY", long = "dependency", short = "d")]
+    pub dependencies: Vec<String>,
+    /// Add the sources of the standard library to the dependencies
+    #[structopt(long = "stdlib")]
+    pub stdlib: bool,
+    /// The address of the modules declared outside of an address block
+    #[structopt(long = "sender", short = "s", parse(try_from_str = Address::parse_str))]
+    pub sender: Option<Address>,
+    /// The initial state of the storage: 'empty', 'staged' or 'fresh' for the genesis built with
+    /// the staged or compiled standard library, or the path of a genesis blob
+    #[structopt(long = "genesis", default_value = "empty")]
+    pub genesis: Genesis,
+    /// The gas units each test can spend
+    #[structopt(long = "gas-budget", default_value = "1000000")]
+    pub gas_budget: u64,
+}
+
+fn main() -> Result<()> {
+    let args = Args::from_args();
+    let mut deps = args.dependencies;
+    if args.stdlib {
+        deps.extend(stdlib::stdlib_files());
+    }
+
+    let (units, test_plan) = compile_tests(&args.source_files, &deps, args.sender)?;
+    let storage = TestStorage::new(&args.genesis)?;
+    let runner = UnitTestRunner::new(storage, units, args.gas_budget);
+    let results = runner.run(&test_plan);
+    if report_results(&mut io::stdout(), &results)? {
+        Ok(())
+    } else {
+        Err(anyhow!("Some tests failed"))
+    }
+}
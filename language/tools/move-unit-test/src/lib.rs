@@ -0,0 +1,209 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Runs the unit tests written in Move.
+//!
+//! The modules under test and their dependencies are compiled with their `#[test]` functions
+//! (see `move_lang::unit_test`) and written to a `TestStorage`. Each test then runs in a fresh
+//! Move VM against that storage, with its module's address as the sender and a `&signer` for it
+//! as every argument. The changes a test makes are discarded, so tests don't depend on each other.
+
+pub mod storage;
+
+use anyhow::{bail, Result};
+use libra_types::{
+    account_address::AccountAddress,
+    vm_error::{StatusCode, VMStatus},
+};
+use move_core_types::{
+    gas_schedule::{CostTable, GasAlgebra, GasUnits},
+    identifier::Identifier,
+    language_storage::ModuleId,
+};
+use move_lang::{
+    compiled_unit::CompiledUnit,
+    errors::report_errors_to_buffer,
+    move_compile_for_testing,
+    shared::Address,
+    unit_test::{ExpectedFailure, TestCase, TestPlan},
+};
+use move_vm_runtime::{data_cache::TransactionDataCache, move_vm::MoveVM};
+use move_vm_types::{gas_schedule::CostStrategy, values::Value};
+use std::{fmt, io::Write};
+use storage::TestStorage;
+use vm::{access::ModuleAccess, errors::VMResult};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestStatus {
+    Passed,
+    Failed(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct TestResult {
+    pub module_id: ModuleId,
+    pub function_name: String,
+    pub status: TestStatus,
+    pub gas_used: u64,
+}
+
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match &self.status {
+            TestStatus::Passed => "PASS",
+            TestStatus::Failed(_) => "FAIL",
+        };
+        write!(
+            f,
+            "[ {} ] {}::{}::{} (gas used: {})",
+            status,
+            self.module_id.address(),
+            self.module_id.name(),
+            self.function_name,
+            self.gas_used
+        )?;
+        if let TestStatus::Failed(reason) = &self.status {
+            write!(f, "\n    {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compiles `targets` with their tests and `deps`. The compilation errors are returned rendered.
+pub fn compile_tests(
+    targets: &[String],
+    deps: &[String],
+    sender: Option<Address>,
+) -> Result<(Vec<CompiledUnit>, TestPlan)> {
+    let (files, units_or_errors) = move_compile_for_testing(targets, deps, sender)?;
+    match units_or_errors {
+        Ok(units_and_plan) => Ok(units_and_plan),
+        Err(errors) => bail!(
+            "Compilation failed:\n{}",
+            String::from_utf8_lossy(&report_errors_to_buffer(files, errors))
+        ),
+    }
+}
+
+pub struct UnitTestRunner {
+    storage: TestStorage,
+    gas_schedule: CostTable,
+    gas_budget: u64,
+}
+
+impl UnitTestRunner {
+    /// A runner over `storage`, with the compiled modules written to it. Every test can spend up
+    /// to `gas_budget` units of gas.
+    pub fn new(mut storage: TestStorage, units: Vec<CompiledUnit>, gas_budget: u64) -> Self {
+        for unit in units {
+            if let CompiledUnit::Module { module, .. } = unit {
+                let mut blob = vec![];
+                module
+                    .serialize(&mut blob)
+                    .expect("Unable to serialize compiled module");
+                storage.add_module(&module.self_id(), blob);
+            }
+        }
+        let gas_schedule = storage.gas_schedule();
+        Self {
+            storage,
+            gas_schedule,
+            gas_budget,
+        }
+    }
+
+    pub fn run(&self, test_plan: &TestPlan) -> Vec<TestResult> {
+        let mut results = vec![];
+        for module_plan in test_plan {
+            let module_id = ModuleId::new(
+                AccountAddress::new(module_plan.address.to_u8()),
+                Identifier::new(module_plan.module_name.as_str())
+                    .expect("Module names are valid identifiers"),
+            );
+            for test in &module_plan.tests {
+                results.push(self.run_test(&module_id, test));
+            }
+        }
+        results
+    }
+
+    fn run_test(&self, module_id: &ModuleId, test: &TestCase) -> TestResult {
+        let vm = MoveVM::new();
+        let mut data_store = TransactionDataCache::new(&self.storage);
+        let mut cost_strategy =
+            CostStrategy::transaction(&self.gas_schedule, GasUnits::new(self.gas_budget));
+        let sender = *module_id.address();
+        let args = (0..test.signers)
+            .map(|_| Value::transaction_argument_signer_reference(sender))
+            .collect();
+        let function_name = Identifier::new(test.function_name.as_str())
+            .expect("Function names are valid identifiers");
+        let result = vm
+            .execute_function(
+                module_id,
+                &function_name,
+                vec![],
+                args,
+                sender,
+                &mut data_store,
+                &mut cost_strategy,
+            )
+            // The test must leave the storage in a state a transaction could commit.
+            .and_then(|()| data_store.make_write_set().map(|_| ()));
+        let gas_used = self.gas_budget - cost_strategy.remaining_gas().get();
+        TestResult {
+            module_id: module_id.clone(),
+            function_name: test.function_name.clone(),
+            status: test_status(test.expected_failure, result),
+            gas_used,
+        }
+    }
+}
+
+fn test_status(expected_failure: Option<ExpectedFailure>, result: VMResult<()>) -> TestStatus {
+    let abort_code = |status: &VMStatus| match status.major_status {
+        StatusCode::ABORTED => status.sub_status,
+        _ => None,
+    };
+    match (expected_failure, result) {
+        (None, Ok(())) | (Some(ExpectedFailure::Any), Err(_)) => TestStatus::Passed,
+        (None, Err(status)) => TestStatus::Failed(match abort_code(&status) {
+            Some(code) => format!("Test aborted with code {}", code),
+            None => format!("Test failed with {}", status),
+        }),
+        (Some(_), Ok(())) => TestStatus::Failed("Test was expected to fail".to_string()),
+        (Some(ExpectedFailure::AbortCode(expected)), Err(status)) => match abort_code(&status) {
+            Some(code) if code == expected => TestStatus::Passed,
+            Some(code) => TestStatus::Failed(format!(
+                "Test was expected to abort with code {} but aborted with code {}",
+                expected, code
+            )),
+            None => TestStatus::Failed(format!(
+                "Test was expected to abort with code {} but failed with {}",
+                expected, status
+            )),
+        },
+    }
+}
+
+/// Writes the results and a summary of them. Returns whether all the tests passed.
+pub fn report_results<W: Write>(writer: &mut W, results: &[TestResult]) -> Result<bool> {
+    for result in results {
+        writeln!(writer, "{}", result)?;
+    }
+    let failed = results
+        .iter()
+        .filter(|result| result.status != TestStatus::Passed)
+        .count();
+    writeln!(
+        writer,
+        "Test result: {}. Total tests: {}; passed: {}; failed: {}",
+        if failed == 0 { "OK" } else { "FAILED" },
+        results.len(),
+        results.len() - failed,
+        failed
+    )?;
+    Ok(failed == 0)
+}
@@ -0,0 +1,110 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! In-memory storage the unit tests run against.
+
+use anyhow::{bail, Context, Result};
+use libra_types::{
+    access_path::AccessPath,
+    on_chain_config::{OnChainConfig, VMConfig},
+    transaction::Transaction,
+    write_set::{WriteOp, WriteSet},
+};
+use move_core_types::{gas_schedule::CostTable, language_storage::ModuleId};
+use move_vm_runtime::data_cache::RemoteCache;
+use std::{collections::HashMap, fs, path::PathBuf, str::FromStr};
+use stdlib::StdLibOptions;
+use vm::errors::VMResult;
+
+/// The initial state of the storage.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Genesis {
+    /// Nothing but the modules under test.
+    Empty,
+    /// The genesis state built with the staged standard library.
+    Staged,
+    /// The genesis state built with the standard library compiled from source.
+    Fresh,
+    /// The genesis transaction in a file, like `genesis.blob`.
+    File(PathBuf),
+}
+
+impl FromStr for Genesis {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "empty" => Genesis::Empty,
+            "staged" => Genesis::Staged,
+            "fresh" => Genesis::Fresh,
+            path => Genesis::File(PathBuf::from(path)),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TestStorage {
+    data: HashMap<AccessPath, Vec<u8>>,
+}
+
+impl TestStorage {
+    pub fn new(genesis: &Genesis) -> Result<Self> {
+        let mut storage = Self::default();
+        match genesis {
+            Genesis::Empty => (),
+            Genesis::Staged => storage.add_write_set(
+                vm_genesis::generate_genesis_change_set_for_testing(StdLibOptions::Staged)
+                    .write_set(),
+            ),
+            Genesis::Fresh => storage.add_write_set(
+                vm_genesis::generate_genesis_change_set_for_testing(StdLibOptions::Fresh)
+                    .write_set(),
+            ),
+            Genesis::File(path) => {
+                let bytes = fs::read(path)
+                    .with_context(|| format!("Unable to read genesis file {:?}", path))?;
+                match lcs::from_bytes(&bytes)
+                    .with_context(|| format!("Unable to deserialize genesis file {:?}", path))?
+                {
+                    Transaction::WaypointWriteSet(change_set) => {
+                        storage.add_write_set(change_set.write_set())
+                    }
+                    _ => bail!("{:?} is not a genesis transaction", path),
+                }
+            }
+        }
+        Ok(storage)
+    }
+
+    pub fn add_write_set(&mut self, write_set: &WriteSet) {
+        for (access_path, write_op) in write_set {
+            match write_op {
+                WriteOp::Value(blob) => {
+                    self.data.insert(access_path.clone(), blob.clone());
+                }
+                WriteOp::Deletion => {
+                    self.data.remove(access_path);
+                }
+            }
+        }
+    }
+
+    /// Adds a serialized module, replacing the module with the same id if any.
+    pub fn add_module(&mut self, module_id: &ModuleId, blob: Vec<u8>) {
+        self.data.insert(AccessPath::from(module_id), blob);
+    }
+
+    /// The gas schedule of the on-chain `VMConfig`, or else the one published at genesis.
+    pub fn gas_schedule(&self) -> CostTable {
+        let remote_cache: &dyn RemoteCache = self;
+        VMConfig::fetch_config(remote_cache)
+            .map(|config| config.gas_schedule)
+            .unwrap_or_else(vm_genesis::initial_gas_schedule)
+    }
+}
+
+impl RemoteCache for TestStorage {
+    fn get(&self, access_path: &AccessPath) -> VMResult<Option<Vec<u8>>> {
+        Ok(self.data.get(access_path).cloned())
+    }
+}
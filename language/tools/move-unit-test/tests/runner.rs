@@ -0,0 +1,73 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_lang::compiled_unit::CompiledUnit;
+use move_unit_test::{
+    compile_tests, report_results,
+    storage::{Genesis, TestStorage},
+    TestStatus, UnitTestRunner,
+};
+use vm::access::ModuleAccess;
+
+const SOURCES_DIR: &str = "tests/sources";
+
+#[test]
+fn run_tests() {
+    let (units, test_plan) = compile_tests(&[SOURCES_DIR.to_string()], &[], None).unwrap();
+    let storage = TestStorage::new(&Genesis::Empty).unwrap();
+    let runner = UnitTestRunner::new(storage, units, 100_000);
+    let results = runner.run(&test_plan);
+
+    let statuses: Vec<_> = results
+        .iter()
+        .map(|result| (result.function_name.as_str(), result.status.clone()))
+        .collect();
+    assert_eq!(
+        &statuses[..4],
+        &[
+            ("incr_works", TestStatus::Passed),
+            ("incr_overflows", TestStatus::Passed),
+            ("aborts_with_expected_code", TestStatus::Passed),
+            ("publishes_counter", TestStatus::Passed),
+        ]
+    );
+    assert_eq!(
+        statuses[4],
+        (
+            "aborts",
+            TestStatus::Failed("Test aborted with code 42".to_string())
+        )
+    );
+    assert_eq!(
+        statuses[5],
+        (
+            "aborts_with_other_code",
+            TestStatus::Failed(
+                "Test was expected to abort with code 2 but aborted with code 3".to_string()
+            )
+        )
+    );
+    assert_eq!(
+        statuses[6],
+        (
+            "succeeds",
+            TestStatus::Failed("Test was expected to fail".to_string())
+        )
+    );
+    assert!(results[0].gas_used > 0);
+
+    let mut report = vec![];
+    assert!(!report_results(&mut report, &results).unwrap());
+    assert!(String::from_utf8(report)
+        .unwrap()
+        .ends_with("Test result: FAILED. Total tests: 7; passed: 4; failed: 3\n"));
+}
+
+#[test]
+fn test_functions_are_not_published_outside_of_tests() {
+    let (_, units) = move_lang::move_compile(&[SOURCES_DIR.to_string()], &[], None).unwrap();
+    match &units[..] {
+        [CompiledUnit::Module { module, .. }] => assert_eq!(module.function_defs().len(), 1),
+        _ => panic!("Expected a single module"),
+    }
+}
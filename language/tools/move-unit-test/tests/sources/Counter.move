@@ -0,0 +1,45 @@
+address 0x2 {
+module Counter {
+    resource struct T { value: u64 }
+
+    public fun incr(value: u64): u64 {
+        value + 1
+    }
+
+    #[test]
+    fun incr_works() {
+        assert(incr(1) == 2, 1);
+    }
+
+    #[test]
+    #[expected_failure]
+    fun incr_overflows() {
+        incr(18446744073709551615);
+    }
+
+    #[test, expected_failure(abort_code = 2)]
+    fun aborts_with_expected_code() {
+        assert(incr(1) == 3, 2);
+    }
+
+    #[test]
+    fun publishes_counter(account: &signer) {
+        move_to(account, T { value: incr(0) });
+    }
+
+    // The tests below fail.
+
+    #[test]
+    fun aborts() {
+        abort 42
+    }
+
+    #[test, expected_failure(abort_code = 2)]
+    fun aborts_with_other_code() {
+        abort 3
+    }
+
+    #[test, expected_failure]
+    fun succeeds() {}
+}
+}
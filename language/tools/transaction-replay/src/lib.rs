@@ -0,0 +1,171 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Executes transactions in a traced VM, against the state of a LibraDB or the state after a
+//! testing genesis. Committed transactions are replayed against the state they were executed on.
+
+use anyhow::{bail, format_err, Result};
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_state::AccountState,
+    transaction::{ChangeSet, Transaction, TransactionInfo, TransactionOutput, Version},
+    write_set::WriteOp,
+};
+use libra_vm::LibraVM;
+use move_vm_runtime::tracer::VMTracer;
+use std::{
+    cell::RefCell,
+    collections::{hash_map::Entry, HashMap},
+    convert::TryFrom,
+    sync::Arc,
+};
+use stdlib::StdLibOptions;
+use storage_interface::DbReader;
+
+/// A view of the state of a LibraDB after the transaction at `version` was committed, or of the
+/// empty state before genesis if `version` is `None`
+pub struct DbStateView<'a> {
+    db: &'a dyn DbReader,
+    version: Option<Version>,
+    account_states: RefCell<HashMap<AccountAddress, AccountState>>,
+}
+
+impl<'a> DbStateView<'a> {
+    pub fn new(db: &'a dyn DbReader, version: Option<Version>) -> Self {
+        Self {
+            db,
+            version,
+            account_states: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl<'a> StateView for DbStateView<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        let version = match self.version {
+            Some(version) => version,
+            None => return Ok(None),
+        };
+        match self.account_states.borrow_mut().entry(access_path.address) {
+            Entry::Occupied(entry) => Ok(entry.get().get(&access_path.path).cloned()),
+            Entry::Vacant(entry) => {
+                let (blob, _proof) = self
+                    .db
+                    .get_account_state_with_proof_by_version(access_path.address, version)?;
+                let account_state = match blob {
+                    Some(blob) => AccountState::try_from(&blob)?,
+                    None => AccountState::default(),
+                };
+                Ok(entry.insert(account_state).get(&access_path.path).cloned())
+            }
+        }
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths
+            .iter()
+            .map(|access_path| self.get(access_path))
+            .collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.version.is_none()
+    }
+}
+
+/// The state written by a change set, e.g. a genesis
+pub struct ChangeSetStateView {
+    data: HashMap<AccessPath, Vec<u8>>,
+}
+
+impl ChangeSetStateView {
+    pub fn new(change_set: &ChangeSet) -> Self {
+        let mut data = HashMap::new();
+        for (access_path, write_op) in change_set.write_set() {
+            match write_op {
+                WriteOp::Value(blob) => {
+                    data.insert(access_path.clone(), blob.clone());
+                }
+                WriteOp::Deletion => {
+                    data.remove(access_path);
+                }
+            }
+        }
+        Self { data }
+    }
+
+    /// The state after the testing genesis of the staged standard library
+    pub fn genesis() -> Self {
+        Self::new(&vm_genesis::generate_genesis_change_set_for_testing(
+            StdLibOptions::Staged,
+        ))
+    }
+}
+
+impl StateView for ChangeSetStateView {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        Ok(self.data.get(access_path).cloned())
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths
+            .iter()
+            .map(|access_path| self.get(access_path))
+            .collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
+/// Executes `transaction` on `state_view`, reporting the execution to `tracer`.
+pub fn execute_transaction(
+    state_view: &dyn StateView,
+    transaction: Transaction,
+    tracer: Arc<dyn VMTracer>,
+) -> Result<TransactionOutput> {
+    let mut vm = LibraVM::new_with_tracer(tracer);
+    vm.load_configs(state_view);
+    vm.execute_transactions(vec![transaction], state_view)
+        .map_err(|status| format_err!("Unable to execute the transaction: {:?}", status))?
+        .pop()
+        .ok_or_else(|| format_err!("No output for the transaction"))
+}
+
+/// Executes the transaction committed at `version` again, on the state it was committed on,
+/// reporting the execution to `tracer`. Returns the transaction with its replayed output and
+/// the info it was committed with.
+pub fn replay_transaction(
+    db: &dyn DbReader,
+    version: Version,
+    tracer: Arc<dyn VMTracer>,
+) -> Result<(Transaction, TransactionOutput, TransactionInfo)> {
+    let latest_version = db.get_latest_version()?;
+    if version > latest_version {
+        bail!(
+            "Version {} is not committed, the latest version is {}",
+            version,
+            latest_version
+        )
+    }
+    let mut transactions = db.get_transactions(version, 1, latest_version, false)?;
+    let transaction = transactions
+        .transactions
+        .pop()
+        .ok_or_else(|| format_err!("No transaction at version {}", version))?;
+    let info = transactions
+        .proof
+        .transaction_infos()
+        .first()
+        .cloned()
+        .ok_or_else(|| format_err!("No transaction info at version {}", version))?;
+
+    let state_view = DbStateView::new(db, version.checked_sub(1));
+    let output = execute_transaction(&state_view, transaction.clone(), tracer)?;
+    Ok((transaction, output, info))
+}
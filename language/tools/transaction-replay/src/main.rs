@@ -0,0 +1,71 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::Result;
+use libra_types::transaction::{Transaction, TransactionStatus, Version};
+use libradb::LibraDB;
+use move_vm_runtime::tracer::TraceWriter;
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::PathBuf,
+};
+use structopt::StructOpt;
+use transaction_replay::replay_transaction;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "transaction-replay",
+    about = "Replay a committed transaction in a traced Move VM"
+)]
+struct Args {
+    /// The directory of the LibraDB holding the transaction
+    #[structopt(long = "db", parse(from_os_str))]
+    pub db: PathBuf,
+    /// The version of the transaction to replay
+    #[structopt(long = "version", short = "v")]
+    pub version: Version,
+    /// Optional path for the trace. Printed to stdout if not present.
+    #[structopt(long = "trace-path", short = "o", parse(from_os_str))]
+    pub trace_path: Option<PathBuf>,
+}
+
+fn display_kind(transaction: &Transaction) -> &'static str {
+    match transaction {
+        Transaction::UserTransaction(_) => "user transaction",
+        Transaction::WaypointWriteSet(_) => "write set",
+        Transaction::BlockMetadata(_) | Transaction::BlockMetadataWithFailedProposers(_) => {
+            "block metadata"
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Args::from_args();
+    let db = LibraDB::open(&args.db, true /* readonly */, None /* pruner */)?;
+
+    let trace_writer: Box<dyn Write + Send> = match &args.trace_path {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    let (transaction, output, info) =
+        replay_transaction(&db, args.version, TraceWriter::new(trace_writer))?;
+
+    let replayed_status = match output.status() {
+        TransactionStatus::Keep(status) => format!("kept, {:?}", status.major_status),
+        TransactionStatus::Discard(status) => format!("discarded, {:?}", status.major_status),
+        TransactionStatus::Retry => "retry".to_string(),
+    };
+    eprintln!(
+        "Version {} ({}): replayed {} with {} gas, committed {:?} with {} gas",
+        args.version,
+        display_kind(&transaction),
+        replayed_status,
+        output.gas_used(),
+        info.major_status(),
+        info.gas_used()
+    );
+    Ok(())
+}
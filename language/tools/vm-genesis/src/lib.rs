@@ -20,11 +20,16 @@ use libra_network_address::RawNetworkAddress;
 use libra_types::{
     account_config,
     contract_event::ContractEvent,
-    on_chain_config::{config_address, new_epoch_event_key, VMPublishingOption},
+    on_chain_config::{
+        config_address, new_epoch_event_key, OnChainConsensusConfig, VMPublishingOption,
+    },
     transaction::{authenticator::AuthenticationKey, ChangeSet, Script, Transaction},
 };
 use libra_vm::data_cache::StateViewCache;
-use move_core_types::language_storage::{StructTag, TypeTag};
+use move_core_types::{
+    gas_schedule::{CostTable, GasConstants},
+    language_storage::{StructTag, TypeTag},
+};
 use move_vm_types::{data_store::DataStore, loaded_data::types::FatStructType, values::Value};
 use once_cell::sync::Lazy;
 use rand::prelude::*;
@@ -36,6 +41,7 @@ use vm::access::ModuleAccess;
 const GENESIS_SEED: [u8; 32] = [42; 32];
 
 const GENESIS_MODULE_NAME: &str = "Genesis";
+const CONSENSUS_CONFIG_MODULE_NAME: &str = "LibraConsensusConfig";
 
 pub static GENESIS_KEYPAIR: Lazy<(Ed25519PrivateKey, Ed25519PublicKey)> = Lazy::new(|| {
     let mut rng = StdRng::from_seed(GENESIS_SEED);
@@ -87,6 +93,14 @@ pub fn encode_genesis_change_set(
     create_and_initialize_main_accounts(&mut genesis_context, &public_key, &lbr_ty);
     initialize_validators(&mut genesis_context, &validators, &lbr_ty);
     setup_vm_config(&mut genesis_context, vm_publishing_option);
+    // Standard libraries staged before LibraConsensusConfig don't have it, the nodes then use the
+    // default config.
+    if stdlib_modules
+        .iter()
+        .any(|module| module.self_id().name().as_str() == CONSENSUS_CONFIG_MODULE_NAME)
+    {
+        setup_consensus_config(&mut genesis_context, &OnChainConsensusConfig::default());
+    }
     reconfigure(&mut genesis_context);
 
     let mut interpreter_context = genesis_context.into_data_store();
@@ -241,6 +255,21 @@ fn setup_vm_config(context: &mut GenesisContext, publishing_option: VMPublishing
     );
 }
 
+fn setup_consensus_config(context: &mut GenesisContext, consensus_config: &OnChainConsensusConfig) {
+    context.set_sender(config_address());
+
+    let config_bytes = lcs::to_bytes(consensus_config).expect("Cannot serialize consensus config");
+    context.exec(
+        CONSENSUS_CONFIG_MODULE_NAME,
+        "initialize",
+        vec![],
+        vec![
+            Value::transaction_argument_signer_reference(config_address()),
+            Value::vector_u8(config_bytes),
+        ],
+    );
+}
+
 fn remove_genesis(stdlib_modules: &[VerifiedModule]) -> impl Iterator<Item = &VerifiedModule> {
     stdlib_modules
         .iter()
@@ -323,6 +352,18 @@ pub fn generate_genesis_type_mapping() -> BTreeMap<Vec<u8>, FatStructType> {
     .1
 }
 
+/// The gas schedule published at genesis, for running Move code outside of a Libra chain
+pub fn initial_gas_schedule() -> CostTable {
+    let (instruction_table, native_table) = &*INITIAL_GAS_SCHEDULE;
+    CostTable {
+        instruction_table: lcs::from_bytes(instruction_table)
+            .expect("Unable to deserialize genesis gas schedule for instructions"),
+        native_table: lcs::from_bytes(native_table)
+            .expect("Unable to deserialize genesis gas schedule for natives"),
+        gas_constants: GasConstants::default(),
+    }
+}
+
 pub fn validator_registrations(node_configs: &[NodeConfig]) -> Vec<ValidatorRegistration> {
     node_configs
         .iter()
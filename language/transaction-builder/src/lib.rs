@@ -6,7 +6,7 @@
 use libra_types::{
     account_address::AccountAddress,
     block_metadata::BlockMetadata,
-    on_chain_config::{LibraVersion, VMPublishingOption},
+    on_chain_config::{LibraVersion, OnChainConsensusConfig, VMPublishingOption},
     transaction::{authenticator::AuthenticationKey, Script, Transaction, TransactionArgument},
 };
 use mirai_annotations::*;
@@ -323,6 +323,18 @@ pub fn encode_publishing_option_script(config: VMPublishingOption) -> Script {
     )
 }
 
+/// Updates the on-chain consensus config, the validators switch to it at the next epoch.
+pub fn encode_update_consensus_config(config: &OnChainConsensusConfig) -> Script {
+    let bytes = lcs::to_bytes(config).expect("Cannot serialize OnChainConsensusConfig");
+    Script::new(
+        StdlibScript::UpdateConsensusConfig
+            .compiled_bytes()
+            .into_vec(),
+        vec![],
+        vec![TransactionArgument::U8Vector(bytes)],
+    )
+}
+
 pub fn encode_update_libra_version(libra_version: LibraVersion) -> Script {
     Script::new(
         StdlibScript::UpdateLibraVersion.compiled_bytes().into_vec(),
@@ -333,7 +345,7 @@ pub fn encode_update_libra_version(libra_version: LibraVersion) -> Script {
 
 // TODO: this should go away once we are no longer using it in tests
 pub fn encode_block_prologue_script(block_metadata: BlockMetadata) -> Transaction {
-    Transaction::BlockMetadata(block_metadata)
+    Transaction::block_metadata(block_metadata)
 }
 
 // TODO: delete and use StdlibScript::try_from directly if it's ok to drop the "_transaction"?
@@ -0,0 +1,226 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compatibility of a new version of a module with the version it replaces.
+//!
+//! A module can be republished (e.g. by a writeset transaction) over an existing one, and both
+//! the values of its types already in global storage and the modules calling it were built
+//! against the old version. The new version is compatible when
+//! - every struct of the old version is still defined, with the same kind, type parameters and
+//!   fields (names and types, in order), so that existing values keep their layout
+//! - every public function of the old version is still public, with the same type parameters,
+//!   parameters and return types, and the same `acquires` list
+//!
+//! Anything else (private functions, new structs or functions, code) may change freely.
+//! Handles and pools are indexed differently in the two versions, so types are compared by the
+//! names of the modules and structs they refer to.
+
+use crate::{
+    access::ModuleAccess,
+    file_format::{
+        CompiledModule, FunctionDefinition, Kind, SignatureIndex, SignatureToken, StructDefinition,
+        StructFieldInformation, StructHandleIndex, TypeParameterIndex,
+    },
+};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// A change in a new version of a module that breaks its existing data or callers
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Incompatibility {
+    StructRemoved(Identifier),
+    /// A struct became a resource or stopped being one
+    StructKindChanged(Identifier),
+    StructTypeParametersChanged(Identifier),
+    /// Fields were added, removed, renamed, reordered or changed type
+    StructFieldsChanged(Identifier),
+    /// A public function was removed or made private
+    PublicFunctionRemoved(Identifier),
+    PublicFunctionSignatureChanged(Identifier),
+    AcquiresChanged(Identifier),
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Incompatibility::*;
+        match self {
+            StructRemoved(n) => write!(f, "struct '{}' was removed", n),
+            StructKindChanged(n) => write!(f, "struct '{}' changed kind (resource or not)", n),
+            StructTypeParametersChanged(n) => write!(f, "struct '{}' changed type parameters", n),
+            StructFieldsChanged(n) => write!(f, "struct '{}' changed fields", n),
+            PublicFunctionRemoved(n) => {
+                write!(f, "public function '{}' was removed or made private", n)
+            }
+            PublicFunctionSignatureChanged(n) => {
+                write!(f, "public function '{}' changed signature", n)
+            }
+            AcquiresChanged(n) => write!(f, "public function '{}' changed acquires list", n),
+        }
+    }
+}
+
+/// Returns all the changes in `new` that are incompatible with `old`, sorted. `new` is
+/// compatible with `old` when there are none.
+pub fn check_compatibility(old: &CompiledModule, new: &CompiledModule) -> Vec<Incompatibility> {
+    let mut incompatibilities = vec![];
+
+    let new_structs = structs_by_name(new);
+    for old_def in old.struct_defs() {
+        let name = struct_name(old, old_def);
+        let new_def = match new_structs.get(&name) {
+            Some(def) => *def,
+            None => {
+                incompatibilities.push(Incompatibility::StructRemoved(name));
+                continue;
+            }
+        };
+        let old_handle = old.struct_handle_at(old_def.struct_handle);
+        let new_handle = new.struct_handle_at(new_def.struct_handle);
+        if old_handle.is_nominal_resource != new_handle.is_nominal_resource {
+            incompatibilities.push(Incompatibility::StructKindChanged(name.clone()));
+        }
+        if old_handle.type_parameters != new_handle.type_parameters {
+            incompatibilities.push(Incompatibility::StructTypeParametersChanged(name.clone()));
+        }
+        if fields(old, old_def) != fields(new, new_def) {
+            incompatibilities.push(Incompatibility::StructFieldsChanged(name));
+        }
+    }
+
+    let new_functions = public_functions_by_name(new);
+    for (name, old_def) in public_functions_by_name(old) {
+        let new_def = match new_functions.get(&name) {
+            Some(def) => *def,
+            None => {
+                incompatibilities.push(Incompatibility::PublicFunctionRemoved(name));
+                continue;
+            }
+        };
+        if signature(old, old_def) != signature(new, new_def) {
+            incompatibilities.push(Incompatibility::PublicFunctionSignatureChanged(
+                name.clone(),
+            ));
+        }
+        if acquires(old, old_def) != acquires(new, new_def) {
+            incompatibilities.push(Incompatibility::AcquiresChanged(name));
+        }
+    }
+
+    incompatibilities.sort();
+    incompatibilities
+}
+
+//**************************************************************************************************
+// Module independent views
+//**************************************************************************************************
+
+/// A type as found in a signature, with structs referred to by name instead of by handle
+#[derive(Debug, Eq, PartialEq)]
+enum Type {
+    Bool,
+    U8,
+    U64,
+    U128,
+    Address,
+    Signer,
+    Vector(Box<Type>),
+    Struct(ModuleId, Identifier, Vec<Type>),
+    Reference(Box<Type>),
+    MutableReference(Box<Type>),
+    TypeParameter(TypeParameterIndex),
+}
+
+impl Type {
+    fn new(m: &CompiledModule, token: &SignatureToken) -> Self {
+        use SignatureToken as S;
+        let struct_type = |idx: StructHandleIndex, type_arguments: &[SignatureToken]| {
+            let handle = m.struct_handle_at(idx);
+            let module = m.module_id_for_handle(m.module_handle_at(handle.module));
+            let name = m.identifier_at(handle.name).to_owned();
+            let type_arguments = type_arguments.iter().map(|t| Type::new(m, t)).collect();
+            Type::Struct(module, name, type_arguments)
+        };
+        match token {
+            S::Bool => Type::Bool,
+            S::U8 => Type::U8,
+            S::U64 => Type::U64,
+            S::U128 => Type::U128,
+            S::Address => Type::Address,
+            S::Signer => Type::Signer,
+            S::Vector(t) => Type::Vector(Box::new(Type::new(m, t))),
+            S::Struct(idx) => struct_type(*idx, &[]),
+            S::StructInstantiation(idx, type_arguments) => struct_type(*idx, type_arguments),
+            S::Reference(t) => Type::Reference(Box::new(Type::new(m, t))),
+            S::MutableReference(t) => Type::MutableReference(Box::new(Type::new(m, t))),
+            S::TypeParameter(idx) => Type::TypeParameter(*idx),
+        }
+    }
+}
+
+/// Type parameters, parameters and return types
+type FunctionSignature = (Vec<Kind>, Vec<Type>, Vec<Type>);
+
+fn struct_name(m: &CompiledModule, def: &StructDefinition) -> Identifier {
+    m.identifier_at(m.struct_handle_at(def.struct_handle).name)
+        .to_owned()
+}
+
+fn structs_by_name(m: &CompiledModule) -> BTreeMap<Identifier, &StructDefinition> {
+    m.struct_defs()
+        .iter()
+        .map(|def| (struct_name(m, def), def))
+        .collect()
+}
+
+/// `None` for native structs
+fn fields(m: &CompiledModule, def: &StructDefinition) -> Option<Vec<(Identifier, Type)>> {
+    match &def.field_information {
+        StructFieldInformation::Native => None,
+        StructFieldInformation::Declared(fields) => Some(
+            fields
+                .iter()
+                .map(|field| {
+                    let name = m.identifier_at(field.name).to_owned();
+                    (name, Type::new(m, &field.signature.0))
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn public_functions_by_name(m: &CompiledModule) -> BTreeMap<Identifier, &FunctionDefinition> {
+    m.function_defs()
+        .iter()
+        .filter(|def| def.is_public())
+        .map(|def| {
+            let handle = m.function_handle_at(def.function);
+            (m.identifier_at(handle.name).to_owned(), def)
+        })
+        .collect()
+}
+
+fn signature(m: &CompiledModule, def: &FunctionDefinition) -> FunctionSignature {
+    let handle = m.function_handle_at(def.function);
+    let types = |idx: SignatureIndex| -> Vec<Type> {
+        m.signature_at(idx)
+            .0
+            .iter()
+            .map(|t| Type::new(m, t))
+            .collect()
+    };
+    (
+        handle.type_parameters.clone(),
+        types(handle.parameters),
+        types(handle.return_),
+    )
+}
+
+fn acquires(m: &CompiledModule, def: &FunctionDefinition) -> BTreeSet<Identifier> {
+    def.acquires_global_resources
+        .iter()
+        .map(|idx| struct_name(m, m.struct_def_at(*idx)))
+        .collect()
+}
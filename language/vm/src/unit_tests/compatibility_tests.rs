@@ -0,0 +1,134 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    compatibility::{check_compatibility, Incompatibility},
+    file_format::*,
+};
+use move_core_types::identifier::Identifier;
+
+// module <SELF> {
+//     struct Bar { x: u64 }
+//
+//     public foo() {
+//     }
+// }
+fn old_module() -> CompiledModuleMut {
+    let mut m = basic_test_module();
+    m.function_defs[0].is_public = true;
+    m
+}
+
+fn check(old: CompiledModuleMut, new: CompiledModuleMut) -> Vec<Incompatibility> {
+    check_compatibility(&old.freeze().unwrap(), &new.freeze().unwrap())
+}
+
+fn ident(s: &str) -> Identifier {
+    Identifier::new(s.to_string()).unwrap()
+}
+
+#[test]
+fn same_module() {
+    assert_eq!(check(old_module(), old_module()), vec![]);
+}
+
+#[test]
+fn additions_are_compatible() {
+    let mut new = old_module();
+    new.function_handles.push(FunctionHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(new.identifiers.len() as u16),
+        parameters: SignatureIndex(0),
+        return_: SignatureIndex(0),
+        type_parameters: vec![],
+    });
+    new.identifiers.push(ident("bar"));
+    new.function_defs.push(FunctionDefinition {
+        function: FunctionHandleIndex(1),
+        is_public: true,
+        acquires_global_resources: vec![],
+        code: Some(CodeUnit::default()),
+    });
+    assert_eq!(check(old_module(), new), vec![]);
+}
+
+#[test]
+fn struct_changes() {
+    let mut removed = old_module();
+    removed.identifiers[2] = ident("Baz");
+    assert_eq!(
+        check(old_module(), removed),
+        vec![Incompatibility::StructRemoved(ident("Bar"))]
+    );
+
+    let mut resource = old_module();
+    resource.struct_handles[0].is_nominal_resource = true;
+    assert_eq!(
+        check(old_module(), resource),
+        vec![Incompatibility::StructKindChanged(ident("Bar"))]
+    );
+
+    let mut generic = old_module();
+    generic.struct_handles[0].type_parameters = vec![Kind::All];
+    assert_eq!(
+        check(old_module(), generic),
+        vec![Incompatibility::StructTypeParametersChanged(ident("Bar"))]
+    );
+
+    let mut retyped = old_module();
+    retyped.struct_defs[0].field_information =
+        StructFieldInformation::Declared(vec![FieldDefinition {
+            name: IdentifierIndex(3),
+            signature: TypeSignature(SignatureToken::U128),
+        }]);
+    assert_eq!(
+        check(old_module(), retyped),
+        vec![Incompatibility::StructFieldsChanged(ident("Bar"))]
+    );
+
+    let mut native = old_module();
+    native.struct_defs[0].field_information = StructFieldInformation::Native;
+    assert_eq!(
+        check(old_module(), native),
+        vec![Incompatibility::StructFieldsChanged(ident("Bar"))]
+    );
+}
+
+#[test]
+fn public_function_changes() {
+    let mut private = old_module();
+    private.function_defs[0].is_public = false;
+    assert_eq!(
+        check(old_module(), private),
+        vec![Incompatibility::PublicFunctionRemoved(ident("foo"))]
+    );
+
+    let mut resignatured = old_module();
+    resignatured
+        .signatures
+        .push(Signature(vec![SignatureToken::U64]));
+    resignatured.function_handles[0].parameters = SignatureIndex(1);
+    assert_eq!(
+        check(old_module(), resignatured),
+        vec![Incompatibility::PublicFunctionSignatureChanged(ident(
+            "foo"
+        ))]
+    );
+
+    let mut acquiring = old_module();
+    acquiring.function_defs[0].acquires_global_resources = vec![StructDefinitionIndex(0)];
+    assert_eq!(
+        check(old_module(), acquiring),
+        vec![Incompatibility::AcquiresChanged(ident("foo"))]
+    );
+}
+
+#[test]
+fn private_functions_can_change() {
+    let mut old = old_module();
+    old.function_defs[0].is_public = false;
+    let mut new = basic_test_module();
+    new.signatures.push(Signature(vec![SignatureToken::U64]));
+    new.function_handles[0].parameters = SignatureIndex(1);
+    assert_eq!(check(old, new), vec![]);
+}
@@ -3,7 +3,7 @@
 
 #![forbid(unsafe_code)]
 
-use libra_config::config::NodeConfig;
+use libra_config::config::{NodeConfig, RootPath};
 use libra_types::PeerId;
 use std::{
     path::PathBuf,
@@ -31,7 +31,16 @@ static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 fn main() {
     let args = Args::from_args();
 
-    let mut config = NodeConfig::load(args.config).expect("Failed to load node config");
+    let mut config = NodeConfig::load(&args.config).expect("Failed to load node config");
+    // The peers files are reloaded while the node runs, relative to the node config.
+    let root_dir = RootPath::new(&args.config);
+    for network in config
+        .validator_network
+        .iter_mut()
+        .chain(config.full_node_networks.iter_mut())
+    {
+        network.resolve_peers_files(&root_dir);
+    }
     println!("Using node config {:?}", &config);
     crash_handler::setup_panic_handler();
 
@@ -54,7 +63,8 @@ fn main() {
         }
     }
 
-    let _node_handle = libra_node::main_node::setup_environment(&mut config);
+    let _node_handle =
+        libra_node::main_node::setup_environment(&mut config).expect("Failed to set up the node");
 
     let term = Arc::new(AtomicBool::new(false));
 
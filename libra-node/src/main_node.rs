@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use admission_control_service::admission_control_service::AdmissionControlService;
+use anyhow::{Context, Result};
 use backup_service::start_backup_service;
 use consensus::consensus_provider::start_consensus;
 use debug_interface::node_debug_service::NodeDebugService;
@@ -19,14 +20,20 @@ use libra_metrics::metric_server;
 use libra_types::{on_chain_config::ON_CHAIN_CONFIG_REGISTRY, waypoint::Waypoint, PeerId};
 use libra_vm::LibraVM;
 use libradb::LibraDB;
-use network::validator_network::network_builder::{AuthenticationMode, NetworkBuilder};
+use network::{
+    capture::TrafficRecorder,
+    peer_manager::admission::InboundConnectionLimits,
+    validator_network::network_builder::{AuthenticationMode, NetworkBuilder},
+    PeerMetadata, ProtocolId, ProtocolRateLimit, ProtocolRateLimits,
+};
 use network_simple_onchain_discovery::ConfigurationChangeListener;
 use onchain_discovery::{client::OnchainDiscovery, service::OnchainDiscoveryService};
 use state_synchronizer::StateSynchronizer;
 use std::{
     boxed::Box,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     net::ToSocketAddrs,
+    path::PathBuf,
     sync::Arc,
     thread,
     time::{Duration, Instant},
@@ -86,6 +93,7 @@ pub fn setup_onchain_discovery(
     let onchain_discovery_service = OnchainDiscoveryService::new(
         executor.clone(),
         peer_mgr_notifs_rx,
+        network_tx.clone(),
         Arc::clone(&libra_db),
         max_concurrent_inbound_queries,
     );
@@ -116,7 +124,8 @@ pub fn setup_network(
     role: RoleType,
     libra_db: Arc<dyn DbReader>,
     waypoint: Waypoint,
-) -> (Runtime, NetworkBuilder) {
+    peer_metadata: PeerMetadata,
+) -> Result<(Runtime, NetworkBuilder)> {
     let runtime = Builder::new()
         .thread_name("network-")
         .threaded_scheduler()
@@ -130,7 +139,56 @@ pub fn setup_network(
         role,
         config.listen_address.clone(),
     );
-    network_builder.add_connection_monitoring();
+    network_builder
+        .peer_metadata(peer_metadata)
+        .add_connection_monitoring();
+    let protocol_rate_limits: ProtocolRateLimits = config
+        .protocol_rate_limits
+        .iter()
+        .map(|(protocol, limit)| {
+            let protocol = protocol
+                .parse::<ProtocolId>()
+                .context("Invalid protocol_rate_limits")?;
+            let limit = ProtocolRateLimit {
+                inbound_bytes_per_sec: limit.inbound_bytes_per_sec,
+                outbound_bytes_per_sec: limit.outbound_bytes_per_sec,
+            };
+            Ok((protocol, limit))
+        })
+        .collect::<Result<_>>()?;
+    let compression_thresholds: HashMap<_, _> = config
+        .compression_thresholds
+        .iter()
+        .map(|(protocol, min_size)| {
+            let protocol = protocol
+                .parse::<ProtocolId>()
+                .context("Invalid compression_thresholds")?;
+            Ok((protocol, *min_size))
+        })
+        .collect::<Result<_>>()?;
+    // The trusted peers are always preferred, on validators they follow the on-chain validator
+    // set. Seed peers are added by the NetworkBuilder.
+    let preferred_inbound_peers = if role == RoleType::Validator {
+        HashSet::new()
+    } else {
+        config.network_peers.peers.keys().cloned().collect()
+    };
+    network_builder
+        .inbound_connection_limits(InboundConnectionLimits {
+            max_connections: config.max_inbound_connections,
+            max_connections_per_ip: config.max_inbound_connections_per_ip,
+            max_connections_per_subnet: config.max_inbound_connections_per_subnet,
+            max_handshakes_per_sec: config.max_inbound_handshakes_per_sec,
+        })
+        .preferred_inbound_peers(preferred_inbound_peers)
+        .peer_reputation(config.peer_reputation)
+        .protocol_rate_limits(protocol_rate_limits)
+        .compression_thresholds(compression_thresholds);
+    if let Some(path) = &config.traffic_capture_file {
+        network_builder.traffic_recorder(
+            TrafficRecorder::new(path).expect("Failed to create traffic capture file"),
+        );
+    }
 
     // TODO(philiphayes): it might make more sense to refactor this so we have a
     // config per "archetype"?
@@ -178,6 +236,25 @@ pub fn setup_network(
             .connectivity_check_interval_ms(config.connectivity_check_interval_ms)
             // TODO:  Why is the connectivity manager related to remote_authentication?
             .add_connectivity_manager();
+        if !config.dns_seeds.is_empty() {
+            network_builder
+                .add_dns_discovery(config.dns_seeds.clone(), config.dns_discovery_interval_ms);
+        }
+        // The eligible nodes of validators are the on-chain validator set, not the peers files.
+        if config.peers_reload_interval_ms > 0 && role != RoleType::Validator {
+            let peers_file = |path: &PathBuf| {
+                if path.as_os_str().is_empty() {
+                    None
+                } else {
+                    Some(path.clone())
+                }
+            };
+            network_builder.add_peers_file_watcher(
+                peers_file(&config.network_peers_file),
+                peers_file(&config.seed_peers_file),
+                config.peers_reload_interval_ms,
+            );
+        }
     } else if config.enable_noise {
         let identity_key = config
             .identity_keypair
@@ -217,10 +294,10 @@ pub fn setup_network(
         DiscoveryMethod::None => {}
     }
 
-    (runtime, network_builder)
+    Ok((runtime, network_builder))
 }
 
-pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
+pub fn setup_environment(node_config: &mut NodeConfig) -> Result<LibraHandle> {
     crash_handler::setup_panic_handler();
 
     // Some of our code uses the rayon global thread pool. Name the rayon threads so it doesn't
@@ -265,6 +342,8 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
     let mut mempool_network_handles = vec![];
     let mut validator_network_provider = None;
     let mut reconfig_subscriptions = vec![];
+    // The health checkers of all networks measure the peers for state sync and mempool.
+    let peer_metadata = PeerMetadata::new();
 
     let (mempool_reconfig_subscription, mempool_reconfig_events) =
         ReconfigSubscription::subscribe(MEMPOOL_SUBSCRIBED_CONFIGS);
@@ -280,7 +359,8 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
             RoleType::Validator,
             Arc::clone(&db_rw.reader),
             node_config.base.waypoint.expect("No waypoint in config"),
-        );
+            peer_metadata.clone(),
+        )?;
 
         // Set up to listen for network configuration changes from StateSync.
         if let Some(conn_mgr_reqs_tx) = network_builder.conn_mgr_reqs_tx() {
@@ -312,7 +392,8 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
             RoleType::FullNode,
             Arc::clone(&db_rw.reader),
             node_config.base.waypoint.expect("No waypoint in config"),
-        );
+            peer_metadata.clone(),
+        )?;
 
         network_runtimes.push(runtime);
         let (state_sync_sender, state_sync_events) =
@@ -329,7 +410,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
         mempool_network_handles.push((full_node_network.peer_id, mempool_sender, mempool_events));
 
         // Start the network provider.
-        let _listen_addr = network_builder.build();
+        let _listen_addr = network_builder.build()?;
         debug!("Network started for peer_id: {}", full_node_network.peer_id);
     }
 
@@ -346,6 +427,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
         chunk_executor,
         &node_config,
         reconfig_subscriptions,
+        peer_metadata.clone(),
     );
     let (mp_client_sender, mp_client_events) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
 
@@ -368,6 +450,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
         consensus_requests,
         state_sync_requests,
         mempool_reconfig_events,
+        peer_metadata,
     );
     debug!("Mempool started in {} ms", instant.elapsed().as_millis());
 
@@ -383,7 +466,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
         // consensus initialization async instead of blocking on state synchronizer.
         let (consensus_network_sender, consensus_network_events) =
             consensus::network_interface::add_to_network(&mut network_builder);
-        let _listen_addr = network_builder.build();
+        let _listen_addr = network_builder.build()?;
         network_runtimes.push(runtime);
         debug!("Network started for peer_id: {}", peer_id);
 
@@ -421,7 +504,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
         metric_server::start_server(public_metric_host, public_metrics_port, true)
     });
 
-    LibraHandle {
+    Ok(LibraHandle {
         _network_runtimes: network_runtimes,
         _ac: admission_control_runtime,
         _rpc: rpc_runtime,
@@ -430,5 +513,5 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
         _consensus_runtime: consensus_runtime,
         _debug: debug_if,
         _backup: backup_service,
-    }
+    })
 }
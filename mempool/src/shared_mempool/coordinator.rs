@@ -161,6 +161,13 @@ pub(crate) async fn coordinator<V>(
                         security_log(SecurityEvent::InvalidNetworkEventMP)
                             .error(&e)
                             .log();
+                        if let Some(misbehavior) = e.misbehavior() {
+                            if let Some(network_sender) = smp.network_senders.get_mut(&network_id) {
+                                if let Err(err) = network_sender.report_misbehavior(misbehavior.peer_id, misbehavior.misbehavior) {
+                                    error!("[shared mempool] failed to report misbehavior: {:?}", err);
+                                }
+                            }
+                        }
                     }
                 };
             },
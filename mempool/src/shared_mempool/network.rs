@@ -8,7 +8,7 @@ use channel::message_queues::QueueStyle;
 use libra_types::{transaction::SignedTransaction, PeerId};
 use network::{
     error::NetworkError,
-    peer_manager::{ConnectionRequestSender, PeerManagerRequestSender},
+    peer_manager::{reputation::Misbehavior, ConnectionRequestSender, PeerManagerRequestSender},
     protocols::network::{NetworkEvents, NetworkSender},
     validator_network::network_builder::NetworkBuilder,
     ProtocolId,
@@ -97,4 +97,13 @@ impl MempoolNetworkSender {
         let protocol = ProtocolId::MempoolDirectSend;
         self.inner.send_to(recipient, protocol, message)
     }
+
+    /// Report the misbehavior of a peer to the network layer.
+    pub fn report_misbehavior(
+        &mut self,
+        peer: PeerId,
+        misbehavior: Misbehavior,
+    ) -> Result<(), NetworkError> {
+        self.inner.report_misbehavior(peer, misbehavior)
+    }
 }
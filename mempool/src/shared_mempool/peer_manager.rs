@@ -2,7 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use libra_config::config::{PeerNetworkId, UpstreamConfig};
-use std::{collections::HashMap, sync::Mutex};
+use network::PeerMetadata;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
 
 /// stores only peers that receive txns from this node
 pub(crate) type PeerInfo = HashMap<PeerNetworkId, PeerSyncState>;
@@ -20,14 +24,24 @@ pub(crate) struct PeerManager {
     upstream_config: UpstreamConfig,
     peer_info: Mutex<PeerInfo>,
     min_broadcast_recipient_count: usize,
+    peer_metadata: PeerMetadata,
+    /// The fallback peers picked as broadcast recipients, with the `PeerMetadata::version` they
+    /// were picked at. `None` once the peers changed.
+    picked_fallbacks: Mutex<Option<(u64, HashSet<PeerNetworkId>)>>,
 }
 
 impl PeerManager {
-    pub fn new(upstream_config: UpstreamConfig, min_broadcast_recipient_count: usize) -> Self {
+    pub fn new(
+        upstream_config: UpstreamConfig,
+        min_broadcast_recipient_count: usize,
+        peer_metadata: PeerMetadata,
+    ) -> Self {
         Self {
             upstream_config,
             peer_info: Mutex::new(PeerInfo::new()),
             min_broadcast_recipient_count,
+            peer_metadata,
+            picked_fallbacks: Mutex::new(None),
         }
     }
 
@@ -47,6 +61,8 @@ impl PeerManager {
                 })
                 .is_alive = true;
         }
+        drop(peer_info);
+        self.invalidate_picked_fallbacks();
         is_new_peer
     }
 
@@ -59,6 +75,7 @@ impl PeerManager {
         {
             state.is_alive = false;
         }
+        self.invalidate_picked_fallbacks();
     }
 
     pub fn update_peer_broadcast(&self, peer: PeerNetworkId, timeline_id: u64) {
@@ -90,23 +107,65 @@ impl PeerManager {
 
     // checks whether a peer is a chosen broadcast recipient:
     // - all primary peers
-    // - fallback peers, if k-policy is enabled
-    // this does NOT check for whether this peer is alive
+    // - the k live fallback peers with the best round-trip times and ping success rates, if
+    //   k-policy is enabled and no primary peer is alive
+    // this does NOT check for whether a primary peer is alive
     pub fn is_picked_peer(&self, peer: PeerNetworkId) -> bool {
         if self.is_primary_upstream_peer(peer) {
             return true;
         }
+        if self.min_broadcast_recipient_count == 0 {
+            return false;
+        }
+
+        // The picks only change with the peers and their measurements.
+        let version = self.peer_metadata.version();
+        let mut picked_fallbacks = self
+            .picked_fallbacks
+            .lock()
+            .expect("failed to acquire picked fallbacks lock");
+        match &*picked_fallbacks {
+            Some((picked_version, _)) if *picked_version == version => (),
+            _ => *picked_fallbacks = Some((version, self.pick_fallbacks())),
+        }
+        picked_fallbacks
+            .as_ref()
+            .map_or(false, |(_, fallbacks)| fallbacks.contains(&peer))
+    }
 
-        let no_live_primaries = self
+    // the k live fallback peers with the best round-trip times and ping success rates, none if a
+    // primary peer is alive
+    fn pick_fallbacks(&self) -> HashSet<PeerNetworkId> {
+        let peer_info = self
             .peer_info
             .lock()
-            .expect("failed to acquire peer info lock")
+            .expect("failed to acquire peer info lock");
+        let no_live_primaries = peer_info
             .iter()
             .find(|(peer, state)| self.is_primary_upstream_peer(**peer) && state.is_alive)
             .is_none();
+        if !no_live_primaries {
+            return HashSet::new();
+        }
 
-        // for fallback peers, broadcast if k-policy is on
-        // TODO change from sending to k fallback peers instead of sending to all fallback peers
-        no_live_primaries && self.min_broadcast_recipient_count > 0
+        let mut live_fallbacks: Vec<_> = peer_info
+            .iter()
+            .filter(|(peer, state)| state.is_alive && !self.is_primary_upstream_peer(**peer))
+            .map(|(peer, _state)| *peer)
+            .collect();
+        drop(peer_info);
+        self.peer_metadata.sort_by_preference(&mut live_fallbacks);
+        live_fallbacks
+            .into_iter()
+            .take(self.min_broadcast_recipient_count)
+            .collect()
+    }
+
+    // the picks must be made again once the peers change
+    fn invalidate_picked_fallbacks(&self) {
+        *self
+            .picked_fallbacks
+            .lock()
+            .expect("failed to acquire picked fallbacks lock") = None;
     }
 }
@@ -19,6 +19,7 @@ use futures::channel::{
 };
 use libra_config::config::NodeConfig;
 use libra_types::{on_chain_config::OnChainConfigPayload, transaction::SignedTransaction, PeerId};
+use network::PeerMetadata;
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex, RwLock},
@@ -46,6 +47,7 @@ pub(crate) fn start_shared_mempool<V>(
     db: Arc<dyn DbReader>,
     validator: Arc<RwLock<V>>,
     subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
+    peer_metadata: PeerMetadata,
 ) where
     V: TransactionValidation + 'static,
 {
@@ -56,6 +58,7 @@ pub(crate) fn start_shared_mempool<V>(
             .mempool
             .shared_mempool_min_broadcast_recipient_count
             .unwrap_or(DEFAULT_MIN_BROADCAST_RECIPIENT_COUNT),
+        peer_metadata,
     ));
     let config_clone = config.clone_for_template();
 
@@ -104,6 +107,7 @@ pub fn bootstrap(
     consensus_requests: Receiver<ConsensusRequest>,
     state_sync_requests: Receiver<CommitNotification>,
     mempool_reconfig_events: libra_channel::Receiver<(), OnChainConfigPayload>,
+    peer_metadata: PeerMetadata,
 ) -> Runtime {
     let runtime = Builder::new()
         .thread_name("shared-mem-")
@@ -125,6 +129,7 @@ pub fn bootstrap(
         db,
         vm_validator,
         vec![],
+        peer_metadata,
     );
     runtime
 }
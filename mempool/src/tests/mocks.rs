@@ -12,8 +12,9 @@ use channel::{self, libra_channel, message_queues::QueueStyle};
 use futures::channel::{mpsc, oneshot};
 use libra_config::config::{NetworkConfig, NodeConfig};
 use libra_types::{mempool_status::MempoolStatusCode, transaction::SignedTransaction, PeerId};
-use network::peer_manager::{
-    conn_notifs_channel, ConnectionRequestSender, PeerManagerRequestSender,
+use network::{
+    peer_manager::{conn_notifs_channel, ConnectionRequestSender, PeerManagerRequestSender},
+    PeerMetadata,
 };
 use std::{
     num::NonZeroUsize,
@@ -92,6 +93,7 @@ impl MockSharedMempool {
             Arc::new(MockDbReader),
             Arc::new(RwLock::new(MockVMValidator)),
             vec![],
+            PeerMetadata::new(),
         );
 
         Self {
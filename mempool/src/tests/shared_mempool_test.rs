@@ -28,7 +28,7 @@ use network::{
         conn_notifs_channel, ConnectionNotification, ConnectionRequestSender,
         PeerManagerNotification, PeerManagerRequest, PeerManagerRequestSender,
     },
-    DisconnectReason, ProtocolId,
+    DisconnectReason, PeerMetadata, ProtocolId,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -95,6 +95,7 @@ fn init_single_shared_mempool(smp: &mut SharedMempoolNetwork, peer_id: PeerId, c
         Arc::new(MockDbReader),
         Arc::new(RwLock::new(MockVMValidator)),
         vec![sender],
+        PeerMetadata::new(),
     );
 
     smp.mempools.insert(peer_id, mempool);
@@ -161,6 +162,7 @@ fn init_smp_multiple_networks(
         Arc::new(MockDbReader),
         Arc::new(RwLock::new(MockVMValidator)),
         vec![sender],
+        PeerMetadata::new(),
     );
 
     let main_peer_id = network_ids[0];
@@ -0,0 +1,213 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Decodes the network traffic captured by a node with `traffic_capture_file` set, and replays
+//! the messages a peer sent into a single node under test.
+
+use anyhow::{bail, ensure, Context, Result};
+use consensus::network_interface::ConsensusMsg;
+use libra_config::config::{NetworkConfig, NodeConfig};
+use libra_mempool::network::MempoolSyncMsg;
+use libra_network_address::{NetworkAddress, Protocol};
+use libra_types::{transaction::SignedTransaction, PeerId};
+use network::{
+    capture::{replay_inbound, CaptureReader, CapturedMessage, Direction},
+    protocols::{health_checker::HealthCheckerMsg, wire::messaging::v1::NetworkMessage},
+    ProtocolId,
+};
+use serde::de::DeserializeOwned;
+use state_synchronizer::network::StateSynchronizerMsg;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+use structopt::StructOpt;
+use tokio::runtime::Runtime;
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Decode and replay the network traffic captured by a Libra node")]
+enum Command {
+    /// Print the captured messages, with the payloads of the known protocols decoded
+    #[structopt(name = "decode")]
+    Decode {
+        /// The capture file
+        #[structopt(parse(from_os_str))]
+        capture: PathBuf,
+        /// Only print the messages exchanged with this peer
+        #[structopt(long)]
+        peer: Option<PeerId>,
+    },
+    /// Start a node and send it the RPC requests and DirectSend messages a peer sent in a capture
+    #[structopt(name = "replay")]
+    Replay {
+        /// The capture file
+        #[structopt(parse(from_os_str))]
+        capture: PathBuf,
+        /// The node config of the node under test. Its network must listen on a /memory/<port>
+        /// address with noise disabled
+        #[structopt(long, parse(from_os_str))]
+        config: PathBuf,
+        /// The peer whose messages are replayed
+        #[structopt(long)]
+        peer: PeerId,
+        /// Space the messages as they were captured instead of sending them as fast as possible
+        #[structopt(long)]
+        realtime: bool,
+        /// How long the node keeps running after the replay, in seconds
+        #[structopt(long, default_value = "10")]
+        linger_secs: u64,
+    },
+}
+
+/// The payload of the blocks proposed by Libra validators.
+type Payload = Vec<SignedTransaction>;
+
+fn read_capture(path: &Path) -> Result<Vec<CapturedMessage>> {
+    CaptureReader::open(path)
+        .with_context(|| format!("Unable to open capture file {:?}", path))?
+        .collect::<Result<_>>()
+        .with_context(|| format!("Unable to read capture file {:?}", path))
+}
+
+fn decode_as<T: DeserializeOwned + Debug>(bytes: &[u8]) -> String {
+    match lcs::from_bytes::<T>(bytes) {
+        Ok(message) => format!("{:?}", message),
+        Err(err) => format!("<undecodable: {}> {}", err, hex::encode(bytes)),
+    }
+}
+
+fn decode_payload(protocol: Option<ProtocolId>, bytes: &[u8]) -> String {
+    match protocol {
+        Some(ProtocolId::ConsensusRpc) | Some(ProtocolId::ConsensusDirectSend) => {
+            decode_as::<ConsensusMsg<Payload>>(bytes)
+        }
+        Some(ProtocolId::MempoolDirectSend) => decode_as::<MempoolSyncMsg>(bytes),
+        Some(ProtocolId::StateSynchronizerDirectSend) => decode_as::<StateSynchronizerMsg>(bytes),
+        Some(ProtocolId::HealthCheckerRpc) => decode_as::<HealthCheckerMsg>(bytes),
+        _ => hex::encode(bytes),
+    }
+}
+
+fn decode(path: &Path, peer: Option<PeerId>) -> Result<()> {
+    // The protocols of the RPC requests in flight, to decode their responses.
+    let mut rpc_protocols = HashMap::new();
+    for record in read_capture(path)? {
+        if peer.map_or(false, |peer| peer != record.peer_id) {
+            continue;
+        }
+        let arrow = match record.direction {
+            Direction::Inbound => "<-",
+            Direction::Outbound => "->",
+        };
+        let header = format!("{} {} {}", record.timestamp_micros, arrow, record.peer_id);
+        match &record.message {
+            NetworkMessage::RpcRequest(request) => {
+                rpc_protocols.insert(
+                    (record.peer_id, record.direction, request.request_id),
+                    request.protocol_id,
+                );
+                println!(
+                    "{} RpcRequest {} #{}: {}",
+                    header,
+                    request.protocol_id,
+                    request.request_id,
+                    decode_payload(Some(request.protocol_id), &request.raw_request)
+                );
+            }
+            NetworkMessage::RpcResponse(response) => {
+                let request_direction = match record.direction {
+                    Direction::Inbound => Direction::Outbound,
+                    Direction::Outbound => Direction::Inbound,
+                };
+                let protocol = rpc_protocols
+                    .remove(&(record.peer_id, request_direction, response.request_id))
+                    .or(record.protocol);
+                println!(
+                    "{} RpcResponse {} #{}: {}",
+                    header,
+                    protocol.map_or("<unknown protocol>", ProtocolId::as_str),
+                    response.request_id,
+                    decode_payload(protocol, &response.raw_response)
+                );
+            }
+            NetworkMessage::DirectSendMsg(message) => println!(
+                "{} DirectSend {}: {}",
+                header,
+                message.protocol_id,
+                decode_payload(Some(message.protocol_id), &message.raw_msg)
+            ),
+            message => println!("{} {:?}", header, message),
+        }
+    }
+    Ok(())
+}
+
+/// The network of the node under test: its validator network, or else its first full node
+/// network.
+fn network_under_test(config: &NodeConfig) -> Result<&NetworkConfig> {
+    let network = match config
+        .validator_network
+        .as_ref()
+        .or_else(|| config.full_node_networks.first())
+    {
+        Some(network) => network,
+        None => bail!("The node config has no network"),
+    };
+    ensure!(
+        !network.enable_noise,
+        "Replay requires a network with noise disabled"
+    );
+    match network.listen_address.as_slice() {
+        [Protocol::Memory(_)] => Ok(network),
+        _ => bail!(
+            "Replay requires a network listening on a /memory/<port> address, not {}",
+            network.listen_address
+        ),
+    }
+}
+
+fn replay(
+    path: &Path,
+    config: &Path,
+    peer: PeerId,
+    realtime: bool,
+    linger: Duration,
+) -> Result<()> {
+    let records = read_capture(path)?;
+    let mut config = NodeConfig::load(config)
+        .with_context(|| format!("Unable to load node config {:?}", config))?;
+    let addr: NetworkAddress = network_under_test(&config)?.listen_address.clone();
+
+    libra_logger::Logger::new().init();
+    let _node_handle = libra_node::main_node::setup_environment(&mut config)?;
+
+    let mut rt = Runtime::new()?;
+    let sent = rt.block_on(replay_inbound(records, peer, addr, realtime))?;
+    println!("Replayed {} messages from {}", sent, peer);
+    thread::sleep(linger);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    match Command::from_args() {
+        Command::Decode { capture, peer } => decode(&capture, peer),
+        Command::Replay {
+            capture,
+            config,
+            peer,
+            realtime,
+            linger_secs,
+        } => replay(
+            &capture,
+            &config,
+            peer,
+            realtime,
+            Duration::from_secs(linger_secs),
+        ),
+    }
+}
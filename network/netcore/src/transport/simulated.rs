@@ -0,0 +1,885 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! In-memory transport with simulated network faults, for tests.
+//!
+//! A [`NetworkSimulator`] connects the nodes of a test through in-memory sockets and delivers the
+//! bytes written on a connection to the other end after the delay of the directed link between
+//! the two nodes. Messages of a connection are delivered in order, as on a TCP stream, but messages
+//! on different connections can be reordered.
+//!
+//! Every write on a [`SimulatedSocket`] is delivered as is after the delay of the link, so that
+//! the connection can be set up. Once it is, the connection can be wrapped in a [`FaultySocket`],
+//! which splits the bytes written into the frames of the default `LengthDelimitedCodec` (a
+//! big-endian `u32` length followed by the payload) and applies the faults of the link to whole
+//! frames:
+//!  * drops and duplication of frames, with the probabilities of the link;
+//!  * partitions, which drop all the frames between two groups of nodes and refuse new
+//!  connections between them.
+//!
+//! The `FaultySocket` can sit on top of an encrypted stream over the `SimulatedSocket`: the frames
+//! it drops or duplicates before the encryption are, for the other end, frames lost or duplicated
+//! by the network, while the encrypted stream itself stays intact.
+//!
+//! Time is virtual: nothing is delivered until the clock of the simulator passes the delivery
+//! time, except the messages without delay which are delivered as soon as they are written. Tests
+//! step the clock with [`NetworkSimulator::advance`]. [`Script`]s are applied on the same clock.
+//!
+//! Delays and fault decisions are drawn from random number generators per connection and
+//! direction, seeded from the seed of the simulator and the link, so that a run is reproducible
+//! regardless of the interleaving of the other connections: for the same writes, the same frames
+//! are dropped and duplicated and the messages are delivered in the same order at the same virtual
+//! times.
+
+use crate::transport::Transport;
+use bytes::{Bytes, BytesMut};
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    future::{self, FutureExt},
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    ready,
+    stream::{Stream, StreamExt},
+};
+use libra_network_address::{parse_memory, NetworkAddress, Protocol};
+use memsocket::MemorySocket;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    cmp,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// Length of the header of a frame of the default `LengthDelimitedCodec`.
+const FRAME_HEADER_LEN: usize = 4;
+
+/// Identifier of a node of the simulated network, in the order the nodes are added.
+pub type NodeId = usize;
+
+/// Distribution of the delay of the segments on a link.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DelayDistribution {
+    Fixed(Duration),
+    Uniform { min: Duration, max: Duration },
+    Exponential { mean: Duration },
+}
+
+impl Default for DelayDistribution {
+    fn default() -> Self {
+        DelayDistribution::Fixed(Duration::from_millis(0))
+    }
+}
+
+impl DelayDistribution {
+    fn sample(&self, rng: &mut StdRng) -> Duration {
+        match *self {
+            DelayDistribution::Fixed(delay) => delay,
+            DelayDistribution::Uniform { min, max } => {
+                if max <= min {
+                    return min;
+                }
+                min + (max - min).mul_f64(rng.gen::<f64>())
+            }
+            DelayDistribution::Exponential { mean } => mean.mul_f64(-(1.0 - rng.gen::<f64>()).ln()),
+        }
+    }
+}
+
+/// Configuration of the directed link between two nodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinkConfig {
+    pub delay: DelayDistribution,
+    pub drop_probability: f64,
+    pub duplicate_probability: f64,
+}
+
+/// A change of the simulated network, applied immediately or at a given time of a [`Script`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimulationEvent {
+    /// Sets the configuration of the link from a node to another.
+    SetLink {
+        from: NodeId,
+        to: NodeId,
+        config: LinkConfig,
+    },
+    /// Sets the configuration of the links which don't have one of their own.
+    SetDefaultLink(LinkConfig),
+    /// Cuts the links between two groups of nodes, in both directions.
+    Partition(Vec<NodeId>, Vec<NodeId>),
+    /// Removes all the partitions.
+    Heal,
+}
+
+/// A sequence of events, each applied at a time relative to the start of the script.
+#[derive(Clone, Debug, Default)]
+pub struct Script {
+    events: Vec<(Duration, SimulationEvent)>,
+}
+
+impl Script {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an event to apply at time `at`.
+    pub fn at(mut self, at: Duration, event: SimulationEvent) -> Self {
+        self.events.push((at, event));
+        self
+    }
+}
+
+/// Something to do at a time of the virtual clock.
+enum Scheduled {
+    /// Writes bytes to the other end of a connection, or closes it if `None`.
+    Delivery {
+        writer: u64,
+        bytes: Option<Bytes>,
+    },
+    Event(SimulationEvent),
+}
+
+struct SimulatorState {
+    seed: u64,
+    num_nodes: usize,
+    next_port: u16,
+    listeners: HashMap<u16, (NodeId, UnboundedSender<SimulatedSocket>)>,
+    default_link: LinkConfig,
+    links: HashMap<(NodeId, NodeId), LinkConfig>,
+    partitions: Vec<(HashSet<NodeId>, HashSet<NodeId>)>,
+    /// Number of connections opened on each link, to seed the generator of the next one.
+    num_connections: HashMap<(NodeId, NodeId), u64>,
+    /// The virtual time since the start of the simulation.
+    now: Duration,
+    /// The deliveries and events to come, by time and then in the order they were scheduled.
+    scheduled: BTreeMap<(Duration, u64), Scheduled>,
+    next_seq: u64,
+    /// The sockets writing to the reading end of each direction of the connections.
+    writers: HashMap<u64, MemorySocket>,
+    next_writer: u64,
+}
+
+impl SimulatorState {
+    fn link(&self, from: NodeId, to: NodeId) -> LinkConfig {
+        *self.links.get(&(from, to)).unwrap_or(&self.default_link)
+    }
+
+    fn is_partitioned(&self, from: NodeId, to: NodeId) -> bool {
+        self.partitions.iter().any(|(a, b)| {
+            (a.contains(&from) && b.contains(&to)) || (b.contains(&from) && a.contains(&to))
+        })
+    }
+
+    fn connection_rng(&mut self, from: NodeId, to: NodeId) -> StdRng {
+        let index = self.num_connections.entry((from, to)).or_insert(0);
+        *index += 1;
+        StdRng::seed_from_u64(self.seed ^ ((from as u64) << 48) ^ ((to as u64) << 32) ^ *index)
+    }
+
+    fn add_writer(&mut self, socket: MemorySocket) -> u64 {
+        let writer = self.next_writer;
+        self.next_writer += 1;
+        self.writers.insert(writer, socket);
+        writer
+    }
+
+    fn schedule(&mut self, at: Duration, scheduled: Scheduled) {
+        self.scheduled.insert((at, self.next_seq), scheduled);
+        self.next_seq += 1;
+    }
+
+    fn apply(&mut self, event: SimulationEvent) {
+        match event {
+            SimulationEvent::SetLink { from, to, config } => {
+                self.links.insert((from, to), config);
+            }
+            SimulationEvent::SetDefaultLink(config) => self.default_link = config,
+            SimulationEvent::Partition(a, b) => {
+                self.partitions
+                    .push((a.into_iter().collect(), b.into_iter().collect()));
+            }
+            SimulationEvent::Heal => self.partitions.clear(),
+        }
+    }
+
+    /// Writes to the other end of a connection. The writes to a `MemorySocket` complete
+    /// immediately, unless the other end is gone, in which case the connection is forgotten.
+    fn deliver(&mut self, writer: u64, bytes: Option<Bytes>) {
+        let socket = match self.writers.get_mut(&writer) {
+            Some(socket) => socket,
+            None => return,
+        };
+        let delivered = match bytes {
+            Some(bytes) => matches!(socket.write_all(&bytes).now_or_never(), Some(Ok(()))),
+            None => false,
+        };
+        if !delivered {
+            if let Some(mut socket) = self.writers.remove(&writer) {
+                let _ = socket.close().now_or_never();
+            }
+        }
+    }
+
+    /// Moves the clock to `time`, delivering the messages and applying the events due until then
+    /// in order.
+    fn run_until(&mut self, time: Duration) {
+        while let Some(&(at, seq)) = self.scheduled.keys().next() {
+            if at > time {
+                break;
+            }
+            self.now = cmp::max(self.now, at);
+            match self.scheduled.remove(&(at, seq)).unwrap() {
+                Scheduled::Delivery { writer, bytes } => self.deliver(writer, bytes),
+                Scheduled::Event(event) => self.apply(event),
+            }
+        }
+        self.now = cmp::max(self.now, time);
+    }
+}
+
+/// The simulated network shared by the [`SimulatedTransport`]s of the nodes of a test.
+#[derive(Clone)]
+pub struct NetworkSimulator {
+    state: Arc<Mutex<SimulatorState>>,
+}
+
+impl NetworkSimulator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SimulatorState {
+                seed,
+                num_nodes: 0,
+                next_port: 1,
+                listeners: HashMap::new(),
+                default_link: LinkConfig::default(),
+                links: HashMap::new(),
+                partitions: vec![],
+                num_connections: HashMap::new(),
+                now: Duration::from_millis(0),
+                scheduled: BTreeMap::new(),
+                next_seq: 0,
+                writers: HashMap::new(),
+                next_writer: 0,
+            })),
+        }
+    }
+
+    /// Adds a node to the network, returning the transport the node must use.
+    pub fn add_node(&self) -> SimulatedTransport {
+        let mut state = self.state.lock().unwrap();
+        let node = state.num_nodes;
+        state.num_nodes += 1;
+        SimulatedTransport {
+            simulator: self.clone(),
+            node,
+        }
+    }
+
+    pub fn apply(&self, event: SimulationEvent) {
+        self.state.lock().unwrap().apply(event);
+    }
+
+    /// Schedules the events of the script at their time, starting now.
+    pub fn schedule(&self, script: Script) {
+        let mut state = self.state.lock().unwrap();
+        let start = state.now;
+        for (at, event) in script.events {
+            state.schedule(start + at, Scheduled::Event(event));
+        }
+    }
+
+    /// The virtual time since the start of the simulation.
+    pub fn now(&self) -> Duration {
+        self.state.lock().unwrap().now
+    }
+
+    /// Advances the virtual clock by `duration`, delivering the messages and applying the events
+    /// of the scripts due in the meantime, in order.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let time = state.now + duration;
+        state.run_until(time);
+    }
+}
+
+impl fmt::Debug for NetworkSimulator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NetworkSimulator")
+    }
+}
+
+/// Transport of a node of a [`NetworkSimulator`]. It listens on and dials `/memory/<port>`
+/// addresses, which are private to the simulator.
+#[derive(Clone, Debug)]
+pub struct SimulatedTransport {
+    simulator: NetworkSimulator,
+    node: NodeId,
+}
+
+impl SimulatedTransport {
+    pub fn node(&self) -> NodeId {
+        self.node
+    }
+}
+
+impl Transport for SimulatedTransport {
+    type Output = SimulatedSocket;
+    type Error = io::Error;
+    type Listener = Listener;
+    type Inbound = future::Ready<io::Result<SimulatedSocket>>;
+    type Outbound = future::Ready<io::Result<SimulatedSocket>>;
+
+    fn listen_on(&self, addr: NetworkAddress) -> io::Result<(Self::Listener, NetworkAddress)> {
+        let (port, addr_suffix) =
+            parse_memory(addr.as_slice()).ok_or_else(|| invalid_addr(&addr))?;
+        let mut state = self.simulator.state.lock().unwrap();
+        let port = if port == 0 {
+            while state.listeners.contains_key(&state.next_port) {
+                state.next_port = state.next_port.checked_add(1).unwrap_or(1);
+            }
+            state.next_port
+        } else if state.listeners.contains_key(&port) {
+            return Err(io::ErrorKind::AddrInUse.into());
+        } else {
+            port
+        };
+        let (incoming_tx, incoming) = mpsc::unbounded();
+        state.listeners.insert(port, (self.node, incoming_tx));
+        let actual_addr =
+            NetworkAddress::from(Protocol::Memory(port)).extend_from_slice(addr_suffix);
+        Ok((
+            Listener {
+                simulator: self.simulator.clone(),
+                port,
+                incoming,
+            },
+            actual_addr,
+        ))
+    }
+
+    fn dial(&self, addr: NetworkAddress) -> io::Result<Self::Outbound> {
+        let (port, _addr_suffix) =
+            parse_memory(addr.as_slice()).ok_or_else(|| invalid_addr(&addr))?;
+        let mut state = self.simulator.state.lock().unwrap();
+        let (listener_node, incoming_tx) = state
+            .listeners
+            .get(&port)
+            .map(|(node, incoming_tx)| (*node, incoming_tx.clone()))
+            .ok_or(io::ErrorKind::AddrNotAvailable)?;
+        if state.is_partitioned(self.node, listener_node) {
+            return Err(io::ErrorKind::ConnectionRefused.into());
+        }
+        // One pair of sockets per direction: the simulator writes to one end of a pair after the
+        // delay of the link, the node reads from the other end.
+        let (dialer_reader, listener_writer) = MemorySocket::new_pair();
+        let (listener_reader, dialer_writer) = MemorySocket::new_pair();
+        let dialer_socket = SimulatedSocket {
+            reader: dialer_reader,
+            simulator: self.simulator.clone(),
+            writer: state.add_writer(dialer_writer),
+            from: self.node,
+            to: listener_node,
+            rng: state.connection_rng(self.node, listener_node),
+            last_delivery: state.now,
+            closed: false,
+        };
+        let listener_socket = SimulatedSocket {
+            reader: listener_reader,
+            simulator: self.simulator.clone(),
+            writer: state.add_writer(listener_writer),
+            from: listener_node,
+            to: self.node,
+            rng: state.connection_rng(listener_node, self.node),
+            last_delivery: state.now,
+            closed: false,
+        };
+        drop(state);
+
+        incoming_tx
+            .unbounded_send(listener_socket)
+            .map_err(|_| io::Error::from(io::ErrorKind::ConnectionRefused))?;
+        Ok(future::ready(Ok(dialer_socket)))
+    }
+}
+
+fn invalid_addr(addr: &NetworkAddress) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid NetworkAddress: '{}'", addr),
+    )
+}
+
+#[must_use = "streams do nothing unless polled"]
+pub struct Listener {
+    simulator: NetworkSimulator,
+    port: u16,
+    incoming: UnboundedReceiver<SimulatedSocket>,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.simulator
+            .state
+            .lock()
+            .unwrap()
+            .listeners
+            .remove(&self.port);
+    }
+}
+
+impl Stream for Listener {
+    type Item = io::Result<(future::Ready<io::Result<SimulatedSocket>>, NetworkAddress)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Option<Self::Item>> {
+        // As with the MemoryTransport, the dialer address is not an address to dial.
+        self.incoming.poll_next_unpin(context).map(|socket| {
+            socket.map(|socket| {
+                Ok((
+                    future::ready(Ok(socket)),
+                    NetworkAddress::from(Protocol::Memory(0)),
+                ))
+            })
+        })
+    }
+}
+
+/// One end of a simulated connection. Reads come straight from an in-memory socket, writes are
+/// delivered to the other end by the simulator after the delay of the link.
+pub struct SimulatedSocket {
+    reader: MemorySocket,
+    simulator: NetworkSimulator,
+    /// The id of the socket the simulator writes to the other end with.
+    writer: u64,
+    from: NodeId,
+    to: NodeId,
+    /// Draws the delays of the messages.
+    rng: StdRng,
+    /// The delivery time of the last message, to keep the messages in order.
+    last_delivery: Duration,
+    closed: bool,
+}
+
+impl SimulatedSocket {
+    /// The faults of the link this socket writes on, for a [`FaultySocket`] wrapping the
+    /// connection.
+    pub fn faults(&mut self) -> LinkFaults {
+        LinkFaults {
+            simulator: self.simulator.clone(),
+            from: self.from,
+            to: self.to,
+            rng: StdRng::seed_from_u64(self.rng.gen()),
+        }
+    }
+
+    /// Schedules the delivery of a message, after the delay of the link and the previous message.
+    fn send(&mut self, state: &mut SimulatorState, message: Bytes) {
+        let delay = state.link(self.from, self.to).delay.sample(&mut self.rng);
+        let deliver_at = cmp::max(state.now + delay, self.last_delivery);
+        self.last_delivery = deliver_at;
+        state.schedule(
+            deliver_at,
+            Scheduled::Delivery {
+                writer: self.writer,
+                bytes: Some(message),
+            },
+        );
+    }
+
+    /// Closes the connection once the pending messages are delivered.
+    fn close(&mut self) {
+        if self.closed {
+            return;
+        }
+        self.closed = true;
+        let simulator = self.simulator.clone();
+        let mut state = simulator.state.lock().unwrap();
+        let close_at = cmp::max(state.now, self.last_delivery);
+        state.schedule(
+            close_at,
+            Scheduled::Delivery {
+                writer: self.writer,
+                bytes: None,
+            },
+        );
+        let now = state.now;
+        state.run_until(now);
+    }
+}
+
+/// Splits the first complete frame of the default `LengthDelimitedCodec` off `buf`.
+fn split_frame(buf: &mut BytesMut) -> Option<Bytes> {
+    if buf.len() < FRAME_HEADER_LEN {
+        return None;
+    }
+    let len = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+    if buf.len() < FRAME_HEADER_LEN + len {
+        return None;
+    }
+    Some(buf.split_to(FRAME_HEADER_LEN + len).freeze())
+}
+
+impl Drop for SimulatedSocket {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl fmt::Debug for SimulatedSocket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SimulatedSocket({} -> {})", self.from, self.to)
+    }
+}
+
+impl AsyncRead for SimulatedSocket {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.reader).poll_read(context, buf)
+    }
+}
+
+impl AsyncWrite for SimulatedSocket {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        if this.closed {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        let simulator = this.simulator.clone();
+        let mut state = simulator.state.lock().unwrap();
+        this.send(&mut state, Bytes::copy_from_slice(buf));
+        // The messages without delay are delivered right away.
+        let now = state.now;
+        state.run_until(now);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _context: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Closes the connection once the pending messages are delivered.
+    fn poll_close(mut self: Pin<&mut Self>, _context: &mut Context) -> Poll<io::Result<()>> {
+        self.close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// The faults of a direction of a connection, see [`SimulatedSocket::faults`].
+pub struct LinkFaults {
+    simulator: NetworkSimulator,
+    from: NodeId,
+    to: NodeId,
+    /// Draws the fault decisions of the frames.
+    rng: StdRng,
+}
+
+impl LinkFaults {
+    /// The number of copies of a frame to deliver: none when the frame is dropped or cut by a
+    /// partition, two when it is duplicated.
+    fn copies(&mut self) -> usize {
+        let state = self.simulator.state.lock().unwrap();
+        let link = state.link(self.from, self.to);
+        // The decisions are always drawn, so that they don't depend on the partitions.
+        let dropped = self.rng.gen_bool(link.drop_probability.max(0.0).min(1.0));
+        let duplicated = self
+            .rng
+            .gen_bool(link.duplicate_probability.max(0.0).min(1.0));
+        if dropped || state.is_partitioned(self.from, self.to) {
+            0
+        } else if duplicated {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// A connection whose frames of the default `LengthDelimitedCodec` are subject to the faults of a
+/// simulated link. The frames are written to the inner socket on flush and on close.
+pub struct FaultySocket<TSocket> {
+    inner: TSocket,
+    faults: LinkFaults,
+    /// The bytes of the frame being written.
+    frame: BytesMut,
+    /// The frames to write to `inner`.
+    pending: BytesMut,
+}
+
+impl<TSocket> FaultySocket<TSocket>
+where
+    TSocket: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Wraps a connection over the `SimulatedSocket` `faults` come from. The connection must be at
+    /// the start of a frame.
+    pub fn new(inner: TSocket, faults: LinkFaults) -> Self {
+        Self {
+            inner,
+            faults,
+            frame: BytesMut::new(),
+            pending: BytesMut::new(),
+        }
+    }
+
+    fn poll_write_pending(&mut self, context: &mut Context) -> Poll<io::Result<()>> {
+        while !self.pending.is_empty() {
+            let written = ready!(Pin::new(&mut self.inner).poll_write(context, &self.pending))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            let _ = self.pending.split_to(written);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<TSocket: fmt::Debug> fmt::Debug for FaultySocket<TSocket> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FaultySocket({:?})", self.inner)
+    }
+}
+
+impl<TSocket> AsyncRead for FaultySocket<TSocket>
+where
+    TSocket: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_read(context, buf)
+    }
+}
+
+impl<TSocket> AsyncWrite for FaultySocket<TSocket>
+where
+    TSocket: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        ready!(this.poll_write_pending(context))?;
+        this.frame.extend_from_slice(buf);
+        while let Some(frame) = split_frame(&mut this.frame) {
+            for _ in 0..this.faults.copies() {
+                this.pending.extend_from_slice(&frame);
+            }
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        ready!(self.poll_write_pending(context))?;
+        Pin::new(&mut self.inner).poll_flush(context)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        // An incomplete frame is delivered as is, the other end sees a truncated stream.
+        if !this.frame.is_empty() {
+            let partial = this.frame.split();
+            this.pending.extend_from_slice(&partial);
+        }
+        ready!(this.poll_write_pending(context))?;
+        Pin::new(&mut this.inner).poll_close(context)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::io::AsyncReadExt;
+
+    /// Length of the frames of the tests: a header and a payload of `PAYLOAD_LEN` bytes.
+    const PAYLOAD_LEN: usize = 6;
+    const FRAME_LEN: usize = FRAME_HEADER_LEN + PAYLOAD_LEN;
+
+    /// A frame whose payload is `index` repeated.
+    fn frame(index: u8) -> Vec<u8> {
+        let mut frame = (PAYLOAD_LEN as u32).to_be_bytes().to_vec();
+        frame.extend_from_slice(&[index; PAYLOAD_LEN]);
+        frame
+    }
+
+    /// Adds two nodes and connects the first one to the second one.
+    async fn connect(
+        simulator: &NetworkSimulator,
+    ) -> (SimulatedTransport, SimulatedSocket, SimulatedSocket) {
+        let (a, b) = (simulator.add_node(), simulator.add_node());
+        let (mut listener, addr) = b.listen_on("/memory/0".parse().unwrap()).unwrap();
+        let dialer = a.dial(addr).unwrap().await.unwrap();
+        let (inbound, _) = listener.next().await.unwrap().unwrap();
+        (b, dialer, inbound.await.unwrap())
+    }
+
+    fn with_faults(mut socket: SimulatedSocket) -> FaultySocket<SimulatedSocket> {
+        let faults = socket.faults();
+        FaultySocket::new(socket, faults)
+    }
+
+    /// Reads the frames received until the connection is closed, checking that they are whole,
+    /// and returns their index.
+    async fn receive(socket: &mut SimulatedSocket) -> Vec<u8> {
+        let mut received = vec![];
+        socket.read_to_end(&mut received).await.unwrap();
+        assert_eq!(received.len() % FRAME_LEN, 0);
+        received
+            .chunks(FRAME_LEN)
+            .map(|chunk| {
+                assert_eq!(chunk, &frame(chunk[FRAME_HEADER_LEN])[..]);
+                chunk[FRAME_HEADER_LEN]
+            })
+            .collect()
+    }
+
+    /// Writes `count` frames with faults, in writes of `write_len` bytes regardless of the frames,
+    /// and returns the index of the frames received, in order.
+    async fn exchange(
+        simulator: &NetworkSimulator,
+        config: LinkConfig,
+        count: u8,
+        write_len: usize,
+    ) -> Vec<u8> {
+        simulator.apply(SimulationEvent::SetDefaultLink(config));
+        let (_b, dialer, mut listener_socket) = connect(simulator).await;
+        let mut dialer = with_faults(dialer);
+        let bytes = (0..count).flat_map(frame).collect::<Vec<_>>();
+        for chunk in bytes.chunks(write_len) {
+            dialer.write_all(chunk).await.unwrap();
+            dialer.flush().await.unwrap();
+        }
+        dialer.close().await.unwrap();
+        simulator.advance(Duration::from_secs(1));
+        receive(&mut listener_socket).await
+    }
+
+    #[tokio::test]
+    async fn delays_use_the_virtual_clock() {
+        let simulator = NetworkSimulator::new(0);
+        simulator.apply(SimulationEvent::SetDefaultLink(LinkConfig {
+            delay: DelayDistribution::Uniform {
+                min: Duration::from_millis(1),
+                max: Duration::from_millis(20),
+            },
+            ..LinkConfig::default()
+        }));
+        let (_b, dialer, mut listener_socket) = connect(&simulator).await;
+        let mut dialer = with_faults(dialer);
+        for i in 0..10 {
+            dialer.write_all(&frame(i)).await.unwrap();
+            dialer.flush().await.unwrap();
+        }
+        dialer.close().await.unwrap();
+
+        // nothing is delivered until the clock moves
+        let mut buf = [0; FRAME_LEN];
+        assert!(listener_socket.read(&mut buf).now_or_never().is_none());
+        simulator.advance(Duration::from_millis(20));
+        assert_eq!(
+            receive(&mut listener_socket).await,
+            (0..10).collect::<Vec<_>>()
+        );
+        assert_eq!(simulator.now(), Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn faults_are_reproducible() {
+        let config = LinkConfig {
+            delay: DelayDistribution::Exponential {
+                mean: Duration::from_millis(5),
+            },
+            drop_probability: 0.3,
+            duplicate_probability: 0.3,
+        };
+        let first = exchange(&NetworkSimulator::new(42), config, 100, 7).await;
+        let second = exchange(&NetworkSimulator::new(42), config, 100, 7).await;
+        assert_eq!(first, second);
+        // duplicates are adjacent and the remaining frames are in order
+        let mut deduped = first.clone();
+        deduped.dedup();
+        assert!(deduped.windows(2).all(|w| w[0] < w[1]));
+        assert!(deduped.len() < 100);
+        assert!(deduped.len() < first.len());
+    }
+
+    #[tokio::test]
+    async fn faults_only_apply_once_wrapped() {
+        let simulator = NetworkSimulator::new(0);
+        simulator.apply(SimulationEvent::SetDefaultLink(LinkConfig {
+            drop_probability: 1.0,
+            ..LinkConfig::default()
+        }));
+        let (_b, mut dialer, mut listener_socket) = connect(&simulator).await;
+        dialer.write_all(b"handshake").await.unwrap();
+        let mut dialer = with_faults(dialer);
+        dialer.write_all(&frame(0)).await.unwrap();
+        dialer.close().await.unwrap();
+        let mut received = vec![];
+        listener_socket.read_to_end(&mut received).await.unwrap();
+        assert_eq!(received, b"handshake");
+    }
+
+    #[tokio::test]
+    async fn partitions() {
+        let simulator = NetworkSimulator::new(0);
+        let (b, dialer, mut listener_socket) = connect(&simulator).await;
+        let mut dialer = with_faults(dialer);
+        let c = simulator.add_node();
+        let (_listener, addr) = b.listen_on("/memory/0".parse().unwrap()).unwrap();
+
+        simulator.apply(SimulationEvent::Partition(vec![0, 2], vec![1]));
+        assert_eq!(
+            c.dial(addr.clone()).unwrap_err().kind(),
+            io::ErrorKind::ConnectionRefused
+        );
+        dialer.write_all(&frame(0)).await.unwrap();
+
+        simulator.apply(SimulationEvent::Heal);
+        assert!(c.dial(addr).is_ok());
+        dialer.write_all(&frame(1)).await.unwrap();
+        dialer.close().await.unwrap();
+        assert_eq!(receive(&mut listener_socket).await, vec![1]);
+    }
+
+    #[test]
+    fn scripts() {
+        let simulator = NetworkSimulator::new(0);
+        let config = LinkConfig {
+            drop_probability: 1.0,
+            ..LinkConfig::default()
+        };
+        simulator.advance(Duration::from_millis(5));
+        let script = Script::new()
+            .at(
+                Duration::from_millis(10),
+                SimulationEvent::SetLink {
+                    from: 0,
+                    to: 1,
+                    config,
+                },
+            )
+            .at(Duration::from_millis(0), SimulationEvent::Heal);
+        simulator.schedule(script);
+
+        simulator.advance(Duration::from_millis(9));
+        assert_eq!(
+            simulator.state.lock().unwrap().link(0, 1),
+            LinkConfig::default()
+        );
+        simulator.advance(Duration::from_millis(1));
+        let state = simulator.state.lock().unwrap();
+        assert_eq!(state.link(0, 1), config);
+        assert_eq!(state.link(1, 0), LinkConfig::default());
+    }
+}
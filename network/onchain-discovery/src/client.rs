@@ -24,7 +24,8 @@ use libra_types::{
 };
 use network::{
     connectivity_manager::{ConnectivityRequest, DiscoverySource},
-    peer_manager::{conn_notifs_channel, ConnectionNotification},
+    peer_manager::{conn_notifs_channel, reputation::Misbehavior, ConnectionNotification},
+    protocols::rpc::error::RpcError,
 };
 use option_future::OptionFuture;
 use rand::{rngs::SmallRng, Rng, SeedableRng};
@@ -262,6 +263,9 @@ where
                         req_msg.known_version,
                         err
                     );
+                    if let Some(misbehavior) = Misbehavior::from_verification_error(&err) {
+                        report_misbehavior(&mut self.network_tx, peer_id, misbehavior);
+                    }
                     return;
                 }
             };
@@ -361,6 +365,17 @@ async fn peer_query_discovery_set(
     let res_msg = network_tx
         .query_discovery_set(peer_id, req_msg.clone(), outbound_rpc_timeout)
         .await
+        .map_err(|err| {
+            let misbehavior = match err {
+                RpcError::LcsError(_) => Some(Misbehavior::UndecodableMessage),
+                RpcError::InvalidRpcResponse => Some(Misbehavior::ProtocolViolation),
+                _ => None,
+            };
+            if let Some(misbehavior) = misbehavior {
+                report_misbehavior(&mut network_tx, peer_id, misbehavior);
+            }
+            err
+        })
         .with_context(|| {
             format!(
                 "failed to query peer discovery set: peer: {}, latest_version: {}",
@@ -371,3 +386,17 @@ async fn peer_query_discovery_set(
 
     Ok((peer_id, req_msg, res_msg))
 }
+
+fn report_misbehavior(
+    network_tx: &mut OnchainDiscoveryNetworkSender,
+    peer_id: PeerId,
+    misbehavior: Misbehavior,
+) {
+    if let Err(err) = network_tx.report_misbehavior(peer_id, misbehavior) {
+        warn!(
+            "failed to report misbehavior: peer: {}, err: {:?}",
+            peer_id.short_str(),
+            err
+        );
+    }
+}
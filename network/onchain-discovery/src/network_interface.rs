@@ -8,9 +8,10 @@ use futures::{channel::mpsc, sink::SinkExt};
 use libra_types::PeerId;
 use network::{
     connectivity_manager::ConnectivityRequest,
+    error::NetworkError,
     peer_manager::{
-        conn_notifs_channel, ConnectionRequestSender, PeerManagerNotification,
-        PeerManagerRequestSender,
+        conn_notifs_channel, reputation::Misbehavior, ConnectionRequestSender,
+        PeerManagerNotification, PeerManagerRequestSender,
     },
     protocols::{network::NetworkSender, rpc::error::RpcError},
     validator_network::network_builder::{NetworkBuilder, NETWORK_CHANNEL_SIZE},
@@ -60,6 +61,15 @@ impl OnchainDiscoveryNetworkSender {
         Ok(res_msg)
     }
 
+    /// Report the misbehavior of a peer, the network layer bans peers that misbehave repeatedly.
+    pub fn report_misbehavior(
+        &mut self,
+        peer: PeerId,
+        misbehavior: Misbehavior,
+    ) -> Result<(), NetworkError> {
+        self.network_sender.report_misbehavior(peer, misbehavior)
+    }
+
     pub async fn send_connectivity_request(
         &mut self,
         req: ConnectivityRequest,
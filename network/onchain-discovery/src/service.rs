@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    network_interface::OnchainDiscoveryNetworkSender,
     storage_query_discovery_set_async,
     types::{OnchainDiscoveryMsg, QueryDiscoverySetRequest},
 };
@@ -16,7 +17,11 @@ use futures::{
 };
 use libra_logger::prelude::*;
 use libra_types::PeerId;
-use network::{peer_manager::PeerManagerNotification, protocols::rpc::error::RpcError, ProtocolId};
+use network::{
+    peer_manager::{reputation::Misbehavior, PeerManagerNotification},
+    protocols::rpc::error::RpcError,
+    ProtocolId,
+};
 use std::{sync::Arc, task::Context};
 use storage_interface::DbReader;
 use tokio::runtime::Handle;
@@ -32,6 +37,8 @@ pub struct OnchainDiscoveryService {
     // TODO(philiphayes): refactor LibraNet interface to better support this kind
     // of use case.
     peer_mgr_notifs_rx: libra_channel::Receiver<(PeerId, ProtocolId), PeerManagerNotification>,
+    /// A channel to report misbehaving peers to the network.
+    network_tx: OnchainDiscoveryNetworkSender,
     /// handle to LibraDB storage
     libra_db: Arc<dyn DbReader>,
 }
@@ -40,12 +47,14 @@ impl OnchainDiscoveryService {
     pub fn new(
         executor: Handle,
         peer_mgr_notifs_rx: libra_channel::Receiver<(PeerId, ProtocolId), PeerManagerNotification>,
+        network_tx: OnchainDiscoveryNetworkSender,
         libra_db: Arc<dyn DbReader>,
         max_concurrent_inbound_queries: usize,
     ) -> Self {
         Self {
             inbound_rpc_executor: BoundedExecutor::new(max_concurrent_inbound_queries, executor),
             peer_mgr_notifs_rx,
+            network_tx,
             libra_db,
         }
     }
@@ -83,7 +92,7 @@ impl OnchainDiscoveryService {
     }
 
     fn handle_inbound_rpc(
-        &self,
+        &mut self,
         peer_id: PeerId,
         protocol: ProtocolId,
         data: Bytes,
@@ -95,12 +104,20 @@ impl OnchainDiscoveryService {
             protocol
         );
 
-        let req_msg: OnchainDiscoveryMsg =
-            lcs::from_bytes(data.as_ref()).context("failed to deserialize rpc")?;
+        let req_msg: OnchainDiscoveryMsg = match lcs::from_bytes(data.as_ref()) {
+            Ok(req_msg) => req_msg,
+            Err(err) => {
+                self.report_misbehavior(peer_id, Misbehavior::UndecodableMessage);
+                return Err(err).context("failed to deserialize rpc");
+            }
+        };
 
         let req_msg = match req_msg {
             OnchainDiscoveryMsg::QueryDiscoverySetRequest(req_msg) => req_msg,
-            OnchainDiscoveryMsg::QueryDiscoverySetResponse(_) => bail!("unexpected rpc from peer"),
+            OnchainDiscoveryMsg::QueryDiscoverySetResponse(_) => {
+                self.report_misbehavior(peer_id, Misbehavior::ProtocolViolation);
+                bail!("unexpected rpc from peer")
+            }
         };
 
         debug!(
@@ -121,6 +138,16 @@ impl OnchainDiscoveryService {
                 format_err!("inbound discovery set query executor at capcity; dropped rpc request")
             })
     }
+
+    fn report_misbehavior(&mut self, peer_id: PeerId, misbehavior: Misbehavior) {
+        if let Err(err) = self.network_tx.report_misbehavior(peer_id, misbehavior) {
+            warn!(
+                "failed to report misbehavior: peer: {}, err: {:?}",
+                peer_id.short_str(),
+                err
+            );
+        }
+    }
 }
 
 async fn handle_query_discovery_set_request(
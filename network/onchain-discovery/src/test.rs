@@ -222,7 +222,7 @@ fn setup_onchain_discovery(
         peer_id,
         role,
         waypoint,
-        network_reqs_tx,
+        network_reqs_tx.clone(),
         conn_notifs_rx,
         Arc::clone(&libra_db),
         peer_query_ticker_rx,
@@ -234,6 +234,7 @@ fn setup_onchain_discovery(
     let service = OnchainDiscoveryService::new(
         executor.clone(),
         peer_mgr_notifs_rx,
+        network_reqs_tx,
         libra_db,
         max_concurrent_inbound_rpcs,
     );
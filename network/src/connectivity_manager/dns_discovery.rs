@@ -0,0 +1,457 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! DNS seed discovery.
+//!
+//! The `DnsDiscovery` actor periodically resolves the TXT records of a list of seed hostnames and
+//! feeds the peers they advertise to the `ConnectivityManager` as `DiscoverySource::Dns`
+//! addresses. Each TXT record advertises one address of a peer as `<peer_id> <network_address>`,
+//! e.g. `a1b2..ef /dns4/node1.example.com/tcp/6180/ln-noise-ik/<pubkey>/ln-handshake/0`. Operators
+//! can then rotate seeds by updating DNS rather than the config of every node.
+//!
+//! The `/dns4`, `/dns6` and `/dns` hostnames within the advertised addresses are resolved on every
+//! tick as well: each such address is passed on preceded by the `/ip4` and `/ip6` addresses its
+//! hostname currently resolves to, so a hostname which moves to another IP updates the addresses
+//! of the peer in the `ConnectivityManager`. As for an address change from any other discovery
+//! source, a connection to the old IP is kept until it drops. The addresses of peers from the other
+//! discovery sources are passed on as is and resolved by the transport on each dial.
+//!
+//! When the lookup of a seed or of a hostname fails, the peers or the IPs last resolved from it
+//! are kept until the next successful lookup.
+
+use crate::connectivity_manager::{ConnectivityRequest, DiscoverySource};
+use anyhow::{anyhow, ensure, Result};
+use futures::{
+    future::{BoxFuture, FutureExt},
+    sink::SinkExt,
+    stream::{Stream, StreamExt},
+};
+use libra_logger::prelude::*;
+use libra_network_address::{NetworkAddress, Protocol};
+use libra_types::PeerId;
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    str::FromStr,
+    sync::Arc,
+};
+use trust_dns_resolver::TokioAsyncResolver;
+
+/// Resolves the TXT records and the IPs of DNS names. Injectable for tests.
+pub trait DnsResolver: Send + Sync {
+    /// Returns the TXT records of `name`, the strings of each record concatenated.
+    fn lookup_txt(&self, name: &str) -> BoxFuture<'static, Result<Vec<String>>>;
+
+    /// Returns the IPv4 and IPv6 addresses of `name`.
+    fn lookup_ip(&self, name: &str) -> BoxFuture<'static, Result<Vec<IpAddr>>>;
+}
+
+/// A `DnsResolver` using the system resolver configuration.
+pub struct SystemDnsResolver {
+    resolver: TokioAsyncResolver,
+}
+
+impl SystemDnsResolver {
+    pub async fn new() -> Result<Self> {
+        Ok(Self {
+            resolver: TokioAsyncResolver::tokio_from_system_conf().await?,
+        })
+    }
+}
+
+impl DnsResolver for SystemDnsResolver {
+    fn lookup_txt(&self, name: &str) -> BoxFuture<'static, Result<Vec<String>>> {
+        let resolver = self.resolver.clone();
+        let name = name.to_string();
+        async move {
+            let lookup = resolver.txt_lookup(name).await?;
+            Ok(lookup
+                .iter()
+                .map(|txt| {
+                    txt.txt_data()
+                        .iter()
+                        .map(|data| String::from_utf8_lossy(data))
+                        .collect()
+                })
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn lookup_ip(&self, name: &str) -> BoxFuture<'static, Result<Vec<IpAddr>>> {
+        let resolver = self.resolver.clone();
+        let name = name.to_string();
+        async move { Ok(resolver.lookup_ip(name).await?.iter().collect()) }.boxed()
+    }
+}
+
+/// Parses a TXT record of a seed hostname.
+pub fn parse_txt_record(record: &str) -> Result<(PeerId, NetworkAddress)> {
+    let mut parts = record.split_whitespace();
+    let (peer_id, addr) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(peer_id), Some(addr), None) => (peer_id, addr),
+        _ => return Err(anyhow!("Expected '<peer_id> <network_address>'")),
+    };
+    let peer_id = PeerId::from_str(peer_id)?;
+    let addr = NetworkAddress::from_str(addr)?;
+    ensure!(
+        addr.is_libranet_addr(),
+        "Unexpected address format: '{}'",
+        addr
+    );
+    Ok((peer_id, addr))
+}
+
+/// The hostname of an address starting with `/dns`, `/dns4` or `/dns6`.
+fn hostname(addr: &NetworkAddress) -> Option<&str> {
+    match addr.as_slice().first() {
+        Some(Protocol::Dns(name)) | Some(Protocol::Dns4(name)) | Some(Protocol::Dns6(name)) => {
+            Some(name.as_ref())
+        }
+        _ => None,
+    }
+}
+
+/// The addresses of `ips` which `addr` resolves to, followed by `addr` itself.
+fn resolve_addr(addr: &NetworkAddress, ips: &[IpAddr]) -> Vec<NetworkAddress> {
+    let protos = addr.as_slice();
+    let mut addrs: Vec<_> = ips
+        .iter()
+        .filter_map(|ip| {
+            let ip = match (protos.first(), ip) {
+                (Some(Protocol::Dns(_)), IpAddr::V4(ip))
+                | (Some(Protocol::Dns4(_)), IpAddr::V4(ip)) => Protocol::Ip4(*ip),
+                (Some(Protocol::Dns(_)), IpAddr::V6(ip))
+                | (Some(Protocol::Dns6(_)), IpAddr::V6(ip)) => Protocol::Ip6(*ip),
+                _ => return None,
+            };
+            Some(NetworkAddress::from(ip).extend_from_slice(&protos[1..]))
+        })
+        .collect();
+    addrs.push(addr.clone());
+    addrs
+}
+
+/// The DnsDiscovery actor.
+pub struct DnsDiscovery<TTicker> {
+    seeds: Vec<String>,
+    resolver: Arc<dyn DnsResolver>,
+    ticker: TTicker,
+    conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+    /// The peers last resolved from each seed.
+    resolved: HashMap<String, HashMap<PeerId, Vec<NetworkAddress>>>,
+    /// The IPs last resolved for each hostname of the advertised addresses.
+    hostname_ips: HashMap<String, Vec<IpAddr>>,
+    /// The addresses last sent to the `ConnectivityManager`.
+    discovered: HashMap<PeerId, Vec<NetworkAddress>>,
+}
+
+impl<TTicker> DnsDiscovery<TTicker>
+where
+    TTicker: Stream + Unpin,
+{
+    pub fn new(
+        seeds: Vec<String>,
+        resolver: Arc<dyn DnsResolver>,
+        ticker: TTicker,
+        conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+    ) -> Self {
+        Self {
+            seeds,
+            resolver,
+            ticker,
+            conn_mgr_reqs_tx,
+            resolved: HashMap::new(),
+            hostname_ips: HashMap::new(),
+            discovered: HashMap::new(),
+        }
+    }
+
+    /// Resolves the seeds and the hostnames they advertise on every tick.
+    pub async fn start(mut self) {
+        while self.ticker.next().await.is_some() {
+            self.resolve().await;
+        }
+        info!("DnsDiscovery terminated");
+    }
+
+    async fn resolve(&mut self) {
+        for seed in &self.seeds {
+            match self.resolver.lookup_txt(seed).await {
+                Ok(records) => {
+                    let mut peers: HashMap<PeerId, Vec<NetworkAddress>> = HashMap::new();
+                    for record in records {
+                        match parse_txt_record(&record) {
+                            Ok((peer_id, addr)) => peers.entry(peer_id).or_default().push(addr),
+                            Err(err) => warn!(
+                                "Ignoring TXT record '{}' of DNS seed {}: {}",
+                                record, seed, err
+                            ),
+                        }
+                    }
+                    self.resolved.insert(seed.clone(), peers);
+                }
+                Err(err) => warn!("Failed to resolve DNS seed {}: {}", seed, err),
+            }
+        }
+
+        let mut advertised: HashMap<PeerId, Vec<&NetworkAddress>> = HashMap::new();
+        for seed in &self.seeds {
+            for (peer_id, addrs) in self.resolved.get(seed).into_iter().flatten() {
+                advertised.entry(*peer_id).or_default().extend(addrs);
+            }
+        }
+
+        let hostnames: HashSet<String> = advertised
+            .values()
+            .flatten()
+            .filter_map(|addr| hostname(addr))
+            .map(str::to_string)
+            .collect();
+        self.hostname_ips.retain(|name, _| hostnames.contains(name));
+        for name in hostnames {
+            match self.resolver.lookup_ip(&name).await {
+                Ok(ips) => {
+                    self.hostname_ips.insert(name, ips);
+                }
+                Err(err) => warn!("Failed to resolve hostname {}: {}", name, err),
+            }
+        }
+
+        let mut discovered: HashMap<PeerId, Vec<NetworkAddress>> = HashMap::new();
+        for (peer_id, addrs) in advertised {
+            let peer_addrs = discovered.entry(peer_id).or_default();
+            for addr in addrs {
+                let ips = hostname(addr)
+                    .and_then(|name| self.hostname_ips.get(name))
+                    .map_or(&[][..], |ips| ips.as_slice());
+                for addr in resolve_addr(addr, ips) {
+                    if !peer_addrs.contains(&addr) {
+                        peer_addrs.push(addr);
+                    }
+                }
+            }
+        }
+        if discovered == self.discovered {
+            return;
+        }
+
+        // Peers which are no longer advertised lose their DNS addresses.
+        let mut updates = discovered.clone();
+        for peer_id in self.discovered.keys() {
+            updates.entry(*peer_id).or_insert_with(Vec::new);
+        }
+        info!("DNS seeds resolved to {} peers", discovered.len());
+        if let Err(err) = self
+            .conn_mgr_reqs_tx
+            .send(ConnectivityRequest::UpdateAddresses(
+                DiscoverySource::Dns,
+                updates,
+            ))
+            .await
+        {
+            warn!(
+                "Failed to send DNS addresses to ConnectivityManager: {}",
+                err
+            );
+        }
+        self.discovered = discovered;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::runtime::Runtime;
+
+    #[derive(Default)]
+    struct MockResolver {
+        records: Mutex<HashMap<String, Result<Vec<String>, String>>>,
+        ips: Mutex<HashMap<String, Result<Vec<IpAddr>, String>>>,
+    }
+
+    impl MockResolver {
+        fn set(&self, name: &str, records: Result<Vec<String>, String>) {
+            self.records
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), records);
+        }
+
+        fn set_ips(&self, name: &str, ips: Result<Vec<&str>, String>) {
+            let ips = ips.map(|ips| ips.iter().map(|ip| ip.parse().unwrap()).collect());
+            self.ips.lock().unwrap().insert(name.to_string(), ips);
+        }
+    }
+
+    impl DnsResolver for MockResolver {
+        fn lookup_txt(&self, name: &str) -> BoxFuture<'static, Result<Vec<String>>> {
+            let records = self
+                .records
+                .lock()
+                .unwrap()
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| Err("NXDOMAIN".to_string()))
+                .map_err(|err| anyhow!(err));
+            futures::future::ready(records).boxed()
+        }
+
+        fn lookup_ip(&self, name: &str) -> BoxFuture<'static, Result<Vec<IpAddr>>> {
+            let ips = self
+                .ips
+                .lock()
+                .unwrap()
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| Err("NXDOMAIN".to_string()))
+                .map_err(|err| anyhow!(err));
+            futures::future::ready(ips).boxed()
+        }
+    }
+
+    const ADDR_1: &str = "/dns4/node1.example.com/tcp/6180/ln-noise-ik/080e287879c918794170e258bfaddd75acac5b3e350419044655e4983a487120/ln-handshake/0";
+    const ADDR_2: &str = "/ip4/10.0.0.2/tcp/6180/ln-noise-ik/080e287879c918794170e258bfaddd75acac5b3e350419044655e4983a487120/ln-handshake/0";
+
+    #[test]
+    fn test_parse_txt_record() {
+        let peer_id = PeerId::random();
+        let (parsed_peer_id, addr) = parse_txt_record(&format!("{} {}", peer_id, ADDR_1)).unwrap();
+        assert_eq!(parsed_peer_id, peer_id);
+        assert_eq!(addr, NetworkAddress::from_str(ADDR_1).unwrap());
+
+        assert!(parse_txt_record(ADDR_1).is_err());
+        assert!(parse_txt_record(&format!("{} {} extra", peer_id, ADDR_1)).is_err());
+        assert!(parse_txt_record(&format!("{} /ip4/10.0.0.1/tcp/6180", peer_id)).is_err());
+        assert!(parse_txt_record(&format!("not_a_peer_id {}", ADDR_1)).is_err());
+    }
+
+    async fn expect_update(
+        conn_mgr_reqs_rx: &mut channel::Receiver<ConnectivityRequest>,
+        expected: Vec<(PeerId, Vec<NetworkAddress>)>,
+    ) {
+        match conn_mgr_reqs_rx.next().await.unwrap() {
+            ConnectivityRequest::UpdateAddresses(DiscoverySource::Dns, addrs) => {
+                assert_eq!(addrs, expected.into_iter().collect());
+            }
+            request => panic!("Unexpected request: {:?}", request),
+        }
+    }
+
+    #[test]
+    fn test_dns_discovery() {
+        let mut rt = Runtime::new().unwrap();
+        let resolver = Arc::new(MockResolver::default());
+        let (mut ticker_tx, ticker_rx) = channel::new_test::<()>(1);
+        let (conn_mgr_reqs_tx, mut conn_mgr_reqs_rx) = channel::new_test(1);
+        let discovery = DnsDiscovery::new(
+            vec![
+                "seeds-a.example.com".to_string(),
+                "seeds-b.example.com".to_string(),
+            ],
+            resolver.clone(),
+            ticker_rx,
+            conn_mgr_reqs_tx,
+        );
+        rt.spawn(discovery.start());
+
+        let (peer_1, peer_2) = (PeerId::random(), PeerId::random());
+        let addr_1 = NetworkAddress::from_str(ADDR_1).unwrap();
+        let addr_2 = NetworkAddress::from_str(ADDR_2).unwrap();
+        rt.block_on(async move {
+            resolver.set(
+                "seeds-a.example.com",
+                Ok(vec![
+                    format!("{} {}", peer_1, ADDR_1),
+                    format!("{} {}", peer_2, ADDR_2),
+                    "malformed record".to_string(),
+                ]),
+            );
+            resolver.set(
+                "seeds-b.example.com",
+                Ok(vec![format!("{} {}", peer_1, ADDR_2)]),
+            );
+            ticker_tx.send(()).await.unwrap();
+            expect_update(
+                &mut conn_mgr_reqs_rx,
+                vec![
+                    (peer_1, vec![addr_1.clone(), addr_2.clone()]),
+                    (peer_2, vec![addr_2.clone()]),
+                ],
+            )
+            .await;
+
+            // a failed lookup keeps the peers of the seed
+            resolver.set("seeds-a.example.com", Err("SERVFAIL".to_string()));
+            resolver.set("seeds-b.example.com", Ok(vec![]));
+            ticker_tx.send(()).await.unwrap();
+            expect_update(
+                &mut conn_mgr_reqs_rx,
+                vec![(peer_1, vec![addr_1]), (peer_2, vec![addr_2])],
+            )
+            .await;
+
+            // peers which are no longer advertised lose their addresses
+            resolver.set("seeds-a.example.com", Ok(vec![]));
+            ticker_tx.send(()).await.unwrap();
+            expect_update(
+                &mut conn_mgr_reqs_rx,
+                vec![(peer_1, vec![]), (peer_2, vec![])],
+            )
+            .await;
+        });
+    }
+
+    #[test]
+    fn test_dns_discovery_resolves_hostnames() {
+        let mut rt = Runtime::new().unwrap();
+        let resolver = Arc::new(MockResolver::default());
+        let (mut ticker_tx, ticker_rx) = channel::new_test::<()>(1);
+        let (conn_mgr_reqs_tx, mut conn_mgr_reqs_rx) = channel::new_test(1);
+        let discovery = DnsDiscovery::new(
+            vec!["seeds.example.com".to_string()],
+            resolver.clone(),
+            ticker_rx,
+            conn_mgr_reqs_tx,
+        );
+        rt.spawn(discovery.start());
+
+        let peer = PeerId::random();
+        let addr = NetworkAddress::from_str(ADDR_1).unwrap();
+        let ip_addr = |ip: &str| {
+            NetworkAddress::from_str(&ADDR_1.replace("/dns4/node1.example.com", ip)).unwrap()
+        };
+        rt.block_on(async move {
+            resolver.set(
+                "seeds.example.com",
+                Ok(vec![format!("{} {}", peer, ADDR_1)]),
+            );
+            // `/dns4` only resolves to IPv4 addresses
+            resolver.set_ips("node1.example.com", Ok(vec!["10.0.0.1", "::1"]));
+            ticker_tx.send(()).await.unwrap();
+            expect_update(
+                &mut conn_mgr_reqs_rx,
+                vec![(peer, vec![ip_addr("/ip4/10.0.0.1"), addr.clone()])],
+            )
+            .await;
+
+            // the hostname moves to another IP
+            resolver.set_ips("node1.example.com", Ok(vec!["10.0.0.3"]));
+            ticker_tx.send(()).await.unwrap();
+            expect_update(
+                &mut conn_mgr_reqs_rx,
+                vec![(peer, vec![ip_addr("/ip4/10.0.0.3"), addr.clone()])],
+            )
+            .await;
+
+            // a failed lookup keeps the IPs of the hostname, so there is no update
+            resolver.set_ips("node1.example.com", Err("SERVFAIL".to_string()));
+            ticker_tx.send(()).await.unwrap();
+
+            resolver.set("seeds.example.com", Ok(vec![]));
+            ticker_tx.send(()).await.unwrap();
+            expect_update(&mut conn_mgr_reqs_rx, vec![(peer, vec![])]).await;
+        });
+    }
+}
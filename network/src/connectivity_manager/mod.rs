@@ -9,12 +9,13 @@
 //! Consensus actor informs the ConnectivityManager of eligible nodes.
 //!
 //! Different discovery sources notify the ConnectivityManager of updates to
-//! peers' addresses. Currently, there are 3 discovery sources (ordered by
+//! peers' addresses. Currently, there are 4 discovery sources (ordered by
 //! decreasing dial priority, i.e., first is highest priority):
 //!
 //! 1. Onchain discovery protocol
 //! 2. Gossip discovery protocol
-//! 3. Seed peers from config
+//! 3. DNS seeds
+//! 4. Seed peers from config
 //!
 //! In other words, if a we have some addresses discovered via onchain discovery
 //! and some seed addresses from our local config, we will try the onchain
@@ -49,6 +50,8 @@ use std::{
 };
 use tokio::time;
 
+pub mod dns_discovery;
+pub mod peers_file_watcher;
 #[cfg(test)]
 mod test;
 
@@ -92,6 +95,7 @@ pub struct ConnectivityManager<TTicker, TBackoff> {
 pub enum DiscoverySource {
     OnChain,
     Gossip,
+    Dns,
     Config,
 }
 
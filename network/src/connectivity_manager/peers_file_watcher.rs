@@ -0,0 +1,257 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reloads the network peers and seed peers files of a running node.
+//!
+//! `NetworkConfig::load` only reads `network_peers_file` and `seed_peers_file` at startup. The
+//! `PeersFileWatcher` re-reads them on every tick and pushes the changes to the
+//! `ConnectivityManager`:
+//!  * a changed network peers file replaces the set of eligible nodes, which is also the set of
+//!  trusted peers checked by the Noise handshake;
+//!  * a changed seed peers file replaces the `DiscoverySource::Config` addresses, the peers removed
+//!  from the file get an empty list of addresses.
+//!
+//! The `ConnectivityManager` then disconnects from the removed peers and dials the new ones on its
+//! next connectivity check. The peers of both files become the preferred inbound peers of the
+//! `PeerManager`. A file which can't be read or parsed, e.g. while it is being written, is skipped
+//! until the next tick.
+//!
+//! The network peers file replaces the eligible nodes, so the watcher must not run on validator
+//! networks, whose eligible nodes are the on-chain validator set.
+
+use crate::{
+    common::NetworkPublicKeys,
+    connectivity_manager::{ConnectivityRequest, DiscoverySource},
+};
+use anyhow::Result;
+use futures::{
+    sink::SinkExt,
+    stream::{Stream, StreamExt},
+};
+use libra_config::config::{NetworkPeersConfig, PersistableConfig, SeedPeersConfig};
+use libra_logger::prelude::*;
+use libra_network_address::NetworkAddress;
+use libra_types::PeerId;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+pub struct PeersFileWatcher {
+    network_peers_file: Option<PathBuf>,
+    seed_peers_file: Option<PathBuf>,
+    /// The network peers and seed peers last pushed to the `ConnectivityManager`.
+    network_peers: HashMap<PeerId, NetworkPublicKeys>,
+    seed_peers: HashMap<PeerId, Vec<NetworkAddress>>,
+    conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+    preferred_peers: Arc<RwLock<HashSet<PeerId>>>,
+}
+
+impl PeersFileWatcher {
+    /// Creates a watcher for the given files, `None` for a file the node doesn't use. The network
+    /// peers and seed peers are the ones the network was started with.
+    pub fn new(
+        network_peers_file: Option<PathBuf>,
+        seed_peers_file: Option<PathBuf>,
+        network_peers: HashMap<PeerId, NetworkPublicKeys>,
+        seed_peers: HashMap<PeerId, Vec<NetworkAddress>>,
+        conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+        preferred_peers: Arc<RwLock<HashSet<PeerId>>>,
+    ) -> Self {
+        Self {
+            network_peers_file,
+            seed_peers_file,
+            network_peers,
+            seed_peers,
+            conn_mgr_reqs_tx,
+            preferred_peers,
+        }
+    }
+
+    /// Reloads the files on every tick.
+    pub async fn start<TTicker>(mut self, mut ticker: TTicker)
+    where
+        TTicker: Stream + Unpin,
+    {
+        while ticker.next().await.is_some() {
+            self.reload().await;
+        }
+        info!("PeersFileWatcher terminated");
+    }
+
+    /// Reloads the files and pushes the changes to the `ConnectivityManager`.
+    pub async fn reload(&mut self) {
+        let mut changed = false;
+        if let Some(path) = self.network_peers_file.clone() {
+            match NetworkPeersConfig::load_config(&path) {
+                Ok(config) if config.peers != self.network_peers => {
+                    info!(
+                        "Network peers file {:?} changed: {} network peers",
+                        path,
+                        config.peers.len()
+                    );
+                    self.send(ConnectivityRequest::UpdateEligibleNodes(
+                        config.peers.clone(),
+                    ))
+                    .await;
+                    self.network_peers = config.peers;
+                    changed = true;
+                }
+                Ok(_) => (),
+                Err(err) => warn!("Failed to reload network peers file {:?}: {}", path, err),
+            }
+        }
+
+        if let Some(path) = self.seed_peers_file.clone() {
+            match load_seed_peers(&path) {
+                Ok(seed_peers) if seed_peers != self.seed_peers => {
+                    info!(
+                        "Seed peers file {:?} changed: {} seed peers",
+                        path,
+                        seed_peers.len()
+                    );
+                    let mut updates = seed_peers.clone();
+                    for peer_id in self.seed_peers.keys() {
+                        updates.entry(*peer_id).or_insert_with(Vec::new);
+                    }
+                    self.send(ConnectivityRequest::UpdateAddresses(
+                        DiscoverySource::Config,
+                        updates,
+                    ))
+                    .await;
+                    self.seed_peers = seed_peers;
+                    changed = true;
+                }
+                Ok(_) => (),
+                Err(err) => warn!("Failed to reload seed peers file {:?}: {}", path, err),
+            }
+        }
+
+        if changed {
+            *self.preferred_peers.write().unwrap() = self
+                .network_peers
+                .keys()
+                .chain(self.seed_peers.keys())
+                .cloned()
+                .collect();
+        }
+    }
+
+    async fn send(&mut self, request: ConnectivityRequest) {
+        if let Err(err) = self.conn_mgr_reqs_tx.send(request).await {
+            warn!(
+                "Failed to send peers update to ConnectivityManager: {}",
+                err
+            );
+        }
+    }
+}
+
+fn load_seed_peers(path: &Path) -> Result<HashMap<PeerId, Vec<NetworkAddress>>> {
+    let config = SeedPeersConfig::load_config(path)?;
+    config.verify_libranet_addrs()?;
+    Ok(config.seed_peers)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::FutureExt;
+    use libra_config::config::{NetworkPeerInfo, HANDSHAKE_VERSION};
+    use libra_crypto::{test_utils::TEST_SEED, x25519, Uniform};
+    use libra_temppath::TempPath;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::str::FromStr;
+    use tokio::runtime::Runtime;
+
+    fn libranet_addr(rng: &mut StdRng) -> NetworkAddress {
+        let pubkey = x25519::PrivateKey::generate(rng).public_key();
+        NetworkAddress::from_str("/ip4/127.0.0.1/tcp/6180")
+            .unwrap()
+            .append_prod_protos(pubkey, HANDSHAKE_VERSION)
+    }
+
+    #[test]
+    fn test_reload() {
+        let mut rt = Runtime::new().unwrap();
+        let mut rng = StdRng::from_seed(TEST_SEED);
+        let network_peers_file = TempPath::new();
+        let seed_peers_file = TempPath::new();
+        let (conn_mgr_reqs_tx, mut conn_mgr_reqs_rx) = channel::new_test(8);
+
+        let removed_peer = PeerId::random();
+        let kept_peer = PeerId::random();
+        let new_peer = PeerId::random();
+        let mut network_peers = NetworkPeersConfig::default();
+        for peer_id in &[removed_peer, kept_peer] {
+            network_peers.peers.insert(
+                *peer_id,
+                NetworkPeerInfo {
+                    identity_public_key: x25519::PrivateKey::generate(&mut rng).public_key(),
+                },
+            );
+        }
+        let mut seed_peers = SeedPeersConfig::default();
+        seed_peers
+            .seed_peers
+            .insert(removed_peer, vec![libranet_addr(&mut rng)]);
+        network_peers
+            .save_config(network_peers_file.path())
+            .unwrap();
+        seed_peers.save_config(seed_peers_file.path()).unwrap();
+
+        let preferred_peers = Arc::new(RwLock::new(
+            network_peers.peers.keys().cloned().collect::<HashSet<_>>(),
+        ));
+        let mut watcher = PeersFileWatcher::new(
+            Some(network_peers_file.path().to_path_buf()),
+            Some(seed_peers_file.path().to_path_buf()),
+            network_peers.peers.clone(),
+            seed_peers.seed_peers.clone(),
+            conn_mgr_reqs_tx,
+            preferred_peers.clone(),
+        );
+        rt.block_on(async {
+            // unchanged files don't trigger updates
+            watcher.reload().await;
+            assert!(conn_mgr_reqs_rx.next().now_or_never().is_none());
+
+            let info = network_peers.peers.remove(&removed_peer).unwrap();
+            network_peers.peers.insert(new_peer, info);
+            network_peers
+                .save_config(network_peers_file.path())
+                .unwrap();
+            seed_peers.seed_peers.remove(&removed_peer);
+            let new_addrs = vec![libranet_addr(&mut rng)];
+            seed_peers.seed_peers.insert(new_peer, new_addrs.clone());
+            seed_peers.save_config(seed_peers_file.path()).unwrap();
+            watcher.reload().await;
+
+            match conn_mgr_reqs_rx.next().await.unwrap() {
+                ConnectivityRequest::UpdateEligibleNodes(nodes) => {
+                    assert_eq!(nodes, network_peers.peers)
+                }
+                request => panic!("Unexpected request: {:?}", request),
+            }
+            match conn_mgr_reqs_rx.next().await.unwrap() {
+                ConnectivityRequest::UpdateAddresses(DiscoverySource::Config, addrs) => {
+                    assert_eq!(addrs.len(), 2);
+                    assert_eq!(addrs[&new_peer], new_addrs);
+                    assert!(addrs[&removed_peer].is_empty());
+                }
+                request => panic!("Unexpected request: {:?}", request),
+            }
+            // the peers of the files are the preferred inbound peers
+            assert_eq!(
+                *preferred_peers.read().unwrap(),
+                [kept_peer, new_peer].iter().cloned().collect()
+            );
+
+            // invalid files are skipped
+            std::fs::write(seed_peers_file.path(), "not toml").unwrap();
+            watcher.reload().await;
+            assert!(conn_mgr_reqs_rx.next().now_or_never().is_none());
+        });
+    }
+}
@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use libra_metrics::{
-    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, HistogramVec,
-    IntCounterVec, IntGauge, IntGaugeVec, OpMetrics,
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    OpMetrics,
 };
 use once_cell::sync::Lazy;
 
@@ -17,6 +18,11 @@ pub const DECLINED_LABEL: &str = "declined";
 pub const FAILED_LABEL: &str = "failed";
 pub const RECEIVED_LABEL: &str = "received";
 pub const SENT_LABEL: &str = "sent";
+pub const SUCCEEDED_LABEL: &str = "succeeded";
+
+// some compression labels
+pub const COMPRESSED_LABEL: &str = "compressed";
+pub const UNCOMPRESSED_LABEL: &str = "uncompressed";
 
 pub static LIBRA_NETWORK_PEERS: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -30,6 +36,51 @@ pub static LIBRA_NETWORK_PEERS: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static LIBRA_NETWORK_PEER_MISBEHAVIOR: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_network_peer_misbehavior",
+        "Libra network misbehavior reported against remote peers",
+        &["misbehavior"]
+    )
+    .unwrap()
+});
+
+/// Counter of peers banned for misbehavior.
+pub static LIBRA_NETWORK_PEER_BANS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_network_peer_bans",
+        "Libra network peers banned for misbehavior"
+    )
+    .unwrap()
+});
+
+/// Gauge of currently banned peers.
+pub static LIBRA_NETWORK_BANNED_PEERS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "libra_network_banned_peers",
+        "Libra network currently banned peers"
+    )
+    .unwrap()
+});
+
+pub static LIBRA_NETWORK_INBOUND_CONNECTIONS_REJECTED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_network_inbound_connections_rejected",
+        "Libra network inbound connections rejected by the admission policy",
+        &["limit"]
+    )
+    .unwrap()
+});
+
+/// Counter of unknown inbound peers disconnected to make room for preferred peers.
+pub static LIBRA_NETWORK_INBOUND_CONNECTIONS_EVICTED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_network_inbound_connections_evicted",
+        "Libra network unknown inbound peers evicted in favor of preferred peers"
+    )
+    .unwrap()
+});
+
 pub static LIBRA_NETWORK_DISCOVERY_NOTES: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
         // metric name
@@ -87,6 +138,77 @@ pub static LIBRA_NETWORK_DIRECT_SEND_BYTES: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter of the bytes sent and received on the wire, per protocol and per peer. The series of a
+/// peer are removed once it disconnects.
+pub static LIBRA_NETWORK_PROTOCOL_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_network_protocol_bytes",
+        "Libra network bytes sent and received per protocol and peer",
+        &["state", "protocol_id", "peer_id"]
+    )
+    .unwrap()
+});
+
+/// Counter of the messages sent and received on the wire, per protocol and per peer. The series of
+/// a peer are removed once it disconnects.
+pub static LIBRA_NETWORK_PROTOCOL_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_network_protocol_messages",
+        "Libra network messages sent and received per protocol and peer",
+        &["state", "protocol_id", "peer_id"]
+    )
+    .unwrap()
+});
+
+/// Counter of the records of the traffic capture dropped because the capture file can't keep up.
+pub static LIBRA_NETWORK_CAPTURE_DROPPED_RECORDS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_network_capture_dropped_records",
+        "Libra network traffic capture records dropped because the writer is behind"
+    )
+    .unwrap()
+});
+
+/// Counter of the payload bytes before and after compression, per protocol.
+pub static LIBRA_NETWORK_COMPRESSION_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_network_compression_bytes",
+        "Libra network payload bytes before and after compression",
+        &["protocol_id", "state"]
+    )
+    .unwrap()
+});
+
+/// Counter of the messages dropped because they exceed the rate limit of their protocol.
+pub static LIBRA_NETWORK_PROTOCOL_RATE_LIMITED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_network_protocol_rate_limited",
+        "Libra network messages dropped by the per-protocol rate limits",
+        &["state", "protocol_id"]
+    )
+    .unwrap()
+});
+
+/// Histogram of the round-trip times of the HealthChecker pings, per peer.
+pub static LIBRA_NETWORK_HEALTH_CHECK_RTT: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "libra_network_health_check_rtt_seconds",
+        "Libra network round-trip times of the health checker pings",
+        &["peer_id"]
+    )
+    .unwrap()
+});
+
+/// Counter of the HealthChecker pings which succeeded and failed, per peer.
+pub static LIBRA_NETWORK_HEALTH_CHECK_PINGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_network_health_check_pings",
+        "Libra network health checker pings which succeeded and failed",
+        &["peer_id", "state"]
+    )
+    .unwrap()
+});
+
 /// Counters(queued,dequeued,dropped) related to inbound network notifications for RPCs and
 /// DirectSends.
 pub static PENDING_NETWORK_NOTIFICATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
@@ -1,9 +1,9 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::peer_manager::PeerManagerError;
+use crate::peer_manager::{reputation::Misbehavior, PeerManagerError};
 use futures::channel::{mpsc, oneshot};
-use libra_types::validator_verifier::VerifyError;
+use libra_types::{validator_verifier::VerifyError, PeerId};
 use std::io;
 use thiserror::Error;
 
@@ -53,6 +53,37 @@ pub enum NetworkErrorKind {
     NotConnected,
 }
 
+/// Context attached to errors caused by the misbehavior of a remote peer, so that upper layers
+/// can report the peer.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Error)]
+#[error("Peer {peer_id} misbehaved: {misbehavior}")]
+pub struct PeerMisbehavior {
+    pub peer_id: PeerId,
+    pub misbehavior: Misbehavior,
+}
+
+impl NetworkError {
+    /// An error caused by the misbehavior of a remote peer.
+    pub fn from_misbehavior<E: Into<NetworkError>>(
+        peer_id: PeerId,
+        misbehavior: Misbehavior,
+        err: E,
+    ) -> NetworkError {
+        err.into()
+            .inner
+            .context(PeerMisbehavior {
+                peer_id,
+                misbehavior,
+            })
+            .into()
+    }
+
+    /// The peer at fault and its misbehavior, if the error was caused by a remote peer.
+    pub fn misbehavior(&self) -> Option<PeerMisbehavior> {
+        self.inner.downcast_ref::<PeerMisbehavior>().copied()
+    }
+}
+
 impl From<NetworkErrorKind> for NetworkError {
     fn from(kind: NetworkErrorKind) -> NetworkError {
         NetworkError {
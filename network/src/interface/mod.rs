@@ -13,11 +13,14 @@
 //! handler, determined using the protocol negotiated on the RPC substream.
 use crate::{
     counters,
-    peer::{Peer, PeerHandle, PeerNotification},
+    peer::{
+        bandwidth::ProtocolRateLimits, capture::TrafficRecorder, Peer, PeerHandle, PeerNotification,
+    },
     peer_manager::TransportNotification,
     protocols::{
         direct_send::{DirectSend, DirectSendNotification, DirectSendRequest, Message},
         rpc::{InboundRpcRequest, OutboundRpcRequest, Rpc, RpcNotification},
+        wire::messaging::v1::compression::CompressionThresholds,
     },
     transport::Connection,
     validator_network, ProtocolId,
@@ -69,6 +72,9 @@ where
         max_concurrent_reqs: usize,
         max_concurrent_notifs: usize,
         channel_size: usize,
+        protocol_rate_limits: &ProtocolRateLimits,
+        compression_thresholds: &CompressionThresholds,
+        traffic_recorder: Option<TrafficRecorder>,
     ) -> (
         libra_channel::Sender<ProtocolId, NetworkRequest>,
         libra_channel::Receiver<ProtocolId, NetworkNotification>,
@@ -110,6 +116,9 @@ where
             peer_notifs_tx,
             peer_rpc_notifs_tx,
             peer_ds_notifs_tx,
+            protocol_rate_limits,
+            compression_thresholds,
+            traffic_recorder,
         );
         executor.spawn(peer.start());
 
@@ -9,12 +9,18 @@
 // Public exports
 pub use common::NetworkPublicKeys;
 pub use interface::NetworkProvider;
+pub use peer::{
+    bandwidth::{ProtocolRateLimit, ProtocolRateLimits},
+    capture,
+};
+pub use peer_metadata::PeerMetadata;
 
 pub mod common;
 pub mod connectivity_manager;
 pub mod error;
 pub mod interface;
 pub mod peer_manager;
+pub mod peer_metadata;
 pub mod protocols;
 pub mod validator_network;
 
@@ -0,0 +1,183 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-protocol bandwidth accounting of a connection.
+//!
+//! Every message sent or received by the `Peer` actor is accounted against its `ProtocolId`: the
+//! bytes and messages are exported to metrics, and the protocols with a configured rate limit get
+//! a token-bucket per direction. A message over the limit is dropped, so that a burst on one
+//! protocol (e.g. a mempool broadcast storm) can't starve the others. An inbound RPC request over
+//! the limit is answered with an `ErrorCode::RateLimited`, which fails the RPC of the remote peer,
+//! if the peer advertised understanding it in the handshake.
+
+use crate::{counters, ProtocolId};
+use libra_types::PeerId;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
+use stream_ratelimiter::TokenBucket;
+
+/// Rate limits of a protocol on a single connection. `None` means unlimited.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ProtocolRateLimit {
+    pub inbound_bytes_per_sec: Option<u64>,
+    pub outbound_bytes_per_sec: Option<u64>,
+}
+
+pub type ProtocolRateLimits = HashMap<ProtocolId, ProtocolRateLimit>;
+
+/// Protocols written to the socket ahead of the others when it is congested.
+pub fn is_priority_protocol(protocol: ProtocolId) -> bool {
+    match protocol {
+        ProtocolId::ConsensusRpc
+        | ProtocolId::ConsensusDirectSend
+        | ProtocolId::HealthCheckerRpc => true,
+        _ => false,
+    }
+}
+
+pub struct BandwidthLimiter {
+    peer_id: String,
+    /// The state and protocol labels of the series recorded for the peer.
+    recorded: HashSet<(&'static str, ProtocolId)>,
+    inbound: HashMap<ProtocolId, TokenBucket>,
+    outbound: HashMap<ProtocolId, TokenBucket>,
+}
+
+impl BandwidthLimiter {
+    pub fn new(peer_id: PeerId, limits: &ProtocolRateLimits, now: Instant) -> Self {
+        // The buckets hold a second worth of traffic.
+        let buckets = |limit: fn(&ProtocolRateLimit) -> Option<u64>| {
+            limits
+                .iter()
+                .filter_map(|(protocol, rate_limit)| {
+                    limit(rate_limit).map(|bytes_per_sec| {
+                        (
+                            *protocol,
+                            TokenBucket::new(bytes_per_sec, bytes_per_sec, now),
+                        )
+                    })
+                })
+                .collect()
+        };
+        Self {
+            peer_id: peer_id.short_str(),
+            recorded: HashSet::new(),
+            inbound: buckets(|rate_limit| rate_limit.inbound_bytes_per_sec),
+            outbound: buckets(|rate_limit| rate_limit.outbound_bytes_per_sec),
+        }
+    }
+
+    /// Accounts for a message received on `protocol`. Returns false if the message is over the
+    /// inbound limit of the protocol and must be dropped.
+    pub fn inbound(&mut self, protocol: ProtocolId, bytes: usize, now: Instant) -> bool {
+        // Received bytes count whether the message is dropped or not, they used the bandwidth.
+        self.record(counters::RECEIVED_LABEL, protocol, bytes);
+        Self::acquire(
+            &mut self.inbound,
+            counters::RECEIVED_LABEL,
+            protocol,
+            bytes,
+            now,
+        )
+    }
+
+    /// Accounts for a message to be sent on `protocol`. Returns false if the message is over the
+    /// outbound limit of the protocol and must be dropped.
+    pub fn outbound(&mut self, protocol: ProtocolId, bytes: usize, now: Instant) -> bool {
+        if !Self::acquire(
+            &mut self.outbound,
+            counters::SENT_LABEL,
+            protocol,
+            bytes,
+            now,
+        ) {
+            return false;
+        }
+        self.record(counters::SENT_LABEL, protocol, bytes);
+        true
+    }
+
+    fn record(&mut self, state: &'static str, protocol: ProtocolId, bytes: usize) {
+        let labels = [state, protocol.as_str(), self.peer_id.as_str()];
+        counters::LIBRA_NETWORK_PROTOCOL_BYTES
+            .with_label_values(&labels)
+            .inc_by(bytes as i64);
+        counters::LIBRA_NETWORK_PROTOCOL_MESSAGES
+            .with_label_values(&labels)
+            .inc();
+        self.recorded.insert((state, protocol));
+    }
+
+    /// Removes the metric series of the peer, once disconnected.
+    pub fn remove_counters(&mut self) {
+        for (state, protocol) in self.recorded.drain() {
+            let labels = [state, protocol.as_str(), self.peer_id.as_str()];
+            let _ = counters::LIBRA_NETWORK_PROTOCOL_BYTES.remove_label_values(&labels);
+            let _ = counters::LIBRA_NETWORK_PROTOCOL_MESSAGES.remove_label_values(&labels);
+        }
+    }
+
+    fn acquire(
+        buckets: &mut HashMap<ProtocolId, TokenBucket>,
+        state: &str,
+        protocol: ProtocolId,
+        bytes: usize,
+        now: Instant,
+    ) -> bool {
+        let allowed = buckets
+            .get_mut(&protocol)
+            .map_or(true, |bucket| bucket.try_acquire(bytes as u64, now));
+        if !allowed {
+            counters::LIBRA_NETWORK_PROTOCOL_RATE_LIMITED
+                .with_label_values(&[state, protocol.as_str()])
+                .inc();
+        }
+        allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_per_protocol_limits() {
+        let mut limits = ProtocolRateLimits::new();
+        limits.insert(
+            ProtocolId::MempoolDirectSend,
+            ProtocolRateLimit {
+                inbound_bytes_per_sec: Some(1000),
+                outbound_bytes_per_sec: None,
+            },
+        );
+        let now = Instant::now();
+        let peer_id = PeerId::random();
+        let mut limiter = BandwidthLimiter::new(peer_id, &limits, now);
+        assert!(limiter.inbound(ProtocolId::MempoolDirectSend, 800, now));
+        assert!(!limiter.inbound(ProtocolId::MempoolDirectSend, 800, now));
+        // other protocols and directions are not limited
+        assert!(limiter.inbound(ProtocolId::ConsensusRpc, 10_000, now));
+        assert!(limiter.outbound(ProtocolId::MempoolDirectSend, 10_000, now));
+        let later = now + Duration::from_secs(1);
+        assert!(limiter.inbound(ProtocolId::MempoolDirectSend, 800, later));
+
+        let labels = [
+            counters::RECEIVED_LABEL,
+            ProtocolId::MempoolDirectSend.as_str(),
+            &peer_id.short_str(),
+        ];
+        assert_eq!(
+            counters::LIBRA_NETWORK_PROTOCOL_MESSAGES
+                .with_label_values(&labels)
+                .get(),
+            3
+        );
+        limiter.remove_counters();
+        assert!(counters::LIBRA_NETWORK_PROTOCOL_MESSAGES
+            .remove_label_values(&labels)
+            .is_err());
+    }
+}
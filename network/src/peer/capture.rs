@@ -0,0 +1,333 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Capture and replay of the traffic of the `Peer` actors, for debugging.
+//!
+//! When a `TrafficRecorder` is configured, every `Peer` actor appends the messages it sends and
+//! receives to a capture file, as seen by the upper layers: inbound messages are recorded after
+//! decompression and outbound messages before compression. Each record is a `CapturedMessage`
+//! serialized with LCS and preceded by its length as a big-endian `u32`. The records are written
+//! by a dedicated thread, which flushes the file whenever it is idle. The records are queued for
+//! the thread up to `RECORD_QUEUE_SIZE`: when the disk can't keep up, the records which don't fit
+//! are dropped and counted, rather than slowing down the `Peer` actors or using unbounded memory.
+//!
+//! `replay_inbound` plays the inbound messages a node received from a peer into a node under test
+//! listening on a `/memory/<port>` address of the same process.
+
+use crate::{
+    counters,
+    peer::framing::MessageFraming,
+    protocols::wire::{
+        handshake::v1::SupportedProtocols,
+        messaging::v1::{
+            compression::{CompressionThresholds, MessageCompressor},
+            NetworkMessage,
+        },
+    },
+    transport::build_memory_transport,
+    ProtocolId,
+};
+use anyhow::{anyhow, Result};
+use futures::{future, sink::SinkExt, stream::StreamExt};
+use libra_logger::prelude::*;
+use libra_network_address::NetworkAddress;
+use libra_types::PeerId;
+use netcore::{compat::IoCompat, transport::Transport};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::mpsc::{self, TryRecvError, TrySendError},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::time::{delay_until, Instant};
+use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
+
+/// Maximum number of records waiting for the writer thread.
+const RECORD_QUEUE_SIZE: usize = 16_384;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CapturedMessage {
+    /// Microseconds since the UNIX epoch.
+    pub timestamp_micros: u64,
+    pub peer_id: PeerId,
+    pub direction: Direction,
+    /// The protocol of the message, `None` for the inbound messages which don't carry one (RPC
+    /// responses and pings).
+    pub protocol: Option<ProtocolId>,
+    pub message: NetworkMessage,
+}
+
+/// Handle to the thread writing a capture file, shared by all the `Peer` actors.
+#[derive(Clone)]
+pub struct TrafficRecorder {
+    records_tx: mpsc::SyncSender<CapturedMessage>,
+}
+
+impl TrafficRecorder {
+    /// Creates the capture file, truncating it if it exists, and starts the writer thread.
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let (records_tx, records_rx) = mpsc::sync_channel::<CapturedMessage>(RECORD_QUEUE_SIZE);
+        let path = path.to_path_buf();
+        thread::Builder::new()
+            .name("traffic-recorder".to_string())
+            .spawn(move || {
+                loop {
+                    let record = match records_rx.try_recv() {
+                        Ok(record) => record,
+                        Err(TryRecvError::Empty) => {
+                            // Flush while idle, so that the capture can be read as the node runs.
+                            if let Err(err) = file.flush() {
+                                error!("Failed to write capture file {:?}: {}", path, err);
+                                return;
+                            }
+                            match records_rx.recv() {
+                                Ok(record) => record,
+                                Err(_) => break,
+                            }
+                        }
+                        Err(TryRecvError::Disconnected) => break,
+                    };
+                    if let Err(err) = write_record(&mut file, &record) {
+                        error!("Failed to write capture file {:?}: {}", path, err);
+                        return;
+                    }
+                }
+                let _ = file.flush();
+            })?;
+        Ok(Self { records_tx })
+    }
+
+    pub fn record(
+        &self,
+        peer_id: PeerId,
+        direction: Direction,
+        protocol: Option<ProtocolId>,
+        message: &NetworkMessage,
+    ) {
+        let timestamp_micros = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_micros() as u64);
+        let record = CapturedMessage {
+            timestamp_micros,
+            peer_id,
+            direction,
+            protocol,
+            message: message.clone(),
+        };
+        match self.records_tx.try_send(record) {
+            Ok(()) => (),
+            Err(TrySendError::Full(_)) => counters::LIBRA_NETWORK_CAPTURE_DROPPED_RECORDS.inc(),
+            // The writer thread only stops on a write error, which it already logged.
+            Err(TrySendError::Disconnected(_)) => (),
+        }
+    }
+}
+
+pub fn write_record<W: Write>(writer: &mut W, record: &CapturedMessage) -> Result<()> {
+    let bytes = lcs::to_bytes(record)?;
+    writer.write_all(&u32::try_from(bytes.len())?.to_be_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Iterator over the records of a capture file.
+pub struct CaptureReader<R> {
+    reader: R,
+}
+
+impl CaptureReader<BufReader<File>> {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: Read> CaptureReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = Result<CapturedMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut len = [0u8; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => (),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return None,
+            Err(err) => return Some(Err(err.into())),
+        }
+        let mut bytes = vec![0u8; u32::from_be_bytes(len) as usize];
+        Some(
+            self.reader
+                .read_exact(&mut bytes)
+                .map_err(Into::into)
+                .and_then(|()| lcs::from_bytes(&bytes).map_err(Into::into)),
+        )
+    }
+}
+
+/// Connects to the node listening on the in-memory address `addr` as `peer_id`, and sends it the
+/// RPC requests and DirectSend messages that `peer_id` sent in `records`. With `realtime`, the
+/// messages are spaced as they were captured, otherwise they are sent as fast as possible.
+/// Returns the number of messages sent.
+pub async fn replay_inbound(
+    records: Vec<CapturedMessage>,
+    peer_id: PeerId,
+    addr: NetworkAddress,
+    realtime: bool,
+) -> Result<usize> {
+    let records: Vec<_> = records
+        .into_iter()
+        .filter(|record| record.direction == Direction::Inbound && record.peer_id == peer_id)
+        .filter(|record| match record.message {
+            NetworkMessage::RpcRequest(_) | NetworkMessage::DirectSendMsg(_) => true,
+            _ => false,
+        })
+        .collect();
+    let protocols: Vec<_> = records
+        .iter()
+        .filter_map(|record| record.protocol)
+        .collect();
+    let protocols: SupportedProtocols = protocols.iter().into();
+
+    // Without compression advertised in the handshake, the messages are sent as captured.
+    let transport = build_memory_transport(peer_id, protocols);
+    let connection = transport
+        .dial(addr.clone())
+        .map_err(|err| anyhow!("Failed to dial {}: {}", addr, err))?
+        .await
+        .map_err(|err| anyhow!("Failed to connect to {}: {}", addr, err))?;
+    let framing = MessageFraming::new(
+        connection.metadata.messaging_protocol(),
+        MessageCompressor::new(
+            &CompressionThresholds::new(),
+            &SupportedProtocols::default(),
+        ),
+    );
+    let (reader, writer) = tokio::io::split(IoCompat::new(connection.socket));
+    let mut writer = FramedWrite::new(writer, LengthDelimitedCodec::new());
+    // Drain what the node sends back, so that it never blocks on the connection.
+    tokio::spawn(
+        FramedRead::new(reader, LengthDelimitedCodec::new()).for_each(|_| future::ready(())),
+    );
+
+    let start = Instant::now();
+    let first_timestamp = records.first().map_or(0, |record| record.timestamp_micros);
+    for record in &records {
+        if realtime {
+            let offset = record.timestamp_micros.saturating_sub(first_timestamp);
+            delay_until(start + Duration::from_micros(offset)).await;
+        }
+        let protocol = record
+            .protocol
+            .ok_or_else(|| anyhow!("Captured message without protocol"))?;
+        writer
+            .send(framing.encode(record.message.clone(), protocol, false)?)
+            .await?;
+    }
+    writer.flush().await?;
+    Ok(records.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocols::wire::messaging::v1::{DirectSendMsg, Nonce, RpcResponse};
+    use libra_temppath::TempPath;
+
+    fn record(direction: Direction, message: NetworkMessage) -> CapturedMessage {
+        let protocol = match &message {
+            NetworkMessage::DirectSendMsg(message) => Some(message.protocol_id),
+            _ => None,
+        };
+        CapturedMessage {
+            timestamp_micros: 1,
+            peer_id: PeerId::random(),
+            direction,
+            protocol,
+            message,
+        }
+    }
+
+    #[test]
+    fn test_capture_roundtrip() {
+        let records = vec![
+            record(
+                Direction::Inbound,
+                NetworkMessage::DirectSendMsg(DirectSendMsg {
+                    protocol_id: ProtocolId::MempoolDirectSend,
+                    priority: 0,
+                    raw_msg: vec![1, 2, 3],
+                }),
+            ),
+            record(
+                Direction::Outbound,
+                NetworkMessage::RpcResponse(RpcResponse {
+                    request_id: 7,
+                    priority: 0,
+                    raw_response: vec![4, 5],
+                }),
+            ),
+            record(Direction::Inbound, NetworkMessage::Ping(Nonce(42))),
+        ];
+        let mut bytes = vec![];
+        for record in &records {
+            write_record(&mut bytes, record).unwrap();
+        }
+        let read: Vec<_> = CaptureReader::new(&bytes[..])
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(read, records);
+
+        // a truncated record is an error
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(CaptureReader::new(truncated).last().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_recorder() {
+        let path = TempPath::new();
+        let recorder = TrafficRecorder::new(path.path()).unwrap();
+        let peer_id = PeerId::random();
+        recorder.record(
+            peer_id,
+            Direction::Inbound,
+            None,
+            &NetworkMessage::Ping(Nonce(1)),
+        );
+        recorder.record(
+            peer_id,
+            Direction::Outbound,
+            None,
+            &NetworkMessage::Pong(Nonce(1)),
+        );
+        // dropping the last handle stops the writer thread once the records are written
+        drop(recorder);
+        let mut read = vec![];
+        for _ in 0..100 {
+            read = CaptureReader::open(path.path())
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            if read.len() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].direction, Direction::Inbound);
+        assert_eq!(read[1].message, NetworkMessage::Pong(Nonce(1)));
+    }
+}
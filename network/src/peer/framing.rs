@@ -0,0 +1,113 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serialization of the messages of a connection, as per the messaging protocol version negotiated
+//! in the handshake.
+//!
+//! v1 frames are LCS-serialized `NetworkMessage`s, whose payloads carry a codec byte when
+//! compression was negotiated. v2 frames are prefixed with `MessageFlags` instead (see
+//! [`messaging::v2`](crate::protocols::wire::messaging::v2)).
+
+use crate::{
+    protocols::wire::{
+        handshake::v1::MessagingProtocolVersion,
+        messaging::{
+            v1::{compression::MessageCompressor, NetworkMessage},
+            v2::{self, MessageFlags},
+        },
+    },
+    ProtocolId,
+};
+use anyhow::Result;
+use bytes::Bytes;
+
+/// An inbound message whose payload may still be compressed.
+#[derive(Debug)]
+pub struct InboundFrame {
+    pub flags: MessageFlags,
+    pub message: NetworkMessage,
+}
+
+impl InboundFrame {
+    /// The protocol of the message, for the messages which carry one.
+    pub fn protocol(&self) -> Option<ProtocolId> {
+        match &self.message {
+            NetworkMessage::RpcRequest(request) => Some(request.protocol_id),
+            NetworkMessage::DirectSendMsg(message) => Some(message.protocol_id),
+            _ => None,
+        }
+    }
+}
+
+/// Frames the messages of a connection.
+#[derive(Debug)]
+pub struct MessageFraming {
+    messaging_protocol: MessagingProtocolVersion,
+    compressor: MessageCompressor,
+}
+
+impl MessageFraming {
+    pub fn new(
+        messaging_protocol: MessagingProtocolVersion,
+        compressor: MessageCompressor,
+    ) -> Self {
+        Self {
+            messaging_protocol,
+            compressor,
+        }
+    }
+
+    pub fn messaging_protocol(&self) -> MessagingProtocolVersion {
+        self.messaging_protocol
+    }
+
+    /// Serializes an outbound message sent on `protocol`, compressing its payload as negotiated.
+    pub fn encode(
+        &self,
+        message: NetworkMessage,
+        protocol: ProtocolId,
+        priority: bool,
+    ) -> Result<Bytes> {
+        let frame = match self.messaging_protocol {
+            MessagingProtocolVersion::V1 => {
+                lcs::to_bytes(&self.compressor.compress(message, protocol))?
+            }
+            MessagingProtocolVersion::V2 => {
+                let (message, compressed) = self.compressor.deflate_payload(message, protocol);
+                v2::to_frame(MessageFlags::new(compressed, priority), &message)?
+            }
+        };
+        Ok(frame.into())
+    }
+
+    /// Serializes an outbound message which isn't sent on behalf of an application protocol,
+    /// such as a pong.
+    pub fn encode_control(&self, message: &NetworkMessage) -> Result<Bytes> {
+        let frame = match self.messaging_protocol {
+            MessagingProtocolVersion::V1 => lcs::to_bytes(message)?,
+            MessagingProtocolVersion::V2 => v2::to_frame(MessageFlags::new(false, true), message)?,
+        };
+        Ok(frame.into())
+    }
+
+    /// Deserializes an inbound frame, leaving its payload compressed so that it can be checked
+    /// against the rate limits first.
+    pub fn decode(&self, frame: &[u8]) -> Result<InboundFrame> {
+        let (flags, message) = match self.messaging_protocol {
+            MessagingProtocolVersion::V1 => (MessageFlags::default(), lcs::from_bytes(frame)?),
+            MessagingProtocolVersion::V2 => v2::from_frame(frame)?,
+        };
+        Ok(InboundFrame { flags, message })
+    }
+
+    /// Decompresses the payload of an inbound frame.
+    pub fn decompress(&self, frame: InboundFrame) -> Result<NetworkMessage> {
+        match self.messaging_protocol {
+            MessagingProtocolVersion::V1 => self.compressor.decompress(frame.message),
+            MessagingProtocolVersion::V2 if frame.flags.is_compressed() => {
+                self.compressor.inflate_payload(frame.message)
+            }
+            MessagingProtocolVersion::V2 => Ok(frame.message),
+        }
+    }
+}
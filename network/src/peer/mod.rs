@@ -5,13 +5,21 @@
 //! and opening substreams as well as negotiating particular protocols on those substreams.
 use crate::{
     counters,
+    peer::{
+        bandwidth::{is_priority_protocol, BandwidthLimiter, ProtocolRateLimits},
+        capture::{Direction, TrafficRecorder},
+        framing::MessageFraming,
+    },
     peer_manager::PeerManagerError,
-    protocols::wire::messaging::v1::NetworkMessage,
+    protocols::wire::messaging::v1::{
+        compression::{CompressionThresholds, MessageCompressor},
+        ErrorCode, NetworkMessage,
+    },
     transport,
     transport::{Connection, ConnectionMetadata},
     ProtocolId,
 };
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use futures::{
     self,
     channel::oneshot,
@@ -22,7 +30,11 @@ use futures::{
 use libra_logger::prelude::*;
 use libra_types::PeerId;
 use netcore::compat::IoCompat;
-use std::{fmt::Debug, io, time::Duration};
+use std::{
+    fmt::Debug,
+    io,
+    time::{Duration, Instant},
+};
 use stream_ratelimiter::*;
 use tokio::runtime::Handle;
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
@@ -31,9 +43,15 @@ use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 pub const MESSAGE_RATE_LIMIT_WINDOW: Duration = Duration::from_millis(10);
 pub const MESSAGE_RATE_LIMIT_COUNT: usize = 100;
 
+pub mod bandwidth;
+pub mod capture;
+pub mod framing;
 #[cfg(test)]
 mod test;
 
+/// A serialized message queued for the writer task, with the channel to ack its write on.
+type WriteRequest = (Bytes, oneshot::Sender<Result<(), PeerManagerError>>);
+
 #[derive(Debug)]
 pub enum PeerRequest {
     SendMessage(
@@ -76,6 +94,12 @@ pub struct Peer<TSocket> {
     rpc_notifs_tx: channel::Sender<PeerNotification>,
     /// Channel to notify about new inbound DirectSend substreams.
     direct_send_notifs_tx: channel::Sender<PeerNotification>,
+    /// Per-protocol accounting and rate limits of the traffic with the peer.
+    bandwidth: BandwidthLimiter,
+    /// Serialization and compression of the messages, as negotiated in the handshake.
+    framing: MessageFraming,
+    /// Recorder of the messages exchanged with the peer, if traffic capture is enabled.
+    traffic_recorder: Option<TrafficRecorder>,
     /// Flag to indicate if the actor is being shut down.
     state: State,
 }
@@ -91,11 +115,23 @@ where
         peer_notifs_tx: channel::Sender<PeerNotification>,
         rpc_notifs_tx: channel::Sender<PeerNotification>,
         direct_send_notifs_tx: channel::Sender<PeerNotification>,
+        rate_limits: &ProtocolRateLimits,
+        compression_thresholds: &CompressionThresholds,
+        traffic_recorder: Option<TrafficRecorder>,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
             socket,
         } = connection;
+        let bandwidth =
+            BandwidthLimiter::new(connection_metadata.peer_id(), rate_limits, Instant::now());
+        let framing = MessageFraming::new(
+            connection_metadata.messaging_protocol(),
+            MessageCompressor::new(
+                compression_thresholds,
+                connection_metadata.application_protocols(),
+            ),
+        );
         Self {
             executor,
             connection_metadata,
@@ -104,6 +140,9 @@ where
             peer_notifs_tx,
             rpc_notifs_tx,
             direct_send_notifs_tx,
+            bandwidth,
+            framing,
+            traffic_recorder,
             state: State::Connected,
         }
     }
@@ -128,11 +167,12 @@ where
             .fuse();
         // Convert WriteHalf to Sink of length-delimited messages.
         let writer = FramedWrite::new(writer, LengthDelimitedCodec::new());
-        // Start writer "process" as a separate task. We receive two handles to communicate with
+        // Start writer "process" as a separate task. We receive three handles to communicate with
         // the task:
-        // `write_reqs_tx`: Instruction to send a NetworkMessage on the wire.
+        // `write_reqs_tx`: Instruction to send a serialized message on the wire.
+        // `priority_write_reqs_tx`: Same as `write_reqs_tx`, for messages written first.
         // `close_tx`: Instruction to close the underlying connection.
-        let (write_reqs_tx, close_tx) =
+        let (write_reqs_tx, priority_write_reqs_tx, close_tx) =
             Self::start_writer_task(&self.executor, self_peer_id, writer);
         // Start main Peer event loop.
         loop {
@@ -141,7 +181,11 @@ where
                     futures::select! {
                         maybe_req = self.requests_rx.next() => {
                             if let Some(request) = maybe_req {
-                                self.handle_request(request, write_reqs_tx.clone()).await;
+                                self.handle_request(
+                                    request,
+                                    write_reqs_tx.clone(),
+                                    priority_write_reqs_tx.clone(),
+                                ).await;
                             } else {
                                 // This branch will only be taken if all PeerRequest senders for this Peer
                                 // get dropped.
@@ -151,7 +195,7 @@ where
                         maybe_message = reader.next() => {
                             match maybe_message {
                                 Some(Ok(message)) =>  {
-                                    if let Err(err) = self.handle_inbound_message(message, write_reqs_tx.clone()).await {
+                                    if let Err(err) = self.handle_inbound_message(message, priority_write_reqs_tx.clone()).await {
                                         warn!("Error in handling inbound message from peer: {:?}. Error: {:?}",
                                             self_peer_id.short_str(), err);
                                     }
@@ -199,13 +243,17 @@ where
                 }
             }
         }
+        self.bandwidth.remove_counters();
     }
 
     // Start a new task on the given executor which is responsible for writing outbound messages on
-    // the wire. The function returns two channels which can be used to send intructions to the
+    // the wire. The function returns three channels which can be used to send intructions to the
     // task:
-    // 1. The first channel is used to send outbound NetworkMessages to the task
-    // 2. The second channel is used to instruct the task to close the connection and terminate.
+    // 1. The first channel is used to send serialized outbound messages to the task
+    // 2. The second channel is used to send outbound messages which are written before any
+    // message queued on the first one, so that they don't wait behind bulk traffic when the
+    // socket is congested.
+    // 3. The third channel is used to instruct the task to close the connection and terminate.
     // If outbound messages are queued when the task receives a close instruction, it discards
     // them and immediately closes the connection.
     fn start_writer_task<T: tokio::io::AsyncWrite + Send + Unpin + 'static>(
@@ -213,45 +261,36 @@ where
         self_peer_id: PeerId,
         mut writer: FramedWrite<T, LengthDelimitedCodec>,
     ) -> (
-        channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        channel::Sender<WriteRequest>,
+        channel::Sender<WriteRequest>,
         oneshot::Sender<()>,
     ) {
-        let (write_reqs_tx, mut write_reqs_rx): (
-            channel::Sender<(
-                NetworkMessage,
-                oneshot::Sender<Result<(), PeerManagerError>>,
-            )>,
+        let (write_reqs_tx, mut write_reqs_rx): (channel::Sender<WriteRequest>, _) =
+            channel::new(1024, &counters::PENDING_WIRE_MESSAGES);
+        let (priority_write_reqs_tx, mut priority_write_reqs_rx): (
+            channel::Sender<WriteRequest>,
             _,
         ) = channel::new(1024, &counters::PENDING_WIRE_MESSAGES);
         let (close_tx, close_rx) = oneshot::channel();
         let writer_task = async move {
             let mut close_rx = close_rx.into_stream();
             loop {
-                futures::select! {
-                    (message, ack_ch) = write_reqs_rx.select_next_some() => {
-                        if let Err(e) = writer
-                            .send(
-                                lcs::to_bytes(&message)
-                                    .expect("Outboung message failed to serialize")
-                                    .into(),
-                            )
-                            .map_ok(|_| ack_ch.send(Ok(())))
-                            .await
-                        {
-                            warn!(
-                                "Error in sending message to peer: {:?}. Error: {:?}",
-                                self_peer_id.short_str(),
-                                e
-                            );
-                            break;
-                        }
-                    },
+                // The branches are polled in order: a close instruction comes first, then the
+                // priority messages.
+                let (message, ack_ch) = futures::select_biased! {
                     _ = close_rx.select_next_some() => {
                         break;
-                    }
+                    },
+                    write_req = priority_write_reqs_rx.select_next_some() => write_req,
+                    write_req = write_reqs_rx.select_next_some() => write_req,
+                };
+                if let Err(e) = writer.send(message).map_ok(|_| ack_ch.send(Ok(()))).await {
+                    warn!(
+                        "Error in sending message to peer: {:?}. Error: {:?}",
+                        self_peer_id.short_str(),
+                        e
+                    );
+                    break;
                 }
             }
             info!("Closing connection to peer: {:?}", self_peer_id.short_str());
@@ -280,23 +319,58 @@ where
             }
         };
         executor.spawn(writer_task);
-        (write_reqs_tx, close_tx)
+        (write_reqs_tx, priority_write_reqs_tx, close_tx)
     }
 
     async fn handle_inbound_message(
         &mut self,
         message: BytesMut,
-        mut write_reqs_tx: channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        mut priority_write_reqs_tx: channel::Sender<WriteRequest>,
     ) -> Result<(), PeerManagerError> {
         trace!("Received message from Peer {}", self.peer_id().short_str(),);
         // Read inbound message from stream.
         let message = message.freeze();
-        let message: NetworkMessage = lcs::from_bytes(&message)?;
+        let message_len = message.len();
+        let frame = self.framing.decode(&message)?;
+        let protocol = frame.protocol();
+        if let Some(protocol) = protocol {
+            if !self
+                .bandwidth
+                .inbound(protocol, message_len, Instant::now())
+            {
+                debug!(
+                    "Dropping message for protocol {} from peer {}: over the inbound rate limit",
+                    protocol,
+                    self.peer_id().short_str()
+                );
+                // Fail the RPC right away rather than letting the remote peer wait for a timeout,
+                // unless the peer doesn't understand the error.
+                match &frame.message {
+                    NetworkMessage::RpcRequest(request)
+                        if self
+                            .connection_metadata
+                            .application_protocols()
+                            .supports_rate_limited_error() =>
+                    {
+                        let error =
+                            NetworkMessage::Error(ErrorCode::RateLimited(request.request_id));
+                        let error = self.framing.encode_control(&error)?;
+                        let (ack_tx, _) = oneshot::channel();
+                        priority_write_reqs_tx.send((error, ack_tx)).await?;
+                    }
+                    _ => (),
+                }
+                return Ok(());
+            }
+        }
+        let message = self.framing.decompress(frame)?;
+        if let Some(recorder) = &self.traffic_recorder {
+            recorder.record(self.peer_id(), Direction::Inbound, protocol, &message);
+        }
         match message {
-            NetworkMessage::RpcRequest(_) | NetworkMessage::RpcResponse(_) => {
+            NetworkMessage::RpcRequest(_)
+            | NetworkMessage::RpcResponse(_)
+            | NetworkMessage::Error(ErrorCode::RateLimited(_)) => {
                 let notif = PeerNotification::NewMessage(message);
                 self.rpc_notifs_tx.send(notif).await.map_err(|err| {
                     warn!("Failed to send notification to RPC actor. Error: {:?}", err);
@@ -319,10 +393,10 @@ where
                 Ok(())
             }
             NetworkMessage::Ping(nonce) => {
-                let pong = NetworkMessage::Pong(nonce);
+                let pong = self.framing.encode_control(&NetworkMessage::Pong(nonce))?;
                 let (ack_tx, _) = oneshot::channel();
                 // Resond to a ping right away.
-                write_reqs_tx.send((pong, ack_tx)).await?;
+                priority_write_reqs_tx.send((pong, ack_tx)).await?;
                 Ok(())
             }
             _ => unreachable!("Unhandled"),
@@ -332,10 +406,8 @@ where
     async fn handle_request<'a>(
         &'a mut self,
         request: PeerRequest,
-        mut write_reqs_tx: channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        write_reqs_tx: channel::Sender<WriteRequest>,
+        priority_write_reqs_tx: channel::Sender<WriteRequest>,
     ) {
         trace!(
             "Peer {} PeerRequest::{:?}",
@@ -344,6 +416,34 @@ where
         );
         match request {
             PeerRequest::SendMessage(message, protocol, channel) => {
+                if let Some(recorder) = &self.traffic_recorder {
+                    recorder.record(
+                        self.peer_id(),
+                        Direction::Outbound,
+                        Some(protocol),
+                        &message,
+                    );
+                }
+                let priority = is_priority_protocol(protocol);
+                let message = match self.framing.encode(message, protocol, priority) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        let _ = channel.send(Err(e.into()));
+                        return;
+                    }
+                };
+                if !self
+                    .bandwidth
+                    .outbound(protocol, message.len(), Instant::now())
+                {
+                    let _ = channel.send(Err(PeerManagerError::RateLimited(protocol)));
+                    return;
+                }
+                let mut write_reqs_tx = if priority {
+                    priority_write_reqs_tx
+                } else {
+                    write_reqs_tx
+                };
                 if let Err(e) = write_reqs_tx.send((message, channel)).await {
                     error!(
                         "Failed to send message for protocol {:?} to peer: {:?}. Error: {:?}",
@@ -2,15 +2,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    peer::{DisconnectReason, Peer, PeerHandle, PeerNotification},
+    peer::{
+        bandwidth::{ProtocolRateLimit, ProtocolRateLimits},
+        DisconnectReason, Peer, PeerHandle, PeerNotification,
+    },
     protocols::wire::{
-        handshake::v1::MessagingProtocolVersion,
-        messaging::v1::{DirectSendMsg, NetworkMessage},
+        handshake::v1::{MessagingProtocolVersion, SupportedProtocols},
+        messaging::v1::{
+            compression::CompressionThresholds, DirectSendMsg, ErrorCode, NetworkMessage,
+            RpcRequest,
+        },
+    },
+    transport::{
+        build_handshake_msg, perform_handshake, Connection, ConnectionId, ConnectionMetadata,
     },
-    transport::{Connection, ConnectionId, ConnectionMetadata},
     ProtocolId,
 };
-use futures::{future::join, io::AsyncWriteExt, stream::StreamExt, SinkExt};
+use futures::{future::join, io::AsyncWriteExt, stream::StreamExt, FutureExt, SinkExt};
 use libra_network_address::NetworkAddress;
 use libra_types::PeerId;
 use memsocket::MemorySocket;
@@ -34,6 +42,27 @@ fn build_test_peer(
     channel::Receiver<PeerNotification>,
     channel::Receiver<PeerNotification>,
     channel::Receiver<PeerNotification>,
+) {
+    build_rate_limited_test_peer(
+        executor,
+        origin,
+        &ProtocolRateLimits::new(),
+        [].iter().into(),
+    )
+}
+
+fn build_rate_limited_test_peer(
+    executor: Handle,
+    origin: ConnectionOrigin,
+    rate_limits: &ProtocolRateLimits,
+    application_protocols: SupportedProtocols,
+) -> (
+    Peer<MemorySocket>,
+    PeerHandle,
+    MemorySocket,
+    channel::Receiver<PeerNotification>,
+    channel::Receiver<PeerNotification>,
+    channel::Receiver<PeerNotification>,
 ) {
     let (a, b) = MemorySocket::new_pair();
     let peer_id = PeerId::random();
@@ -48,7 +77,7 @@ fn build_test_peer(
             NetworkAddress::from_str("/ip4/127.0.0.1/tcp/8081").unwrap(),
             origin,
             MessagingProtocolVersion::V1,
-            [].iter().into(),
+            application_protocols,
         ),
         socket: a,
     };
@@ -60,6 +89,9 @@ fn build_test_peer(
         peer_notifs_tx,
         peer_rpc_notifs_tx,
         peer_direct_send_notifs_tx,
+        rate_limits,
+        &CompressionThresholds::new(),
+        None,
     );
     let peer_handle = PeerHandle::new(peer_id, peer_req_tx);
 
@@ -127,6 +159,74 @@ fn build_test_connected_peers(
     )
 }
 
+/// Connects a node supporting `messaging_protocols_a` to a node supporting
+/// `messaging_protocols_b`, both with compression, and starts a Peer actor at each end. Returns
+/// the negotiated version and, for each end, the handle of its Peer actor and its DirectSend
+/// notifications.
+fn build_negotiated_peers(
+    rt: &mut Runtime,
+    messaging_protocols_a: &[MessagingProtocolVersion],
+    messaging_protocols_b: &[MessagingProtocolVersion],
+) -> (
+    MessagingProtocolVersion,
+    (PeerHandle, channel::Receiver<PeerNotification>),
+    (PeerHandle, channel::Receiver<PeerNotification>),
+) {
+    let mut application_protocols: SupportedProtocols = [PROTOCOL].iter().into();
+    application_protocols.add_compression([PROTOCOL].iter());
+    let handshake_a = build_handshake_msg(messaging_protocols_a, application_protocols.clone());
+    let handshake_b = build_handshake_msg(messaging_protocols_b, application_protocols);
+    let (peer_id_a, peer_id_b) = (PeerId::random(), PeerId::random());
+    let addr = NetworkAddress::from_str("/memory/0").unwrap();
+    let (socket_a, socket_b) = MemorySocket::new_pair();
+    let (connection_a, connection_b) = rt.block_on(join(
+        perform_handshake(
+            peer_id_b,
+            socket_a,
+            addr.clone(),
+            ConnectionOrigin::Outbound,
+            &handshake_a,
+        ),
+        perform_handshake(
+            peer_id_a,
+            socket_b,
+            addr,
+            ConnectionOrigin::Inbound,
+            &handshake_b,
+        ),
+    ));
+    let (connection_a, connection_b) = (connection_a.unwrap(), connection_b.unwrap());
+    let messaging_protocol = connection_a.metadata.messaging_protocol();
+    assert_eq!(
+        messaging_protocol,
+        connection_b.metadata.messaging_protocol()
+    );
+
+    let start_peer = |connection| {
+        let (peer_notifs_tx, _) = channel::new_test(1);
+        let (peer_rpc_notifs_tx, _) = channel::new_test(1);
+        let (peer_direct_send_notifs_tx, peer_direct_send_notifs_rx) = channel::new_test(1);
+        let (peer_req_tx, peer_req_rx) = channel::new_test(0);
+        let peer = Peer::new(
+            rt.handle().clone(),
+            connection,
+            peer_req_rx,
+            peer_notifs_tx,
+            peer_rpc_notifs_tx,
+            peer_direct_send_notifs_tx,
+            &ProtocolRateLimits::new(),
+            &[(PROTOCOL, 100)].iter().cloned().collect(),
+            None,
+        );
+        let peer_handle = PeerHandle::new(peer.peer_id(), peer_req_tx);
+        rt.spawn(peer.start());
+        (peer_handle, peer_direct_send_notifs_rx)
+    };
+    let peer_a = start_peer(connection_a);
+    let peer_b = start_peer(connection_b);
+    (messaging_protocol, peer_a, peer_b)
+}
+
 async fn assert_new_message_event(peer_notifs_rx: &mut channel::Receiver<PeerNotification>) {
     let event = peer_notifs_rx.next().await;
     assert!(
@@ -238,6 +338,101 @@ fn peer_recv_message() {
     rt.block_on(join(server, client));
 }
 
+fn consensus_rpc_rate_limits() -> ProtocolRateLimits {
+    let mut rate_limits = ProtocolRateLimits::new();
+    rate_limits.insert(
+        ProtocolId::ConsensusRpc,
+        ProtocolRateLimit {
+            inbound_bytes_per_sec: Some(10),
+            outbound_bytes_per_sec: None,
+        },
+    );
+    rate_limits
+}
+
+fn rpc_request_over_limit() -> NetworkMessage {
+    NetworkMessage::RpcRequest(RpcRequest {
+        request_id: 7,
+        protocol_id: ProtocolId::ConsensusRpc,
+        priority: 0,
+        raw_request: Vec::from("a request over the limit"),
+    })
+}
+
+// Test that an inbound RPC request over the rate limit of its protocol is answered with an error.
+#[test]
+fn peer_rejects_rate_limited_rpc() {
+    ::libra_logger::Logger::new().environment_only(true).init();
+    let mut rt = Runtime::new().unwrap();
+    let mut application_protocols: SupportedProtocols = [ProtocolId::ConsensusRpc].iter().into();
+    application_protocols.add_rate_limited_error();
+    let (
+        peer,
+        _peer_handle,
+        connection,
+        _peer_notifs_rx,
+        mut peer_rpc_notifs_rx,
+        _peer_direct_send_notifs_rx,
+    ) = build_rate_limited_test_peer(
+        rt.handle().clone(),
+        ConnectionOrigin::Inbound,
+        &consensus_rpc_rate_limits(),
+        application_protocols,
+    );
+
+    let test = async move {
+        let mut connection = Framed::new(IoCompat::new(connection), LengthDelimitedCodec::new());
+        connection
+            .send(lcs::to_bytes(&rpc_request_over_limit()).unwrap().into())
+            .await
+            .unwrap();
+        let reply = connection.next().await.unwrap().unwrap();
+        assert_eq!(
+            lcs::from_bytes::<NetworkMessage>(&reply).unwrap(),
+            NetworkMessage::Error(ErrorCode::RateLimited(7))
+        );
+        // The request isn't passed on to the RPC actor
+        assert!(peer_rpc_notifs_rx.next().now_or_never().is_none());
+    };
+    rt.spawn(peer.start());
+    rt.block_on(test);
+}
+
+// Test that an inbound RPC request over the rate limit is dropped silently when the peer didn't
+// advertise understanding the rate limited error.
+#[test]
+fn peer_drops_rate_limited_rpc() {
+    ::libra_logger::Logger::new().environment_only(true).init();
+    let mut rt = Runtime::new().unwrap();
+    let (
+        peer,
+        _peer_handle,
+        connection,
+        _peer_notifs_rx,
+        mut peer_rpc_notifs_rx,
+        _peer_direct_send_notifs_rx,
+    ) = build_rate_limited_test_peer(
+        rt.handle().clone(),
+        ConnectionOrigin::Inbound,
+        &consensus_rpc_rate_limits(),
+        [ProtocolId::ConsensusRpc].iter().into(),
+    );
+
+    let test = async move {
+        let mut connection = Framed::new(IoCompat::new(connection), LengthDelimitedCodec::new());
+        connection
+            .send(lcs::to_bytes(&rpc_request_over_limit()).unwrap().into())
+            .await
+            .unwrap();
+        assert!(timeout(Duration::from_millis(100), connection.next())
+            .await
+            .is_err());
+        assert!(peer_rpc_notifs_rx.next().now_or_never().is_none());
+    };
+    rt.spawn(peer.start());
+    rt.block_on(test);
+}
+
 // Test that if two peers request to open a substream with each other simultaneously that
 // we won't deadlock.
 #[test]
@@ -381,3 +576,49 @@ fn peer_terminates_when_request_tx_has_dropped() {
     };
     rt.block_on(join(peer.start(), drop));
 }
+
+// Nodes which only support V1 and nodes which also support V2 exchange messages, compressed or
+// not, over the highest version they both support.
+#[test]
+fn peers_with_different_messaging_protocols_interoperate() {
+    ::libra_logger::Logger::new().environment_only(true).init();
+    let mut rt = Runtime::new().unwrap();
+    let v1_only = [MessagingProtocolVersion::V1];
+    let v1_v2 = [MessagingProtocolVersion::V1, MessagingProtocolVersion::V2];
+    for (messaging_protocols_a, messaging_protocols_b, expected) in vec![
+        (&v1_only[..], &v1_v2[..], MessagingProtocolVersion::V1),
+        (&v1_v2[..], &v1_only[..], MessagingProtocolVersion::V1),
+        (&v1_v2[..], &v1_v2[..], MessagingProtocolVersion::V2),
+    ] {
+        let (
+            messaging_protocol,
+            (mut peer_handle_a, mut peer_direct_send_notifs_rx_a),
+            (mut peer_handle_b, mut peer_direct_send_notifs_rx_b),
+        ) = build_negotiated_peers(&mut rt, messaging_protocols_a, messaging_protocols_b);
+        assert_eq!(messaging_protocol, expected);
+
+        rt.block_on(async move {
+            // The second message is over the compression threshold.
+            for raw_msg in vec![Vec::from("hello world"), vec![7; 10_000]] {
+                let msg = NetworkMessage::DirectSendMsg(DirectSendMsg {
+                    protocol_id: PROTOCOL,
+                    priority: 0,
+                    raw_msg,
+                });
+                for (sender, receiver) in vec![
+                    (&mut peer_handle_a, &mut peer_direct_send_notifs_rx_b),
+                    (&mut peer_handle_b, &mut peer_direct_send_notifs_rx_a),
+                ] {
+                    sender.send_message(msg.clone(), PROTOCOL).await.unwrap();
+                    let received = timeout(Duration::from_secs(10), receiver.next())
+                        .await
+                        .unwrap()
+                        .unwrap();
+                    assert!(
+                        matches!(received, PeerNotification::NewMessage(received_msg) if received_msg == msg)
+                    );
+                }
+            }
+        });
+    }
+}
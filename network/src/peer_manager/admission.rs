@@ -0,0 +1,346 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Admission policy for inbound connections.
+//!
+//! Without remote authentication any node can complete the Noise handshake, so the inbound side
+//! of a public end-point needs bounds of its own:
+//!  * The rate of inbound handshakes per source IP is bounded by the `TransportHandler` before any
+//!  work is done on a new socket, so that a flood from a few addresses doesn't lock the others
+//!  (e.g. the trusted peers) out.
+//!  * Once the handshake completes, `PeerManager` caps the total number of inbound connections and
+//!  the number of inbound connections per IP address and per subnet.
+//!
+//! Preferred peers (trusted peers, seed peers and the configured network peers) are exempt from
+//! the per-IP and per-subnet caps, and an unknown inbound peer is evicted to make room for a
+//! preferred one when the total cap is reached.
+
+use crate::{common::NetworkPublicKeys, transport::ConnectionMetadata};
+use libra_network_address::{parse_ip_tcp, NetworkAddress};
+use libra_types::PeerId;
+use netcore::transport::ConnectionOrigin;
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+use stream_ratelimiter::TokenBucket;
+
+/// IPv4 addresses in the same /24 belong to the same subnet.
+const IPV4_SUBNET_PREFIX_LEN: u32 = 24;
+/// IPv6 addresses in the same /48 belong to the same subnet.
+const IPV6_SUBNET_PREFIX_LEN: u32 = 48;
+
+#[derive(Clone, Copy, Debug)]
+pub struct InboundConnectionLimits {
+    pub max_connections: usize,
+    pub max_connections_per_ip: usize,
+    pub max_connections_per_subnet: usize,
+    pub max_handshakes_per_sec: u64,
+}
+
+/// The outcome of the admission of an inbound connection.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Admission {
+    Accept,
+    /// Accept the connection after disconnecting the given unknown peer.
+    AcceptEvicting(PeerId),
+    /// Reject the connection because of the given limit.
+    Reject(&'static str),
+}
+
+/// Decides which inbound connections `PeerManager` keeps.
+pub struct InboundAdmission {
+    limits: InboundConnectionLimits,
+    trusted_peers: Arc<RwLock<HashMap<PeerId, NetworkPublicKeys>>>,
+    /// Updated by the `PeersFileWatcher` as the peers files change
+    preferred_peers: Arc<RwLock<HashSet<PeerId>>>,
+}
+
+impl InboundAdmission {
+    pub fn new(
+        limits: InboundConnectionLimits,
+        trusted_peers: Arc<RwLock<HashMap<PeerId, NetworkPublicKeys>>>,
+        preferred_peers: Arc<RwLock<HashSet<PeerId>>>,
+    ) -> Self {
+        Self {
+            limits,
+            trusted_peers,
+            preferred_peers,
+        }
+    }
+
+    pub fn limits(&self) -> &InboundConnectionLimits {
+        &self.limits
+    }
+
+    pub fn is_preferred(&self, peer_id: &PeerId) -> bool {
+        self.preferred_peers.read().unwrap().contains(peer_id)
+            || self.trusted_peers.read().unwrap().contains_key(peer_id)
+    }
+
+    /// Decide whether to accept an inbound connection given the currently active connections.
+    /// An active connection with the same peer doesn't count since only one of the two is kept.
+    pub fn admit<'a>(
+        &self,
+        peer_id: PeerId,
+        addr: &NetworkAddress,
+        active_connections: impl Iterator<Item = &'a ConnectionMetadata>,
+    ) -> Admission {
+        let inbound: Vec<_> = active_connections
+            .filter(|conn| conn.origin() == ConnectionOrigin::Inbound && conn.peer_id() != peer_id)
+            .collect();
+        if self.is_preferred(&peer_id) {
+            if inbound.len() < self.limits.max_connections {
+                return Admission::Accept;
+            }
+            // Preferred peers are never rejected, they take the place of an unknown peer if any.
+            return inbound
+                .iter()
+                .map(|conn| conn.peer_id())
+                .find(|other| !self.is_preferred(other))
+                .map_or(Admission::Accept, Admission::AcceptEvicting);
+        }
+
+        if inbound.len() >= self.limits.max_connections {
+            return Admission::Reject("max_connections");
+        }
+        if let Some(ip) = ip_addr(addr) {
+            let unknown_ips: Vec<_> = inbound
+                .iter()
+                .filter(|conn| !self.is_preferred(&conn.peer_id()))
+                .filter_map(|conn| ip_addr(conn.addr()))
+                .collect();
+            if unknown_ips.iter().filter(|other| **other == ip).count()
+                >= self.limits.max_connections_per_ip
+            {
+                return Admission::Reject("max_connections_per_ip");
+            }
+            if unknown_ips
+                .iter()
+                .filter(|other| same_subnet(**other, ip))
+                .count()
+                >= self.limits.max_connections_per_subnet
+            {
+                return Admission::Reject("max_connections_per_subnet");
+            }
+        }
+        Admission::Accept
+    }
+}
+
+/// Bounds the rate of inbound handshakes from each source IP, with a burst of one second worth of
+/// handshakes. Addresses that are not IP addresses (e.g. in-memory ones) are not limited.
+pub struct HandshakeLimiter {
+    /// `0` leaves the handshakes unlimited
+    max_handshakes_per_sec: u64,
+    /// The bucket of each source IP, with the time of its last handshake
+    buckets: HashMap<IpAddr, (TokenBucket, Instant)>,
+}
+
+impl HandshakeLimiter {
+    pub fn new(max_handshakes_per_sec: u64) -> Self {
+        Self {
+            max_handshakes_per_sec,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Takes a handshake from the address if its rate allows it.
+    pub fn try_acquire(&mut self, addr: &NetworkAddress, now: Instant) -> bool {
+        if self.max_handshakes_per_sec == 0 {
+            return true;
+        }
+        let ip = match ip_addr(addr) {
+            Some(ip) => ip,
+            None => return true,
+        };
+        // A bucket idle for a second is full again, it is recreated on the next handshake.
+        self.buckets.retain(|_, (_, last_handshake)| {
+            now.saturating_duration_since(*last_handshake) < Duration::from_secs(1)
+        });
+        let max_handshakes_per_sec = self.max_handshakes_per_sec;
+        let (bucket, last_handshake) = self.buckets.entry(ip).or_insert_with(|| {
+            (
+                TokenBucket::new(max_handshakes_per_sec, max_handshakes_per_sec, now),
+                now,
+            )
+        });
+        *last_handshake = now;
+        bucket.try_acquire(1, now)
+    }
+}
+
+/// The IP address of a peer, if it is connected over IP.
+fn ip_addr(addr: &NetworkAddress) -> Option<IpAddr> {
+    parse_ip_tcp(addr.as_slice()).map(|((ip, _port), _suffix)| ip)
+}
+
+fn same_subnet(a: IpAddr, b: IpAddr) -> bool {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => {
+            let mask = u32::max_value() << (32 - IPV4_SUBNET_PREFIX_LEN);
+            u32::from(a) & mask == u32::from(b) & mask
+        }
+        (IpAddr::V6(a), IpAddr::V6(b)) => {
+            let mask = u128::max_value() << (128 - IPV6_SUBNET_PREFIX_LEN);
+            u128::from(a) & mask == u128::from(b) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        protocols::wire::handshake::v1::MessagingProtocolVersion, transport::ConnectionId,
+        ProtocolId,
+    };
+    use libra_crypto::{x25519, Uniform};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn limits() -> InboundConnectionLimits {
+        InboundConnectionLimits {
+            max_connections: 4,
+            max_connections_per_ip: 2,
+            max_connections_per_subnet: 3,
+            max_handshakes_per_sec: 2,
+        }
+    }
+
+    fn inbound(peer_id: PeerId, addr: &str) -> ConnectionMetadata {
+        ConnectionMetadata::new(
+            peer_id,
+            ConnectionId::default(),
+            addr.parse().unwrap(),
+            ConnectionOrigin::Inbound,
+            MessagingProtocolVersion::V1,
+            [ProtocolId::ConsensusRpc].iter().into(),
+        )
+    }
+
+    #[test]
+    fn test_per_ip_and_subnet_limits() {
+        let preferred = PeerId::random();
+        let admission = InboundAdmission::new(
+            limits(),
+            Arc::new(RwLock::new(HashMap::new())),
+            Arc::new(RwLock::new([preferred].iter().cloned().collect())),
+        );
+        let active = vec![
+            inbound(PeerId::random(), "/ip4/10.0.0.1/tcp/1"),
+            inbound(PeerId::random(), "/ip4/10.0.0.1/tcp/2"),
+        ];
+        let addr = "/ip4/10.0.0.1/tcp/3".parse().unwrap();
+        assert_eq!(
+            admission.admit(PeerId::random(), &addr, active.iter()),
+            Admission::Reject("max_connections_per_ip")
+        );
+        // preferred peers are exempt
+        assert_eq!(
+            admission.admit(preferred, &addr, active.iter()),
+            Admission::Accept
+        );
+        // a reconnecting peer doesn't count against itself
+        assert_eq!(
+            admission.admit(active[0].peer_id(), &addr, active.iter()),
+            Admission::Accept
+        );
+
+        let addr = "/ip4/10.0.0.2/tcp/1".parse().unwrap();
+        assert_eq!(
+            admission.admit(PeerId::random(), &addr, active.iter()),
+            Admission::Accept
+        );
+        let mut active = active;
+        active.push(inbound(PeerId::random(), "/ip4/10.0.0.3/tcp/1"));
+        assert_eq!(
+            admission.admit(PeerId::random(), &addr, active.iter()),
+            Admission::Reject("max_connections_per_subnet")
+        );
+        let addr = "/ip4/10.0.1.1/tcp/1".parse().unwrap();
+        assert_eq!(
+            admission.admit(PeerId::random(), &addr, active.iter()),
+            Admission::Accept
+        );
+    }
+
+    #[test]
+    fn test_preferred_peers_evict_unknown_peers() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let mut pubkeys = || NetworkPublicKeys {
+            identity_public_key: x25519::PrivateKey::generate(&mut rng).public_key(),
+        };
+        let trusted_peers = Arc::new(RwLock::new(HashMap::new()));
+        let admission = InboundAdmission::new(
+            limits(),
+            trusted_peers.clone(),
+            Arc::new(RwLock::new(HashSet::new())),
+        );
+        let unknown = PeerId::random();
+        let mut active = vec![inbound(unknown, "/ip4/10.0.0.1/tcp/1")];
+        let mut trusted = vec![];
+        for i in 0..3 {
+            let peer_id = PeerId::random();
+            trusted.push(peer_id);
+            active.push(inbound(peer_id, &format!("/ip4/10.1.{}.1/tcp/1", i)));
+        }
+        let new_peer = PeerId::random();
+        let addr = "/ip4/10.2.0.1/tcp/1".parse().unwrap();
+        assert_eq!(
+            admission.admit(new_peer, &addr, active.iter()),
+            Admission::Reject("max_connections")
+        );
+
+        // trusted peers are picked up as they are updated
+        {
+            let mut trusted_peers = trusted_peers.write().unwrap();
+            for peer_id in trusted.iter().chain(&[new_peer]) {
+                trusted_peers.insert(*peer_id, pubkeys());
+            }
+        }
+        assert_eq!(
+            admission.admit(new_peer, &addr, active.iter()),
+            Admission::AcceptEvicting(unknown)
+        );
+        // with only preferred peers connected, the limit is exceeded rather than rejecting one
+        active[0] = inbound(PeerId::random(), "/ip4/10.3.0.1/tcp/1");
+        trusted_peers
+            .write()
+            .unwrap()
+            .insert(active[0].peer_id(), pubkeys());
+        assert_eq!(
+            admission.admit(new_peer, &addr, active.iter()),
+            Admission::Accept
+        );
+    }
+
+    #[test]
+    fn test_handshake_flood_does_not_block_other_ips() {
+        let mut limiter = HandshakeLimiter::new(2);
+        let flooding: NetworkAddress = "/ip4/1.2.3.4/tcp/6180".parse().unwrap();
+        let trusted: NetworkAddress = "/ip4/5.6.7.8/tcp/6180".parse().unwrap();
+        let now = Instant::now();
+        let accepted = (0..100)
+            .filter(|_| limiter.try_acquire(&flooding, now))
+            .count();
+        assert_eq!(accepted, 2);
+        // the flood only exhausts the handshakes of its own address
+        assert!(limiter.try_acquire(&trusted, now));
+        assert!(limiter.try_acquire(&trusted, now));
+        assert!(!limiter.try_acquire(&trusted, now));
+
+        // idle addresses are forgotten
+        let later = now + Duration::from_secs(1);
+        assert!(limiter.try_acquire(&flooding, later));
+        assert_eq!(limiter.buckets.len(), 1);
+
+        // in-memory addresses and an unlimited rate are not limited
+        let memory: NetworkAddress = "/memory/0".parse().unwrap();
+        assert!((0..100).all(|_| limiter.try_acquire(&memory, later)));
+        let mut unlimited = HandshakeLimiter::new(0);
+        assert!((0..100).all(|_| unlimited.try_acquire(&flooding, later)));
+    }
+}
@@ -3,6 +3,7 @@
 
 //! Errors that originate from the PeerManager module
 
+use crate::ProtocolId;
 use futures::channel::{mpsc, oneshot};
 use libra_network_address::NetworkAddress;
 use libra_types::PeerId;
@@ -28,6 +29,12 @@ pub enum PeerManagerError {
     #[error("Already connected at {0}")]
     AlreadyConnected(NetworkAddress),
 
+    #[error("Peer {0} is banned")]
+    Banned(PeerId),
+
+    #[error("Outbound rate limit of protocol {0} exceeded")]
+    RateLimited(ProtocolId),
+
     #[error("Sending end of oneshot dropped")]
     OneshotSenderDropped,
 
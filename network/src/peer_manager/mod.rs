@@ -13,10 +13,11 @@
 use crate::{
     counters,
     interface::{NetworkNotification, NetworkProvider, NetworkRequest},
-    peer::DisconnectReason,
+    peer::{bandwidth::ProtocolRateLimits, capture::TrafficRecorder, DisconnectReason},
     protocols::{
         direct_send::Message,
         rpc::{error::RpcError, InboundRpcRequest, OutboundRpcRequest},
+        wire::messaging::v1::compression::CompressionThresholds,
     },
     transport,
     transport::{Connection, ConnectionId, ConnectionMetadata},
@@ -34,22 +35,29 @@ use futures::{
 use libra_config::config::RoleType;
 use libra_logger::prelude::*;
 use libra_network_address::NetworkAddress;
+use libra_security_logger::{security_log, SecurityEvent};
 use libra_types::PeerId;
 use netcore::transport::{ConnectionOrigin, Transport};
 use std::{
     collections::{hash_map::Entry, HashMap},
     fmt::Debug,
     marker::PhantomData,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::runtime::Handle;
 
+pub mod admission;
 pub mod conn_notifs_channel;
 mod error;
+pub mod reputation;
 #[cfg(test)]
 mod tests;
 
 pub use self::error::PeerManagerError;
+use self::{
+    admission::{Admission, HandshakeLimiter, InboundAdmission},
+    reputation::{Misbehavior, PeerReputation},
+};
 
 /// Request received by PeerManager from upstream actors.
 #[derive(Debug)]
@@ -77,6 +85,9 @@ pub enum ConnectionRequest {
         oneshot::Sender<Result<(), PeerManagerError>>,
     ),
     DisconnectPeer(PeerId, oneshot::Sender<Result<(), PeerManagerError>>),
+    /// Penalizes a remote peer for misbehaving; the peer is disconnected and banned once its score
+    /// reaches the ban threshold.
+    ReportMisbehavior(PeerId, Misbehavior),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -204,6 +215,20 @@ impl ConnectionRequestSender {
             .push(peer, ConnectionRequest::DisconnectPeer(peer, oneshot_tx))?;
         oneshot_rx.await?
     }
+
+    /// Report the misbehavior of a remote peer. The function returns when the report has been
+    /// enqueued on PeerManager's event queue.
+    pub fn report_misbehavior(
+        &mut self,
+        peer: PeerId,
+        misbehavior: Misbehavior,
+    ) -> Result<(), PeerManagerError> {
+        self.inner.push(
+            peer,
+            ConnectionRequest::ReportMisbehavior(peer, misbehavior),
+        )?;
+        Ok(())
+    }
 }
 
 /// Responsible for handling and maintaining connections to other Peers
@@ -257,6 +282,16 @@ where
     max_concurrent_network_notifs: usize,
     /// Size of channels between different actors.
     channel_size: usize,
+    /// Misbehavior scores and bans of remote peers.
+    reputation: PeerReputation,
+    /// Admission policy for inbound connections.
+    inbound_admission: InboundAdmission,
+    /// Rate limits applied per protocol on every connection.
+    protocol_rate_limits: ProtocolRateLimits,
+    /// Size thresholds of the protocols compressed on the connections which negotiated it.
+    compression_thresholds: CompressionThresholds,
+    /// Recorder of the traffic of all connections, if traffic capture is enabled.
+    traffic_recorder: Option<TrafficRecorder>,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -282,6 +317,11 @@ where
         channel_size: usize,
         max_concurrent_network_reqs: usize,
         max_concurrent_network_notifs: usize,
+        reputation: PeerReputation,
+        inbound_admission: InboundAdmission,
+        protocol_rate_limits: ProtocolRateLimits,
+        compression_thresholds: CompressionThresholds,
+        traffic_recorder: Option<TrafficRecorder>,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = channel::new(
             channel_size,
@@ -298,6 +338,7 @@ where
                 listen_addr,
                 transport_reqs_rx,
                 transport_notifs_tx_clone,
+                inbound_admission.limits().max_handshakes_per_sec,
             )
         });
         Self {
@@ -319,6 +360,11 @@ where
             max_concurrent_network_reqs,
             max_concurrent_network_notifs,
             channel_size,
+            reputation,
+            inbound_admission,
+            protocol_rate_limits,
+            compression_thresholds,
+            traffic_recorder,
         }
     }
 
@@ -413,8 +459,27 @@ where
         trace!("PeerManagerRequest::{:?}", request);
         match request {
             ConnectionRequest::DialPeer(requested_peer_id, addr, response_tx) => {
-                // Only dial peers which we aren't already connected with
-                if let Some((curr_connection, _)) = self.active_peers.get(&requested_peer_id) {
+                // Only dial peers which we aren't already connected with and which aren't banned
+                if self
+                    .reputation
+                    .is_banned(&requested_peer_id, Instant::now())
+                {
+                    debug!(
+                        "Peer {} is banned. Not dialing address {}",
+                        requested_peer_id.short_str(),
+                        addr
+                    );
+                    if response_tx
+                        .send(Err(PeerManagerError::Banned(requested_peer_id)))
+                        .is_err()
+                    {
+                        warn!(
+                            "Receiver for DialPeer {} dropped",
+                            requested_peer_id.short_str()
+                        );
+                    }
+                } else if let Some((curr_connection, _)) = self.active_peers.get(&requested_peer_id)
+                {
                     let error = PeerManagerError::AlreadyConnected(curr_connection.addr().clone());
                     debug!(
                         "Already connected with Peer {} using connection {:?}. Not dialing address {}",
@@ -454,6 +519,46 @@ where
                     }
                 }
             }
+            ConnectionRequest::ReportMisbehavior(peer_id, misbehavior) => {
+                self.report_misbehavior(peer_id, misbehavior);
+            }
+        }
+    }
+
+    fn report_misbehavior(&mut self, peer_id: PeerId, misbehavior: Misbehavior) {
+        let now = Instant::now();
+        counters::LIBRA_NETWORK_PEER_MISBEHAVIOR
+            .with_label_values(&[misbehavior.as_str()])
+            .inc();
+        for lifted_peer_id in self.reputation.prune(now) {
+            info!("Ban of peer {} expired", lifted_peer_id.short_str());
+        }
+        let ban = self.reputation.report(peer_id, misbehavior, now);
+        counters::LIBRA_NETWORK_BANNED_PEERS.set(self.reputation.num_banned() as i64);
+        let banned_until = match ban {
+            Some(banned_until) => banned_until,
+            None => {
+                debug!(
+                    "Peer {} misbehaved: {}, score: {}",
+                    peer_id.short_str(),
+                    misbehavior,
+                    self.reputation.score(&peer_id, now)
+                );
+                return;
+            }
+        };
+        security_log(SecurityEvent::BannedNetworkPeer)
+            .error("Peer banned for misbehavior")
+            .data(&peer_id)
+            .data(&misbehavior)
+            .data(&banned_until.duration_since(now))
+            .log();
+        counters::LIBRA_NETWORK_PEER_BANS.inc();
+        // Dropping the sender of the NetworkRequest channel closes the connection; upstream is
+        // notified of the LostPeer once the Peer actor shuts down.
+        if let Some((_, sender)) = self.active_peers.remove(&peer_id) {
+            info!("Disconnecting banned peer {}", peer_id.short_str());
+            drop(sender);
         }
     }
 
@@ -528,6 +633,52 @@ where
 
         let mut send_new_peer_notification = true;
 
+        // Refuse connections with banned peers, whoever dialed.
+        if self.reputation.is_banned(&peer_id, Instant::now()) {
+            info!(
+                "Closing {:?} connection with banned Peer {}",
+                conn_meta.origin(),
+                peer_id.short_str()
+            );
+            self.close_connection(connection);
+            return;
+        }
+
+        if conn_meta.origin() == ConnectionOrigin::Inbound {
+            let admission = self.inbound_admission.admit(
+                peer_id,
+                conn_meta.addr(),
+                self.active_peers.values().map(|(metadata, _)| metadata),
+            );
+            match admission {
+                Admission::Accept => {}
+                Admission::AcceptEvicting(evicted_peer_id) => {
+                    info!(
+                        "Disconnecting Peer {} to make room for preferred Peer {}",
+                        evicted_peer_id.short_str(),
+                        peer_id.short_str()
+                    );
+                    counters::LIBRA_NETWORK_INBOUND_CONNECTIONS_EVICTED.inc();
+                    if let Some((_, peer_handle)) = self.active_peers.remove(&evicted_peer_id) {
+                        drop(peer_handle);
+                    }
+                }
+                Admission::Reject(limit) => {
+                    info!(
+                        "Closing inbound connection with Peer {} at {}: {} reached",
+                        peer_id.short_str(),
+                        conn_meta.addr(),
+                        limit
+                    );
+                    counters::LIBRA_NETWORK_INBOUND_CONNECTIONS_REJECTED
+                        .with_label_values(&[limit])
+                        .inc();
+                    self.close_connection(connection);
+                    return;
+                }
+            }
+        }
+
         // Check for and handle simultaneous dialing
         if let Entry::Occupied(active_entry) = self.active_peers.entry(peer_id) {
             let (curr_conn_metadata, _) = active_entry.get();
@@ -551,22 +702,7 @@ where
                     peer_id.short_str()
                 );
                 // Drop the new connection and keep the one already stored in active_peers
-                let drop_fut = async move {
-                    let mut connection = connection;
-                    if let Err(e) = tokio::time::timeout(
-                        transport::TRANSPORT_TIMEOUT,
-                        connection.socket.close(),
-                    )
-                    .await
-                    {
-                        error!(
-                            "Closing connection with Peer {} failed with error: {}",
-                            peer_id.short_str(),
-                            e
-                        );
-                    };
-                };
-                self.executor.spawn(drop_fut);
+                self.close_connection(connection);
                 return;
             }
         }
@@ -579,6 +715,9 @@ where
             self.max_concurrent_network_reqs,
             self.max_concurrent_network_notifs,
             self.channel_size,
+            &self.protocol_rate_limits,
+            &self.compression_thresholds,
+            self.traffic_recorder.clone(),
         );
         // Start background task to handle events (RPCs and DirectSend messages) received from
         // peer.
@@ -599,6 +738,24 @@ where
         }
     }
 
+    /// Close a connection which hasn't been handed to a Peer actor.
+    fn close_connection(&self, connection: Connection<TSocket>) {
+        let peer_id = connection.metadata.peer_id();
+        let drop_fut = async move {
+            let mut connection = connection;
+            if let Err(e) =
+                tokio::time::timeout(transport::TRANSPORT_TIMEOUT, connection.socket.close()).await
+            {
+                error!(
+                    "Closing connection with Peer {} failed with error: {}",
+                    peer_id.short_str(),
+                    e
+                );
+            };
+        };
+        self.executor.spawn(drop_fut);
+    }
+
     fn send_lostpeer_notification(
         &mut self,
         peer_id: PeerId,
@@ -723,6 +880,8 @@ where
     listener: Fuse<TTransport::Listener>,
     transport_reqs_rx: channel::Receiver<TransportRequest>,
     transport_notifs_tx: channel::Sender<TransportNotification<TSocket>>,
+    /// Bounds the rate of inbound handshakes per source IP
+    handshake_limiter: HandshakeLimiter,
 }
 
 impl<TTransport, TSocket> TransportHandler<TTransport, TSocket>
@@ -738,6 +897,7 @@ where
         listen_addr: NetworkAddress,
         transport_reqs_rx: channel::Receiver<TransportRequest>,
         transport_notifs_tx: channel::Sender<TransportNotification<TSocket>>,
+        max_inbound_handshakes_per_sec: u64,
     ) -> (Self, NetworkAddress) {
         let (listener, listen_addr) = transport
             .listen_on(listen_addr)
@@ -749,6 +909,7 @@ where
                 listener: listener.fuse(),
                 transport_reqs_rx,
                 transport_notifs_tx,
+                handshake_limiter: HandshakeLimiter::new(max_inbound_handshakes_per_sec),
             },
             listen_addr,
         )
@@ -770,8 +931,16 @@ where
                 incoming_connection = self.listener.select_next_some() => {
                     match incoming_connection {
                         Ok((upgrade, addr)) => {
-                            debug!("Incoming connection from {}", addr);
-                            pending_inbound_connections.push(upgrade.map(|out| (out, addr)));
+                            if self.handshake_limiter.try_acquire(&addr, Instant::now()) {
+                                debug!("Incoming connection from {}", addr);
+                                pending_inbound_connections.push(upgrade.map(|out| (out, addr)));
+                            } else {
+                                // Dropping the pending upgrade closes the socket.
+                                debug!("Inbound handshake rate exceeded, dropping connection from {}", addr);
+                                counters::LIBRA_NETWORK_INBOUND_CONNECTIONS_REJECTED
+                                    .with_label_values(&["max_handshakes_per_sec"])
+                                    .inc();
+                            }
                         }
                         Err(e) => {
                             warn!("Incoming connection error {}", e);
@@ -0,0 +1,293 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Peer reputation: upper layers report the misbehavior of remote peers (e.g. undecodable
+//! messages, invalid chunks), each report adds a penalty to the peer's score and the score decays
+//! over time. A peer whose score reaches the ban threshold is banned for a while: `PeerManager`
+//! disconnects it, and refuses to dial it or to accept its connections until the ban expires.
+//!
+//! Trusted peers (e.g. the validators of the current epoch) are never banned: a message of a newer
+//! version that this node can't decode must not cut it off from the validator set.
+
+use crate::common::NetworkPublicKeys;
+use libra_config::config::PeerReputationConfig;
+use libra_types::{validator_verifier::VerifyError, PeerId};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+/// Misbehavior of a remote peer, reported by the upper layers.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Misbehavior {
+    /// A message that can't be deserialized.
+    UndecodableMessage,
+    /// A well-formed message that fails validation (e.g. an invalid signature).
+    InvalidMessage,
+    /// A state sync chunk that fails verification.
+    InvalidChunk,
+    /// A valid message that is not expected by the protocol (e.g. an unsolicited response).
+    ProtocolViolation,
+}
+
+impl Misbehavior {
+    /// The score penalty of a single report.
+    pub fn penalty(self, config: &PeerReputationConfig) -> f64 {
+        match self {
+            Misbehavior::UndecodableMessage => config.undecodable_message_penalty,
+            Misbehavior::InvalidMessage => config.invalid_message_penalty,
+            Misbehavior::InvalidChunk => config.invalid_chunk_penalty,
+            Misbehavior::ProtocolViolation => config.protocol_violation_penalty,
+        }
+    }
+
+    /// `InvalidMessage` if the verification failed because of a signature that doesn't verify
+    /// against the validator set. Other failures, e.g. a stale message from a peer lagging behind,
+    /// don't prove that the peer misbehaved.
+    pub fn from_verification_error(err: &anyhow::Error) -> Option<Misbehavior> {
+        if err.chain().any(|cause| cause.is::<VerifyError>()) {
+            Some(Misbehavior::InvalidMessage)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Misbehavior::UndecodableMessage => "undecodable_message",
+            Misbehavior::InvalidMessage => "invalid_message",
+            Misbehavior::InvalidChunk => "invalid_chunk",
+            Misbehavior::ProtocolViolation => "protocol_violation",
+        }
+    }
+}
+
+impl fmt::Display for Misbehavior {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct PeerScore {
+    score: f64,
+    updated_at: Instant,
+}
+
+/// The scores and bans of the remote peers, owned by `PeerManager`.
+#[derive(Debug)]
+pub struct PeerReputation {
+    config: PeerReputationConfig,
+    trusted_peers: Arc<RwLock<HashMap<PeerId, NetworkPublicKeys>>>,
+    scores: HashMap<PeerId, PeerScore>,
+    bans: HashMap<PeerId, Instant>,
+}
+
+impl PeerReputation {
+    pub fn new(
+        config: PeerReputationConfig,
+        trusted_peers: Arc<RwLock<HashMap<PeerId, NetworkPublicKeys>>>,
+    ) -> Self {
+        Self {
+            config,
+            trusted_peers,
+            scores: HashMap::new(),
+            bans: HashMap::new(),
+        }
+    }
+
+    fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.trusted_peers.read().unwrap().contains_key(peer_id)
+    }
+
+    /// Adds the penalty of a misbehavior to the peer's score, unless the peer is trusted. Returns
+    /// the expiration of the ban if the peer just got banned.
+    pub fn report(
+        &mut self,
+        peer_id: PeerId,
+        misbehavior: Misbehavior,
+        now: Instant,
+    ) -> Option<Instant> {
+        if self.is_trusted(&peer_id) {
+            return None;
+        }
+        let score = self.score(&peer_id, now) + misbehavior.penalty(&self.config);
+        if score < self.config.ban_score_threshold {
+            self.scores.insert(
+                peer_id,
+                PeerScore {
+                    score,
+                    updated_at: now,
+                },
+            );
+            return None;
+        }
+        // The peer starts over with a clean score once the ban expires.
+        self.scores.remove(&peer_id);
+        let banned_until = now + Duration::from_millis(self.config.ban_duration_ms);
+        self.bans.insert(peer_id, banned_until);
+        Some(banned_until)
+    }
+
+    /// The decayed score of the peer.
+    pub fn score(&self, peer_id: &PeerId, now: Instant) -> f64 {
+        self.scores.get(peer_id).map_or(0.0, |peer_score| {
+            let elapsed = now.saturating_duration_since(peer_score.updated_at);
+            let half_lives = elapsed.as_secs_f64() / self.score_half_life().as_secs_f64().max(1e-3);
+            peer_score.score * 0.5f64.powf(half_lives)
+        })
+    }
+
+    /// Peers banned before they became trusted (e.g. joining the validator set) are not banned
+    /// anymore.
+    pub fn is_banned(&self, peer_id: &PeerId, now: Instant) -> bool {
+        self.bans
+            .get(peer_id)
+            .map_or(false, |banned_until| now < *banned_until)
+            && !self.is_trusted(peer_id)
+    }
+
+    fn score_half_life(&self) -> Duration {
+        Duration::from_millis(self.config.score_half_life_ms)
+    }
+
+    /// Lifts the expired bans and forgets the scores that decayed to nothing. Returns the peers
+    /// whose ban was lifted.
+    pub fn prune(&mut self, now: Instant) -> Vec<PeerId> {
+        let lifted: Vec<_> = self
+            .bans
+            .iter()
+            .filter(|(_, banned_until)| now >= **banned_until)
+            .map(|(peer_id, _)| *peer_id)
+            .collect();
+        for peer_id in &lifted {
+            self.bans.remove(peer_id);
+        }
+        let score_half_life = self.score_half_life();
+        self.scores.retain(|_, peer_score| {
+            now.saturating_duration_since(peer_score.updated_at) < score_half_life * 10
+        });
+        lifted
+    }
+
+    pub fn num_banned(&self) -> usize {
+        self.bans.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libra_crypto::{x25519, Uniform};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn config() -> PeerReputationConfig {
+        PeerReputationConfig {
+            ban_score_threshold: 50.0,
+            ban_duration_ms: 60_000,
+            score_half_life_ms: 10_000,
+            ..PeerReputationConfig::default()
+        }
+    }
+
+    fn reputation() -> PeerReputation {
+        PeerReputation::new(config(), Arc::new(RwLock::new(HashMap::new())))
+    }
+
+    #[test]
+    fn test_ban_after_repeated_misbehavior() {
+        let mut reputation = reputation();
+        let peer_id = PeerId::random();
+        let now = Instant::now();
+        assert!(reputation
+            .report(peer_id, Misbehavior::InvalidChunk, now)
+            .is_none());
+        assert_eq!(reputation.score(&peer_id, now), 25.0);
+        assert!(!reputation.is_banned(&peer_id, now));
+
+        let banned_until = reputation
+            .report(peer_id, Misbehavior::InvalidChunk, now)
+            .expect("peer is banned");
+        assert_eq!(banned_until, now + Duration::from_secs(60));
+        assert!(reputation.is_banned(&peer_id, now));
+        assert_eq!(reputation.score(&peer_id, now), 0.0);
+        // other peers are not affected
+        assert!(!reputation.is_banned(&PeerId::random(), now));
+    }
+
+    #[test]
+    fn test_score_decay() {
+        let mut reputation = reputation();
+        let peer_id = PeerId::random();
+        let now = Instant::now();
+        reputation.report(peer_id, Misbehavior::InvalidChunk, now);
+        reputation.report(peer_id, Misbehavior::UndecodableMessage, now);
+        let later = now + Duration::from_secs(10);
+        assert!((reputation.score(&peer_id, later) - 22.5).abs() < 1e-9);
+        // the decayed score stays below the threshold
+        assert!(reputation
+            .report(peer_id, Misbehavior::UndecodableMessage, later)
+            .is_none());
+    }
+
+    #[test]
+    fn test_ban_expiration() {
+        let mut reputation = reputation();
+        let (peer_id, other_peer_id) = (PeerId::random(), PeerId::random());
+        let now = Instant::now();
+        for _ in 0..5 {
+            reputation.report(peer_id, Misbehavior::InvalidMessage, now);
+        }
+        reputation.report(other_peer_id, Misbehavior::ProtocolViolation, now);
+        assert!(reputation.is_banned(&peer_id, now));
+        assert_eq!(reputation.num_banned(), 1);
+
+        assert!(reputation.prune(now + Duration::from_secs(59)).is_empty());
+        let expired = now + Duration::from_secs(60);
+        assert!(!reputation.is_banned(&peer_id, expired));
+        assert_eq!(reputation.prune(expired), vec![peer_id]);
+        assert_eq!(reputation.num_banned(), 0);
+        // decayed scores are forgotten
+        reputation.prune(now + Duration::from_secs(100));
+        assert_eq!(reputation.score(&other_peer_id, now), 0.0);
+    }
+
+    #[test]
+    fn test_trusted_peers_are_not_banned() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let mut pubkeys = || NetworkPublicKeys {
+            identity_public_key: x25519::PrivateKey::generate(&mut rng).public_key(),
+        };
+        let trusted_peers = Arc::new(RwLock::new(HashMap::new()));
+        let mut reputation = PeerReputation::new(config(), trusted_peers.clone());
+        let (trusted, joining) = (PeerId::random(), PeerId::random());
+        trusted_peers.write().unwrap().insert(trusted, pubkeys());
+        let now = Instant::now();
+        for _ in 0..10 {
+            assert!(reputation
+                .report(trusted, Misbehavior::UndecodableMessage, now)
+                .is_none());
+            reputation.report(joining, Misbehavior::UndecodableMessage, now);
+        }
+        assert!(!reputation.is_banned(&trusted, now));
+        assert_eq!(reputation.score(&trusted, now), 0.0);
+        assert!(reputation.is_banned(&joining, now));
+
+        // a banned peer joining the trusted peers is not banned anymore
+        trusted_peers.write().unwrap().insert(joining, pubkeys());
+        assert!(!reputation.is_banned(&joining, now));
+    }
+
+    #[test]
+    fn test_misbehavior_from_verification_error() {
+        let err = anyhow::Error::from(VerifyError::InvalidSignature).context("Fail to verify");
+        assert_eq!(
+            Misbehavior::from_verification_error(&err),
+            Some(Misbehavior::InvalidMessage)
+        );
+        let err = anyhow::format_err!("received stale ledger_info");
+        assert_eq!(Misbehavior::from_verification_error(&err), None);
+    }
+}
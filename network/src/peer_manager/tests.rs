@@ -2,14 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    peer::DisconnectReason,
+    peer::{bandwidth::ProtocolRateLimits, DisconnectReason},
     peer_manager::{
-        conn_notifs_channel, error::PeerManagerError, ConnectionNotification, ConnectionRequest,
-        PeerManager, PeerManagerNotification, PeerManagerRequest, TransportNotification,
+        admission::{InboundAdmission, InboundConnectionLimits},
+        conn_notifs_channel,
+        error::PeerManagerError,
+        reputation::{Misbehavior, PeerReputation},
+        ConnectionNotification, ConnectionRequest, PeerManager, PeerManagerNotification,
+        PeerManagerRequest, TransportNotification,
     },
     protocols::wire::{
         handshake::v1::MessagingProtocolVersion,
-        messaging::v1::{NetworkMessage, Nonce},
+        messaging::v1::{compression::CompressionThresholds, NetworkMessage, Nonce},
     },
     transport,
     transport::{Connection, ConnectionId, ConnectionMetadata},
@@ -17,7 +21,7 @@ use crate::{
 };
 use channel::{libra_channel, message_queues::QueueStyle};
 use futures::{channel::oneshot, io::AsyncWriteExt, sink::SinkExt, stream::StreamExt};
-use libra_config::config::RoleType;
+use libra_config::config::{PeerReputationConfig, RoleType};
 use libra_network_address::NetworkAddress;
 use libra_types::PeerId;
 use memsocket::MemorySocket;
@@ -25,7 +29,12 @@ use netcore::{
     compat::IoCompat,
     transport::{boxed::BoxedTransport, memory::MemoryTransport, ConnectionOrigin, TransportExt},
 };
-use std::{collections::HashMap, iter::FromIterator, num::NonZeroUsize};
+use std::{
+    collections::{HashMap, HashSet},
+    iter::FromIterator,
+    num::NonZeroUsize,
+    sync::{Arc, RwLock},
+};
 use tokio::runtime::Handle;
 use tokio_util::codec::{Framed, LengthDelimitedCodec};
 
@@ -102,6 +111,26 @@ fn build_test_peer_manager(
         1024, /* max concurrent network requests */
         1024, /* max concurrent network notifications */
         1024, /* channel size */
+        PeerReputation::new(
+            PeerReputationConfig {
+                ban_score_threshold: 50.0,
+                ..PeerReputationConfig::default()
+            },
+            Arc::new(RwLock::new(HashMap::new())),
+        ),
+        InboundAdmission::new(
+            InboundConnectionLimits {
+                max_connections: 1024,
+                max_connections_per_ip: 1024,
+                max_connections_per_subnet: 1024,
+                max_handshakes_per_sec: 1024,
+            },
+            Arc::new(RwLock::new(HashMap::new())),
+            Arc::new(RwLock::new(HashSet::new())),
+        ),
+        ProtocolRateLimits::new(),
+        CompressionThresholds::new(),
+        None,
     );
 
     (
@@ -616,3 +645,84 @@ fn test_dial_disconnect() {
 
     runtime.block_on(test);
 }
+
+#[test]
+fn test_ban_misbehaving_peer() {
+    ::libra_logger::Logger::new().environment_only(true).init();
+    let mut runtime = ::tokio::runtime::Runtime::new().unwrap();
+
+    let ids = ordered_peer_ids(2);
+    let (mut peer_manager, _request_tx, _connection_reqs_tx, _hello_rx, mut conn_status_rx) =
+        build_test_peer_manager(runtime.handle().clone(), ids[1]);
+
+    let test = async move {
+        let (outbound, _inbound) = build_test_connection();
+        peer_manager.add_peer(create_connection(
+            outbound,
+            ids[0],
+            NetworkAddress::mock(),
+            ConnectionOrigin::Outbound,
+            ConnectionId::from(0),
+        ));
+        let conn_notif = conn_status_rx.next().await.unwrap();
+        assert!(matches!(conn_notif, ConnectionNotification::NewPeer(_, _)));
+
+        // A single report doesn't reach the ban threshold.
+        peer_manager
+            .handle_connection_request(ConnectionRequest::ReportMisbehavior(
+                ids[0],
+                Misbehavior::InvalidChunk,
+            ))
+            .await;
+        assert!(peer_manager.active_peers.contains_key(&ids[0]));
+
+        // The second one bans and disconnects the peer.
+        peer_manager
+            .handle_connection_request(ConnectionRequest::ReportMisbehavior(
+                ids[0],
+                Misbehavior::InvalidChunk,
+            ))
+            .await;
+        assert!(!peer_manager.active_peers.contains_key(&ids[0]));
+        assert_peer_disconnected_event(
+            ids[0],
+            ConnectionOrigin::Outbound,
+            DisconnectReason::Requested,
+            &mut peer_manager,
+        )
+        .await;
+        let conn_notif = conn_status_rx.next().await.unwrap();
+        assert!(matches!(
+            conn_notif,
+            ConnectionNotification::LostPeer(_, _, _)
+        ));
+
+        // Dialing the banned peer fails.
+        let (dial_resp_tx, dial_resp_rx) = oneshot::channel();
+        peer_manager
+            .handle_connection_request(ConnectionRequest::DialPeer(
+                ids[0],
+                NetworkAddress::mock(),
+                dial_resp_tx,
+            ))
+            .await;
+        assert!(matches!(
+            dial_resp_rx.await.unwrap(),
+            Err(PeerManagerError::Banned(_))
+        ));
+
+        // Inbound connections from the banned peer are closed.
+        let (mut outbound, inbound) = build_test_connection();
+        peer_manager.add_peer(create_connection(
+            inbound,
+            ids[0],
+            NetworkAddress::mock(),
+            ConnectionOrigin::Inbound,
+            ConnectionId::from(1),
+        ));
+        assert!(!peer_manager.active_peers.contains_key(&ids[0]));
+        assert!(ping_pong(&mut outbound).await.is_err());
+    };
+
+    runtime.block_on(test);
+}
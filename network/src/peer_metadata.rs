@@ -0,0 +1,340 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Quality of the connections to remote peers, as measured by the HealthChecker.
+//!
+//! `PeerMetadata` is a cheaply cloneable handle to the round-trip times and ping failure rates of
+//! the connected peers of all the networks of a node. The HealthChecker of each network records
+//! the outcome of its pings, and components choosing among upstream peers (state sync, mempool)
+//! query it to prefer the fast and reliable ones. The measurements are also exported to
+//! Prometheus as `libra_network_health_check_rtt_seconds` and
+//! `libra_network_health_check_pings`.
+
+use crate::counters;
+use libra_config::config::PeerNetworkId;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{self, AtomicU64},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+/// Upper bounds of the buckets of the RTT histograms, in milliseconds. RTTs above the last bound
+/// fall in an overflow bucket.
+pub const RTT_BUCKETS_MS: [u64; 12] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000];
+/// Number of most recent pings the failure rate is computed over.
+pub const FAILURE_RATE_WINDOW: usize = 100;
+/// Weight of a new sample in the smoothed RTT, as in TCP (RFC 6298).
+const SMOOTHED_RTT_GAIN: f64 = 0.125;
+/// RTT at which the preference of a peer is halved.
+const REFERENCE_RTT_MS: f64 = 100.0;
+/// Lower bound of the preference, so that every peer remains eligible.
+const MIN_PREFERENCE: f64 = 0.01;
+
+/// Histogram of the round-trip times of the pings to a peer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RttHistogram {
+    /// Number of samples in each bucket of `RTT_BUCKETS_MS`, then in the overflow bucket.
+    buckets: [u64; RTT_BUCKETS_MS.len() + 1],
+    count: u64,
+    max: Duration,
+}
+
+impl RttHistogram {
+    pub fn record(&mut self, rtt: Duration) {
+        let rtt_ms = rtt.as_millis() as u64;
+        let bucket = RTT_BUCKETS_MS
+            .iter()
+            .position(|bound| rtt_ms < *bound)
+            .unwrap_or(RTT_BUCKETS_MS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.max = self.max.max(rtt);
+    }
+
+    /// Number of samples in each bucket, the last one being the overflow bucket.
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// An upper bound of the `quantile` (between 0 and 1) of the samples: the upper bound of the
+    /// bucket it falls in, or the largest sample for the overflow bucket.
+    pub fn quantile(&self, quantile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((quantile.max(0.0).min(1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(match RTT_BUCKETS_MS.get(bucket) {
+                    Some(bound) => Duration::from_millis(*bound).min(self.max),
+                    None => self.max,
+                });
+            }
+        }
+        Some(self.max)
+    }
+}
+
+/// The measurements of the connection to a peer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PeerQuality {
+    pub rtt_histogram: RttHistogram,
+    /// Exponentially weighted moving average of the RTTs.
+    pub smoothed_rtt: Option<Duration>,
+    pub pings_succeeded: u64,
+    pub pings_failed: u64,
+    /// Outcome of the last `FAILURE_RATE_WINDOW` pings, `true` for a failure.
+    recent_failures: VecDeque<bool>,
+}
+
+impl PeerQuality {
+    fn record_success(&mut self, rtt: Duration) {
+        self.rtt_histogram.record(rtt);
+        self.smoothed_rtt = Some(match self.smoothed_rtt {
+            Some(smoothed_rtt) => {
+                smoothed_rtt.mul_f64(1.0 - SMOOTHED_RTT_GAIN) + rtt.mul_f64(SMOOTHED_RTT_GAIN)
+            }
+            None => rtt,
+        });
+        self.pings_succeeded += 1;
+        self.record_outcome(false);
+    }
+
+    fn record_failure(&mut self) {
+        self.pings_failed += 1;
+        self.record_outcome(true);
+    }
+
+    fn record_outcome(&mut self, failed: bool) {
+        if self.recent_failures.len() == FAILURE_RATE_WINDOW {
+            self.recent_failures.pop_front();
+        }
+        self.recent_failures.push_back(failed);
+    }
+
+    /// Fraction of the last `FAILURE_RATE_WINDOW` pings which failed, 0 without pings.
+    pub fn failure_rate(&self) -> f64 {
+        if self.recent_failures.is_empty() {
+            return 0.0;
+        }
+        let failures = self
+            .recent_failures
+            .iter()
+            .filter(|failed| **failed)
+            .count();
+        failures as f64 / self.recent_failures.len() as f64
+    }
+
+    /// How much the peer should be preferred over others, between `MIN_PREFERENCE` and 1: the
+    /// success rate of the pings, halved for every `REFERENCE_RTT_MS` of smoothed RTT.
+    pub fn preference(&self) -> f64 {
+        let rtt_ms = self
+            .smoothed_rtt
+            .map_or(0.0, |rtt| rtt.as_secs_f64() * 1000.0);
+        let preference = (1.0 - self.failure_rate()) / (1.0 + rtt_ms / REFERENCE_RTT_MS);
+        preference.max(MIN_PREFERENCE)
+    }
+}
+
+/// Shared view of the `PeerQuality` of the connected peers.
+#[derive(Clone, Debug, Default)]
+pub struct PeerMetadata {
+    peers: Arc<RwLock<HashMap<PeerNetworkId, PeerQuality>>>,
+    /// Incremented after every change of the measurements.
+    version: Arc<AtomicU64>,
+}
+
+impl PeerMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A number which changes whenever the measurements change, so that the users of the
+    /// measurements can cache what they derive from them.
+    pub fn version(&self) -> u64 {
+        self.version.load(atomic::Ordering::Acquire)
+    }
+
+    pub fn get(&self, peer: &PeerNetworkId) -> Option<PeerQuality> {
+        self.peers.read().unwrap().get(peer).cloned()
+    }
+
+    pub fn smoothed_rtt(&self, peer: &PeerNetworkId) -> Option<Duration> {
+        self.peers
+            .read()
+            .unwrap()
+            .get(peer)
+            .and_then(|quality| quality.smoothed_rtt)
+    }
+
+    pub fn failure_rate(&self, peer: &PeerNetworkId) -> Option<f64> {
+        self.peers
+            .read()
+            .unwrap()
+            .get(peer)
+            .map(PeerQuality::failure_rate)
+    }
+
+    /// The `PeerQuality::preference` of `peer`. Peers which haven't been measured yet get the
+    /// highest preference, so that they get a chance to be picked.
+    pub fn preference(&self, peer: &PeerNetworkId) -> f64 {
+        self.peers
+            .read()
+            .unwrap()
+            .get(peer)
+            .map_or(1.0, PeerQuality::preference)
+    }
+
+    /// Sorts `peers` from the most to the least preferred.
+    pub fn sort_by_preference(&self, peers: &mut [PeerNetworkId]) {
+        let peers_quality = self.peers.read().unwrap();
+        let preference =
+            |peer: &PeerNetworkId| peers_quality.get(peer).map_or(1.0, PeerQuality::preference);
+        peers.sort_by(|a, b| {
+            preference(b)
+                .partial_cmp(&preference(a))
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+
+    /// Records a successful ping to `peer` which took `rtt`.
+    pub fn record_ping_success(&self, peer: PeerNetworkId, rtt: Duration) {
+        let peer_label = peer.peer_id().short_str();
+        counters::LIBRA_NETWORK_HEALTH_CHECK_RTT
+            .with_label_values(&[&peer_label])
+            .observe(rtt.as_secs_f64());
+        counters::LIBRA_NETWORK_HEALTH_CHECK_PINGS
+            .with_label_values(&[&peer_label, counters::SUCCEEDED_LABEL])
+            .inc();
+        self.peers
+            .write()
+            .unwrap()
+            .entry(peer)
+            .or_default()
+            .record_success(rtt);
+        self.version.fetch_add(1, atomic::Ordering::Release);
+    }
+
+    /// Records a failed ping to `peer`.
+    pub fn record_ping_failure(&self, peer: PeerNetworkId) {
+        counters::LIBRA_NETWORK_HEALTH_CHECK_PINGS
+            .with_label_values(&[&peer.peer_id().short_str(), counters::FAILED_LABEL])
+            .inc();
+        self.peers
+            .write()
+            .unwrap()
+            .entry(peer)
+            .or_default()
+            .record_failure();
+        self.version.fetch_add(1, atomic::Ordering::Release);
+    }
+
+    /// Forgets the measurements of a peer we are no longer connected to, including its metric
+    /// series.
+    pub fn remove(&self, peer: &PeerNetworkId) {
+        let peer_label = peer.peer_id().short_str();
+        let _ = counters::LIBRA_NETWORK_HEALTH_CHECK_RTT.remove_label_values(&[&peer_label]);
+        for state in &[counters::SUCCEEDED_LABEL, counters::FAILED_LABEL] {
+            let _ = counters::LIBRA_NETWORK_HEALTH_CHECK_PINGS
+                .remove_label_values(&[&peer_label, state]);
+        }
+        self.peers.write().unwrap().remove(peer);
+        self.version.fetch_add(1, atomic::Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rtt_histogram() {
+        let mut histogram = RttHistogram::default();
+        assert_eq!(histogram.quantile(0.5), None);
+        for rtt_ms in &[3, 4, 15, 30, 7000] {
+            histogram.record(Duration::from_millis(*rtt_ms));
+        }
+        assert_eq!(histogram.count(), 5);
+        assert_eq!(histogram.buckets()[2], 2);
+        assert_eq!(histogram.buckets()[RTT_BUCKETS_MS.len()], 1);
+        assert_eq!(histogram.quantile(0.0), Some(Duration::from_millis(5)));
+        assert_eq!(histogram.quantile(0.5), Some(Duration::from_millis(20)));
+        assert_eq!(histogram.quantile(1.0), Some(Duration::from_millis(7000)));
+    }
+
+    #[test]
+    fn test_peer_quality() {
+        let mut quality = PeerQuality::default();
+        assert_eq!(quality.failure_rate(), 0.0);
+        assert_eq!(quality.preference(), 1.0);
+
+        quality.record_success(Duration::from_millis(100));
+        assert_eq!(quality.smoothed_rtt, Some(Duration::from_millis(100)));
+        quality.record_success(Duration::from_millis(900));
+        let smoothed_rtt = quality.smoothed_rtt.unwrap().as_secs_f64();
+        assert!((smoothed_rtt - 0.2).abs() < 1e-6);
+        quality.record_failure();
+        quality.record_failure();
+        assert_eq!(quality.failure_rate(), 0.5);
+        assert!((quality.preference() - 0.5 / 3.0).abs() < 1e-9);
+
+        // the failure rate only accounts for the last pings
+        for _ in 0..FAILURE_RATE_WINDOW {
+            quality.record_success(Duration::from_millis(200));
+        }
+        assert_eq!(quality.failure_rate(), 0.0);
+        assert_eq!(quality.pings_failed, 2);
+    }
+
+    #[test]
+    fn test_sort_by_preference() {
+        let metadata = PeerMetadata::new();
+        let (fast, slow, failing, unknown) = (
+            PeerNetworkId::random(),
+            PeerNetworkId::random(),
+            PeerNetworkId::random(),
+            PeerNetworkId::random(),
+        );
+        metadata.record_ping_success(fast, Duration::from_millis(5));
+        metadata.record_ping_success(slow, Duration::from_millis(500));
+        metadata.record_ping_success(failing, Duration::from_millis(5));
+        metadata.record_ping_failure(failing);
+        metadata.record_ping_failure(failing);
+
+        let mut peers = vec![failing, slow, fast, unknown];
+        metadata.sort_by_preference(&mut peers);
+        assert_eq!(peers, vec![unknown, fast, failing, slow]);
+
+        metadata.remove(&fast);
+        assert!(metadata.get(&fast).is_none());
+        assert_eq!(metadata.preference(&fast), 1.0);
+    }
+
+    #[test]
+    fn test_version() {
+        let metadata = PeerMetadata::new();
+        let peer = PeerNetworkId::random();
+        let mut version = metadata.version();
+        let mut assert_changed = |metadata: &PeerMetadata| {
+            assert_ne!(metadata.version(), version);
+            version = metadata.version();
+        };
+        metadata.record_ping_success(peer, Duration::from_millis(5));
+        assert_changed(&metadata);
+        metadata.record_ping_failure(peer);
+        assert_changed(&metadata);
+        // clones share the measurements
+        metadata.clone().remove(&peer);
+        assert_changed(&metadata);
+    }
+}
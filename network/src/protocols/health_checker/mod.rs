@@ -11,6 +11,9 @@
 //! disconnect from the peer. It relies on ConnectivityManager or the remote peer to re-establish
 //! the connection.
 //!
+//! The round-trip times and failures of the probes are recorded in the `PeerMetadata` of the node,
+//! for other components to prefer the fast and reliable peers.
+//!
 //! Future Work
 //! -----------
 //! We can make a few other improvements to the health checker. These are:
@@ -21,6 +24,7 @@ use crate::{
     counters,
     error::NetworkError,
     peer_manager::{ConnectionRequestSender, PeerManagerRequestSender},
+    peer_metadata::PeerMetadata,
     protocols::{
         network::{Event, NetworkEvents, NetworkSender},
         rpc::error::RpcError,
@@ -34,12 +38,16 @@ use futures::{
     channel::oneshot,
     stream::{FusedStream, FuturesUnordered, Stream, StreamExt},
 };
+use libra_config::config::PeerNetworkId;
 use libra_logger::prelude::*;
 use libra_security_logger::{security_log, SecurityEvent};
 use libra_types::PeerId;
 use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
 #[cfg(test)]
 mod test;
@@ -148,6 +156,10 @@ pub struct HealthChecker<TTicker> {
     ping_failures_tolerated: u64,
     /// Counter incremented in each round of health checks
     round: u64,
+    /// The network this HealthChecker runs on, identified by our own PeerId in it.
+    network_id: PeerId,
+    /// Where the round-trip times and failures of the pings are recorded.
+    peer_metadata: PeerMetadata,
 }
 
 impl<TTicker> HealthChecker<TTicker>
@@ -161,6 +173,8 @@ where
         network_rx: HealthCheckerNetworkEvents,
         ping_timeout: Duration,
         ping_failures_tolerated: u64,
+        network_id: PeerId,
+        peer_metadata: PeerMetadata,
     ) -> Self {
         HealthChecker {
             ticker,
@@ -171,6 +185,8 @@ where
             ping_timeout,
             ping_failures_tolerated,
             round: 0,
+            network_id,
+            peer_metadata,
         }
     }
 
@@ -185,6 +201,7 @@ where
                         },
                         Ok(Event::LostPeer(peer_id)) => {
                             self.connected.remove(&peer_id);
+                            self.peer_metadata.remove(&PeerNetworkId(self.network_id, peer_id));
                         },
                         Ok(Event::RpcRequest((peer_id, msg, res_tx))) => {
                             match msg {
@@ -234,8 +251,8 @@ where
                     }
                 }
                 res = tick_handlers.select_next_some() => {
-                    let (peer_id, round, nonce, ping_result) = res;
-                    self.handle_ping_response(peer_id, round, nonce, ping_result).await;
+                    let (peer_id, round, nonce, ping_result, rtt) = res;
+                    self.handle_ping_response(peer_id, round, nonce, ping_result, rtt).await;
                 }
                 complete => {
                     break;
@@ -272,12 +289,17 @@ where
         round: u64,
         req_nonce: u32,
         ping_result: Result<Pong, RpcError>,
+        rtt: Duration,
     ) {
         debug!("Got result for ping round: {}", round);
+        let peer = PeerNetworkId(self.network_id, peer_id);
         match ping_result {
             Ok(pong) => {
                 if pong.0 == req_nonce {
                     debug!("Ping successful for peer: {}", peer_id.short_str());
+                    if self.connected.contains_key(&peer_id) {
+                        self.peer_metadata.record_ping_success(peer, rtt);
+                    }
                     // Update last successful ping to current round.
                     self.connected
                         .entry(peer_id)
@@ -313,6 +335,7 @@ where
                         if *prev > round {
                             return;
                         }
+                        self.peer_metadata.record_ping_failure(peer);
                         // Increment num of failures. If the ping failures are now more than
                         // `self.ping_failures_tolerated`, we disconnect from the node.
                         // The HealthChecker only performs the disconnect. It relies on
@@ -340,12 +363,13 @@ where
         round: u64,
         nonce: u32,
         ping_timeout: Duration,
-    ) -> (PeerId, u64, u32, Result<Pong, RpcError>) {
+    ) -> (PeerId, u64, u32, Result<Pong, RpcError>, Duration) {
         debug!(
             "Sending Ping request to peer: {} with nonce: {}",
             peer_id.short_str(),
             nonce
         );
+        let start = Instant::now();
         let res_pong_msg = network_tx
             .send_rpc(peer_id, HealthCheckerMsg::Ping(Ping(nonce)), ping_timeout)
             .await
@@ -353,7 +377,7 @@ where
                 HealthCheckerMsg::Pong(res) => Ok(res),
                 _ => Err(RpcError::InvalidRpcResponse),
             });
-        (peer_id, round, nonce, res_pong_msg)
+        (peer_id, round, nonce, res_pong_msg, start.elapsed())
     }
 
     fn sample_random_peer(&mut self) -> Option<PeerId> {
@@ -6,6 +6,7 @@ use crate::{
     peer_manager::{
         self, conn_notifs_channel, ConnectionRequest, PeerManagerNotification, PeerManagerRequest,
     },
+    peer_metadata::PeerQuality,
     protocols::rpc::InboundRpcRequest,
     ProtocolId,
 };
@@ -26,6 +27,26 @@ fn setup_permissive_health_checker(
     libra_channel::Receiver<PeerId, ConnectionRequest>,
     conn_notifs_channel::Sender,
     channel::Sender<()>,
+) {
+    setup_health_checker(
+        rt,
+        ping_failures_tolerated,
+        PeerId::random(),
+        PeerMetadata::new(),
+    )
+}
+
+fn setup_health_checker(
+    rt: &mut Runtime,
+    ping_failures_tolerated: u64,
+    network_id: PeerId,
+    peer_metadata: PeerMetadata,
+) -> (
+    libra_channel::Receiver<(PeerId, ProtocolId), PeerManagerRequest>,
+    libra_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>,
+    libra_channel::Receiver<PeerId, ConnectionRequest>,
+    conn_notifs_channel::Sender,
+    channel::Sender<()>,
 ) {
     let (ticker_tx, ticker_rx) = channel::new_test(0);
 
@@ -49,6 +70,8 @@ fn setup_permissive_health_checker(
         hc_network_rx,
         PING_TIMEOUT,
         ping_failures_tolerated,
+        network_id,
+        peer_metadata,
     );
     rt.spawn(health_checker.start());
     (
@@ -358,3 +381,51 @@ fn ping_timeout() {
     };
     rt.block_on(events_f);
 }
+
+async fn wait_for_quality<F>(peer_metadata: &PeerMetadata, peer: &PeerNetworkId, condition: F)
+where
+    F: Fn(&PeerQuality) -> bool,
+{
+    for _ in 0..100 {
+        if peer_metadata
+            .get(peer)
+            .map_or(false, |quality| condition(&quality))
+        {
+            return;
+        }
+        tokio::time::delay_for(Duration::from_millis(10)).await;
+    }
+    panic!("Unexpected peer quality: {:?}", peer_metadata.get(peer));
+}
+
+#[test]
+fn peer_metadata() {
+    ::libra_logger::Logger::new().environment_only(true).init();
+    let mut rt = Runtime::new().unwrap();
+    let network_id = PeerId::random();
+    let peer_metadata = PeerMetadata::new();
+    let (mut network_reqs_rx, _, _, mut connection_notifs_tx, mut ticker_tx) =
+        setup_health_checker(&mut rt, 10, network_id, peer_metadata.clone());
+
+    let events_f = async move {
+        let peer_id = PeerId::random();
+        let peer = PeerNetworkId(network_id, peer_id);
+        send_new_peer_notification(peer_id, &mut connection_notifs_tx).await;
+
+        // A successful ping records its round-trip time.
+        ticker_tx.send(()).await.unwrap();
+        expect_ping_send_ok(&mut network_reqs_rx).await;
+        wait_for_quality(&peer_metadata, &peer, |quality| {
+            quality.pings_succeeded == 1 && quality.rtt_histogram.count() == 1
+        })
+        .await;
+        assert!(peer_metadata.smoothed_rtt(&peer).is_some());
+
+        // A failed ping counts towards the failure rate.
+        ticker_tx.send(()).await.unwrap();
+        expect_ping_send_notok(&mut network_reqs_rx).await;
+        wait_for_quality(&peer_metadata, &peer, |quality| quality.pings_failed == 1).await;
+        assert_eq!(peer_metadata.failure_rate(&peer), Some(0.5));
+    };
+    rt.block_on(events_f);
+}
@@ -143,7 +143,7 @@ pub fn setup_network() -> DummyNetwork {
         .trusted_peers(trusted_peers.clone())
         .add_connectivity_manager();
     let (listener_sender, mut listener_events) = add_to_network(&mut network_builder);
-    let listener_addr = network_builder.build();
+    let listener_addr = network_builder.build().unwrap();
 
     // Set up the dialer network
     let mut network_builder = NetworkBuilder::new(
@@ -163,7 +163,7 @@ pub fn setup_network() -> DummyNetwork {
         )
         .add_connectivity_manager();
     let (dialer_sender, mut dialer_events) = add_to_network(&mut network_builder);
-    let _dialer_addr = network_builder.build();
+    let _dialer_addr = network_builder.build().unwrap();
 
     // Wait for establishing connection
     let first_dialer_event = block_on(dialer_events.next()).unwrap().unwrap();
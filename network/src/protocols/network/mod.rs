@@ -7,8 +7,8 @@ pub use crate::protocols::rpc::error::RpcError;
 use crate::{
     error::NetworkError,
     peer_manager::{
-        ConnectionNotification, ConnectionRequestSender, PeerManagerNotification,
-        PeerManagerRequestSender,
+        reputation::Misbehavior, ConnectionNotification, ConnectionRequestSender,
+        PeerManagerNotification, PeerManagerRequestSender,
     },
     ProtocolId,
 };
@@ -133,11 +133,15 @@ fn peer_mgr_notif_to_event<TMessage: Message>(
 ) -> Result<Event<TMessage>, NetworkError> {
     match notif {
         PeerManagerNotification::RecvRpc(peer_id, rpc_req) => {
-            let req_msg: TMessage = lcs::from_bytes(&rpc_req.data)?;
+            let req_msg: TMessage = lcs::from_bytes(&rpc_req.data).map_err(|err| {
+                NetworkError::from_misbehavior(peer_id, Misbehavior::UndecodableMessage, err)
+            })?;
             Ok(Event::RpcRequest((peer_id, req_msg, rpc_req.res_tx)))
         }
         PeerManagerNotification::RecvMessage(peer_id, msg) => {
-            let msg: TMessage = lcs::from_bytes(&msg.mdata)?;
+            let msg: TMessage = lcs::from_bytes(&msg.mdata).map_err(|err| {
+                NetworkError::from_misbehavior(peer_id, Misbehavior::UndecodableMessage, err)
+            })?;
             Ok(Event::Message((peer_id, msg)))
         }
     }
@@ -209,6 +213,18 @@ impl<TMessage> NetworkSender<TMessage> {
         self.connection_reqs_tx.disconnect_peer(peer).await?;
         Ok(())
     }
+
+    /// Report the misbehavior of a given Peer. Peers which misbehave repeatedly are disconnected
+    /// and banned for a while.
+    pub fn report_misbehavior(
+        &mut self,
+        peer: PeerId,
+        misbehavior: Misbehavior,
+    ) -> Result<(), NetworkError> {
+        self.connection_reqs_tx
+            .report_misbehavior(peer, misbehavior)?;
+        Ok(())
+    }
 }
 
 impl<TMessage: Message> NetworkSender<TMessage> {
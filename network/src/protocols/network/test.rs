@@ -106,3 +106,19 @@ fn test_rpc() {
     let (res_msg, _) = tn.runtime.block_on(join(f_send, f_respond));
     assert_eq!(res_msg.unwrap(), msg);
 }
+
+#[test]
+fn test_undecodable_message() {
+    let peer_id = PeerId::random();
+    let notif = PeerManagerNotification::RecvMessage(
+        peer_id,
+        crate::protocols::direct_send::Message {
+            protocol: ProtocolId::ConsensusDirectSend,
+            mdata: Bytes::from_static(&[0xff, 0xff, 0xff]),
+        },
+    );
+    let err = peer_mgr_notif_to_event::<DummyMsg>(notif).unwrap_err();
+    let misbehavior = err.misbehavior().expect("misbehaving peer");
+    assert_eq!(misbehavior.peer_id, peer_id);
+    assert_eq!(misbehavior.misbehavior, Misbehavior::UndecodableMessage);
+}
@@ -3,7 +3,7 @@
 
 //! Rpc protocol errors
 
-use crate::peer_manager::PeerManagerError;
+use crate::{peer_manager::PeerManagerError, ProtocolId};
 use anyhow::anyhow;
 use futures::channel::{mpsc, oneshot};
 use libra_types::PeerId;
@@ -47,6 +47,9 @@ pub enum RpcError {
 
     #[error("Rpc timed out")]
     TimedOut,
+
+    #[error("Rpc request rejected by the rate limit of protocol: {0:?}")]
+    RateLimited(ProtocolId),
 }
 
 impl From<PeerManagerError> for RpcError {
@@ -54,6 +57,7 @@ impl From<PeerManagerError> for RpcError {
         match err {
             PeerManagerError::NotConnected(peer_id) => RpcError::NotConnected(peer_id),
             PeerManagerError::IoError(err) => RpcError::IoError(err),
+            PeerManagerError::RateLimited(protocol) => RpcError::RateLimited(protocol),
             err => RpcError::Error(anyhow!(err)),
         }
     }
@@ -50,7 +50,7 @@ use crate::{
     },
     peer::{PeerHandle, PeerNotification},
     protocols::wire::messaging::v1::{
-        NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse,
+        ErrorCode, NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse,
     },
     ProtocolId,
 };
@@ -177,8 +177,10 @@ pub struct Rpc {
     rpc_handler_tx: channel::Sender<RpcNotification>,
     /// The timeout duration for inbound rpc calls.
     inbound_rpc_timeout: Duration,
-    /// Channels to send Rpc responses to pending outbound RPC tasks.
-    pending_outbound_rpcs: HashMap<RequestId, (ProtocolId, oneshot::Sender<RpcResponse>)>,
+    /// Channels to send Rpc responses, or the rejection of the request by the remote peer, to
+    /// pending outbound RPC tasks.
+    pending_outbound_rpcs:
+        HashMap<RequestId, (ProtocolId, oneshot::Sender<Result<RpcResponse, RpcError>>)>,
     /// RequestId to use for next outbound RPC.
     request_id_gen: RequestIdGenerator,
     /// The maximum number of concurrent outbound rpc requests that we will
@@ -264,6 +266,10 @@ impl Rpc {
                     NetworkMessage::RpcRequest(request) => {
                         self.handle_inbound_request(request, inbound_rpc_tasks);
                     }
+                    // A pending outbound RPC was rejected by the rate limits of the remote peer.
+                    NetworkMessage::Error(ErrorCode::RateLimited(request_id)) => {
+                        self.handle_rate_limited(request_id);
+                    }
                     _ => {
                         error!("Received non-RPC message from Peer actor: {:?}", message);
                     }
@@ -287,7 +293,7 @@ impl Rpc {
                 "Waiting to notify outbound rpc task about inbound response for request_id {}",
                 request_id
             );
-            if let Err(e) = response_tx.send(response) {
+            if let Err(e) = response_tx.send(Ok(response)) {
                 warn!(
                     "Failed to handle inbount RPC response from peer: {} for protocol: {:?}. Error: {:?}",
                     peer_id.short_str(),
@@ -309,6 +315,13 @@ impl Rpc {
         }
     }
 
+    // Fails the pending outbound RPC rejected by the remote peer, rather than letting it time out.
+    fn handle_rate_limited(&mut self, request_id: RequestId) {
+        if let Some((protocol, response_tx)) = self.pending_outbound_rpcs.remove(&request_id) {
+            let _ = response_tx.send(Err(RpcError::RateLimited(protocol)));
+        }
+    }
+
     // Handle inbound request by spawning task (with timeout).
     fn handle_inbound_request(
         &mut self,
@@ -456,7 +469,7 @@ async fn handle_outbound_rpc_inner(
     request_id: RequestId,
     protocol: ProtocolId,
     req_data: Bytes,
-    response_rx: oneshot::Receiver<RpcResponse>,
+    response_rx: oneshot::Receiver<Result<RpcResponse, RpcError>>,
 ) -> Result<Bytes, RpcError> {
     let req_len = req_data.len();
     let peer_id = peer_handle.peer_id();
@@ -499,7 +512,7 @@ async fn handle_outbound_rpc_inner(
         request_id,
         peer_id_str
     );
-    let response = response_rx.await?;
+    let response = response_rx.await??;
     let latency = timer.stop_and_record();
     trace!(
         "Received response for request_id {} from peer: {:?} \
@@ -330,6 +330,52 @@ fn outbound_rpc_timeout() {
     rt.block_on(f);
 }
 
+// Test that an outbound rpc rejected by the rate limits of the remote peer fails without waiting
+// for the timeout.
+#[test]
+#[serial]
+fn outbound_rpc_rate_limited() {
+    ::libra_logger::Logger::new().environment_only(true).init();
+
+    let mut rt = Runtime::new().unwrap();
+    let (mut rpc_requests_tx, _rpc_notifs_rx, mut peer_reqs_rx, mut peer_notifs_tx) =
+        start_rpc_actor(rt.handle().clone());
+
+    let protocol_id = RPC_PROTOCOL_A;
+    let req_data = Bytes::from_static(b"hello");
+    let message = create_network_request(0, protocol_id, req_data.clone());
+
+    // Mock peer which rejects the request.
+    let f_mock_peer = async move {
+        expect_successful_send(&mut peer_reqs_rx, protocol_id, message).await;
+        peer_notifs_tx
+            .send(PeerNotification::NewMessage(NetworkMessage::Error(
+                ErrorCode::RateLimited(0),
+            )))
+            .await
+            .unwrap();
+    };
+
+    let f_send_rpc = async move {
+        let (res_tx, res_rx) = oneshot::channel();
+        rpc_requests_tx
+            .send(OutboundRpcRequest {
+                protocol: protocol_id,
+                data: req_data,
+                res_tx,
+                timeout: Duration::from_secs(60),
+            })
+            .await
+            .unwrap();
+
+        let result: Result<Bytes, RpcError> = res_rx.await.unwrap();
+        assert!(matches!(result, Err(RpcError::RateLimited(protocol)) if protocol == protocol_id));
+    };
+
+    let f = join(f_mock_peer, f_send_rpc);
+    rt.block_on(f);
+}
+
 // Test that outbound rpcs can be canceled immediately after request.
 #[test]
 #[serial]
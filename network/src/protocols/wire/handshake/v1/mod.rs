@@ -9,13 +9,32 @@
 //! supported messaging protocol versions to a bit vector representing application protocols
 //! supported over that messaging protocol. On receipt, both ends will determine the highest
 //! intersecting messaging protocol version and use that for the remainder of the session.
+//! Nodes advertise every messaging protocol version they support, so that a new version is used
+//! between the nodes which support it while connections to the others keep using the older ones.
+//!
+//! The upper half of the bit vector advertises the application protocols whose messages the node
+//! accepts compressed (see [`compression`](crate::protocols::wire::messaging::v1::compression)).
+//! Nodes which don't support compression never set these bits, so they drop out of the
+//! intersection and the connection falls back to uncompressed messages. The last bit of the lower
+//! half is no application protocol either: it advertises that the node understands
+//! `ErrorCode::RateLimited`, and falls back the same way.
 
-use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, convert::TryInto, fmt, iter::Iterator};
+use serde::{
+    de::{EnumAccess, VariantAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::{collections::BTreeMap, convert::TryInto, fmt, iter::Iterator, str::FromStr};
 
 #[cfg(test)]
 mod test;
 
+/// Bit `COMPRESSION_BITS_OFFSET + p` of `SupportedProtocols` is set if the messages of protocol
+/// `p` may be compressed.
+const COMPRESSION_BITS_OFFSET: u8 = 128;
+
+/// Bit of `SupportedProtocols` set if the node understands `ErrorCode::RateLimited`.
+const RATE_LIMITED_ERROR_BIT: u8 = COMPRESSION_BITS_OFFSET - 1;
+
 /// Unique identifier associated with each application protocol.
 /// New application protocols can be added without bumping up the MessagingProtocolVersion.
 #[repr(u8)]
@@ -53,22 +72,48 @@ impl fmt::Display for ProtocolId {
     }
 }
 
+impl FromStr for ProtocolId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        use ProtocolId::*;
+        [
+            ConsensusRpc,
+            ConsensusDirectSend,
+            MempoolDirectSend,
+            StateSynchronizerDirectSend,
+            DiscoveryDirectSend,
+            HealthCheckerRpc,
+            IdentityDirectSend,
+            OnchainDiscoveryRpc,
+        ]
+        .iter()
+        .find(|protocol| protocol.as_str() == s)
+        .copied()
+        .ok_or_else(|| anyhow::format_err!("Unknown protocol: {}", s))
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 pub struct SupportedProtocols(bitvec::BitVec);
 
 /// The HandshakeMsg contains a mapping from MessagingProtocolVersion suppported by the node to a
 /// bit-vector specifying application-level protocols supported over that version.
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Default)]
 pub struct HandshakeMsg {
     pub supported_protocols: BTreeMap<MessagingProtocolVersion, SupportedProtocols>,
 }
 
 /// Enum representing different versions of the Libra network protocol. These should be listed from
-/// old to new, old having the smallest value.
+/// old to new, old having the smallest value. New versions must be added as unit variants, which
+/// older nodes skip when decoding a `HandshakeMsg`.
 /// We derive `PartialOrd` since nodes need to find highest intersecting protocol version.
 #[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Debug, Hash, Deserialize, Serialize)]
 pub enum MessagingProtocolVersion {
     V1 = 0,
+    /// Prefixes every message with `MessageFlags` (see
+    /// [`messaging::v2`](crate::protocols::wire::messaging::v2)).
+    V2 = 1,
 }
 
 impl TryInto<Vec<ProtocolId>> for SupportedProtocols {
@@ -77,7 +122,8 @@ impl TryInto<Vec<ProtocolId>> for SupportedProtocols {
     fn try_into(self) -> lcs::Result<Vec<ProtocolId>> {
         let mut protocols = Vec::with_capacity(self.0.count_ones() as usize);
         if let Some(last_bit) = self.0.last_set_bit() {
-            for i in 0..=last_bit {
+            // The rate limited error and compression bits are not protocols.
+            for i in 0..=last_bit.min(RATE_LIMITED_ERROR_BIT - 1) {
                 if self.0.is_set(i) {
                     let protocol: ProtocolId = lcs::from_bytes(&[i])?;
                     protocols.push(protocol);
@@ -97,6 +143,31 @@ impl<'a, T: Iterator<Item = &'a ProtocolId>> From<T> for SupportedProtocols {
 }
 
 impl SupportedProtocols {
+    /// Advertises compression support for the given protocols.
+    pub fn add_compression<'a>(&mut self, protocols: impl Iterator<Item = &'a ProtocolId>) {
+        protocols.for_each(|p| self.0.set(COMPRESSION_BITS_OFFSET + *p as u8));
+    }
+
+    /// Advertises that the node understands `ErrorCode::RateLimited`.
+    pub fn add_rate_limited_error(&mut self) {
+        self.0.set(RATE_LIMITED_ERROR_BIT);
+    }
+
+    /// Whether `ErrorCode::RateLimited` may be sent. On a negotiated connection, this is the case
+    /// if both ends advertised it.
+    pub fn supports_rate_limited_error(&self) -> bool {
+        self.0.is_set(RATE_LIMITED_ERROR_BIT)
+    }
+
+    /// The protocols whose messages may be compressed. On a negotiated connection, these are the
+    /// protocols for which both ends advertised compression support.
+    pub fn compressed_protocols(&self) -> Vec<ProtocolId> {
+        (0..COMPRESSION_BITS_OFFSET)
+            .filter(|i| self.0.is_set(COMPRESSION_BITS_OFFSET + i))
+            .filter_map(|i| lcs::from_bytes(&[i]).ok())
+            .collect()
+    }
+
     /// Returns a new SupportedProtocols struct that is an intersection.
     fn intersection(self, other: SupportedProtocols) -> SupportedProtocols {
         SupportedProtocols(self.0 & other.0)
@@ -121,29 +192,74 @@ impl HandshakeMsg {
         &self,
         other: &HandshakeMsg,
     ) -> Option<(MessagingProtocolVersion, SupportedProtocols)> {
-        // First, find the highest MessagingProtocolVersion supported by both nodes.
-        let mut inner = other.supported_protocols.iter().rev().peekable();
-        // Iterate over all supported protocol versions in decreasing order.
-        for (k_outer, _) in self.supported_protocols.iter().rev() {
-            // Remove all elements from inner iterator that are larger than the current head of the
-            // outer iterator.
-            match inner.by_ref().find(|(k_inner, _)| *k_inner <= k_outer) {
-                None => {
-                    return None;
-                }
-                Some((k_inner, _)) if k_inner == k_outer => {
-                    // Find all protocols supported by both nodes for the above protocol version.
-                    // Both `self` and `other` shold have entry in map for `key`.
-                    let protocols_self = self.supported_protocols.get(k_inner).unwrap();
-                    let protocols_other = other.supported_protocols.get(k_inner).unwrap();
-                    return Some((
-                        *k_inner,
-                        protocols_self.clone().intersection(protocols_other.clone()),
-                    ));
-                }
-                _ => {}
+        // Find the highest MessagingProtocolVersion supported by both nodes, and all protocols
+        // supported by both nodes over it.
+        self.supported_protocols
+            .iter()
+            .rev()
+            .find_map(|(version, protocols_self)| {
+                other
+                    .supported_protocols
+                    .get(version)
+                    .map(|protocols_other| {
+                        (
+                            *version,
+                            protocols_self.clone().intersection(protocols_other.clone()),
+                        )
+                    })
+            })
+    }
+}
+
+/// The `HandshakeMsg` of nodes running newer releases may advertise messaging protocol versions
+/// this node does not know. These are skipped when decoding instead of failing the handshake, so
+/// that a new version can be advertised once the releases which skip it are deployed.
+impl<'de> Deserialize<'de> for HandshakeMsg {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "HandshakeMsg")]
+        struct RawHandshakeMsg {
+            supported_protocols: BTreeMap<MaybeMessagingProtocolVersion, SupportedProtocols>,
+        }
+
+        let raw = RawHandshakeMsg::deserialize(deserializer)?;
+        Ok(Self {
+            supported_protocols: raw
+                .supported_protocols
+                .into_iter()
+                .filter_map(|(version, protocols)| version.0.map(|version| (version, protocols)))
+                .collect(),
+        })
+    }
+}
+
+/// A `MessagingProtocolVersion`, or `None` if it is not known to this node.
+#[derive(Eq, PartialEq, Ord, PartialOrd)]
+struct MaybeMessagingProtocolVersion(Option<MessagingProtocolVersion>);
+
+impl<'de> Deserialize<'de> for MaybeMessagingProtocolVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct VersionVisitor;
+
+        impl<'de> Visitor<'de> for VersionVisitor {
+            type Value = MaybeMessagingProtocolVersion;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a messaging protocol version")
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+                let (index, variant): (u32, _) = data.variant()?;
+                variant.unit_variant()?;
+                let version = match index {
+                    0 => Some(MessagingProtocolVersion::V1),
+                    1 => Some(MessagingProtocolVersion::V2),
+                    _ => None,
+                };
+                Ok(MaybeMessagingProtocolVersion(version))
             }
         }
-        None
+
+        deserializer.deserialize_enum("MessagingProtocolVersion", &["V1", "V2"], VersionVisitor)
     }
 }
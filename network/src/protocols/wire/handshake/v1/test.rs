@@ -8,6 +8,8 @@ use super::*;
 fn net_protocol() -> lcs::Result<()> {
     let protocol = MessagingProtocolVersion::V1;
     assert_eq!(lcs::to_bytes(&protocol)?, vec![0x00]);
+    let protocol = MessagingProtocolVersion::V2;
+    assert_eq!(lcs::to_bytes(&protocol)?, vec![0x01]);
     Ok(())
 }
 
@@ -82,3 +84,146 @@ fn common_protocols() {
         h1.find_common_protocols(&h2)
     );
 }
+
+#[test]
+fn highest_common_messaging_protocol() {
+    let protocols: SupportedProtocols = [ProtocolId::ConsensusRpc, ProtocolId::MempoolDirectSend]
+        .iter()
+        .into();
+    let mut v1_only = HandshakeMsg::new();
+    v1_only.add(MessagingProtocolVersion::V1, protocols.clone());
+    let mut v1_v2 = HandshakeMsg::new();
+    v1_v2.add(MessagingProtocolVersion::V1, protocols.clone());
+    v1_v2.add(MessagingProtocolVersion::V2, protocols.clone());
+    let mut v2_only = HandshakeMsg::new();
+    v2_only.add(MessagingProtocolVersion::V2, protocols.clone());
+
+    // Both ends agree on the highest version they both support, whichever end computes it.
+    for (h1, h2, expected) in vec![
+        (&v1_v2, &v1_v2, Some(MessagingProtocolVersion::V2)),
+        (&v1_v2, &v1_only, Some(MessagingProtocolVersion::V1)),
+        (&v1_v2, &v2_only, Some(MessagingProtocolVersion::V2)),
+        (&v1_only, &v2_only, None),
+    ] {
+        let common = h1.find_common_protocols(h2);
+        assert_eq!(common, h2.find_common_protocols(h1));
+        assert_eq!(common.map(|(version, _)| version), expected);
+    }
+
+    // The application protocols are those advertised for the negotiated version.
+    let mut v2_consensus = HandshakeMsg::new();
+    v2_consensus.add(MessagingProtocolVersion::V1, protocols.clone());
+    v2_consensus.add(
+        MessagingProtocolVersion::V2,
+        [ProtocolId::ConsensusRpc].iter().into(),
+    );
+    assert_eq!(
+        v1_v2.find_common_protocols(&v2_consensus),
+        Some((
+            MessagingProtocolVersion::V2,
+            [ProtocolId::ConsensusRpc].iter().into()
+        ))
+    );
+}
+
+// Messaging protocol versions unknown to this node, advertised by newer nodes, are skipped.
+#[test]
+fn unknown_messaging_protocols_are_skipped() {
+    let protocols: SupportedProtocols = [ProtocolId::ConsensusRpc].iter().into();
+    // The variant index of a version serializes as its single ULEB128 byte.
+    #[derive(Serialize)]
+    struct FutureHandshakeMsg {
+        supported_protocols: BTreeMap<u8, SupportedProtocols>,
+    }
+    let future_handshake = FutureHandshakeMsg {
+        supported_protocols: [
+            (0, protocols.clone()),
+            (1, protocols.clone()),
+            (42, protocols),
+        ]
+        .iter()
+        .cloned()
+        .collect(),
+    };
+
+    let handshake: HandshakeMsg =
+        lcs::from_bytes(&lcs::to_bytes(&future_handshake).unwrap()).unwrap();
+    assert_eq!(
+        handshake.supported_protocols.keys().collect::<Vec<_>>(),
+        vec![&MessagingProtocolVersion::V1, &MessagingProtocolVersion::V2]
+    );
+
+    let mut known_handshake = HandshakeMsg::new();
+    known_handshake.add(MessagingProtocolVersion::V1, SupportedProtocols::default());
+    let bytes = lcs::to_bytes(&known_handshake).unwrap();
+    let decoded: HandshakeMsg = lcs::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        decoded.supported_protocols,
+        known_handshake.supported_protocols
+    );
+}
+
+#[test]
+fn protocol_id_from_str() {
+    for i in 0..=(ProtocolId::OnchainDiscoveryRpc as u8) {
+        let protocol: ProtocolId = lcs::from_bytes(&[i]).unwrap();
+        assert_eq!(protocol.as_str().parse::<ProtocolId>().unwrap(), protocol);
+    }
+    assert!("UnknownRpc".parse::<ProtocolId>().is_err());
+}
+
+#[test]
+fn compression_negotiation() {
+    let protocols = [
+        ProtocolId::ConsensusRpc,
+        ProtocolId::StateSynchronizerDirectSend,
+    ];
+    let mut with_compression: SupportedProtocols = protocols.iter().into();
+    with_compression.add_compression([ProtocolId::StateSynchronizerDirectSend].iter());
+    let mut h1 = HandshakeMsg::new();
+    h1.add(MessagingProtocolVersion::V1, with_compression.clone());
+
+    // Both ends support compression.
+    let (_, common) = h1.find_common_protocols(&h1.clone()).unwrap();
+    assert_eq!(
+        common.compressed_protocols(),
+        vec![ProtocolId::StateSynchronizerDirectSend]
+    );
+    // The compression bits are not application protocols.
+    assert_eq!(
+        (common.try_into() as Result<Vec<ProtocolId>, _>).unwrap(),
+        protocols.to_vec()
+    );
+
+    // A node without compression support negotiates uncompressed messages.
+    let mut h2 = HandshakeMsg::new();
+    h2.add(MessagingProtocolVersion::V1, protocols.iter().into());
+    let (_, common) = h1.find_common_protocols(&h2).unwrap();
+    assert!(common.compressed_protocols().is_empty());
+    assert_eq!(common, protocols.iter().into());
+}
+
+#[test]
+fn rate_limited_error_negotiation() {
+    let protocols = [ProtocolId::ConsensusRpc];
+    let mut with_error: SupportedProtocols = protocols.iter().into();
+    with_error.add_rate_limited_error();
+    let mut h1 = HandshakeMsg::new();
+    h1.add(MessagingProtocolVersion::V1, with_error);
+
+    // Both ends understand the error.
+    let (_, common) = h1.find_common_protocols(&h1.clone()).unwrap();
+    assert!(common.supports_rate_limited_error());
+    // The rate limited error bit is not an application protocol.
+    assert_eq!(
+        (common.try_into() as Result<Vec<ProtocolId>, _>).unwrap(),
+        protocols.to_vec()
+    );
+
+    // A node which doesn't understand the error never receives it.
+    let mut h2 = HandshakeMsg::new();
+    h2.add(MessagingProtocolVersion::V1, protocols.iter().into());
+    let (_, common) = h1.find_common_protocols(&h2).unwrap();
+    assert!(!common.supports_rate_limited_error());
+    assert_eq!(common, protocols.iter().into());
+}
@@ -0,0 +1,299 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compression of the payloads of RPC and DirectSend messages.
+//!
+//! Compression is negotiated per connection and per protocol during the handshake (see
+//! [`SupportedProtocols::compressed_protocols`]). On a connection where both ends advertised
+//! compression for at least one protocol, the payload of every `RpcRequest`, `RpcResponse` and
+//! `DirectSendMsg` starts with a codec byte, and the sender deflates the payloads of the
+//! negotiated protocols which reach the size threshold of the protocol. The framing is per
+//! connection rather than per protocol since an `RpcResponse` doesn't carry its protocol. On
+//! other connections payloads are sent as is, so that nodes without compression keep working.
+//!
+//! Messaging protocol v2 replaces the codec byte with the `COMPRESSED` flag of its frames (see
+//! [`MessageFlags`](crate::protocols::wire::messaging::v2::MessageFlags)), which
+//! `deflate_payload` and `inflate_payload` implement.
+
+use crate::{
+    counters,
+    protocols::wire::{handshake::v1::SupportedProtocols, messaging::v1::NetworkMessage},
+    ProtocolId,
+};
+use anyhow::{bail, ensure, Result};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+/// Minimum payload size, per protocol, for a payload to be compressed. Only the protocols with a
+/// threshold advertise compression.
+pub type CompressionThresholds = HashMap<ProtocolId, usize>;
+
+/// Upper bound on the size of a decompressed payload, so that a small message can't expand into
+/// an arbitrary amount of memory.
+pub const MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+const CODEC_NONE: u8 = 0;
+const CODEC_DEFLATE: u8 = 1;
+
+/// Compresses the outbound messages and decompresses the inbound messages of a connection.
+#[derive(Debug)]
+pub struct MessageCompressor {
+    /// The thresholds of the protocols negotiated for compression, `None` if the connection
+    /// doesn't use compression framing.
+    thresholds: Option<CompressionThresholds>,
+}
+
+impl MessageCompressor {
+    /// Creates the compressor of a connection, given the local thresholds and the negotiated
+    /// protocols of the connection.
+    pub fn new(thresholds: &CompressionThresholds, negotiated: &SupportedProtocols) -> Self {
+        let thresholds: CompressionThresholds = negotiated
+            .compressed_protocols()
+            .into_iter()
+            .filter_map(|protocol| {
+                thresholds
+                    .get(&protocol)
+                    .map(|min_size| (protocol, *min_size))
+            })
+            .collect();
+        Self {
+            thresholds: if thresholds.is_empty() {
+                None
+            } else {
+                Some(thresholds)
+            },
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.thresholds.is_some()
+    }
+
+    /// Frames the payload of an outbound message sent on `protocol`, compressing it if the
+    /// protocol was negotiated and the payload reaches its threshold.
+    pub fn compress(&self, mut message: NetworkMessage, protocol: ProtocolId) -> NetworkMessage {
+        let thresholds = match &self.thresholds {
+            Some(thresholds) => thresholds,
+            None => return message,
+        };
+        if let Some(payload) = payload_mut(&mut message) {
+            let min_size = thresholds.get(&protocol);
+            *payload = match min_size {
+                Some(min_size) if payload.len() >= *min_size => {
+                    let compressed = deflate(&[CODEC_DEFLATE], payload);
+                    observe_compression(protocol, payload.len(), compressed.len());
+                    compressed
+                }
+                _ => {
+                    let mut framed = Vec::with_capacity(payload.len() + 1);
+                    framed.push(CODEC_NONE);
+                    framed.extend_from_slice(payload);
+                    framed
+                }
+            };
+        }
+        message
+    }
+
+    /// Removes the framing of the payload of an inbound message, decompressing it if needed.
+    pub fn decompress(&self, mut message: NetworkMessage) -> Result<NetworkMessage> {
+        if !self.is_enabled() {
+            return Ok(message);
+        }
+        if let Some(payload) = payload_mut(&mut message) {
+            *payload = match payload.split_first() {
+                Some((&CODEC_NONE, raw)) => raw.to_vec(),
+                Some((&CODEC_DEFLATE, compressed)) => inflate(compressed)?,
+                Some((codec, _)) => bail!("Unknown compression codec: {}", codec),
+                None => bail!("Missing compression codec"),
+            };
+        }
+        Ok(message)
+    }
+
+    /// Compresses the payload of an outbound v2 message sent on `protocol`, if the protocol was
+    /// negotiated and the payload reaches its threshold. Returns whether it was compressed.
+    pub fn deflate_payload(
+        &self,
+        mut message: NetworkMessage,
+        protocol: ProtocolId,
+    ) -> (NetworkMessage, bool) {
+        let min_size = match self
+            .thresholds
+            .as_ref()
+            .and_then(|thresholds| thresholds.get(&protocol))
+        {
+            Some(min_size) => *min_size,
+            None => return (message, false),
+        };
+        let compressed = match payload_mut(&mut message) {
+            Some(payload) if payload.len() >= min_size => {
+                let compressed = deflate(&[], payload);
+                observe_compression(protocol, payload.len(), compressed.len());
+                *payload = compressed;
+                true
+            }
+            _ => false,
+        };
+        (message, compressed)
+    }
+
+    /// Decompresses the payload of an inbound v2 message sent with the `COMPRESSED` flag.
+    pub fn inflate_payload(&self, mut message: NetworkMessage) -> Result<NetworkMessage> {
+        match payload_mut(&mut message) {
+            Some(payload) => *payload = inflate(payload)?,
+            None => bail!("Compressed message without payload"),
+        }
+        Ok(message)
+    }
+}
+
+fn observe_compression(protocol: ProtocolId, uncompressed_len: usize, compressed_len: usize) {
+    counters::LIBRA_NETWORK_COMPRESSION_BYTES
+        .with_label_values(&[protocol.as_str(), counters::UNCOMPRESSED_LABEL])
+        .inc_by(uncompressed_len as i64);
+    counters::LIBRA_NETWORK_COMPRESSION_BYTES
+        .with_label_values(&[protocol.as_str(), counters::COMPRESSED_LABEL])
+        .inc_by(compressed_len as i64);
+}
+
+fn payload_mut(message: &mut NetworkMessage) -> Option<&mut Vec<u8>> {
+    match message {
+        NetworkMessage::RpcRequest(request) => Some(&mut request.raw_request),
+        NetworkMessage::RpcResponse(response) => Some(&mut response.raw_response),
+        NetworkMessage::DirectSendMsg(message) => Some(&mut message.raw_msg),
+        _ => None,
+    }
+}
+
+/// Deflates `payload`, appending it to `header`.
+fn deflate(header: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(header.to_vec(), Compression::fast());
+    encoder
+        .write_all(payload)
+        .expect("Writing to a Vec can't fail");
+    encoder.finish().expect("Writing to a Vec can't fail")
+}
+
+fn inflate(compressed: &[u8]) -> Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    DeflateDecoder::new(compressed)
+        .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+        .read_to_end(&mut payload)?;
+    ensure!(
+        payload.len() <= MAX_DECOMPRESSED_SIZE,
+        "Decompressed payload exceeds {} bytes",
+        MAX_DECOMPRESSED_SIZE
+    );
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::wire::messaging::v1::{DirectSendMsg, RpcResponse};
+
+    fn compressor(
+        thresholds: &[(ProtocolId, usize)],
+        negotiated: &[ProtocolId],
+    ) -> MessageCompressor {
+        let mut supported: SupportedProtocols = negotiated.iter().into();
+        supported.add_compression(negotiated.iter());
+        MessageCompressor::new(&thresholds.iter().cloned().collect(), &supported)
+    }
+
+    fn direct_send(protocol_id: ProtocolId, raw_msg: Vec<u8>) -> NetworkMessage {
+        NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id,
+            priority: 0,
+            raw_msg,
+        })
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let protocol = ProtocolId::StateSynchronizerDirectSend;
+        let compressor = compressor(&[(protocol, 100)], &[protocol]);
+        assert!(compressor.is_enabled());
+        for message in vec![
+            direct_send(protocol, vec![7; 10_000]),
+            direct_send(protocol, vec![7; 10]),
+            // protocols without compression are framed but not compressed
+            direct_send(ProtocolId::ConsensusDirectSend, vec![7; 10_000]),
+            NetworkMessage::RpcResponse(RpcResponse {
+                request_id: 0,
+                priority: 0,
+                raw_response: vec![],
+            }),
+        ] {
+            let protocol = match &message {
+                NetworkMessage::DirectSendMsg(message) => message.protocol_id,
+                _ => protocol,
+            };
+            let compressed = compressor.compress(message.clone(), protocol);
+            assert_eq!(compressor.decompress(compressed).unwrap(), message);
+        }
+
+        let compressed = compressor.compress(direct_send(protocol, vec![7; 10_000]), protocol);
+        assert!(lcs::to_bytes(&compressed).unwrap().len() < 1000);
+        let uncompressed = compressor.compress(
+            direct_send(ProtocolId::ConsensusDirectSend, vec![7; 10_000]),
+            ProtocolId::ConsensusDirectSend,
+        );
+        assert!(lcs::to_bytes(&uncompressed).unwrap().len() > 10_000);
+    }
+
+    #[test]
+    fn test_not_negotiated() {
+        let protocol = ProtocolId::MempoolDirectSend;
+        // the remote peer didn't advertise compression
+        let compressor = compressor(&[(protocol, 0)], &[]);
+        assert!(!compressor.is_enabled());
+        let message = direct_send(protocol, vec![7; 10_000]);
+        assert_eq!(compressor.compress(message.clone(), protocol), message);
+        assert_eq!(compressor.decompress(message.clone()).unwrap(), message);
+    }
+
+    #[test]
+    fn test_invalid_payload() {
+        let protocol = ProtocolId::MempoolDirectSend;
+        let compressor = compressor(&[(protocol, 0)], &[protocol]);
+        assert!(compressor
+            .decompress(direct_send(protocol, vec![]))
+            .is_err());
+        assert!(compressor
+            .decompress(direct_send(protocol, vec![42, 1, 2]))
+            .is_err());
+        assert!(compressor
+            .decompress(direct_send(protocol, vec![CODEC_DEFLATE, 0xff, 0xff]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_v2_payloads() {
+        let protocol = ProtocolId::StateSynchronizerDirectSend;
+        let compressor = compressor(&[(protocol, 100)], &[protocol]);
+        let message = direct_send(protocol, vec![7; 10_000]);
+        let (compressed, is_compressed) = compressor.deflate_payload(message.clone(), protocol);
+        assert!(is_compressed);
+        assert!(lcs::to_bytes(&compressed).unwrap().len() < 1000);
+        assert_eq!(compressor.inflate_payload(compressed).unwrap(), message);
+
+        // small payloads and other protocols are sent as is, without a codec byte
+        for (message, protocol) in vec![
+            (direct_send(protocol, vec![7; 10]), protocol),
+            (
+                direct_send(ProtocolId::ConsensusDirectSend, vec![7; 10_000]),
+                ProtocolId::ConsensusDirectSend,
+            ),
+        ] {
+            assert_eq!(
+                compressor.deflate_payload(message.clone(), protocol),
+                (message, false)
+            );
+        }
+    }
+}
@@ -7,6 +7,7 @@
 use crate::protocols::wire::handshake::v1::{MessagingProtocolVersion, ProtocolId};
 use serde::{Deserialize, Serialize};
 
+pub mod compression;
 #[cfg(test)]
 mod test;
 
@@ -32,6 +33,10 @@ pub enum ErrorCode {
     ParsingError(MessagingProtocolVersion, Box<NetworkMessage>),
     /// A message was received for a protocol that is not supported over this connection.
     NotSupported(ProtocolId),
+    /// An RPC request was dropped by the inbound rate limit of its protocol. Only sent to the peers
+    /// which advertise it in the handshake (see `SupportedProtocols::supports_rate_limited_error`),
+    /// the requests of the others are dropped silently and time out.
+    RateLimited(RequestId),
 }
 
 /// Nonces used by Ping and Pong message types.
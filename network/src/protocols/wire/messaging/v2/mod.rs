@@ -0,0 +1,76 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines the framing of the network messaging protocol v2.
+//!
+//! v2 carries the same `NetworkMessage`s as v1, each frame being prefixed with a `MessageFlags`
+//! byte describing how the message was sent:
+//!
+//! ```text
+//! +-------+---------------------------------+
+//! | flags | LCS-serialized NetworkMessage   |
+//! +-------+---------------------------------+
+//!   1 byte
+//! ```
+//!
+//! * `COMPRESSED`: the payload of the `RpcRequest`, `RpcResponse` or `DirectSendMsg` is deflated.
+//!   Unlike v1, where every payload of a connection with compression starts with a codec byte,
+//!   the payloads of v2 messages are only altered when this flag is set.
+//! * `PRIORITY`: the message was sent ahead of the bulk traffic of the connection.
+//!
+//! A receiver rejects the frames with flags it doesn't know, since they may change the meaning of
+//! the message. New flags therefore require a new messaging protocol version.
+
+use crate::protocols::wire::messaging::v1::NetworkMessage;
+use anyhow::{bail, Result};
+
+#[cfg(test)]
+mod test;
+
+/// Flags of a v2 frame.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MessageFlags(u8);
+
+impl MessageFlags {
+    const COMPRESSED: u8 = 0b0000_0001;
+    const PRIORITY: u8 = 0b0000_0010;
+    const KNOWN: u8 = Self::COMPRESSED | Self::PRIORITY;
+
+    pub fn new(compressed: bool, priority: bool) -> Self {
+        let mut flags = 0;
+        if compressed {
+            flags |= Self::COMPRESSED;
+        }
+        if priority {
+            flags |= Self::PRIORITY;
+        }
+        Self(flags)
+    }
+
+    pub fn is_compressed(self) -> bool {
+        self.0 & Self::COMPRESSED != 0
+    }
+
+    pub fn is_priority(self) -> bool {
+        self.0 & Self::PRIORITY != 0
+    }
+}
+
+/// Serializes `message` into a v2 frame.
+pub fn to_frame(flags: MessageFlags, message: &NetworkMessage) -> lcs::Result<Vec<u8>> {
+    let mut frame = vec![flags.0];
+    frame.extend(lcs::to_bytes(message)?);
+    Ok(frame)
+}
+
+/// Deserializes a v2 frame. The payload of the message is left as is, even if it is compressed.
+pub fn from_frame(frame: &[u8]) -> Result<(MessageFlags, NetworkMessage)> {
+    let (flags, message) = match frame.split_first() {
+        Some((flags, message)) => (*flags, message),
+        None => bail!("Empty frame"),
+    };
+    if flags & !MessageFlags::KNOWN != 0 {
+        bail!("Unknown message flags: {:#010b}", flags);
+    }
+    Ok((MessageFlags(flags), lcs::from_bytes(message)?))
+}
@@ -0,0 +1,44 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    protocols::wire::messaging::v1::{DirectSendMsg, Nonce},
+    ProtocolId,
+};
+
+#[test]
+fn message_flags() {
+    let flags = MessageFlags::default();
+    assert!(!flags.is_compressed() && !flags.is_priority());
+    let flags = MessageFlags::new(true, false);
+    assert!(flags.is_compressed() && !flags.is_priority());
+    let flags = MessageFlags::new(false, true);
+    assert!(!flags.is_compressed() && flags.is_priority());
+}
+
+#[test]
+fn frame_roundtrip() {
+    let message = NetworkMessage::DirectSendMsg(DirectSendMsg {
+        protocol_id: ProtocolId::ConsensusDirectSend,
+        priority: 0,
+        raw_msg: vec![1, 2, 3],
+    });
+    let flags = MessageFlags::new(false, true);
+    let frame = to_frame(flags, &message).unwrap();
+    // The flags are followed by the message as serialized in v1.
+    assert_eq!(frame[0], 0b0000_0010);
+    assert_eq!(frame[1..], lcs::to_bytes(&message).unwrap()[..]);
+    assert_eq!(from_frame(&frame).unwrap(), (flags, message));
+}
+
+#[test]
+fn invalid_frames() {
+    assert!(from_frame(&[]).is_err());
+    let mut frame = to_frame(MessageFlags::default(), &NetworkMessage::Ping(Nonce(1))).unwrap();
+    assert!(from_frame(&frame).is_ok());
+    frame[0] = 0b1000_0000;
+    assert!(from_frame(&frame).is_err());
+    // A v1 message isn't a valid v2 frame.
+    assert!(from_frame(&lcs::to_bytes(&NetworkMessage::Ping(Nonce(1))).unwrap()).is_err());
+}
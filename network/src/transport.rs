@@ -15,7 +15,7 @@ use libra_logger::prelude::*;
 use libra_network_address::NetworkAddress;
 use libra_security_logger::{security_log, SecurityEvent};
 use libra_types::PeerId;
-use netcore::transport::{boxed, memory, tcp, ConnectionOrigin, TransportExt};
+use netcore::transport::{boxed, memory, simulated, tcp, ConnectionOrigin, TransportExt};
 use once_cell::sync::Lazy;
 use std::{
     collections::HashMap,
@@ -28,9 +28,15 @@ use std::{
 
 /// A timeout for the connection to open and complete all of the upgrade steps.
 pub const TRANSPORT_TIMEOUT: Duration = Duration::from_secs(30);
-/// Currently supported messaging protocol version.
-/// TODO: Add ability to support more than one messaging protocol.
-pub const SUPPORTED_MESSAGING_PROTOCOL: MessagingProtocolVersion = MessagingProtocolVersion::V1;
+/// Advertised messaging protocol versions, from old to new. The handshake picks the highest
+/// version advertised by both ends, so that connections to the nodes which only support older
+/// versions keep using those.
+///
+/// `MessagingProtocolVersion::V2` is supported but not advertised yet: deployed releases fail to
+/// decode a handshake advertising it, and only skip unknown versions from this release on. It is
+/// to be added once this release is deployed.
+pub const SUPPORTED_MESSAGING_PROTOCOLS: [MessagingProtocolVersion; 1] =
+    [MessagingProtocolVersion::V1];
 /// Global connection-id generator.
 static CONNECTION_ID_GENERATOR: Lazy<Arc<Mutex<ConnectionIdGenerator>>> =
     Lazy::new(|| Arc::new(Mutex::new(ConnectionIdGenerator::new())));
@@ -122,6 +128,14 @@ impl ConnectionMetadata {
     pub fn origin(&self) -> ConnectionOrigin {
         self.origin
     }
+
+    pub fn messaging_protocol(&self) -> MessagingProtocolVersion {
+        self.messaging_protocol
+    }
+
+    pub fn application_protocols(&self) -> &SupportedProtocols {
+        &self.application_protocols
+    }
 }
 
 /// The `Connection` struct consists of connection metadata and the actual socket for
@@ -175,6 +189,18 @@ fn verify_noise_pubkey(
     Ok(())
 }
 
+/// The `HandshakeMsg` advertising `application_protocols` over each of `messaging_protocols`.
+pub fn build_handshake_msg(
+    messaging_protocols: &[MessagingProtocolVersion],
+    application_protocols: SupportedProtocols,
+) -> HandshakeMsg {
+    let mut handshake = HandshakeMsg::new();
+    for messaging_protocol in messaging_protocols {
+        handshake.add(*messaging_protocol, application_protocols.clone());
+    }
+    handshake
+}
+
 pub async fn perform_handshake<T: TSocket>(
     peer_id: PeerId,
     mut socket: T,
@@ -215,8 +241,7 @@ pub fn build_memory_noise_transport(
     let memory_transport = memory::MemoryTransport::default();
     let noise_config = Arc::new(NoiseWrapper::new(identity_key));
     let noise_timestamps = Arc::new(RwLock::new(AntiReplayTimestamps::default()));
-    let mut own_handshake = HandshakeMsg::new();
-    own_handshake.add(SUPPORTED_MESSAGING_PROTOCOL, application_protocols);
+    let own_handshake = build_handshake_msg(&SUPPORTED_MESSAGING_PROTOCOLS, application_protocols);
 
     memory_transport
         .and_then(move |socket, addr, origin| async move {
@@ -252,8 +277,7 @@ pub fn build_unauthenticated_memory_noise_transport(
 ) -> boxed::BoxedTransport<Connection<impl TSocket>, impl ::std::error::Error> {
     let memory_transport = memory::MemoryTransport::default();
     let noise_config = Arc::new(NoiseWrapper::new(identity_key));
-    let mut own_handshake = HandshakeMsg::new();
-    own_handshake.add(SUPPORTED_MESSAGING_PROTOCOL, application_protocols);
+    let own_handshake = build_handshake_msg(&SUPPORTED_MESSAGING_PROTOCOLS, application_protocols);
 
     memory_transport
         .and_then(move |socket, addr, origin| {
@@ -286,8 +310,7 @@ pub fn build_memory_transport(
     application_protocols: SupportedProtocols,
 ) -> boxed::BoxedTransport<Connection<impl TSocket>, impl ::std::error::Error> {
     let memory_transport = memory::MemoryTransport::default();
-    let mut own_handshake = HandshakeMsg::new();
-    own_handshake.add(SUPPORTED_MESSAGING_PROTOCOL, application_protocols);
+    let own_handshake = build_handshake_msg(&SUPPORTED_MESSAGING_PROTOCOLS, application_protocols);
 
     memory_transport
         .and_then(move |mut socket, _addr, _origin| async move {
@@ -300,6 +323,85 @@ pub fn build_memory_transport(
         .boxed()
 }
 
+/// Like `build_memory_transport`, over a node of a `NetworkSimulator` so that tests can inject
+/// delays, drops and partitions between the nodes. The drops, duplicates and partitions only apply
+/// to the messages of the connections once they are set up.
+pub fn build_simulated_transport(
+    simulated_transport: simulated::SimulatedTransport,
+    own_peer_id: PeerId,
+    application_protocols: SupportedProtocols,
+) -> boxed::BoxedTransport<Connection<impl TSocket>, impl ::std::error::Error> {
+    let own_handshake = build_handshake_msg(&SUPPORTED_MESSAGING_PROTOCOLS, application_protocols);
+
+    simulated_transport
+        .and_then(move |mut socket, _addr, _origin| async move {
+            let faults = socket.faults();
+            let peer_id = exchange_peerid(&own_peer_id, &mut socket).await?;
+            Ok((peer_id, faults, socket))
+        })
+        .and_then(move |(peer_id, faults, socket), addr, origin| async move {
+            let connection =
+                perform_handshake(peer_id, socket, addr, origin, &own_handshake).await?;
+            Ok(with_faults(connection, faults))
+        })
+        .with_timeout(TRANSPORT_TIMEOUT)
+        .boxed()
+}
+
+/// Like `build_memory_noise_transport`, over a node of a `NetworkSimulator`. The drops, duplicates
+/// and partitions apply to the messages before they are encrypted.
+pub fn build_simulated_noise_transport(
+    simulated_transport: simulated::SimulatedTransport,
+    identity_key: x25519::PrivateKey,
+    trusted_peers: Arc<RwLock<HashMap<PeerId, NetworkPublicKeys>>>,
+    application_protocols: SupportedProtocols,
+) -> boxed::BoxedTransport<Connection<impl TSocket>, impl ::std::error::Error> {
+    let noise_config = Arc::new(NoiseWrapper::new(identity_key));
+    let noise_timestamps = Arc::new(RwLock::new(AntiReplayTimestamps::default()));
+    let own_handshake = build_handshake_msg(&SUPPORTED_MESSAGING_PROTOCOLS, application_protocols);
+
+    simulated_transport
+        .and_then(move |mut socket, addr, origin| async move {
+            let faults = socket.faults();
+            let remote_public_key = addr.find_noise_proto();
+            let (remote_static_key, socket) = noise_config
+                .upgrade_connection(
+                    socket,
+                    origin,
+                    Some(noise_timestamps),
+                    remote_public_key,
+                    Some(&trusted_peers),
+                )
+                .await?;
+
+            if let Some(peer_id) =
+                identity_key_to_peer_id(&trusted_peers, remote_static_key.as_slice())
+            {
+                Ok((peer_id, faults, socket))
+            } else {
+                Err(io::Error::new(io::ErrorKind::Other, "Not a trusted peer"))
+            }
+        })
+        .and_then(move |(peer_id, faults, socket), addr, origin| async move {
+            let connection =
+                perform_handshake(peer_id, socket, addr, origin, &own_handshake).await?;
+            Ok(with_faults(connection, faults))
+        })
+        .with_timeout(TRANSPORT_TIMEOUT)
+        .boxed()
+}
+
+/// Applies the faults of a simulated link to the messages of a connection once it is set up.
+fn with_faults<T: TSocket>(
+    connection: Connection<T>,
+    faults: simulated::LinkFaults,
+) -> Connection<simulated::FaultySocket<T>> {
+    Connection {
+        socket: simulated::FaultySocket::new(connection.socket, faults),
+        metadata: connection.metadata,
+    }
+}
+
 //TODO(bmwill) Maybe create an Either Transport so we can merge the building of Memory + Tcp
 pub fn build_tcp_noise_transport(
     identity_key: x25519::PrivateKey,
@@ -308,8 +410,7 @@ pub fn build_tcp_noise_transport(
 ) -> boxed::BoxedTransport<Connection<impl TSocket>, impl ::std::error::Error> {
     let noise_config = Arc::new(NoiseWrapper::new(identity_key));
     let noise_timestamps = Arc::new(RwLock::new(AntiReplayTimestamps::default()));
-    let mut own_handshake = HandshakeMsg::new();
-    own_handshake.add(SUPPORTED_MESSAGING_PROTOCOL, application_protocols);
+    let own_handshake = build_handshake_msg(&SUPPORTED_MESSAGING_PROTOCOLS, application_protocols);
 
     LIBRA_TCP_TRANSPORT
         .and_then(move |socket, addr, origin| async move {
@@ -351,8 +452,7 @@ pub fn build_unauthenticated_tcp_noise_transport(
     application_protocols: SupportedProtocols,
 ) -> boxed::BoxedTransport<Connection<impl TSocket>, impl ::std::error::Error> {
     let noise_config = Arc::new(NoiseWrapper::new(identity_key));
-    let mut own_handshake = HandshakeMsg::new();
-    own_handshake.add(SUPPORTED_MESSAGING_PROTOCOL, application_protocols);
+    let own_handshake = build_handshake_msg(&SUPPORTED_MESSAGING_PROTOCOLS, application_protocols);
 
     LIBRA_TCP_TRANSPORT
         .and_then(move |socket, addr, origin| {
@@ -384,8 +484,7 @@ pub fn build_tcp_transport(
     own_peer_id: PeerId,
     application_protocols: SupportedProtocols,
 ) -> boxed::BoxedTransport<Connection<impl TSocket>, impl ::std::error::Error> {
-    let mut own_handshake = HandshakeMsg::new();
-    own_handshake.add(SUPPORTED_MESSAGING_PROTOCOL, application_protocols);
+    let own_handshake = build_handshake_msg(&SUPPORTED_MESSAGING_PROTOCOLS, application_protocols);
 
     LIBRA_TCP_TRANSPORT
         .and_then(move |mut socket, _addr, _origin| async move {
@@ -11,34 +11,47 @@
 //! long as the latter is in its trusted peers set.
 use crate::{
     common::NetworkPublicKeys,
-    connectivity_manager::{ConnectivityManager, ConnectivityRequest},
+    connectivity_manager::{
+        dns_discovery::{DnsDiscovery, SystemDnsResolver},
+        peers_file_watcher::PeersFileWatcher,
+        ConnectivityManager, ConnectivityRequest,
+    },
     counters,
+    peer::{bandwidth::ProtocolRateLimits, capture::TrafficRecorder},
     peer_manager::{
-        conn_notifs_channel, ConnectionRequest, ConnectionRequestSender, PeerManager,
-        PeerManagerNotification, PeerManagerRequest, PeerManagerRequestSender,
+        admission::{InboundAdmission, InboundConnectionLimits},
+        conn_notifs_channel,
+        reputation::PeerReputation,
+        ConnectionRequest, ConnectionRequestSender, PeerManager, PeerManagerNotification,
+        PeerManagerRequest, PeerManagerRequestSender,
     },
+    peer_metadata::PeerMetadata,
     protocols::{
         discovery::{self, Discovery},
         health_checker::{self, HealthChecker},
-        wire::handshake::v1::SupportedProtocols,
+        wire::{
+            handshake::v1::SupportedProtocols, messaging::v1::compression::CompressionThresholds,
+        },
     },
     transport,
     transport::*,
     ProtocolId,
 };
+use anyhow::{bail, Result};
 use channel::{self, libra_channel, message_queues::QueueStyle};
 use futures::stream::StreamExt;
-use libra_config::config::{RoleType, HANDSHAKE_VERSION};
+use libra_config::config::{PeerReputationConfig, RoleType, HANDSHAKE_VERSION};
 use libra_crypto::x25519;
 use libra_logger::prelude::*;
 use libra_metrics::IntCounterVec;
 use libra_network_address::NetworkAddress;
 use libra_types::PeerId;
-use netcore::transport::Transport;
+use netcore::transport::{simulated::SimulatedTransport, Transport};
 use std::{
     clone::Clone,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     num::NonZeroUsize,
+    path::PathBuf,
     sync::{Arc, RwLock},
     time::Duration,
 };
@@ -62,6 +75,11 @@ pub const PING_FAILURES_TOLERATED: u64 = 10;
 pub const MAX_CONCURRENT_NETWORK_REQS: usize = 100;
 pub const MAX_CONCURRENT_NETWORK_NOTIFS: usize = 100;
 pub const MAX_CONNECTION_DELAY_MS: u64 = 10 * 60 * 1000 /* 10 minutes */;
+pub const MAX_INBOUND_CONNECTIONS: usize = 100;
+pub const MAX_INBOUND_CONNECTIONS_PER_IP: usize = 8;
+pub const MAX_INBOUND_CONNECTIONS_PER_SUBNET: usize = 32;
+pub const MAX_INBOUND_HANDSHAKES_PER_SEC: u64 = 20;
+pub const COMPRESSION_MIN_SIZE: usize = 4096;
 
 #[derive(Debug)]
 pub enum AuthenticationMode {
@@ -131,6 +149,14 @@ pub struct NetworkBuilder {
     max_concurrent_network_reqs: usize,
     max_concurrent_network_notifs: usize,
     max_connection_delay_ms: u64,
+    peer_reputation: PeerReputationConfig,
+    inbound_connection_limits: InboundConnectionLimits,
+    preferred_inbound_peers: Arc<RwLock<HashSet<PeerId>>>,
+    protocol_rate_limits: ProtocolRateLimits,
+    compression_thresholds: CompressionThresholds,
+    simulated_transport: Option<SimulatedTransport>,
+    traffic_recorder: Option<TrafficRecorder>,
+    peer_metadata: PeerMetadata,
 }
 
 impl NetworkBuilder {
@@ -180,6 +206,28 @@ impl NetworkBuilder {
             max_concurrent_network_reqs: MAX_CONCURRENT_NETWORK_REQS,
             max_concurrent_network_notifs: MAX_CONCURRENT_NETWORK_NOTIFS,
             max_connection_delay_ms: MAX_CONNECTION_DELAY_MS,
+            peer_reputation: PeerReputationConfig::default(),
+            inbound_connection_limits: InboundConnectionLimits {
+                max_connections: MAX_INBOUND_CONNECTIONS,
+                max_connections_per_ip: MAX_INBOUND_CONNECTIONS_PER_IP,
+                max_connections_per_subnet: MAX_INBOUND_CONNECTIONS_PER_SUBNET,
+                max_handshakes_per_sec: MAX_INBOUND_HANDSHAKES_PER_SEC,
+            },
+            preferred_inbound_peers: Arc::new(RwLock::new(HashSet::new())),
+            protocol_rate_limits: ProtocolRateLimits::new(),
+            compression_thresholds: [
+                (ProtocolId::MempoolDirectSend, COMPRESSION_MIN_SIZE),
+                (
+                    ProtocolId::StateSynchronizerDirectSend,
+                    COMPRESSION_MIN_SIZE,
+                ),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+            simulated_transport: None,
+            traffic_recorder: None,
+            peer_metadata: PeerMetadata::new(),
         }
     }
 
@@ -225,15 +273,83 @@ impl NetworkBuilder {
         self
     }
 
+    /// Set the misbehavior penalties and the score at which a peer gets banned
+    pub fn peer_reputation(&mut self, peer_reputation: PeerReputationConfig) -> &mut Self {
+        self.peer_reputation = peer_reputation;
+        self
+    }
+
+    /// Set the limits on inbound connections
+    pub fn inbound_connection_limits(
+        &mut self,
+        inbound_connection_limits: InboundConnectionLimits,
+    ) -> &mut Self {
+        self.inbound_connection_limits = inbound_connection_limits;
+        self
+    }
+
+    /// Set peers which are exempt from the per-IP and per-subnet inbound limits and take
+    /// precedence over unknown peers once the inbound connection limit is reached. Trusted peers
+    /// and seed peers are always preferred.
+    pub fn preferred_inbound_peers(
+        &mut self,
+        preferred_inbound_peers: HashSet<PeerId>,
+    ) -> &mut Self {
+        *self.preferred_inbound_peers.write().unwrap() = preferred_inbound_peers;
+        self
+    }
+
+    /// Set the per-connection bandwidth limits of protocols. Protocols without a limit are
+    /// unlimited.
+    pub fn protocol_rate_limits(&mut self, protocol_rate_limits: ProtocolRateLimits) -> &mut Self {
+        self.protocol_rate_limits = protocol_rate_limits;
+        self
+    }
+
+    /// Set the protocols whose messages are compressed, with the minimum size of the messages to
+    /// compress. Compression is only used on connections where the remote peer supports it too.
+    pub fn compression_thresholds(
+        &mut self,
+        compression_thresholds: CompressionThresholds,
+    ) -> &mut Self {
+        self.compression_thresholds = compression_thresholds;
+        self
+    }
+
+    /// Connect through a node of a `NetworkSimulator` rather than plain in-memory sockets. Only
+    /// applies to a `/memory/<port>` listen address, `build` fails if the authentication mode is
+    /// `AuthenticationMode::ServerOnly`.
+    pub fn simulated_transport(&mut self, simulated_transport: SimulatedTransport) -> &mut Self {
+        self.simulated_transport = Some(simulated_transport);
+        self
+    }
+
+    /// Record the messages exchanged on all connections. See `network::capture`.
+    pub fn traffic_recorder(&mut self, traffic_recorder: TrafficRecorder) -> &mut Self {
+        self.traffic_recorder = Some(traffic_recorder);
+        self
+    }
+
+    /// Record the round-trip times and failures of the health checker pings in `peer_metadata`,
+    /// which may be shared with the other networks of the node.
+    pub fn peer_metadata(&mut self, peer_metadata: PeerMetadata) -> &mut Self {
+        self.peer_metadata = peer_metadata;
+        self
+    }
+
     pub fn conn_mgr_reqs_tx(&self) -> Option<channel::Sender<ConnectivityRequest>> {
         self.conn_mgr_reqs_tx.clone()
     }
 
     fn supported_protocols(&self) -> SupportedProtocols {
-        self.direct_send_protocols
+        let mut supported_protocols: SupportedProtocols = self
+            .direct_send_protocols
             .iter()
             .chain(&self.rpc_protocols)
-            .into()
+            .into();
+        supported_protocols.add_compression(self.compression_thresholds.keys());
+        supported_protocols.add_rate_limited_error();
+        supported_protocols
     }
 
     /// Add a handler for given protocols using raw bytes.
@@ -321,6 +437,58 @@ impl NetworkBuilder {
         self
     }
 
+    /// Reload the network peers file and the seed peers file every `reload_interval_ms`, and
+    /// push the changes to the [`ConnectivityManager`], which must be added first. `None` for a
+    /// file the node doesn't use. Once a file changes, the preferred inbound peers are the peers
+    /// of both files.
+    pub fn add_peers_file_watcher(
+        &mut self,
+        network_peers_file: Option<PathBuf>,
+        seed_peers_file: Option<PathBuf>,
+        reload_interval_ms: u64,
+    ) -> &mut Self {
+        let conn_mgr_reqs_tx = self
+            .conn_mgr_reqs_tx()
+            .expect("ConnectivityManager must be added before the PeersFileWatcher");
+        let network_peers = self.trusted_peers.read().unwrap().clone();
+        let peers_file_watcher = PeersFileWatcher::new(
+            network_peers_file,
+            seed_peers_file,
+            network_peers,
+            self.seed_peers.clone(),
+            conn_mgr_reqs_tx,
+            self.preferred_inbound_peers.clone(),
+        );
+        let ticker = self
+            .executor
+            .enter(|| interval(Duration::from_millis(reload_interval_ms)));
+        self.executor.spawn(peers_file_watcher.start(ticker));
+        self
+    }
+
+    /// Add DNS seed discovery to the network: the TXT records of `dns_seeds` and the hostnames of
+    /// the addresses they advertise are resolved every `interval_ms`, and the peers are passed to
+    /// the [`ConnectivityManager`], which must be added first.
+    pub fn add_dns_discovery(&mut self, dns_seeds: Vec<String>, interval_ms: u64) -> &mut Self {
+        let conn_mgr_reqs_tx = self
+            .conn_mgr_reqs_tx()
+            .expect("ConnectivityManager must be added before DnsDiscovery");
+        let ticker = self
+            .executor
+            .enter(|| interval(Duration::from_millis(interval_ms)));
+        self.executor.spawn(async move {
+            match SystemDnsResolver::new().await {
+                Ok(resolver) => {
+                    DnsDiscovery::new(dns_seeds, Arc::new(resolver), ticker, conn_mgr_reqs_tx)
+                        .start()
+                        .await
+                }
+                Err(err) => error!("Failed to create DNS resolver, no DNS discovery: {}", err),
+            }
+        });
+        self
+    }
+
     /// Add the (gossip) [`Discovery`] protocol to the network.
     ///
     /// (gossip) [`Discovery`] discovers other eligible peers' network addresses
@@ -382,6 +550,8 @@ impl NetworkBuilder {
         let ping_interval_ms = self.ping_interval_ms;
         let ping_timeout_ms = self.ping_timeout_ms;
         let ping_failures_tolerated = self.ping_failures_tolerated;
+        let network_id = self.peer_id;
+        let peer_metadata = self.peer_metadata.clone();
         let health_checker = self.executor.enter(|| {
             HealthChecker::new(
                 interval(Duration::from_millis(ping_interval_ms)).fuse(),
@@ -389,6 +559,8 @@ impl NetworkBuilder {
                 hc_network_rx,
                 Duration::from_millis(ping_timeout_ms),
                 ping_failures_tolerated,
+                network_id,
+                peer_metadata,
             )
         });
         self.executor.spawn(health_checker.start());
@@ -397,8 +569,9 @@ impl NetworkBuilder {
     }
 
     /// Create the configured transport and start PeerManager.
-    /// Return the actual NetworkAddress over which this peer is listening.
-    pub fn build(mut self) -> NetworkAddress {
+    /// Return the actual NetworkAddress over which this peer is listening, or an error if the
+    /// listen address or the authentication mode isn't supported.
+    pub fn build(mut self) -> Result<NetworkAddress> {
         use libra_network_address::Protocol::*;
 
         let peer_id = self.peer_id;
@@ -433,6 +606,25 @@ impl NetworkBuilder {
                     self.build_with_transport(build_tcp_noise_transport(key, trusted_peers, protos))
                 }
             },
+            [Memory(_)] if self.simulated_transport.is_some() => {
+                let simulated_transport = self.simulated_transport.take().unwrap();
+                match authentication_mode {
+                    AuthenticationMode::Unauthenticated => self.build_with_transport(
+                        build_simulated_transport(simulated_transport, peer_id, protos),
+                    ),
+                    AuthenticationMode::Mutual(key) => {
+                        self.build_with_transport(build_simulated_noise_transport(
+                            simulated_transport,
+                            key,
+                            trusted_peers,
+                            protos,
+                        ))
+                    }
+                    AuthenticationMode::ServerOnly(_) => {
+                        bail!("The simulated transport doesn't support server-only authentication")
+                    }
+                }
+            }
             [Memory(_)] => match authentication_mode {
                 AuthenticationMode::Unauthenticated => {
                     self.build_with_transport(build_memory_transport(peer_id, protos))
@@ -443,7 +635,7 @@ impl NetworkBuilder {
                 AuthenticationMode::Mutual(key) => self
                     .build_with_transport(build_memory_noise_transport(key, trusted_peers, protos)),
             },
-            _ => panic!(
+            _ => bail!(
                 "Unsupported listen_address: '{}', expected '/memory/<port>', \
                  '/ip4/<addr>/tcp/<port>', or '/ip6/<addr>/tcp/<port>'.",
                 self.listen_address
@@ -452,7 +644,7 @@ impl NetworkBuilder {
 
         // do some surgery here...
         let libranet_protos = &unbound_listen_addr.as_slice()[unbound_len..];
-        bound_listen_addr.extend_from_slice(libranet_protos)
+        Ok(bound_listen_addr.extend_from_slice(libranet_protos))
     }
 
     /// Given a transport build and launch PeerManager.
@@ -462,6 +654,10 @@ impl NetworkBuilder {
         TTransport: Transport<Output = Connection<TSocket>> + Send + 'static,
         TSocket: transport::TSocket,
     {
+        self.preferred_inbound_peers
+            .write()
+            .unwrap()
+            .extend(self.seed_peers.keys().cloned());
         let peer_mgr = PeerManager::new(
             self.executor.clone(),
             transport,
@@ -477,6 +673,15 @@ impl NetworkBuilder {
             self.max_concurrent_network_reqs,
             self.max_concurrent_network_notifs,
             self.channel_size,
+            PeerReputation::new(self.peer_reputation, self.trusted_peers.clone()),
+            InboundAdmission::new(
+                self.inbound_connection_limits,
+                self.trusted_peers.clone(),
+                self.preferred_inbound_peers.clone(),
+            ),
+            self.protocol_rate_limits.clone(),
+            self.compression_thresholds.clone(),
+            self.traffic_recorder.clone(),
         );
         let listen_addr = peer_mgr.listen_addr().clone();
 
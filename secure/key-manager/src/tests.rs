@@ -79,7 +79,7 @@ impl<T: LibraInterface> Node<T> {
         let clock = self.time.now() * 1_000_000;
 
         let block_id = HashValue::zero();
-        let block_metadata = BlockMetadata::new(block_id, 0, clock, vec![], self.account);
+        let block_metadata = BlockMetadata::new(block_id, 0, clock, vec![], self.account, vec![]);
         let prologue = Transaction::BlockMetadata(block_metadata);
         block.insert(0, prologue);
 
@@ -6,13 +6,14 @@ use libra_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
     hash::HashValue,
 };
-use libra_types::transaction::Transaction;
+use libra_types::{block_info::BlockInfo, transaction::Transaction};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 #[serde(content = "value", rename_all = "snake_case", tag = "type")]
 #[allow(clippy::large_enum_variant)]
 pub enum Value {
+    BlockInfo(BlockInfo),
     Ed25519PrivateKey(Ed25519PrivateKey),
     Ed25519PublicKey(Ed25519PublicKey),
     HashValue(HashValue),
@@ -22,6 +23,14 @@ pub enum Value {
 }
 
 impl Value {
+    pub fn block_info(self) -> Result<BlockInfo, Error> {
+        if let Value::BlockInfo(value) = self {
+            Ok(value)
+        } else {
+            Err(Error::UnexpectedValueType)
+        }
+    }
+
     pub fn ed25519_private_key(self) -> Result<Ed25519PrivateKey, Error> {
         if let Value::Ed25519PrivateKey(value) = self {
             Ok(value)
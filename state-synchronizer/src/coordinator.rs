@@ -26,7 +26,7 @@ use libra_types::{
     transaction::{Transaction, TransactionListWithProof, Version},
     waypoint::Waypoint,
 };
-use network::protocols::network::Event;
+use network::{peer_manager::reputation::Misbehavior, protocols::network::Event, PeerMetadata};
 use std::{
     collections::HashMap,
     time::{Duration, SystemTime, UNIX_EPOCH},
@@ -117,6 +117,7 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
         waypoint: Option<Waypoint>,
         config: StateSyncConfig,
         upstream_config: UpstreamConfig,
+        peer_metadata: PeerMetadata,
         executor_proxy: T,
         initial_state: SynchronizerState,
     ) -> Self {
@@ -134,7 +135,7 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
             role,
             waypoint,
             network_senders,
-            peer_manager: PeerManager::new(upstream_config),
+            peer_manager: PeerManager::new(upstream_config, peer_metadata),
             subscriptions: HashMap::new(),
             sync_request: None,
             initialization_listener: None,
@@ -199,7 +200,12 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
                                 _ => warn!("[state sync] unexpected event: {:?}", event),
                             }
                         },
-                        Err(err) => { error!("[state sync] network error {}", err); },
+                        Err(err) => {
+                            error!("[state sync] network error {}", err);
+                            if let Some(misbehavior) = err.misbehavior() {
+                                self.report_misbehavior(&PeerNetworkId(network_id, misbehavior.peer_id), misbehavior.misbehavior);
+                            }
+                        },
                     }
                 },
                 _ = interval.select_next_some() => {
@@ -664,6 +670,7 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
         .map_err(|e| {
             self.peer_manager
                 .update_score(peer, PeerScoreUpdateType::InvalidChunk);
+            self.report_misbehavior(peer, Misbehavior::InvalidChunk);
             format_err!("[state sync] failed to apply chunk: {}", e)
         })?;
 
@@ -686,6 +693,19 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
             .await
     }
 
+    /// Report a misbehaving peer to the network layer, which bans peers that misbehave
+    /// repeatedly.
+    fn report_misbehavior(&mut self, peer: &PeerNetworkId, misbehavior: Misbehavior) {
+        if let Some(network_sender) = self.network_senders.get_mut(&peer.network_id()) {
+            if let Err(err) = network_sender.report_misbehavior(peer.peer_id(), misbehavior) {
+                error!(
+                    "[state sync] failed to report misbehavior of {:?}: {}",
+                    peer, err
+                );
+            }
+        }
+    }
+
     /// Processing chunk responses that carry a LedgerInfo that should be verified using the
     /// current local trusted validator set.
     fn process_response_with_verifiable_li(
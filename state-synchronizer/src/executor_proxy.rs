@@ -5,15 +5,23 @@ use crate::SynchronizerState;
 use anyhow::{ensure, format_err, Result};
 use executor_types::{ChunkExecutor, ExecutedTrees};
 use itertools::Itertools;
+use libra_logger::prelude::*;
 use libra_types::{
     account_state::AccountState,
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     move_resource::MoveStorage,
-    on_chain_config::{config_address, OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY},
+    on_chain_config::{
+        config_address, ConfigID, OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY,
+        OPTIONAL_ON_CHAIN_CONFIGS,
+    },
     transaction::TransactionListWithProof,
 };
-use std::{collections::HashSet, convert::TryFrom, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    sync::Arc,
+};
 use storage_interface::DbReader;
 use subscription_service::ReconfigSubscription;
 
@@ -87,11 +95,36 @@ impl ExecutorProxy {
     // TODO make this into more general trait method in `on_chain_config`
     // once `StorageRead` trait is replaced with `DbReader` and `batch_fetch_config` method is no longer async
     fn fetch_all_configs(storage: &dyn DbReader) -> Result<OnChainConfigPayload> {
-        let access_paths = ON_CHAIN_CONFIG_REGISTRY
+        let (optional_configs, required_configs): (Vec<ConfigID>, Vec<ConfigID>) =
+            ON_CHAIN_CONFIG_REGISTRY
+                .iter()
+                .copied()
+                .partition(|config_id| OPTIONAL_ON_CHAIN_CONFIGS.contains(config_id));
+        let access_paths = required_configs
             .iter()
             .map(|config_id| config_id.access_path())
             .collect();
-        let configs = storage.batch_fetch_resources(access_paths)?;
+        let mut configs: HashMap<ConfigID, Vec<u8>> = required_configs
+            .into_iter()
+            .zip_eq(storage.batch_fetch_resources(access_paths)?)
+            .collect();
+        for config_id in optional_configs {
+            let access_path = config_id.access_path();
+            let config = storage
+                .get_latest_account_state(access_path.address)?
+                .map(|blob| AccountState::try_from(&blob))
+                .transpose()?
+                .and_then(|state| state.get(&access_path.path).cloned());
+            match config {
+                Some(config) => {
+                    configs.insert(config_id, config);
+                }
+                None => warn!(
+                    "[state sync] On-chain config {:?} is uninitialized",
+                    config_id
+                ),
+            }
+        }
         let epoch = storage
             .get_latest_account_state(config_address())?
             .map(|blob| {
@@ -104,16 +137,7 @@ impl ExecutorProxy {
             })
             .ok_or_else(|| format_err!("Failed to fetch ConfigurationResource"))??;
 
-        Ok(OnChainConfigPayload::new(
-            epoch,
-            Arc::new(
-                ON_CHAIN_CONFIG_REGISTRY
-                    .iter()
-                    .cloned()
-                    .zip_eq(configs)
-                    .collect(),
-            ),
-        ))
+        Ok(OnChainConfigPayload::new(epoch, Arc::new(configs)))
     }
 }
 
@@ -200,14 +224,8 @@ impl ExecutorProxyTrait for ExecutorProxy {
         let changed_configs = new_configs
             .configs()
             .iter()
-            .filter(|(id, cfg)| {
-                &self
-                    .on_chain_configs
-                    .configs()
-                    .get(id)
-                    .expect("missing on-chain config value in local copy")
-                    != cfg
-            })
+            // optional configs may be initialized by this reconfiguration
+            .filter(|(id, cfg)| self.on_chain_configs.configs().get(id) != Some(cfg))
             .map(|(id, _)| *id)
             .collect::<HashSet<_>>();
 
@@ -4,6 +4,7 @@
 use crate::counters;
 use libra_config::config::{PeerNetworkId, UpstreamConfig};
 use libra_logger::prelude::*;
+use network::PeerMetadata;
 use rand::{
     distributions::{Distribution, WeightedIndex},
     thread_rng,
@@ -65,15 +66,18 @@ pub struct PeerManager {
     requests: BTreeMap<u64, ChunkRequestInfo>,
     weighted_index: Option<WeightedIndex<f64>>,
     upstream_config: UpstreamConfig,
+    // round-trip times and ping failures of the peers, measured by the health checkers
+    peer_metadata: PeerMetadata,
 }
 
 impl PeerManager {
-    pub fn new(upstream_config: UpstreamConfig) -> Self {
+    pub fn new(upstream_config: UpstreamConfig, peer_metadata: PeerMetadata) -> Self {
         Self {
             peers: HashMap::new(),
             requests: BTreeMap::new(),
             weighted_index: None,
             upstream_config,
+            peer_metadata,
         }
     }
 
@@ -132,9 +136,10 @@ impl PeerManager {
         counters::ACTIVE_UPSTREAM_PEERS.set(active_peers.len() as i64);
 
         if !active_peers.is_empty() {
+            // prefer the peers with a good score which are also fast and reliable to reach
             let weights: Vec<_> = active_peers
                 .iter()
-                .map(|(_, peer_info)| peer_info.score)
+                .map(|(peer, peer_info)| peer_info.score * self.peer_metadata.preference(peer))
                 .collect();
             match WeightedIndex::new(&weights) {
                 Ok(weighted_index) => {
@@ -150,7 +155,9 @@ impl PeerManager {
         }
     }
 
-    pub fn pick_peer(&self) -> Option<PeerNetworkId> {
+    pub fn pick_peer(&mut self) -> Option<PeerNetworkId> {
+        // the round-trip times change independently of the scores
+        self.compute_weighted_index();
         let active_peers = self.get_active_upstream_peers();
         debug!("[state sync] (pick_peer) state: {:?}", self.peers);
 
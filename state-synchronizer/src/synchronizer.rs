@@ -19,6 +19,7 @@ use libra_types::{
     contract_event::ContractEvent, ledger_info::LedgerInfoWithSignatures, transaction::Transaction,
     waypoint::Waypoint, PeerId,
 };
+use network::PeerMetadata;
 use std::{
     boxed::Box,
     collections::HashMap,
@@ -46,6 +47,7 @@ impl StateSynchronizer {
         executor: Box<dyn ChunkExecutor>,
         config: &NodeConfig,
         reconfig_event_subscriptions: Vec<ReconfigSubscription>,
+        peer_metadata: PeerMetadata,
     ) -> Self {
         let runtime = Builder::new()
             .thread_name("state-sync-")
@@ -63,6 +65,7 @@ impl StateSynchronizer {
             config.base.waypoint,
             &config.state_sync,
             config.upstream.clone(),
+            peer_metadata,
             executor_proxy,
         )
     }
@@ -75,6 +78,7 @@ impl StateSynchronizer {
         waypoint: Option<Waypoint>,
         state_sync_config: &StateSyncConfig,
         upstream_config: UpstreamConfig,
+        peer_metadata: PeerMetadata,
         executor_proxy: E,
     ) -> Self {
         let (coordinator_sender, coordinator_receiver) = mpsc::unbounded();
@@ -96,6 +100,7 @@ impl StateSynchronizer {
             waypoint,
             state_sync_config.clone(),
             upstream_config,
+            peer_metadata,
             executor_proxy,
             initial_state,
         );
@@ -7,7 +7,7 @@ use crate::{
 };
 use anyhow::{bail, Result};
 use executor_types::ExecutedTrees;
-use futures::executor::block_on;
+use futures::{executor::block_on, future::FutureExt};
 use libra_config::config::{PeerNetworkId, RoleType};
 use libra_crypto::{hash::ACCUMULATOR_PLACEHOLDER_HASH, test_utils::TEST_SEED, x25519, Uniform};
 use libra_mempool::mocks::MockSharedMempool;
@@ -19,21 +19,31 @@ use libra_types::{
     validator_info::ValidatorInfo, validator_signer::ValidatorSigner,
     validator_verifier::random_validator_verifier, waypoint::Waypoint,
 };
+use netcore::transport::simulated::{
+    DelayDistribution, LinkConfig, NetworkSimulator, SimulationEvent,
+};
 use network::{
     validator_network::network_builder::{AuthenticationMode, NetworkBuilder},
-    NetworkPublicKeys,
+    NetworkPublicKeys, PeerMetadata,
 };
 use rand::{rngs::StdRng, SeedableRng};
 use std::{
     collections::HashMap,
     convert::TryFrom,
+    future::Future,
     sync::{
         atomic::{AtomicUsize, Ordering},
         Arc, RwLock,
     },
+    time::Duration,
 };
 use tokio::runtime::Runtime;
 
+// The step of the clock of the network simulator, and the real time the nodes get to process the
+// messages delivered at each step.
+const SIMULATOR_STEP: Duration = Duration::from_millis(1);
+const SIMULATOR_STEP_INTERVAL: Duration = Duration::from_millis(1);
+
 type MockRpcHandler = Box<
     dyn Fn(TransactionListWithProof) -> Result<TransactionListWithProof> + Send + Sync + 'static,
 >;
@@ -117,6 +127,8 @@ struct SynchronizerEnv {
     peer_ids: Vec<PeerId>,
     peer_addresses: Vec<NetworkAddress>,
     mempools: Vec<MockSharedMempool>,
+    // connects the peers when set, rather than plain in-memory sockets
+    simulator: Option<NetworkSimulator>,
 }
 
 impl SynchronizerEnv {
@@ -198,6 +210,33 @@ impl SynchronizerEnv {
             peer_ids,
             peer_addresses: vec![],
             mempools: vec![],
+            simulator: None,
+        }
+    }
+
+    // Connects the peers through a network simulator with the given links, whose clock the test
+    // steps while it waits, see `run`.
+    fn new_simulated(num_peers: usize, link: LinkConfig) -> Self {
+        let mut env = Self::new(num_peers);
+        let simulator = NetworkSimulator::new(0);
+        simulator.apply(SimulationEvent::SetDefaultLink(link));
+        env.simulator = Some(simulator);
+        env
+    }
+
+    // Waits for `f`. With a network simulator, its clock is stepped until `f` completes.
+    fn run<F: Future>(&self, f: F) -> F::Output {
+        let simulator = match &self.simulator {
+            Some(simulator) => simulator,
+            None => return block_on(f),
+        };
+        let mut f = Box::pin(f);
+        loop {
+            if let Some(output) = (&mut f).now_or_never() {
+                return output;
+            }
+            simulator.advance(SIMULATOR_STEP);
+            std::thread::sleep(SIMULATOR_STEP_INTERVAL);
         }
     }
 
@@ -252,9 +291,12 @@ impl SynchronizerEnv {
             .seed_peers(seed_peers)
             .add_connectivity_manager()
             .add_gossip_discovery();
+        if let Some(simulator) = &self.simulator {
+            network_builder.simulated_transport(simulator.add_node());
+        }
 
         let (sender, events) = crate::network::add_to_network(&mut network_builder);
-        let peer_addr = network_builder.build();
+        let peer_addr = network_builder.build().unwrap();
 
         let mut config = config_builder::test_config().0;
         let mut network = config.validator_network.unwrap();
@@ -287,6 +329,7 @@ impl SynchronizerEnv {
             waypoint,
             &config.state_sync,
             config.upstream,
+            PeerMetadata::new(),
             MockExecutorProxy::new(handler, storage_proxy.clone()),
         );
         self.mempools
@@ -303,7 +346,7 @@ impl SynchronizerEnv {
     }
 
     fn sync_to(&self, peer_id: usize, target: LedgerInfoWithSignatures) {
-        block_on(self.clients[peer_id].sync_to(target)).unwrap()
+        self.run(self.clients[peer_id].sync_to(target)).unwrap()
     }
 
     // commit new txns up to the given version
@@ -392,6 +435,39 @@ fn test_basic_catch_up() {
     assert_eq!(env.latest_li(1).ledger_info().version(), 2000);
 }
 
+#[test]
+fn test_catch_up_over_lossy_network() {
+    let mut env = SynchronizerEnv::new_simulated(
+        2,
+        LinkConfig {
+            delay: DelayDistribution::Uniform {
+                min: Duration::from_millis(1),
+                max: Duration::from_millis(50),
+            },
+            drop_probability: 0.1,
+            duplicate_probability: 0.0,
+        },
+    );
+    // lost chunk requests and responses are retried after the timeout
+    for _ in 0..2 {
+        env.setup_next_synchronizer(
+            SynchronizerEnv::default_handler(),
+            RoleType::Validator,
+            None,
+            1_000,
+        );
+    }
+
+    for version in 1..5 {
+        env.commit(0, version);
+        env.sync_to(1, env.latest_li(0));
+        assert_eq!(env.latest_li(1).ledger_info().version(), version);
+    }
+    env.commit(0, 2000);
+    env.sync_to(1, env.latest_li(0));
+    assert_eq!(env.latest_li(1).ledger_info().version(), 2000);
+}
+
 #[test]
 fn test_flaky_peer_sync() {
     // create handler that causes error, but has successful retries
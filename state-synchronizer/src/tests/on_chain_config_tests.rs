@@ -16,18 +16,20 @@ use libra_crypto::{
 };
 use libra_types::{
     account_config::{association_address, lbr_type_tag},
-    on_chain_config::{OnChainConfig, VMConfig, VMPublishingOption},
+    on_chain_config::{OnChainConfig, OnChainConsensusConfig, VMConfig, VMPublishingOption},
     transaction::authenticator::AuthenticationKey,
 };
 use libra_vm::LibraVM;
 use libradb::LibraDB;
-use stdlib::transaction_scripts::StdlibScript;
+use stdlib::{stdlib_modules, transaction_scripts::StdlibScript, StdLibOptions};
 use storage_interface::DbReaderWriter;
 use subscription_service::ReconfigSubscription;
 use transaction_builder::{
     encode_block_prologue_script, encode_publishing_option_script,
     encode_rotate_consensus_pubkey_script, encode_transfer_with_metadata_script,
 };
+use vm::access::ModuleAccess;
+use vm_genesis::{encode_genesis_transaction, validator_registrations};
 
 // TODO test for subscription with multiple subscribed configs once there are >1 on-chain configs
 #[test]
@@ -207,3 +209,73 @@ fn test_on_chain_config_pub_sub() {
         "did not expect reconfig update"
     );
 }
+
+#[test]
+fn test_consensus_config_missing_from_old_genesis() {
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let subscribed_configs = &[VMConfig::CONFIG_ID, OnChainConsensusConfig::CONFIG_ID];
+    let (subscription, mut reconfig_receiver) = ReconfigSubscription::subscribe(subscribed_configs);
+
+    // genesis with a standard library from before LibraConsensusConfig
+    let (config, genesis_key) = config_builder::test_config();
+    let old_stdlib: Vec<_> = stdlib_modules(StdLibOptions::Fresh)
+        .iter()
+        .filter(|module| module.self_id().name().as_str() != "LibraConsensusConfig")
+        .cloned()
+        .collect();
+    let genesis = encode_genesis_transaction(
+        genesis_key.public_key(),
+        &validator_registrations(std::slice::from_ref(&config)),
+        &old_stdlib,
+        VMPublishingOption::Open,
+    );
+    let (db, db_rw) = DbReaderWriter::wrap(LibraDB::new_for_test(&config.storage.dir()));
+    bootstrap_db_if_empty::<LibraVM>(&db_rw, &genesis).unwrap();
+
+    let mut block_executor = Box::new(Executor::<LibraVM>::new(db_rw.clone()));
+    let chunk_executor = Box::new(Executor::<LibraVM>::new(db_rw));
+    let mut executor_proxy = ExecutorProxy::new(db, chunk_executor, vec![subscription]);
+
+    let payload = reconfig_receiver
+        .select_next_some()
+        .now_or_never()
+        .expect("expect initial config notification");
+    assert!(payload.get::<VMConfig>().is_ok());
+    assert!(payload.get::<OnChainConsensusConfig>().is_err());
+
+    // later reconfigurations are still published
+    let validator_account = config.validator_network.as_ref().unwrap().peer_id;
+    let txn1 = encode_block_prologue_script(gen_block_metadata(1, validator_account));
+    let vm_publishing_option = VMPublishingOption::Locked(StdlibScript::whitelist());
+    let txn2 = get_test_signed_transaction(
+        association_address(),
+        /* sequence_number = */ 1,
+        genesis_key.clone(),
+        genesis_key.public_key(),
+        Some(encode_publishing_option_script(
+            vm_publishing_option.clone(),
+        )),
+    );
+    let block_id = gen_block_id(1);
+    let output = block_executor
+        .execute_block(
+            (block_id, vec![txn1, txn2]),
+            block_executor.committed_block_id(),
+        )
+        .expect("failed to execute block");
+    let ledger_info_with_sigs = gen_ledger_info_with_sigs(1, output, block_id, vec![]);
+    let (_, reconfig_events) = block_executor
+        .commit_blocks(vec![block_id], ledger_info_with_sigs)
+        .unwrap();
+    executor_proxy
+        .publish_on_chain_config_updates(reconfig_events)
+        .expect("failed to publish on-chain configs");
+
+    let receive_reconfig = async {
+        let payload = reconfig_receiver.select_next_some().await;
+        let received_config = payload.get::<VMConfig>().unwrap();
+        assert_eq!(received_config.publishing_option, vm_publishing_option);
+        assert!(payload.get::<OnChainConsensusConfig>().is_err());
+    };
+    rt.block_on(receive_reconfig);
+}
@@ -3,7 +3,8 @@
 
 use crate::peer_manager::{PeerManager, PeerScoreUpdateType};
 use libra_config::config::{PeerNetworkId, UpstreamConfig};
-use std::collections::HashMap;
+use network::PeerMetadata;
+use std::{collections::HashMap, time::Duration};
 
 #[test]
 fn test_peer_manager() {
@@ -15,7 +16,7 @@ fn test_peer_manager() {
     ];
     let mut upstream_config = UpstreamConfig::default();
     upstream_config.upstream_peers = peers.iter().cloned().collect();
-    let mut peer_manager = PeerManager::new(upstream_config);
+    let mut peer_manager = PeerManager::new(upstream_config, PeerMetadata::new());
     for peer_id in peers.clone() {
         peer_manager.enable_peer(peer_id);
     }
@@ -38,12 +39,33 @@ fn test_peer_manager() {
     assert!(pick_counts.get(&peers[0]).unwrap_or(&0) < pick_counts.get(&peers[3]).unwrap());
 }
 
+#[test]
+fn test_peer_manager_prefers_fast_peers() {
+    let peers = vec![PeerNetworkId::random(), PeerNetworkId::random()];
+    let mut upstream_config = UpstreamConfig::default();
+    upstream_config.upstream_peers = peers.iter().cloned().collect();
+    let peer_metadata = PeerMetadata::new();
+    peer_metadata.record_ping_success(peers[0], Duration::from_millis(2000));
+    peer_metadata.record_ping_success(peers[1], Duration::from_millis(5));
+    let mut peer_manager = PeerManager::new(upstream_config, peer_metadata);
+    for peer in peers.iter() {
+        peer_manager.enable_peer(*peer);
+    }
+
+    let mut pick_counts = HashMap::new();
+    for _ in 0..1000 {
+        let picked_peer_id = peer_manager.pick_peer().unwrap();
+        *pick_counts.entry(picked_peer_id).or_insert(0) += 1;
+    }
+    assert!(pick_counts.get(&peers[0]).unwrap_or(&0) < pick_counts.get(&peers[1]).unwrap());
+}
+
 #[test]
 fn test_remove_requests() {
     let peers = vec![PeerNetworkId::random(), PeerNetworkId::random()];
     let mut upstream_config = UpstreamConfig::default();
     upstream_config.upstream_peers = peers.iter().cloned().collect();
-    let mut peer_manager = PeerManager::new(upstream_config);
+    let mut peer_manager = PeerManager::new(upstream_config, PeerMetadata::new());
     for peer in peers.iter() {
         peer_manager.enable_peer(*peer);
     }
@@ -68,7 +90,7 @@ fn test_peer_manager_request_metadata() {
     let peers = vec![PeerNetworkId::random(), PeerNetworkId::random()];
     let mut upstream_config = UpstreamConfig::default();
     upstream_config.upstream_peers = peers.iter().cloned().collect();
-    let mut peer_manager = PeerManager::new(upstream_config);
+    let mut peer_manager = PeerManager::new(upstream_config, PeerMetadata::new());
     for peer in peers.iter() {
         peer_manager.enable_peer(*peer);
     }
@@ -148,6 +148,10 @@ impl Experiment for PerformanceBenchmark {
                     .expect("No trace node found");
             info!("Tracing {}", node);
             debug_interface::libra_trace::trace_node(&events[..], &node);
+            info!(
+                "Consensus round latency breakdown:\n{}",
+                debug_interface::round_latency::consensus_round_latencies(&events[..])
+            );
         }
         let end = unix_timestamp_now() - buffer;
         let start = end - window + 2 * buffer;
@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use cli::client_proxy::ClientProxy;
-use debug_interface::{libra_trace, NodeDebugClient};
-use libra_config::config::{NodeConfig, OnDiskStorageConfig, RoleType, SecureBackend, TestConfig};
+use debug_interface::{libra_trace, round_latency, NodeDebugClient};
+use libra_config::config::{
+    NodeConfig, OnDiskStorageConfig, RoleType, SafetyRulesService, SecureBackend, TestConfig,
+};
 use libra_crypto::{
     ed25519::Ed25519PrivateKey, hash::CryptoHash, traits::ValidCryptoMaterialStringExt, PrivateKey,
     SigningKey, Uniform,
@@ -47,12 +49,20 @@ struct TestEnvironment {
 
 impl TestEnvironment {
     fn new(num_validators: usize) -> Self {
+        Self::new_with_template(num_validators, |_| ())
+    }
+
+    fn new_with_template(
+        num_validators: usize,
+        update_template: impl FnOnce(&mut NodeConfig),
+    ) -> Self {
         ::libra_logger::Logger::new().init();
         let mut template = NodeConfig::default();
         template.test = Some(TestConfig::open_module());
         template.state_sync.chunk_limit = 2;
         template.consensus.safety_rules.backend =
             SecureBackend::OnDiskStorage(OnDiskStorageConfig::default());
+        update_template(&mut template);
 
         let validator_swarm = LibraSwarm::configure_swarm(
             num_validators,
@@ -434,6 +444,14 @@ fn test_trace() {
     let txn_node = format!("txn::{}::{}", association_address(), 1);
     println!("Tracing {}", txn_node);
     libra_trace::trace_node(&events[..], &txn_node);
+    let report = round_latency::consensus_round_latencies(&events[..]);
+    println!("{}", report);
+    let execution = report
+        .0
+        .iter()
+        .find(|latency| latency.phase == "execution")
+        .expect("execution phase is reported");
+    assert!(execution.count() > 0);
 }
 
 #[test]
@@ -446,6 +464,69 @@ fn test_basic_fault_tolerance() {
     test_smoke_script(client_proxy);
 }
 
+#[test]
+fn test_decoupled_execution() {
+    let mut env = TestEnvironment::new_with_template(4, |template| {
+        template.consensus.decoupled_execution = true;
+        template.consensus.safety_rules.service = SafetyRulesService::Local;
+    });
+    env.launch_swarm(RoleType::Validator);
+    // the execution pipeline tolerates a single node failure as well
+    env.validator_swarm.kill_node(0);
+    let client_proxy = env.get_validator_ac_client(1, None);
+    test_smoke_script(client_proxy);
+}
+
+#[test]
+fn test_decoupled_execution_restartability() {
+    let mut env = TestEnvironment::new_with_template(4, |template| {
+        template.consensus.decoupled_execution = true;
+        template.consensus.safety_rules.service = SafetyRulesService::Local;
+    });
+    env.launch_swarm(RoleType::Validator);
+    let mut client_proxy = env.get_validator_ac_client(1, None);
+    client_proxy.create_next_account(false).unwrap();
+    client_proxy.create_next_account(false).unwrap();
+    client_proxy
+        .mint_coins(&["mb", "0", "100", "LBR"], true)
+        .unwrap();
+    // kill the validator while the blocks ordered before are still being executed
+    for _ in 0..5 {
+        client_proxy
+            .transfer_coins(&["t", "0", "1", "1", "LBR"], false)
+            .unwrap();
+    }
+    let peer_to_restart = 0;
+    env.validator_swarm.kill_node(peer_to_restart);
+    assert!(env
+        .validator_swarm
+        .add_node(peer_to_restart, RoleType::Validator, false)
+        .is_ok());
+    client_proxy
+        .transfer_coins(&["tb", "0", "1", "5", "LBR"], true)
+        .unwrap();
+    // the restarted validator is needed for the quorum, it recovered and executes the blocks
+    env.validator_swarm.kill_node(2);
+    client_proxy
+        .transfer_coins(&["tb", "0", "1", "10", "LBR"], true)
+        .unwrap();
+    let accounts = client_proxy.copy_all_accounts();
+    let mut restarted_client = env.get_validator_ac_client(peer_to_restart, None);
+    let sender_address = accounts[0].address;
+    restarted_client.set_accounts(accounts);
+    restarted_client
+        .wait_for_transaction(sender_address, 7)
+        .unwrap();
+    assert!(compare_balances(
+        vec![(80.0, "LBR".to_string())],
+        restarted_client.get_balances(&["b", "0"]).unwrap()
+    ));
+    assert!(compare_balances(
+        vec![(20.0, "LBR".to_string())],
+        restarted_client.get_balances(&["b", "1"]).unwrap()
+    ));
+}
+
 #[test]
 fn test_basic_restartability() {
     let (mut env, mut client_proxy) = setup_swarm_and_client_proxy(4, 0);
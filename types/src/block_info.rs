@@ -2,9 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{epoch_state::EpochState, on_chain_config::ValidatorSet, transaction::Version};
-use libra_crypto::hash::HashValue;
-#[cfg(any(test, feature = "fuzzing"))]
-use libra_crypto::hash::ACCUMULATOR_PLACEHOLDER_HASH;
+use libra_crypto::hash::{HashValue, ACCUMULATOR_PLACEHOLDER_HASH};
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
@@ -20,6 +18,10 @@ pub const GENESIS_ROUND: Round = 0;
 pub const GENESIS_VERSION: Version = 0;
 pub const GENESIS_TIMESTAMP_USECS: u64 = 0;
 
+/// The version of blocks that are ordered by consensus but not executed yet (decoupled execution).
+/// Their executed state id is `ACCUMULATOR_PLACEHOLDER_HASH`, which no executed ledger has.
+pub const ORDERED_ONLY_VERSION: Version = 0;
+
 /// This structure contains all the information needed for tracking a block
 /// without having access to the block or its execution output state. It
 /// assumes that the block is the last block executed within the ledger.
@@ -121,6 +123,19 @@ impl BlockInfo {
         Self::genesis(*ACCUMULATOR_PLACEHOLDER_HASH, validator_set)
     }
 
+    /// The executed state id of blocks that are only ordered, see `ORDERED_ONLY_VERSION`.
+    pub fn ordered_only_state_id() -> HashValue {
+        *ACCUMULATOR_PLACEHOLDER_HASH
+    }
+
+    /// True if this block was ordered by consensus without being executed, so that its executed
+    /// state id and version carry no information.
+    pub fn is_ordered_only(&self) -> bool {
+        self.version == ORDERED_ONLY_VERSION
+            && self.executed_state_id == Self::ordered_only_state_id()
+            && self.next_epoch_state.is_none()
+    }
+
     /// The epoch after this block committed
     pub fn next_block_epoch(&self) -> u64 {
         self.next_epoch_state().map_or(self.epoch(), |e| e.epoch)
@@ -10,7 +10,7 @@ use anyhow::Result;
 use libra_crypto::HashValue;
 use move_core_types::move_resource::MoveResource;
 use once_cell::sync::Lazy;
-use serde::{Deserialize, Serialize};
+use serde::{ser, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Struct that will be persisted on chain to store the information of the current block.
 ///
@@ -23,7 +23,11 @@ use serde::{Deserialize, Serialize};
 /// 3. Once that special resource is modified, the other user transactions can read the consensus
 ///    info by calling into the read method of that resource, which would thus give users the
 ///    information such as the current leader.
+///
+/// The blocks without failed proposer are committed as `Transaction::BlockMetadata`, in the
+/// layout from before the failed proposers got recorded, see `legacy_layout`.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "BlockMetadataWithFailedProposers")]
 pub struct BlockMetadata {
     id: HashValue,
     round: u64,
@@ -31,6 +35,8 @@ pub struct BlockMetadata {
     // The vector has to be sorted to ensure consistent result among all nodes
     previous_block_votes: Vec<AccountAddress>,
     proposer: AccountAddress,
+    // The expected proposers of the rounds skipped before this block, in increasing rounds
+    failed_proposers: Vec<AccountAddress>,
 }
 
 impl BlockMetadata {
@@ -40,6 +46,7 @@ impl BlockMetadata {
         timestamp_usecs: u64,
         previous_block_votes: Vec<AccountAddress>,
         proposer: AccountAddress,
+        failed_proposers: Vec<AccountAddress>,
     ) -> Self {
         Self {
             id,
@@ -47,6 +54,7 @@ impl BlockMetadata {
             timestamp_usecs,
             previous_block_votes,
             proposer,
+            failed_proposers,
         }
     }
 
@@ -54,12 +62,22 @@ impl BlockMetadata {
         self.id
     }
 
-    pub fn into_inner(self) -> Result<(u64, u64, Vec<AccountAddress>, AccountAddress)> {
+    #[allow(clippy::type_complexity)]
+    pub fn into_inner(
+        self,
+    ) -> Result<(
+        u64,
+        u64,
+        Vec<AccountAddress>,
+        AccountAddress,
+        Vec<AccountAddress>,
+    )> {
         Ok((
             self.round,
             self.timestamp_usecs,
             self.previous_block_votes.clone(),
             self.proposer,
+            self.failed_proposers,
         ))
     }
 
@@ -70,6 +88,60 @@ impl BlockMetadata {
     pub fn voters(&self) -> Vec<AccountAddress> {
         self.previous_block_votes.clone()
     }
+
+    pub fn failed_proposers(&self) -> &[AccountAddress] {
+        &self.failed_proposers
+    }
+}
+
+/// The layout of the block metadata transactions from before the failed proposers got recorded.
+#[derive(Deserialize, Serialize)]
+#[serde(rename = "BlockMetadata")]
+struct LegacyBlockMetadata {
+    id: HashValue,
+    round: u64,
+    timestamp_usecs: u64,
+    previous_block_votes: Vec<AccountAddress>,
+    proposer: AccountAddress,
+}
+
+/// (De)serializes the block metadata of `Transaction::BlockMetadata`, which keeps the layout and
+/// the hash of the transactions committed before the failed proposers got recorded.
+pub(crate) mod legacy_layout {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        metadata: &BlockMetadata,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        if !metadata.failed_proposers.is_empty() {
+            return Err(ser::Error::custom(
+                "The legacy block metadata layout can't record failed proposers",
+            ));
+        }
+        LegacyBlockMetadata {
+            id: metadata.id,
+            round: metadata.round,
+            timestamp_usecs: metadata.timestamp_usecs,
+            previous_block_votes: metadata.previous_block_votes.clone(),
+            proposer: metadata.proposer,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<BlockMetadata, D::Error> {
+        let legacy = LegacyBlockMetadata::deserialize(deserializer)?;
+        Ok(BlockMetadata::new(
+            legacy.id,
+            legacy.round,
+            legacy.timestamp_usecs,
+            legacy.previous_block_votes,
+            legacy.proposer,
+            vec![],
+        ))
+    }
 }
 
 pub fn new_block_event_key() -> EventKey {
@@ -107,6 +179,16 @@ pub struct NewBlockEvent {
     proposer: AccountAddress,
     votes: Vec<AccountAddress>,
     timestamp: u64,
+    failed_proposers: Vec<AccountAddress>,
+}
+
+/// The layout of the events emitted before the failed proposers got recorded.
+#[derive(Deserialize)]
+struct LegacyNewBlockEvent {
+    round: u64,
+    proposer: AccountAddress,
+    votes: Vec<AccountAddress>,
+    timestamp: u64,
 }
 
 impl NewBlockEvent {
@@ -115,14 +197,32 @@ impl NewBlockEvent {
         proposer: AccountAddress,
         votes: Vec<AccountAddress>,
         timestamp: u64,
+        failed_proposers: Vec<AccountAddress>,
     ) -> Self {
         Self {
             round,
             proposer,
             votes,
             timestamp,
+            failed_proposers,
         }
     }
+
+    /// Decode the event data, the events emitted by an older LibraBlock module don't record
+    /// any failed proposer.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self> {
+        lcs::from_bytes(bytes).or_else(|_| {
+            let legacy = lcs::from_bytes::<LegacyNewBlockEvent>(bytes)?;
+            Ok(Self::new(
+                legacy.round,
+                legacy.proposer,
+                legacy.votes,
+                legacy.timestamp,
+                vec![],
+            ))
+        })
+    }
+
     pub fn round(&self) -> u64 {
         self.round
     }
@@ -134,4 +234,8 @@ impl NewBlockEvent {
     pub fn votes(&self) -> Vec<AccountAddress> {
         self.votes.clone()
     }
+
+    pub fn failed_proposers(&self) -> &[AccountAddress] {
+        &self.failed_proposers
+    }
 }
@@ -0,0 +1,242 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::on_chain_config::OnChainConfig;
+use anyhow::{ensure, format_err, Result};
+use serde::{Deserialize, Serialize};
+
+/// The on-chain consensus config. It is stored on chain as an LCS encoded blob (see
+/// `LibraConsensusConfig`) and wrapped in an enum so that new versions can be introduced
+/// without breaking validators running the old ones.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum OnChainConsensusConfig {
+    V1(ConsensusConfigV1),
+}
+
+impl OnChainConsensusConfig {
+    pub fn proposer_election_type(&self) -> &ProposerElectionType {
+        match self {
+            OnChainConsensusConfig::V1(config) => &config.proposer_election_type,
+        }
+    }
+
+    /// Reject configs consensus can't run with. `LibraConsensusConfig` applies the same checks
+    /// when the config is published.
+    pub fn validate(&self) -> Result<()> {
+        self.proposer_election_type().validate()
+    }
+}
+
+/// The config consensus uses when there is none on chain.
+impl Default for OnChainConsensusConfig {
+    fn default() -> Self {
+        OnChainConsensusConfig::V1(ConsensusConfigV1::default())
+    }
+}
+
+impl OnChainConfig for OnChainConsensusConfig {
+    const IDENTIFIER: &'static str = "LibraConsensusConfig";
+
+    // The Move resource only holds the serialized bytes of the config, so a second round of
+    // deserialization is needed to get to `Self`
+    fn deserialize_into_config(bytes: &[u8]) -> Result<Self> {
+        let raw_bytes: Vec<u8> = lcs::from_bytes(bytes).map_err(|e| {
+            format_err!(
+                "Failed first round of deserialization for OnChainConsensusConfig: {}",
+                e
+            )
+        })?;
+        let config: Self = lcs::from_bytes(&raw_bytes).map_err(|e| {
+            format_err!(
+                "Failed second round of deserialization for OnChainConsensusConfig: {}",
+                e
+            )
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ConsensusConfigV1 {
+    pub proposer_election_type: ProposerElectionType,
+}
+
+impl Default for ConsensusConfigV1 {
+    fn default() -> Self {
+        Self {
+            proposer_election_type: ProposerElectionType::LeaderReputation(
+                LeaderReputationConfig {
+                    active_weights: 99,
+                    inactive_weights: 1,
+                    window_num_validators_multiplier: 1,
+                },
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ProposerElectionType {
+    /// Choose the smallest PeerId as the proposer, for the given number of contiguous rounds
+    FixedProposer(u32),
+    /// Round robin rotation of proposers, each one active for the given number of contiguous
+    /// rounds
+    RotatingProposer(u32),
+    /// Committed history based proposer election
+    LeaderReputation(LeaderReputationConfig),
+    /// Committed history based proposer election that also down-weights the proposers of
+    /// recently failed rounds
+    FailureAwareLeaderReputation(FailureAwareLeaderReputationConfig),
+}
+
+/// The largest weight of a proposer election candidate, the sum of the weights of all the
+/// candidates of an epoch must fit in a u64.
+pub const MAX_PROPOSER_WEIGHT: u64 = u32::MAX as u64;
+
+impl ProposerElectionType {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ProposerElectionType::FixedProposer(contiguous_rounds)
+            | ProposerElectionType::RotatingProposer(contiguous_rounds) => {
+                ensure!(
+                    *contiguous_rounds > 0,
+                    "Proposers must be active for at least one round"
+                );
+            }
+            ProposerElectionType::LeaderReputation(config) => config.validate()?,
+            ProposerElectionType::FailureAwareLeaderReputation(config) => {
+                config.leader_reputation.validate()?;
+                ensure!(
+                    config.failed_weights <= MAX_PROPOSER_WEIGHT,
+                    "Failed weights {} exceed {}",
+                    config.failed_weights,
+                    MAX_PROPOSER_WEIGHT
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LeaderReputationConfig {
+    /// Weight of a candidate that proposed or voted within the history window
+    pub active_weights: u64,
+    /// Weight of a candidate that did not show up within the history window
+    pub inactive_weights: u64,
+    /// Size of the history window, as a multiple of the number of validators in the epoch
+    pub window_num_validators_multiplier: u64,
+}
+
+impl LeaderReputationConfig {
+    pub fn window_size(&self, num_validators: usize) -> usize {
+        num_validators
+            .saturating_mul(std::cmp::max(self.window_num_validators_multiplier, 1) as usize)
+    }
+
+    fn validate(&self) -> Result<()> {
+        // A zero weight for inactive candidates leaves nobody to choose from when the history is
+        // empty, e.g. at the start of an epoch
+        for (name, weights) in &[
+            ("Active", self.active_weights),
+            ("Inactive", self.inactive_weights),
+        ] {
+            ensure!(
+                *weights > 0 && *weights <= MAX_PROPOSER_WEIGHT,
+                "{} weights {} must be within [1, {}]",
+                name,
+                weights,
+                MAX_PROPOSER_WEIGHT
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FailureAwareLeaderReputationConfig {
+    pub leader_reputation: LeaderReputationConfig,
+    /// Weight of a candidate that was the expected proposer of a failed round within the
+    /// history window, it takes precedence over the active weight
+    pub failed_weights: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consensus_config_round_trip() {
+        let config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
+            proposer_election_type: ProposerElectionType::RotatingProposer(3),
+        });
+        let inner = lcs::to_bytes(&config).unwrap();
+        let outer = lcs::to_bytes(&inner).unwrap();
+        assert_eq!(
+            OnChainConsensusConfig::deserialize_into_config(&outer).unwrap(),
+            config
+        );
+        assert!(OnChainConsensusConfig::deserialize_into_config(&inner).is_err());
+    }
+
+    fn encode(proposer_election_type: ProposerElectionType) -> Vec<u8> {
+        let config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
+            proposer_election_type,
+        });
+        lcs::to_bytes(&lcs::to_bytes(&config).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_invalid_consensus_config() {
+        let leader_reputation = |active_weights, inactive_weights| LeaderReputationConfig {
+            active_weights,
+            inactive_weights,
+            window_num_validators_multiplier: 1,
+        };
+        let invalid = vec![
+            ProposerElectionType::FixedProposer(0),
+            ProposerElectionType::RotatingProposer(0),
+            ProposerElectionType::LeaderReputation(leader_reputation(0, 0)),
+            ProposerElectionType::LeaderReputation(leader_reputation(99, 0)),
+            ProposerElectionType::LeaderReputation(leader_reputation(u64::MAX, 1)),
+            ProposerElectionType::FailureAwareLeaderReputation(
+                FailureAwareLeaderReputationConfig {
+                    leader_reputation: leader_reputation(99, 1),
+                    failed_weights: MAX_PROPOSER_WEIGHT + 1,
+                },
+            ),
+        ];
+        for proposer_election_type in invalid {
+            assert!(
+                OnChainConsensusConfig::deserialize_into_config(&encode(
+                    proposer_election_type.clone()
+                ))
+                .is_err(),
+                "{:?} should be rejected",
+                proposer_election_type
+            );
+        }
+
+        let valid = vec![
+            ProposerElectionType::RotatingProposer(1),
+            ProposerElectionType::LeaderReputation(leader_reputation(
+                MAX_PROPOSER_WEIGHT,
+                MAX_PROPOSER_WEIGHT,
+            )),
+            ProposerElectionType::FailureAwareLeaderReputation(
+                FailureAwareLeaderReputationConfig {
+                    leader_reputation: leader_reputation(99, 1),
+                    failed_weights: 0,
+                },
+            ),
+        ];
+        for proposer_election_type in valid {
+            assert!(OnChainConsensusConfig::deserialize_into_config(&encode(
+                proposer_election_type
+            ))
+            .is_ok());
+        }
+        assert!(OnChainConsensusConfig::default().validate().is_ok());
+    }
+}
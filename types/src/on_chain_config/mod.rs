@@ -16,12 +16,17 @@ use move_core_types::{
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 
+mod consensus_config;
 mod libra_version;
 mod registered_currencies;
 mod validator_set;
 mod vm_config;
 
 pub use self::{
+    consensus_config::{
+        ConsensusConfigV1, FailureAwareLeaderReputationConfig, LeaderReputationConfig,
+        OnChainConsensusConfig, ProposerElectionType,
+    },
     libra_version::LibraVersion,
     registered_currencies::RegisteredCurrencies,
     validator_set::ValidatorSet,
@@ -48,14 +53,20 @@ impl ConfigID {
     }
 }
 
-/// State sync will panic if the value of any config in this registry is uninitialized
+/// State sync will panic if the value of any config in this registry is uninitialized, except for
+/// the `OPTIONAL_ON_CHAIN_CONFIGS`
 pub const ON_CHAIN_CONFIG_REGISTRY: &[ConfigID] = &[
     VMConfig::CONFIG_ID,
     LibraVersion::CONFIG_ID,
     ValidatorSet::CONFIG_ID,
     RegisteredCurrencies::CONFIG_ID,
+    OnChainConsensusConfig::CONFIG_ID,
 ];
 
+/// Configs which networks started from an older genesis don't have. They are left out of the
+/// `OnChainConfigPayload` when uninitialized, and their subscribers fall back to a default.
+pub const OPTIONAL_ON_CHAIN_CONFIGS: &[ConfigID] = &[OnChainConsensusConfig::CONFIG_ID];
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct OnChainConfigPayload {
     epoch: u64,
@@ -930,31 +930,47 @@ impl Arbitrary for TransactionListWithProof {
     type Strategy = BoxedStrategy<Self>;
 }
 
-impl Arbitrary for BlockMetadata {
-    type Parameters = SizeRange;
-    fn arbitrary_with(num_validators_range: Self::Parameters) -> Self::Strategy {
-        let signature_strategy = (any::<HashValue>(), Just(num_validators_range)).prop_flat_map(
-            |(hash, num_validators_range)| {
-                prop::collection::vec(arb_validator_signature_for_hash(hash), num_validators_range)
-            },
-        );
-        (
-            any::<HashValue>(),
-            any::<u64>(),
-            any::<u64>(),
-            signature_strategy,
-            any::<AccountAddress>(),
-        )
-            .prop_map(|(id, round, timestamp, signatures, proposer)| {
+fn arb_block_metadata(
+    num_validators_range: SizeRange,
+    num_failed_proposers_range: SizeRange,
+) -> impl Strategy<Value = BlockMetadata> {
+    let signature_strategy = (any::<HashValue>(), Just(num_validators_range)).prop_flat_map(
+        |(hash, num_validators_range)| {
+            prop::collection::vec(arb_validator_signature_for_hash(hash), num_validators_range)
+        },
+    );
+    (
+        any::<HashValue>(),
+        any::<u64>(),
+        any::<u64>(),
+        signature_strategy,
+        any::<AccountAddress>(),
+        vec(any::<AccountAddress>(), num_failed_proposers_range),
+    )
+        .prop_map(
+            |(id, round, timestamp, signatures, proposer, failed_proposers)| {
                 BlockMetadata::new(
                     id,
                     round,
                     timestamp,
                     signatures.into_iter().map(|(addr, _)| addr).collect(),
                     proposer,
+                    failed_proposers,
                 )
-            })
-            .boxed()
+            },
+        )
+}
+
+/// `Transaction::BlockMetadata` can't record failed proposers.
+pub fn arb_legacy_block_metadata_transaction() -> impl Strategy<Value = Transaction> {
+    arb_block_metadata(SizeRange::default(), SizeRange::from(0..=0))
+        .prop_map(Transaction::BlockMetadata)
+}
+
+impl Arbitrary for BlockMetadata {
+    type Parameters = SizeRange;
+    fn arbitrary_with(num_validators_range: Self::Parameters) -> Self::Strategy {
+        arb_block_metadata(num_validators_range, SizeRange::from(0..3)).boxed()
     }
 
     type Strategy = BoxedStrategy<Self>;
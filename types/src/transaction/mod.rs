@@ -922,11 +922,31 @@ pub enum Transaction {
     /// genesis right now.
     WaypointWriteSet(ChangeSet),
 
-    /// Transaction to update the block metadata resource at the beginning of a block.
+    /// Transaction to update the block metadata resource at the beginning of a block, used when
+    /// no proposer failed since the previous block.
+    #[serde(with = "crate::block_metadata::legacy_layout")]
+    #[cfg_attr(
+        any(test, feature = "fuzzing"),
+        proptest(strategy = "crate::proptest_types::arb_legacy_block_metadata_transaction()")
+    )]
     BlockMetadata(BlockMetadata),
+
+    /// Transaction to update the block metadata resource at the beginning of a block, recording
+    /// the proposers that failed since the previous block.
+    BlockMetadataWithFailedProposers(BlockMetadata),
 }
 
 impl Transaction {
+    /// The transaction updating the block metadata resource, which keeps the layout of the
+    /// transactions committed before the failed proposers got recorded when none failed.
+    pub fn block_metadata(block_metadata: BlockMetadata) -> Self {
+        if block_metadata.failed_proposers().is_empty() {
+            Transaction::BlockMetadata(block_metadata)
+        } else {
+            Transaction::BlockMetadataWithFailedProposers(block_metadata)
+        }
+    }
+
     pub fn as_signed_user_txn(&self) -> Result<&SignedTransaction> {
         match self {
             Transaction::UserTransaction(txn) => Ok(txn),
@@ -942,7 +962,10 @@ impl Transaction {
             // TODO: display proper information for client
             Transaction::WaypointWriteSet(_write_set) => String::from("genesis"),
             // TODO: display proper information for client
-            Transaction::BlockMetadata(_block_metadata) => String::from("block_metadata"),
+            Transaction::BlockMetadata(_block_metadata)
+            | Transaction::BlockMetadataWithFailedProposers(_block_metadata) => {
+                String::from("block_metadata")
+            }
         }
     }
 }
@@ -1,9 +1,15 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::block_metadata::BlockMetadata;
+use crate::{
+    account_address::AccountAddress,
+    block_metadata::BlockMetadata,
+    transaction::{ChangeSet, SignedTransaction, Transaction},
+};
 use lcs::test_helpers::assert_canonical_encode_decode;
+use libra_crypto::HashValue;
 use proptest::prelude::*;
+use serde::Serialize;
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(20))]
@@ -13,3 +19,54 @@ proptest! {
         assert_canonical_encode_decode(data);
     }
 }
+
+/// The layout of the block metadata before the failed proposers got recorded.
+#[derive(Serialize)]
+struct OldBlockMetadata {
+    id: HashValue,
+    round: u64,
+    timestamp_usecs: u64,
+    previous_block_votes: Vec<AccountAddress>,
+    proposer: AccountAddress,
+}
+
+#[allow(dead_code)]
+#[derive(Serialize)]
+enum OldTransaction {
+    UserTransaction(SignedTransaction),
+    WaypointWriteSet(ChangeSet),
+    BlockMetadata(OldBlockMetadata),
+}
+
+#[test]
+fn test_old_block_metadata_transaction() {
+    let id = HashValue::random();
+    let voters = vec![AccountAddress::random(), AccountAddress::random()];
+    let proposer = AccountAddress::random();
+    let old_bytes = lcs::to_bytes(&OldTransaction::BlockMetadata(OldBlockMetadata {
+        id,
+        round: 3,
+        timestamp_usecs: 42,
+        previous_block_votes: voters.clone(),
+        proposer,
+    }))
+    .unwrap();
+
+    let txn: Transaction = lcs::from_bytes(&old_bytes).unwrap();
+    let block_metadata = BlockMetadata::new(id, 3, 42, voters.clone(), proposer, vec![]);
+    assert_eq!(txn, Transaction::BlockMetadata(block_metadata.clone()));
+    // the committed transactions keep their encoding, and so their hash
+    assert_eq!(lcs::to_bytes(&txn).unwrap(), old_bytes);
+    assert_eq!(Transaction::block_metadata(block_metadata), txn);
+
+    // the failed proposers are recorded in a new variant only
+    let failed_proposers = vec![AccountAddress::random()];
+    let block_metadata = BlockMetadata::new(id, 3, 42, voters, proposer, failed_proposers);
+    assert!(lcs::to_bytes(&Transaction::BlockMetadata(block_metadata.clone())).is_err());
+    let txn = Transaction::block_metadata(block_metadata.clone());
+    assert_eq!(
+        txn,
+        Transaction::BlockMetadataWithFailedProposers(block_metadata)
+    );
+    assert_canonical_encode_decode(txn);
+}